@@ -1,16 +1,33 @@
 //! Add a node to today's journal page.
 
 use clap::Args;
-use miette::Result;
+use flow_core::audio;
+use flow_core::context::{Context, ContextTarget};
+use flow_core::space::Space;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
 use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 
 use crate::common::{Command, GlobalArgs};
+use crate::daemon::Client;
+use crate::error::CliError;
 
 /// Output structure for the add command.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct AddOutput {
     pub content: String,
     pub message: String,
+    pub context: Context,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scratch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recording: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<usize>,
 }
 
 /// Arguments for the add command.
@@ -20,7 +37,27 @@ pub struct AddArgs {
     pub global: GlobalArgs,
 
     /// Content to add to today's journal
-    pub content: String,
+    #[arg(required_unless_present_any = ["audio", "file"])]
+    pub content: Option<String>,
+
+    /// Add an audio memo, transcribed if a transcription backend is configured
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["content", "scratch"])]
+    pub audio: Option<PathBuf>,
+
+    /// Add every non-empty line of a file as its own block, `-` reads stdin
+    ///
+    /// Indented lines are nested below the line above. All blocks are added
+    /// at once and saved once.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["content", "audio", "scratch"])]
+    pub file: Option<PathBuf>,
+
+    /// Add even if the target page is locked
+    #[arg(long)]
+    pub force: bool,
+
+    /// Add to a throwaway in-memory journal and print it, saving nothing
+    #[arg(long, visible_alias = "ephemeral", conflicts_with = "force")]
+    pub scratch: bool,
 }
 
 /// Add command implementation.
@@ -41,25 +78,166 @@ impl Command for AddCommand {
     }
 
     fn run(self) -> Result<Self::Output> {
+        if let Some(file) = &self.args.audio {
+            return self.add_audio(file);
+        }
+        if let Some(file) = &self.args.file {
+            return self.add_file(file);
+        }
+        let content = self.args.content.clone().unwrap_or_default();
         self.args
             .global
-            .step(&format!("Adding content: {}", self.args.content));
+            .step(&format!("Adding content: {}", content));
 
-        // Load graph using global.load_graph() which respects --graph flag
-        self.args.global.step("Loading graph");
-        let mut graph = self.args.global.load_graph()?;
+        if self.args.scratch {
+            let mut space = Space::in_memory()?;
+            space.add(&content)?;
+            let page = space.read_page(&space.journal_page(space.today()))?;
+            return Ok(AddOutput {
+                content,
+                message: "Added to a scratch journal, nothing was saved".to_string(),
+                context: Context::default(),
+                scratch: page,
+                recording: None,
+                blocks: None,
+            });
+        }
 
-        graph.add(&self.args.content)?;
+        let forwarded = match Client::connect() {
+            Some(mut daemon) => {
+                self.args.global.step("Forwarding to daemon");
+                let path = self.args.global.graph_path()?;
+                daemon.forward(
+                    Some(&path),
+                    "add",
+                    json!({ "content": content, "force": self.args.force }),
+                )?
+            }
+            None => None,
+        };
+        if forwarded.is_none() {
+            // Load graph using global.load_graph() which respects --graph flag
+            self.args.global.step("Loading graph");
+            let mut graph = self.args.global.load_graph()?;
+            graph.set_allow_locked(self.args.force);
 
+            graph.add(&content)?;
+        }
+
+        let context = Context::load(&self.args.global.graph_path()?);
         Ok(AddOutput {
-            content: self.args.content.clone(),
-            message: "Added to today's journal".to_string(),
+            content,
+            message: message(&context),
+            context,
+            scratch: None,
+            recording: None,
+            blocks: None,
         })
     }
 
     fn format_output(output: &Self::Output, global: &GlobalArgs) {
         global.success(&output.message);
         global.blank();
-        global.kv("Content", &output.content);
+        match output.blocks {
+            Some(blocks) => global.kv("Blocks", &blocks.to_string()),
+            None => global.kv("Content", &output.content),
+        }
+        if let Some(recording) = &output.recording {
+            global.kv("Recording", recording);
+        }
+        if let Some(page) = &output.scratch {
+            global.blank();
+            global.print(page.trim());
+        }
+    }
+}
+
+impl AddCommand {
+    /// Saves an audio memo and adds it, transcribed if a backend is configured.
+    fn add_audio(&self, file: &Path) -> Result<AddOutput> {
+        self.args.global.step("Loading graph");
+        let mut graph = self.args.global.load_graph()?;
+        graph.set_allow_locked(self.args.force);
+
+        let transcriber = match &graph.audio_settings().transcription {
+            Some(settings) => Some(audio::transcriber(settings)?),
+            None => None,
+        };
+        if transcriber.is_some() {
+            self.args
+                .global
+                .step(&format!("Transcribing {}", file.display()));
+        }
+        let added = graph.add_audio(file, transcriber.as_deref())?;
+
+        let context = Context::load(&self.args.global.graph_path()?);
+        Ok(AddOutput {
+            content: added.transcript.unwrap_or_default(),
+            message: message(&context),
+            context,
+            scratch: None,
+            recording: Some(added.recording),
+            blocks: None,
+        })
+    }
+
+    /// Adds every line of a file, or of stdin for `-`, as its own block.
+    fn add_file(&self, file: &Path) -> Result<AddOutput> {
+        let text = if file == Path::new("-") {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).into_diagnostic()?;
+            text
+        } else {
+            fs::read_to_string(file)
+                .map_err(|err| miette::miette!("Failed to read '{}': {}", file.display(), err))?
+        };
+
+        let forwarded = match Client::connect() {
+            Some(mut daemon) => {
+                self.args.global.step("Forwarding to daemon");
+                let path = self.args.global.graph_path()?;
+                daemon.forward(
+                    Some(&path),
+                    "add",
+                    json!({ "content": text, "force": self.args.force, "batch": true }),
+                )?
+            }
+            None => None,
+        };
+        let blocks = match forwarded {
+            Some(result) => result
+                .get("blocks")
+                .and_then(|blocks| blocks.as_u64())
+                .ok_or_else(|| CliError::daemon_request("Response is missing 'blocks'"))?
+                as usize,
+            None => {
+                self.args.global.step("Loading graph");
+                let mut graph = self.args.global.load_graph()?;
+                graph.set_allow_locked(self.args.force);
+                graph.add_all(&text)?
+            }
+        };
+
+        let context = Context::load(&self.args.global.graph_path()?);
+        Ok(AddOutput {
+            content: text,
+            message: message(&context),
+            context,
+            scratch: None,
+            recording: None,
+            blocks: Some(blocks),
+        })
+    }
+}
+
+/// Describes where added content went.
+fn message(context: &Context) -> String {
+    match (&context.meeting, &context.target) {
+        (Some(meeting), _) => format!("Added to meeting '{}'", meeting.title),
+        (None, Some(ContextTarget::Page(page))) => format!("Added to [[{}]]", page),
+        (None, Some(ContextTarget::Heading(heading))) => {
+            format!("Added below '{}' in today's journal", heading)
+        }
+        _ => "Added to today's journal".to_string(),
     }
 }
@@ -0,0 +1,59 @@
+//! Answer questions from the notes of a graph with a language model.
+
+use clap::Args;
+use flow_core::ai::{self, Answer};
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Arguments for the ask command.
+#[derive(Args)]
+pub struct AskArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Question to answer from your notes
+    pub question: String,
+}
+
+/// Ask command implementation.
+pub struct AskCommand {
+    args: AskArgs,
+}
+
+impl Command for AskCommand {
+    type Args = AskArgs;
+    type Output = Answer;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+        if space.ai_settings().endpoint.is_none() {
+            return Err(CliError::ai_not_configured(space.name()).into());
+        }
+        let model = ai::language_model(space.ai_settings())?;
+
+        self.args.global.step(&format!("Asking {}", model.id()));
+        space.ask(&self.args.question, model.as_ref())
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.print(&output.answer);
+        global.blank();
+        let sources = output
+            .sources
+            .iter()
+            .map(|source| format!("[[{}]]", source))
+            .collect::<Vec<_>>()
+            .join(", ");
+        global.kv("Sources", &sources);
+    }
+}
@@ -0,0 +1,81 @@
+//! Attach files to today's journal page.
+
+use clap::Args;
+use flow_core::attach::Attached;
+use flow_core::ocr;
+use miette::Result;
+use std::path::PathBuf;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Arguments for the attach command.
+#[derive(Args)]
+pub struct AttachArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// File to attach
+    pub file: PathBuf,
+
+    /// Recognize the text of images and store it below the attachment
+    #[arg(long)]
+    pub ocr: bool,
+
+    /// Attach even if today's journal page is locked
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Attach command implementation.
+pub struct AttachCommand {
+    args: AttachArgs,
+}
+
+impl Command for AttachCommand {
+    type Args = AttachArgs;
+    type Output = Attached;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        space.set_allow_locked(self.args.force);
+
+        let recognizer = if self.args.ocr {
+            let Some(backend) = space.ocr_backend() else {
+                return Err(CliError::ocr_not_configured(space.name()).into());
+            };
+            self.args
+                .global
+                .step(&format!("Recognizing text in {}", self.args.file.display()));
+            Some(ocr::recognizer(backend))
+        } else {
+            None
+        };
+
+        space.attach(&self.args.file, recognizer.as_deref())
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.success(&format!(
+            "Attached {} to [[{}]]",
+            output.path,
+            flow_core::page::name_from_id(&output.page)
+        ));
+        match &output.text {
+            Some(text) if !text.is_empty() => {
+                global.blank();
+                global.kv("Text", text);
+            }
+            Some(_) => global.info("No text recognized"),
+            None => {}
+        }
+    }
+}
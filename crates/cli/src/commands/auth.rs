@@ -0,0 +1,192 @@
+//! Manage credentials referenced from the Flow configuration.
+
+use clap::{Args, Subcommand};
+use flow_core::secrets::{SecretBackend, Secrets, SECRET_PREFIX};
+use inquire::Password;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for a single secret.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SecretSummary {
+    pub name: String,
+    pub backend: SecretBackend,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// Output structure for the auth command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AuthOutput {
+    pub action: String,
+    pub secrets: Vec<SecretSummary>,
+}
+
+/// Auth actions.
+#[derive(Subcommand)]
+pub enum AuthAction {
+    /// Store a secret (prompts for the value if not given)
+    Set {
+        /// Name to reference the secret by, as `secret:<name>` in flow.toml
+        name: String,
+
+        /// Value of the secret, avoid on shared machines as it ends up in the shell history
+        #[arg(long)]
+        value: Option<String>,
+    },
+    /// Print the value of a secret
+    Get {
+        /// Name of the secret
+        name: String,
+    },
+    /// Remove a secret
+    Remove {
+        /// Name of the secret
+        name: String,
+    },
+    /// List the names of all secrets
+    List,
+}
+
+/// Arguments for the auth command.
+#[derive(Args)]
+pub struct AuthArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: AuthAction,
+}
+
+/// Auth command implementation.
+pub struct AuthCommand {
+    args: AuthArgs,
+}
+
+impl Command for AuthCommand {
+    type Args = AuthArgs;
+    type Output = AuthOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn interactive(&mut self) -> Result<()> {
+        if let AuthAction::Set { name, value: None } = &self.args.action {
+            let value = Password::new(&format!("Value of '{}':", name))
+                .without_confirmation()
+                .prompt()
+                .map_err(CliError::from)?;
+            self.args.action = AuthAction::Set {
+                name: name.clone(),
+                value: Some(value),
+            };
+        }
+        Ok(())
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let secrets = Secrets::open()?;
+
+        let (action, summaries) = match self.args.action {
+            AuthAction::Set { name, value } => {
+                let value = value.ok_or_else(|| CliError::Other {
+                    message: "No value given, pass --value when using --json".to_string(),
+                })?;
+                let backend = secrets.set(&name, &value)?;
+                let summary = SecretSummary {
+                    name,
+                    backend,
+                    value: None,
+                };
+                ("set", vec![summary])
+            }
+            AuthAction::Get { name } => {
+                let value = secrets.get(&name)?.ok_or_else(|| CliError::Other {
+                    message: format!("Secret '{}' is not set", name),
+                })?;
+                let backend = secrets
+                    .list()?
+                    .into_iter()
+                    .find(|(listed, _)| *listed == name)
+                    .map_or(SecretBackend::File, |(_, backend)| backend);
+                let summary = SecretSummary {
+                    name,
+                    backend,
+                    value: Some(value),
+                };
+                ("get", vec![summary])
+            }
+            AuthAction::Remove { name } => {
+                if !secrets.remove(&name)? {
+                    return Err(CliError::Other {
+                        message: format!("Secret '{}' is not set", name),
+                    }
+                    .into());
+                }
+                ("remove", Vec::new())
+            }
+            AuthAction::List => {
+                let summaries = secrets
+                    .list()?
+                    .into_iter()
+                    .map(|(name, backend)| SecretSummary {
+                        name,
+                        backend,
+                        value: None,
+                    })
+                    .collect();
+                ("list", summaries)
+            }
+        };
+
+        Ok(AuthOutput {
+            action: action.to_string(),
+            secrets: summaries,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let describe = |backend: SecretBackend| match backend {
+            SecretBackend::Keychain => "keychain",
+            SecretBackend::File => "secrets file",
+        };
+
+        match output.action.as_str() {
+            "set" => {
+                for secret in &output.secrets {
+                    global.success(&format!(
+                        "Stored '{}' in the {}",
+                        secret.name,
+                        describe(secret.backend)
+                    ));
+                    global.info(&format!(
+                        "Reference it as \"{}{}\" in flow.toml",
+                        SECRET_PREFIX, secret.name
+                    ));
+                }
+            }
+            // Print the bare value so it can be used in scripts
+            "get" => {
+                for secret in &output.secrets {
+                    global.print(secret.value.as_deref().unwrap_or_default());
+                }
+            }
+            "remove" => global.success("Secret removed"),
+            _ if output.secrets.is_empty() => global.info("No secrets stored"),
+            _ => {
+                for secret in &output.secrets {
+                    global.kv(&secret.name, describe(secret.backend));
+                }
+            }
+        }
+    }
+}
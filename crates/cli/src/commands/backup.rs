@@ -0,0 +1,109 @@
+//! List and restore document backups of a Flow graph.
+
+use clap::{Args, Subcommand};
+use flow_core::backup::{self, BackupSettings};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+
+/// Output structure for a single backup entry.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BackupEntry {
+    id: String,
+    path: String,
+    size: u64,
+}
+
+/// Output structure for the backup command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BackupOutput {
+    backups: Vec<BackupEntry>,
+    restored: Option<String>,
+}
+
+/// Backup actions.
+#[derive(Subcommand)]
+pub enum BackupAction {
+    /// List available backups, newest first
+    List,
+
+    /// Restore a backup as the current document
+    Restore {
+        /// Id of the backup to restore (see `flow backup list`)
+        id: String,
+    },
+}
+
+/// Arguments for the backup command.
+#[derive(Args)]
+pub struct BackupArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: BackupAction,
+}
+
+/// Backup command implementation.
+pub struct BackupCommand {
+    args: BackupArgs,
+}
+
+impl Command for BackupCommand {
+    type Args = BackupArgs;
+    type Output = BackupOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        // Resolve the path only, restoring must work on graphs that fail to load
+        let path = self.args.global.graph_path()?;
+
+        let restored = match self.args.action {
+            BackupAction::List => None,
+            BackupAction::Restore { id } => {
+                self.args.global.step(&format!("Restoring backup {}", id));
+                let settings = BackupSettings::for_space(&path);
+                Some(backup::restore(&path, &id, &settings)?.id)
+            }
+        };
+
+        let backups = backup::list(&path)?
+            .into_iter()
+            .map(|backup| BackupEntry {
+                id: backup.id,
+                path: path_to_display_string(&backup.path),
+                size: backup.size,
+            })
+            .collect();
+
+        Ok(BackupOutput { backups, restored })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if let Some(ref id) = output.restored {
+            global.success(&format!("Restored backup {}", id));
+            return;
+        }
+
+        global.heading("Backups");
+        global.blank();
+
+        if output.backups.is_empty() {
+            global.info("No backups found");
+            return;
+        }
+
+        for entry in &output.backups {
+            global.kv(&entry.id, &format!("{} bytes", entry.size));
+        }
+    }
+}
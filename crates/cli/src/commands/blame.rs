@@ -0,0 +1,152 @@
+//! Show who last changed each line or block of a page.
+
+use clap::Args;
+use flow_core::attribution::{self, BlameBlock, BlameLine, Change};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the blame command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BlameOutput {
+    pub page: String,
+    pub lines: Vec<BlameLine>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<BlameBlock>>,
+}
+
+/// Arguments for the blame command.
+#[derive(Args)]
+pub struct BlameArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Name of the page
+    pub page: String,
+
+    /// Attribute whole blocks (a bullet with its continuation lines) instead of lines
+    #[arg(long)]
+    pub blocks: bool,
+}
+
+/// Blame command implementation.
+pub struct BlameCommand {
+    args: BlameArgs,
+}
+
+impl Command for BlameCommand {
+    type Args = BlameArgs;
+    type Output = BlameOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+        let lines = space
+            .blame(&self.args.page)?
+            .ok_or_else(|| CliError::page_not_found(&self.args.page))?;
+        let blocks = self.args.blocks.then(|| attribution::blocks(&lines));
+
+        Ok(BlameOutput {
+            page: self.args.page,
+            lines,
+            blocks,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let rows: Vec<(String, &Option<Change>, &str)> = match &output.blocks {
+            Some(blocks) => blocks
+                .iter()
+                .map(|block| {
+                    let lines = if block.start == block.end {
+                        block.start.to_string()
+                    } else {
+                        format!("{}-{}", block.start, block.end)
+                    };
+                    (lines, &block.change, block.text.as_str())
+                })
+                .collect(),
+            None => output
+                .lines
+                .iter()
+                .map(|line| (line.line.to_string(), &line.change, line.text.as_str()))
+                .collect(),
+        };
+        if rows.is_empty() {
+            global.info("Page is empty");
+            return;
+        }
+
+        let cells: Vec<[String; 4]> = rows
+            .iter()
+            .map(|(lines, change, _)| {
+                let (author, device, time) = match change {
+                    Some(change) => (
+                        change
+                            .author
+                            .as_ref()
+                            .map(|author| author.name.clone())
+                            .unwrap_or_else(|| "unknown".to_string()),
+                        change
+                            .author
+                            .as_ref()
+                            .map(|author| author.device.clone())
+                            .unwrap_or_default(),
+                        change
+                            .time()
+                            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_default(),
+                    ),
+                    None => ("unknown".to_string(), String::new(), String::new()),
+                };
+                [lines.clone(), author, device, time]
+            })
+            .collect();
+
+        let header = ["LINE", "AUTHOR", "DEVICE", "CHANGED"];
+        let mut widths = header.map(str::len);
+        for row in &cells {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let format_row = |cells: [&str; 4]| {
+            format!(
+                "{:>w0$}  {:<w1$}  {:<w2$}  {:<w3$}",
+                cells[0],
+                cells[1],
+                cells[2],
+                cells[3],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3]
+            )
+        };
+
+        global.print(&format!("{}  TEXT", format_row(header)));
+        let indent = " ".repeat(widths.iter().sum::<usize>() + 6);
+        for (row, (_, _, text)) in cells.iter().zip(&rows) {
+            let mut lines = text.lines();
+            let first = lines.next().unwrap_or_default();
+            global.print(&format!(
+                "{}  {}",
+                format_row([&row[0], &row[1], &row[2], &row[3]]),
+                first
+            ));
+            for line in lines {
+                global.print(&format!("{}  {}", indent, line));
+            }
+        }
+    }
+}
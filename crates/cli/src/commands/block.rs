@@ -0,0 +1,134 @@
+//! Indent, outdent and move blocks of the outline.
+
+use clap::{Args, Subcommand};
+use flow_core::outline::{BlockLocation, Destination};
+use flow_core::space::Space;
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Block actions.
+///
+/// Blocks are given as `PAGE:LINE`, the page name or alias and the line the
+/// block starts at, e.g. `Plan:4` or `2024-05-01:2`.
+#[derive(Subcommand)]
+pub enum BlockAction {
+    /// Make a block the last child of its previous sibling
+    Indent {
+        /// Block as PAGE:LINE
+        block: String,
+    },
+
+    /// Make a block the next sibling of its parent
+    Outdent {
+        /// Block as PAGE:LINE
+        block: String,
+    },
+
+    /// Move a block with its children after another block or to a page
+    Move {
+        /// Block as PAGE:LINE
+        block: String,
+
+        /// Block (PAGE:LINE) to move the block after, as its next sibling
+        #[arg(
+            long,
+            value_name = "BLOCK",
+            required_unless_present = "to",
+            conflicts_with = "to"
+        )]
+        after: Option<String>,
+
+        /// Page to move the block to the end of
+        #[arg(long, value_name = "PAGE")]
+        to: Option<String>,
+    },
+}
+
+/// Arguments for the block command.
+#[derive(Args)]
+pub struct BlockArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Change blocks even on locked pages
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(subcommand)]
+    pub action: BlockAction,
+}
+
+/// Block command implementation.
+pub struct BlockCommand {
+    args: BlockArgs,
+}
+
+impl Command for BlockCommand {
+    type Args = BlockArgs;
+    type Output = BlockLocation;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        space.set_allow_locked(self.args.force);
+
+        match &self.args.action {
+            BlockAction::Indent { block } => {
+                let (id, line) = resolve(&space, block)?;
+                space.indent_block(&id, line)
+            }
+            BlockAction::Outdent { block } => {
+                let (id, line) = resolve(&space, block)?;
+                space.outdent_block(&id, line)
+            }
+            BlockAction::Move { block, after, to } => {
+                let (id, line) = resolve(&space, block)?;
+                let destination = match (after, to) {
+                    (Some(after), _) => {
+                        let (id, line) = resolve(&space, after)?;
+                        Destination::After { id, line }
+                    }
+                    (None, Some(name)) => Destination::Page(page(&space, name)?),
+                    (None, None) => miette::bail!("Either --after or --to is required"),
+                };
+                space.move_block(&id, line, &destination)
+            }
+        }
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.success(&format!(
+            "Block is now at [[{}]]:{}",
+            output.page, output.line
+        ));
+    }
+}
+
+/// Resolves a block given as `PAGE:LINE` to the page id and line.
+fn resolve(space: &Space, block: &str) -> Result<(String, usize)> {
+    let Some((name, line)) = block.rsplit_once(':') else {
+        miette::bail!("Invalid block '{}', expected PAGE:LINE", block);
+    };
+    let Ok(line) = line.parse() else {
+        miette::bail!("Invalid line '{}' in block '{}'", line, block);
+    };
+    Ok((page(space, name)?, line))
+}
+
+/// Resolves a page name or alias to the id of an existing page.
+fn page(space: &Space, name: &str) -> Result<String> {
+    let id = space.page_index()?.resolve(name);
+    if space.read_page(&id)?.is_none() {
+        return Err(CliError::page_not_found(name).into());
+    }
+    Ok(id)
+}
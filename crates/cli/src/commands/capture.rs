@@ -0,0 +1,121 @@
+//! Capture external items, such as issues, into the journal.
+
+use clap::{Args, Subcommand};
+use flow_core::integrations::{self, capture::IssueUpdate, Issue};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the capture command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CaptureOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue: Option<Issue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub updates: Vec<IssueUpdate>,
+}
+
+/// Capture targets.
+#[derive(Subcommand)]
+pub enum CaptureAction {
+    /// Capture a GitHub, GitLab, Jira or Linear issue into today's journal
+    Issue {
+        /// URL of the issue
+        #[arg(required_unless_present = "sync")]
+        url: Option<String>,
+
+        /// Refresh the state of all captured issues instead
+        #[arg(long, conflicts_with = "url")]
+        sync: bool,
+    },
+}
+
+/// Arguments for the capture command.
+#[derive(Args)]
+pub struct CaptureArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: CaptureAction,
+}
+
+/// Capture command implementation.
+pub struct CaptureCommand {
+    args: CaptureArgs,
+}
+
+impl Command for CaptureCommand {
+    type Args = CaptureArgs;
+    type Output = CaptureOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let CaptureAction::Issue { url, sync } = self.args.action;
+        let mut space = self.args.global.load_graph()?;
+        let providers = integrations::providers(space.integration_settings())?;
+
+        if sync {
+            self.args.global.step("Fetching captured issues");
+            let updates = space.sync_issues(|url| integrations::fetch(&providers, url))?;
+            return Ok(CaptureOutput {
+                issue: None,
+                page: None,
+                updates,
+            });
+        }
+
+        let url = url.ok_or_else(|| CliError::missing_argument("url"))?;
+        self.args.global.step(&format!("Fetching {}", url));
+        let issue = integrations::fetch(&providers, &url)?;
+        let id = space.capture_issue(&issue)?;
+
+        Ok(CaptureOutput {
+            issue: Some(issue),
+            page: Some(flow_core::page::name_from_id(&id)),
+            updates: Vec::new(),
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if let (Some(issue), Some(page)) = (&output.issue, &output.page) {
+            global.success(&format!("Captured {} to [[{}]]", issue.reference, page));
+            global.blank();
+            global.kv("Title", &issue.title);
+            global.kv("State", issue.state.as_str());
+            if !issue.labels.is_empty() {
+                global.kv("Labels", &issue.labels.join(", "));
+            }
+            return;
+        }
+
+        if output.updates.is_empty() {
+            global.info("All captured issues are up to date");
+            return;
+        }
+        for update in &output.updates {
+            global.kv(
+                &update.url,
+                &format!("{} -> {}", update.from.as_str(), update.to.as_str()),
+            );
+        }
+        global.blank();
+        global.success(&format!(
+            "Updated {} issue{}",
+            output.updates.len(),
+            if output.updates.len() == 1 { "" } else { "s" }
+        ));
+    }
+}
@@ -1,15 +1,20 @@
-//! Remove orphaned graphs from configuration.
+//! Remove orphaned graphs from configuration, and with `--deep` data the
+//! registered graphs no longer need.
 
 use clap::Args;
+use flow_core::clean::{self, Finding, FindingKind};
 use flow_core::config::Config;
-use flow_core::graph::Graph;
+use flow_core::space::Space;
+use inquire::Confirm;
 use miette::Result;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::daemon::{self, Client};
 
 /// Output structure for a removed graph entry.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct RemovedGraph {
     name: String,
     path: String,
@@ -17,18 +22,28 @@ pub struct RemovedGraph {
 }
 
 /// Output structure for a kept graph entry.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct KeptGraph {
     name: String,
     path: String,
 }
 
+/// Output structure for a finding of a deep clean.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CleanFinding {
+    #[serde(flatten)]
+    finding: Finding,
+    fixed: bool,
+}
+
 /// Output structure for the clean command.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct CleanOutput {
     checked: usize,
     removed: Vec<RemovedGraph>,
     kept: Vec<KeptGraph>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    findings: Option<Vec<CleanFinding>>,
     dry_run: bool,
 }
 
@@ -41,11 +56,55 @@ pub struct CleanArgs {
     /// Show what would be removed without making changes
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Also find duplicate registrations, stale files, oversized backups and orphaned assets
+    #[arg(long)]
+    pub deep: bool,
+
+    /// Fix all findings of a deep clean without asking
+    #[arg(long, short)]
+    pub yes: bool,
 }
 
 /// Clean command implementation.
 pub struct CleanCommand {
     args: CleanArgs,
+    /// Findings confirmed in interactive mode
+    approved: Vec<Finding>,
+}
+
+impl CleanCommand {
+    /// Finds the data the registered graphs no longer need.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The Flow configuration
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Finding>>` - Findings of all graphs and the daemon socket
+    fn findings(&self, config: &Config) -> Result<Vec<Finding>> {
+        let mut findings = clean::duplicate_registrations(config);
+        for (name, graph_config) in config.all_spaces() {
+            if Space::exists(&graph_config.path) {
+                self.args.global.debug("Inspecting", &name);
+                findings.extend(clean::inspect(&name, &graph_config.path)?);
+            }
+        }
+
+        // A socket nobody listens on is left over from a crashed daemon
+        let socket = daemon::socket_path();
+        if socket.exists() && Client::connect().is_none() {
+            findings.push(Finding {
+                kind: FindingKind::StaleFile,
+                space: String::new(),
+                path: socket,
+                detail: "daemon socket without a running daemon".to_string(),
+                size: 0,
+            });
+        }
+        Ok(findings)
+    }
 }
 
 impl Command for CleanCommand {
@@ -53,18 +112,43 @@ impl Command for CleanCommand {
     type Output = CleanOutput;
 
     fn from_args(args: Self::Args) -> Self {
-        Self { args }
+        Self {
+            args,
+            approved: Vec::new(),
+        }
     }
 
     fn global_args(&self) -> &GlobalArgs {
         &self.args.global
     }
 
+    fn interactive(&mut self) -> Result<()> {
+        if !self.args.deep || self.args.dry_run || self.args.yes {
+            return Ok(());
+        }
+
+        let config = Config::load()?;
+        for finding in self.findings(&config)? {
+            let confirmed = Confirm::new(&format!(
+                "{} {}?",
+                capitalize(finding.kind.action()),
+                subject(&finding)
+            ))
+            .with_help_message(&format!("{}: {}", finding.kind.label(), finding.detail))
+            .with_default(true)
+            .prompt()?;
+            if confirmed {
+                self.approved.push(finding);
+            }
+        }
+        Ok(())
+    }
+
     fn run(self) -> Result<Self::Output> {
         self.args.global.step("Loading configuration");
         let mut config = Config::load()?;
 
-        let graph_count = config.graph_count();
+        let graph_count = config.space_count();
         self.args.global.info(&format!(
             "Checking {} registered graph{}",
             graph_count,
@@ -73,7 +157,7 @@ impl Command for CleanCommand {
 
         let mut removed = Vec::new();
         let mut kept = Vec::new();
-        let graphs_to_check = config.all_graphs();
+        let graphs_to_check = config.all_spaces();
 
         for (name, graph_config) in graphs_to_check {
             let path = &graph_config.path;
@@ -108,7 +192,7 @@ impl Command for CleanCommand {
             }
 
             // Check if it's a valid Flow graph
-            if !Graph::exists(path) {
+            if !Space::exists(path) {
                 if self.args.dry_run {
                     self.args
                         .global
@@ -142,10 +226,27 @@ impl Command for CleanCommand {
             });
         }
 
+        let findings = if self.args.deep {
+            self.args.global.step("Inspecting graphs");
+            let mut findings = Vec::new();
+            for finding in self.findings(&config)? {
+                let fixed =
+                    !self.args.dry_run && (self.args.yes || self.approved.contains(&finding));
+                if fixed {
+                    clean::fix(&mut config, &finding)?;
+                }
+                findings.push(CleanFinding { finding, fixed });
+            }
+            Some(findings)
+        } else {
+            None
+        };
+
         Ok(CleanOutput {
             checked: graph_count,
             removed,
             kept,
+            findings,
             dry_run: self.args.dry_run,
         })
     }
@@ -188,6 +289,107 @@ impl Command for CleanCommand {
                 output.removed.len(),
                 if output.removed.len() == 1 { "" } else { "s" }
             ));
+            global.info("Run 'flow config undo' to restore them");
         }
+
+        if let Some(findings) = &output.findings {
+            global.blank();
+            print_findings(findings, output.dry_run, global);
+        }
+    }
+}
+
+/// Prints the findings of a deep clean as a table with a summary.
+fn print_findings(findings: &[CleanFinding], dry_run: bool, global: &GlobalArgs) {
+    if findings.is_empty() {
+        global.success("Nothing else to clean up");
+        return;
+    }
+
+    let cells: Vec<[String; 5]> = findings
+        .iter()
+        .map(|entry| {
+            let finding = &entry.finding;
+            let status = match (entry.fixed, dry_run) {
+                (true, _) => "fixed",
+                (false, true) => "dry run",
+                (false, false) => "skipped",
+            };
+            [
+                finding.kind.label().to_string(),
+                subject(finding),
+                format!("{} bytes", finding.size),
+                finding.kind.action().to_string(),
+                status.to_string(),
+            ]
+        })
+        .collect();
+
+    let header = ["KIND", "WHAT", "SIZE", "ACTION", "STATUS"];
+    let mut widths = header.map(str::len);
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_row = |cells: [&str; 5]| {
+        format!(
+            "{:<w0$}  {:<w1$}  {:>w2$}  {:<w3$}  {}",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            cells[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        )
+    };
+
+    global.print(&format_row(header));
+    for (row, entry) in cells.iter().zip(findings) {
+        global.print(&format_row([&row[0], &row[1], &row[2], &row[3], &row[4]]));
+        global.print_verbose(&format!("    {}", entry.finding.detail));
+    }
+    global.blank();
+
+    let fixed: Vec<&Finding> = findings
+        .iter()
+        .filter(|entry| entry.fixed)
+        .map(|entry| &entry.finding)
+        .collect();
+    let freed: u64 = fixed.iter().map(|finding| finding.size).sum();
+    if dry_run {
+        global.warning(&format!(
+            "Dry run: {} finding{} would be fixed",
+            findings.len(),
+            if findings.len() == 1 { "" } else { "s" }
+        ));
+    } else {
+        global.success(&format!(
+            "Fixed {} of {} finding{}, freeing {} bytes",
+            fixed.len(),
+            findings.len(),
+            if findings.len() == 1 { "" } else { "s" },
+            freed
+        ));
+    }
+}
+
+/// Returns what a finding is about, the name for registrations, the path otherwise.
+fn subject(finding: &Finding) -> String {
+    match finding.kind {
+        FindingKind::DuplicateRegistration => finding.space.clone(),
+        _ => path_to_display_string(&finding.path),
+    }
+}
+
+/// Uppercases the first letter of a word.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
     }
 }
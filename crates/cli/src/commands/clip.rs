@@ -0,0 +1,65 @@
+//! Clip web articles into the space for reading later.
+
+use clap::Args;
+use flow_core::clip::{Clip, SavedClip};
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Arguments for the clip command.
+#[derive(Args)]
+pub struct ClipArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// URL of the article
+    pub url: String,
+
+    /// Name the clip instead of using the article's title
+    #[arg(long)]
+    pub title: Option<String>,
+}
+
+/// Clip command implementation.
+pub struct ClipCommand {
+    args: ClipArgs,
+}
+
+impl Command for ClipCommand {
+    type Args = ClipArgs;
+    type Output = SavedClip;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+
+        self.args
+            .global
+            .step(&format!("Fetching {}", self.args.url));
+        let mut clip = Clip::fetch(&self.args.url)?;
+        if let Some(title) = self.args.title {
+            clip.title = title;
+        }
+
+        space.save_clip(&clip)
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.kv("Source", &output.url);
+        global.kv(
+            "Journal",
+            &format!("[[{}]]", flow_core::page::name_from_id(&output.journal)),
+        );
+        global.success(&format!(
+            "Clipped [[{}]]",
+            flow_core::page::name_from_id(&output.page)
+        ));
+    }
+}
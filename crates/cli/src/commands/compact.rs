@@ -0,0 +1,134 @@
+//! Trim old document history of a Flow graph.
+//!
+//! With `--gc` the containers of pages deleted or renamed on disk are
+//! collected first. Their space is reclaimed once the collection is older
+//! than the horizon, `--horizon 0` reclaims it right away.
+
+use clap::Args;
+use flow_core::page;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+
+/// Output structure for the compact command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CompactOutput {
+    compacted: bool,
+    horizon_days: u32,
+    size_before: u64,
+    size_after: u64,
+    saved: u64,
+    archive: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    collected: Option<Vec<String>>,
+    collected_bytes: u64,
+}
+
+/// Arguments for the compact command.
+#[derive(Args)]
+pub struct CompactArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Trim history older than this many days (defaults to the graph setting)
+    #[arg(long)]
+    pub horizon: Option<u32>,
+
+    /// Keep a full-history copy in .flow/archive/ before compacting
+    #[arg(long)]
+    pub archive: bool,
+
+    /// Collect the leftovers of pages deleted or renamed on disk first
+    #[arg(long)]
+    pub gc: bool,
+}
+
+/// Compact command implementation.
+pub struct CompactCommand {
+    args: CompactArgs,
+}
+
+impl Command for CompactCommand {
+    type Args = CompactArgs;
+    type Output = CompactOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        self.args.global.step("Loading graph");
+        let mut graph = self.args.global.load_graph()?;
+
+        let settings = graph.compact_settings().clone();
+        let horizon_days = self.args.horizon.unwrap_or(settings.horizon_days);
+        let archive = self.args.archive || settings.archive;
+
+        let garbage = if self.args.gc {
+            self.args.global.step("Collecting deleted pages");
+            Some(graph.collect_garbage()?)
+        } else {
+            None
+        };
+
+        self.args.global.step(&format!(
+            "Trimming history older than {} day{}",
+            horizon_days,
+            if horizon_days == 1 { "" } else { "s" }
+        ));
+        let report = graph.compact(horizon_days, archive)?;
+
+        Ok(CompactOutput {
+            compacted: report.compacted,
+            horizon_days,
+            size_before: report.size_before,
+            size_after: report.size_after,
+            saved: report.saved(),
+            archive: report.archive.as_deref().map(path_to_display_string),
+            collected_bytes: garbage.as_ref().map_or(0, |garbage| garbage.bytes),
+            collected: garbage.map(|garbage| {
+                garbage
+                    .pages
+                    .iter()
+                    .map(|id| page::name_from_id(id))
+                    .collect()
+            }),
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if let Some(collected) = &output.collected {
+            for page in collected {
+                global.print(&format!("  {}", page));
+            }
+            global.success(&format!(
+                "Collected {} deleted page{} ({} bytes)",
+                collected.len(),
+                if collected.len() == 1 { "" } else { "s" },
+                output.collected_bytes
+            ));
+        }
+
+        if !output.compacted {
+            global.success("Nothing to compact");
+            global.blank();
+            global.kv("Size", &format!("{} bytes", output.size_before));
+            return;
+        }
+
+        global.success("Graph compacted successfully");
+        global.blank();
+        global.kv("Before", &format!("{} bytes", output.size_before));
+        global.kv("After", &format!("{} bytes", output.size_after));
+        global.kv("Saved", &format!("{} bytes", output.saved));
+        if let Some(ref archive) = output.archive {
+            global.kv("Archive", archive);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Show, undo, export and import the Flow configuration.
+
+use clap::{Args, Subcommand};
+use flow_core::config::{Config, ConfigImport};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+
+/// Output structure for a single configuration version.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfigVersionEntry {
+    id: String,
+    path: String,
+    size: u64,
+}
+
+/// Output structure for the config command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfigOutput {
+    path: String,
+    versions: Vec<ConfigVersionEntry>,
+    restored: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exported: Option<Export>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    imported: Option<ConfigImport>,
+}
+
+/// Output structure for an exported configuration.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Export {
+    /// File written to, `None` when printed
+    file: Option<String>,
+    content: String,
+}
+
+/// Config actions.
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// List previous versions of the configuration, newest first
+    History,
+
+    /// Restore the previous version of the configuration
+    Undo,
+
+    /// Export the configuration for another machine, paths below home as `~`
+    Export {
+        /// File to write to, printed if omitted
+        file: Option<PathBuf>,
+    },
+
+    /// Import a configuration exported on another machine
+    Import {
+        /// Exported configuration file
+        file: PathBuf,
+
+        /// Add to the current configuration instead of replacing it
+        #[arg(long)]
+        merge: bool,
+    },
+}
+
+/// Arguments for the config command.
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+/// Config command implementation.
+pub struct ConfigCommand {
+    args: ConfigArgs,
+}
+
+impl Command for ConfigCommand {
+    type Args = ConfigArgs;
+    type Output = ConfigOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut restored = None;
+        let mut exported = None;
+        let mut imported = None;
+        match self.args.action {
+            ConfigAction::History => {}
+            ConfigAction::Undo => {
+                self.args.global.step("Restoring previous configuration");
+                restored = Some(Config::undo()?.id);
+            }
+            ConfigAction::Export { file } => {
+                let content = Config::load()?.export()?;
+                if let Some(file) = &file {
+                    fs::write(file, &content).into_diagnostic()?;
+                }
+                exported = Some(Export {
+                    file: file.as_deref().map(path_to_display_string),
+                    content,
+                });
+            }
+            ConfigAction::Import { file, merge } => {
+                self.args.global.step(&format!(
+                    "Importing configuration from {}",
+                    path_to_display_string(&file)
+                ));
+                let content = fs::read_to_string(&file).into_diagnostic()?;
+                imported = Some(Config::load()?.import(&content, merge)?);
+            }
+        }
+
+        let versions = Config::history()?
+            .into_iter()
+            .map(|version| ConfigVersionEntry {
+                id: version.id,
+                path: path_to_display_string(&version.path),
+                size: version.size,
+            })
+            .collect();
+
+        Ok(ConfigOutput {
+            path: path_to_display_string(&Config::path()?),
+            versions,
+            restored,
+            exported,
+            imported,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if let Some(export) = &output.exported {
+            match &export.file {
+                Some(file) => global.success(&format!("Exported configuration to {}", file)),
+                None => global.print(export.content.trim_end()),
+            }
+            return;
+        }
+
+        if let Some(import) = &output.imported {
+            for name in &import.added {
+                global.kv(name, "imported");
+            }
+            for name in &import.skipped {
+                global.kv(name, "already registered, kept");
+            }
+            for name in &import.missing {
+                global.warning(&format!(
+                    "The directory of {} doesn't exist on this machine yet",
+                    name
+                ));
+            }
+            global.blank();
+            global.success(&format!(
+                "Imported {} space{}",
+                import.added.len(),
+                if import.added.len() == 1 { "" } else { "s" }
+            ));
+            global.info("Run 'flow config undo' to restore the previous configuration");
+            return;
+        }
+
+        if let Some(ref id) = output.restored {
+            global.success(&format!("Restored configuration version {}", id));
+            global.info(&format!(
+                "{} older version{} left to undo",
+                output.versions.len(),
+                if output.versions.len() == 1 { "" } else { "s" }
+            ));
+            return;
+        }
+
+        global.heading("Configuration History");
+        global.blank();
+
+        if output.versions.is_empty() {
+            global.info(&format!("No previous versions of {}", output.path));
+            return;
+        }
+
+        for entry in &output.versions {
+            global.kv(&entry.id, &format!("{} bytes", entry.size));
+        }
+    }
+}
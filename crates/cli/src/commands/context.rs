@@ -0,0 +1,88 @@
+//! Manage the sticky capture context of a Flow graph.
+
+use clap::{Args, Subcommand};
+use flow_core::context::{Context, ContextTarget};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the context command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ContextOutput {
+    pub context: Context,
+}
+
+/// Context actions.
+#[derive(Subcommand)]
+pub enum ContextAction {
+    /// Route or tag everything added from now on
+    Set {
+        /// `#tag`, `[[Page]]` or a heading block such as `## Standup`
+        target: String,
+    },
+    /// Stop routing and tagging added nodes
+    Clear,
+    /// Show the current context
+    Show,
+}
+
+/// Arguments for the context command.
+#[derive(Args)]
+pub struct ContextArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: ContextAction,
+}
+
+/// Context command implementation.
+pub struct ContextCommand {
+    args: ContextArgs,
+}
+
+impl Command for ContextCommand {
+    type Args = ContextArgs;
+    type Output = ContextOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph()?;
+
+        let context = match &self.args.action {
+            ContextAction::Set { target } => {
+                let target = ContextTarget::parse(target).ok_or_else(|| CliError::Other {
+                    message: "Context target must not be empty".to_string(),
+                })?;
+                space.set_context_target(Some(target))?
+            }
+            ContextAction::Clear => space.set_context_target(None)?,
+            ContextAction::Show => space.context(),
+        };
+
+        Ok(ContextOutput { context })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let context = &output.context;
+        match &context.target {
+            Some(ContextTarget::Tag(tag)) => global.kv("Tag", &format!("#{}", tag)),
+            Some(ContextTarget::Page(page)) => global.kv("Page", &format!("[[{}]]", page)),
+            Some(ContextTarget::Heading(heading)) => global.kv("Heading", heading),
+            None => global.info("No context set, nodes go to today's journal page"),
+        }
+        if let Some(meeting) = &context.meeting {
+            global.kv("Meeting", &meeting.title);
+        }
+    }
+}
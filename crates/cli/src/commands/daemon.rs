@@ -0,0 +1,105 @@
+//! Run, stop or query the Flow background daemon.
+
+use clap::Args;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::daemon::{self, Client};
+use crate::error::CliError;
+
+/// Output structure for the daemon command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DaemonOutput {
+    pub socket: String,
+    pub running: bool,
+    pub connections: Option<usize>,
+    pub message: String,
+}
+
+/// Arguments for the daemon command.
+#[derive(Args)]
+pub struct DaemonArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Stop the running daemon
+    #[arg(long, conflicts_with = "status")]
+    pub stop: bool,
+
+    /// Check whether the daemon is running
+    #[arg(long)]
+    pub status: bool,
+}
+
+/// Daemon command implementation.
+pub struct DaemonCommand {
+    args: DaemonArgs,
+}
+
+impl Command for DaemonCommand {
+    type Args = DaemonArgs;
+    type Output = DaemonOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let socket = daemon::socket_path();
+        let display = path_to_display_string(&socket);
+
+        if self.args.status {
+            let running = Client::connect().is_some();
+            return Ok(DaemonOutput {
+                socket: display,
+                running,
+                connections: None,
+                message: if running {
+                    "Daemon is running".to_string()
+                } else {
+                    "Daemon is not running".to_string()
+                },
+            });
+        }
+
+        if self.args.stop {
+            let mut client = Client::connect().ok_or(CliError::DaemonNotRunning)?;
+            client.call(None, "shutdown", json!({}))?;
+            return Ok(DaemonOutput {
+                socket: display,
+                running: false,
+                connections: None,
+                message: "Daemon stopped".to_string(),
+            });
+        }
+
+        self.args.global.step(&format!("Listening on {}", display));
+        let connections = daemon::serve(&socket)?;
+
+        Ok(DaemonOutput {
+            socket: display,
+            running: false,
+            connections: Some(connections),
+            message: "Daemon shut down".to_string(),
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.running || output.connections.is_none() {
+            global.info(&output.message);
+        } else {
+            global.success(&output.message);
+        }
+        global.kv("Socket", &output.socket);
+        if let Some(connections) = output.connections {
+            global.kv("Connections", &connections.to_string());
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Navigate to today's, yesterday's or tomorrow's journal page.
+
+use schemars::JsonSchema;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use chrono::{Days, NaiveDate};
+use clap::Args;
+use flow_core::config::Config;
+use flow_core::page;
+use miette::{IntoDiagnostic, Result};
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the day commands.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DayOutput {
+    pub name: String,
+    pub id: String,
+    pub created: bool,
+    pub edited: bool,
+    pub content: Option<String>,
+}
+
+/// Arguments for the day commands.
+#[derive(Args)]
+pub struct DayArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Open the page in the configured editor, $VISUAL or $EDITOR, creating it if needed
+    #[arg(long)]
+    pub edit: bool,
+
+    /// Create the page from the daily template if it doesn't exist
+    #[arg(long)]
+    pub create: bool,
+
+    /// Edit the page even if it is locked
+    #[arg(long, requires = "edit")]
+    pub force: bool,
+}
+
+/// Day command implementation, resolving a journal page relative to today.
+pub struct DayCommand {
+    args: DayArgs,
+    offset: i64,
+}
+
+impl DayCommand {
+    /// Create a day command for the journal page `offset` days from today.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - Parsed command arguments
+    /// * `offset` - Days relative to today, e.g. `-1` for yesterday
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The command
+    pub fn new(args: DayArgs, offset: i64) -> Self {
+        Self { args, offset }
+    }
+
+    /// Resolve the date of the journal page.
+    ///
+    /// # Arguments
+    ///
+    /// * `today` - Current journal day of the space
+    fn date(&self, today: NaiveDate) -> NaiveDate {
+        let days = Days::new(self.offset.unsigned_abs());
+        if self.offset < 0 {
+            today - days
+        } else {
+            today + days
+        }
+    }
+}
+
+impl Command for DayCommand {
+    type Args = DayArgs;
+    type Output = DayOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self::new(args, 0)
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        let date = self.date(space.today());
+        let id = space.journal_page(date);
+        let name = page::name_from_id(&id);
+        space.set_allow_locked(self.args.force);
+        if self.args.edit && !self.args.force && space.is_locked(&id)? {
+            return Err(CliError::page_locked(&name).into());
+        }
+
+        let created = if self.args.create || self.args.edit {
+            space.create_journal_page(date)?
+        } else {
+            false
+        };
+
+        if self.args.edit {
+            let file_path = space.path().join(&id);
+            open_in_editor(&file_path)?;
+            // Bring the edits into the document
+            let content = fs::read_to_string(&file_path).into_diagnostic()?;
+            space.write_page(&id, &content)?;
+        }
+
+        Ok(DayOutput {
+            content: space.read_page(&id)?,
+            name,
+            id,
+            created,
+            edited: self.args.edit,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.edited {
+            global.success(&format!("Saved {}", output.name));
+            return;
+        }
+
+        match &output.content {
+            Some(content) => {
+                if output.created {
+                    global.success(&format!("Created {}", output.name));
+                    global.blank();
+                }
+                global.print(content.trim_end());
+            }
+            None => global.info(&format!(
+                "No journal page for {} yet, use --create or --edit to start one",
+                output.name
+            )),
+        }
+    }
+}
+
+/// Opens a file in the user's editor and waits for it to close.
+fn open_in_editor(file_path: &Path) -> Result<()> {
+    // The configured editor wins over the environment, like git's core.editor
+    let editor = match Config::load()?.editor() {
+        Some(editor) => editor.to_string(),
+        None => env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .unwrap_or_else(|_| {
+                if cfg!(windows) {
+                    "notepad".to_string()
+                } else {
+                    "vi".to_string()
+                }
+            }),
+    };
+
+    // Editors are commonly configured with arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = process::Command::new(program)
+        .args(parts)
+        .arg(file_path)
+        .status()
+        .map_err(|err| CliError::editor(&editor, err.to_string()))?;
+
+    if !status.success() {
+        return Err(CliError::editor(&editor, format!("exited with {}", status)).into());
+    }
+    Ok(())
+}
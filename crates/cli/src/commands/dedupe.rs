@@ -0,0 +1,137 @@
+//! Find pages and blocks with identical or highly similar content.
+
+use clap::Args;
+use flow_core::dedupe::{self, DuplicateCluster, DuplicateKind};
+use flow_core::merge::MergedPage;
+use inquire::Select;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the dedupe command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DedupeOutput {
+    pub clusters: Vec<DuplicateCluster>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<MergedPage>,
+}
+
+/// Arguments for the dedupe command.
+#[derive(Args)]
+pub struct DedupeArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Similarity from which notes are duplicates, from 0 to 1
+    #[arg(short, long, default_value_t = dedupe::DEFAULT_THRESHOLD)]
+    pub threshold: f64,
+
+    /// Pick a page to keep for each cluster of duplicate pages and merge the others into it
+    #[arg(long)]
+    pub merge: bool,
+}
+
+/// Dedupe command implementation.
+pub struct DedupeCommand {
+    args: DedupeArgs,
+}
+
+impl Command for DedupeCommand {
+    type Args = DedupeArgs;
+    type Output = DedupeOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        if !(0.0..=1.0).contains(&self.args.threshold) {
+            miette::bail!("The threshold must be between 0 and 1");
+        }
+
+        let mut space = if self.args.merge {
+            global.load_graph()?
+        } else {
+            global.load_graph_readonly()?
+        };
+        global.step("Comparing notes");
+        let clusters = space.find_duplicates(self.args.threshold)?;
+
+        let mut merged = Vec::new();
+        // Merging needs someone to pick the page to keep
+        if self.args.merge && !global.json {
+            const SKIP: &str = "Skip";
+            for cluster in clusters.iter().filter(|c| c.kind == DuplicateKind::Page) {
+                let mut options: Vec<String> =
+                    cluster.items.iter().map(|item| item.page.clone()).collect();
+                options.push(SKIP.to_string());
+
+                let prompt = format!(
+                    "Keep which page? The others are merged into it ({:.0}% similar)",
+                    cluster.similarity * 100.0
+                );
+                let Ok(choice) = Select::new(&prompt, options).prompt() else {
+                    break;
+                };
+                let Some(keep) = cluster.items.iter().find(|item| item.page == choice) else {
+                    continue;
+                };
+                for item in cluster.items.iter().filter(|item| item.id != keep.id) {
+                    merged.push(space.merge_page(&item.id, &keep.id)?);
+                }
+            }
+        }
+
+        Ok(DedupeOutput { clusters, merged })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.clusters.is_empty() {
+            global.info("No duplicates found");
+            return;
+        }
+
+        for cluster in &output.clusters {
+            global.heading(&format!(
+                "Duplicate {}s ({:.0}% similar)",
+                match cluster.kind {
+                    DuplicateKind::Page => "page",
+                    DuplicateKind::Block => "block",
+                },
+                cluster.similarity * 100.0
+            ));
+            for item in &cluster.items {
+                let location = match item.line {
+                    Some(line) => format!("[[{}]]:{}", item.page, line),
+                    None => format!("[[{}]]", item.page),
+                };
+                global.kv(&location, &item.text);
+            }
+            global.blank();
+        }
+
+        for merge in &output.merged {
+            global.success(&format!(
+                "Merged [[{}]] into [[{}]], rewriting {} link{}",
+                flow_core::page::name_from_id(&merge.source),
+                flow_core::page::name_from_id(&merge.target),
+                merge.links,
+                if merge.links == 1 { "" } else { "s" }
+            ));
+        }
+        if output.merged.is_empty() {
+            global.info(&format!(
+                "Found {} cluster{} of duplicates",
+                output.clusters.len(),
+                if output.clusters.len() == 1 { "" } else { "s" }
+            ));
+        }
+    }
+}
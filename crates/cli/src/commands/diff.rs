@@ -0,0 +1,88 @@
+//! Diff a page against a historical version or its markdown file.
+
+use clap::Args;
+use console::style;
+use flow_core::diff::{LineKind, PageDiff};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the diff command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DiffOutput {
+    #[serde(flatten)]
+    pub diff: PageDiff,
+}
+
+/// Arguments for the diff command.
+#[derive(Args)]
+pub struct DiffArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Name of the page
+    pub page: String,
+
+    /// Compare with the page at a checkpoint or after a change listed by `flow log`
+    #[arg(long, value_name = "VERSION", conflicts_with = "disk")]
+    pub at: Option<String>,
+
+    /// Compare the document with the markdown file on disk (the default)
+    #[arg(long)]
+    pub disk: bool,
+}
+
+/// Diff command implementation.
+pub struct DiffCommand {
+    args: DiffArgs,
+}
+
+impl Command for DiffCommand {
+    type Args = DiffArgs;
+    type Output = DiffOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+        let diff = match &self.args.at {
+            Some(version) => space.diff_at(&self.args.page, version)?,
+            None => space.diff_disk(&self.args.page)?,
+        };
+
+        Ok(DiffOutput {
+            diff: diff.ok_or_else(|| CliError::page_not_found(&self.args.page))?,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let diff = &output.diff;
+        if diff.is_empty() {
+            global.info(&format!("{} has no differences", diff.page));
+            return;
+        }
+
+        global.print(&style(format!("--- {}", diff.old)).bold().to_string());
+        global.print(&style(format!("+++ {}", diff.new)).bold().to_string());
+        for hunk in &diff.hunks {
+            global.print(&style(hunk.header()).cyan().to_string());
+            for line in &hunk.lines {
+                let formatted = match line.kind {
+                    LineKind::Context => format!(" {}", line.text),
+                    LineKind::Removed => style(format!("-{}", line.text)).red().to_string(),
+                    LineKind::Added => style(format!("+{}", line.text)).green().to_string(),
+                };
+                global.print(&formatted);
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Print copy-pasteable usage examples.
+
+use clap::Args;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+use crate::examples::{self, Example, EXAMPLES};
+
+/// Output structure for the examples command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExamplesOutput {
+    /// The requested command, `None` when all examples were requested.
+    pub command: Option<String>,
+    pub examples: Vec<Example>,
+}
+
+/// Arguments for the examples command.
+#[derive(Args)]
+pub struct ExamplesArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Command to print the examples of, all commands if omitted
+    pub command: Option<String>,
+}
+
+/// Examples command implementation.
+pub struct ExamplesCommand {
+    args: ExamplesArgs,
+}
+
+impl Command for ExamplesCommand {
+    type Args = ExamplesArgs;
+    type Output = ExamplesOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let examples = match self.args.command.as_deref() {
+            Some(command) => {
+                let examples = examples::for_command(command);
+                if examples.is_empty() {
+                    return Err(CliError::no_examples(command).into());
+                }
+                examples
+            }
+            None => EXAMPLES.to_vec(),
+        };
+
+        Ok(ExamplesOutput {
+            command: self.args.command,
+            examples,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        // One section per command, in the order of the registry
+        let mut commands: Vec<&str> = Vec::new();
+        for example in &output.examples {
+            if !commands.contains(&example.command) {
+                commands.push(example.command);
+            }
+        }
+
+        for (index, command) in commands.iter().enumerate() {
+            if index > 0 {
+                global.blank();
+            }
+            global.heading(&format!("flow {}", command));
+            let examples: Vec<Example> = output
+                .examples
+                .iter()
+                .filter(|example| example.command == *command)
+                .copied()
+                .collect();
+            global.print(&examples::render(&examples));
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! Export a Flow graph to other formats.
+
+use schemars::JsonSchema;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use clap::{Args, Subcommand};
+use flow_core::archive::{Manifest, ARCHIVE_EXTENSION};
+use flow_core::config::Config;
+use flow_core::feed::FEED_FILE;
+use flow_core::links;
+use flow_core::render::{self, RenderPage};
+use miette::{IntoDiagnostic, Result};
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::error::CliError;
+use crate::interrupt;
+
+/// Output structure for the export command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExportOutput {
+    pub format: String,
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<Manifest>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renderer: Option<String>,
+}
+
+/// Export actions.
+#[derive(Subcommand)]
+pub enum ExportAction {
+    /// Pack the whole graph into a single archive file
+    Archive {
+        /// Archive file to write (`.flowpack` is added if no extension is given)
+        file: PathBuf,
+    },
+    /// Render a page or a range of journal days to PDF
+    Pdf {
+        /// Page name or alias
+        #[arg(conflicts_with_all = ["since", "until"])]
+        page: Option<String>,
+
+        /// Export journal pages from this date on (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<NaiveDate>,
+
+        /// Export journal pages up to this date (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<NaiveDate>,
+
+        /// PDF file to write (defaults to the page name or date range)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Renderer command with {input} and {output} placeholders (overrides `pdf_renderer` in the config)
+        #[arg(long)]
+        renderer: Option<String>,
+    },
+    /// Convert pages to org-mode files
+    Org {
+        /// Page name or alias (defaults to all pages)
+        page: Option<String>,
+
+        /// Directory to write the org files to
+        #[arg(short, long, default_value = "org")]
+        output: PathBuf,
+    },
+    /// Write an Atom feed of recent journal days and featured pages
+    Feed {
+        /// Feed file to write
+        #[arg(short, long, default_value = FEED_FILE)]
+        output: PathBuf,
+
+        /// Base URL of the published site (defaults to `publish.feed.url` in space.toml)
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Maximum number of entries (defaults to `publish.feed.entries` in space.toml)
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+    /// Write the graph of pages and links in Graphviz DOT format
+    Dot {
+        /// DOT file to write
+        #[arg(short, long, default_value = "graph.dot")]
+        output: PathBuf,
+    },
+}
+
+/// Arguments for the export command.
+#[derive(Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: ExportAction,
+}
+
+/// Export command implementation.
+pub struct ExportCommand {
+    args: ExportArgs,
+}
+
+impl Command for ExportCommand {
+    type Args = ExportArgs;
+    type Output = ExportOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+        match self.args.action {
+            ExportAction::Archive { mut file } => {
+                if file.extension().is_none() {
+                    file.set_extension(ARCHIVE_EXTENSION);
+                }
+
+                self.args
+                    .global
+                    .step(&format!("Packing {} into {}", space.name(), file.display()));
+                let manifest = space.export_archive(
+                    &file,
+                    self.args.global.progress().as_ref(),
+                    &interrupt::token(),
+                )?;
+
+                Ok(ExportOutput {
+                    format: "archive".to_string(),
+                    file: path_to_display_string(&file),
+                    manifest: Some(manifest),
+                    pages: Vec::new(),
+                    renderer: None,
+                })
+            }
+            ExportAction::Pdf {
+                page,
+                since,
+                until,
+                output,
+                renderer,
+            } => {
+                let (title, pages) = match page {
+                    Some(name) => {
+                        let id = space.page_index()?.resolve(&name);
+                        let content = space
+                            .read_page(&id)?
+                            .ok_or_else(|| CliError::page_not_found(&name))?;
+                        let content = space.expand_embeds(&id, &content)?.content;
+                        (name, vec![RenderPage { id, content }])
+                    }
+                    None if since.is_some() || until.is_some() => {
+                        let format = |date: Option<NaiveDate>| {
+                            date.map(|date| date.format("%Y-%m-%d").to_string())
+                                .unwrap_or_default()
+                        };
+                        let pages: Vec<RenderPage> = space
+                            .journal(since, until)?
+                            .into_iter()
+                            .map(|day| {
+                                let content = space.expand_embeds(&day.id, &day.content)?.content;
+                                Ok(RenderPage {
+                                    id: day.id,
+                                    content,
+                                })
+                            })
+                            .collect::<Result<_>>()?;
+                        if pages.is_empty() {
+                            return Err(CliError::Other {
+                                message: "No journal pages in the given date range".to_string(),
+                            }
+                            .into());
+                        }
+                        (format!("{}..{}", format(since), format(until)), pages)
+                    }
+                    None => return Err(CliError::missing_argument("page").into()),
+                };
+
+                let file = output.unwrap_or_else(|| {
+                    PathBuf::from(format!("{}.pdf", title.replace(['/', '\\'], "-")))
+                });
+                let renderer = match renderer {
+                    Some(renderer) => Some(renderer),
+                    None => Config::load()?.pdf_renderer().map(str::to_string),
+                };
+
+                self.args.global.step(&format!(
+                    "Rendering {} page{} to {}",
+                    pages.len(),
+                    if pages.len() == 1 { "" } else { "s" },
+                    file.display()
+                ));
+                let html = render::to_html(&title, &pages);
+                let used = render::to_pdf(&html, &file, renderer.as_deref())?;
+
+                Ok(ExportOutput {
+                    format: "pdf".to_string(),
+                    file: path_to_display_string(&file),
+                    manifest: None,
+                    pages: pages.into_iter().map(|page| page.id).collect(),
+                    renderer: Some(used),
+                })
+            }
+            ExportAction::Org { page, output } => {
+                self.args.global.step(&format!(
+                    "Converting {} to org in {}",
+                    page.as_deref().unwrap_or("all pages"),
+                    output.display()
+                ));
+                if let Some(name) = &page {
+                    let id = space.page_index()?.resolve(name);
+                    if space.read_page(&id)?.is_none() {
+                        return Err(CliError::page_not_found(name).into());
+                    }
+                }
+                let files = space.export_org(&output, page.as_deref())?;
+
+                Ok(ExportOutput {
+                    format: "org".to_string(),
+                    file: path_to_display_string(&output),
+                    manifest: None,
+                    pages: files
+                        .iter()
+                        .map(|file| path_to_display_string(file))
+                        .collect(),
+                    renderer: None,
+                })
+            }
+            ExportAction::Feed { output, url, limit } => {
+                let settings = &space.publish_settings().feed;
+                let url = url.or_else(|| settings.url.clone());
+                let limit = limit.unwrap_or(settings.entries);
+
+                self.args
+                    .global
+                    .step(&format!("Writing feed to {}", output.display()));
+                let atom = space.feed(url.as_deref(), limit)?;
+                fs::write(&output, &atom).into_diagnostic()?;
+
+                Ok(ExportOutput {
+                    format: "atom".to_string(),
+                    file: path_to_display_string(&output),
+                    manifest: None,
+                    pages: Vec::new(),
+                    renderer: None,
+                })
+            }
+            ExportAction::Dot { output } => {
+                self.args
+                    .global
+                    .step(&format!("Writing link graph to {}", output.display()));
+                let graph = links::graph(&space.page_index()?);
+                fs::write(&output, graph.to_dot(space.name())).into_diagnostic()?;
+
+                Ok(ExportOutput {
+                    format: "dot".to_string(),
+                    file: path_to_display_string(&output),
+                    manifest: None,
+                    pages: graph.nodes.into_iter().map(|node| node.id).collect(),
+                    renderer: None,
+                })
+            }
+        }
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        match (output.format.as_str(), &output.manifest) {
+            ("archive", Some(manifest)) => {
+                global.success(&format!("Exported {}", manifest.space));
+                global.blank();
+                global.kv("Archive", &output.file);
+                global.kv("Files", &manifest.entries.len().to_string());
+                global.kv("Size", &format!("{} bytes", manifest.size()));
+            }
+            ("pdf", _) => {
+                global.success("Exported PDF");
+                global.blank();
+                global.kv("File", &output.file);
+                global.kv("Pages", &output.pages.len().to_string());
+                if let Some(renderer) = &output.renderer {
+                    global.kv("Renderer", renderer);
+                }
+            }
+            ("org", _) => {
+                global.success(&format!(
+                    "Exported {} page{} to org",
+                    output.pages.len(),
+                    if output.pages.len() == 1 { "" } else { "s" }
+                ));
+                global.blank();
+                global.kv("Directory", &output.file);
+                for file in &output.pages {
+                    global.print_verbose(file);
+                }
+            }
+            ("dot", _) => {
+                global.success("Exported link graph");
+                global.blank();
+                global.kv("File", &output.file);
+                global.kv("Pages", &output.pages.len().to_string());
+            }
+            _ => global.success(&format!("Exported {}", output.file)),
+        }
+    }
+}
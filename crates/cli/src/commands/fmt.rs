@@ -0,0 +1,94 @@
+//! Format the markdown of pages.
+
+use clap::Args;
+use flow_core::index::PageIndex;
+use flow_core::page;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the fmt command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FmtOutput {
+    pub pages: Vec<String>,
+}
+
+/// Arguments for the fmt command.
+#[derive(Args)]
+pub struct FmtArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias (defaults to all pages)
+    pub page: Option<String>,
+
+    /// Fail if pages are not formatted, without changing them
+    #[arg(long)]
+    pub check: bool,
+
+    /// Format locked pages too
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Fmt command implementation.
+pub struct FmtCommand {
+    args: FmtArgs,
+}
+
+impl Command for FmtCommand {
+    type Args = FmtArgs;
+    type Output = FmtOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+
+        if let Some(name) = &self.args.page {
+            let id = PageIndex::load(space.path())?.resolve(name);
+            if space.read_page(&id)?.is_none() {
+                return Err(CliError::page_not_found(name).into());
+            }
+            if !self.args.check && !self.args.force && space.is_locked(&id)? {
+                return Err(CliError::page_locked(name).into());
+            }
+        }
+        space.set_allow_locked(self.args.force);
+
+        let pages: Vec<String> = space
+            .format_pages(self.args.page.as_deref(), self.args.check)?
+            .iter()
+            .map(|id| page::name_from_id(id))
+            .collect();
+        if self.args.check && !pages.is_empty() {
+            return Err(CliError::unformatted(&pages).into());
+        }
+
+        Ok(FmtOutput { pages })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.pages.is_empty() {
+            global.success("All pages are formatted");
+            return;
+        }
+        for page in &output.pages {
+            global.print(&format!("  {}", page));
+        }
+        global.success(&format!(
+            "Formatted {} page{}",
+            output.pages.len(),
+            if output.pages.len() == 1 { "" } else { "s" }
+        ));
+    }
+}
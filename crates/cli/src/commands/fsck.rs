@@ -0,0 +1,146 @@
+//! Check and repair the integrity of a Flow graph.
+
+use clap::Args;
+use flow_core::fsck::{self, Issue};
+use flow_core::space::Space;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::error::CliError;
+use crate::interrupt;
+
+/// Output structure for the fsck command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FsckOutput {
+    path: String,
+    checked: usize,
+    issues: Vec<Issue>,
+    repaired: Option<String>,
+    rebuilt: usize,
+    backup: Option<String>,
+}
+
+/// Arguments for the fsck command.
+#[derive(Args)]
+pub struct FsckArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Rebuild the document from the markdown files (loses history, backs up the document first)
+    #[arg(long, conflicts_with = "rebuild_files")]
+    pub rebuild_doc: bool,
+
+    /// Rewrite the markdown files from the document
+    #[arg(long)]
+    pub rebuild_files: bool,
+}
+
+/// Fsck command implementation.
+pub struct FsckCommand {
+    args: FsckArgs,
+}
+
+impl Command for FsckCommand {
+    type Args = FsckArgs;
+    type Output = FsckOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let path = self.args.global.graph_path()?;
+        if !Space::exists(&path) {
+            return Err(CliError::invalid_graph(path).into());
+        }
+
+        self.args
+            .global
+            .step(&format!("Checking graph at {}", path.display()));
+        let report = fsck::check(&path)?;
+
+        let mut repaired = None;
+        let mut rebuilt = 0;
+        let mut backup = None;
+        if self.args.rebuild_doc {
+            self.args
+                .global
+                .step("Rebuilding document from markdown files");
+            let result = fsck::rebuild_doc(
+                &path,
+                self.args.global.progress().as_ref(),
+                &interrupt::token(),
+            )?;
+            rebuilt = result.pages;
+            backup = result.backup.map(|backup| backup.id);
+            repaired = Some("document".to_string());
+        } else if self.args.rebuild_files {
+            self.args
+                .global
+                .step("Rewriting markdown files from document");
+            rebuilt = fsck::rebuild_files(
+                &path,
+                self.args.global.progress().as_ref(),
+                &interrupt::token(),
+            )?;
+            repaired = Some("files".to_string());
+        }
+
+        Ok(FsckOutput {
+            path: path_to_display_string(&path),
+            checked: report.checked,
+            issues: report.issues,
+            repaired,
+            rebuilt,
+            backup,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.heading("Integrity Check");
+        global.blank();
+        global.kv("Path", &output.path);
+        global.kv("Checked", &output.checked.to_string());
+        global.blank();
+
+        for issue in &output.issues {
+            global.warning(&issue.describe());
+        }
+        if !output.issues.is_empty() {
+            global.blank();
+        }
+
+        match output.repaired.as_deref() {
+            Some("document") => {
+                global.success(&format!(
+                    "Rebuilt document from {} markdown file{}",
+                    output.rebuilt,
+                    if output.rebuilt == 1 { "" } else { "s" }
+                ));
+                if let Some(backup) = &output.backup {
+                    global.info(&format!(
+                        "Backed up the previous document as {}, restore it with: flow backup restore {}",
+                        backup, backup
+                    ));
+                }
+            }
+            Some(_) => global.success(&format!(
+                "Rewrote {} markdown file{} from document",
+                output.rebuilt,
+                if output.rebuilt == 1 { "" } else { "s" }
+            )),
+            None if output.issues.is_empty() => global.success("No issues found"),
+            None => global.info(&format!(
+                "Found {} issue{}, rerun with --rebuild-doc or --rebuild-files to repair",
+                output.issues.len(),
+                if output.issues.len() == 1 { "" } else { "s" }
+            )),
+        }
+    }
+}
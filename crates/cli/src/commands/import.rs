@@ -0,0 +1,181 @@
+//! Import a Flow graph from other formats.
+
+use schemars::JsonSchema;
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use flow_core::archive::{self, Manifest};
+use flow_core::config::Config;
+use flow_core::org::OrgImport;
+use flow_core::space::Space;
+use miette::{IntoDiagnostic, Result};
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, registration_name, Command, GlobalArgs};
+use crate::error::CliError;
+use crate::interrupt;
+
+/// Output structure for the import command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImportOutput {
+    pub name: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<Manifest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org: Option<OrgImport>,
+}
+
+/// Import actions.
+#[derive(Subcommand)]
+pub enum ImportAction {
+    /// Unpack a graph archive created by `flow export archive`
+    Archive {
+        /// Archive file to import
+        file: PathBuf,
+
+        /// Directory for the graph (defaults to the archived graph's name)
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Register the graph under a different name if its name is taken
+        #[arg(long)]
+        rename_to: Option<String>,
+    },
+    /// Convert org-mode files into pages of the graph
+    Org {
+        /// Org file or directory of org files
+        source: PathBuf,
+
+        /// Replace pages that already exist instead of skipping them
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+/// Arguments for the import command.
+#[derive(Args)]
+pub struct ImportArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: ImportAction,
+}
+
+/// Import command implementation.
+pub struct ImportCommand {
+    args: ImportArgs,
+}
+
+impl Command for ImportCommand {
+    type Args = ImportArgs;
+    type Output = ImportOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        let (file, path, rename_to) = match &self.args.action {
+            ImportAction::Archive {
+                file,
+                path,
+                rename_to,
+            } => (file, path, rename_to),
+            ImportAction::Org { source, overwrite } => {
+                let mut space = global.load_graph()?;
+                global.step(&format!("Converting {}", source.display()));
+                let progress = global.progress();
+                let report = space.import_org(source, *overwrite, progress.as_ref())?;
+                return Ok(ImportOutput {
+                    name: space.name().to_string(),
+                    path: path_to_display_string(space.path()),
+                    manifest: None,
+                    org: Some(report),
+                });
+            }
+        };
+
+        let manifest = archive::read_manifest(file)?;
+        let path = path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(&manifest.space));
+
+        if Space::exists(&path) {
+            return Err(CliError::graph_already_exists(path).into());
+        }
+        if let Some(parent) = Space::enclosing(&path) {
+            return Err(CliError::nested_graph(path, parent).into());
+        }
+
+        // Resolve name collisions before anything is written to disk
+        let mut config = Config::load()?;
+        let absolute_path = std::path::absolute(&path).into_diagnostic()?;
+        let registered_name = registration_name(
+            global,
+            &config,
+            &manifest.space,
+            &absolute_path,
+            rename_to.as_ref(),
+        )?;
+
+        global.step(&format!(
+            "Unpacking {} into {}",
+            file.display(),
+            path.display()
+        ));
+        let (graph, manifest) =
+            Space::import_archive(file, &path, global.progress().as_ref(), &interrupt::token())?;
+
+        global.step("Registering graph in configuration");
+        config.register_space_as(&graph, &registered_name)?;
+
+        let canonical_path = path.canonicalize().into_diagnostic()?;
+        Ok(ImportOutput {
+            name: registered_name,
+            path: path_to_display_string(&canonical_path),
+            manifest: Some(manifest),
+            org: None,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if let Some(report) = &output.org {
+            let count = report.imported.len();
+            global.success(&format!(
+                "Imported {} page{} into {}",
+                count,
+                if count == 1 { "" } else { "s" },
+                output.name
+            ));
+            for id in &report.imported {
+                global.print_verbose(id);
+            }
+            if !report.skipped.is_empty() {
+                global.warning(&format!(
+                    "Skipped {} existing page{}, use --overwrite to replace them",
+                    report.skipped.len(),
+                    if report.skipped.len() == 1 { "" } else { "s" }
+                ));
+                for id in &report.skipped {
+                    global.print_verbose(id);
+                }
+            }
+            return;
+        }
+
+        global.success("Graph imported successfully");
+        global.blank();
+        global.kv("Name", &output.name);
+        global.kv("Path", &output.path);
+        if let Some(manifest) = &output.manifest {
+            global.kv("Files", &manifest.entries.len().to_string());
+        }
+    }
+}
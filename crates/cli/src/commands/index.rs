@@ -0,0 +1,105 @@
+//! Maintain the indexes of a Flow graph.
+
+use schemars::JsonSchema;
+use std::time::Instant;
+
+use clap::{Args, Subcommand};
+use flow_core::config::Config;
+use flow_core::fulltext::TextIndex;
+use flow_core::index::PageIndex;
+use miette::Result;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::interrupt;
+
+/// Output structure for the index command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IndexOutput {
+    pub pages: usize,
+    pub threads: usize,
+    pub elapsed_ms: u128,
+}
+
+/// Index actions.
+#[derive(Subcommand)]
+pub enum IndexAction {
+    /// Rebuild the page and full-text indexes from the markdown files
+    Rebuild {
+        /// Number of worker threads (defaults to `threads` from the config, 0 = all cores)
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+}
+
+/// Arguments for the index command.
+#[derive(Args)]
+pub struct IndexArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: IndexAction,
+}
+
+/// Index command implementation.
+pub struct IndexCommand {
+    args: IndexArgs,
+}
+
+impl Command for IndexCommand {
+    type Args = IndexArgs;
+    type Output = IndexOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        let path = global.graph_path()?;
+        let IndexAction::Rebuild { threads } = self.args.action;
+        let threads = match threads {
+            Some(threads) => threads,
+            None => Config::load()?.threads(),
+        };
+
+        let progress = global.progress();
+        let cancel = interrupt::token();
+        let started = Instant::now();
+
+        // Build both before saving either, so cancelling leaves the old indexes intact
+        let index = PageIndex::rebuild_with(&path, threads, progress.as_ref(), &cancel)?;
+        let text_index = TextIndex::rebuild(&path, progress.as_ref(), &cancel)?;
+        index.save(&path)?;
+        text_index.save(&path)?;
+
+        Ok(IndexOutput {
+            pages: index.len(),
+            threads,
+            elapsed_ms: started.elapsed().as_millis(),
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.success(&format!(
+            "Indexed {} page{}",
+            output.pages,
+            if output.pages == 1 { "" } else { "s" }
+        ));
+        global.blank();
+        global.kv(
+            "Threads",
+            &if output.threads == 0 {
+                "all cores".to_string()
+            } else {
+                output.threads.to_string()
+            },
+        );
+        global.kv("Time", &format!("{} ms", output.elapsed_ms));
+    }
+}
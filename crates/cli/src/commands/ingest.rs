@@ -0,0 +1,101 @@
+//! Ingest notes arriving outside of Flow, such as mail sent to yourself.
+
+use clap::{Args, Subcommand};
+use flow_core::ingest::{self, mail::IngestedMail};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the ingest command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IngestOutput {
+    pub source: String,
+    pub ingested: Vec<IngestedMail>,
+}
+
+/// Ingestion sources.
+#[derive(Subcommand)]
+pub enum IngestAction {
+    /// Turn unprocessed mail of the configured mailbox into notes
+    Mail,
+}
+
+/// Arguments for the ingest command.
+#[derive(Args)]
+pub struct IngestArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: IngestAction,
+}
+
+/// Ingest command implementation.
+pub struct IngestCommand {
+    args: IngestArgs,
+}
+
+impl Command for IngestCommand {
+    type Args = IngestArgs;
+    type Output = IngestOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let IngestAction::Mail = self.args.action;
+        let mut space = self.args.global.load_graph()?;
+        let Some(settings) = space.ingest_settings().mail.clone() else {
+            return Err(CliError::mail_not_configured(space.name()).into());
+        };
+
+        let source = settings.source.describe();
+        self.args
+            .global
+            .step(&format!("Reading mail from {}", source));
+        let mut mailbox = ingest::mailbox(&settings)?;
+        let ingested = space.ingest_mailbox(mailbox.as_mut(), settings.target)?;
+
+        Ok(IngestOutput { source, ingested })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.ingested.is_empty() {
+            global.info(&format!("No new mail in {}", output.source));
+            return;
+        }
+
+        for mail in &output.ingested {
+            let subject = if mail.subject.is_empty() {
+                "(no subject)"
+            } else {
+                &mail.subject
+            };
+            global.kv(
+                subject,
+                &format!(
+                    "[[{}]] from {}",
+                    flow_core::page::name_from_id(&mail.page),
+                    mail.from
+                ),
+            );
+            for attachment in &mail.attachments {
+                global.print(&format!("  {}", attachment));
+            }
+        }
+        global.blank();
+        global.success(&format!(
+            "Ingested {} message{}",
+            output.ingested.len(),
+            if output.ingested.len() == 1 { "" } else { "s" }
+        ));
+    }
+}
@@ -2,20 +2,33 @@
 
 use clap::Args;
 use flow_core::config::Config;
-use flow_core::graph::Graph;
+use flow_core::repo;
+use flow_core::space::Space;
+use flow_core::template::{self, SpaceTemplate};
 use inquire::Text;
 use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::common::{path_to_display_string, registration_name, Command, GlobalArgs};
 use crate::error::CliError;
 
 /// Output structure for the init command.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct InitOutput {
     pub name: String,
     pub path: String,
+    /// Root of the git repository a docs graph was initialized in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// Name of the template the graph was scaffolded from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Files scaffolded from the template
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scaffolded: Vec<String>,
 }
 
 /// Arguments for the init command.
@@ -31,14 +44,36 @@ pub struct InitArgs {
     #[arg(short, long)]
     pub name: Option<String>,
 
-    /// Initialize with template structure
+    /// Scaffold from a template: a name from 'flow template list', a directory or a git URL
     #[arg(short, long)]
     pub template: Option<String>,
+
+    /// Value of a template variable, e.g. --var team=Platform (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var, requires = "template")]
+    pub vars: Vec<(String, String)>,
+
+    /// Skip scaffolding the journal directory
+    #[arg(long)]
+    pub bare: bool,
+
+    /// Register the graph under a different name if its name is taken
+    #[arg(long)]
+    pub rename_to: Option<String>,
+
+    /// Initialize a docs graph inside a git repository, e.g. docs/notes
+    #[arg(long)]
+    pub docs: bool,
+
+    /// Keep the snapshot of a docs graph tracked by git instead of ignored
+    #[arg(long, requires = "docs")]
+    pub track_snapshot: bool,
 }
 
 /// Init command implementation.
 pub struct InitCommand {
     args: InitArgs,
+    /// Template resolved while asking for its variables
+    template: Option<SpaceTemplate>,
 }
 
 impl Command for InitCommand {
@@ -46,7 +81,10 @@ impl Command for InitCommand {
     type Output = InitOutput;
 
     fn from_args(args: Self::Args) -> Self {
-        Self { args }
+        Self {
+            args,
+            template: None,
+        }
     }
 
     fn global_args(&self) -> &GlobalArgs {
@@ -80,6 +118,32 @@ impl Command for InitCommand {
             }
         }
 
+        // Ask for the template variables without value
+        if let Some(source) = self.args.template.clone() {
+            let resolved = template::resolve(&source)?;
+            for variable in &resolved.manifest.variables {
+                if self
+                    .args
+                    .vars
+                    .iter()
+                    .any(|(name, _)| name == &variable.name)
+                {
+                    continue;
+                }
+                let message = format!("{}:", variable.name);
+                let mut prompt = Text::new(&message);
+                if !variable.description.is_empty() {
+                    prompt = prompt.with_help_message(&variable.description);
+                }
+                if let Some(default) = &variable.default {
+                    prompt = prompt.with_default(default);
+                }
+                let value = prompt.prompt().map_err(CliError::from)?;
+                self.args.vars.push((variable.name.clone(), value));
+            }
+            self.template = Some(resolved);
+        }
+
         Ok(())
     }
 
@@ -94,32 +158,86 @@ impl Command for InitCommand {
         let mut config = Config::load()?;
 
         // Check if path already exists and has a .flow directory
-        if Graph::exists(path.as_path()) {
+        if Space::exists(path.as_path()) {
             return Err(CliError::graph_already_exists(path).into());
         }
 
+        // Refuse to nest a graph inside another one
+        if let Some(parent) = Space::enclosing(&path) {
+            return Err(CliError::nested_graph(path, parent).into());
+        }
+
+        // Docs graphs need the repository they are initialized in
+        let repository = if self.args.docs {
+            Some(repo::root(&path).ok_or_else(|| CliError::not_in_repository(&path))?)
+        } else {
+            None
+        };
+
+        // Resolve name collisions before anything is written to disk
+        let absolute_path = std::path::absolute(&path).into_diagnostic()?;
+        let mut preferred_name = name.clone().unwrap_or_else(|| {
+            absolute_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        if let Some(root) = &repository {
+            preferred_name = repo::namespaced(root, &preferred_name);
+        }
+        let registered_name = registration_name(
+            &self.args.global,
+            &config,
+            &preferred_name,
+            &absolute_path,
+            self.args.rename_to.as_ref(),
+        )?;
+
         self.args
             .global
             .step(&format!("Initializing graph at {}", path.display()));
 
-        let graph = Graph::init(&path, name.as_ref())?;
+        // Resolve the template before anything is written to disk
+        let template = match (self.template, &self.args.template) {
+            (Some(resolved), _) => Some(resolved),
+            (None, Some(source)) => Some(template::resolve(source)?),
+            (None, None) => None,
+        };
+        let values: BTreeMap<String, String> = self.args.vars.into_iter().collect();
+        if let Some(template) = &template {
+            template.values(&values)?;
+        }
+
+        let mut graph = Space::init(&path, name.as_ref(), self.args.bare)?;
+
+        let mut scaffolded = Vec::new();
+        if let Some(template) = &template {
+            self.args.global.step(&format!(
+                "Scaffolding from template '{}'",
+                template.manifest.name
+            ));
+            scaffolded = graph.apply_template(template, &values)?;
+        }
 
-        // TODO: Handle template parameter when template support is implemented
-        if self.args.template.is_some() {
+        if let Some(root) = &repository {
             self.args
                 .global
-                .warning("Template support not yet implemented");
+                .step("Ignoring graph internals in .gitignore");
+            repo::ignore_internals(root, &path, self.args.track_snapshot)?;
         }
 
         self.args.global.step("Registering graph in configuration");
-        config.add_graph(&graph)?;
+        config.register_space_as(&graph, &registered_name)?;
 
         let canonical_path = path.canonicalize().into_diagnostic()?;
         let display_path = path_to_display_string(&canonical_path);
 
         Ok(InitOutput {
-            name: graph.name().to_string(),
+            name: registered_name,
             path: display_path,
+            repository: repository.as_deref().map(path_to_display_string),
+            template: template.map(|template| template.manifest.name),
+            scaffolded,
         })
     }
 
@@ -128,5 +246,22 @@ impl Command for InitCommand {
         global.blank();
         global.kv("Name", &output.name);
         global.kv("Path", &output.path);
+        if let Some(repository) = &output.repository {
+            global.kv("Repository", repository);
+        }
+        if let Some(template) = &output.template {
+            global.kv("Template", template);
+            global.kv("Scaffolded", &format!("{} files", output.scaffolded.len()));
+        }
+    }
+}
+
+/// Parses a `--var NAME=VALUE` template variable.
+fn parse_var(value: &str) -> std::result::Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("invalid variable '{}', expected NAME=VALUE", value)),
     }
 }
@@ -0,0 +1,119 @@
+//! Check the wikilinks of a Flow graph.
+
+use clap::{Args, Subcommand};
+use flow_core::index::PageIndex;
+use flow_core::links::{self, DeadLink, OrphanPage};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the links command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LinksOutput {
+    pub dead: Vec<DeadLink>,
+    pub orphans: Vec<OrphanPage>,
+    pub created: Vec<String>,
+}
+
+/// Links actions.
+#[derive(Subcommand)]
+pub enum LinksAction {
+    /// Report dead wikilinks and orphan pages
+    Check {
+        /// Create empty pages for all dead link targets
+        #[arg(long)]
+        create_missing: bool,
+    },
+}
+
+/// Arguments for the links command.
+#[derive(Args)]
+pub struct LinksArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: LinksAction,
+}
+
+/// Links command implementation.
+pub struct LinksCommand {
+    args: LinksArgs,
+}
+
+impl Command for LinksCommand {
+    type Args = LinksArgs;
+    type Output = LinksOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        let path = global.graph_path()?;
+        let LinksAction::Check { create_missing } = self.args.action;
+
+        let mut index = PageIndex::load(&path)?;
+        if index.is_stale(&path)? {
+            global.step("Index is stale, rebuilding");
+            index = PageIndex::rebuild(&path)?;
+            index.save(&path)?;
+        }
+        let report = links::check(&index);
+
+        let mut created = Vec::new();
+        if create_missing && !report.dead.is_empty() {
+            let mut space = global.load_graph()?;
+            created = space.create_pages(&report.missing())?;
+        }
+
+        Ok(LinksOutput {
+            dead: report.dead,
+            orphans: report.orphans,
+            created,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if !output.dead.is_empty() {
+            global.heading("Dead links");
+            for dead in &output.dead {
+                global.kv(&dead.page, &format!("[[{}]]", dead.target));
+            }
+            global.blank();
+        }
+
+        if !output.orphans.is_empty() {
+            global.heading("Orphan pages");
+            for orphan in &output.orphans {
+                global.print(&format!("  {}", orphan.page));
+            }
+            global.blank();
+        }
+
+        if !output.created.is_empty() {
+            global.success(&format!(
+                "Created {} missing page{}",
+                output.created.len(),
+                if output.created.len() == 1 { "" } else { "s" }
+            ));
+        } else if output.dead.is_empty() && output.orphans.is_empty() {
+            global.success("No dead links or orphan pages");
+        } else {
+            global.info(&format!(
+                "{} dead link{}, {} orphan page{}",
+                output.dead.len(),
+                if output.dead.len() == 1 { "" } else { "s" },
+                output.orphans.len(),
+                if output.orphans.len() == 1 { "" } else { "s" }
+            ));
+        }
+    }
+}
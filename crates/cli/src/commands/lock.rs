@@ -0,0 +1,94 @@
+//! Lock or unlock a page against changes.
+
+use clap::Args;
+use flow_core::index::PageIndex;
+use flow_core::page;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the lock commands.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LockOutput {
+    pub name: String,
+    pub id: String,
+    pub locked: bool,
+    pub changed: bool,
+}
+
+/// Arguments for the lock commands.
+#[derive(Args)]
+pub struct LockArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias
+    pub page: String,
+}
+
+/// Lock command implementation, locking or unlocking a page.
+pub struct LockCommand {
+    args: LockArgs,
+    locked: bool,
+}
+
+impl LockCommand {
+    /// Create a lock command.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - Parsed command arguments
+    /// * `locked` - Whether to lock or unlock the page
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The command
+    pub fn new(args: LockArgs, locked: bool) -> Self {
+        Self { args, locked }
+    }
+}
+
+impl Command for LockCommand {
+    type Args = LockArgs;
+    type Output = LockOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self::new(args, true)
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        let id = PageIndex::load(space.path())?.resolve(&self.args.page);
+        if space.read_page(&id)?.is_none() {
+            return Err(CliError::page_not_found(&self.args.page).into());
+        }
+
+        let changed = space.set_locked(&id, self.locked)?;
+        Ok(LockOutput {
+            name: page::name_from_id(&id),
+            id,
+            locked: self.locked,
+            changed,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let state = if output.locked { "locked" } else { "unlocked" };
+        if output.changed {
+            global.success(&format!(
+                "{} {}",
+                if output.locked { "Locked" } else { "Unlocked" },
+                output.name
+            ));
+        } else {
+            global.info(&format!("{} is already {}", output.name, state));
+        }
+    }
+}
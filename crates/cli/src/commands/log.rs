@@ -0,0 +1,107 @@
+//! Show the change history and named checkpoints of a Flow graph.
+
+use clap::Args;
+use flow_core::attribution::Change;
+use flow_core::checkpoints::Checkpoint;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the log command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LogOutput {
+    pub changes: Vec<Change>,
+    pub versions: Vec<Checkpoint>,
+    #[serde(skip)]
+    pub list_versions: bool,
+}
+
+/// Arguments for the log command.
+#[derive(Args)]
+pub struct LogArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Number of changes to list
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// List the named checkpoints instead of changes
+    #[arg(long)]
+    pub versions: bool,
+}
+
+/// Log command implementation.
+pub struct LogCommand {
+    args: LogArgs,
+}
+
+impl Command for LogCommand {
+    type Args = LogArgs;
+    type Output = LogOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+
+        if self.args.versions {
+            return Ok(LogOutput {
+                changes: Vec::new(),
+                versions: space.checkpoints().to_vec(),
+                list_versions: true,
+            });
+        }
+
+        Ok(LogOutput {
+            changes: space.log(self.args.limit),
+            versions: Vec::new(),
+            list_versions: false,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.list_versions {
+            if output.versions.is_empty() {
+                global.info("No checkpoints yet, create one with `flow tag-version <name>`");
+            }
+            for checkpoint in &output.versions {
+                let time = checkpoint
+                    .created_at()
+                    .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                global.kv(&checkpoint.name, &time);
+            }
+            return;
+        }
+
+        if output.changes.is_empty() {
+            global.info("No changes yet");
+            return;
+        }
+
+        for change in &output.changes {
+            let time = change
+                .time()
+                .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            global.kv(&change.id, &format!("{} {}", time, describe(change)));
+        }
+    }
+}
+
+/// Describes who made a change, `name (device)`.
+fn describe(change: &Change) -> String {
+    match &change.author {
+        Some(author) => format!("{} ({})", author.name, author.device),
+        None => "unknown".to_string(),
+    }
+}
@@ -0,0 +1,104 @@
+//! Capture meeting notes into today's journal page.
+
+use chrono::Local;
+use clap::{Args, Subcommand};
+use flow_core::context::Meeting;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the meeting command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MeetingOutput {
+    pub action: String,
+    pub meeting: Option<Meeting>,
+}
+
+/// Meeting actions.
+#[derive(Subcommand)]
+pub enum MeetingAction {
+    /// Start a meeting, `flow add` nests notes below it until it ends
+    Start {
+        /// Title of the meeting
+        title: String,
+    },
+    /// End the running meeting
+    End,
+    /// Show the running meeting
+    Status,
+}
+
+/// Arguments for the meeting command.
+#[derive(Args)]
+pub struct MeetingArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: MeetingAction,
+}
+
+/// Meeting command implementation.
+pub struct MeetingCommand {
+    args: MeetingArgs,
+}
+
+impl Command for MeetingCommand {
+    type Args = MeetingArgs;
+    type Output = MeetingOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+
+        let (action, meeting) = match &self.args.action {
+            MeetingAction::Start { title } => ("start", Some(space.start_meeting(title)?)),
+            MeetingAction::End => ("end", space.end_meeting()?),
+            MeetingAction::Status => ("status", space.current_meeting()),
+        };
+
+        Ok(MeetingOutput {
+            action: action.to_string(),
+            meeting,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let Some(meeting) = &output.meeting else {
+            global.info("No meeting running");
+            return;
+        };
+
+        let minutes = meeting
+            .started_at()
+            .map(|started| (Local::now() - started).num_minutes())
+            .unwrap_or_default();
+        match output.action.as_str() {
+            "start" => {
+                global.success(&format!("Started '{}'", meeting.title));
+                global.info(
+                    "Notes added with 'flow add' go below the meeting until 'flow meeting end'",
+                );
+            }
+            "end" => global.success(&format!(
+                "Ended '{}' after {} minute{}",
+                meeting.title,
+                minutes,
+                if minutes == 1 { "" } else { "s" }
+            )),
+            _ => {
+                global.kv("Meeting", &meeting.title);
+                global.kv("Running", &format!("{} min", minutes));
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Find unlinked mentions of a page.
+
+use clap::Args;
+use flow_core::mentions::Mention;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::common::{Command, GlobalArgs};
+use crate::daemon::Client;
+use crate::error::CliError;
+
+/// Output structure for the mentions command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MentionsOutput {
+    pub page: String,
+    pub linked: bool,
+    pub mentions: Vec<Mention>,
+}
+
+/// Arguments for the mentions command.
+#[derive(Args)]
+pub struct MentionsArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias
+    pub page: String,
+
+    /// Wrap every unlinked mention in a [[wikilink]]
+    #[arg(long)]
+    pub link_all: bool,
+}
+
+/// Mentions command implementation.
+pub struct MentionsCommand {
+    args: MentionsArgs,
+}
+
+impl Command for MentionsCommand {
+    type Args = MentionsArgs;
+    type Output = MentionsOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let forwarded = match Client::connect() {
+            Some(mut daemon) => {
+                self.args.global.step("Forwarding to daemon");
+                let path = self.args.global.graph_path()?;
+                daemon.forward(
+                    Some(&path),
+                    "mentions",
+                    json!({ "page": self.args.page, "link": self.args.link_all }),
+                )?
+            }
+            None => None,
+        };
+        let mentions = match forwarded {
+            Some(result) => serde_json::from_value(result).map_err(CliError::from)?,
+            None => {
+                let mut space = self.args.global.load_graph()?;
+                if self.args.link_all {
+                    space.link_mentions(&self.args.page)?
+                } else {
+                    space.mentions(&self.args.page)?
+                }
+            }
+        };
+
+        Ok(MentionsOutput {
+            page: self.args.page.clone(),
+            linked: self.args.link_all,
+            mentions,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.mentions.is_empty() {
+            global.info(&format!("No unlinked mentions of '{}'", output.page));
+            return;
+        }
+
+        for mention in &output.mentions {
+            global.kv(
+                &format!("{}:{}", mention.page, mention.line),
+                mention.text.trim(),
+            );
+        }
+        global.blank();
+        let count = output.mentions.len();
+        let plural = if count == 1 { "" } else { "s" };
+        if output.linked {
+            global.success(&format!("Linked {} mention{}", count, plural));
+        } else {
+            global.info(&format!(
+                "{} unlinked mention{}, run with --link-all to link them",
+                count, plural
+            ));
+        }
+    }
+}
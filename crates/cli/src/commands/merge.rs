@@ -0,0 +1,83 @@
+//! Merge a page into another, rewriting the links to it.
+
+use clap::Args;
+use flow_core::merge::MergedPage;
+use flow_core::page;
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Arguments for the merge command.
+#[derive(Args)]
+pub struct MergeArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Name or alias of the page to merge and trash
+    pub source: String,
+
+    /// Name or alias of the page to merge it into
+    pub target: String,
+
+    /// Rewrite links on locked pages too
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Merge command implementation.
+pub struct MergeCommand {
+    args: MergeArgs,
+}
+
+impl Command for MergeCommand {
+    type Args = MergeArgs;
+    type Output = MergedPage;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        space.set_allow_locked(self.args.force);
+
+        let index = space.page_index()?;
+        let source = index.resolve(&self.args.source);
+        let target = index.resolve(&self.args.target);
+        for (id, name) in [(&source, &self.args.source), (&target, &self.args.target)] {
+            if space.read_page(id)?.is_none() {
+                return Err(CliError::page_not_found(name).into());
+            }
+        }
+
+        space.merge_page(&source, &target)
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        for id in &output.relinked {
+            global.kv("Relinked", &format!("[[{}]]", page::name_from_id(id)));
+        }
+        for id in &output.locked {
+            global.warning(&format!(
+                "[[{}]] is locked and still links to [[{}]], use --force to rewrite it",
+                page::name_from_id(id),
+                page::name_from_id(&output.source)
+            ));
+        }
+        if !output.relinked.is_empty() || !output.locked.is_empty() {
+            global.blank();
+        }
+        global.success(&format!(
+            "Merged [[{}]] into [[{}]], rewriting {} link{}",
+            page::name_from_id(&output.source),
+            page::name_from_id(&output.target),
+            output.links,
+            if output.links == 1 { "" } else { "s" }
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+//! Migrate a Flow graph to the current on-disk format.
+
+use clap::Args;
+use flow_core::migration::{self, CURRENT_FORMAT};
+use flow_core::space::Space;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the migrate command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MigrateOutput {
+    from: u32,
+    to: u32,
+    migrations: Vec<String>,
+    dry_run: bool,
+}
+
+/// Arguments for the migrate command.
+#[derive(Args)]
+pub struct MigrateArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Show pending migrations without applying them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Migrate command implementation.
+pub struct MigrateCommand {
+    args: MigrateArgs,
+}
+
+impl Command for MigrateCommand {
+    type Args = MigrateArgs;
+    type Output = MigrateOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        // Loading a graph migrates it implicitly, so work on the path only
+        let path = self.args.global.graph_path()?;
+        if !Space::exists(&path) {
+            return Err(CliError::invalid_graph(path).into());
+        }
+
+        let from = migration::format_of(&path)?;
+        self.args
+            .global
+            .debug("Format", &format!("{} (current: {})", from, CURRENT_FORMAT));
+
+        let migrations = if self.args.dry_run {
+            migration::pending(&path)?
+        } else {
+            self.args.global.step("Running migrations");
+            migration::migrate(&path)?
+        };
+
+        Ok(MigrateOutput {
+            from,
+            to: if self.args.dry_run {
+                from
+            } else {
+                from.max(CURRENT_FORMAT)
+            },
+            migrations: migrations
+                .iter()
+                .map(|migration| migration.description.to_string())
+                .collect(),
+            dry_run: self.args.dry_run,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.from > CURRENT_FORMAT {
+            global.warning(&format!(
+                "Graph format {} is newer than supported format {}, upgrade Flow to modify it",
+                output.from, CURRENT_FORMAT
+            ));
+            return;
+        }
+
+        if output.migrations.is_empty() {
+            global.success("Graph is up to date");
+            return;
+        }
+
+        if output.dry_run {
+            global.info("Pending migrations:");
+        } else {
+            global.info("Applied migrations:");
+        }
+        for description in &output.migrations {
+            global.step(description);
+        }
+        global.blank();
+
+        if output.dry_run {
+            global.warning(&format!(
+                "Dry run: {} migration{} would be applied",
+                output.migrations.len(),
+                if output.migrations.len() == 1 {
+                    ""
+                } else {
+                    "s"
+                }
+            ));
+        } else {
+            global.success(&format!("Graph migrated to format {}", output.to));
+        }
+    }
+}
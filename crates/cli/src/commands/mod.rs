@@ -1,6 +1,70 @@
 //! CLI command modules.
 
 pub mod add;
+#[cfg(feature = "ai")]
+pub mod ask;
+pub mod attach;
+pub mod auth;
+pub mod backup;
+pub mod blame;
+pub mod block;
+#[cfg(feature = "integrations")]
+pub mod capture;
 pub mod clean;
+#[cfg(feature = "clip")]
+pub mod clip;
+pub mod compact;
+pub mod config;
+pub mod context;
+pub mod daemon;
+pub mod day;
+pub mod dedupe;
+pub mod diff;
+pub mod examples;
+pub mod export;
+pub mod fmt;
+pub mod fsck;
+pub mod import;
+pub mod index;
+#[cfg(feature = "mail")]
+pub mod ingest;
 pub mod init;
+pub mod links;
+pub mod lock;
+pub mod log;
+pub mod meeting;
+pub mod mentions;
+pub mod merge;
+pub mod migrate;
 pub mod open;
+pub mod pages;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod project;
+pub mod publish;
+pub mod random;
+pub mod recent;
+pub mod recover;
+pub mod remind;
+pub mod rename;
+pub mod restore;
+pub mod review;
+pub mod rollover;
+pub mod rpc;
+pub mod schema;
+pub mod search;
+pub mod setup;
+pub mod shell_init;
+pub mod show;
+#[cfg(feature = "semantic")]
+pub mod similar;
+pub mod split;
+pub mod stats;
+pub mod status;
+#[cfg(feature = "ai")]
+pub mod summarize;
+pub mod sync;
+pub mod tag_version;
+pub mod tags;
+pub mod template;
+pub mod wc;
@@ -1,21 +1,27 @@
-//! Open an existing Flow graph.
+//! Open an existing Flow graph, or create it with `--create`.
 
 use clap::Args;
+use console::Term;
 use flow_core::config::Config;
-use flow_core::graph::Graph;
+use flow_core::space::Space;
 use inquire::Select;
 use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::path::PathBuf;
 
-use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::common::{fuzzy_scorer, path_to_display_string, registration_name, Command, GlobalArgs};
 use crate::error::CliError;
 
 /// Output structure for the open command.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct OpenOutput {
     pub name: String,
     pub path: String,
+    /// Whether the graph was initialized by opening it.
+    pub created: bool,
+    #[serde(skip)]
+    pub print_path: bool,
 }
 
 /// Arguments for the open command.
@@ -30,6 +36,22 @@ pub struct OpenArgs {
     /// Make this the default graph
     #[arg(long)]
     pub set_default: bool,
+
+    /// Register the graph under a different name if its name is taken
+    #[arg(long)]
+    pub rename_to: Option<String>,
+
+    /// Print only the graph path, e.g. for `cd "$(flow open --print-path work)"`
+    #[arg(long)]
+    pub print_path: bool,
+
+    /// Initialize the graph if the path holds none (default: `open_creates` in the config)
+    #[arg(long)]
+    pub create: bool,
+
+    /// Keep the active graph and print only the path, for `export FLOW_GRAPH="$(flow open --session work)"`
+    #[arg(long)]
+    pub session: bool,
 }
 
 /// Open command implementation.
@@ -41,7 +63,11 @@ impl Command for OpenCommand {
     type Args = OpenArgs;
     type Output = OpenOutput;
 
-    fn from_args(args: Self::Args) -> Self {
+    fn from_args(mut args: Self::Args) -> Self {
+        // The path is the only output, so it can be captured by the shell
+        if args.print_path || args.session {
+            args.global.quiet = true;
+        }
         Self { args }
     }
 
@@ -55,7 +81,7 @@ impl Command for OpenCommand {
             self.args.global.info("Entering interactive mode");
 
             let config = Config::load()?;
-            let all_graphs = config.all_graphs();
+            let all_graphs = config.all_spaces();
 
             if all_graphs.is_empty() {
                 return Err(CliError::Other {
@@ -82,6 +108,7 @@ impl Command for OpenCommand {
                 .collect();
 
             let selection = Select::new("Select a graph to open:", options)
+                .with_scorer(&fuzzy_scorer::<String>)
                 .prompt()
                 .map_err(CliError::from)?;
 
@@ -112,6 +139,8 @@ impl Command for OpenCommand {
         let mut config = Config::load()?;
 
         // Try to interpret as a registered graph name or path first
+        let mut registered_name = None;
+        let mut created = false;
         let graph = if let Some(graph_config) = config.get_space_config(&path_or_name) {
             // It's a registered graph (by name or path)
             self.args.global.debug(
@@ -119,30 +148,52 @@ impl Command for OpenCommand {
                 &graph_config.path.display().to_string(),
             );
 
-            let graph = Graph::load(&graph_config.path)?;
-            config.set_active_space(&path_or_name)?;
+            let graph = Space::load(&graph_config.path)?;
+            if !self.args.session {
+                config.set_active_space(&path_or_name)?;
+            }
             graph
         } else {
             // Try to interpret as a path
             let path = PathBuf::from(&path_or_name);
 
+            let create = self.args.create || config.open_creates();
+
             // Check if the path exists
-            if !path.exists() {
+            if !path.exists() && !create {
                 return Err(CliError::graph_not_found(&path_or_name).into());
             }
 
-            self.args
-                .global
-                .step(&format!("Loading graph from path: {}", path.display()));
+            let graph = if create && !Space::exists(&path) {
+                // Refuse to nest a graph inside another one, like `flow init`
+                if let Some(parent) = Space::enclosing(&path) {
+                    return Err(CliError::nested_graph(path, parent).into());
+                }
 
-            // Try to load the graph to validate it
-            let graph = Graph::load(&path).map_err(|_| CliError::invalid_graph(path.clone()))?;
+                self.args
+                    .global
+                    .step(&format!("Initializing graph at {}", path.display()));
+                created = true;
+                Space::init(&path, None, false)?
+            } else {
+                self.args
+                    .global
+                    .step(&format!("Loading graph from path: {}", path.display()));
+
+                // Try to load the graph to validate it
+                Space::load(&path).map_err(|_| CliError::invalid_graph(path.clone()))?
+            };
 
             // Canonicalize path before checking if registered (config stores canonical paths)
             let canonical_check_path = path.canonicalize().into_diagnostic()?;
             let is_registered = config.is_space_registered(&canonical_check_path);
 
-            if is_registered {
+            if is_registered && self.args.session {
+                self.args.global.debug(
+                    "Status",
+                    "Graph already registered, using it for the session",
+                );
+            } else if is_registered {
                 // If already registered, set it as active
                 self.args
                     .global
@@ -150,10 +201,19 @@ impl Command for OpenCommand {
                 config.set_active_space(&canonical_check_path.to_string_lossy())?;
             } else {
                 // If not registered, add it to the config
+                let name = registration_name(
+                    &self.args.global,
+                    &config,
+                    graph.name(),
+                    &canonical_check_path,
+                    self.args.rename_to.as_ref(),
+                )?;
+
                 self.args
                     .global
                     .step("Registering new graph in configuration");
-                config.add_graph(&graph)?;
+                config.register_space_as(&graph, &name)?;
+                registered_name = Some(name);
             }
 
             graph
@@ -163,13 +223,24 @@ impl Command for OpenCommand {
         let display_path = path_to_display_string(&canonical_path);
 
         Ok(OpenOutput {
-            name: graph.name().to_string(),
+            name: registered_name.unwrap_or_else(|| graph.name().to_string()),
             path: display_path,
+            created,
+            print_path: self.args.print_path || self.args.session,
         })
     }
 
     fn format_output(output: &Self::Output, global: &GlobalArgs) {
-        global.success("Graph opened successfully");
+        if output.print_path {
+            // Written despite `--quiet`, which only silenced the progress messages
+            let _ = Term::stdout().write_line(&output.path);
+            return;
+        }
+        if output.created {
+            global.success("Graph created and opened successfully");
+        } else {
+            global.success("Graph opened successfully");
+        }
         global.blank();
         global.kv("Name", &output.name);
         global.kv("Path", &output.path);
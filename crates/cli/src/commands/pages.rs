@@ -0,0 +1,235 @@
+//! List the pages of a Flow graph.
+
+use chrono::DateTime;
+use clap::{Args, ValueEnum};
+use flow_core::index::{PageEntry, PageIndex};
+use flow_core::page::NAMESPACE_SEPARATOR;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::common::{Command, GlobalArgs};
+use crate::daemon::Client;
+use crate::error::CliError;
+
+/// Output formats of the pages command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PagesFormat {
+    /// Human readable list
+    #[default]
+    List,
+    /// One page per line, tab separated, for editor plugins and fzf
+    Completion,
+}
+
+/// Sort orders of the pages command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PagesSort {
+    /// Alphabetically by name
+    #[default]
+    Name,
+    /// Most recently modified first
+    Modified,
+    /// Most recently created first
+    Created,
+}
+
+/// Output structure for a single page.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PageSummary {
+    name: String,
+    id: String,
+    aliases: Vec<String>,
+    created: i64,
+    modified: i64,
+}
+
+/// Output structure for the pages command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PagesOutput {
+    pages: Vec<PageSummary>,
+    #[serde(skip)]
+    format: PagesFormat,
+    #[serde(skip)]
+    aliases: bool,
+    #[serde(skip)]
+    modified: bool,
+    #[serde(skip)]
+    tree: bool,
+}
+
+/// Arguments for the pages command.
+#[derive(Args)]
+pub struct PagesArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: PagesFormat,
+
+    /// Include page aliases
+    #[arg(long)]
+    pub aliases: bool,
+
+    /// Include last-modified timestamps
+    #[arg(long)]
+    pub modified: bool,
+
+    /// Sort order
+    #[arg(long, value_enum, default_value_t)]
+    pub sort: PagesSort,
+
+    /// Show pages nested below their namespaces
+    #[arg(long)]
+    pub tree: bool,
+}
+
+/// Pages command implementation.
+pub struct PagesCommand {
+    args: PagesArgs,
+}
+
+impl Command for PagesCommand {
+    type Args = PagesArgs;
+    type Output = PagesOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let path = self.args.global.graph_path()?;
+
+        // The daemon keeps the index up to date with external edits
+        let forwarded = match Client::connect() {
+            Some(mut daemon) => daemon.forward(Some(&path), "pages", json!({}))?,
+            None => None,
+        };
+        let entries: Vec<PageEntry> = match forwarded {
+            Some(result) => serde_json::from_value(result).map_err(CliError::from)?,
+            // Read the index only, loading the document is too slow for completion
+            None => PageIndex::load(&path)?.pages().cloned().collect(),
+        };
+
+        let mut pages: Vec<PageSummary> = entries
+            .iter()
+            .map(|entry| PageSummary {
+                name: entry.name.clone(),
+                id: entry.id.clone(),
+                aliases: entry.aliases.clone(),
+                created: entry.created,
+                modified: entry.modified,
+            })
+            .collect();
+        match self.args.sort {
+            PagesSort::Name => pages.sort_by_key(|page| page.name.to_lowercase()),
+            PagesSort::Modified => pages.sort_by_key(|page| std::cmp::Reverse(page.modified)),
+            PagesSort::Created => pages.sort_by_key(|page| std::cmp::Reverse(page.created)),
+        }
+
+        Ok(PagesOutput {
+            pages,
+            format: self.args.format,
+            aliases: self.args.aliases,
+            modified: self.args.modified,
+            tree: self.args.tree,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        match output.format {
+            PagesFormat::Completion => {
+                for page in &output.pages {
+                    let mut line = page.name.clone();
+                    if output.aliases {
+                        line.push('\t');
+                        line.push_str(&page.aliases.join(","));
+                    }
+                    if output.modified {
+                        line.push('\t');
+                        line.push_str(&page.modified.to_string());
+                    }
+                    global.print(&line);
+                }
+            }
+            PagesFormat::List => {
+                global.heading("Pages");
+                global.blank();
+
+                if output.tree {
+                    print_tree(&output.pages, global);
+                } else {
+                    print_list(output, global);
+                }
+
+                global.blank();
+                global.info(&format!(
+                    "{} page{}",
+                    output.pages.len(),
+                    if output.pages.len() == 1 { "" } else { "s" }
+                ));
+            }
+        }
+    }
+}
+
+/// Prints pages one per line with the requested details.
+fn print_list(output: &PagesOutput, global: &GlobalArgs) {
+    for page in &output.pages {
+        let mut details = Vec::new();
+        if output.aliases && !page.aliases.is_empty() {
+            details.push(format!("aka {}", page.aliases.join(", ")));
+        }
+        if output.modified {
+            if let Some(time) = DateTime::from_timestamp(page.modified, 0) {
+                details.push(time.format("%Y-%m-%d %H:%M").to_string());
+            }
+        }
+
+        if details.is_empty() {
+            global.print(&format!("  {}", page.name));
+        } else {
+            global.kv(&page.name, &details.join(" · "));
+        }
+    }
+}
+
+/// Prints pages nested below their namespaces, namespaces without a page of
+/// their own marked by a trailing `/`.
+fn print_tree(pages: &[PageSummary], global: &GlobalArgs) {
+    let mut sorted: Vec<&PageSummary> = pages.iter().collect();
+    sorted.sort_by_cached_key(|page| {
+        page.name
+            .to_lowercase()
+            .split(NAMESPACE_SEPARATOR)
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    });
+
+    let mut printed: Vec<String> = Vec::new();
+    for page in sorted {
+        let segments: Vec<&str> = page.name.split(NAMESPACE_SEPARATOR).collect();
+        for (depth, segment) in segments.iter().enumerate() {
+            let path = segments[..=depth].join("/").to_lowercase();
+            if printed.get(depth) == Some(&path) {
+                continue;
+            }
+            printed.truncate(depth);
+            printed.push(path);
+
+            let namespace = if depth + 1 < segments.len() { "/" } else { "" };
+            global.print(&format!(
+                "{}{}{}",
+                "  ".repeat(depth + 1),
+                segment,
+                namespace
+            ));
+        }
+    }
+}
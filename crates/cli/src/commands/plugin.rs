@@ -0,0 +1,201 @@
+//! List and run WASM plugins of a Flow graph.
+
+use clap::{Args, Subcommand};
+use flow_core::config::Config;
+use flow_core::index::PageIndex;
+use flow_core::plugin::{Capability, PluginInfo, PluginOutput};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the plugin command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PluginCommandOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugins: Option<Vec<PluginInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run: Option<PluginOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granted: Option<PluginGrant>,
+}
+
+/// Capabilities granted to a plugin.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PluginGrant {
+    pub plugin: String,
+    pub capabilities: Vec<Capability>,
+}
+
+/// Plugin actions.
+#[derive(Subcommand)]
+pub enum PluginAction {
+    /// List installed plugins with their commands and renderers
+    List,
+    /// Run a command registered by a plugin
+    Run {
+        /// Name of the command
+        command: String,
+
+        /// Arguments passed to the command
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Render a page with a renderer registered by a plugin
+    Render {
+        /// Name of the renderer
+        renderer: String,
+
+        /// Page name or alias
+        page: String,
+    },
+    /// Grant capabilities to a plugin, replacing earlier grants
+    Grant {
+        /// Module name of the plugin (file name without .wasm)
+        plugin: String,
+
+        /// Capabilities to grant (read, write), none revokes all
+        capabilities: Vec<Capability>,
+    },
+}
+
+/// Arguments for the plugin command.
+#[derive(Args)]
+pub struct PluginArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: PluginAction,
+}
+
+/// Plugin command implementation.
+pub struct PluginCommand {
+    args: PluginArgs,
+}
+
+impl Command for PluginCommand {
+    type Args = PluginArgs;
+    type Output = PluginCommandOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        let mut output = PluginCommandOutput {
+            plugins: None,
+            run: None,
+            rendered: None,
+            granted: None,
+        };
+
+        match self.args.action {
+            PluginAction::List => output.plugins = Some(space.plugins()?),
+            PluginAction::Run { command, args } => {
+                self.args
+                    .global
+                    .step(&format!("Running plugin command {}", command));
+                output.run = Some(space.run_plugin_command(&command, &args)?);
+            }
+            PluginAction::Render { renderer, page } => {
+                let id = PageIndex::load(space.path())?.resolve(&page);
+                let content = space
+                    .read_page(&id)?
+                    .ok_or_else(|| CliError::page_not_found(&page))?;
+                output.rendered = Some(space.render_with_plugin(&renderer, &content)?);
+            }
+            PluginAction::Grant {
+                plugin,
+                capabilities,
+            } => {
+                let names = capabilities
+                    .iter()
+                    .map(|capability| capability.name().to_string())
+                    .collect();
+                Config::load()?.grant_plugin(space.path(), &plugin, names)?;
+                output.granted = Some(PluginGrant {
+                    plugin,
+                    capabilities,
+                });
+            }
+        }
+        Ok(output)
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if let Some(plugins) = &output.plugins {
+            if plugins.is_empty() {
+                global.info("No plugins installed in .flow/plugins");
+            }
+            for plugin in plugins {
+                global.heading(&plugin.manifest.name);
+                if !plugin.manifest.description.is_empty() {
+                    global.print(&format!("  {}", plugin.manifest.description));
+                }
+                let capabilities: Vec<String> = plugin
+                    .manifest
+                    .capabilities
+                    .iter()
+                    .map(|capability| {
+                        if plugin.granted.contains(capability) {
+                            capability.name().to_string()
+                        } else {
+                            format!("{} (not granted)", capability.name())
+                        }
+                    })
+                    .collect();
+                global.kv("Capabilities", &capabilities.join(", "));
+                global.kv("Commands", &plugin.commands.join(", "));
+                global.kv("Renderers", &plugin.renderers.join(", "));
+                global.blank();
+            }
+        }
+
+        if let Some(run) = &output.run {
+            for line in &run.log {
+                global.print_verbose(line);
+            }
+            if !run.output.is_empty() {
+                global.print(run.output.trim_end());
+            }
+            if !run.appended.is_empty() {
+                global.success(&format!(
+                    "Appended to {} page{}",
+                    run.appended.len(),
+                    if run.appended.len() == 1 { "" } else { "s" }
+                ));
+            }
+        }
+
+        if let Some(rendered) = &output.rendered {
+            global.print(rendered.trim_end());
+        }
+
+        if let Some(granted) = &output.granted {
+            if granted.capabilities.is_empty() {
+                global.success(&format!("Revoked all capabilities of {}", granted.plugin));
+            } else {
+                let names: Vec<&str> = granted
+                    .capabilities
+                    .iter()
+                    .map(|capability| capability.name())
+                    .collect();
+                global.success(&format!(
+                    "Granted {} to {}",
+                    names.join(", "),
+                    granted.plugin
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Per-project scratchpads named after the current git repository.
+
+use clap::{Args, Subcommand};
+use flow_core::page;
+use flow_core::project;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the project command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProjectOutput {
+    pub action: String,
+    pub project: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<String>,
+}
+
+/// Project actions.
+#[derive(Subcommand)]
+pub enum ProjectAction {
+    /// Add a note to the project's page
+    Note {
+        /// Content of the note
+        text: String,
+
+        /// Add even if the project's page is locked
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the project's page
+    Show,
+    /// List the open tasks of the project's page
+    Tasks,
+}
+
+/// Arguments for the project command.
+#[derive(Args)]
+pub struct ProjectArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Project name (defaults to the git repository of the current directory)
+    #[arg(long, global = true)]
+    pub project: Option<String>,
+
+    #[command(subcommand)]
+    pub action: ProjectAction,
+}
+
+/// Project command implementation.
+pub struct ProjectCommand {
+    args: ProjectArgs,
+}
+
+impl Command for ProjectCommand {
+    type Args = ProjectArgs;
+    type Output = ProjectOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let name = match self.args.project {
+            Some(name) => name,
+            None => {
+                let cwd = std::env::current_dir().into_diagnostic()?;
+                project::detect(&cwd).ok_or_else(|| CliError::no_project(cwd))?
+            }
+        };
+
+        let mut space = self.args.global.load_graph()?;
+
+        let mut output = ProjectOutput {
+            action: String::new(),
+            project: name.clone(),
+            id: space.page_index()?.resolve(&name),
+            note: None,
+            content: None,
+            tasks: Vec::new(),
+        };
+        match self.args.action {
+            ProjectAction::Note { text, force } => {
+                output.action = "note".to_string();
+                space.set_allow_locked(force);
+                output.id = space.add_project_note(&name, &text)?;
+                output.note = Some(text);
+            }
+            ProjectAction::Show => {
+                output.action = "show".to_string();
+                output.content = space.read_page(&output.id)?;
+            }
+            ProjectAction::Tasks => {
+                output.action = "tasks".to_string();
+                let content = space.read_page(&output.id)?.unwrap_or_default();
+                output.tasks = project::open_tasks(&content);
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        match output.action.as_str() {
+            "note" => global.success(&format!("Added to [[{}]]", output.project)),
+            "show" => match &output.content {
+                Some(content) => global.print(content.trim()),
+                None => global.info(&format!("No notes for '{}' yet", output.project)),
+            },
+            _ if output.tasks.is_empty() => {
+                global.info(&format!("No open tasks in '{}'", output.project))
+            }
+            _ => {
+                for task in &output.tasks {
+                    global.print(&format!("- {}", task));
+                }
+                global.blank();
+                global.heading(&format!(
+                    "{} open task{} in {}",
+                    output.tasks.len(),
+                    if output.tasks.len() == 1 { "" } else { "s" },
+                    page::name_from_id(&output.id)
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Publish a Flow graph as a static site.
+
+use clap::Args;
+use flow_core::feed::FEED_FILE;
+use flow_core::publish::PublishReport;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::error::CliError;
+
+/// A configured publish target.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TargetEntry {
+    pub name: String,
+    pub destination: String,
+}
+
+/// Output structure for the publish command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PublishOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<PublishReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<TargetEntry>,
+}
+
+/// Arguments for the publish command.
+#[derive(Args)]
+pub struct PublishArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Target to publish to, as configured in space.toml (defaults to the only target)
+    pub target: Option<String>,
+
+    /// Render every page again instead of only the modified ones
+    #[arg(long)]
+    pub full: bool,
+
+    /// List the configured targets
+    #[arg(long, conflicts_with_all = ["target", "full"])]
+    pub list: bool,
+}
+
+/// Publish command implementation.
+pub struct PublishCommand {
+    args: PublishArgs,
+}
+
+impl Command for PublishCommand {
+    type Args = PublishArgs;
+    type Output = PublishOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        let space = global.load_graph()?;
+        let targets = &space.publish_settings().targets;
+
+        if self.args.list {
+            return Ok(PublishOutput {
+                report: None,
+                targets: targets
+                    .iter()
+                    .map(|(name, target)| TargetEntry {
+                        name: name.clone(),
+                        destination: target.describe(),
+                    })
+                    .collect(),
+            });
+        }
+
+        let name = match &self.args.target {
+            Some(name) => name.clone(),
+            None if targets.len() == 1 => targets.keys().next().cloned().unwrap_or_default(),
+            None if targets.is_empty() => {
+                return Err(CliError::Other {
+                    message: "No publish targets configured, add one under [publish.targets] in space.toml".to_string(),
+                }
+                .into());
+            }
+            None => return Err(CliError::missing_argument("target").into()),
+        };
+
+        global.step(&format!("Publishing {} to {}", space.name(), name));
+        let progress = global.progress();
+        Ok(PublishOutput {
+            report: Some(space.publish(&name, self.args.full, progress.as_ref())?),
+            targets: Vec::new(),
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let Some(report) = &output.report else {
+            if output.targets.is_empty() {
+                global.info("No publish targets configured");
+            }
+            for target in &output.targets {
+                global.kv(&target.name, &target.destination);
+            }
+            return;
+        };
+
+        if report.deployed {
+            global.success(&format!("Published to {}", report.target));
+        } else {
+            global.success(&format!("{} is up to date", report.target));
+        }
+        global.blank();
+        global.kv("Pages", &report.published.len().to_string());
+        global.kv("Rendered", &report.rendered.len().to_string());
+        if !report.removed.is_empty() {
+            global.kv("Removed", &report.removed.len().to_string());
+        }
+        if !report.excluded.is_empty() {
+            global.kv("Private", &report.excluded.len().to_string());
+        }
+        if report.feed {
+            global.kv(
+                "Feed",
+                &path_to_display_string(&report.site.join(FEED_FILE)),
+            );
+        }
+        global.kv("Site", &path_to_display_string(&report.site));
+        for id in &report.rendered {
+            global.print_verbose(id);
+        }
+    }
+}
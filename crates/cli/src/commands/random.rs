@@ -0,0 +1,94 @@
+//! Resurface old pages to revisit.
+
+use chrono::{DateTime, Local};
+use clap::Args;
+use flow_core::resurface::{self, ResurfaceOptions, Resurfaced};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the random command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RandomOutput {
+    pub pages: Vec<Resurfaced>,
+}
+
+/// Arguments for the random command.
+#[derive(Args)]
+pub struct RandomArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Only pages with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Only pages not modified for this long (e.g. 90d, 2w, 6m, 1y)
+    #[arg(long, value_parser = parse_older_than)]
+    pub older_than: Option<i64>,
+
+    /// Number of pages to surface
+    #[arg(short = 'n', long, default_value_t = 1)]
+    pub count: usize,
+
+    /// Surface the least recently seen pages instead of random ones
+    #[arg(long)]
+    pub least_seen: bool,
+}
+
+/// Random command implementation.
+pub struct RandomCommand {
+    args: RandomArgs,
+}
+
+impl Command for RandomCommand {
+    type Args = RandomArgs;
+    type Output = RandomOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+
+        let options = ResurfaceOptions {
+            tag: self.args.tag,
+            older_than: self.args.older_than,
+            count: self.args.count,
+            least_seen: self.args.least_seen,
+        };
+        Ok(RandomOutput {
+            pages: space.resurface(&options)?,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.pages.is_empty() {
+            global.info("No pages match");
+            return;
+        }
+
+        for page in &output.pages {
+            let time = DateTime::from_timestamp(page.modified, 0)
+                .map(|time| time.with_timezone(&Local).format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            global.kv(&page.page, &format!("last modified {}", time));
+            if !page.preview.is_empty() {
+                global.print(&format!("    {}", page.preview));
+            }
+        }
+    }
+}
+
+/// Parses the `--older-than` age into days.
+fn parse_older_than(value: &str) -> std::result::Result<i64, String> {
+    resurface::parse_age(value)
+        .ok_or_else(|| format!("invalid age '{}', expected e.g. 90d, 2w, 6m or 1y", value))
+}
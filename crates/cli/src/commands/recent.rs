@@ -0,0 +1,74 @@
+//! List recently modified pages.
+
+use chrono::{DateTime, Local};
+use clap::Args;
+use flow_core::recent::RecentPage;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the recent command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RecentOutput {
+    pub pages: Vec<RecentPage>,
+}
+
+/// Arguments for the recent command.
+#[derive(Args)]
+pub struct RecentArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Number of pages to list (0 for all)
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub limit: usize,
+}
+
+/// Recent command implementation.
+pub struct RecentCommand {
+    args: RecentArgs,
+}
+
+impl Command for RecentCommand {
+    type Args = RecentArgs;
+    type Output = RecentOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+
+        Ok(RecentOutput {
+            pages: space.recent(self.args.limit)?,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.pages.is_empty() {
+            global.info("No pages yet");
+            return;
+        }
+
+        for page in &output.pages {
+            let time = DateTime::from_timestamp(page.modified, 0)
+                .map(|time| {
+                    time.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            global.kv(&page.page, &time);
+            if !page.preview.is_empty() {
+                global.print(&format!("    {}", page.preview));
+            }
+        }
+    }
+}
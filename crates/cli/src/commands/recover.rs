@@ -0,0 +1,242 @@
+//! Recover a Flow graph whose document no longer loads.
+
+use clap::Args;
+use flow_core::page;
+use flow_core::recovery::{self, Method, Plan, RecoveryReport};
+use flow_core::space::Space;
+use inquire::{Confirm, Select};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+use crate::error::CliError;
+use crate::interrupt;
+
+/// Output structure for the recover command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RecoverOutput {
+    path: String,
+    #[serde(flatten)]
+    plan: Plan,
+    report: Option<RecoveryReport>,
+}
+
+/// Arguments for the recover command.
+#[derive(Args)]
+pub struct RecoverArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Restore the backup with this id (see `flow backup list`)
+    #[arg(long, value_name = "ID", conflicts_with = "rebuild")]
+    pub backup: Option<String>,
+
+    /// Rebuild the document from the markdown files (loses history)
+    #[arg(long)]
+    pub rebuild: bool,
+
+    /// Recover without asking for confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+/// Recover command implementation.
+pub struct RecoverCommand {
+    args: RecoverArgs,
+}
+
+impl RecoverCommand {
+    /// Returns the recovery method chosen by the arguments.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Method>` - The method, `None` if neither `--backup` nor `--rebuild` was given
+    fn method(&self) -> Option<Method> {
+        match (&self.args.backup, self.args.rebuild) {
+            (Some(id), _) => Some(Method::Backup(id.clone())),
+            (None, true) => Some(Method::Rebuild),
+            (None, false) => None,
+        }
+    }
+}
+
+impl Command for RecoverCommand {
+    type Args = RecoverArgs;
+    type Output = RecoverOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn interactive(&mut self) -> Result<()> {
+        let path = self.args.global.graph_path()?;
+        if !Space::exists(&path) {
+            return Ok(());
+        }
+        let plan = recovery::plan(&path)?;
+        if plan.problem.is_none() && self.method().is_none() {
+            return Ok(());
+        }
+        print_plan(&plan, &self.args.global);
+
+        if self.method().is_none() {
+            let usable: Vec<_> = plan
+                .backups
+                .iter()
+                .filter(|candidate| candidate.error.is_none())
+                .collect();
+            let mut options: Vec<String> = usable
+                .iter()
+                .map(|candidate| {
+                    format!(
+                        "Restore backup {} ({} pages, {} taken over from disk)",
+                        candidate.backup.id, candidate.pages, candidate.changed
+                    )
+                })
+                .collect();
+            options.push(format!(
+                "Rebuild from {} markdown files (loses history)",
+                plan.files
+            ));
+
+            let choice = Select::new("How do you want to recover?", options.clone()).prompt()?;
+            match usable.get(
+                options
+                    .iter()
+                    .position(|option| *option == choice)
+                    .unwrap_or(0),
+            ) {
+                Some(candidate) => self.args.backup = Some(candidate.backup.id.clone()),
+                None => self.args.rebuild = true,
+            }
+        }
+
+        if !self.args.yes {
+            self.args.yes = Confirm::new("Replace the document of the graph?")
+                .with_help_message("The current document is kept in .flow/corrupt/")
+                .with_default(false)
+                .prompt()?;
+            if !self.args.yes {
+                miette::bail!("Recovery cancelled, the graph was left unchanged");
+            }
+        }
+        Ok(())
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let path = self.args.global.graph_path()?;
+        if !Space::exists(&path) {
+            return Err(CliError::invalid_graph(path).into());
+        }
+
+        let plan = recovery::plan(&path)?;
+        let method = match self.method() {
+            Some(method) => method,
+            None if plan.problem.is_none() => {
+                return Ok(RecoverOutput {
+                    path: path_to_display_string(&path),
+                    plan,
+                    report: None,
+                })
+            }
+            None => return Err(CliError::missing_argument("--backup <ID> or --rebuild").into()),
+        };
+        if !self.args.yes {
+            return Err(CliError::missing_argument("--yes").into());
+        }
+
+        match &method {
+            Method::Backup(id) => self.args.global.step(&format!("Restoring backup {}", id)),
+            Method::Rebuild => self
+                .args
+                .global
+                .step("Rebuilding document from markdown files"),
+        }
+        let report = recovery::recover(
+            &path,
+            &method,
+            self.args.global.progress().as_ref(),
+            &interrupt::token(),
+        )?;
+
+        Ok(RecoverOutput {
+            path: path_to_display_string(&path),
+            plan,
+            report: Some(report),
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let Some(report) = &output.report else {
+            global.success("The document loads fine, nothing to recover");
+            return;
+        };
+
+        for file in &report.preserved {
+            global.print_verbose(&format!("Kept {}", path_to_display_string(file)));
+        }
+        for id in &report.updated {
+            global.step(&format!("Took over {} from disk", page::name_from_id(id)));
+        }
+        match &report.backup {
+            Some(id) => global.success(&format!(
+                "Restored backup {} with {} page{}, {} taken over from disk",
+                id,
+                report.pages,
+                if report.pages == 1 { "" } else { "s" },
+                report.updated.len()
+            )),
+            None => global.success(&format!(
+                "Rebuilt document from {} markdown file{}",
+                report.pages,
+                if report.pages == 1 { "" } else { "s" }
+            )),
+        }
+        if let Some(file) = report.preserved.first() {
+            global.info(&format!(
+                "The previous document was kept in {}",
+                path_to_display_string(file.parent().unwrap_or(file))
+            ));
+        }
+    }
+}
+
+/// Prints what is wrong with a graph and what it can be recovered from.
+///
+/// # Arguments
+///
+/// * `plan` - The recovery plan
+/// * `global` - Global arguments for output
+fn print_plan(plan: &Plan, global: &GlobalArgs) {
+    global.heading("Recovery");
+    global.blank();
+    if let Some(problem) = &plan.problem {
+        global.warning(problem);
+        global.blank();
+    }
+    for candidate in &plan.backups {
+        match &candidate.error {
+            None => global.kv(
+                &format!("Backup {}", candidate.backup.id),
+                &format!(
+                    "{} pages, {} changed on disk since",
+                    candidate.pages, candidate.changed
+                ),
+            ),
+            Some(error) => global.kv(
+                &format!("Backup {}", candidate.backup.id),
+                &format!("unusable: {}", error),
+            ),
+        }
+    }
+    if plan.backups.is_empty() {
+        global.kv("Backups", "none");
+    }
+    global.kv("Markdown files", &plan.files.to_string());
+    global.blank();
+}
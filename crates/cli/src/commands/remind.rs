@@ -0,0 +1,154 @@
+//! List reminders of scheduled and due tasks, optionally notifying about them.
+
+use schemars::JsonSchema;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use clap::Args;
+use flow_core::config::Config;
+use flow_core::paths;
+use flow_core::reminders::Reminder;
+use flow_core::resurface;
+use miette::Result;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::interrupt;
+use crate::notify;
+
+/// Seconds in a day.
+const DAY: i64 = 24 * 60 * 60;
+
+/// Output structure for the remind command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RemindOutput {
+    pub reminders: Vec<Reminder>,
+    pub notified: usize,
+}
+
+/// Arguments for the remind command.
+#[derive(Args)]
+pub struct RemindArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// List reminders up to this far ahead (e.g. 1d, 2w)
+    #[arg(long, value_parser = parse_within, default_value = "1d")]
+    pub within: i64,
+
+    /// Include reminders that are already overdue
+    #[arg(long)]
+    pub overdue: bool,
+
+    /// Keep running and notify when tasks become scheduled or due
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// Remind command implementation.
+pub struct RemindCommand {
+    args: RemindArgs,
+}
+
+impl Command for RemindCommand {
+    type Args = RemindArgs;
+    type Output = RemindOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        let now = Local::now().timestamp();
+        let from = if self.args.overdue { i64::MIN } else { now };
+        let reminders = space.reminders(from, now + self.args.within * DAY)?;
+
+        if !self.args.watch {
+            return Ok(RemindOutput {
+                reminders,
+                notified: 0,
+            });
+        }
+
+        if !notify::supported() {
+            self.args
+                .global
+                .warning("Built without the notify feature, reminders are only printed");
+        }
+        let config = Config::load()?;
+        let path = paths::normalize(space.path());
+        let settings = config
+            .all_spaces()
+            .into_iter()
+            .find(|(_, space)| paths::same(&space.path, &path))
+            .map(|(_, space)| space.notifications)
+            .unwrap_or_default();
+
+        self.args
+            .global
+            .info("Watching for reminders, press Ctrl-C to stop");
+        let cancel = interrupt::token();
+        let mut notified = 0;
+        let mut last = now;
+        while !cancel.is_cancelled() {
+            // Sleep in steps so Ctrl-C stops watching promptly
+            for _ in 0..notify::CHECK_INTERVAL {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                thread::sleep(Duration::from_secs(1));
+            }
+            let now = Local::now().timestamp();
+            // Pick up pages edited in the meantime
+            space = self.args.global.load_graph()?;
+            if notify::supported() {
+                notified += notify::fire(&space, &settings, last, now)?.len();
+            } else {
+                for reminder in space.reminders(last, now)? {
+                    let summary = notify::summary(space.name(), &reminder);
+                    self.args.global.kv(&summary, &reminder.task);
+                }
+            }
+            last = now;
+        }
+
+        Ok(RemindOutput {
+            reminders,
+            notified,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.notified > 0 {
+            global.success(&format!("Sent {} notifications", output.notified));
+            return;
+        }
+        if output.reminders.is_empty() {
+            global.info("No reminders");
+            return;
+        }
+
+        for reminder in &output.reminders {
+            let time = DateTime::from_timestamp(reminder.at, 0)
+                .map(|time| {
+                    time.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            global.kv(&time, &format!("{} ({})", reminder.task, reminder.page));
+        }
+    }
+}
+
+/// Parses the `--within` duration into days.
+fn parse_within(value: &str) -> std::result::Result<i64, String> {
+    resurface::parse_age(value)
+        .ok_or_else(|| format!("invalid duration '{}', expected e.g. 1d or 2w", value))
+}
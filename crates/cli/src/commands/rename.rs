@@ -0,0 +1,74 @@
+//! Rename a page or namespace, moving the pages below it along.
+
+use clap::Args;
+use flow_core::namespace::RenamedPages;
+use flow_core::page;
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Arguments for the rename command.
+#[derive(Args)]
+pub struct RenameArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Name of the page or namespace, e.g. `projects/flow`
+    pub from: String,
+
+    /// New name, e.g. `archive/flow`
+    pub to: String,
+
+    /// Rewrite links on locked pages too
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Rename command implementation.
+pub struct RenameCommand {
+    args: RenameArgs,
+}
+
+impl Command for RenameCommand {
+    type Args = RenameArgs;
+    type Output = RenamedPages;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        space.set_allow_locked(self.args.force);
+        space.rename_namespace(&self.args.from, &self.args.to)
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        for renamed in &output.renamed {
+            global.kv(
+                &format!("[[{}]]", page::name_from_id(&renamed.from)),
+                &format!("[[{}]]", page::name_from_id(&renamed.to)),
+            );
+        }
+        for id in &output.locked {
+            global.warning(&format!(
+                "[[{}]] is locked and still links to old names, use --force to rewrite it",
+                page::name_from_id(id)
+            ));
+        }
+        global.blank();
+        global.success(&format!(
+            "Renamed {} page{}, rewriting {} link{} on {} other page{}",
+            output.renamed.len(),
+            if output.renamed.len() == 1 { "" } else { "s" },
+            output.links,
+            if output.links == 1 { "" } else { "s" },
+            output.relinked.len(),
+            if output.relinked.len() == 1 { "" } else { "s" }
+        ));
+    }
+}
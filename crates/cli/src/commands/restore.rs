@@ -0,0 +1,92 @@
+//! Restore the pages of a Flow graph to a named checkpoint.
+
+use clap::Args;
+use flow_core::checkpoints::RestoreReport;
+use flow_core::page;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the restore command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RestoreOutput {
+    pub version: String,
+    #[serde(flatten)]
+    pub report: RestoreReport,
+}
+
+/// Arguments for the restore command.
+#[derive(Args)]
+pub struct RestoreArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Checkpoint (or change listed by `flow log`) to restore
+    #[arg(long, value_name = "NAME")]
+    pub version: String,
+
+    /// Restore locked pages too
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Restore command implementation.
+pub struct RestoreCommand {
+    args: RestoreArgs,
+}
+
+impl Command for RestoreCommand {
+    type Args = RestoreArgs;
+    type Output = RestoreOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        space.set_allow_locked(self.args.force);
+        let report = space.restore_version(&self.args.version)?;
+
+        Ok(RestoreOutput {
+            version: self.args.version,
+            report,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let report = &output.report;
+        for id in &report.restored {
+            global.step(&format!("Restored {}", page::name_from_id(id)));
+        }
+        for id in &report.kept {
+            global.print_verbose(&format!(
+                "Kept {}, created after the checkpoint",
+                page::name_from_id(id)
+            ));
+        }
+        for id in &report.locked {
+            global.warning(&format!(
+                "[[{}]] is locked and was not restored, use --force to restore it",
+                page::name_from_id(id)
+            ));
+        }
+
+        if !report.restored.is_empty() {
+            global.success(&format!(
+                "Restored {} page{} to {}",
+                report.restored.len(),
+                if report.restored.len() == 1 { "" } else { "s" },
+                output.version
+            ));
+        } else if report.locked.is_empty() {
+            global.info(&format!("All pages already match {}", output.version));
+        }
+    }
+}
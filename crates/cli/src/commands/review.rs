@@ -0,0 +1,160 @@
+//! Review flashcards collected from the graph.
+
+use chrono::NaiveDate;
+use clap::{Args, Subcommand};
+use flow_core::cards::Card;
+use flow_core::index::PageIndex;
+use inquire::{Select, Text};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Answers offered after a card is revealed, with their SM-2 grades.
+const GRADES: &[(&str, u8)] = &[("Again", 1), ("Hard", 3), ("Good", 4), ("Easy", 5)];
+
+/// A card reviewed in this session.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReviewedCard {
+    pub key: String,
+    pub page: String,
+    pub question: String,
+    pub grade: u8,
+    pub due: NaiveDate,
+}
+
+/// Output structure for the review command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReviewOutput {
+    pub due: Vec<Card>,
+    pub reviewed: Vec<ReviewedCard>,
+}
+
+/// Review actions.
+#[derive(Subcommand)]
+pub enum ReviewAction {
+    /// Review the due flashcards (lists them with --json)
+    Cards {
+        /// Only review the cards of this page
+        #[arg(long)]
+        page: Option<String>,
+
+        /// Maximum number of cards to review (0 for all)
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+/// Arguments for the review command.
+#[derive(Args)]
+pub struct ReviewArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: ReviewAction,
+}
+
+/// Review command implementation.
+pub struct ReviewCommand {
+    args: ReviewArgs,
+}
+
+impl Command for ReviewCommand {
+    type Args = ReviewArgs;
+    type Output = ReviewOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        let space = global.load_graph()?;
+        let ReviewAction::Cards { page, limit } = &self.args.action;
+
+        let id = match page {
+            Some(name) => {
+                let id = PageIndex::load(space.path())?.resolve(name);
+                if space.read_page(&id)?.is_none() {
+                    return Err(CliError::page_not_found(name).into());
+                }
+                Some(id)
+            }
+            None => None,
+        };
+        let mut due = space.due_cards(id.as_deref(), *limit)?;
+
+        // Scripts and editors get the due cards to review on their own
+        if global.json {
+            return Ok(ReviewOutput {
+                due,
+                reviewed: Vec::new(),
+            });
+        }
+
+        let mut reviewed = Vec::new();
+        let total = due.len();
+        while !due.is_empty() {
+            let card = &due[0];
+            global.blank();
+            global.heading(&format!("{} ({}/{})", card.page, reviewed.len() + 1, total));
+            global.print(&card.question);
+
+            // Cancelling a prompt ends the session, reviewed cards are kept
+            if Text::new("Press Enter to show the answer")
+                .prompt()
+                .is_err()
+            {
+                break;
+            }
+            global.print(&card.answer);
+            let options: Vec<&str> = GRADES.iter().map(|(label, _)| *label).collect();
+            let Ok(answer) = Select::new("How well did you remember?", options).prompt() else {
+                break;
+            };
+            let grade = GRADES
+                .iter()
+                .find(|(label, _)| *label == answer)
+                .map_or(0, |(_, grade)| *grade);
+
+            let card = due.remove(0);
+            let review = space.review_card(&card.key, grade)?;
+            reviewed.push(ReviewedCard {
+                key: card.key,
+                page: card.page,
+                question: card.question,
+                grade,
+                due: review.due,
+            });
+        }
+
+        Ok(ReviewOutput { due, reviewed })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.reviewed.is_empty() && output.due.is_empty() {
+            global.success("No cards due for review");
+            return;
+        }
+        global.blank();
+        global.success(&format!(
+            "Reviewed {} card{}",
+            output.reviewed.len(),
+            if output.reviewed.len() == 1 { "" } else { "s" }
+        ));
+        if !output.due.is_empty() {
+            global.info(&format!(
+                "{} card{} still due",
+                output.due.len(),
+                if output.due.len() == 1 { "" } else { "s" }
+            ));
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Carry open tasks of the previous journal page over to today.
+
+use clap::Args;
+use flow_core::page;
+use flow_core::rollover::Rollover;
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Arguments for the rollover command.
+#[derive(Args)]
+pub struct RolloverArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+}
+
+/// Rollover command implementation.
+pub struct RolloverCommand {
+    args: RolloverArgs,
+}
+
+impl Command for RolloverCommand {
+    type Args = RolloverArgs;
+    type Output = Rollover;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        space.rollover()
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let Some(from) = &output.from else {
+            global.info("No earlier journal page to carry tasks over from");
+            return;
+        };
+        if output.tasks.is_empty() {
+            global.info(&format!(
+                "No open tasks left on [[{}]]",
+                page::name_from_id(from)
+            ));
+            return;
+        }
+
+        for task in &output.tasks {
+            global.print(&format!("  - {}", task));
+        }
+        global.blank();
+        global.success(&format!(
+            "Carried {} task{} over from [[{}]] to [[{}]]",
+            output.tasks.len(),
+            if output.tasks.len() == 1 { "" } else { "s" },
+            page::name_from_id(from),
+            page::name_from_id(&output.to)
+        ));
+    }
+}
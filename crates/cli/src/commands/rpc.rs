@@ -0,0 +1,60 @@
+//! Serve JSON-RPC requests for editor integrations.
+
+use schemars::JsonSchema;
+use std::io;
+
+use clap::Args;
+use miette::Result;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::rpc::Server;
+
+/// Output structure for the rpc command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RpcOutput {
+    pub handled: usize,
+}
+
+/// Arguments for the rpc command.
+#[derive(Args)]
+pub struct RpcArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Serve newline-delimited JSON-RPC over stdin/stdout
+    #[arg(long, required = true)]
+    pub stdio: bool,
+}
+
+/// Rpc command implementation.
+pub struct RpcCommand {
+    args: RpcArgs,
+}
+
+impl Command for RpcCommand {
+    type Args = RpcArgs;
+    type Output = RpcOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph()?;
+        let mut server = Server::new(space);
+
+        let handled = server.serve(io::stdin().lock(), io::stdout().lock())?;
+
+        Ok(RpcOutput { handled })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        // Only printed after the client closed the stream
+        global.print_verbose(&format!("Handled {} requests", output.handled));
+    }
+}
@@ -0,0 +1,182 @@
+//! Print the JSON Schemas of command outputs.
+
+use clap::Args;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::commands;
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the schema command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SchemaOutput {
+    /// The requested command, `None` when all schemas were requested.
+    pub command: Option<String>,
+    /// JSON Schemas of the command outputs by command name.
+    pub schemas: BTreeMap<String, serde_json::Value>,
+}
+
+/// Arguments for the schema command.
+#[derive(Args)]
+pub struct SchemaArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Command to print the output schema of, all commands if omitted
+    pub command: Option<String>,
+}
+
+/// Schema command implementation.
+pub struct SchemaCommand {
+    args: SchemaArgs,
+}
+
+impl Command for SchemaCommand {
+    type Args = SchemaArgs;
+    type Output = SchemaOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut schemas = schemas()?;
+        if let Some(command) = self.args.command.as_deref() {
+            let (name, schema) = schemas
+                .remove_entry(command)
+                .ok_or_else(|| CliError::unknown_schema(command))?;
+            schemas = BTreeMap::from([(name, schema)]);
+        }
+
+        Ok(SchemaOutput {
+            schemas: schemas
+                .into_iter()
+                .map(|(name, schema)| (name.to_string(), schema))
+                .collect(),
+            command: self.args.command,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let value = match &output.command {
+            Some(command) => serde_json::to_string_pretty(&output.schemas[command]),
+            None => serde_json::to_string_pretty(&output.schemas),
+        };
+        if let Ok(json) = value {
+            global.print(&json);
+        }
+    }
+}
+
+/// Generates the JSON Schema of a command's output.
+///
+/// # Returns
+///
+/// * `Result<serde_json::Value>` - The schema of `C::Output`
+///
+/// # Errors
+///
+/// Returns an error if the schema can't be serialized
+fn schema<C: Command>() -> Result<serde_json::Value> {
+    serde_json::to_value(schemars::schema_for!(C::Output)).into_diagnostic()
+}
+
+/// Generates the JSON Schemas of all command outputs.
+///
+/// Keys are the command names as invoked on the command line. Commands
+/// sharing an output type, like `today` and `yesterday`, share a schema.
+///
+/// # Returns
+///
+/// * `Result<BTreeMap<&'static str, serde_json::Value>>` - Schemas by command name
+///
+/// # Errors
+///
+/// Returns an error if a schema can't be serialized
+pub fn schemas() -> Result<BTreeMap<&'static str, serde_json::Value>> {
+    use commands::*;
+
+    let day = schema::<day::DayCommand>()?;
+    let lock = schema::<lock::LockCommand>()?;
+    let mut schemas = BTreeMap::new();
+    schemas.insert("add", schema::<add::AddCommand>()?);
+    schemas.insert("attach", schema::<attach::AttachCommand>()?);
+    schemas.insert("auth", schema::<auth::AuthCommand>()?);
+    schemas.insert("backup", schema::<backup::BackupCommand>()?);
+    schemas.insert("blame", schema::<blame::BlameCommand>()?);
+    schemas.insert("block", schema::<block::BlockCommand>()?);
+    schemas.insert("clean", schema::<clean::CleanCommand>()?);
+    schemas.insert("compact", schema::<compact::CompactCommand>()?);
+    schemas.insert("config", schema::<config::ConfigCommand>()?);
+    schemas.insert("context", schema::<context::ContextCommand>()?);
+    schemas.insert("daemon", schema::<daemon::DaemonCommand>()?);
+    schemas.insert("dedupe", schema::<dedupe::DedupeCommand>()?);
+    schemas.insert("diff", schema::<diff::DiffCommand>()?);
+    schemas.insert("examples", schema::<examples::ExamplesCommand>()?);
+    schemas.insert("export", schema::<export::ExportCommand>()?);
+    schemas.insert("fmt", schema::<fmt::FmtCommand>()?);
+    schemas.insert("fsck", schema::<fsck::FsckCommand>()?);
+    schemas.insert("import", schema::<import::ImportCommand>()?);
+    schemas.insert("index", schema::<index::IndexCommand>()?);
+    schemas.insert("init", schema::<init::InitCommand>()?);
+    schemas.insert("links", schema::<links::LinksCommand>()?);
+    schemas.insert("lock", lock.clone());
+    schemas.insert("log", schema::<log::LogCommand>()?);
+    schemas.insert("meeting", schema::<meeting::MeetingCommand>()?);
+    schemas.insert("mentions", schema::<mentions::MentionsCommand>()?);
+    schemas.insert("merge", schema::<merge::MergeCommand>()?);
+    schemas.insert("migrate", schema::<migrate::MigrateCommand>()?);
+    schemas.insert("open", schema::<open::OpenCommand>()?);
+    schemas.insert("pages", schema::<pages::PagesCommand>()?);
+    schemas.insert("project", schema::<project::ProjectCommand>()?);
+    schemas.insert("publish", schema::<publish::PublishCommand>()?);
+    schemas.insert("random", schema::<random::RandomCommand>()?);
+    schemas.insert("recent", schema::<recent::RecentCommand>()?);
+    schemas.insert("recover", schema::<recover::RecoverCommand>()?);
+    schemas.insert("remind", schema::<remind::RemindCommand>()?);
+    schemas.insert("rename", schema::<rename::RenameCommand>()?);
+    schemas.insert("restore", schema::<restore::RestoreCommand>()?);
+    schemas.insert("review", schema::<review::ReviewCommand>()?);
+    schemas.insert("rollover", schema::<rollover::RolloverCommand>()?);
+    schemas.insert("rpc", schema::<rpc::RpcCommand>()?);
+    schemas.insert("schema", schema::<SchemaCommand>()?);
+    schemas.insert("search", schema::<search::SearchCommand>()?);
+    schemas.insert("setup", schema::<setup::SetupCommand>()?);
+    schemas.insert("shell-init", schema::<shell_init::ShellInitCommand>()?);
+    schemas.insert("show", schema::<show::ShowCommand>()?);
+    schemas.insert("split", schema::<split::SplitCommand>()?);
+    schemas.insert("stats", schema::<stats::StatsCommand>()?);
+    schemas.insert("status", schema::<status::StatusCommand>()?);
+    schemas.insert("sync", schema::<sync::SyncCommand>()?);
+    schemas.insert("tag-version", schema::<tag_version::TagVersionCommand>()?);
+    schemas.insert("tags", schema::<tags::TagsCommand>()?);
+    schemas.insert("template", schema::<template::TemplateCommand>()?);
+    schemas.insert("today", day.clone());
+    schemas.insert("tomorrow", day.clone());
+    schemas.insert("unlock", lock);
+    schemas.insert("wc", schema::<wc::WcCommand>()?);
+    schemas.insert("yesterday", day);
+    #[cfg(feature = "semantic")]
+    schemas.insert("similar", schema::<similar::SimilarCommand>()?);
+    #[cfg(feature = "integrations")]
+    schemas.insert("capture", schema::<capture::CaptureCommand>()?);
+    #[cfg(feature = "mail")]
+    schemas.insert("ingest", schema::<ingest::IngestCommand>()?);
+    #[cfg(feature = "clip")]
+    schemas.insert("clip", schema::<clip::ClipCommand>()?);
+    #[cfg(feature = "ai")]
+    schemas.insert("summarize", schema::<summarize::SummarizeCommand>()?);
+    #[cfg(feature = "ai")]
+    schemas.insert("ask", schema::<ask::AskCommand>()?);
+    #[cfg(feature = "plugins")]
+    schemas.insert("plugin", schema::<plugin::PluginCommand>()?);
+
+    Ok(schemas)
+}
@@ -0,0 +1,140 @@
+//! Search the pages of a Flow graph.
+
+use chrono::NaiveDate;
+use clap::Args;
+use flow_core::search::{RankedHit, SearchScope};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the search command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SearchOutput {
+    pub query: String,
+    pub hits: Vec<RankedHit>,
+}
+
+/// Arguments for the search command.
+#[derive(Args)]
+pub struct SearchArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Search query: words, "exact phrases" and prefixes (e.g. edit*)
+    pub query: String,
+
+    /// Maximum number of results (0 for all)
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Only search journal pages
+    #[arg(long, conflicts_with = "pages_only")]
+    pub journal_only: bool,
+
+    /// Only search non-journal pages
+    #[arg(long)]
+    pub pages_only: bool,
+
+    /// Only search pages with this tag (repeatable, all must match)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Only search pages whose path matches this glob (e.g. "pages/projects/**")
+    #[arg(long, value_name = "GLOB")]
+    pub path: Option<String>,
+
+    /// Only search journal days and pages modified since this date (YYYY-MM-DD)
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+
+    /// Search by meaning instead of by words (ignores scope filters)
+    #[cfg(feature = "semantic")]
+    #[arg(long, conflicts_with_all = ["journal_only", "pages_only", "tags", "path", "since"])]
+    pub semantic: bool,
+}
+
+/// Search command implementation.
+pub struct SearchCommand {
+    args: SearchArgs,
+}
+
+impl Command for SearchCommand {
+    type Args = SearchArgs;
+    type Output = SearchOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+
+        #[cfg(feature = "semantic")]
+        if self.args.semantic {
+            let progress = self.args.global.progress();
+            let hits = space
+                .search_semantic(
+                    &self.args.query,
+                    self.args.limit,
+                    progress.as_ref(),
+                    &crate::interrupt::token(),
+                )?
+                .into_iter()
+                .map(|similar| RankedHit {
+                    id: similar.id,
+                    page: similar.page,
+                    score: similar.score,
+                    line: None,
+                    text: None,
+                })
+                .collect();
+            return Ok(SearchOutput {
+                query: self.args.query.clone(),
+                hits,
+            });
+        }
+
+        let scope = SearchScope {
+            journal_only: self.args.journal_only,
+            pages_only: self.args.pages_only,
+            tags: self.args.tags.clone(),
+            path: self.args.path.clone(),
+            since: self.args.since,
+        };
+
+        let hits = space.search_ranked(&self.args.query, &scope, self.args.limit)?;
+
+        Ok(SearchOutput {
+            query: self.args.query.clone(),
+            hits,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.hits.is_empty() {
+            global.info(&format!("No matches for '{}'", output.query));
+            return;
+        }
+
+        for hit in &output.hits {
+            match (hit.line, &hit.text) {
+                (Some(line), Some(text)) => {
+                    global.kv(&hit.page, &format!("{}: {}", line, text.trim()))
+                }
+                _ => global.print(&format!("  {}", hit.page)),
+            }
+        }
+        global.blank();
+        global.info(&format!(
+            "{} match{}",
+            output.hits.len(),
+            if output.hits.len() == 1 { "" } else { "es" }
+        ));
+    }
+}
@@ -0,0 +1,328 @@
+//! First-run wizard setting up Flow in one go.
+//!
+//! Walks through everything a new user would otherwise find in several
+//! commands: creating a graph or importing an existing one, the journal
+//! granularity and daily template, the editor, the shell integration (see
+//! `flow shell-init`) and optionally git and a sync remote. Every answer can
+//! be given as a flag instead, so the same setup can be scripted.
+
+use clap::Args;
+use flow_core::config::Config;
+use flow_core::journal::{Granularity, JournalSettings};
+use flow_core::paths;
+use flow_core::repo;
+use flow_core::space::Space;
+use flow_core::sync::Remote;
+use inquire::{Confirm, Select, Text};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::shell_init::{self, Shell};
+use crate::common::{path_to_display_string, registration_name, Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Daily template written when asked for one and the graph has none yet.
+const DAILY_TEMPLATE: &str = "- Plan\n  - \n- Log\n  - \n- Notes\n  - \n";
+
+/// Name of the sync remote added by the wizard.
+const REMOTE_NAME: &str = "origin";
+
+/// Output structure for the setup command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SetupOutput {
+    pub name: String,
+    pub path: String,
+    /// Whether the graph was created, `false` if an existing one was imported.
+    pub created: bool,
+    pub granularity: Option<String>,
+    /// Whether a daily template was written.
+    pub template: bool,
+    pub editor: Option<String>,
+    /// Startup file the shell integration was added to.
+    pub shell: Option<String>,
+    /// Git repository tracking the graph.
+    pub repository: Option<String>,
+    /// Address of the added sync remote.
+    pub remote: Option<String>,
+}
+
+/// Arguments for the setup command.
+#[derive(Args)]
+pub struct SetupArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Graph to create, or to import if it exists (enters the wizard if not provided)
+    pub path: Option<PathBuf>,
+
+    /// How much time a journal page covers
+    #[arg(long, value_parser = ["daily", "weekly", "monthly"])]
+    pub granularity: Option<String>,
+
+    /// Write a daily template, unless the graph has one
+    #[arg(long)]
+    pub template: bool,
+
+    /// Editor command pages are opened in, e.g. "code --wait"
+    #[arg(long)]
+    pub editor: Option<String>,
+
+    /// Add the shell integration to the startup file of this shell
+    #[arg(long, value_enum)]
+    pub shell: Option<Shell>,
+
+    /// Track the graph with git
+    #[arg(long)]
+    pub git: bool,
+
+    /// Address of a device to sync with, added as the remote 'origin'
+    #[arg(long, value_name = "ADDR")]
+    pub remote: Option<String>,
+
+    /// Register the graph under a different name if its name is taken
+    #[arg(long)]
+    pub rename_to: Option<String>,
+}
+
+/// Setup command implementation.
+pub struct SetupCommand {
+    args: SetupArgs,
+}
+
+impl Command for SetupCommand {
+    type Args = SetupArgs;
+    type Output = SetupOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn interactive(&mut self) -> Result<()> {
+        // Flags alone make a scripted setup
+        if self.args.path.is_some() {
+            return Ok(());
+        }
+        self.args
+            .global
+            .info("Welcome to Flow, let's set things up");
+
+        let path = Text::new("Where should your graph live?")
+            .with_default("~/notes")
+            .with_help_message("An existing graph at the path is imported")
+            .prompt()
+            .map_err(CliError::from)?;
+        self.args.path = Some(PathBuf::from(path));
+
+        let granularity = Select::new(
+            "How much time should a journal page cover?",
+            vec!["daily", "weekly", "monthly"],
+        )
+        .prompt()
+        .map_err(CliError::from)?;
+        self.args.granularity = Some(granularity.to_string());
+
+        self.args.template = Confirm::new("Start journal pages from a daily template?")
+            .with_default(true)
+            .with_help_message("An existing template is kept")
+            .prompt()
+            .map_err(CliError::from)?;
+
+        let editor = Text::new("Editor command:")
+            .with_help_message("e.g. code --wait, leave empty to use $VISUAL or $EDITOR")
+            .prompt()
+            .map_err(CliError::from)?;
+        self.args.editor = Some(editor).filter(|editor| !editor.trim().is_empty());
+
+        let shells = vec!["bash", "zsh", "fish", "skip"];
+        let detected = match Shell::detect() {
+            Some(Shell::Bash) => 0,
+            Some(Shell::Zsh) => 1,
+            Some(Shell::Fish) => 2,
+            None => 3,
+        };
+        let shell = Select::new("Install the shell integration for:", shells)
+            .with_starting_cursor(detected)
+            .prompt()
+            .map_err(CliError::from)?;
+        self.args.shell = match shell {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        };
+
+        self.args.git = Confirm::new("Track the graph with git?")
+            .with_default(false)
+            .prompt()
+            .map_err(CliError::from)?;
+
+        let sync = Confirm::new("Sync with another device?")
+            .with_default(false)
+            .prompt()
+            .map_err(CliError::from)?;
+        if sync {
+            let address = Text::new("Address of the device:")
+                .with_help_message("host or host:port, the device runs `flow sync --listen 0.0.0.0`")
+                .prompt()
+                .map_err(CliError::from)?;
+            self.args.remote = Some(address).filter(|address| !address.trim().is_empty());
+        }
+
+        Ok(())
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let path = self
+            .args
+            .path
+            .ok_or_else(|| CliError::missing_argument("path"))?;
+        let path = match paths::home() {
+            Some(home) => paths::expand_home(&path, &home),
+            None => path,
+        };
+        let mut config = Config::load()?;
+
+        // Create or import the graph
+        let created = !Space::exists(&path);
+        let mut graph = if created {
+            if let Some(parent) = Space::enclosing(&path) {
+                return Err(CliError::nested_graph(path, parent).into());
+            }
+            self.args
+                .global
+                .step(&format!("Initializing graph at {}", path.display()));
+            Space::init(&path, None, false)?
+        } else {
+            self.args
+                .global
+                .step(&format!("Importing graph at {}", path.display()));
+            Space::load(&path).map_err(|_| CliError::invalid_graph(path.clone()))?
+        };
+
+        let canonical_path = path.canonicalize().into_diagnostic()?;
+        let name = if config.is_space_registered(&canonical_path) {
+            self.args
+                .global
+                .debug("Status", "Graph already registered, setting as active");
+            config.set_active_space(&canonical_path.to_string_lossy())?;
+            graph.name().to_string()
+        } else {
+            let name = registration_name(
+                &self.args.global,
+                &config,
+                graph.name(),
+                &canonical_path,
+                self.args.rename_to.as_ref(),
+            )?;
+            self.args.global.step("Registering graph in configuration");
+            config.register_space_as(&graph, &name)?;
+            config.set_active_space(&name)?;
+            name
+        };
+
+        if let Some(granularity) = &self.args.granularity {
+            self.args.global.step("Configuring the journal");
+            let granularity = match granularity.as_str() {
+                "weekly" => Granularity::Weekly,
+                "monthly" => Granularity::Monthly,
+                _ => Granularity::Daily,
+            };
+            graph.set_journal_settings(JournalSettings {
+                granularity,
+                ..graph.journal_settings().clone()
+            })?;
+        }
+
+        let template = self.args.template && graph.daily_template()?.is_none();
+        if template {
+            self.args.global.step("Writing the daily template");
+            graph.set_daily_template(DAILY_TEMPLATE)?;
+        }
+
+        if let Some(editor) = &self.args.editor {
+            config.set_editor(Some(editor.clone()))?;
+        }
+
+        let shell = match self.args.shell {
+            Some(shell) => {
+                self.args.global.step("Installing the shell integration");
+                let (file, _) = shell_init::install(shell)?;
+                Some(path_to_display_string(&file))
+            }
+            None => None,
+        };
+
+        let repository = if self.args.git {
+            self.args.global.step("Tracking the graph with git");
+            Some(path_to_display_string(&repo::track(&path)?))
+        } else {
+            None
+        };
+
+        if let Some(address) = &self.args.remote {
+            self.args
+                .global
+                .step(&format!("Adding sync remote '{}'", REMOTE_NAME));
+            graph.add_remote(
+                REMOTE_NAME,
+                Remote {
+                    address: Some(address.clone()),
+                    ..Remote::default()
+                },
+            )?;
+        }
+
+        Ok(SetupOutput {
+            name,
+            path: path_to_display_string(&canonical_path),
+            created,
+            granularity: self.args.granularity,
+            template,
+            editor: self.args.editor,
+            shell,
+            repository,
+            remote: self.args.remote,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.created {
+            global.success("Graph created, Flow is set up");
+        } else {
+            global.success("Graph imported, Flow is set up");
+        }
+        global.blank();
+        global.kv("Name", &output.name);
+        global.kv("Path", &output.path);
+        if let Some(granularity) = &output.granularity {
+            global.kv("Journal", granularity);
+        }
+        if output.template {
+            global.kv("Template", "templates/daily");
+        }
+        if let Some(editor) = &output.editor {
+            global.kv("Editor", editor);
+        }
+        if let Some(shell) = &output.shell {
+            global.kv("Shell", shell);
+        }
+        if let Some(repository) = &output.repository {
+            global.kv("Repository", repository);
+        }
+        if let Some(remote) = &output.remote {
+            global.kv(&format!("Remote {}", REMOTE_NAME), remote);
+        }
+
+        global.blank();
+        global.info("Capture your first note with: flow add \"Hello Flow\"");
+        if output.shell.is_some() {
+            global.info("Open a new terminal to load the shell integration");
+        }
+    }
+}
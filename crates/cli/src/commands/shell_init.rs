@@ -0,0 +1,222 @@
+//! Print shell integration for cd-ing into graphs and per-session graphs.
+
+use clap::{Args, ValueEnum};
+use flow_core::paths;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::common::{Command, GlobalArgs};
+
+/// Default name of the generated shell function.
+const DEFAULT_FUNCTION: &str = "fcd";
+
+/// Default name of the generated function selecting the graph of the session.
+const DEFAULT_SESSION_FUNCTION: &str = "fuse";
+
+/// Shells the integration can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Output structure for the shell-init command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ShellInitOutput {
+    pub shell: Shell,
+    pub function: String,
+    pub session_function: String,
+    pub script: String,
+}
+
+/// Arguments for the shell-init command.
+#[derive(Args)]
+pub struct ShellInitArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Shell to generate the integration for
+    #[arg(value_enum)]
+    pub shell: Shell,
+
+    /// Name of the function that opens a graph and changes into its directory
+    #[arg(long, default_value = DEFAULT_FUNCTION)]
+    pub function: String,
+
+    /// Name of the function that selects a graph for this terminal session only
+    #[arg(long, default_value = DEFAULT_SESSION_FUNCTION)]
+    pub session_function: String,
+}
+
+/// Shell-init command implementation.
+pub struct ShellInitCommand {
+    args: ShellInitArgs,
+}
+
+impl Command for ShellInitCommand {
+    type Args = ShellInitArgs;
+    type Output = ShellInitOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        Ok(ShellInitOutput {
+            shell: self.args.shell,
+            script: script(
+                self.args.shell,
+                &self.args.function,
+                &self.args.session_function,
+            ),
+            function: self.args.function,
+            session_function: self.args.session_function,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.print(&output.script);
+    }
+}
+
+impl Shell {
+    /// Returns the startup file of the shell, relative to the home directory.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - Path of the file sourced by interactive shells
+    pub fn startup_file(&self) -> &'static str {
+        match self {
+            Shell::Bash => ".bashrc",
+            Shell::Zsh => ".zshrc",
+            Shell::Fish => ".config/fish/config.fish",
+        }
+    }
+
+    /// Returns the line loading the integration in the startup file.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - Line evaluating `flow shell-init`
+    pub fn init_line(&self) -> &'static str {
+        match self {
+            Shell::Bash => "eval \"$(flow shell-init bash)\"",
+            Shell::Zsh => "eval \"$(flow shell-init zsh)\"",
+            Shell::Fish => "flow shell-init fish | source",
+        }
+    }
+
+    /// Detects the user's shell from `$SHELL`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Shell>` - The shell, `None` if unset or not supported
+    pub fn detect() -> Option<Shell> {
+        let shell = env::var("SHELL").ok()?;
+        match Path::new(&shell).file_name()?.to_str()? {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// Adds the line loading the integration to the shell's startup file.
+///
+/// # Arguments
+///
+/// * `shell` - Shell to install the integration for
+///
+/// # Returns
+///
+/// * `Result<(PathBuf, bool)>` - The startup file, and whether it was changed
+///
+/// # Errors
+///
+/// Returns an error if the home directory is unknown or the file can't be written
+pub fn install(shell: Shell) -> Result<(PathBuf, bool)> {
+    let home = paths::home().ok_or_else(|| miette::miette!("Home directory not found"))?;
+    let file = home.join(shell.startup_file());
+    let existing = fs::read_to_string(&file).unwrap_or_default();
+    if existing
+        .lines()
+        .any(|line| line.trim() == shell.init_line())
+    {
+        return Ok((file, false));
+    }
+
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).into_diagnostic()?;
+    }
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!(
+        "\n# Flow shell integration\n{}\n",
+        shell.init_line()
+    ));
+    fs::write(&file, content).into_diagnostic()?;
+    Ok((file, true))
+}
+
+/// Generates the shell functions opening a graph and changing into its
+/// directory, and selecting a graph for the session via `FLOW_GRAPH`.
+///
+/// Without an argument the functions pick the graph interactively, like
+/// `flow open`.
+///
+/// # Arguments
+///
+/// * `shell` - Shell to generate the functions for
+/// * `function` - Name of the function changing into the graph
+/// * `session_function` - Name of the function selecting the graph of the session
+///
+/// # Returns
+///
+/// * `String` - Script to evaluate in the shell's startup file
+fn script(shell: Shell, function: &str, session_function: &str) -> String {
+    match shell {
+        Shell::Bash | Shell::Zsh => {
+            let rc = if shell == Shell::Bash {
+                "~/.bashrc"
+            } else {
+                "~/.zshrc"
+            };
+            let name = if shell == Shell::Bash { "bash" } else { "zsh" };
+            format!(
+                r#"# Flow shell integration, add to {rc}:
+#   eval "$(flow shell-init {name})"
+{function}() {{
+    local dir
+    dir="$(command flow open --print-path "$@")" && cd -- "$dir"
+}}
+{session_function}() {{
+    local dir
+    dir="$(command flow open --session "$@")" && export FLOW_GRAPH="$dir"
+}}"#
+            )
+        }
+        Shell::Fish => format!(
+            r#"# Flow shell integration, add to ~/.config/fish/config.fish:
+#   flow shell-init fish | source
+function {function} --description 'Open a Flow graph and cd into it'
+    set -l dir (command flow open --print-path $argv); and cd -- $dir
+end
+function {session_function} --description 'Use a Flow graph in this terminal only'
+    set -l dir (command flow open --session $argv); and set -gx FLOW_GRAPH $dir
+end"#
+        ),
+    }
+}
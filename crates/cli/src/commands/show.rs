@@ -0,0 +1,238 @@
+//! Show a page or a range of journal pages.
+//!
+//! `--week` shows the journal pages of a week as a weekly review. Weeks start
+//! on the configured first day of the week and dates are shown in the
+//! configured date format (see [`flow_core::config::Locale`]); `--iso-week`
+//! ignores both and uses ISO 8601 weeks and dates.
+//!
+//! In a terminal, fenced `csv`, `mermaid` and `tasks` blocks and the blocks
+//! of plugin renderers are rendered (see [`flow_core::display`]); piped
+//! output and `--plain` keep the markdown.
+
+use chrono::NaiveDate;
+use clap::Args;
+use console::{style, Term};
+use flow_core::config::{Config, Locale};
+use flow_core::display::{DisplayLine, LineKind};
+use flow_core::journal;
+use flow_core::page::{self, Heading};
+use flow_core::space::Space;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for a single shown page.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ShownPage {
+    pub name: String,
+    #[serde(skip)]
+    pub title: String,
+    pub id: String,
+    pub created: i64,
+    pub modified: i64,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toc: Option<Vec<Heading>>,
+    #[serde(skip)]
+    pub display: Option<Vec<DisplayLine>>,
+}
+
+/// Output structure for the show command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ShowOutput {
+    pub pages: Vec<ShownPage>,
+}
+
+/// Arguments for the show command.
+#[derive(Args)]
+pub struct ShowArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias (defaults to today's journal page)
+    #[arg(conflicts_with_all = ["since", "until", "week"])]
+    pub page: Option<String>,
+
+    /// Show journal pages from this date on (YYYY-MM-DD)
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+
+    /// Show journal pages up to this date (YYYY-MM-DD)
+    #[arg(long)]
+    pub until: Option<NaiveDate>,
+
+    /// Show the journal pages of the week containing this date (YYYY-MM-DD, defaults to today)
+    #[arg(long, conflicts_with_all = ["since", "until"])]
+    pub week: Option<Option<NaiveDate>>,
+
+    /// Use ISO 8601 weeks and dates instead of the configured locale
+    #[arg(long)]
+    pub iso_week: bool,
+
+    /// Show embeds as written instead of the embedded content
+    #[arg(long)]
+    pub raw: bool,
+
+    /// Show the outline of headings instead of the content
+    #[arg(long)]
+    pub toc: bool,
+
+    /// Show fenced blocks as written instead of rendering tables, diagrams and task lists
+    #[arg(long)]
+    pub plain: bool,
+}
+
+/// Show command implementation.
+pub struct ShowCommand {
+    args: ShowArgs,
+}
+
+impl Command for ShowCommand {
+    type Args = ShowArgs;
+    type Output = ShowOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+        let locale = if self.args.iso_week {
+            Locale::iso()
+        } else {
+            Config::load()?.locale().clone()
+        };
+
+        let rich = !self.args.plain
+            && !self.args.toc
+            && !self.args.global.json
+            && Term::stdout().is_term();
+        let display = |content: &str| rich.then(|| space.display(content));
+
+        let range = match self.args.week {
+            Some(date) => {
+                let date = date.unwrap_or_else(|| space.today());
+                let (first, last) = journal::week(date, locale.first_day_of_week.weekday());
+                Some((Some(first), Some(last)))
+            }
+            None if self.args.since.is_some() || self.args.until.is_some() => {
+                Some((self.args.since, self.args.until))
+            }
+            None => None,
+        };
+
+        if let Some((since, until)) = range {
+            let pages = space
+                .journal(since, until)?
+                .into_iter()
+                .map(|day| {
+                    let times = space.page_times(&day.id);
+                    let content = expand(&space, &day.id, day.content, self.args.raw)?;
+                    let name = page::name_from_id(&day.id);
+                    // Weekly and monthly pages keep their name
+                    let title = match journal::period(&name) {
+                        Some((first, last)) if first == last => locale.format_date(day.date),
+                        _ => name.clone(),
+                    };
+                    Ok(ShownPage {
+                        name,
+                        title,
+                        id: day.id,
+                        created: times.created,
+                        modified: times.modified,
+                        toc: self.args.toc.then(|| page::headings(&content)),
+                        display: display(&content),
+                        content,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            return Ok(ShowOutput { pages });
+        }
+
+        let id = match &self.args.page {
+            Some(name) => space.page_index()?.resolve(name),
+            None => space.journal_page(space.today()),
+        };
+        let name = page::name_from_id(&id);
+        let content = space
+            .read_page(&id)?
+            .ok_or_else(|| CliError::page_not_found(self.args.page.as_deref().unwrap_or(&name)))?;
+
+        let times = space.page_times(&id);
+        let content = expand(&space, &id, content, self.args.raw)?;
+
+        Ok(ShowOutput {
+            pages: vec![ShownPage {
+                title: name.clone(),
+                name,
+                id,
+                created: times.created,
+                modified: times.modified,
+                toc: self.args.toc.then(|| page::headings(&content)),
+                display: display(&content),
+                content,
+            }],
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        // Plain markdown unless rendered for a terminal, so the output can be piped into other tools
+        let single = output.pages.len() == 1;
+        for (index, page) in output.pages.iter().enumerate() {
+            if !single {
+                if index > 0 {
+                    global.blank();
+                }
+                global.print(&format!("# {}", page.title));
+                global.blank();
+            }
+            match &page.toc {
+                Some(toc) => {
+                    // Indent relative to the highest level on the page
+                    let top = toc.iter().map(|heading| heading.level).min().unwrap_or(1);
+                    for heading in toc {
+                        global.print(&format!(
+                            "{}- {}",
+                            "  ".repeat(heading.level - top),
+                            heading.text
+                        ));
+                    }
+                }
+                None => match &page.display {
+                    Some(lines) => {
+                        for line in lines {
+                            global.print(&styled(line));
+                        }
+                    }
+                    None => global.print(page.content.trim_end()),
+                },
+            }
+        }
+    }
+}
+
+/// Styles a rendered line by what it shows.
+fn styled(line: &DisplayLine) -> String {
+    match line.kind {
+        LineKind::Table { header: true } => style(&line.text).bold().to_string(),
+        LineKind::Diagram => style(&line.text).cyan().to_string(),
+        LineKind::Notice => style(&line.text).dim().italic().to_string(),
+        LineKind::Task { done: true } => style(&line.text).dim().to_string(),
+        _ => line.text.clone(),
+    }
+}
+
+/// Inlines the pages embedded into a page, unless raw output was requested.
+fn expand(space: &Space, id: &str, content: String, raw: bool) -> Result<String> {
+    if raw {
+        return Ok(content);
+    }
+    Ok(space.expand_embeds(id, &content)?.content)
+}
@@ -0,0 +1,80 @@
+//! Find pages similar in meaning to a page.
+
+use clap::Args;
+use flow_core::embedding::SimilarPage;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+use crate::interrupt;
+
+/// Output structure for the similar command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SimilarOutput {
+    pub page: String,
+    pub similar: Vec<SimilarPage>,
+}
+
+/// Arguments for the similar command.
+#[derive(Args)]
+pub struct SimilarArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias
+    pub page: String,
+
+    /// Maximum number of results (0 for all)
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+}
+
+/// Similar command implementation.
+pub struct SimilarCommand {
+    args: SimilarArgs,
+}
+
+impl Command for SimilarCommand {
+    type Args = SimilarArgs;
+    type Output = SimilarOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+        let progress = self.args.global.progress();
+
+        let similar = space
+            .similar(
+                &self.args.page,
+                self.args.limit,
+                progress.as_ref(),
+                &interrupt::token(),
+            )?
+            .ok_or_else(|| CliError::page_not_found(&self.args.page))?;
+
+        Ok(SimilarOutput {
+            page: self.args.page.clone(),
+            similar,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.similar.is_empty() {
+            global.info(&format!("No pages similar to '{}'", output.page));
+            return;
+        }
+
+        for similar in &output.similar {
+            global.kv(&similar.page, &format!("{:.2}", similar.score));
+        }
+    }
+}
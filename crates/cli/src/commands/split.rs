@@ -0,0 +1,74 @@
+//! Split a page into one page per top-level heading.
+
+use clap::Args;
+use flow_core::page;
+use flow_core::split::SplitPage;
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Arguments for the split command.
+#[derive(Args)]
+pub struct SplitArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias
+    pub page: String,
+
+    /// Extract each top-level heading into its own page
+    #[arg(long, required = true)]
+    pub by_heading: bool,
+
+    /// Replace the sections with embeds instead of links
+    #[arg(long)]
+    pub embed: bool,
+
+    /// Split even if the page is locked
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Split command implementation.
+pub struct SplitCommand {
+    args: SplitArgs,
+}
+
+impl Command for SplitCommand {
+    type Args = SplitArgs;
+    type Output = SplitPage;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        space.set_allow_locked(self.args.force);
+
+        let id = space.page_index()?.resolve(&self.args.page);
+        if space.read_page(&id)?.is_none() {
+            return Err(CliError::page_not_found(&self.args.page).into());
+        }
+
+        space.split_page(&id, self.args.embed)
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        for id in &output.pages {
+            global.kv("Created", &format!("[[{}]]", page::name_from_id(id)));
+        }
+        global.blank();
+        global.success(&format!(
+            "Split [[{}]] into {} page{}",
+            page::name_from_id(&output.page),
+            output.pages.len(),
+            if output.pages.len() == 1 { "" } else { "s" }
+        ));
+    }
+}
@@ -0,0 +1,51 @@
+//! Show statistics of the active graph.
+//!
+//! The statistics are cached and only recomputed once the graph changed, so
+//! repeated invocations don't read every page.
+
+use clap::Args;
+use flow_core::stats::SpaceStats;
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Arguments for the stats command.
+#[derive(Args)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+}
+
+/// Stats command implementation.
+pub struct StatsCommand {
+    args: StatsArgs,
+}
+
+impl Command for StatsCommand {
+    type Args = StatsArgs;
+    type Output = SpaceStats;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+        space.stats()
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.kv("Pages", &output.pages.to_string());
+        global.kv("Journal pages", &output.journal_pages.to_string());
+        global.kv("Words", &output.counts.words.to_string());
+        global.kv("Blocks", &output.counts.blocks.to_string());
+        global.kv("Open tasks", &output.open_tasks.to_string());
+        global.kv("Done tasks", &output.done_tasks.to_string());
+        global.kv("Links", &output.links.to_string());
+        global.kv("Tags", &output.tags.to_string());
+    }
+}
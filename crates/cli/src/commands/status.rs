@@ -0,0 +1,222 @@
+//! Show the status of the active graph, fast enough for shell prompts.
+//!
+//! Only the configuration, the graph metadata, the cached statistics (or the
+//! page index if there are none yet) and the sync marker are read, the
+//! document and pages never are, so the command finishes in milliseconds even
+//! for large graphs. A missing index is reported as empty instead of rebuilt.
+//!
+//! With `--porcelain` a single tab-separated line is printed, in a format
+//! kept stable across releases:
+//!
+//! ```text
+//! <graph>\t<due>\t<sync>
+//! ```
+//!
+//! `<due>` counts open tasks scheduled or due by the end of today, overdue
+//! ones included, where today follows the clock settings of the graph.
+//! `<sync>` is `*` if pages changed since the last sync, `=` if not, and `-`
+//! if the graph never synced. Nothing is printed if no graph is active.
+//!
+//! Without `--porcelain` the full status of the graph is shown as well, like
+//! `git status` (see [`flow_core::status`]): pages whose markdown file and
+//! document diverged, the last save, pending changes per sync remote, the
+//! freshness of the index and the locked pages. This reads every page.
+
+use chrono::{DateTime, Local};
+use clap::Args;
+use flow_core::clock::ClockSettings;
+use flow_core::config::Config;
+use flow_core::index::PageIndex;
+use flow_core::stats::SpaceStats;
+use flow_core::status::{self, IndexState, SpaceStatus};
+use flow_core::sync;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+
+/// Output structure for the status command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StatusOutput {
+    pub graph: Option<String>,
+    pub path: Option<String>,
+    pub due: usize,
+    pub unsynced: Option<bool>,
+    /// Full status of the graph, `None` with `--porcelain`.
+    pub status: Option<SpaceStatus>,
+    #[serde(skip)]
+    pub porcelain: bool,
+}
+
+/// Arguments for the status command.
+#[derive(Args)]
+pub struct StatusArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Print a single stable, tab-separated line for shell prompts
+    #[arg(long)]
+    pub porcelain: bool,
+}
+
+/// Status command implementation.
+pub struct StatusCommand {
+    args: StatusArgs,
+}
+
+impl Command for StatusCommand {
+    type Args = StatusArgs;
+    type Output = StatusOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let config = Config::load()?;
+        let graph = match self.args.global.target() {
+            Some(name_or_path) => config
+                .get_space_config(&name_or_path)
+                .map(|space| (name_or_path, space)),
+            None => config
+                .get_active_space_name()
+                .zip(config.get_active_space())
+                .map(|(name, space)| (name.to_string(), space)),
+        };
+        let Some((name, space)) = graph else {
+            return Ok(StatusOutput {
+                graph: None,
+                path: None,
+                due: 0,
+                unsynced: None,
+                status: None,
+                porcelain: self.args.porcelain,
+            });
+        };
+
+        let end_of_today = ClockSettings::load(&space.path).end_of_today();
+
+        // The statistics are much smaller than the index, if they are current
+        let (due, modified) = match SpaceStats::cached(&space.path) {
+            Some(stats) => (stats.due, stats.modified),
+            None => {
+                let index = PageIndex::cached(&space.path).unwrap_or_default();
+                let due = index.pages().flat_map(|entry| entry.due.clone()).collect();
+                let modified = index.pages().map(|entry| entry.modified).max();
+                (due, modified.unwrap_or_default())
+            }
+        };
+        let due = due.iter().filter(|at| **at <= end_of_today).count();
+        let unsynced = sync::last_synced(&space.path).map(|synced| modified > synced);
+
+        // Prompts call the porcelain variant on every keystroke
+        let status = if self.args.porcelain {
+            None
+        } else {
+            Some(status::inspect(&space.path)?)
+        };
+
+        Ok(StatusOutput {
+            graph: Some(name),
+            path: Some(path_to_display_string(&space.path)),
+            due,
+            unsynced,
+            status,
+            porcelain: self.args.porcelain,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        let sync = match output.unsynced {
+            Some(true) => "*",
+            Some(false) => "=",
+            None => "-",
+        };
+
+        if output.porcelain {
+            if let Some(graph) = &output.graph {
+                global.print(&format!("{}\t{}\t{}", graph, output.due, sync));
+            }
+            return;
+        }
+
+        let Some(graph) = &output.graph else {
+            global.info("No active graph, run `flow init` or `flow open`");
+            return;
+        };
+        global.heading(graph);
+        global.blank();
+        if let Some(path) = &output.path {
+            global.kv("Path", path);
+        }
+        global.kv("Due today", &output.due.to_string());
+        global.kv(
+            "Sync",
+            match output.unsynced {
+                Some(true) => "changes since last sync",
+                Some(false) => "up to date",
+                None => "never synced",
+            },
+        );
+
+        let Some(status) = &output.status else {
+            return;
+        };
+        global.kv(
+            "Last saved",
+            &status.saved.map_or("never".to_string(), format_time),
+        );
+        for remote in &status.remotes {
+            let address = remote.address.as_deref().unwrap_or("no address");
+            global.kv(
+                &format!("Remote {}", remote.name),
+                &format!("{} pending ({})", remote.pending.len(), address),
+            );
+        }
+        global.kv(
+            "Index",
+            match status.index {
+                IndexState::Fresh => "fresh",
+                IndexState::Stale => "stale, run `flow index`",
+                IndexState::Missing => "missing, run `flow index`",
+            },
+        );
+
+        global.blank();
+        if status.is_clean() {
+            global.success("Markdown files and document are in sync");
+        } else {
+            global.warning(&format!(
+                "{} change{} between markdown files and document, run `flow fsck`",
+                status.changes.len(),
+                if status.changes.len() == 1 { "" } else { "s" }
+            ));
+            for change in &status.changes {
+                global.print(&format!("    {}", change.describe()));
+            }
+        }
+        if !status.locked.is_empty() {
+            global.blank();
+            global.heading("Locked pages");
+            for id in &status.locked {
+                global.print(&format!("    {}", id));
+            }
+        }
+    }
+}
+
+/// Formats a unix timestamp as local time.
+fn format_time(at: i64) -> String {
+    DateTime::from_timestamp(at, 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,87 @@
+//! Summarize a page or a week of journal pages with a language model.
+
+use chrono::NaiveDate;
+use clap::Args;
+use flow_core::ai::{self, Summary, SummaryTarget};
+use flow_core::config::{Config, Locale};
+use flow_core::journal;
+use miette::Result;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Arguments for the summarize command.
+#[derive(Args)]
+pub struct SummarizeArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias to summarize
+    #[arg(required_unless_present = "week", conflicts_with = "week")]
+    pub page: Option<String>,
+
+    /// Summarize the journal pages of the week containing this date (YYYY-MM-DD, defaults to today)
+    #[arg(long)]
+    pub week: Option<Option<NaiveDate>>,
+
+    /// Use ISO 8601 weeks instead of the configured locale
+    #[arg(long)]
+    pub iso_week: bool,
+}
+
+/// Summarize command implementation.
+pub struct SummarizeCommand {
+    args: SummarizeArgs,
+}
+
+impl Command for SummarizeCommand {
+    type Args = SummarizeArgs;
+    type Output = Summary;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+        if space.ai_settings().endpoint.is_none() {
+            return Err(CliError::ai_not_configured(space.name()).into());
+        }
+        let model = ai::language_model(space.ai_settings())?;
+
+        let target = match (&self.args.page, self.args.week) {
+            (Some(page), _) => SummaryTarget::Page(page.clone()),
+            (None, week) => {
+                let locale = if self.args.iso_week {
+                    Locale::iso()
+                } else {
+                    Config::load()?.locale().clone()
+                };
+                let date = week.flatten().unwrap_or_else(|| space.today());
+                let (first, last) = journal::week(date, locale.first_day_of_week.weekday());
+                SummaryTarget::Week(first, last)
+            }
+        };
+
+        self.args
+            .global
+            .step(&format!("Summarizing with {}", model.id()));
+        space.summarize(&target, model.as_ref())
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.heading(&format!("Summary of [[{}]]", output.source));
+        for point in &output.points {
+            global.print(&format!("- {}", point));
+        }
+        global.blank();
+        global.success(&format!(
+            "Appended to [[{}]]",
+            flow_core::page::name_from_id(&output.page)
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+//! Sync a Flow graph live with another device on the local network, and
+//! review blocks both devices changed concurrently.
+
+use clap::Args;
+use flow_core::attribution::Author;
+use flow_core::conflicts::Conflict;
+use flow_core::sync::{resolve, SyncEvent, SyncStats};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+use crate::interrupt;
+
+/// Output structure for the sync command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SyncOutput {
+    #[serde(flatten)]
+    pub stats: SyncStats,
+    pub conflicts: Vec<Conflict>,
+    pub cleared: usize,
+    #[serde(skip)]
+    pub synced: bool,
+}
+
+/// Arguments for the sync command.
+#[derive(Args)]
+pub struct SyncArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Wait for devices to connect, optionally on ADDR (defaults to this machine only, other
+    /// addresses need a sync secret)
+    #[arg(
+        long,
+        value_name = "ADDR",
+        num_args = 0..=1,
+        default_missing_value = "127.0.0.1",
+        required_unless_present_any = ["connect", "remote", "show_conflicts", "clear_conflicts"],
+        conflicts_with = "connect"
+    )]
+    pub listen: Option<String>,
+
+    /// Connect to a listening device at ADDR (host or host:port)
+    #[arg(long, value_name = "ADDR")]
+    pub connect: Option<String>,
+
+    /// Apply the include/exclude patterns of a remote from space.toml, connecting to its address
+    /// unless --listen or --connect is given
+    #[arg(long, value_name = "NAME")]
+    pub remote: Option<String>,
+
+    /// List blocks changed on both sides of earlier syncs instead of syncing
+    #[arg(long, conflicts_with_all = ["listen", "connect", "remote"])]
+    pub show_conflicts: bool,
+
+    /// Empty the queue of conflicts after reviewing them
+    #[arg(long, conflicts_with_all = ["listen", "connect", "remote", "show_conflicts"])]
+    pub clear_conflicts: bool,
+    /// Accept changes devices made to locked pages instead of reverting them
+    #[arg(long, conflicts_with_all = ["show_conflicts", "clear_conflicts"])]
+    pub force: bool,
+}
+
+/// Sync command implementation.
+pub struct SyncCommand {
+    args: SyncArgs,
+}
+
+impl Command for SyncCommand {
+    type Args = SyncArgs;
+    type Output = SyncOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        let mut space = global.load_graph()?;
+        space.set_allow_locked(self.args.force);
+
+        if self.args.show_conflicts || self.args.clear_conflicts {
+            let conflicts = space.conflicts()?;
+            let cleared = if self.args.clear_conflicts {
+                space.clear_conflicts()?
+            } else {
+                0
+            };
+            return Ok(SyncOutput {
+                stats: SyncStats::default(),
+                conflicts,
+                cleared,
+                synced: false,
+            });
+        }
+
+        let cancel = interrupt::token();
+        let mut detected = Vec::new();
+
+        let mut on_event = |event: SyncEvent| match event {
+            SyncEvent::Listening { addr } => {
+                global.info(&format!("Listening on {}, press Ctrl-C to stop", addr))
+            }
+            SyncEvent::Connected { peer } => global.success(&format!("Connected to {}", peer)),
+            SyncEvent::Sent { bytes } => global.print_verbose(&format!("Sent {} bytes", bytes)),
+            SyncEvent::Received {
+                pages, conflicts, ..
+            } => {
+                for page in pages {
+                    global.step(&format!("Updated {}", page));
+                }
+                for conflict in conflicts {
+                    global.warning(&format!(
+                        "Conflict in {} at line {}, changed on both sides{}",
+                        conflict.page,
+                        conflict.line,
+                        by(&conflict.remote_author)
+                    ));
+                    detected.push(conflict);
+                }
+            }
+            SyncEvent::Disconnected { peer } => global.info(&format!("{} disconnected", peer)),
+            SyncEvent::Rejected { peer, reason } => {
+                global.warning(&format!("Rejected {}: {}", peer, reason))
+            }
+        };
+
+        let remote = self.args.remote.as_deref();
+        let stats = match (&self.args.listen, &self.args.connect) {
+            (_, Some(addr)) => {
+                space.sync_connect(resolve(addr)?, remote, &cancel, &mut on_event)?
+            }
+            (Some(addr), None) => {
+                space.sync_listen(resolve(addr)?, remote, &cancel, &mut on_event)?
+            }
+            (None, None) => {
+                let name = remote.unwrap_or_default();
+                let addr = space
+                    .sync_settings()
+                    .remotes
+                    .get(name)
+                    .and_then(|remote| remote.address.clone())
+                    .ok_or_else(|| CliError::Other {
+                        message: format!(
+                            "Remote '{}' is not configured or has no address, use --connect",
+                            name
+                        ),
+                    })?;
+                space.sync_connect(resolve(&addr)?, remote, &cancel, &mut on_event)?
+            }
+        };
+
+        Ok(SyncOutput {
+            stats,
+            conflicts: detected,
+            cleared: 0,
+            synced: true,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.synced {
+            let stats = &output.stats;
+            global.blank();
+            global.kv("Peers", &stats.peers.to_string());
+            global.kv("Pages updated", &stats.pages.to_string());
+            global.kv("Sent", &format!("{} bytes", stats.sent));
+            global.kv("Received", &format!("{} bytes", stats.received));
+            if stats.conflicts > 0 {
+                global.kv("Conflicts", &stats.conflicts.to_string());
+                global.info("Review them with `flow sync --show-conflicts`");
+            }
+            return;
+        }
+
+        if output.conflicts.is_empty() {
+            global.info("No conflicts to review");
+            return;
+        }
+        for conflict in &output.conflicts {
+            global.heading(&format!("{}:{}", conflict.page, conflict.line));
+            global.kv("Base", &conflict.base);
+            global.kv(
+                &format!("Local{}", by(&conflict.local_author)),
+                &conflict.local,
+            );
+            global.kv(
+                &format!("Remote{}", by(&conflict.remote_author)),
+                &conflict.remote,
+            );
+            global.kv("Merged", &conflict.merged);
+            global.blank();
+        }
+        if output.cleared > 0 {
+            global.success(&format!(
+                "Cleared {} conflict{}",
+                output.cleared,
+                if output.cleared == 1 { "" } else { "s" }
+            ));
+        }
+    }
+}
+
+/// Formats the author of one side of a conflict, empty if unknown.
+fn by(author: &Option<Author>) -> String {
+    author
+        .as_ref()
+        .map(|author| format!(" by {} ({})", author.name, author.device))
+        .unwrap_or_default()
+}
@@ -0,0 +1,60 @@
+//! Create a named checkpoint of a Flow graph.
+
+use clap::Args;
+use flow_core::checkpoints::Checkpoint;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+
+/// Output structure for the tag-version command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TagVersionOutput {
+    #[serde(flatten)]
+    pub checkpoint: Checkpoint,
+}
+
+/// Arguments for the tag-version command.
+#[derive(Args)]
+pub struct TagVersionArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Name of the checkpoint
+    pub name: String,
+}
+
+/// Tag-version command implementation.
+pub struct TagVersionCommand {
+    args: TagVersionArgs,
+}
+
+impl Command for TagVersionCommand {
+    type Args = TagVersionArgs;
+    type Output = TagVersionOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut space = self.args.global.load_graph()?;
+
+        Ok(TagVersionOutput {
+            checkpoint: space.tag_version(&self.args.name)?,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        global.success(&format!("Created checkpoint {}", output.checkpoint.name));
+        global.info(&format!(
+            "Compare with `flow diff <page> --at {0}`, restore with `flow restore --version {0}`",
+            output.checkpoint.name
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+//! Suggest tags for pages from the tags already used in the graph.
+
+use clap::{Args, Subcommand};
+use flow_core::ai;
+use flow_core::tagging::{self, TagSuggestion};
+use inquire::MultiSelect;
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Output structure for the tags command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TagsOutput {
+    pub suggestions: Vec<TagSuggestion>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applied: Vec<TagSuggestion>,
+}
+
+/// Tag actions.
+#[derive(Subcommand)]
+pub enum TagsAction {
+    /// Suggest tags for a page, or for all untagged pages
+    Suggest {
+        /// Page name or alias
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        page: Option<String>,
+
+        /// Suggest tags for every untagged page
+        #[arg(long)]
+        all: bool,
+
+        /// Add the accepted tags to the pages
+        #[arg(long)]
+        apply: bool,
+
+        /// Ask the configured language model instead of using heuristics
+        #[arg(long)]
+        ai: bool,
+
+        /// Maximum number of tags per page
+        #[arg(short = 'n', long, default_value_t = 3)]
+        limit: usize,
+    },
+}
+
+/// Arguments for the tags command.
+#[derive(Args)]
+pub struct TagsArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: TagsAction,
+}
+
+/// Tags command implementation.
+pub struct TagsCommand {
+    args: TagsArgs,
+}
+
+impl Command for TagsCommand {
+    type Args = TagsArgs;
+    type Output = TagsOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let global = &self.args.global;
+        let TagsAction::Suggest {
+            page,
+            all,
+            apply,
+            ai: use_ai,
+            limit,
+        } = &self.args.action;
+        let mut space = global.load_graph()?;
+
+        let model = if *use_ai {
+            if space.ai_settings().endpoint.is_none() {
+                return Err(CliError::ai_not_configured(space.name()).into());
+            }
+            Some(ai::language_model(space.ai_settings())?)
+        } else {
+            None
+        };
+
+        let ids = match page {
+            Some(name) => {
+                let id = space.page_index()?.resolve(name);
+                if space.read_page(&id)?.is_none() {
+                    return Err(CliError::page_not_found(name).into());
+                }
+                vec![id]
+            }
+            None if *all => space.untagged_pages()?,
+            None => Vec::new(),
+        };
+
+        global.step("Collecting tags");
+        let vocabulary = space.tag_vocabulary()?;
+        let mut suggestions = Vec::new();
+        for id in ids {
+            let content = space.read_page(&id)?.unwrap_or_default();
+            let tags = match &model {
+                Some(model) => {
+                    global.step(&format!(
+                        "Asking {} about [[{}]]",
+                        model.id(),
+                        flow_core::page::name_from_id(&id)
+                    ));
+                    tagging::suggest_with_model(model.as_ref(), &vocabulary, &content, *limit)?
+                }
+                None => vocabulary.suggest(&content, *limit),
+            };
+            if !tags.is_empty() {
+                suggestions.push(TagSuggestion {
+                    page: flow_core::page::name_from_id(&id),
+                    id,
+                    tags,
+                });
+            }
+        }
+
+        let mut applied = Vec::new();
+        if *apply {
+            for suggestion in &suggestions {
+                // Scripts accept all suggestions, people pick the ones to keep
+                let accepted = if global.json {
+                    suggestion.tags.clone()
+                } else {
+                    let prompt = format!("Tags for [[{}]]", suggestion.page);
+                    match MultiSelect::new(&prompt, suggestion.tags.clone())
+                        .with_all_selected_by_default()
+                        .prompt()
+                    {
+                        Ok(accepted) => accepted,
+                        Err(_) => break,
+                    }
+                };
+                let added = space.add_tags(&suggestion.id, &accepted)?;
+                if !added.is_empty() {
+                    applied.push(TagSuggestion {
+                        id: suggestion.id.clone(),
+                        page: suggestion.page.clone(),
+                        tags: added,
+                    });
+                }
+            }
+        }
+
+        Ok(TagsOutput {
+            suggestions,
+            applied,
+        })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.suggestions.is_empty() {
+            global.info("No tags to suggest");
+            return;
+        }
+
+        if output.applied.is_empty() {
+            for suggestion in &output.suggestions {
+                global.kv(
+                    &format!("[[{}]]", suggestion.page),
+                    &suggestion.tags.join(", "),
+                );
+            }
+            return;
+        }
+
+        for applied in &output.applied {
+            global.kv(&format!("[[{}]]", applied.page), &applied.tags.join(", "));
+        }
+        global.blank();
+        global.success(&format!(
+            "Tagged {} page{}",
+            output.applied.len(),
+            if output.applied.len() == 1 { "" } else { "s" }
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+//! Manage the registry of space templates used by `flow init --template`.
+
+use clap::{Args, Subcommand};
+use flow_core::template::{self, SpaceTemplate};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{path_to_display_string, Command, GlobalArgs};
+
+/// Output structure for the template command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TemplateOutput {
+    /// Directory of the registry
+    pub registry: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub templates: Option<Vec<SpaceTemplate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added: Option<SpaceTemplate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<SpaceTemplate>,
+}
+
+/// Template actions.
+#[derive(Subcommand)]
+pub enum TemplateAction {
+    /// List the templates of the registry with their variables
+    List,
+    /// Add a template to the registry
+    Add {
+        /// Directory or git URL of the template
+        source: String,
+
+        /// Name to add the template as (defaults to the name in its manifest)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove a template from the registry
+    Remove {
+        /// Name of the template
+        name: String,
+    },
+}
+
+/// Arguments for the template command.
+#[derive(Args)]
+pub struct TemplateArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub action: TemplateAction,
+}
+
+/// Template command implementation.
+pub struct TemplateCommand {
+    args: TemplateArgs,
+}
+
+impl Command for TemplateCommand {
+    type Args = TemplateArgs;
+    type Output = TemplateOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let mut output = TemplateOutput {
+            registry: path_to_display_string(&template::registry_dir()?),
+            templates: None,
+            added: None,
+            removed: None,
+        };
+
+        match self.args.action {
+            TemplateAction::List => output.templates = Some(template::list()?),
+            TemplateAction::Add { source, name } => {
+                self.args
+                    .global
+                    .step(&format!("Adding template from {}", source));
+                output.added = Some(template::add(&source, name.as_deref())?);
+            }
+            TemplateAction::Remove { name } => {
+                output.removed = Some(template::remove(&name)?);
+            }
+        }
+        Ok(output)
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if let Some(templates) = &output.templates {
+            if templates.is_empty() {
+                global.info(&format!("No templates in {}", output.registry));
+            }
+            for template in templates {
+                print_template(template, global);
+            }
+        }
+
+        if let Some(added) = &output.added {
+            global.success(&format!("Added template '{}'", added.manifest.name));
+            global.blank();
+            print_template(added, global);
+            global.info(&format!(
+                "Use it with: flow init <path> --template {}",
+                added.manifest.name
+            ));
+        }
+
+        if let Some(removed) = &output.removed {
+            global.success(&format!("Removed template '{}'", removed.manifest.name));
+        }
+    }
+}
+
+/// Prints a template with its variables.
+fn print_template(template: &SpaceTemplate, global: &GlobalArgs) {
+    global.heading(&template.manifest.name);
+    if !template.manifest.description.is_empty() {
+        global.print(&format!("  {}", template.manifest.description));
+    }
+    global.kv("Files", &template.files.len().to_string());
+    for variable in &template.manifest.variables {
+        let default = match &variable.default {
+            Some(default) => format!(" (default: {})", default),
+            None => " (required)".to_string(),
+        };
+        global.kv(
+            &format!("{{{{{}}}}}", variable.name),
+            &format!("{}{}", variable.description, default),
+        );
+    }
+    global.blank();
+}
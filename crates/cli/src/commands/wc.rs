@@ -0,0 +1,137 @@
+//! Count the words of pages and journal days.
+
+use chrono::NaiveDate;
+use clap::Args;
+use flow_core::page;
+use flow_core::wordcount::{self, WordCount};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::common::{Command, GlobalArgs};
+use crate::error::CliError;
+
+/// Counts of a single page or journal day.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CountedPage {
+    pub name: String,
+    pub id: String,
+    #[serde(flatten)]
+    pub counts: WordCount,
+}
+
+/// Output structure for the wc command.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WcOutput {
+    pub pages: Vec<CountedPage>,
+    pub total: WordCount,
+}
+
+/// Arguments for the wc command.
+#[derive(Args)]
+pub struct WcArgs {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Page name or alias (defaults to all pages)
+    #[arg(conflicts_with_all = ["since", "until"])]
+    pub page: Option<String>,
+
+    /// Count journal days from this date on (YYYY-MM-DD)
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+
+    /// Count journal days up to this date (YYYY-MM-DD)
+    #[arg(long)]
+    pub until: Option<NaiveDate>,
+}
+
+/// Wc command implementation.
+pub struct WcCommand {
+    args: WcArgs,
+}
+
+impl Command for WcCommand {
+    type Args = WcArgs;
+    type Output = WcOutput;
+
+    fn from_args(args: Self::Args) -> Self {
+        Self { args }
+    }
+
+    fn global_args(&self) -> &GlobalArgs {
+        &self.args.global
+    }
+
+    fn run(self) -> Result<Self::Output> {
+        let space = self.args.global.load_graph_readonly()?;
+
+        let pages: Vec<CountedPage> = if self.args.since.is_some() || self.args.until.is_some() {
+            // One entry per journal page, so writing habits show
+            space
+                .journal(self.args.since, self.args.until)?
+                .into_iter()
+                .map(|day| CountedPage {
+                    name: page::name_from_id(&day.id),
+                    id: day.id,
+                    counts: wordcount::count(&day.content),
+                })
+                .collect()
+        } else if let Some(name) = &self.args.page {
+            let id = space.page_index()?.resolve(name);
+            let content = space
+                .read_page(&id)?
+                .ok_or_else(|| CliError::page_not_found(name))?;
+            vec![CountedPage {
+                name: page::name_from_id(&id),
+                id,
+                counts: wordcount::count(&content),
+            }]
+        } else {
+            let mut pages = Vec::new();
+            for id in space.page_ids()? {
+                if let Some(content) = space.read_page(&id)? {
+                    pages.push(CountedPage {
+                        name: page::name_from_id(&id),
+                        id,
+                        counts: wordcount::count(&content),
+                    });
+                }
+            }
+            pages
+        };
+
+        let mut total = WordCount::default();
+        for page in &pages {
+            total.add(&page.counts);
+        }
+        Ok(WcOutput { pages, total })
+    }
+
+    fn format_output(output: &Self::Output, global: &GlobalArgs) {
+        if output.pages.is_empty() {
+            global.info("No pages to count");
+            return;
+        }
+
+        if output.pages.len() > 1 {
+            for page in &output.pages {
+                global.kv(&page.name, &format!("{} words", page.counts.words));
+            }
+            global.blank();
+            global.heading(&format!("Total of {} pages", output.pages.len()));
+        }
+        let total = &output.total;
+        global.kv("Words", &total.words.to_string());
+        global.kv("Blocks", &total.blocks.to_string());
+        global.kv("Characters", &total.characters.to_string());
+        global.kv(
+            "Reading time",
+            &format!(
+                "{} minute{}",
+                total.reading_minutes,
+                if total.reading_minutes == 1 { "" } else { "s" }
+            ),
+        );
+    }
+}
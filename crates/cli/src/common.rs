@@ -1,22 +1,19 @@
 //! Common types and utilities shared across all CLI commands.
 
 use clap::Args;
-use console::{style, Emoji, Term};
-use flow_core::config::Config;
-use flow_core::graph::Graph;
+use console::{style, Term};
+use flow_core::config::{Config, Icons};
+use flow_core::fuzzy;
+use flow_core::paths;
+use flow_core::progress::{NoProgress, Progress};
+use flow_core::space::Space;
+use inquire::Text;
 use miette::{Context, IntoDiagnostic, Result};
 use std::path::{Path, PathBuf};
 
-use crate::error::CliError;
-
-// Emojis with fallbacks for terminals that don't support them
-static SPARKLE: Emoji<'_, '_> = Emoji("✨ ", "* ");
-static INFO: Emoji<'_, '_> = Emoji("ℹ️  ", "[i] ");
-static SUCCESS: Emoji<'_, '_> = Emoji("✅ ", "[+] ");
-static WARN: Emoji<'_, '_> = Emoji("⚠️  ", "[!] ");
-static ERROR: Emoji<'_, '_> = Emoji("❌ ", "[x] ");
-static DEBUG: Emoji<'_, '_> = Emoji("🔍 ", "[?] ");
-static ARROW: Emoji<'_, '_> = Emoji("→ ", "-> ");
+use crate::error::{self, CliError};
+use crate::progress::TerminalProgress;
+use crate::theme::{self, Icon, Styles};
 
 /// Converts a canonicalized path to a clean display string.
 ///
@@ -33,15 +30,90 @@ static ARROW: Emoji<'_, '_> = Emoji("→ ", "-> ");
 pub fn path_to_display_string(path: &Path) -> String {
     let path_str = path.display().to_string();
 
-    #[cfg(windows)]
-    {
-        // Remove Windows extended-length path prefix
-        if let Some(stripped) = path_str.strip_prefix(r"\\?\") {
-            return stripped.to_string();
+    // Remove Windows extended-length path prefix
+    paths::strip_verbatim(&path_str).unwrap_or(path_str)
+}
+
+/// Scores picker options with Flow's fuzzy matching.
+///
+/// Used as scorer of `inquire` selections, so pickers rank options the same
+/// way as the TUI command palette.
+///
+/// # Arguments
+///
+/// * `input` - The typed filter
+/// * `value` - The displayed option
+///
+/// # Returns
+///
+/// * `Option<i64>` - Score of the option, `None` hides it
+pub fn fuzzy_scorer<T>(input: &str, _option: &T, value: &str, _index: usize) -> Option<i64> {
+    fuzzy::score(input, value)
+}
+
+/// Resolve the name a graph gets registered under, handling name collisions.
+///
+/// If `rename_to` is given it is used as-is (and must not collide). Otherwise
+/// `name` is used unless a different graph is already registered under it, in
+/// which case the user is asked for a new name (or an error is returned in
+/// JSON mode).
+///
+/// # Arguments
+///
+/// * `global` - Global args, used for output and to detect JSON mode
+/// * `config` - The loaded configuration
+/// * `name` - The preferred name of the graph
+/// * `path` - The canonical path of the graph
+/// * `rename_to` - An explicit name requested via `--rename-to`
+///
+/// # Returns
+///
+/// * `Result<String>` - The name to register the graph under
+///
+/// # Errors
+///
+/// Returns an error if the name collides and can't be resolved interactively
+pub fn registration_name(
+    global: &GlobalArgs,
+    config: &Config,
+    name: &str,
+    path: &Path,
+    rename_to: Option<&String>,
+) -> Result<String> {
+    if let Some(new_name) = rename_to {
+        if config.name_collision(new_name, path).is_some() {
+            return Err(CliError::name_collision(new_name.as_str()).into());
         }
+        return Ok(new_name.clone());
     }
 
-    path_str
+    let Some(existing) = config.name_collision(name, path) else {
+        return Ok(name.to_string());
+    };
+
+    if global.json {
+        return Err(CliError::name_collision(existing).into());
+    }
+
+    global.warning(&format!(
+        "A different graph is already registered as '{}'",
+        existing
+    ));
+    loop {
+        let input = Text::new("Register as:")
+            .with_help_message("Choose a different name for this graph")
+            .prompt()
+            .map_err(CliError::from)?;
+
+        let input = input.trim();
+        if input.is_empty() {
+            continue;
+        }
+        if config.name_collision(input, path).is_none() {
+            return Ok(input.to_string());
+        }
+        global.warning(&format!("'{}' is taken as well", input));
+    }
 }
 
 /// Global flags available for all commands.
@@ -65,7 +137,7 @@ pub struct GlobalArgs {
     #[arg(long, global = true)]
     pub json: bool,
 
-    /// Target specific graph by name or path (overrides active graph)
+    /// Target specific graph by name or path (overrides FLOW_GRAPH and the active graph)
     #[arg(long, global = true)]
     pub graph: Option<String>,
 
@@ -76,6 +148,10 @@ pub struct GlobalArgs {
     /// Suppress non-error output
     #[arg(short, long, global = true)]
     pub quiet: bool,
+
+    /// Output prefixes: emoji, ascii or minimal (overrides `theme.icons` in the config)
+    #[arg(long, global = true)]
+    pub theme: Option<Icons>,
 }
 
 impl GlobalArgs {
@@ -89,6 +165,11 @@ impl GlobalArgs {
         Term::stderr()
     }
 
+    /// Get the output theme
+    fn styles(&self) -> &'static Styles {
+        theme::styles(self.theme)
+    }
+
     /// Load the target graph based on global flags and config.
     ///
     /// This method respects the `--graph` flag if provided (which can be either
@@ -97,7 +178,7 @@ impl GlobalArgs {
     ///
     /// # Returns
     ///
-    /// * `Result<Graph>` - The loaded graph
+    /// * `Result<Space>` - The loaded graph
     ///
     /// # Errors
     ///
@@ -106,34 +187,125 @@ impl GlobalArgs {
     /// - The specified graph path doesn't exist
     /// - No graph is specified and no active graph is set
     /// - The graph fails to load
-    pub fn load_graph(&self) -> Result<Graph> {
+    pub fn load_graph(&self) -> Result<Space> {
+        self.load_graph_with(Space::load)
+    }
+
+    /// Load the target graph read-only.
+    ///
+    /// Resolves the graph like `load_graph()`, but never writes to it: no
+    /// migration, no saving and no persisted index rebuilds. Used by commands
+    /// that only inspect a graph, so they work on read-only mounts.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Space>` - The loaded graph
+    ///
+    /// # Errors
+    ///
+    /// Same as `load_graph()`, or the graph needs a migration first
+    pub fn load_graph_readonly(&self) -> Result<Space> {
+        self.load_graph_with(Space::load_readonly)
+    }
+
+    /// Returns the graph targeted explicitly, by `--graph` or the session.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - Name or path of the graph, `None` for the active graph
+    pub fn target(&self) -> Option<String> {
+        self.graph.clone().or_else(Config::session_space)
+    }
+
+    /// Load the target graph with the given loader.
+    fn load_graph_with(&self, load: fn(&Path) -> Result<Space>) -> Result<Space> {
         let config = Config::load()?;
 
-        if let Some(ref name_or_path) = self.graph {
+        let mut space = if let Some(ref name_or_path) = self.target() {
             if let Some(graph_config) = config.get_space_config(name_or_path) {
-                Graph::load(&graph_config.path).with_context(|| {
+                load(&graph_config.path).with_context(|| {
                     format!(
                         "Failed to load graph from '{}'",
                         graph_config.path.display()
                     )
-                })
+                })?
             } else {
                 let path = PathBuf::from(name_or_path);
                 if !path.exists() {
                     return Err(CliError::graph_not_found(name_or_path).into());
                 }
-                Graph::load(&path).map_err(|_| CliError::invalid_graph(path.clone()).into())
+                load(&path).map_err(|report| match flow_core::Error::of(&report) {
+                    Some(flow_core::Error::MetadataMissing { .. }) => {
+                        CliError::invalid_graph(path.clone()).into()
+                    }
+                    _ => report,
+                })?
             }
         } else {
             let active = config
                 .get_active_space()
                 .ok_or_else(|| CliError::NoActiveGraph)?;
-            Graph::load(&active.path).with_context(|| {
+            load(&active.path).with_context(|| {
                 format!(
                     "Failed to load active graph from '{}'",
                     active.path.display()
                 )
-            })
+            })?
+        };
+
+        space.set_author(config.author());
+        Ok(space)
+    }
+
+    /// Resolve the target graph path without loading it.
+    ///
+    /// Uses the same `--graph`/active graph resolution as `load_graph()`, but
+    /// stops short of opening the document. Useful for commands that must work
+    /// on graphs which fail to load (e.g. `fsck`).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf>` - The path of the target graph
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The specified graph name is not registered and the path doesn't exist
+    /// - No graph is specified and no active graph is set
+    pub fn graph_path(&self) -> Result<PathBuf> {
+        let config = Config::load()?;
+
+        if let Some(ref name_or_path) = self.target() {
+            if let Some(graph_config) = config.get_space_config(name_or_path) {
+                return Ok(graph_config.path.clone());
+            }
+
+            let path = PathBuf::from(name_or_path);
+            if !path.exists() {
+                return Err(CliError::graph_not_found(name_or_path).into());
+            }
+            Ok(path)
+        } else {
+            config
+                .get_active_space()
+                .map(|active| active.path.clone())
+                .ok_or_else(|| CliError::NoActiveGraph.into())
+        }
+    }
+
+    /// Get a progress receiver for long-running core operations.
+    ///
+    /// Renders progress bars on the terminal, unless `--json` or `--quiet` is
+    /// set, in which case all progress is discarded.
+    ///
+    /// # Returns
+    ///
+    /// * `Box<dyn Progress>` - The progress receiver
+    pub fn progress(&self) -> Box<dyn Progress> {
+        if self.json || self.quiet {
+            Box::new(NoProgress)
+        } else {
+            Box::new(TerminalProgress::new())
         }
     }
 
@@ -165,9 +337,12 @@ impl GlobalArgs {
     /// ```
     pub fn success(&self, message: &str) {
         if !self.quiet && !self.json {
-            let _ =
-                self.term()
-                    .write_line(&format!("{}{}", SUCCESS, style(message).green().bold()));
+            let styles = self.styles();
+            let _ = self.term().write_line(&format!(
+                "{}{}",
+                styles.icon(Icon::Success),
+                styles.success.apply_to(message)
+            ));
         }
     }
 
@@ -185,9 +360,12 @@ impl GlobalArgs {
     /// ```
     pub fn info(&self, message: &str) {
         if !self.quiet && !self.json {
-            let _ = self
-                .term()
-                .write_line(&format!("{}{}", INFO, style(message).cyan()));
+            let styles = self.styles();
+            let _ = self.term().write_line(&format!(
+                "{}{}",
+                styles.icon(Icon::Info),
+                styles.info.apply_to(message)
+            ));
         }
     }
 
@@ -205,9 +383,12 @@ impl GlobalArgs {
     /// ```
     pub fn warning(&self, message: &str) {
         if !self.quiet && !self.json {
-            let _ = self
-                .term()
-                .write_line(&format!("{}{}", WARN, style(message).yellow().bold()));
+            let styles = self.styles();
+            let _ = self.term().write_line(&format!(
+                "{}{}",
+                styles.icon(Icon::Warning),
+                styles.warning.apply_to(message)
+            ));
         }
     }
 
@@ -225,9 +406,12 @@ impl GlobalArgs {
     /// ```
     pub fn step(&self, message: &str) {
         if !self.quiet && !self.json {
-            let _ = self
-                .term()
-                .write_line(&format!("{}{}", ARROW, style(message).dim()));
+            let styles = self.styles();
+            let _ = self.term().write_line(&format!(
+                "{}{}",
+                styles.icon(Icon::Step),
+                style(message).dim()
+            ));
         }
     }
 
@@ -241,9 +425,12 @@ impl GlobalArgs {
     /// * `message` - The verbose message to print
     pub fn print_verbose(&self, message: &str) {
         if self.verbose && !self.quiet && !self.json {
-            let _ = self
-                .term()
-                .write_line(&format!("{}{}", DEBUG, style(message).dim()));
+            let styles = self.styles();
+            let _ = self.term().write_line(&format!(
+                "{}{}",
+                styles.icon(Icon::Debug),
+                style(message).dim()
+            ));
         }
     }
 
@@ -262,9 +449,10 @@ impl GlobalArgs {
     /// ```
     pub fn debug(&self, label: &str, value: &str) {
         if self.verbose && !self.quiet && !self.json {
+            let styles = self.styles();
             let _ = self.term().write_line(&format!(
                 "{}{}: {}",
-                DEBUG,
+                styles.icon(Icon::Debug),
                 style(label).dim(),
                 style(value).dim().italic()
             ));
@@ -281,9 +469,12 @@ impl GlobalArgs {
     /// * `message` - The error message to print
     pub fn print_error(&self, message: &str) {
         if !self.quiet && !self.json {
-            let _ =
-                self.term_err()
-                    .write_line(&format!("{}{}", ERROR, style(message).red().bold()));
+            let styles = self.styles();
+            let _ = self.term_err().write_line(&format!(
+                "{}{}",
+                styles.icon(Icon::Error),
+                styles.error.apply_to(message)
+            ));
         }
     }
 
@@ -301,9 +492,10 @@ impl GlobalArgs {
     /// ```
     pub fn heading(&self, heading: &str) {
         if !self.quiet && !self.json {
+            let styles = self.styles();
             let _ = self.term().write_line(&format!(
                 "{}{}",
-                SPARKLE,
+                styles.icon(Icon::Heading),
                 style(heading).bold().underlined()
             ));
         }
@@ -327,9 +519,10 @@ impl GlobalArgs {
     /// ```
     pub fn kv(&self, key: &str, value: &str) {
         if !self.quiet && !self.json {
+            let styles = self.styles();
             let _ = self.term().write_line(&format!(
                 "  {}: {}",
-                style(key).cyan().bold(),
+                styles.accent.apply_to(key),
                 style(value).white()
             ));
         }
@@ -382,9 +575,10 @@ impl GlobalArgs {
 /// # Example
 ///
 /// ```rust,ignore
+/// use schemars::JsonSchema;
 /// use serde::Serialize;
 ///
-/// #[derive(Serialize)]
+/// #[derive(Serialize, JsonSchema)]
 /// struct InitOutput {
 ///     name: String,
 ///     path: String,
@@ -413,7 +607,7 @@ impl GlobalArgs {
 ///
 ///     fn run(self) -> Result<Self::Output> {
 ///         // Execute the command logic
-///         let graph = Graph::init(&self.args.path)?;
+///         let graph = Space::init(&self.args.path)?;
 ///         Ok(InitOutput {
 ///             name: graph.name().to_string(),
 ///             path: graph.path().display().to_string(),
@@ -433,8 +627,9 @@ pub trait Command: Sized {
     /// The argument type for this command
     type Args;
 
-    /// The output type - must be serializable for JSON output
-    type Output: serde::Serialize;
+    /// The output type - must be serializable for JSON output and describe
+    /// its shape as JSON Schema for `flow schema`
+    type Output: serde::Serialize + schemars::JsonSchema;
 
     /// Create a command instance from parsed arguments
     fn from_args(args: Self::Args) -> Self;
@@ -512,7 +707,7 @@ pub trait Command: Sized {
         }
 
         // Run the command to get structured output
-        let output = self.run()?;
+        let output = self.run().map_err(error::from_core)?;
 
         // Output in appropriate format
         if is_json {
@@ -536,6 +731,7 @@ mod tests {
             graph: None,
             verbose: false,
             quiet: true,
+            theme: None,
         };
 
         // These should not panic, just not print
@@ -550,6 +746,7 @@ mod tests {
             graph: None,
             verbose: true,
             quiet: false,
+            theme: None,
         };
 
         // This would print in real usage, but we can't test output easily
@@ -0,0 +1,502 @@
+//! Background daemon keeping spaces loaded between CLI invocations.
+//!
+//! `flow daemon` listens on a unix socket and serves the same JSON-RPC methods
+//! as `flow rpc` (see [`crate::rpc`]), with an additional `space` parameter
+//! selecting the space a request targets. Spaces are loaded on first use and
+//! kept in memory, and reloaded when another process saved them meanwhile. A
+//! watcher thread rebuilds the indexes whenever markdown files change on disk
+//! and, with the `notify` feature, fires notifications for tasks of registered
+//! spaces that become scheduled or due.
+//!
+//! CLI commands use [`Client::connect`] and [`Client::forward`] to
+//! transparently forward requests to a running daemon and fall back to loading
+//! the space themselves otherwise, or when the request can't be sent. Once a
+//! request was sent the daemon may still carry it out, so a daemon that doesn't
+//! answer in time is an error rather than a reason to run it twice.
+//!
+//! The socket lives in a directory only the current user can access, so no
+//! other local user can bind it first and capture forwarded requests.
+//!
+//! Named pipes (Windows) are not supported yet, there the daemon is unavailable
+//! and commands always run in-process.
+
+use std::path::{Path, PathBuf};
+
+use miette::Result;
+use serde_json::Value;
+
+use crate::error::CliError;
+
+/// Name of the daemon socket file.
+const SOCKET_NAME: &str = "flow.sock";
+
+/// Returns the path of the daemon socket for the current user.
+///
+/// Uses `$XDG_RUNTIME_DIR` when set, a `flow-<uid>` directory in the system
+/// temp directory otherwise.
+pub fn socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join(SOCKET_NAME),
+        None => std::env::temp_dir()
+            .join(format!("flow-{}", user_id()))
+            .join(SOCKET_NAME),
+    }
+}
+
+/// Returns the id of the current user.
+#[cfg(unix)]
+fn user_id() -> String {
+    // SAFETY: getuid has no preconditions and always succeeds
+    unsafe { libc::getuid() }.to_string()
+}
+
+/// Returns the name of the current user.
+#[cfg(not(unix))]
+fn user_id() -> String {
+    std::env::var("USERNAME").unwrap_or_default()
+}
+
+#[cfg(unix)]
+pub use unix::{serve, Client};
+
+#[cfg(not(unix))]
+pub use unsupported::{serve, Client};
+
+#[cfg(unix)]
+mod unix {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::io::{BufRead, BufReader, ErrorKind, Write};
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    use chrono::Local;
+    use flow_core::cancel::CancellationToken;
+    use flow_core::config::Config;
+    use flow_core::fulltext::TextIndex;
+    use flow_core::index::PageIndex;
+    use flow_core::paths;
+    use flow_core::progress::NoProgress;
+    use flow_core::space::{self, Space};
+    use miette::IntoDiagnostic;
+    use serde_json::json;
+
+    use super::*;
+    use crate::notify;
+    use crate::rpc::Server;
+
+    /// How often the watcher checks loaded spaces for changed files.
+    const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+    /// How long a client waits for the daemon to answer a request.
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Spaces loaded by the daemon, keyed by normalized path.
+    type Servers = Arc<Mutex<HashMap<PathBuf, Served>>>;
+
+    /// Server of a loaded space and the state of its files when it last served a request.
+    struct Served {
+        server: Server,
+        on_disk: Vec<Option<(u64, SystemTime)>>,
+    }
+
+    /// Why a request to the daemon failed.
+    enum Failure {
+        /// The request never reached the daemon, it can run in-process instead.
+        Unsent(CliError),
+        /// The daemon received the request, it may have carried it out.
+        Sent(CliError),
+    }
+
+    impl From<Failure> for CliError {
+        fn from(failure: Failure) -> Self {
+            match failure {
+                Failure::Unsent(err) | Failure::Sent(err) => err,
+            }
+        }
+    }
+
+    /// Connection to a running daemon.
+    pub struct Client {
+        reader: BufReader<UnixStream>,
+        next_id: u64,
+    }
+
+    impl Client {
+        /// Connects to the daemon if one is running.
+        ///
+        /// # Returns
+        ///
+        /// * `Option<Self>` - The connection, `None` if no daemon is listening
+        pub fn connect() -> Option<Self> {
+            let socket = socket_path();
+            // Whoever could create the socket could read the requests
+            if !is_private(socket.parent()?) {
+                return None;
+            }
+            let stream = UnixStream::connect(socket).ok()?;
+            stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+            stream.set_write_timeout(Some(TIMEOUT)).ok()?;
+            Some(Self {
+                reader: BufReader::new(stream),
+                next_id: 1,
+            })
+        }
+
+        /// Sends a request to the daemon and waits for its result.
+        ///
+        /// # Arguments
+        ///
+        /// * `space` - Path of the space the request targets, if any
+        /// * `method` - The JSON-RPC method
+        /// * `params` - The method parameters (an object)
+        ///
+        /// # Returns
+        ///
+        /// * `Result<Value>` - The result returned by the daemon
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the connection fails, the daemon doesn't answer in
+        /// time or reports an error.
+        pub fn call(&mut self, space: Option<&Path>, method: &str, params: Value) -> Result<Value> {
+            self.request(space, method, params)
+                .map_err(|failure| CliError::from(failure).into())
+        }
+
+        /// Sends a request to the daemon unless it can't be sent.
+        ///
+        /// # Arguments
+        ///
+        /// * `space` - Path of the space the request targets, if any
+        /// * `method` - The JSON-RPC method
+        /// * `params` - The method parameters (an object)
+        ///
+        /// # Returns
+        ///
+        /// * `Result<Option<Value>>` - The result returned by the daemon, `None` if
+        ///   the request couldn't be sent and should run in-process
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the daemon doesn't answer in time after receiving
+        /// the request, or reports an error.
+        pub fn forward(
+            &mut self,
+            space: Option<&Path>,
+            method: &str,
+            params: Value,
+        ) -> Result<Option<Value>> {
+            match self.request(space, method, params) {
+                Ok(result) => Ok(Some(result)),
+                Err(Failure::Unsent(_)) => Ok(None),
+                Err(Failure::Sent(err)) => Err(err.into()),
+            }
+        }
+
+        /// Sends a request to the daemon and reads its response.
+        fn request(
+            &mut self,
+            space: Option<&Path>,
+            method: &str,
+            params: Value,
+        ) -> std::result::Result<Value, Failure> {
+            let mut params = match params {
+                Value::Object(map) => map,
+                _ => Default::default(),
+            };
+            if let Some(space) = space {
+                params.insert(
+                    "space".to_string(),
+                    json!(paths::normalize(space).to_string_lossy()),
+                );
+            }
+
+            let id = self.next_id;
+            self.next_id += 1;
+            let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+            // A partially written request doesn't parse, the daemon never runs it
+            let stream = self.reader.get_mut();
+            writeln!(stream, "{}", request)
+                .and_then(|_| stream.flush())
+                .map_err(|err| Failure::Unsent(io_error(err)))?;
+
+            let mut line = String::new();
+            self.reader
+                .read_line(&mut line)
+                .map_err(|err| Failure::Sent(io_error(err)))?;
+            let mut response: Value =
+                serde_json::from_str(&line).map_err(|err| Failure::Sent(err.into()))?;
+
+            if let Some(error) = response.get("error") {
+                let message = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error");
+                return Err(Failure::Sent(CliError::daemon_request(message)));
+            }
+            Ok(response
+                .get_mut("result")
+                .map(Value::take)
+                .unwrap_or_default())
+        }
+    }
+
+    /// Converts an IO error on the socket, telling timeouts apart.
+    fn io_error(err: std::io::Error) -> CliError {
+        match err.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => CliError::DaemonUnresponsive,
+            _ => CliError::from(err),
+        }
+    }
+
+    /// Checks that a directory is owned by the current user and inaccessible to others.
+    fn is_private(dir: &Path) -> bool {
+        fs::metadata(dir).is_ok_and(|metadata| {
+            // SAFETY: getuid has no preconditions and always succeeds
+            metadata.uid() == unsafe { libc::getuid() } && metadata.mode() & 0o077 == 0
+        })
+    }
+
+    /// Runs the daemon until a `shutdown` request is received.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - Path of the socket to listen on
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize>` - Number of served connections
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another daemon is running, the socket directory is
+    /// accessible by other users or the socket can't be bound.
+    pub fn serve(socket: &Path) -> Result<usize> {
+        if let Some(dir) = socket.parent() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                .into_diagnostic()?;
+            if !is_private(dir) {
+                return Err(CliError::daemon_insecure(dir).into());
+            }
+        }
+        if UnixStream::connect(socket).is_ok() {
+            return Err(CliError::daemon_running(socket).into());
+        }
+        // Left behind by a daemon that didn't shut down cleanly
+        let _ = fs::remove_file(socket);
+
+        let listener = UnixListener::bind(socket).into_diagnostic()?;
+        let servers: Servers = Arc::default();
+        let running = Arc::new(AtomicBool::new(true));
+
+        {
+            let servers = Arc::clone(&servers);
+            let running = Arc::clone(&running);
+            thread::spawn(move || watch(servers, running));
+        }
+
+        let mut connections = 0;
+        for stream in listener.incoming() {
+            if !running.load(Ordering::SeqCst) {
+                break;
+            }
+            let Ok(stream) = stream else {
+                continue;
+            };
+            connections += 1;
+
+            let servers = Arc::clone(&servers);
+            let running = Arc::clone(&running);
+            let socket = socket.to_path_buf();
+            thread::spawn(move || {
+                let _ = handle_connection(stream, &servers, &running);
+                if !running.load(Ordering::SeqCst) {
+                    // Wake up the accept loop so it notices the shutdown
+                    let _ = UnixStream::connect(&socket);
+                }
+            });
+        }
+
+        let _ = fs::remove_file(socket);
+        Ok(connections)
+    }
+
+    /// Serves all requests of a single client connection.
+    fn handle_connection(
+        stream: UnixStream,
+        servers: &Servers,
+        running: &AtomicBool,
+    ) -> std::io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        let reader = BufReader::new(stream);
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = route(&line, servers, running);
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+
+            if !running.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Routes a request to the server of the space it targets.
+    fn route(line: &str, servers: &Servers, running: &AtomicBool) -> String {
+        let request: Value = serde_json::from_str(line).unwrap_or_default();
+        let id = request.get("id").cloned().unwrap_or_default();
+        let error = |message: String| {
+            json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32602, "message": message } })
+                .to_string()
+        };
+
+        if request.get("method").and_then(Value::as_str) == Some("shutdown") {
+            running.store(false, Ordering::SeqCst);
+            return json!({ "jsonrpc": "2.0", "id": id, "result": null }).to_string();
+        }
+
+        let Some(space) = request.pointer("/params/space").and_then(Value::as_str) else {
+            return error("Missing string parameter 'space'".to_string());
+        };
+        let path = paths::normalize(Path::new(space));
+
+        let mut servers = servers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Other Flow processes saved the space meanwhile, the loaded document is stale
+        if servers
+            .get(&path)
+            .is_some_and(|served| served.on_disk != space::disk_state(&path))
+        {
+            servers.remove(&path);
+        }
+        if !servers.contains_key(&path) {
+            match Space::load(&path) {
+                Ok(mut space) => {
+                    if let Ok(config) = Config::load() {
+                        space.set_author(config.author());
+                    }
+                    servers.insert(
+                        path.clone(),
+                        Served {
+                            server: Server::new(space),
+                            on_disk: space::disk_state(&path),
+                        },
+                    );
+                }
+                Err(err) => return error(format!("Failed to load space: {}", err)),
+            }
+        }
+
+        let Some(served) = servers.get_mut(&path) else {
+            return String::new();
+        };
+        let response = served.server.handle(line).unwrap_or_default();
+        served.on_disk = space::disk_state(&path);
+        response
+    }
+
+    /// Rebuilds the indexes of loaded spaces whose files changed on disk and fires reminders.
+    fn watch(servers: Servers, running: Arc<AtomicBool>) {
+        let mut reminded = Local::now().timestamp();
+        while running.load(Ordering::SeqCst) {
+            thread::sleep(WATCH_INTERVAL);
+
+            let now = Local::now().timestamp();
+            if notify::supported() && now - reminded >= notify::CHECK_INTERVAL as i64 {
+                remind(reminded, now);
+                reminded = now;
+            }
+
+            let paths: Vec<PathBuf> = match servers.lock() {
+                Ok(servers) => servers.keys().cloned().collect(),
+                Err(_) => return,
+            };
+
+            for path in paths {
+                let Ok(index) = PageIndex::load(&path) else {
+                    continue;
+                };
+                if index.is_stale(&path).unwrap_or(false) {
+                    if let Ok(index) = PageIndex::rebuild(&path) {
+                        let _ = index.save(&path);
+                    }
+                    let cancel = CancellationToken::new();
+                    if let Ok(text_index) = TextIndex::rebuild(&path, &NoProgress, &cancel) {
+                        let _ = text_index.save(&path);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fires the reminders of all registered spaces in a time range.
+    fn remind(from: i64, until: i64) {
+        let Ok(config) = Config::load() else {
+            return;
+        };
+        for (_, space_config) in config.all_spaces() {
+            if !space_config.notifications.enabled {
+                continue;
+            }
+            if let Ok(space) = Space::load_readonly(&space_config.path) {
+                let _ = notify::fire(&space, &space_config.notifications, from, until);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use super::*;
+
+    /// Connection to a running daemon (unavailable on this platform).
+    pub struct Client;
+
+    impl Client {
+        /// Always returns `None`, there is no daemon on this platform.
+        pub fn connect() -> Option<Self> {
+            None
+        }
+
+        /// Never called, as no client can be connected.
+        pub fn forward(
+            &mut self,
+            _space: Option<&Path>,
+            _method: &str,
+            _params: Value,
+        ) -> Result<Option<Value>> {
+            Err(CliError::DaemonNotRunning.into())
+        }
+
+        /// Never called, as no client can be connected.
+        pub fn call(
+            &mut self,
+            _space: Option<&Path>,
+            _method: &str,
+            _params: Value,
+        ) -> Result<Value> {
+            Err(CliError::DaemonNotRunning.into())
+        }
+    }
+
+    /// Fails, the daemon requires unix sockets.
+    pub fn serve(_socket: &Path) -> Result<usize> {
+        Err(CliError::DaemonUnsupported.into())
+    }
+}
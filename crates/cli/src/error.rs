@@ -29,6 +29,30 @@ pub enum CliError {
         path: PathBuf,
     },
 
+    /// Graph name already taken by another graph
+    #[error("Graph name '{name}' is already taken")]
+    #[diagnostic(
+        code(flow::graph::name_collision),
+        help("A different graph is registered under this name.\nUse --rename-to <name> to register this graph under another name.")
+    )]
+    NameCollision {
+        /// The name that is already taken
+        name: String,
+    },
+
+    /// Graph would be nested inside another graph
+    #[error("Graph would be nested inside another graph at {}", parent.display())]
+    #[diagnostic(
+        code(flow::graph::nested),
+        help("Choose a path outside of the existing graph or add notes to it instead")
+    )]
+    NestedGraph {
+        /// The path where the graph was to be initialized
+        path: PathBuf,
+        /// The root of the enclosing graph
+        parent: PathBuf,
+    },
+
     /// No active graph set
     #[error("No active graph")]
     #[diagnostic(
@@ -56,6 +80,61 @@ pub enum CliError {
         path: PathBuf,
     },
 
+    /// Docs graph outside of a git repository
+    #[error("{} is not inside a git repository", path.display())]
+    #[diagnostic(
+        code(flow::graph::not_in_repository),
+        help("Docs graphs live in a code repository, e.g.: flow init --docs docs/notes\nDrop --docs to initialize a standalone graph")
+    )]
+    NotInRepository {
+        /// The path where the graph was to be initialized
+        path: PathBuf,
+    },
+
+    /// No project detected for `flow project`
+    #[error("{} is not inside a git repository", path.display())]
+    #[diagnostic(
+        code(flow::project::not_found),
+        help("Run it inside a git repository or name the project with: --project <name>")
+    )]
+    NoProject {
+        /// The directory the project was detected from
+        path: PathBuf,
+    },
+
+    /// No mailbox configured for `flow ingest mail`
+    #[error("No mailbox configured for graph '{name}'")]
+    #[diagnostic(
+        code(flow::ingest::not_configured),
+        help("Add an [ingest.mail] section to .flow/space.toml, e.g.:\n[ingest.mail]\nkind = \"maildir\"\npath = \"/home/me/Mail/flow\"")
+    )]
+    MailNotConfigured {
+        /// The name of the graph
+        name: String,
+    },
+
+    /// No OCR backend configured for `flow attach --ocr`
+    #[error("No OCR backend configured for graph '{name}'")]
+    #[diagnostic(
+        code(flow::attach::ocr_not_configured),
+        help("Add an [ocr] section to .flow/space.toml, e.g.:\n[ocr]\nkind = \"tesseract\"\nlanguages = \"eng\"")
+    )]
+    OcrNotConfigured {
+        /// The name of the graph
+        name: String,
+    },
+
+    /// No language model configured for `flow summarize` and `flow ask`
+    #[error("No language model configured for graph '{name}'")]
+    #[diagnostic(
+        code(flow::ai::not_configured),
+        help("Add an [ai] section to .flow/space.toml, e.g.:\n[ai]\nendpoint = \"http://localhost:11434/v1/chat/completions\"\nmodel = \"llama3.1\"")
+    )]
+    AiNotConfigured {
+        /// The name of the graph
+        name: String,
+    },
+
     /// Invalid graph structure
     #[error("Invalid graph structure")]
     #[diagnostic(
@@ -67,6 +146,32 @@ pub enum CliError {
         path: PathBuf,
     },
 
+    /// Graph document can't be loaded
+    #[error("Graph document at {} is corrupt", path.display())]
+    #[diagnostic(
+        code(flow::graph::corrupt),
+        help("{message}\nRecover it from a backup or the markdown files with: flow recover")
+    )]
+    CorruptGraph {
+        /// The graph with the corrupt document
+        path: PathBuf,
+        /// The import error
+        message: String,
+    },
+
+    /// Graph format is newer than supported
+    #[error("Graph format {found} is newer than the supported format {supported}")]
+    #[diagnostic(
+        code(flow::graph::unsupported_format),
+        help("Upgrade Flow to modify this graph")
+    )]
+    UnsupportedFormat {
+        /// Format of the graph
+        found: u32,
+        /// Newest format this version supports
+        supported: u32,
+    },
+
     /// Configuration error
     #[error("Configuration error")]
     #[diagnostic(code(flow::config::error))]
@@ -86,6 +191,130 @@ pub enum CliError {
         source: std::io::Error,
     },
 
+    /// Page not found
+    #[error("Page '{name}' not found")]
+    #[diagnostic(
+        code(flow::page::not_found),
+        help("List available pages with: flow pages")
+    )]
+    PageNotFound {
+        /// The page name that was not found
+        name: String,
+    },
+
+    /// Page is locked
+    #[error("Page '{name}' is locked")]
+    #[diagnostic(
+        code(flow::page::locked),
+        help("Unlock it with: flow unlock {name}\nOr pass --force to change it anyway")
+    )]
+    PageLocked {
+        /// The locked page
+        name: String,
+    },
+
+    /// Daemon is already running
+    #[error("Daemon is already running at {}", socket.display())]
+    #[diagnostic(
+        code(flow::daemon::running),
+        help("Stop it first with: flow daemon --stop")
+    )]
+    DaemonRunning {
+        /// The socket the daemon listens on
+        socket: PathBuf,
+    },
+
+    /// Daemon is not running
+    #[error("Daemon is not running")]
+    #[diagnostic(code(flow::daemon::not_running), help("Start it with: flow daemon"))]
+    DaemonNotRunning,
+
+    /// Daemon mode is not available on this platform
+    #[error("Daemon mode is not supported on this platform")]
+    #[diagnostic(code(flow::daemon::unsupported))]
+    DaemonUnsupported,
+
+    /// Daemon didn't answer a forwarded request in time
+    #[error("Daemon did not respond")]
+    #[diagnostic(
+        code(flow::daemon::unresponsive),
+        help("The daemon may still complete the request, check the graph before retrying\nRestart it with: flow daemon --stop && flow daemon")
+    )]
+    DaemonUnresponsive,
+
+    /// The daemon socket directory is accessible by other users
+    #[error("Daemon socket directory '{}' is not private", dir.display())]
+    #[diagnostic(
+        code(flow::daemon::insecure),
+        help("It must be owned by you and inaccessible to other users (mode 0700)")
+    )]
+    DaemonInsecure {
+        /// The directory holding the socket
+        dir: PathBuf,
+    },
+
+    /// Request forwarded to the daemon failed
+    #[error("Daemon request failed: {message}")]
+    #[diagnostic(code(flow::daemon::request))]
+    DaemonRequest {
+        /// The error message returned by the daemon
+        message: String,
+    },
+
+    /// The editor could not be started or failed
+    #[error("Editor '{editor}' failed: {message}")]
+    #[diagnostic(
+        code(flow::editor),
+        help("Set `editor` in the config (see flow setup), or $VISUAL or $EDITOR, to the editor command to use")
+    )]
+    Editor {
+        /// The editor command
+        editor: String,
+        /// What went wrong
+        message: String,
+    },
+
+    /// Neither a built-in command nor a plugin
+    #[error("Unknown command '{name}'")]
+    #[diagnostic(
+        code(flow::command::unknown),
+        help("List the built-in commands with: flow --help\nPlugins are 'flow-<name>' executables on PATH")
+    )]
+    UnknownCommand {
+        /// The command that was invoked
+        name: String,
+    },
+
+    /// No output schema exists for the command, reported by `flow schema`
+    #[error("No output schema for command '{name}'")]
+    #[diagnostic(
+        code(flow::schema::unknown),
+        help("List the commands with an output schema with: flow schema")
+    )]
+    UnknownSchema {
+        /// The command that was requested
+        name: String,
+    },
+
+    /// No examples exist for the command, reported by `flow examples`
+    #[error("No examples for command '{name}'")]
+    #[diagnostic(
+        code(flow::examples::unknown),
+        help("List all examples with: flow examples")
+    )]
+    NoExamples {
+        /// The command that was requested
+        name: String,
+    },
+
+    /// Pages are not formatted, reported by `flow fmt --check`
+    #[error("Pages are not formatted: {pages}")]
+    #[diagnostic(code(flow::fmt::unformatted), help("Format them with: flow fmt"))]
+    Unformatted {
+        /// Names of the unformatted pages
+        pages: String,
+    },
+
     /// Interactive mode cancelled
     #[error("Operation cancelled")]
     #[diagnostic(code(flow::interactive::cancelled))]
@@ -113,6 +342,44 @@ impl CliError {
         Self::GraphAlreadyExists { path: path.into() }
     }
 
+    /// Create a NameCollision error
+    pub fn name_collision(name: impl Into<String>) -> Self {
+        Self::NameCollision { name: name.into() }
+    }
+
+    /// Create a NotInRepository error
+    pub fn not_in_repository(path: impl Into<PathBuf>) -> Self {
+        Self::NotInRepository { path: path.into() }
+    }
+
+    /// Create a NoProject error
+    pub fn no_project(path: impl Into<PathBuf>) -> Self {
+        Self::NoProject { path: path.into() }
+    }
+
+    /// Create a MailNotConfigured error
+    pub fn mail_not_configured(name: impl Into<String>) -> Self {
+        Self::MailNotConfigured { name: name.into() }
+    }
+
+    /// Create an OcrNotConfigured error
+    pub fn ocr_not_configured(name: impl Into<String>) -> Self {
+        Self::OcrNotConfigured { name: name.into() }
+    }
+
+    /// Create an AiNotConfigured error
+    pub fn ai_not_configured(name: impl Into<String>) -> Self {
+        Self::AiNotConfigured { name: name.into() }
+    }
+
+    /// Create a NestedGraph error
+    pub fn nested_graph(path: impl Into<PathBuf>, parent: impl Into<PathBuf>) -> Self {
+        Self::NestedGraph {
+            path: path.into(),
+            parent: parent.into(),
+        }
+    }
+
     /// Create a PathNotFound error
     pub fn path_not_found(path: impl Into<PathBuf>) -> Self {
         Self::PathNotFound { path: path.into() }
@@ -130,6 +397,65 @@ impl CliError {
         }
     }
 
+    /// Create a PageNotFound error
+    pub fn page_not_found(name: impl Into<String>) -> Self {
+        Self::PageNotFound { name: name.into() }
+    }
+
+    /// Create a PageLocked error
+    pub fn page_locked(name: impl Into<String>) -> Self {
+        Self::PageLocked { name: name.into() }
+    }
+
+    /// Create a DaemonRunning error
+    pub fn daemon_running(socket: impl Into<PathBuf>) -> Self {
+        Self::DaemonRunning {
+            socket: socket.into(),
+        }
+    }
+
+    /// Create a DaemonInsecure error
+    pub fn daemon_insecure(dir: impl Into<PathBuf>) -> Self {
+        Self::DaemonInsecure { dir: dir.into() }
+    }
+
+    /// Create a DaemonRequest error
+    pub fn daemon_request(message: impl Into<String>) -> Self {
+        Self::DaemonRequest {
+            message: message.into(),
+        }
+    }
+
+    /// Create an Editor error
+    pub fn editor(editor: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Editor {
+            editor: editor.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Create an UnknownCommand error
+    pub fn unknown_command(name: impl Into<String>) -> Self {
+        Self::UnknownCommand { name: name.into() }
+    }
+
+    /// Create an UnknownSchema error
+    pub fn unknown_schema(name: impl Into<String>) -> Self {
+        Self::UnknownSchema { name: name.into() }
+    }
+
+    /// Create a NoExamples error
+    pub fn no_examples(name: impl Into<String>) -> Self {
+        Self::NoExamples { name: name.into() }
+    }
+
+    /// Create an Unformatted error
+    pub fn unformatted(pages: &[String]) -> Self {
+        Self::Unformatted {
+            pages: pages.join(", "),
+        }
+    }
+
     /// Create an IoError
     pub fn io_error(source: std::io::Error, path: Option<PathBuf>) -> Self {
         Self::IoError { path, source }
@@ -144,7 +470,47 @@ impl CliError {
 /// Result type for CLI operations
 pub type Result<T> = std::result::Result<T, CliError>;
 
+/// Converts a report from flow_core into a CLI diagnostic where possible.
+///
+/// Reports caused by a typed `flow_core::Error` become the matching
+/// `CliError`, all other reports are returned unchanged.
+///
+/// # Arguments
+///
+/// * `report` - The report returned by a core function
+///
+/// # Returns
+///
+/// * `miette::Report` - The report to show
+pub fn from_core(report: miette::Report) -> miette::Report {
+    match report.downcast::<flow_core::Error>() {
+        Ok(err) => CliError::from(err).into(),
+        Err(report) => report,
+    }
+}
+
 // Implement From for common error types
+impl From<flow_core::Error> for CliError {
+    fn from(err: flow_core::Error) -> Self {
+        match err {
+            flow_core::Error::Io { path, source } => Self::IoError {
+                path: Some(path),
+                source,
+            },
+            flow_core::Error::Crdt { path, message } => Self::CorruptGraph { path, message },
+            flow_core::Error::MetadataMissing { path } => Self::InvalidGraph { path },
+            flow_core::Error::PageNotFound { name } => Self::PageNotFound { name },
+            flow_core::Error::PageLocked { name } => Self::PageLocked { name },
+            flow_core::Error::VersionMismatch { found, supported } => {
+                Self::UnsupportedFormat { found, supported }
+            }
+            err @ flow_core::Error::Parse { .. } => Self::Other {
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
 impl From<std::io::Error> for CliError {
     fn from(err: std::io::Error) -> Self {
         Self::IoError {
@@ -0,0 +1,253 @@
+//! Usage examples of the commands.
+//!
+//! Every example lives in the [`EXAMPLES`] registry instead of the help texts
+//! of the command modules. The registry feeds both the `Examples:` section of
+//! the long help (`flow add --help`, see [`with_examples`]) and the
+//! `flow examples` command, so the two never drift apart.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// A copy-pasteable usage example.
+///
+/// # Fields
+///
+/// * `command` - Command the example belongs to, as invoked on the command line
+/// * `description` - What the example does
+/// * `command_line` - The shell command line, possibly a pipeline
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct Example {
+    pub command: &'static str,
+    pub description: &'static str,
+    pub command_line: &'static str,
+}
+
+/// Shorthand for the entries of [`EXAMPLES`].
+const fn example(
+    command: &'static str,
+    description: &'static str,
+    command_line: &'static str,
+) -> Example {
+    Example {
+        command,
+        description,
+        command_line,
+    }
+}
+
+/// All examples, grouped by command in the order shown.
+pub const EXAMPLES: &[Example] = &[
+    example(
+        "add",
+        "Quick capture to today's journal",
+        "flow add \"Call the plumber\"",
+    ),
+    example(
+        "add",
+        "Capture a task",
+        "flow add \"TODO Review the sync PR\"",
+    ),
+    example(
+        "add",
+        "Capture the output of another command",
+        "flow add \"$(git log -1 --format=%s)\"",
+    ),
+    example(
+        "add",
+        "Add many nested blocks at once from another command",
+        "printf 'Standup\\n  Shipped the importer\\n  Blocked on review\\n' | flow add --file -",
+    ),
+    example(
+        "add",
+        "Try a capture without saving anything",
+        "flow add --scratch \"Just testing\"",
+    ),
+    example(
+        "clean",
+        "Find stale graph data and fix it",
+        "flow clean --deep",
+    ),
+    example(
+        "clean",
+        "Fix everything without asking",
+        "flow clean --deep --yes",
+    ),
+    example(
+        "config",
+        "Move the configuration to another machine",
+        "flow config export flow.toml && flow config import flow.toml --merge",
+    ),
+    example(
+        "config",
+        "Revert the last configuration change",
+        "flow config undo",
+    ),
+    example(
+        "init",
+        "Scaffold a graph from a template of the registry",
+        "flow init ~/notes/team --template meeting-notes --var team=Platform",
+    ),
+    example(
+        "open",
+        "Create a graph if needed and make it active",
+        "flow open ~/notes/work --create",
+    ),
+    example(
+        "open",
+        "Use a graph in this terminal only",
+        "export FLOW_GRAPH=\"$(flow open --session work)\"",
+    ),
+    example(
+        "open",
+        "Change into a graph's directory",
+        "cd \"$(flow open --print-path work)\"",
+    ),
+    example(
+        "rollover",
+        "Carry open tasks over to today",
+        "flow rollover",
+    ),
+    example(
+        "schema",
+        "Print the JSON output schema of a command",
+        "flow schema search",
+    ),
+    example(
+        "search",
+        "Search pages and journal",
+        "flow search \"merge queue\"",
+    ),
+    example(
+        "search",
+        "Print the ids of matching pages for scripts",
+        "flow search sync --json | jq -r '.hits[].id'",
+    ),
+    example(
+        "search",
+        "Search recent journal days with a tag",
+        "flow search standup --journal-only --tag work --since 2024-01-01",
+    ),
+    example(
+        "setup",
+        "Set up Flow without prompts",
+        "flow setup ~/notes --granularity daily --template --shell zsh --git",
+    ),
+    example(
+        "shell-init",
+        "Install the shell integration",
+        "eval \"$(flow shell-init zsh)\"",
+    ),
+    example("show", "Print a page", "flow show \"Sync rewrite\""),
+    example("show", "Review this week's journal", "flow show --week"),
+    example(
+        "show",
+        "Print a page as markdown instead of rendered tables and diagrams",
+        "flow show Budget --plain",
+    ),
+    example("stats", "Count pages, words and tasks", "flow stats"),
+    example(
+        "stats",
+        "Print the number of open tasks",
+        "flow stats --json | jq .open_tasks",
+    ),
+    example(
+        "status",
+        "Show the graph in a shell prompt",
+        "PS1='$(flow status --porcelain | cut -f1) '\"$PS1\"",
+    ),
+    example(
+        "status",
+        "Check what changed since the last save",
+        "flow status",
+    ),
+    example(
+        "template",
+        "Add a template from a git repository",
+        "flow template add https://github.com/me/flow-templates.git --name meeting-notes",
+    ),
+    example(
+        "template",
+        "Show the templates and their variables",
+        "flow template list",
+    ),
+    example(
+        "today",
+        "Open today's journal page in the editor",
+        "flow today --edit",
+    ),
+    example(
+        "wc",
+        "Count the words written this year",
+        "flow wc --since 2024-01-01",
+    ),
+];
+
+/// Returns the examples of a command.
+///
+/// # Arguments
+///
+/// * `command` - Command name as invoked on the command line
+///
+/// # Returns
+///
+/// * `Vec<Example>` - The examples, empty if the command has none
+pub fn for_command(command: &str) -> Vec<Example> {
+    EXAMPLES
+        .iter()
+        .filter(|example| example.command == command)
+        .copied()
+        .collect()
+}
+
+/// Renders examples as a help section.
+///
+/// # Arguments
+///
+/// * `examples` - Examples to render
+///
+/// # Returns
+///
+/// * `String` - One `# description` comment followed by the command line per example
+pub fn render(examples: &[Example]) -> String {
+    examples
+        .iter()
+        .map(|example| {
+            format!(
+                "  # {}\n  {}",
+                example.description,
+                indent(example.command_line)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Adds the examples of every subcommand to its long help.
+///
+/// # Arguments
+///
+/// * `command` - The top-level `flow` command
+///
+/// # Returns
+///
+/// * `clap::Command` - The command with an `Examples:` section after the long help of each subcommand
+pub fn with_examples(mut command: clap::Command) -> clap::Command {
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    for name in names {
+        let examples = for_command(&name);
+        if examples.is_empty() {
+            continue;
+        }
+        let help = format!("Examples:\n{}", render(&examples));
+        command = command.mut_subcommand(name, |sub| sub.after_long_help(help));
+    }
+    command
+}
+
+/// Indents the continuation lines of a multi-line command line.
+fn indent(command_line: &str) -> String {
+    command_line.replace('\n', "\n  ")
+}
@@ -0,0 +1,37 @@
+//! Ctrl-C handling for long-running commands.
+
+use std::sync::OnceLock;
+
+use flow_core::cancel::CancellationToken;
+
+/// Exit code used when the process is interrupted a second time.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+
+/// Returns the process-wide cancellation token, cancelled by Ctrl-C.
+///
+/// The handler is installed on first use. The first Ctrl-C cancels the token
+/// so the running operation can stop at its next safe checkpoint; a second
+/// Ctrl-C exits immediately.
+///
+/// # Returns
+///
+/// * `CancellationToken` - Token shared with the Ctrl-C handler
+pub fn token() -> CancellationToken {
+    TOKEN
+        .get_or_init(|| {
+            let token = CancellationToken::new();
+            let handler_token = token.clone();
+            // Failing to install the handler only means Ctrl-C kills the process as before
+            let _ = ctrlc::set_handler(move || {
+                if handler_token.is_cancelled() {
+                    std::process::exit(INTERRUPTED_EXIT_CODE);
+                }
+                eprintln!("Cancelling, press Ctrl-C again to force quit");
+                handler_token.cancel();
+            });
+            token
+        })
+        .clone()
+}
@@ -17,10 +17,19 @@
 
 pub mod commands;
 pub mod common;
+pub mod daemon;
 pub mod error;
+pub mod examples;
+pub mod interrupt;
+pub mod notify;
+pub mod plugins;
+pub mod progress;
+pub mod rpc;
+pub mod theme;
 
 use clap::Subcommand;
 use miette::Result;
+use std::ffi::OsString;
 
 use crate::common::Command;
 
@@ -38,8 +47,199 @@ pub enum Commands {
     /// Add a node to today's journal page
     Add(commands::add::AddArgs),
 
-    /// Remove orphaned graphs from configuration
+    /// Remove orphaned graphs from configuration, with --deep also stale graph data
     Clean(commands::clean::CleanArgs),
+
+    /// Check graph integrity and repair divergence
+    Fsck(commands::fsck::FsckArgs),
+
+    /// List or restore document backups
+    Backup(commands::backup::BackupArgs),
+
+    /// Trim old document history to reduce snapshot size
+    Compact(commands::compact::CompactArgs),
+
+    /// Migrate the graph to the current on-disk format
+    Migrate(commands::migrate::MigrateArgs),
+
+    /// List pages of the graph
+    Pages(commands::pages::PagesArgs),
+
+    /// Serve JSON-RPC requests for editor plugins
+    Rpc(commands::rpc::RpcArgs),
+
+    /// Run the background daemon that keeps graphs loaded
+    Daemon(commands::daemon::DaemonArgs),
+
+    /// Maintain the page and full-text indexes of the graph
+    Index(commands::index::IndexArgs),
+
+    /// Show a page or a range of journal pages
+    Show(commands::show::ShowArgs),
+
+    /// Search pages, optionally scoped to journal, pages, tags or paths
+    Search(commands::search::SearchArgs),
+
+    /// Find pages similar in meaning to a page
+    #[cfg(feature = "semantic")]
+    Similar(commands::similar::SimilarArgs),
+
+    /// Find unlinked mentions of a page, optionally linking them
+    Mentions(commands::mentions::MentionsArgs),
+
+    /// Check wikilinks for dead targets and orphan pages
+    Links(commands::links::LinksArgs),
+
+    /// Find duplicate pages and blocks, optionally merging duplicate pages
+    Dedupe(commands::dedupe::DedupeArgs),
+
+    /// Merge a page into another, rewriting links to it, and trash it
+    Merge(commands::merge::MergeArgs),
+
+    /// Split a page into one page per top-level heading
+    Split(commands::split::SplitArgs),
+
+    /// Rename a page or namespace with all pages below it, rewriting links
+    Rename(commands::rename::RenameArgs),
+
+    /// Indent, outdent or move blocks of the outline
+    Block(commands::block::BlockArgs),
+
+    /// Carry open tasks of the previous journal page over to today
+    Rollover(commands::rollover::RolloverArgs),
+
+    /// Show, undo, export or import the Flow configuration
+    Config(commands::config::ConfigArgs),
+
+    /// Show statistics of the graph, cached until it changes
+    Stats(commands::stats::StatsArgs),
+
+    /// Print copy-pasteable usage examples of the commands
+    Examples(commands::examples::ExamplesArgs),
+
+    /// Set up Flow step by step on first run
+    Setup(commands::setup::SetupArgs),
+
+    /// List, add or remove the space templates used by flow init --template
+    Template(commands::template::TemplateArgs),
+
+    /// List recently modified pages
+    Recent(commands::recent::RecentArgs),
+
+    /// Show or edit today's journal page
+    Today(commands::day::DayArgs),
+
+    /// Show or edit yesterday's journal page
+    Yesterday(commands::day::DayArgs),
+
+    /// Show or edit tomorrow's journal page
+    Tomorrow(commands::day::DayArgs),
+
+    /// Attach a file to today's journal page, optionally recognizing the text of images
+    Attach(commands::attach::AttachArgs),
+
+    /// Capture meeting notes into today's journal page
+    Meeting(commands::meeting::MeetingArgs),
+
+    /// Capture notes and tasks on a page named after the current git repository
+    Project(commands::project::ProjectArgs),
+
+    /// Capture external items, e.g. GitHub/GitLab issues, into the journal
+    #[cfg(feature = "integrations")]
+    Capture(commands::capture::CaptureArgs),
+
+    /// Ingest notes from outside of Flow, e.g. mail sent to a dedicated mailbox
+    #[cfg(feature = "mail")]
+    Ingest(commands::ingest::IngestArgs),
+
+    /// Save the readable content of a web article as a page for reading later
+    #[cfg(feature = "clip")]
+    Clip(commands::clip::ClipArgs),
+
+    /// Summarize a page or a week of journal pages into the review page with a language model
+    #[cfg(feature = "ai")]
+    Summarize(commands::summarize::SummarizeArgs),
+
+    /// Answer a question from your notes with a language model
+    #[cfg(feature = "ai")]
+    Ask(commands::ask::AskArgs),
+
+    /// Suggest tags for pages from the tags already used in the graph
+    Tags(commands::tags::TagsArgs),
+
+    /// Set, clear or show the sticky capture context
+    Context(commands::context::ContextArgs),
+
+    /// Manage credentials stored in the OS keychain
+    Auth(commands::auth::AuthArgs),
+
+    /// Sync the graph live with another device on the local network
+    Sync(commands::sync::SyncArgs),
+
+    /// Show the change history of the graph with authors and devices
+    Log(commands::log::LogArgs),
+
+    /// Show who last changed each line or block of a page
+    Blame(commands::blame::BlameArgs),
+
+    /// Diff a page against a historical version or its markdown file
+    Diff(commands::diff::DiffArgs),
+
+    /// Create a named checkpoint of the graph
+    TagVersion(commands::tag_version::TagVersionArgs),
+
+    /// Restore all pages to a named checkpoint
+    Restore(commands::restore::RestoreArgs),
+
+    /// Export the graph, e.g. to a portable archive
+    Export(commands::export::ExportArgs),
+
+    /// Import a graph, e.g. from a portable archive
+    Import(commands::import::ImportArgs),
+
+    /// Publish the graph as a static site to a configured target
+    Publish(commands::publish::PublishArgs),
+
+    /// Normalize the markdown formatting of pages
+    Fmt(commands::fmt::FmtArgs),
+
+    /// Review flashcards tagged #card with spaced repetition
+    Review(commands::review::ReviewArgs),
+
+    /// Surface a random or least recently seen page to revisit
+    Random(commands::random::RandomArgs),
+
+    /// Count words, blocks and characters of pages or journal days
+    Wc(commands::wc::WcArgs),
+
+    /// List and run WASM plugins
+    #[cfg(feature = "plugins")]
+    Plugin(commands::plugin::PluginArgs),
+
+    /// List reminders of scheduled and due tasks, notifying in watch mode
+    Remind(commands::remind::RemindArgs),
+
+    /// Print shell integration, e.g. a function that cds into a graph
+    ShellInit(commands::shell_init::ShellInitArgs),
+
+    /// Show the active graph, due tasks and sync state (--porcelain for prompts)
+    Status(commands::status::StatusArgs),
+
+    /// Lock a page so it can't be changed without --force
+    Lock(commands::lock::LockArgs),
+
+    /// Unlock a locked page
+    Unlock(commands::lock::LockArgs),
+
+    /// Recover a graph whose document no longer loads
+    Recover(commands::recover::RecoverArgs),
+
+    /// Print the JSON Schema of a command's --json output
+    Schema(commands::schema::SchemaArgs),
+
+    /// Run a `flow-<name>` plugin from PATH
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
 }
 
 /// Runs the CLI command.
@@ -65,5 +265,79 @@ pub fn run(cmd: Commands) -> Result<()> {
         Commands::Open(args) => commands::open::OpenCommand::from_args(args).execute(),
         Commands::Add(args) => commands::add::AddCommand::from_args(args).execute(),
         Commands::Clean(args) => commands::clean::CleanCommand::from_args(args).execute(),
+        Commands::Fsck(args) => commands::fsck::FsckCommand::from_args(args).execute(),
+        Commands::Backup(args) => commands::backup::BackupCommand::from_args(args).execute(),
+        Commands::Compact(args) => commands::compact::CompactCommand::from_args(args).execute(),
+        Commands::Migrate(args) => commands::migrate::MigrateCommand::from_args(args).execute(),
+        Commands::Pages(args) => commands::pages::PagesCommand::from_args(args).execute(),
+        Commands::Rpc(args) => commands::rpc::RpcCommand::from_args(args).execute(),
+        Commands::Daemon(args) => commands::daemon::DaemonCommand::from_args(args).execute(),
+        Commands::Index(args) => commands::index::IndexCommand::from_args(args).execute(),
+        Commands::Show(args) => commands::show::ShowCommand::from_args(args).execute(),
+        Commands::Search(args) => commands::search::SearchCommand::from_args(args).execute(),
+        #[cfg(feature = "semantic")]
+        Commands::Similar(args) => commands::similar::SimilarCommand::from_args(args).execute(),
+        Commands::Mentions(args) => commands::mentions::MentionsCommand::from_args(args).execute(),
+        Commands::Links(args) => commands::links::LinksCommand::from_args(args).execute(),
+        Commands::Dedupe(args) => commands::dedupe::DedupeCommand::from_args(args).execute(),
+        Commands::Merge(args) => commands::merge::MergeCommand::from_args(args).execute(),
+        Commands::Split(args) => commands::split::SplitCommand::from_args(args).execute(),
+        Commands::Rename(args) => commands::rename::RenameCommand::from_args(args).execute(),
+        Commands::Block(args) => commands::block::BlockCommand::from_args(args).execute(),
+        Commands::Rollover(args) => commands::rollover::RolloverCommand::from_args(args).execute(),
+        Commands::Config(args) => commands::config::ConfigCommand::from_args(args).execute(),
+        Commands::Stats(args) => commands::stats::StatsCommand::from_args(args).execute(),
+        Commands::Examples(args) => commands::examples::ExamplesCommand::from_args(args).execute(),
+        Commands::Setup(args) => commands::setup::SetupCommand::from_args(args).execute(),
+        Commands::Template(args) => commands::template::TemplateCommand::from_args(args).execute(),
+        Commands::Recent(args) => commands::recent::RecentCommand::from_args(args).execute(),
+        Commands::Today(args) => commands::day::DayCommand::new(args, 0).execute(),
+        Commands::Yesterday(args) => commands::day::DayCommand::new(args, -1).execute(),
+        Commands::Tomorrow(args) => commands::day::DayCommand::new(args, 1).execute(),
+        Commands::Attach(args) => commands::attach::AttachCommand::from_args(args).execute(),
+        Commands::Meeting(args) => commands::meeting::MeetingCommand::from_args(args).execute(),
+        Commands::Project(args) => commands::project::ProjectCommand::from_args(args).execute(),
+        #[cfg(feature = "integrations")]
+        Commands::Capture(args) => commands::capture::CaptureCommand::from_args(args).execute(),
+        #[cfg(feature = "mail")]
+        Commands::Ingest(args) => commands::ingest::IngestCommand::from_args(args).execute(),
+        #[cfg(feature = "clip")]
+        Commands::Clip(args) => commands::clip::ClipCommand::from_args(args).execute(),
+        #[cfg(feature = "ai")]
+        Commands::Summarize(args) => {
+            commands::summarize::SummarizeCommand::from_args(args).execute()
+        }
+        #[cfg(feature = "ai")]
+        Commands::Ask(args) => commands::ask::AskCommand::from_args(args).execute(),
+        Commands::Tags(args) => commands::tags::TagsCommand::from_args(args).execute(),
+        Commands::Context(args) => commands::context::ContextCommand::from_args(args).execute(),
+        Commands::Auth(args) => commands::auth::AuthCommand::from_args(args).execute(),
+        Commands::Sync(args) => commands::sync::SyncCommand::from_args(args).execute(),
+        Commands::Log(args) => commands::log::LogCommand::from_args(args).execute(),
+        Commands::Blame(args) => commands::blame::BlameCommand::from_args(args).execute(),
+        Commands::Diff(args) => commands::diff::DiffCommand::from_args(args).execute(),
+        Commands::TagVersion(args) => {
+            commands::tag_version::TagVersionCommand::from_args(args).execute()
+        }
+        Commands::Restore(args) => commands::restore::RestoreCommand::from_args(args).execute(),
+        Commands::Export(args) => commands::export::ExportCommand::from_args(args).execute(),
+        Commands::Import(args) => commands::import::ImportCommand::from_args(args).execute(),
+        Commands::Publish(args) => commands::publish::PublishCommand::from_args(args).execute(),
+        Commands::Fmt(args) => commands::fmt::FmtCommand::from_args(args).execute(),
+        Commands::Review(args) => commands::review::ReviewCommand::from_args(args).execute(),
+        Commands::Random(args) => commands::random::RandomCommand::from_args(args).execute(),
+        Commands::Wc(args) => commands::wc::WcCommand::from_args(args).execute(),
+        #[cfg(feature = "plugins")]
+        Commands::Plugin(args) => commands::plugin::PluginCommand::from_args(args).execute(),
+        Commands::Remind(args) => commands::remind::RemindCommand::from_args(args).execute(),
+        Commands::ShellInit(args) => {
+            commands::shell_init::ShellInitCommand::from_args(args).execute()
+        }
+        Commands::Status(args) => commands::status::StatusCommand::from_args(args).execute(),
+        Commands::Lock(args) => commands::lock::LockCommand::new(args, true).execute(),
+        Commands::Unlock(args) => commands::lock::LockCommand::new(args, false).execute(),
+        Commands::Recover(args) => commands::recover::RecoverCommand::from_args(args).execute(),
+        Commands::Schema(args) => commands::schema::SchemaCommand::from_args(args).execute(),
+        Commands::External(args) => plugins::run(args),
     }
 }
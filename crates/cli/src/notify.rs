@@ -0,0 +1,106 @@
+//! Desktop notifications for reminders.
+//!
+//! Fires native notifications for scheduled and due tasks (see
+//! [`flow_core::reminders`]), used by `flow remind --watch` and the daemon.
+//! Notifications need the `notify` feature; without it reminders are only
+//! listed.
+//!
+//! Whether a space notifies, and its quiet hours, are configured per space in
+//! the Flow configuration:
+//!
+//! ```toml
+//! [spaces.notes.notifications]
+//! enabled = true
+//! quiet_hours = { start = "22:00:00", end = "07:00:00" }
+//! ```
+
+use chrono::Local;
+use flow_core::reminders::{NotificationSettings, Reminder, ReminderKind};
+use flow_core::space::Space;
+use miette::Result;
+
+/// Interval reminders are checked at while watching, in seconds.
+pub const CHECK_INTERVAL: u64 = 60;
+
+/// Checks whether this build can show desktop notifications.
+///
+/// # Returns
+///
+/// * `bool` - True if built with the `notify` feature
+pub fn supported() -> bool {
+    cfg!(feature = "notify")
+}
+
+/// Fires notifications for the reminders of a space in a time range.
+///
+/// Nothing is shown while notifications are disabled for the space, during
+/// its quiet hours, or if this build has no notification support.
+///
+/// # Arguments
+///
+/// * `space` - The space to check
+/// * `settings` - Notification settings of the space
+/// * `from` - Start of the range as unix timestamp, exclusive
+/// * `until` - End of the range as unix timestamp, inclusive
+///
+/// # Returns
+///
+/// * `Result<Vec<Reminder>>` - The reminders notified about
+///
+/// # Errors
+///
+/// Returns an error if pages can't be read or a notification can't be shown.
+pub fn fire(
+    space: &Space,
+    settings: &NotificationSettings,
+    from: i64,
+    until: i64,
+) -> Result<Vec<Reminder>> {
+    if !supported() || !settings.allows(Local::now().time()) {
+        return Ok(Vec::new());
+    }
+
+    let reminders = space.reminders(from, until)?;
+    for reminder in &reminders {
+        show(space.name(), reminder)?;
+    }
+    Ok(reminders)
+}
+
+/// Shows the notification of a single reminder.
+#[cfg(feature = "notify")]
+fn show(space: &str, reminder: &Reminder) -> Result<()> {
+    use miette::IntoDiagnostic;
+
+    notify_rust::Notification::new()
+        .appname("Flow")
+        .summary(&summary(space, reminder))
+        .body(&reminder.task)
+        .show()
+        .into_diagnostic()?;
+    Ok(())
+}
+
+/// Shows the notification of a single reminder.
+#[cfg(not(feature = "notify"))]
+fn show(_space: &str, _reminder: &Reminder) -> Result<()> {
+    Ok(())
+}
+
+/// Returns the title of a reminder's notification.
+///
+/// # Arguments
+///
+/// * `space` - Name of the space
+/// * `reminder` - The reminder
+///
+/// # Returns
+///
+/// * `String` - Title naming the kind of reminder, page and space
+pub fn summary(space: &str, reminder: &Reminder) -> String {
+    let kind = match reminder.kind {
+        ReminderKind::Scheduled => "Scheduled",
+        ReminderKind::Deadline => "Due",
+    };
+    format!("{}: {} ({})", kind, reminder.page, space)
+}
@@ -0,0 +1,159 @@
+//! External subcommands.
+//!
+//! Like git, `flow <name>` runs a `flow-<name>` executable found on `PATH`
+//! when `<name>` is no built-in command, so Flow can be extended without
+//! forking it. All arguments after the name are passed on unchanged,
+//! including global flags like `--json`.
+//!
+//! Plugins get their context from the environment:
+//!
+//! * `FLOW_CONTEXT` - JSON object with all of the below, plus `args`
+//! * `FLOW_SPACE` - Path of the target space (`--graph` or the active one), if any
+//! * `FLOW_CONFIG` - Path of the Flow configuration file
+//! * `FLOW_JSON`, `FLOW_VERBOSE`, `FLOW_QUIET` - `1` if the global flag was given
+//! * `FLOW_VERSION` - Version of the invoking `flow`
+//!
+//! Standard input and output are inherited, so plugins can be interactive
+//! and their output piped. The exit code of the plugin becomes the exit code
+//! of `flow`.
+
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use flow_core::config::Config;
+use miette::{IntoDiagnostic, Result};
+use serde_json::json;
+
+use crate::error::CliError;
+
+/// Prefix of plugin executables.
+const PLUGIN_PREFIX: &str = "flow-";
+
+/// Runs an external subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The subcommand name followed by its arguments
+///
+/// # Returns
+///
+/// * `Result<()>` - Success if the plugin exited successfully
+///
+/// # Errors
+///
+/// Returns an error if no plugin of that name exists or it can't be started.
+/// A plugin failing exits the process with its exit code.
+pub fn run(args: Vec<OsString>) -> Result<()> {
+    let Some((name, args)) = args.split_first() else {
+        return Err(CliError::missing_argument("command").into());
+    };
+    let name = name.to_string_lossy().to_string();
+    let executable = find_plugin(&name).ok_or_else(|| CliError::unknown_command(name.clone()))?;
+
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect();
+    let flag = |long: &str, short: Option<&str>| {
+        args.iter()
+            .any(|arg| arg == long || short.is_some_and(|short| arg == short))
+    };
+    let (json, verbose, quiet) = (
+        flag("--json", None),
+        flag("--verbose", Some("-v")),
+        flag("--quiet", Some("-q")),
+    );
+
+    let config = Config::load()?;
+    let space = match graph_arg(&args) {
+        Some(name_or_path) => Some(
+            config
+                .get_space_config(&name_or_path)
+                .map(|space| space.path.clone())
+                .unwrap_or_else(|| PathBuf::from(name_or_path)),
+        ),
+        None => config.get_active_space().map(|space| space.path.clone()),
+    };
+    let config_path = Config::path()?;
+
+    let context = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "space": space,
+        "config": config_path,
+        "json": json,
+        "verbose": verbose,
+        "quiet": quiet,
+        "args": args,
+    });
+
+    let mut command = process::Command::new(&executable);
+    command
+        .args(&args)
+        .env("FLOW_CONTEXT", context.to_string())
+        .env("FLOW_CONFIG", &config_path)
+        .env("FLOW_VERSION", env!("CARGO_PKG_VERSION"));
+    if let Some(space) = &space {
+        command.env("FLOW_SPACE", space);
+    }
+    for (variable, set) in [
+        ("FLOW_JSON", json),
+        ("FLOW_VERBOSE", verbose),
+        ("FLOW_QUIET", quiet),
+    ] {
+        if set {
+            command.env(variable, "1");
+        }
+    }
+
+    let status = command.status().into_diagnostic()?;
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Returns the value of a `--graph` flag in the arguments.
+fn graph_arg(args: &[String]) -> Option<String> {
+    args.iter().enumerate().find_map(|(index, arg)| {
+        if arg == "--graph" {
+            args.get(index + 1).cloned()
+        } else {
+            arg.strip_prefix("--graph=").map(str::to_string)
+        }
+    })
+}
+
+/// Finds the executable of a plugin on `PATH`.
+fn find_plugin(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return None;
+    }
+    let file = format!("{}{}", PLUGIN_PREFIX, name);
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths).find_map(|dir| executable(&dir, &file))
+}
+
+/// Returns the executable of a file name in a directory, trying `PATHEXT` extensions on Windows.
+fn executable(dir: &Path, file: &str) -> Option<PathBuf> {
+    if cfg!(windows) {
+        let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string());
+        return extensions
+            .split(';')
+            .map(|extension| dir.join(format!("{}{}", file, extension.to_lowercase())))
+            .find(|path| path.is_file());
+    }
+
+    let path = dir.join(file);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = path.metadata().ok()?.permissions().mode();
+        (path.is_file() && mode & 0o111 != 0).then_some(path)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file().then_some(path)
+    }
+}
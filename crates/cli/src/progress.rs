@@ -0,0 +1,69 @@
+//! Terminal progress bars for long-running core operations.
+
+use std::sync::Mutex;
+
+use flow_core::progress::Progress;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Renders core progress reports as `indicatif` bars and spinners on stderr.
+///
+/// Each phase started by the operation replaces the previous bar. Phases with
+/// a known total get a determinate bar, others a spinner.
+#[derive(Default)]
+pub struct TerminalProgress {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl TerminalProgress {
+    /// Creates a progress renderer without an active bar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_bar(&self, f: impl FnOnce(&mut Option<ProgressBar>)) {
+        let mut bar = self
+            .bar
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut bar);
+    }
+}
+
+impl Progress for TerminalProgress {
+    fn start(&self, message: &str, total: Option<u64>) {
+        let next = match total {
+            Some(total) => ProgressBar::new(total).with_style(
+                ProgressStyle::with_template("{msg} {bar:40.cyan/blue} {pos}/{len}")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            ),
+            None => {
+                let spinner = ProgressBar::new_spinner();
+                spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+                spinner
+            }
+        };
+        next.set_message(message.to_string());
+
+        self.with_bar(|bar| {
+            if let Some(previous) = bar.replace(next) {
+                previous.finish_and_clear();
+            }
+        });
+    }
+
+    fn advance(&self, steps: u64) {
+        self.with_bar(|bar| {
+            if let Some(bar) = bar {
+                bar.inc(steps);
+            }
+        });
+    }
+
+    fn finish(&self) {
+        self.with_bar(|bar| {
+            if let Some(bar) = bar.take() {
+                bar.finish_and_clear();
+            }
+        });
+    }
+}
@@ -0,0 +1,286 @@
+//! JSON-RPC server for editor integrations.
+//!
+//! Editor plugins keep a single `flow rpc` process running and send
+//! newline-delimited JSON-RPC 2.0 requests to it. The space is loaded once and
+//! kept warm, so every request only pays for the operation itself.
+//!
+//! # Methods
+//!
+//! * `add` - `{ "content": string, "batch"?: bool }` - Add a node to today's journal page, every line as its own block for a batch
+//! * `search` - `{ "query": string, "scope"?: SearchScope, "limit"?: number }` - Ranked search
+//! * `show` - `{ "page": string }` - Read a page by name or alias
+//! * `pages` - List all pages
+//! * `backlinks` - `{ "page": string }` - List pages linking to a page
+//! * `embedded_by` - `{ "page": string }` - List pages embedding a page
+//! * `mentions` - `{ "page": string, "link"?: bool }` - List (or link) unlinked mentions of a page
+//! * `shutdown` - Stop the server
+
+use std::io::{BufRead, Write};
+
+use flow_core::index::PageIndex;
+use flow_core::page;
+use flow_core::search::SearchScope;
+use flow_core::space::Space;
+use miette::{IntoDiagnostic, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// JSON-RPC error code for malformed JSON.
+const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code for requests that are not valid request objects.
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code for unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for missing or malformed parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for failures inside the operation itself.
+const INTERNAL_ERROR: i64 = -32603;
+
+/// A JSON-RPC request.
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// An error returned to the client.
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// JSON-RPC server over a loaded space.
+pub struct Server {
+    space: Space,
+    running: bool,
+}
+
+impl Server {
+    /// Creates a server for the given space.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The space to serve
+    pub fn new(space: Space) -> Self {
+        Self {
+            space,
+            running: true,
+        }
+    }
+
+    /// Serves requests line by line until the input ends or `shutdown` is received.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Source of newline-delimited requests
+    /// * `writer` - Sink for newline-delimited responses
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize>` - Number of handled requests
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading requests or writing responses fails.
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: R, mut writer: W) -> Result<usize> {
+        let mut handled = 0;
+
+        for line in reader.lines() {
+            let line = line.into_diagnostic()?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(response) = self.handle(&line) {
+                writeln!(writer, "{}", response).into_diagnostic()?;
+                writer.flush().into_diagnostic()?;
+            }
+            handled += 1;
+
+            if !self.running {
+                break;
+            }
+        }
+
+        Ok(handled)
+    }
+
+    /// Handles a single request line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The raw JSON request
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The JSON response, `None` for notifications
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        let value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, err.to_string()),
+                ))
+            }
+        };
+
+        let request: Request = match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(INVALID_REQUEST, err.to_string()),
+                ))
+            }
+        };
+
+        // Requests without an id are notifications and get no response
+        let notification = request.id.is_null();
+        let id = request.id.clone();
+        let response = match self.dispatch(&request.method, &request.params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+            Err(err) => error_response(id, err),
+        };
+
+        (!notification).then_some(response)
+    }
+
+    /// Runs the operation for a method.
+    fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "add" => {
+                let content = string_param(params, "content")?;
+                let force = params
+                    .get("force")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                // A batch adds every line as its own block
+                let batch = params
+                    .get("batch")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                self.space.set_allow_locked(force);
+                let added = if batch {
+                    self.space.add_all(&content)
+                } else {
+                    self.space.add(&content).map(|_| 1)
+                };
+                self.space.set_allow_locked(false);
+                let blocks = added.map_err(internal)?;
+                Ok(json!({ "content": content, "blocks": blocks }))
+            }
+            "search" => {
+                let query = string_param(params, "query")?;
+                let scope: SearchScope = match params.get("scope") {
+                    Some(scope) => serde_json::from_value(scope.clone())
+                        .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))?,
+                    None => SearchScope::default(),
+                };
+                let limit = params.get("limit").and_then(Value::as_u64).unwrap_or(0) as usize;
+                let hits = self
+                    .space
+                    .search_ranked(&query, &scope, limit)
+                    .map_err(internal)?;
+                serde_json::to_value(hits).map_err(internal)
+            }
+            "show" => {
+                let name = string_param(params, "page")?;
+                let index = PageIndex::load(self.space.path()).map_err(internal)?;
+                let id = index.resolve(&name);
+
+                match self.space.read_page(&id).map_err(internal)? {
+                    Some(content) => {
+                        let times = self.space.page_times(&id);
+                        Ok(json!({
+                            "name": page::name_from_id(&id),
+                            "id": id,
+                            "created": times.created,
+                            "modified": times.modified,
+                            "content": content,
+                        }))
+                    }
+                    None => Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!("Page not found: {}", name),
+                    )),
+                }
+            }
+            "pages" => {
+                let index = PageIndex::load(self.space.path()).map_err(internal)?;
+                let pages: Vec<_> = index.pages().collect();
+                serde_json::to_value(pages).map_err(internal)
+            }
+            "backlinks" => {
+                let name = string_param(params, "page")?;
+                let index = PageIndex::load(self.space.path()).map_err(internal)?;
+                serde_json::to_value(index.backlinks(&name)).map_err(internal)
+            }
+            "embedded_by" => {
+                let name = string_param(params, "page")?;
+                let index = PageIndex::load(self.space.path()).map_err(internal)?;
+                serde_json::to_value(index.embedded_by(&name)).map_err(internal)
+            }
+            "mentions" => {
+                let name = string_param(params, "page")?;
+                let link = params.get("link").and_then(Value::as_bool).unwrap_or(false);
+                let mentions = if link {
+                    self.space.link_mentions(&name)
+                } else {
+                    self.space.mentions(&name)
+                }
+                .map_err(internal)?;
+                serde_json::to_value(mentions).map_err(internal)
+            }
+            "shutdown" => {
+                self.running = false;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method: {}", method),
+            )),
+        }
+    }
+}
+
+/// Reads a required string parameter.
+fn string_param(params: &Value, name: &str) -> Result<String, RpcError> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            RpcError::new(
+                INVALID_PARAMS,
+                format!("Missing string parameter '{}'", name),
+            )
+        })
+}
+
+/// Maps an operation failure to an internal error.
+fn internal(err: impl std::fmt::Display) -> RpcError {
+    RpcError::new(INTERNAL_ERROR, err.to_string())
+}
+
+/// Builds an error response.
+fn error_response(id: Value, err: RpcError) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+    .to_string()
+}
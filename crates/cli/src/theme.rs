@@ -0,0 +1,148 @@
+//! Output theme.
+//!
+//! Resolves the [`Theme`] of the Flow configuration, or the `--theme`
+//! override, into the prefixes and colors used by the
+//! [`GlobalArgs`](crate::common::GlobalArgs) print helpers. The theme is
+//! resolved once per process, on the first line printed.
+
+use std::sync::OnceLock;
+
+use console::{Color, Emoji, Style};
+use flow_core::config::{Config, Icons, Theme};
+
+static STYLES: OnceLock<Styles> = OnceLock::new();
+
+/// Kinds of output lines with a prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    Heading,
+    Info,
+    Success,
+    Warning,
+    Error,
+    Debug,
+    Step,
+}
+
+impl Icon {
+    /// Returns the emoji prefix, with the ASCII prefix as fallback.
+    fn emoji(self) -> Emoji<'static, 'static> {
+        match self {
+            Self::Heading => Emoji("✨ ", "* "),
+            Self::Info => Emoji("ℹ️  ", "[i] "),
+            Self::Success => Emoji("✅ ", "[+] "),
+            Self::Warning => Emoji("⚠️  ", "[!] "),
+            Self::Error => Emoji("❌ ", "[x] "),
+            Self::Debug => Emoji("🔍 ", "[?] "),
+            Self::Step => Emoji("→ ", "-> "),
+        }
+    }
+}
+
+/// Resolved prefixes and colors of terminal output.
+///
+/// # Fields
+///
+/// * `icons` - Kind of prefixes
+/// * `success` - Style of success messages
+/// * `info` - Style of info messages
+/// * `warning` - Style of warnings
+/// * `error` - Style of errors
+/// * `accent` - Style of keys in key-value output
+#[derive(Debug, Clone)]
+pub struct Styles {
+    pub icons: Icons,
+    pub success: Style,
+    pub info: Style,
+    pub warning: Style,
+    pub error: Style,
+    pub accent: Style,
+}
+
+impl Styles {
+    /// Resolves a theme.
+    ///
+    /// # Arguments
+    ///
+    /// * `theme` - The configured theme
+    /// * `icons` - Prefixes overriding the configured ones
+    ///
+    /// # Returns
+    ///
+    /// * `Styles` - Styles with unset colors at their defaults
+    pub fn new(theme: &Theme, icons: Option<Icons>) -> Self {
+        Self {
+            icons: icons.unwrap_or(theme.icons),
+            success: Style::new()
+                .fg(color(theme.success.as_deref(), Color::Green))
+                .bold(),
+            info: Style::new().fg(color(theme.info.as_deref(), Color::Cyan)),
+            warning: Style::new()
+                .fg(color(theme.warning.as_deref(), Color::Yellow))
+                .bold(),
+            error: Style::new()
+                .fg(color(theme.error.as_deref(), Color::Red))
+                .bold(),
+            accent: Style::new()
+                .fg(color(theme.accent.as_deref(), Color::Cyan))
+                .bold(),
+        }
+    }
+
+    /// Returns the prefix of a kind of output line.
+    ///
+    /// # Arguments
+    ///
+    /// * `icon` - Kind of the line
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The prefix including trailing spacing, empty for the minimal theme
+    pub fn icon(&self, icon: Icon) -> String {
+        match self.icons {
+            Icons::Emoji => icon.emoji().to_string(),
+            Icons::Ascii => icon.emoji().1.to_string(),
+            Icons::Minimal => String::new(),
+        }
+    }
+}
+
+/// Returns the styles of this process, resolving them on first use.
+///
+/// # Arguments
+///
+/// * `icons` - The `--theme` override, only honored on first use
+///
+/// # Returns
+///
+/// * `&'static Styles` - The resolved styles
+pub fn styles(icons: Option<Icons>) -> &'static Styles {
+    STYLES.get_or_init(|| {
+        // An unreadable config shouldn't break printing, fall back to defaults
+        let theme = Config::load()
+            .map(|config| config.theme().clone())
+            .unwrap_or_default();
+        Styles::new(&theme, icons)
+    })
+}
+
+/// Parses a color name or 256-color palette number, falling back to a default.
+fn color(name: Option<&str>, default: Color) -> Color {
+    let Some(name) = name else {
+        return default;
+    };
+    if let Ok(number) = name.parse::<u8>() {
+        return Color::Color256(number);
+    }
+    match name.to_lowercase().as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        _ => default,
+    }
+}
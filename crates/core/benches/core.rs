@@ -0,0 +1,87 @@
+//! Core Benchmarks
+//!
+//! Measures the hot paths of `flow_core` over generated spaces of 100, 1k and
+//! 10k pages. Run with `cargo bench -p flow-core`.
+//!
+//! # Budgets
+//!
+//! Budgets are for a release build on a typical laptop. Regressions beyond
+//! them should be treated as bugs.
+//!
+//! | Operation       | 100 pages | 1k pages | 10k pages |
+//! |-----------------|-----------|----------|-----------|
+//! | `load`          | 5 ms      | 30 ms    | 300 ms    |
+//! | `add` (+ save)  | 3 ms      | 15 ms    | 150 ms    |
+//! | search          | 2 ms      | 20 ms    | 200 ms    |
+//! | index rebuild   | 5 ms      | 50 ms    | 500 ms    |
+//!
+//! `add` includes saving. The document part of a save appends an incremental
+//! update instead of exporting the whole document, but the page and full-text
+//! indexes (`pages.json`, `text.json`) are loaded and rewritten in full, so a
+//! save grows linearly with the number of pages.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use flow_core::cancel::CancellationToken;
+use flow_core::fsck;
+use flow_core::index::PageIndex;
+use flow_core::progress::NoProgress;
+use flow_core::space::Space;
+use std::fs;
+use std::path::PathBuf;
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+/// Generates a space with the given number of linked pages.
+fn generate(pages: usize) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("flow-bench-{}-{}", pages, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    Space::init(&path, None, false).unwrap();
+
+    let pages_dir = path.join("pages");
+    fs::create_dir_all(&pages_dir).unwrap();
+    for i in 0..pages {
+        let content = format!(
+            "alias:: p{i}\n- Page {i} links to [[page-{}]]\n- Some text about topic {}\n",
+            (i + 1) % pages,
+            i % 17
+        );
+        fs::write(pages_dir.join(format!("page-{}.md", i)), content).unwrap();
+    }
+    fsck::rebuild_doc(&path, &NoProgress, &CancellationToken::new()).unwrap();
+
+    path
+}
+
+fn bench_core(c: &mut Criterion) {
+    for pages in SIZES {
+        let path = generate(pages);
+
+        let mut group = c.benchmark_group(format!("{}_pages", pages));
+        if pages >= 10_000 {
+            group.sample_size(10);
+        }
+
+        group.bench_function(BenchmarkId::new("load", pages), |b| {
+            b.iter(|| Space::load(&path).unwrap())
+        });
+
+        let mut space = Space::load(&path).unwrap();
+        group.bench_function(BenchmarkId::new("add", pages), |b| {
+            b.iter(|| space.add("benchmark node").unwrap())
+        });
+
+        group.bench_function(BenchmarkId::new("search", pages), |b| {
+            b.iter(|| space.search("topic 3").unwrap())
+        });
+
+        group.bench_function(BenchmarkId::new("index_rebuild", pages), |b| {
+            b.iter(|| PageIndex::rebuild(&path).unwrap())
+        });
+
+        group.finish();
+        let _ = fs::remove_dir_all(&path);
+    }
+}
+
+criterion_group!(benches, bench_core);
+criterion_main!(benches);
@@ -0,0 +1,498 @@
+//! AI Assistance
+//!
+//! Summarizes pages or a week of journal pages, and answers questions from
+//! the notes of a space, using a language model behind an OpenAI-compatible
+//! `/chat/completions` endpoint: a hosted API or a local server such as
+//! Ollama or llama.cpp. Other backends plug in by implementing
+//! [`LanguageModel`].
+//!
+//! Nothing leaves the machine unless an endpoint is configured per space in
+//! the space metadata and a summary or answer is explicitly asked for. Talking
+//! to endpoints needs Flow built with the `ai` feature:
+//!
+//! ```toml
+//! [ai]
+//! endpoint = "http://localhost:11434/v1/chat/completions"
+//! model = "llama3.1"
+//! ```
+//!
+//! Summaries are appended to the review page (`Reviews` by default) as a
+//! block linking to what was summarized:
+//!
+//! ```markdown
+//! - Summary of [[2024-W18]]
+//!   summarized:: 2024-05-03
+//!   model:: llama3.1
+//!   - Finished the sync rewrite design.
+//! ```
+//!
+//! Questions are answered from the pages best matching the question in the
+//! full-text index, which are sent along as context.
+
+use chrono::{Datelike, Days, NaiveDate};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::fulltext;
+use crate::page;
+use crate::space::Space;
+
+/// Page summaries are appended to if none is configured.
+const DEFAULT_REVIEW_PAGE: &str = "Reviews";
+
+/// Model requested if none is configured.
+#[cfg(feature = "ai")]
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// Maximum number of characters of notes sent along with a request.
+const MAX_CONTEXT_CHARS: usize = 24_000;
+
+/// Number of pages retrieved to answer a question.
+const ASK_PAGES: usize = 5;
+
+const SUMMARIZE_PROMPT: &str = "You summarize personal notes written as markdown outlines. \
+Reply with 3 to 7 short bullet points covering decisions, progress and open tasks. \
+Do not invent anything that isn't in the notes.";
+
+const ASK_PROMPT: &str = "You answer questions from personal notes written as markdown outlines. \
+Only use the notes below, refer to the pages you used as [[page name]], and say so if the \
+notes don't contain the answer.";
+
+/// AI settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `endpoint` (`Option<String>`) - URL of the `/chat/completions` endpoint, AI is off if `None`.
+/// - `model` (`Option<String>`) - Model requested from the endpoint.
+/// - `api_key` (`Option<String>`) - API key, usually a `secret:` reference.
+/// - `review_page` (`Option<String>`) - Page summaries are appended to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AiSettings {
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub review_page: Option<String>,
+}
+
+/// Generates text from a prompt.
+pub trait LanguageModel {
+    /// Identifies the backend and model.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Name of the model.
+    fn id(&self) -> String;
+
+    /// Completes a conversation of a system and a user message.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`LanguageModel`) - Model to use.
+    /// - `system` (`&str`) - Instructions for the model.
+    /// - `prompt` (`&str`) - The request, including any notes.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - The model's reply.
+    ///
+    /// # Errors
+    ///
+    /// Backend specific errors, e.g. when an endpoint is unreachable.
+    fn complete(&self, system: &str, prompt: &str) -> Result<String>;
+}
+
+/// Language model behind an OpenAI-compatible `/chat/completions` endpoint.
+///
+/// # Fields
+///
+/// - `endpoint` (`String`) - URL of the endpoint.
+/// - `model` (`String`) - Model to request.
+/// - `api_key` (`Option<String>`) - Resolved API key, sent as bearer token.
+#[cfg(feature = "ai")]
+#[derive(Debug, Clone)]
+pub struct HttpModel {
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[cfg(feature = "ai")]
+impl LanguageModel for HttpModel {
+    fn id(&self) -> String {
+        self.model.clone()
+    }
+
+    fn complete(&self, system: &str, prompt: &str) -> Result<String> {
+        use miette::IntoDiagnostic;
+
+        #[derive(Deserialize)]
+        struct Message {
+            content: String,
+        }
+        #[derive(Deserialize)]
+        struct Choice {
+            message: Message,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            choices: Vec<Choice>,
+        }
+
+        let mut request = ureq::post(&self.endpoint);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let response: Response = request
+            .send_json(serde_json::json!({
+                "model": self.model,
+                "messages": [
+                    { "role": "system", "content": system },
+                    { "role": "user", "content": prompt },
+                ],
+            }))
+            .into_diagnostic()?
+            .into_json()
+            .into_diagnostic()?;
+
+        response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| miette::miette!("{} returned no reply", self.endpoint))
+    }
+}
+
+/// Creates the language model configured by the given settings.
+///
+/// # Arguments
+///
+/// - `settings` (`&AiSettings`) - AI settings of a space.
+///
+/// # Returns
+///
+/// - `Result<Box<dyn LanguageModel>>` - The configured model.
+///
+/// # Errors
+///
+/// No endpoint is configured, the API key can't be resolved, or Flow was
+/// built without the `ai` feature.
+pub fn language_model(settings: &AiSettings) -> Result<Box<dyn LanguageModel>> {
+    let Some(endpoint) = &settings.endpoint else {
+        miette::bail!("AI requires an endpoint in the [ai] section of space.toml");
+    };
+
+    #[cfg(feature = "ai")]
+    {
+        let api_key = match &settings.api_key {
+            Some(key) => Some(crate::secrets::Secrets::open()?.resolve(key)?),
+            None => None,
+        };
+        Ok(Box::new(HttpModel {
+            endpoint: endpoint.clone(),
+            model: settings
+                .model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            api_key,
+        }))
+    }
+    #[cfg(not(feature = "ai"))]
+    {
+        let _ = endpoint;
+        miette::bail!("AI assistance requires Flow built with the 'ai' feature")
+    }
+}
+
+/// What a summary covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SummaryTarget {
+    /// A single page, by name or alias.
+    Page(String),
+    /// The journal pages of a week, from its first to its last day.
+    Week(NaiveDate, NaiveDate),
+}
+
+/// A summary appended to the review page.
+///
+/// # Fields
+///
+/// - `source` (`String`) - Name of what was summarized, a page or a week.
+/// - `page` (`String`) - Id of the review page.
+/// - `points` (`Vec<String>`) - The summary, one point per block.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Summary {
+    pub source: String,
+    pub page: String,
+    pub points: Vec<String>,
+}
+
+/// An answer to a question.
+///
+/// # Fields
+///
+/// - `answer` (`String`) - The model's answer.
+/// - `sources` (`Vec<String>`) - Names of the pages sent along as context.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Answer {
+    pub answer: String,
+    pub sources: Vec<String>,
+}
+
+impl Space {
+    /// Returns the AI settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&AiSettings` - Reference to the space's AI settings.
+    pub fn ai_settings(&self) -> &AiSettings {
+        &self.metadata.ai
+    }
+
+    /// Summarizes a page or week and appends the summary to the review page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to summarize.
+    /// - `target` (`&SummaryTarget`) - What to summarize.
+    /// - `model` (`&dyn LanguageModel`) - Model writing the summary.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Summary>` - The appended summary.
+    ///
+    /// # Errors
+    ///
+    /// There is nothing to summarize, the model fails, the review page is
+    /// locked, or IO errors when reading or writing pages.
+    pub fn summarize(
+        &mut self,
+        target: &SummaryTarget,
+        model: &dyn LanguageModel,
+    ) -> Result<Summary> {
+        let (source, notes) = match target {
+            SummaryTarget::Page(name) => {
+                let id = self.page_index()?.resolve(name);
+                let content = self.read_page(&id)?.unwrap_or_default();
+                (page::name_from_id(&id), content)
+            }
+            SummaryTarget::Week(first, last) => {
+                let notes = self
+                    .journal(Some(*first), Some(*last))?
+                    .into_iter()
+                    .map(|day| format!("# {}\n{}", page::name_from_id(&day.id), day.content))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                // The middle of the week, so weeks starting on Sunday get the right ISO week
+                let week = (*first + Days::new(3)).iso_week();
+                (format!("{}-W{:02}", week.year(), week.week()), notes)
+            }
+        };
+        if notes.trim().is_empty() {
+            miette::bail!("Nothing to summarize in [[{}]]", source);
+        }
+
+        let reply = model.complete(SUMMARIZE_PROMPT, &truncate(&notes, MAX_CONTEXT_CHARS))?;
+        let points = points(&reply);
+        if points.is_empty() {
+            miette::bail!("{} returned an empty summary", model.id());
+        }
+
+        let review = self
+            .ai_settings()
+            .review_page
+            .clone()
+            .unwrap_or_else(|| DEFAULT_REVIEW_PAGE.to_string());
+        let id = self.page_index()?.resolve(&review);
+        let mut lines = vec![
+            format!("- Summary of [[{}]]", source),
+            format!("  summarized:: {}", self.today().format("%Y-%m-%d")),
+            format!("  model:: {}", model.id()),
+        ];
+        lines.extend(points.iter().map(|point| format!("  - {}", point)));
+        self.append(&id, &lines.join("\n"))?;
+
+        Ok(Summary {
+            source,
+            page: id,
+            points,
+        })
+    }
+
+    /// Answers a question from the pages best matching it.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to answer from.
+    /// - `question` (`&str`) - The question.
+    /// - `model` (`&dyn LanguageModel`) - Model writing the answer.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Answer>` - The answer and the pages it was given.
+    ///
+    /// # Errors
+    ///
+    /// No page matches the question, the model fails, or IO errors when
+    /// reading the index or pages.
+    pub fn ask(&self, question: &str, model: &dyn LanguageModel) -> Result<Answer> {
+        let ids = self.retrieve(question, ASK_PAGES)?;
+        if ids.is_empty() {
+            miette::bail!("No notes match '{}'", question);
+        }
+
+        let budget = MAX_CONTEXT_CHARS / ids.len();
+        let mut notes = Vec::with_capacity(ids.len());
+        let mut sources = Vec::with_capacity(ids.len());
+        for id in ids {
+            let name = page::name_from_id(&id);
+            let content = self.read_page(&id)?.unwrap_or_default();
+            notes.push(format!("# {}\n{}", name, truncate(&content, budget)));
+            sources.push(name);
+        }
+
+        let prompt = format!("{}\n\nQuestion: {}", notes.join("\n\n"), question);
+        Ok(Answer {
+            answer: model.complete(ASK_PROMPT, &prompt)?.trim().to_string(),
+            sources,
+        })
+    }
+
+    /// Finds the pages matching most words of a question.
+    ///
+    /// Unlike [`Space::search_ranked`] pages don't need to contain every word,
+    /// the scores of each word are added up instead.
+    fn retrieve(&self, question: &str, limit: usize) -> Result<Vec<String>> {
+        let index = self.text_index()?;
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        for token in fulltext::tokenize(question) {
+            // Short words are mostly stop words and match everything
+            if token.chars().count() < 3 {
+                continue;
+            }
+            for found in index.search(&token) {
+                *scores.entry(found.id).or_default() += found.score;
+            }
+        }
+
+        let mut ranked: Vec<(String, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(ranked.into_iter().take(limit).map(|(id, _)| id).collect())
+    }
+}
+
+/// Splits a reply into points, dropping list markers and empty lines.
+fn points(reply: &str) -> Vec<String> {
+    reply
+        .lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(['-', '*', '•'])
+                .trim()
+                .to_string()
+        })
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Cuts text to at most the given number of characters.
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}\n…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    struct Canned {
+        reply: &'static str,
+        prompts: RefCell<Vec<String>>,
+    }
+
+    impl Canned {
+        fn new(reply: &'static str) -> Self {
+            Self {
+                reply,
+                prompts: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl LanguageModel for Canned {
+        fn id(&self) -> String {
+            "canned".to_string()
+        }
+
+        fn complete(&self, _system: &str, prompt: &str) -> Result<String> {
+            self.prompts.borrow_mut().push(prompt.to_string());
+            Ok(self.reply.to_string())
+        }
+    }
+
+    #[test]
+    fn test_summarize_week_appends_to_review_page() {
+        let mut space = Space::in_memory().unwrap();
+        space.add("Designed the sync rewrite").unwrap();
+        let (first, last) = crate::journal::week(space.today(), chrono::Weekday::Mon);
+
+        let model = Canned::new("- Designed the sync rewrite.\n\n* Nothing else.");
+        let summary = space
+            .summarize(&SummaryTarget::Week(first, last), &model)
+            .unwrap();
+        assert_eq!(
+            summary.points,
+            vec!["Designed the sync rewrite.", "Nothing else."]
+        );
+        assert!(model.prompts.borrow()[0].contains("Designed the sync rewrite"));
+
+        let review = space.read_page(&summary.page).unwrap().unwrap();
+        assert!(review.contains(&format!("- Summary of [[{}]]", summary.source)));
+        assert!(
+            review.contains("  model:: canned\n  - Designed the sync rewrite.\n  - Nothing else.")
+        );
+    }
+
+    #[test]
+    fn test_summarize_rejects_empty_pages() {
+        let mut space = Space::in_memory().unwrap();
+        let model = Canned::new("- Anything");
+        assert!(space
+            .summarize(&SummaryTarget::Page("Missing".to_string()), &model)
+            .is_err());
+        assert!(model.prompts.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_ask_sends_matching_pages() {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page("pages/Sync.md", "- The sync rewrite uses a merge queue\n")
+            .unwrap();
+        space
+            .write_page("pages/Garden.md", "- Planted tomatoes\n")
+            .unwrap();
+
+        let model = Canned::new(" It uses a merge queue, see [[Sync]]. ");
+        let answer = space.ask("How does the sync work?", &model).unwrap();
+        assert_eq!(answer.answer, "It uses a merge queue, see [[Sync]].");
+        assert_eq!(answer.sources, vec!["Sync"]);
+        assert!(!model.prompts.borrow()[0].contains("tomatoes"));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("abc", 5), "abc");
+        assert_eq!(truncate("äbcdef", 2), "äb\n…");
+    }
+}
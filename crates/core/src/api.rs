@@ -0,0 +1,269 @@
+//! Public API
+//!
+//! The stable surface of `flow_core` for tools building on Flow. Everything
+//! reachable from this module follows semantic versioning: breaking changes
+//! only come with a new major version. The other modules of the crate are the
+//! building blocks of Flow's own frontends and may change in any release.
+//!
+//! ```
+//! use flow_core::api::{Query, Space};
+//!
+//! # fn main() -> flow_core::api::Result<()> {
+//! let mut space = Space::in_memory()?;
+//! space.write_page("pages/rust.md", "- Ownership #lang\n  - Borrowing\n")?;
+//!
+//! let page = space.page("rust")?.expect("page exists");
+//! assert_eq!(page.tags(), vec!["lang".to_string()]);
+//! assert_eq!(page.blocks()[1].text, "Borrowing");
+//!
+//! let hits = space.query(&Query::new("borrowing").limit(5))?;
+//! assert_eq!(hits[0].page, "rust");
+//! # Ok(())
+//! # }
+//! ```
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::page;
+
+pub use miette::Report;
+
+pub use crate::cancel::{CancellationToken, Cancelled};
+pub use crate::config::{Config, Locale, SpaceConfig};
+pub use crate::error::Error;
+pub use crate::index::{PageEntry, PageIndex};
+pub use crate::page::Heading;
+pub use crate::progress::{NoProgress, Progress};
+pub use crate::search::{RankedHit, SearchScope};
+pub use crate::space::Space;
+pub use crate::storage::{FsStorage, MemoryStorage, Storage, StorageEntry};
+pub use crate::timestamps::PageTimes;
+
+/// Result of all fallible operations, a [`Report`] that may carry an [`Error`].
+pub type Result<T, E = Report> = std::result::Result<T, E>;
+
+/// A page of a space.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page (relative markdown path).
+/// - `name` (`String`) - User-facing name of the page.
+/// - `content` (`String`) - Markdown content of the page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Page {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+}
+
+impl Page {
+    /// Returns the page-level properties.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(String, String)>` - Lowercased keys and values in order of appearance.
+    pub fn properties(&self) -> Vec<(String, String)> {
+        page::properties(&self.content)
+    }
+
+    /// Returns the tags of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<String>` - Tags without the leading `#`.
+    pub fn tags(&self) -> Vec<String> {
+        page::tags(&self.content)
+    }
+
+    /// Returns the names of the pages this page links to.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<String>` - Link targets in order of appearance.
+    pub fn links(&self) -> Vec<String> {
+        page::links(&self.content)
+    }
+
+    /// Returns the headings of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Heading>` - Headings in page order.
+    pub fn headings(&self) -> Vec<Heading> {
+        page::headings(&self.content)
+    }
+
+    /// Returns the blocks of the page.
+    ///
+    /// A block starts with a `- ` bullet, lines that follow without a bullet
+    /// continue it. Properties and text before the first bullet aren't blocks.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Block>` - Blocks in page order.
+    pub fn blocks(&self) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for (index, line) in self.content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+            let bullet = trimmed
+                .strip_prefix("- ")
+                .or((trimmed == "-").then_some(""));
+            match (bullet, blocks.last_mut()) {
+                (Some(text), _) => blocks.push(Block {
+                    line: index + 1,
+                    depth: indent
+                        .chars()
+                        .map(|c| if c == '\t' { 2 } else { 1 })
+                        .sum::<usize>()
+                        / 2,
+                    text: text.to_string(),
+                }),
+                (None, Some(block)) if !trimmed.is_empty() => {
+                    block.text.push('\n');
+                    block.text.push_str(trimmed);
+                }
+                (None, _) => {}
+            }
+        }
+        blocks
+    }
+}
+
+/// A block (outline item) of a page.
+///
+/// # Fields
+///
+/// - `line` (`usize`) - Line the block starts at, starting at 1.
+/// - `depth` (`usize`) - Nesting depth, 0 for top-level blocks.
+/// - `text` (`String`) - Text of the block without the bullet, continuation lines joined by `\n`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Block {
+    pub line: usize,
+    pub depth: usize,
+    pub text: String,
+}
+
+/// A full-text query.
+///
+/// # Fields
+///
+/// - `text` (`String`) - Query (see [`fulltext`](crate::fulltext) for the syntax).
+/// - `scope` (`SearchScope`) - Pages to search.
+/// - `limit` (`usize`) - Maximum number of hits, `0` for all.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub text: String,
+    pub scope: SearchScope,
+    pub limit: usize,
+}
+
+impl Query {
+    /// Creates an unscoped, unlimited query.
+    ///
+    /// # Arguments
+    ///
+    /// - `text` (`impl Into<String>`) - Query text.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The query.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Restricts the pages the query searches.
+    ///
+    /// # Arguments
+    ///
+    /// - `scope` (`SearchScope`) - Pages to search.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The scoped query.
+    pub fn scope(mut self, scope: SearchScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Limits the number of hits.
+    ///
+    /// # Arguments
+    ///
+    /// - `limit` (`usize`) - Maximum number of hits, `0` for all.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The limited query.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+impl Space {
+    /// Reads a page by name or alias.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the page belongs to.
+    /// - `name` (`&str`) - Name or alias of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<Page>>` - The page, `None` if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the index or reading the page.
+    pub fn page(&self, name: &str) -> Result<Option<Page>> {
+        let id = self.page_index()?.resolve(name);
+        Ok(self.read_page(&id)?.map(|content| Page {
+            name: page::name_from_id(&id),
+            id,
+            content,
+        }))
+    }
+
+    /// Runs a full-text query.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `query` (`&Query`) - The query.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<RankedHit>>` - Matching pages, best match first.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the indexes or reading pages.
+    pub fn query(&self, query: &Query) -> Result<Vec<RankedHit>> {
+        self.search_ranked(&query.text, &query.scope, query.limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks() {
+        let page = Page {
+            id: "pages/plan.md".to_string(),
+            name: "plan".to_string(),
+            content: "title:: Plan\n- First\n  continued\n\t- Nested\n-\n".to_string(),
+        };
+
+        let blocks = page.blocks();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].text, "First\ncontinued");
+        assert_eq!((blocks[1].line, blocks[1].depth), (4, 1));
+        assert_eq!(blocks[2].text, "");
+    }
+}
@@ -0,0 +1,517 @@
+//! Space Archives
+//!
+//! A space can be packed into a single `.flowpack` file to move it to another
+//! machine. The archive is a deflate-compressed zip holding the metadata, a
+//! fresh snapshot of the document, and every markdown file and asset of the
+//! space. A `manifest.json` lists each file with its size and SHA-256
+//! checksum, which is verified when the archive is imported.
+//!
+//! State that belongs to this machine (indexes, backups, sync projections,
+//! the capture context and the conflict queue) isn't archived; indexes are
+//! rebuilt after import.
+
+use chrono::Local;
+use loro::ExportMode;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::cancel::CancellationToken;
+use crate::fulltext::TextIndex;
+use crate::index::PageIndex;
+use crate::progress::Progress;
+use crate::space::{Space, DOCUMENT_FILE, FLOW_DIR, METADATA_FILE};
+
+/// File extension of space archives.
+pub const ARCHIVE_EXTENSION: &str = "flowpack";
+
+/// Archive format written by this version.
+const ARCHIVE_FORMAT: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// A file in an archive.
+///
+/// # Fields
+///
+/// - `path` (`String`) - Path relative to the space root, `/` separated.
+/// - `size` (`u64`) - Size in bytes.
+/// - `sha256` (`String`) - Hex encoded SHA-256 checksum.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Contents of an archive.
+///
+/// # Fields
+///
+/// - `format` (`u32`) - Archive format version.
+/// - `space` (`String`) - Name of the archived space.
+/// - `version` (`String`) - Flow version that created the archive.
+/// - `created` (`i64`) - Creation time as unix timestamp in seconds.
+/// - `entries` (`Vec<ArchiveEntry>`) - Archived files.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub space: String,
+    pub version: String,
+    pub created: i64,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+impl Manifest {
+    /// Returns the total size of the archived files.
+    ///
+    /// # Returns
+    ///
+    /// - `u64` - Uncompressed size in bytes.
+    pub fn size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+}
+
+impl Space {
+    /// Packs the space into an archive file.
+    ///
+    /// The archive is written next to the target first and only renamed over
+    /// it once complete, so a failed or cancelled export leaves no broken
+    /// archive behind.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to archive.
+    /// - `file` (`&Path`) - Archive file to write, replaced if it exists.
+    /// - `progress` (`&dyn Progress`) - Receives one step per packed file.
+    /// - `cancel` (`&CancellationToken`) - Checked before each file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Manifest>` - Contents of the written archive.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the space or writing the archive,
+    /// [`Cancelled`](crate::cancel::Cancelled) if the token was cancelled.
+    pub fn export_archive(
+        &self,
+        file: &Path,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Manifest> {
+        let partial = file.with_extension(format!("{}.partial", ARCHIVE_EXTENSION));
+        match self.write_archive(&partial, progress, cancel) {
+            Ok(manifest) => {
+                fs::rename(&partial, file).into_diagnostic()?;
+                Ok(manifest)
+            }
+            Err(err) => {
+                let _ = fs::remove_file(&partial);
+                Err(err)
+            }
+        }
+    }
+
+    /// Writes the archive of the space to a file.
+    fn write_archive(
+        &self,
+        file: &Path,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Manifest> {
+        let files = space_files(&self.path)?;
+        let mut writer = ZipWriter::new(File::create(file).into_diagnostic()?);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(true);
+
+        let mut manifest = Manifest {
+            format: ARCHIVE_FORMAT,
+            space: self.name().to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            created: Local::now().timestamp(),
+            entries: Vec::with_capacity(files.len() + 2),
+        };
+        let mut add = |path: String, content: Vec<u8>| -> Result<()> {
+            writer
+                .start_file(path.as_str(), options)
+                .into_diagnostic()?;
+            writer.write_all(&content).into_diagnostic()?;
+            manifest.entries.push(ArchiveEntry {
+                path,
+                size: content.len() as u64,
+                sha256: checksum(&content),
+            });
+            Ok(())
+        };
+
+        let flow_dir = format!("{}/", FLOW_DIR);
+        add(
+            format!("{}{}", flow_dir, METADATA_FILE),
+            fs::read(self.path.join(FLOW_DIR).join(METADATA_FILE)).into_diagnostic()?,
+        )?;
+        add(
+            format!("{}{}", flow_dir, DOCUMENT_FILE),
+            self.document
+                .export(ExportMode::Snapshot)
+                .into_diagnostic()?,
+        )?;
+
+        progress.start("Packing files", Some(files.len() as u64));
+        for path in files {
+            if let Err(cancelled) = cancel.check() {
+                progress.finish();
+                return Err(cancelled.into());
+            }
+            let content = fs::read(self.path.join(&path)).into_diagnostic()?;
+            add(path, content)?;
+            progress.advance(1);
+        }
+        progress.finish();
+
+        let json = serde_json::to_vec_pretty(&manifest).into_diagnostic()?;
+        writer
+            .start_file(MANIFEST_FILE, options)
+            .into_diagnostic()?;
+        writer.write_all(&json).into_diagnostic()?;
+        writer.finish().into_diagnostic()?;
+
+        Ok(manifest)
+    }
+
+    /// Unpacks an archive into a new space.
+    ///
+    /// Every file is checked against the manifest before anything is written.
+    /// The files are then unpacked into a directory next to the target, which
+    /// is only moved into place once complete, so a corrupt archive or a
+    /// failed or cancelled import never produces a partial space.
+    ///
+    /// # Arguments
+    ///
+    /// - `file` (`&Path`) - Archive file to read.
+    /// - `target` (`&Path`) - Directory to create the space in, must not exist or be empty.
+    /// - `progress` (`&dyn Progress`) - Receives one step per verified and per unpacked file.
+    /// - `cancel` (`&CancellationToken`) - Checked before each file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<(Self, Manifest)>` - The imported space and the archive contents.
+    ///
+    /// # Errors
+    ///
+    /// The target isn't empty, the archive is corrupt or from a newer Flow
+    /// version, IO errors when writing the space, or
+    /// [`Cancelled`](crate::cancel::Cancelled) if the token was cancelled.
+    pub fn import_archive(
+        file: &Path,
+        target: &Path,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<(Self, Manifest)> {
+        if fs::read_dir(target).is_ok_and(|mut entries| entries.next().is_some()) {
+            miette::bail!("Target directory '{}' is not empty", target.display());
+        }
+
+        let mut archive = ZipArchive::new(File::open(file).into_diagnostic()?).into_diagnostic()?;
+        let manifest = manifest(&mut archive)?;
+
+        let mut files = Vec::with_capacity(manifest.entries.len());
+        progress.start("Verifying files", Some(manifest.entries.len() as u64));
+        for entry in &manifest.entries {
+            if let Err(cancelled) = cancel.check() {
+                progress.finish();
+                return Err(cancelled.into());
+            }
+            let safe = Path::new(&entry.path)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+            if !safe || !archivable(&entry.path) {
+                miette::bail!("Archive contains an unsafe path '{}'", entry.path);
+            }
+
+            let mut content = Vec::new();
+            archive
+                .by_name(&entry.path)
+                .into_diagnostic()?
+                .read_to_end(&mut content)
+                .into_diagnostic()?;
+            if content.len() as u64 != entry.size || checksum(&content) != entry.sha256 {
+                miette::bail!(
+                    "Checksum mismatch for '{}', the archive is corrupt",
+                    entry.path
+                );
+            }
+            files.push((entry.path.as_str(), content));
+            progress.advance(1);
+        }
+        progress.finish();
+
+        let name = target.file_name().unwrap_or_default().to_string_lossy();
+        let partial = target.with_file_name(format!(".{}.partial", name));
+        let _ = fs::remove_dir_all(&partial);
+        if let Err(err) = unpack(&partial, files, progress, cancel) {
+            let _ = fs::remove_dir_all(&partial);
+            return Err(err);
+        }
+        // An empty target can't be renamed over on every platform
+        if target.exists() {
+            fs::remove_dir(target).into_diagnostic()?;
+        }
+        fs::rename(&partial, target).into_diagnostic()?;
+
+        let space = Space::load(target)?;
+        // Loading missing indexes builds them
+        PageIndex::load(target)?;
+        TextIndex::load(target)?;
+        Ok((space, manifest))
+    }
+}
+
+/// Writes verified archive files into a directory.
+fn unpack(
+    dir: &Path,
+    files: Vec<(&str, Vec<u8>)>,
+    progress: &dyn Progress,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    fs::create_dir_all(dir).into_diagnostic()?;
+    progress.start("Unpacking files", Some(files.len() as u64));
+    for (path, content) in files {
+        if let Err(cancelled) = cancel.check() {
+            progress.finish();
+            return Err(cancelled.into());
+        }
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).into_diagnostic()?;
+        }
+        fs::write(path, content).into_diagnostic()?;
+        progress.advance(1);
+    }
+    progress.finish();
+    Ok(())
+}
+
+/// Reads the manifest of an archive without unpacking it.
+///
+/// # Arguments
+///
+/// - `file` (`&Path`) - Archive file to read.
+///
+/// # Returns
+///
+/// - `Result<Manifest>` - Contents of the archive.
+///
+/// # Errors
+///
+/// The file isn't an archive or is from a newer Flow version.
+pub fn read_manifest(file: &Path) -> Result<Manifest> {
+    let mut archive = ZipArchive::new(File::open(file).into_diagnostic()?).into_diagnostic()?;
+    manifest(&mut archive)
+}
+
+/// Reads and checks the manifest of an opened archive.
+fn manifest(archive: &mut ZipArchive<File>) -> Result<Manifest> {
+    let mut json = Vec::new();
+    archive
+        .by_name(MANIFEST_FILE)
+        .into_diagnostic()?
+        .read_to_end(&mut json)
+        .into_diagnostic()?;
+    let manifest: Manifest = serde_json::from_slice(&json).into_diagnostic()?;
+
+    if manifest.format > ARCHIVE_FORMAT {
+        miette::bail!(
+            "Archive format {} is newer than the supported format {}, upgrade Flow to import it",
+            manifest.format,
+            ARCHIVE_FORMAT
+        );
+    }
+    Ok(manifest)
+}
+
+/// Hex encoded SHA-256 checksum of some bytes.
+fn checksum(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Checks whether an archive path is one [`Space::export_archive`] writes.
+///
+/// Anything else in `.flow/` (like hook scripts) or in hidden directories
+/// (like `.git/`) would run or be trusted on the importing machine.
+fn archivable(path: &str) -> bool {
+    let flow_file = |file: &str| path == format!("{}/{}", FLOW_DIR, file);
+    if flow_file(METADATA_FILE) || flow_file(DOCUMENT_FILE) {
+        return true;
+    }
+    !path.split('/').any(|segment| segment.starts_with('.'))
+        && Path::new(path)
+            .extension()
+            .is_none_or(|ext| ext != ARCHIVE_EXTENSION)
+}
+
+/// Lists all files of a space outside hidden directories, markdown and assets.
+///
+/// Like [`markdown_files`](crate::space::markdown_files), but for every file type.
+fn space_files(root: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).into_diagnostic()? {
+            let entry = entry.into_diagnostic()?;
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == ARCHIVE_EXTENSION) {
+                // Earlier archives saved inside the space
+                continue;
+            } else {
+                let relative = path.strip_prefix(root).into_diagnostic()?;
+                let id = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push(id);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use std::path::PathBuf;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("flow-archive-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn exported(dir: &Path) -> PathBuf {
+        let mut space = Space::init(&dir.join("space"), None, true).unwrap();
+        space.write_page("pages/notes.md", "- Packed").unwrap();
+        fs::write(dir.join("space").join("photo.png"), [0u8, 1, 2]).unwrap();
+
+        let file = dir.join("space.flowpack");
+        space
+            .export_archive(&file, &NoProgress, &CancellationToken::new())
+            .unwrap();
+        file
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = scratch_dir("round-trip");
+        let file = exported(&dir);
+        assert!(!dir.join("space.flowpack.partial").exists());
+
+        let target = dir.join("imported");
+        let (space, manifest) =
+            Space::import_archive(&file, &target, &NoProgress, &CancellationToken::new()).unwrap();
+        assert!(manifest
+            .entries
+            .iter()
+            .any(|entry| entry.path == "photo.png"));
+        assert_eq!(
+            space.read_page("pages/notes.md").unwrap().as_deref(),
+            Some("- Packed")
+        );
+        assert_eq!(fs::read(target.join("photo.png")).unwrap(), [0u8, 1, 2]);
+    }
+
+    #[test]
+    fn test_tampered_entry_is_rejected() {
+        let dir = scratch_dir("tampered");
+        let file = exported(&dir);
+
+        // Repack the archive with a changed page but the original manifest
+        let mut archive = ZipArchive::new(File::open(&file).unwrap()).unwrap();
+        let tampered = dir.join("tampered.flowpack");
+        let mut writer = ZipWriter::new(File::create(&tampered).unwrap());
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).unwrap();
+            let name = entry.name().to_string();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            if name == "pages/notes.md" {
+                content = b"- Tampered".to_vec();
+            }
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(&content).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let target = dir.join("imported");
+        let err = Space::import_archive(&tampered, &target, &NoProgress, &CancellationToken::new())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_hidden_entry_is_rejected() {
+        let dir = scratch_dir("hidden");
+        let file = exported(&dir);
+
+        // Repack the archive with a hook script listed in the manifest
+        let mut archive = ZipArchive::new(File::open(&file).unwrap()).unwrap();
+        let mut manifest = manifest(&mut archive).unwrap();
+        let hook = b"#!/bin/sh\necho pwned\n".to_vec();
+        manifest.entries.push(ArchiveEntry {
+            path: ".flow/hooks/post-add".to_string(),
+            size: hook.len() as u64,
+            sha256: checksum(&hook),
+        });
+        let smuggled = dir.join("smuggled.flowpack");
+        let mut writer = ZipWriter::new(File::create(&smuggled).unwrap());
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).unwrap();
+            let name = entry.name().to_string();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            if name == MANIFEST_FILE {
+                content = serde_json::to_vec(&manifest).unwrap();
+            }
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(&content).unwrap();
+        }
+        writer
+            .start_file(".flow/hooks/post-add", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&hook).unwrap();
+        writer.finish().unwrap();
+
+        let target = dir.join("imported");
+        let err = Space::import_archive(&smuggled, &target, &NoProgress, &CancellationToken::new())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("unsafe path"));
+        assert!(!target.exists());
+    }
+}
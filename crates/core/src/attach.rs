@@ -0,0 +1,198 @@
+//! Attachments
+//!
+//! Copies files into the space's `assets/` directory and links them from
+//! today's journal page. Images are embedded, other files linked:
+//!
+//! ```markdown
+//! - ![whiteboard.png](../assets/whiteboard.png)
+//!   - Sync rewrite: merge queue first, then the transport
+//!     ocr:: assets/whiteboard.png
+//! ```
+//!
+//! With a recognizer (see [`ocr`](crate::ocr)) the text of an image is
+//! recognized and stored as a sidecar block below the attachment, tagged with
+//! an `ocr::` property naming the file it was read from. Being part of the
+//! page, the text is indexed by full-text search like any other block, so
+//! screenshots and photos of whiteboards become searchable.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::ocr::Recognizer;
+use crate::space::Space;
+
+/// Directory attached files are saved to, relative to the space.
+pub const ATTACHMENT_DIR: &str = "assets";
+
+/// File extensions of attachments embedded as images.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg"];
+
+/// Name of the property linking recognized text to its attachment.
+pub const OCR_PROPERTY: &str = "ocr";
+
+/// A file attached to the journal.
+///
+/// # Fields
+///
+/// - `path` (`String`) - Path of the saved file, relative to the space.
+/// - `page` (`String`) - Id of the page linking to the file.
+/// - `text` (`Option<String>`) - Recognized text, `None` if OCR didn't run.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Attached {
+    pub path: String,
+    pub page: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// Checks whether a file is embedded as an image.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name or path of the file.
+///
+/// # Returns
+///
+/// - `bool` - True for common image extensions.
+pub fn is_image(name: &str) -> bool {
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Builds the markdown link to a saved file from a page.
+///
+/// # Arguments
+///
+/// - `path` (`&str`) - Path of the file, relative to the space.
+/// - `id` (`&str`) - Id of the linking page.
+///
+/// # Returns
+///
+/// - `String` - An image embed for images, a link otherwise.
+pub fn link(path: &str, id: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    format!(
+        "{}[{}]({}{})",
+        if is_image(name) { "!" } else { "" },
+        name,
+        "../".repeat(id.matches('/').count()),
+        path
+    )
+}
+
+impl Space {
+    /// Attaches a file to today's journal page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to attach the file to.
+    /// - `file` (`&Path`) - The file.
+    /// - `recognizer` (`Option<&dyn Recognizer>`) - Recognizer reading the text of images, if any.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Attached>` - The saved file and its recognized text.
+    ///
+    /// # Errors
+    ///
+    /// The file can't be read, recognition fails, the journal page is locked,
+    /// or IO errors when writing files.
+    pub fn attach(&mut self, file: &Path, recognizer: Option<&dyn Recognizer>) -> Result<Attached> {
+        let content = fs::read(file)
+            .map_err(|error| miette::miette!("Failed to read '{}': {}", file.display(), error))?;
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // Only images carry text worth recognizing
+        let text = match recognizer {
+            Some(recognizer) if is_image(&name) => Some(
+                recognizer
+                    .recognize(file)?
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            _ => None,
+        };
+
+        let path = self.save_asset(ATTACHMENT_DIR, &name, &content)?;
+        let page = self.today_page()?;
+        let mut lines = vec![format!("- {}", link(&path, &page))];
+        if let Some(text) = text.as_deref().filter(|text| !text.is_empty()) {
+            lines.push(format!("  - {}", text));
+            lines.push(format!("    {}:: {}", OCR_PROPERTY, path));
+        }
+        self.append(&page, &lines.join("\n"))?;
+
+        Ok(Attached { path, page, text })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str);
+
+    impl Recognizer for Fixed {
+        fn recognize(&self, _path: &Path) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn file(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("flow-{}-{}", std::process::id(), name));
+        fs::write(&path, b"\x89PNG").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_link() {
+        assert_eq!(
+            link("assets/scan.PNG", "journal/2024-05-01.md"),
+            "![scan.PNG](../assets/scan.PNG)"
+        );
+        assert_eq!(
+            link("assets/paper.pdf", "pages/clips/paper.md"),
+            "[paper.pdf](../../assets/paper.pdf)"
+        );
+    }
+
+    #[test]
+    fn test_attach_stores_recognized_text_below_image() {
+        let mut space = Space::in_memory().unwrap();
+        let image = file("board.png");
+        let attached = space
+            .attach(&image, Some(&Fixed("Merge queue\nfirst\n")))
+            .unwrap();
+        assert_eq!(attached.text.as_deref(), Some("Merge queue first"));
+
+        let journal = space.read_page(&attached.page).unwrap().unwrap();
+        let name = attached.path.rsplit('/').next().unwrap();
+        assert!(journal.contains(&format!(
+            "- ![{}](../{})\n  - Merge queue first\n    ocr:: {}",
+            name, attached.path, attached.path
+        )));
+        assert!(space
+            .text_index()
+            .unwrap()
+            .search("merge queue")
+            .iter()
+            .any(|found| found.id == attached.page));
+        fs::remove_file(image).unwrap();
+    }
+
+    #[test]
+    fn test_attach_skips_recognition_for_other_files() {
+        let mut space = Space::in_memory().unwrap();
+        let document = file("notes.pdf");
+        let attached = space.attach(&document, Some(&Fixed("ignored"))).unwrap();
+        assert!(attached.text.is_none());
+        fs::remove_file(document).unwrap();
+    }
+}
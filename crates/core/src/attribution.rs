@@ -0,0 +1,397 @@
+//! Change Attribution
+//!
+//! Every save commits the pending document changes with the author and device
+//! of this machine as commit message, so the identity travels with the changes
+//! through sync, backups and snapshots. The identity is configured per machine
+//! (`author` and `device` in the Flow configuration) and falls back to the
+//! user and host names of the system.
+//!
+//! From the recorded changes, [`Space::log`] lists the history of a space and
+//! [`Space::blame`] finds who last touched each block of a page. Blame replays
+//! the history change by change, so it gets slower as the history grows;
+//! compacted history is attributed to the oldest remaining change.
+
+use chrono::{DateTime, Local};
+use loro::{LoroDoc, VersionVector, ID};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+
+use crate::space::{container_names, Space};
+
+/// Identity changes are attributed to.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name of the author.
+/// - `device` (`String`) - Name of the device the author works on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+pub struct Author {
+    pub name: String,
+    pub device: String,
+}
+
+impl Default for Author {
+    fn default() -> Self {
+        let name = env::var("USER")
+            .or_else(|_| env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        let device = env::var("HOSTNAME")
+            .or_else(|_| env::var("COMPUTERNAME"))
+            .ok()
+            .or_else(|| {
+                fs::read_to_string("/etc/hostname")
+                    .ok()
+                    .map(|host| host.trim().to_string())
+            })
+            .filter(|host| !host.is_empty())
+            .unwrap_or_else(|| "unknown".to_string());
+        Self { name, device }
+    }
+}
+
+impl Author {
+    /// Encodes the author as commit message.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - JSON encoded author.
+    pub(crate) fn to_message(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Decodes an author from a commit message.
+    ///
+    /// # Arguments
+    ///
+    /// - `message` (`&str`) - Commit message of a change.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<Self>` - The author, `None` for changes made without attribution.
+    pub(crate) fn from_message(message: &str) -> Option<Self> {
+        serde_json::from_str(message).ok()
+    }
+}
+
+/// A change in the history of a space.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the change, `counter@peer`.
+/// - `timestamp` (`i64`) - Commit time as unix timestamp in seconds.
+/// - `author` (`Option<Author>`) - Who made the change, if recorded.
+/// - `operations` (`usize`) - Number of operations in the change.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct Change {
+    pub id: String,
+    pub timestamp: i64,
+    pub author: Option<Author>,
+    pub operations: usize,
+}
+
+impl Change {
+    /// Returns when the change was committed.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<DateTime<Local>>` - Local commit time, `None` if out of range.
+    pub fn time(&self) -> Option<DateTime<Local>> {
+        DateTime::from_timestamp(self.timestamp, 0).map(|time| time.with_timezone(&Local))
+    }
+}
+
+/// Attribution of a line of a page.
+///
+/// # Fields
+///
+/// - `line` (`usize`) - 1-based line number.
+/// - `text` (`String`) - Content of the line.
+/// - `change` (`Option<Change>`) - Change that last touched the line, `None` if unknown.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct BlameLine {
+    pub line: usize,
+    pub text: String,
+    pub change: Option<Change>,
+}
+
+/// Attribution of a block of a page, a bullet with its continuation lines.
+///
+/// # Fields
+///
+/// - `start` (`usize`) - 1-based first line of the block.
+/// - `end` (`usize`) - 1-based last line of the block.
+/// - `text` (`String`) - Content of the block.
+/// - `change` (`Option<Change>`) - Latest change touching any line of the block, `None` if unknown.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct BlameBlock {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub change: Option<Change>,
+}
+
+/// Groups attributed lines into blocks.
+///
+/// A block starts at every bullet (`- `) and every line following a blank
+/// line, and extends over the lines below it that don't start a new block.
+///
+/// # Arguments
+///
+/// - `lines` (`&[BlameLine]`) - Attributed lines of a page.
+///
+/// # Returns
+///
+/// - `Vec<BlameBlock>` - Attributed blocks in page order.
+pub fn blocks(lines: &[BlameLine]) -> Vec<BlameBlock> {
+    let mut blocks: Vec<BlameBlock> = Vec::new();
+    let mut previous_blank = true;
+    for line in lines {
+        let trimmed = line.text.trim_start();
+        let blank = trimmed.is_empty();
+        let starts_block = trimmed.starts_with("- ") || trimmed == "-" || previous_blank;
+        previous_blank = blank;
+        if blank {
+            continue;
+        }
+
+        match blocks.last_mut() {
+            Some(block) if !starts_block => {
+                block.end = line.line;
+                block.text.push('\n');
+                block.text.push_str(&line.text);
+                let newer = match (&block.change, &line.change) {
+                    (Some(current), Some(change)) => change.timestamp > current.timestamp,
+                    (None, Some(_)) => true,
+                    _ => false,
+                };
+                if newer {
+                    block.change = line.change.clone();
+                }
+            }
+            _ => blocks.push(BlameBlock {
+                start: line.line,
+                end: line.line,
+                text: line.text.clone(),
+                change: line.change.clone(),
+            }),
+        }
+    }
+    blocks
+}
+
+impl Space {
+    /// Returns the identity changes of this space are attributed to.
+    ///
+    /// # Returns
+    ///
+    /// - `&Author` - Reference to the author.
+    pub fn author(&self) -> &Author {
+        &self.author
+    }
+
+    /// Sets the identity future changes are attributed to.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to attribute changes in.
+    /// - `author` (`Author`) - Author of future changes.
+    pub fn set_author(&mut self, author: Author) {
+        self.author = author;
+    }
+
+    /// Lists the changes of the space, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to list the history of.
+    /// - `limit` (`usize`) - Maximum number of changes.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Change>` - The most recent changes.
+    pub fn log(&self, limit: usize) -> Vec<Change> {
+        let mut changes = changes(&self.document);
+        changes.reverse();
+        changes.truncate(limit);
+        changes.into_iter().map(|(_, _, change)| change).collect()
+    }
+
+    /// Finds the change that last touched each line of a page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space containing the page.
+    /// - `name` (`&str`) - Name of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<Vec<BlameLine>>>` - Attributed lines, `None` if the page doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the index.
+    pub fn blame(&self, name: &str) -> Result<Option<Vec<BlameLine>>> {
+        let id = self.page_index()?.resolve(name);
+        if !container_names(&self.document).contains(&id) {
+            return Ok(None);
+        }
+
+        let doc = &self.document;
+        let mut lines: Vec<(String, Option<Change>)> = Vec::new();
+        let mut version = VersionVector::new();
+        let mut previous = String::new();
+
+        // Lamport order is causal, so every prefix of it is a valid version
+        for (last, _, change) in changes(doc) {
+            version.set_last(last);
+            // Versions before a compaction can't be checked out
+            if doc.checkout(&doc.vv_to_frontiers(&version)).is_err() {
+                continue;
+            }
+            let content = doc.get_text(id.as_str()).to_string();
+            if content != previous {
+                lines = attribute(&lines, &content, &change);
+                previous = content;
+            }
+        }
+        doc.checkout_to_latest();
+
+        let current = doc.get_text(id.as_str()).to_string();
+        if current != previous {
+            // Changes not committed yet
+            lines = current
+                .lines()
+                .map(|line| (line.to_string(), None))
+                .collect();
+        }
+
+        Ok(Some(
+            lines
+                .into_iter()
+                .enumerate()
+                .map(|(index, (text, change))| BlameLine {
+                    line: index + 1,
+                    text,
+                    change,
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Lists all changes of a document in Lamport order, oldest first.
+///
+/// # Returns
+///
+/// - `Vec<(ID, u32, Change)>` - Id of the last operation, Lamport timestamp and the change.
+pub(crate) fn changes(doc: &LoroDoc) -> Vec<(ID, u32, Change)> {
+    let mut changes = Vec::new();
+    for (&peer, &end) in doc.oplog_vv().iter() {
+        let mut counter = 0;
+        while counter < end {
+            let Some(change) = doc.get_change(ID::new(peer, counter)) else {
+                break;
+            };
+            counter = change.id.counter + change.len as i32;
+            changes.push((
+                ID::new(peer, counter - 1),
+                change.lamport,
+                Change {
+                    id: format!("{}@{}", change.id.counter, peer),
+                    timestamp: change.timestamp,
+                    author: change.message.as_deref().and_then(Author::from_message),
+                    operations: change.len,
+                },
+            ));
+        }
+    }
+    changes.sort_by_key(|(id, lamport, _)| (*lamport, id.peer));
+    changes
+}
+
+/// Carries line attributions over to a new version of a page.
+///
+/// Lines kept unchanged keep their change, all others are attributed to the
+/// change that produced the new version.
+fn attribute(
+    lines: &[(String, Option<Change>)],
+    content: &str,
+    change: &Change,
+) -> Vec<(String, Option<Change>)> {
+    let new: Vec<&str> = content.lines().collect();
+
+    // Longest common subsequence of lines, table of suffix lengths
+    let mut table = vec![vec![0usize; new.len() + 1]; lines.len() + 1];
+    for i in (0..lines.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i][j] = if lines[i].0 == new[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut attributed = Vec::with_capacity(new.len());
+    let (mut i, mut j) = (0, 0);
+    while j < new.len() {
+        if i < lines.len() && lines[i].0 == new[j] {
+            attributed.push(lines[i].clone());
+            i += 1;
+            j += 1;
+        } else if i < lines.len() && table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            attributed.push((new[j].to_string(), Some(change.clone())));
+            j += 1;
+        }
+    }
+    attributed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks() {
+        let change = |timestamp| Change {
+            id: format!("{}@1", timestamp),
+            timestamp,
+            author: None,
+            operations: 1,
+        };
+        let line = |line, text: &str, timestamp| BlameLine {
+            line,
+            text: text.to_string(),
+            change: Some(change(timestamp)),
+        };
+        let lines = vec![
+            line(1, "- first", 10),
+            line(2, "  continued", 30),
+            line(3, "  - child", 20),
+            line(4, "", 40),
+            line(5, "paragraph", 50),
+        ];
+
+        let blocks = blocks(&lines);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!((blocks[0].start, blocks[0].end), (1, 2));
+        assert_eq!(blocks[0].change.as_ref().unwrap().timestamp, 30);
+        assert_eq!(blocks[1].text, "  - child");
+        assert_eq!(blocks[2].start, 5);
+    }
+
+    #[test]
+    fn test_author_message_roundtrip() {
+        let author = Author {
+            name: "ada".to_string(),
+            device: "laptop".to_string(),
+        };
+        assert_eq!(Author::from_message(&author.to_message()), Some(author));
+        assert_eq!(Author::from_message("fix typo"), None);
+    }
+}
@@ -0,0 +1,406 @@
+//! Audio Memos
+//!
+//! Adds voice memos to the journal. The recording is saved to `assets/audio/`
+//! and, if a transcription backend is configured, transcribed into the text
+//! of the block linking to it:
+//!
+//! ```markdown
+//! - Remember to ask about the sync rewrite. [memo.m4a](../assets/audio/memo.m4a)
+//! ```
+//!
+//! Backends are configured per space in the space metadata. A local
+//! [whisper.cpp](https://github.com/ggerganov/whisper.cpp) binary keeps
+//! recordings on the machine; any OpenAI-compatible `/audio/transcriptions`
+//! endpoint works as well with Flow built with the `transcription-http`
+//! feature:
+//!
+//! ```toml
+//! [audio.transcription]
+//! kind = "whisper-cpp"
+//! model = "/home/me/models/ggml-base.en.bin"
+//! ```
+//!
+//! ```toml
+//! [audio.transcription]
+//! kind = "http"
+//! endpoint = "https://api.openai.com/v1/audio/transcriptions"
+//! api-key = "secret:openai"
+//! ```
+//!
+//! Without a backend the recording is linked on its own.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::attach;
+use crate::space::Space;
+
+/// Directory recordings are saved to, relative to the space.
+pub const RECORDING_DIR: &str = "assets/audio";
+
+/// Audio settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `transcription` (`Option<Transcription>`) - Transcription backend, `None` if not configured.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioSettings {
+    #[serde(default)]
+    pub transcription: Option<Transcription>,
+}
+
+/// A backend transcribing recordings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Transcription {
+    /// A local whisper.cpp binary.
+    WhisperCpp {
+        /// Path or name of the binary.
+        #[serde(default = "default_binary")]
+        binary: PathBuf,
+        /// Model file to transcribe with.
+        model: PathBuf,
+        /// Spoken language, detected if not set.
+        #[serde(default)]
+        language: Option<String>,
+    },
+    /// An OpenAI-compatible `/audio/transcriptions` endpoint.
+    #[serde(rename_all = "kebab-case")]
+    Http {
+        /// URL of the endpoint.
+        endpoint: String,
+        /// Model to request.
+        #[serde(default = "default_model")]
+        model: String,
+        /// API key, usually a `secret:` reference.
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+}
+
+fn default_binary() -> PathBuf {
+    PathBuf::from("whisper-cli")
+}
+
+fn default_model() -> String {
+    "whisper-1".to_string()
+}
+
+/// Transcribes recordings into text.
+pub trait Transcriber {
+    /// Transcribes a recording.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Transcriber`) - Transcriber to use.
+    /// - `path` (`&Path`) - Path of the recording.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - The spoken text.
+    ///
+    /// # Errors
+    ///
+    /// Backend specific errors, e.g. when a binary is missing or an endpoint
+    /// is unreachable.
+    fn transcribe(&self, path: &Path) -> Result<String>;
+}
+
+/// Transcriber running a local whisper.cpp binary.
+///
+/// # Fields
+///
+/// - `binary` (`PathBuf`) - Path or name of the binary.
+/// - `model` (`PathBuf`) - Model file to transcribe with.
+/// - `language` (`Option<String>`) - Spoken language, detected if `None`.
+#[derive(Debug, Clone)]
+pub struct WhisperCpp {
+    binary: PathBuf,
+    model: PathBuf,
+    language: Option<String>,
+}
+
+impl Transcriber for WhisperCpp {
+    fn transcribe(&self, path: &Path) -> Result<String> {
+        let mut command = process::Command::new(&self.binary);
+        command
+            .arg("--model")
+            .arg(&self.model)
+            .arg("--file")
+            .arg(path)
+            .args(["--no-timestamps", "--no-prints"]);
+        if let Some(language) = &self.language {
+            command.args(["--language", language]);
+        }
+
+        let output = command.output().map_err(|error| {
+            miette::miette!("Failed to run '{}': {}", self.binary.display(), error)
+        })?;
+        if !output.status.success() {
+            miette::bail!(
+                "'{}' failed: {}",
+                self.binary.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// Transcriber querying an OpenAI-compatible `/audio/transcriptions` endpoint.
+///
+/// # Fields
+///
+/// - `endpoint` (`String`) - URL of the endpoint.
+/// - `model` (`String`) - Model to request.
+/// - `api_key` (`Option<String>`) - Resolved API key, sent as bearer token.
+#[cfg(feature = "transcription-http")]
+#[derive(Debug, Clone)]
+pub struct HttpTranscriber {
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[cfg(feature = "transcription-http")]
+impl Transcriber for HttpTranscriber {
+    fn transcribe(&self, path: &Path) -> Result<String> {
+        use miette::IntoDiagnostic;
+
+        #[derive(Deserialize)]
+        struct Response {
+            text: String,
+        }
+
+        // ureq has no multipart support, the form is small enough to build by hand
+        let boundary = format!("flow-{}", uuid::Uuid::new_v4().simple());
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().replace('"', ""))
+            .unwrap_or_else(|| "recording".to_string());
+        let mut body = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\n{}\r\n--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+            self.model,
+            name,
+            b = boundary
+        )
+        .into_bytes();
+        body.extend(fs::read(path).into_diagnostic()?);
+        body.extend(format!("\r\n--{}--\r\n", boundary).into_bytes());
+
+        let mut request = ureq::post(&self.endpoint).set(
+            "Content-Type",
+            &format!("multipart/form-data; boundary={}", boundary),
+        );
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let response: Response = request
+            .send_bytes(&body)
+            .into_diagnostic()?
+            .into_json()
+            .into_diagnostic()?;
+        Ok(response.text)
+    }
+}
+
+/// Creates the transcriber configured by the given settings.
+///
+/// # Arguments
+///
+/// - `settings` (`&Transcription`) - Transcription backend of a space.
+///
+/// # Returns
+///
+/// - `Result<Box<dyn Transcriber>>` - The configured transcriber.
+///
+/// # Errors
+///
+/// The API key can't be resolved, or Flow was built without the
+/// `transcription-http` feature for HTTP backends.
+pub fn transcriber(settings: &Transcription) -> Result<Box<dyn Transcriber>> {
+    match settings {
+        Transcription::WhisperCpp {
+            binary,
+            model,
+            language,
+        } => Ok(Box::new(WhisperCpp {
+            binary: binary.clone(),
+            model: model.clone(),
+            language: language.clone(),
+        })),
+        #[cfg(feature = "transcription-http")]
+        Transcription::Http {
+            endpoint,
+            model,
+            api_key,
+        } => {
+            let api_key = match api_key {
+                Some(key) => Some(crate::secrets::Secrets::open()?.resolve(key)?),
+                None => None,
+            };
+            Ok(Box::new(HttpTranscriber {
+                endpoint: endpoint.clone(),
+                model: model.clone(),
+                api_key,
+            }))
+        }
+        #[cfg(not(feature = "transcription-http"))]
+        Transcription::Http { .. } => {
+            miette::bail!(
+                "The HTTP transcription backend requires Flow built with the 'transcription-http' feature"
+            )
+        }
+    }
+}
+
+/// An audio memo added to the journal.
+///
+/// # Fields
+///
+/// - `recording` (`String`) - Path of the saved recording, relative to the space.
+/// - `transcript` (`Option<String>`) - The transcript, `None` without a transcription backend.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AddedAudio {
+    pub recording: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
+}
+
+impl Space {
+    /// Returns the audio settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&AudioSettings` - Reference to the space's audio settings.
+    pub fn audio_settings(&self) -> &AudioSettings {
+        &self.metadata.audio
+    }
+
+    /// Saves a recording and adds it as a note, transcribed if possible.
+    ///
+    /// The note goes where [`Space::add`] puts content, respecting the
+    /// capture context.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to add the memo to.
+    /// - `file` (`&Path`) - The recording.
+    /// - `transcriber` (`Option<&dyn Transcriber>`) - Backend transcribing the recording, if any.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<AddedAudio>` - The saved recording and its transcript.
+    ///
+    /// # Errors
+    ///
+    /// The recording can't be read, transcription fails, the target page is
+    /// locked, or IO errors when writing files.
+    pub fn add_audio(
+        &mut self,
+        file: &Path,
+        transcriber: Option<&dyn Transcriber>,
+    ) -> Result<AddedAudio> {
+        let content = fs::read(file)
+            .map_err(|error| miette::miette!("Failed to read '{}': {}", file.display(), error))?;
+        let transcript = match transcriber {
+            Some(transcriber) => Some(
+                transcriber
+                    .transcribe(file)?
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            None => None,
+        };
+
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let recording = self.save_asset(RECORDING_DIR, &name, &content)?;
+        // Blocks land on pages one directory deep, the journal or `pages/`
+        let link = attach::link(&recording, &self.journal_page(self.today()));
+        match transcript.as_deref() {
+            Some(text) if !text.is_empty() => self.add(&format!("{} {}", text, link))?,
+            _ => self.add(&link)?,
+        }
+
+        Ok(AddedAudio {
+            recording,
+            transcript,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str);
+
+    impl Transcriber for Fixed {
+        fn transcribe(&self, _path: &Path) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn recording(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("flow-{}-{}", std::process::id(), name));
+        fs::write(&path, b"RIFF").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_add_audio_with_transcript() {
+        let mut space = Space::in_memory().unwrap();
+        let file = recording("memo.wav");
+        let added = space
+            .add_audio(&file, Some(&Fixed(" Ask about\n the sync rewrite.\n")))
+            .unwrap();
+        let name = file.file_name().unwrap().to_string_lossy().to_string();
+        assert_eq!(added.recording, format!("assets/audio/{}", name));
+        assert_eq!(
+            added.transcript.as_deref(),
+            Some("Ask about the sync rewrite.")
+        );
+
+        let journal = space
+            .read_page(&space.journal_page(space.today()))
+            .unwrap()
+            .unwrap();
+        assert!(journal.contains(&format!(
+            "- Ask about the sync rewrite. [{}](../assets/audio/{})",
+            name, name
+        )));
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_add_audio_without_transcriber_links_recording() {
+        let mut space = Space::in_memory().unwrap();
+        let file = recording("plain.wav");
+        let first = space.add_audio(&file, None).unwrap();
+        let second = space.add_audio(&file, None).unwrap();
+        assert!(first.transcript.is_none());
+        assert_ne!(first.recording, second.recording);
+        assert!(second.recording.ends_with("-2.wav"));
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_transcription_settings() {
+        let settings: AudioSettings = toml::from_str(
+            "[transcription]\nkind = \"whisper-cpp\"\nmodel = \"ggml-base.en.bin\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            settings.transcription,
+            Some(Transcription::WhisperCpp { binary, .. }) if binary == Path::new("whisper-cli")
+        ));
+    }
+}
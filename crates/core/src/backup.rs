@@ -0,0 +1,342 @@
+//! Space Document Backups
+//!
+//! Before the document snapshot of a space is overwritten, the previous
+//! snapshot is copied to `.flow/backups/`. Backups are rotated according to the
+//! space's [`BackupSettings`], keeping at most `count` files and at most
+//! `max_size` bytes in total (the newest backup is always kept).
+
+use chrono::{DateTime, Local};
+use loro::{ExportMode, LoroDoc};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cancel::CancellationToken;
+use crate::fsck;
+use crate::fulltext::TextIndex;
+use crate::index::PageIndex;
+use crate::progress::NoProgress;
+use crate::space::{
+    read_document, read_updates, write_snapshot, Metadata, DOCUMENT_FILE, FLOW_DIR,
+};
+use crate::storage::FsStorage;
+
+const BACKUP_DIR: &str = "backups";
+const BACKUP_EXTENSION: &str = "loro";
+const DEFAULT_COUNT: usize = 5;
+const DEFAULT_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Backup settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `count` (`usize`) - Maximum number of backups to keep (0 disables backups).
+/// - `max_size` (`u64`) - Maximum total size of all backups in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSettings {
+    #[serde(default = "default_count")]
+    pub count: usize,
+    #[serde(default = "default_max_size")]
+    pub max_size: u64,
+}
+
+fn default_count() -> usize {
+    DEFAULT_COUNT
+}
+
+fn default_max_size() -> u64 {
+    DEFAULT_MAX_SIZE
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            count: DEFAULT_COUNT,
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+impl BackupSettings {
+    /// Reads the backup settings of the space at the given path.
+    ///
+    /// Falls back to the defaults if the space metadata can not be read, so
+    /// backups stay usable when a space is partially broken.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - Backup settings of the space.
+    pub fn for_space(space_path: &Path) -> Self {
+        Metadata::read(&FsStorage, space_path)
+            .map(|metadata| metadata.backups)
+            .unwrap_or_default()
+    }
+}
+
+/// A single document backup.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Identifier of the backup (its timestamp).
+/// - `path` (`PathBuf`) - Path of the backup file.
+/// - `size` (`u64`) - Size of the backup in bytes.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Backup {
+    pub id: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Backs up the current document snapshot of a space and rotates old backups.
+///
+/// Does nothing if the space has no snapshot yet or backups are disabled.
+///
+/// # Arguments
+///
+/// - `space_path` (`&Path`) - Path of the space.
+/// - `settings` (`&BackupSettings`) - Rotation settings.
+///
+/// # Returns
+///
+/// - `Result<Option<Backup>>` - The created backup, if any.
+///
+/// # Errors
+///
+/// IO errors when copying the snapshot or removing old backups.
+pub fn create(space_path: &Path, settings: &BackupSettings) -> Result<Option<Backup>> {
+    let doc_path = space_path.join(FLOW_DIR).join(DOCUMENT_FILE);
+    if settings.count == 0 || !doc_path.exists() {
+        return Ok(None);
+    }
+
+    let backup_dir = backup_dir(space_path);
+    fs::create_dir_all(&backup_dir).into_diagnostic()?;
+
+    let id = backup_id(Local::now());
+    let path = backup_dir.join(format!("{}.{}", id, BACKUP_EXTENSION));
+    let size = if read_updates(&FsStorage, space_path)?.is_empty() {
+        fs::copy(&doc_path, &path).into_diagnostic()?
+    } else {
+        // Merge pending updates so the backup captures the current state
+        let doc = LoroDoc::new();
+        read_document(&FsStorage, space_path, &doc)?;
+        let snapshot = doc.export(ExportMode::Snapshot).into_diagnostic()?;
+        fs::write(&path, &snapshot).into_diagnostic()?;
+        snapshot.len() as u64
+    };
+
+    rotate(space_path, settings)?;
+
+    Ok(Some(Backup { id, path, size }))
+}
+
+/// Lists all backups of a space, newest first.
+///
+/// # Arguments
+///
+/// - `space_path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<Vec<Backup>>` - All backups of the space.
+///
+/// # Errors
+///
+/// IO errors when reading the backup directory.
+pub fn list(space_path: &Path) -> Result<Vec<Backup>> {
+    let backup_dir = backup_dir(space_path);
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&backup_dir).into_diagnostic()? {
+        let entry = entry.into_diagnostic()?;
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != BACKUP_EXTENSION) {
+            continue;
+        }
+
+        let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let size = entry.metadata().into_diagnostic()?.len();
+        backups.push(Backup { id, path, size });
+    }
+
+    // Ids are timestamps, so sorting them sorts by creation time.
+    backups.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(backups)
+}
+
+/// Restores a backup as the current document snapshot of a space.
+///
+/// The current snapshot is backed up first, so a restore can be undone. The
+/// markdown files and the indexes are rebuilt from the restored document,
+/// otherwise the next save would write the newer content back.
+///
+/// # Arguments
+///
+/// - `space_path` (`&Path`) - Path of the space.
+/// - `id` (`&str`) - Identifier of the backup to restore.
+/// - `settings` (`&BackupSettings`) - Rotation settings.
+///
+/// # Returns
+///
+/// - `Result<Backup>` - The restored backup.
+///
+/// # Errors
+///
+/// Returns an error if no backup with the given id exists or IO fails.
+pub fn restore(space_path: &Path, id: &str, settings: &BackupSettings) -> Result<Backup> {
+    let backup = list(space_path)?
+        .into_iter()
+        .find(|backup| backup.id == id)
+        .ok_or_else(|| miette::miette!("Backup '{}' not found", id))?;
+
+    // Read first, the backup itself might be rotated away by the next step.
+    let bytes = fs::read(&backup.path).into_diagnostic()?;
+    create(space_path, settings)?;
+
+    write_snapshot(&FsStorage, space_path, &bytes)?;
+    fsck::rebuild_files(space_path, &NoProgress, &CancellationToken::new())?;
+    PageIndex::rebuild(space_path)?.save(space_path)?;
+    TextIndex::rebuild(space_path, &NoProgress, &CancellationToken::new())?.save(space_path)?;
+
+    Ok(backup)
+}
+
+/// Returns the backups exceeding the rotation settings.
+///
+/// Backups pile up when the settings are lowered, as rotation only runs when
+/// a new backup is created.
+///
+/// # Arguments
+///
+/// - `backups` (`&[Backup]`) - Backups of a space, newest first (see [`list`]).
+/// - `settings` (`&BackupSettings`) - Rotation settings.
+///
+/// # Returns
+///
+/// - `Vec<Backup>` - Backups rotation removes, the newest is always kept.
+pub fn excess(backups: &[Backup], settings: &BackupSettings) -> Vec<Backup> {
+    let mut total = 0;
+    let mut excess = Vec::new();
+    for (index, backup) in backups.iter().enumerate() {
+        total += backup.size;
+        let keep = index == 0 || (index < settings.count && total <= settings.max_size);
+        if !keep {
+            excess.push(backup.clone());
+        }
+    }
+    excess
+}
+
+/// Removes the oldest backups until the rotation settings are satisfied.
+///
+/// # Arguments
+///
+/// - `space_path` (`&Path`) - Path of the space.
+/// - `settings` (`&BackupSettings`) - Rotation settings.
+///
+/// # Returns
+///
+/// - `Result<Vec<Backup>>` - The removed backups.
+///
+/// # Errors
+///
+/// IO errors when reading the backup directory or removing backups.
+pub fn rotate(space_path: &Path, settings: &BackupSettings) -> Result<Vec<Backup>> {
+    let excess = excess(&list(space_path)?, settings);
+    for backup in &excess {
+        fs::remove_file(&backup.path).into_diagnostic()?;
+    }
+    Ok(excess)
+}
+
+fn backup_dir(space_path: &Path) -> PathBuf {
+    space_path.join(FLOW_DIR).join(BACKUP_DIR)
+}
+
+fn backup_id(time: DateTime<Local>) -> String {
+    time.format("%Y%m%dT%H%M%S%3f").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::space::Space;
+
+    fn scratch_space(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("flow-backup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Space::init(&dir, None, true).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_restore_rewrites_pages() {
+        let path = scratch_space("restore");
+        let settings = BackupSettings::default();
+        let mut space = Space::load(&path).unwrap();
+        space.write_page("notes.md", "- Old\n").unwrap();
+        let backup = create(&path, &settings).unwrap().unwrap();
+        space.write_page("notes.md", "- New\n").unwrap();
+        drop(space);
+
+        restore(&path, &backup.id, &settings).unwrap();
+        assert_eq!(
+            fs::read_to_string(path.join("notes.md")).unwrap(),
+            "- Old\n"
+        );
+
+        let mut space = Space::load(&path).unwrap();
+        space.write_page("other.md", "- Other\n").unwrap();
+        assert_eq!(
+            fs::read_to_string(path.join("notes.md")).unwrap(),
+            "- Old\n"
+        );
+        assert_eq!(
+            space.read_page("notes.md").unwrap().as_deref(),
+            Some("- Old\n")
+        );
+    }
+
+    #[test]
+    fn test_excess() {
+        let backup = |id: &str, size| Backup {
+            id: id.to_string(),
+            path: PathBuf::from(format!("{}.loro", id)),
+            size,
+        };
+        let backups = [backup("3", 40), backup("2", 40), backup("1", 40)];
+        let ids = |settings: &BackupSettings| -> Vec<String> {
+            excess(&backups, settings)
+                .into_iter()
+                .map(|backup| backup.id)
+                .collect()
+        };
+
+        let settings = BackupSettings {
+            count: 2,
+            max_size: 1000,
+        };
+        assert_eq!(ids(&settings), vec!["1"]);
+        let settings = BackupSettings {
+            count: 5,
+            max_size: 50,
+        };
+        assert_eq!(ids(&settings), vec!["2", "1"]);
+        let settings = BackupSettings {
+            count: 0,
+            max_size: 0,
+        };
+        assert_eq!(ids(&settings), vec!["2", "1"]);
+    }
+}
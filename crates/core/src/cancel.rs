@@ -0,0 +1,67 @@
+//! Cancellation
+//!
+//! Long-running operations take a [`CancellationToken`] and check it at safe
+//! checkpoints, i.e. between steps that each leave the space consistent. A
+//! cancelled operation stops with a [`Cancelled`] error before writing any
+//! partial results.
+
+use miette::Diagnostic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Error returned by operations that were cancelled.
+#[derive(Debug, Error, Diagnostic)]
+#[error("Operation cancelled")]
+#[diagnostic(code(flow::cancelled))]
+pub struct Cancelled;
+
+/// Shared flag requesting an operation to stop.
+///
+/// Clones share the same flag, so a token can be handed to a signal handler
+/// while the operation checks another clone.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - A fresh token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of all operations holding a clone of this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Checks whether cancellation was requested.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Checkpoint for operations, fails if cancellation was requested.
+    ///
+    /// # Errors
+    ///
+    /// [`Cancelled`] if the token was cancelled.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {}
@@ -0,0 +1,390 @@
+//! Flashcards
+//!
+//! Blocks tagged `#card` are flashcards: the block is the question, its
+//! nested blocks are the answer. Blocks with a cloze deletion
+//! (`The capital of France is {{cloze Paris}}`) are cards as well, asking
+//! for the hidden text.
+//!
+//! Cards are scheduled with SM-2: each review grades recall from 0 to 5 and
+//! moves the next review further out the better it went. Review state is
+//! stored per space in `.flow/cards.json`, keyed by the page and question of
+//! each card, so editing a question starts its card over.
+
+use chrono::{Duration, NaiveDate};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::page;
+use crate::space::{write_atomic, Space, FLOW_DIR};
+
+const CARDS_FILE: &str = "cards.json";
+
+/// Tag marking a block as flashcard.
+pub const CARD_TAG: &str = "#card";
+
+/// Ease factor of cards never reviewed.
+const INITIAL_EASE: f64 = 2.5;
+
+/// Lowest ease factor, so hard cards still move out eventually.
+const MINIMUM_EASE: f64 = 1.3;
+
+/// A flashcard.
+///
+/// # Fields
+///
+/// - `key` (`String`) - Stable key of the card, derived from its page and question.
+/// - `id` (`String`) - Id of the page containing the card.
+/// - `page` (`String`) - Name of the page containing the card.
+/// - `question` (`String`) - Question, with cloze deletions hidden.
+/// - `answer` (`String`) - Answer, the nested blocks or the full cloze text.
+/// - `review` (`Option<Review>`) - Review state, `None` for new cards.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Card {
+    pub key: String,
+    pub id: String,
+    pub page: String,
+    pub question: String,
+    pub answer: String,
+    pub review: Option<Review>,
+}
+
+impl Card {
+    /// Checks whether the card is due for review.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Card`) - Card to check.
+    /// - `today` (`NaiveDate`) - Date of the review session.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True for new cards and cards due today or earlier.
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.review
+            .as_ref()
+            .is_none_or(|review| review.due <= today)
+    }
+}
+
+/// SM-2 review state of a card.
+///
+/// # Fields
+///
+/// - `ease` (`f64`) - Ease factor the interval grows by.
+/// - `interval` (`u32`) - Days until the next review.
+/// - `repetitions` (`u32`) - Successful reviews in a row.
+/// - `reviewed` (`NaiveDate`) - Date of the last review.
+/// - `due` (`NaiveDate`) - Date of the next review.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema, Deserialize)]
+pub struct Review {
+    pub ease: f64,
+    pub interval: u32,
+    pub repetitions: u32,
+    pub reviewed: NaiveDate,
+    pub due: NaiveDate,
+}
+
+impl Review {
+    /// Schedules the next review of a card.
+    ///
+    /// Grades below 3 count as forgotten and start the card over, the ease
+    /// factor is adjusted by every review.
+    ///
+    /// # Arguments
+    ///
+    /// - `previous` (`Option<&Review>`) - Review state so far, `None` for new cards.
+    /// - `grade` (`u8`) - Quality of recall from 0 (blackout) to 5 (perfect), clamped.
+    /// - `today` (`NaiveDate`) - Date of the review.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The new review state.
+    pub fn schedule(previous: Option<&Review>, grade: u8, today: NaiveDate) -> Self {
+        let grade = grade.min(5);
+        let (ease, interval, repetitions) = previous
+            .map(|review| (review.ease, review.interval, review.repetitions))
+            .unwrap_or((INITIAL_EASE, 0, 0));
+
+        let (interval, repetitions) = if grade < 3 {
+            (1, 0)
+        } else {
+            let interval = match repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (f64::from(interval) * ease).round() as u32,
+            };
+            (interval, repetitions + 1)
+        };
+        let miss = f64::from(5 - grade);
+        let ease = (ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MINIMUM_EASE);
+
+        Self {
+            ease,
+            interval,
+            repetitions,
+            reviewed: today,
+            due: today + Duration::days(i64::from(interval)),
+        }
+    }
+}
+
+/// Review state of all cards of a space.
+///
+/// # Fields
+///
+/// - `reviews` (`BTreeMap<String, Review>`) - Review state by card key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Deck {
+    #[serde(default)]
+    pub reviews: BTreeMap<String, Review>,
+}
+
+impl Deck {
+    /// Loads the review state of a space, empty if missing or unreadable.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The review state.
+    pub fn load(space_path: &Path) -> Self {
+        fs::read_to_string(space_path.join(FLOW_DIR).join(CARDS_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persists the review state of a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Deck`) - Review state to persist.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the cards file.
+    pub fn save(&self, space_path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).into_diagnostic()?;
+        write_atomic(&space_path.join(FLOW_DIR).join(CARDS_FILE), json.as_bytes())
+    }
+}
+
+impl Space {
+    /// Collects the flashcards of the space with their review state.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to read from.
+    /// - `page` (`Option<&str>`) - Only collect the cards of this page (id), all pages if `None`.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Card>>` - Cards in page and document order.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages.
+    pub fn cards(&self, page: Option<&str>) -> Result<Vec<Card>> {
+        let deck = Deck::load(&self.path);
+        let ids = match page {
+            Some(id) => vec![id.to_string()],
+            None => self.page_ids()?,
+        };
+
+        let mut cards = Vec::new();
+        for id in ids {
+            let Some(content) = self.read_page(&id)? else {
+                continue;
+            };
+            for (question, answer) in parse_cards(&content) {
+                let key = card_key(&id, &question);
+                cards.push(Card {
+                    review: deck.reviews.get(&key).cloned(),
+                    key,
+                    page: page::name_from_id(&id),
+                    id: id.clone(),
+                    question,
+                    answer,
+                });
+            }
+        }
+        Ok(cards)
+    }
+
+    /// Returns the cards due for review, most overdue first, new cards last.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to read from.
+    /// - `page` (`Option<&str>`) - Only review the cards of this page (id), all pages if `None`.
+    /// - `limit` (`usize`) - Maximum number of cards, `0` for all.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Card>>` - Due cards.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages.
+    pub fn due_cards(&self, page: Option<&str>, limit: usize) -> Result<Vec<Card>> {
+        let today = self.today();
+        let mut due: Vec<Card> = self
+            .cards(page)?
+            .into_iter()
+            .filter(|card| card.is_due(today))
+            .collect();
+        due.sort_by_key(|card| card.review.as_ref().map_or(NaiveDate::MAX, |r| r.due));
+        if limit > 0 {
+            due.truncate(limit);
+        }
+        Ok(due)
+    }
+
+    /// Records the review of a card and schedules its next review.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space containing the card.
+    /// - `key` (`&str`) - Key of the reviewed card.
+    /// - `grade` (`u8`) - Quality of recall from 0 to 5, see [`Review::schedule`].
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Review>` - The new review state.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the cards file.
+    pub fn review_card(&self, key: &str, grade: u8) -> Result<Review> {
+        let today = self.today();
+        let mut deck = Deck::load(&self.path);
+        let review = Review::schedule(deck.reviews.get(key), grade, today);
+        deck.reviews.insert(key.to_string(), review.clone());
+        deck.save(&self.path)?;
+        Ok(review)
+    }
+}
+
+/// Returns the stable key of a card.
+fn card_key(id: &str, question: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(format!("{}\n{}", id, question)));
+    digest[..16].to_string()
+}
+
+/// Parses the cards of a page into questions and answers.
+fn parse_cards(content: &str) -> Vec<(String, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut cards = Vec::new();
+    let mut code = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            code = !code;
+        }
+        let Some(text) = trimmed.strip_prefix("- ").filter(|_| !code) else {
+            continue;
+        };
+
+        let tagged = has_card_tag(text);
+        let text = remove_card_tag(text);
+        if let Some((question, answer)) = cloze(&text) {
+            cards.push((question, answer));
+        } else if tagged {
+            let indent = line.len() - trimmed.len();
+            let answer: Vec<&str> = lines[index + 1..]
+                .iter()
+                .take_while(|l| l.trim().is_empty() || l.len() - l.trim_start().len() > indent)
+                .map(|l| l.get(indent + 2..).unwrap_or(l.trim_start()))
+                .collect();
+            cards.push((text, answer.join("\n").trim_end().to_string()));
+        }
+    }
+    cards
+}
+
+/// Checks whether a block carries the card tag.
+fn has_card_tag(text: &str) -> bool {
+    text.match_indices(CARD_TAG).any(|(start, _)| {
+        let end = start + CARD_TAG.len();
+        text[end..]
+            .chars()
+            .next()
+            .is_none_or(|c| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '/')))
+    })
+}
+
+/// Removes the card tag from a block.
+fn remove_card_tag(text: &str) -> String {
+    text.split_whitespace()
+        .filter(|word| *word != CARD_TAG)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits a block with cloze deletions into question and answer, `None` without any.
+fn cloze(text: &str) -> Option<(String, String)> {
+    let mut question = String::new();
+    let mut answer = String::new();
+    let mut rest = text;
+    let mut found = false;
+
+    while let Some(start) = rest.find("{{cloze") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let hidden = rest[start + "{{cloze".len()..start + end].trim();
+        question.push_str(&rest[..start]);
+        question.push_str("[...]");
+        answer.push_str(&rest[..start]);
+        answer.push_str(hidden);
+        rest = &rest[start + end + 2..];
+        found = true;
+    }
+    question.push_str(rest);
+    answer.push_str(rest);
+    found.then_some((question, answer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cards() {
+        let content = "- What does SM-2 stand for? #card\n  - SuperMemo 2\n  - from 1987\n- Rust was first released in {{cloze 2015}}\n- #cardboard is no card";
+        assert_eq!(
+            parse_cards(content),
+            vec![
+                (
+                    "What does SM-2 stand for?".to_string(),
+                    "- SuperMemo 2\n- from 1987".to_string()
+                ),
+                (
+                    "Rust was first released in [...]".to_string(),
+                    "Rust was first released in 2015".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schedule() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let first = Review::schedule(None, 4, today);
+        assert_eq!((first.interval, first.repetitions), (1, 1));
+        let second = Review::schedule(Some(&first), 5, today);
+        assert_eq!((second.interval, second.repetitions), (6, 2));
+        let forgotten = Review::schedule(Some(&second), 1, today);
+        assert_eq!((forgotten.interval, forgotten.repetitions), (1, 0));
+        assert!(forgotten.ease < second.ease && forgotten.ease >= MINIMUM_EASE);
+    }
+}
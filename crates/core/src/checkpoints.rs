@@ -0,0 +1,257 @@
+//! Named Versions
+//!
+//! A checkpoint gives the current version of the whole space a name, so it
+//! can later be diffed against or restored. Checkpoints are stored in the
+//! space metadata as the version vector of the document at the time, which
+//! freezes the exact set of changes they include.
+//!
+//! Restoring a checkpoint doesn't rewind history: the content pages had at
+//! the checkpoint is written as a new change, which syncs like any other edit.
+//! Pages created after the checkpoint are kept, Flow has no page deletion.
+//! Locked pages (see [`Space::set_locked`]) are left as they are unless
+//! locked pages may be written.
+
+use chrono::{DateTime, Local};
+use loro::{Frontiers, UpdateOptions, VersionVector, ID};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::attribution;
+use crate::lock;
+use crate::space::{container_names, Space};
+
+/// A named version of a space.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name of the checkpoint.
+/// - `created` (`i64`) - Creation time as unix timestamp in seconds.
+/// - `version` (`BTreeMap<String, i32>`) - Number of operations per peer included.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct Checkpoint {
+    pub name: String,
+    pub created: i64,
+    pub version: BTreeMap<String, i32>,
+}
+
+impl Checkpoint {
+    /// Returns when the checkpoint was created.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<DateTime<Local>>` - Local creation time, `None` if out of range.
+    pub fn created_at(&self) -> Option<DateTime<Local>> {
+        DateTime::from_timestamp(self.created, 0).map(|time| time.with_timezone(&Local))
+    }
+
+    /// Converts the stored version into a version vector.
+    fn version_vector(&self) -> VersionVector {
+        let mut version = VersionVector::new();
+        for (peer, &end) in &self.version {
+            if let (Ok(peer), true) = (peer.parse(), end > 0) {
+                version.set_last(ID::new(peer, end - 1));
+            }
+        }
+        version
+    }
+}
+
+/// Outcome of restoring a checkpoint.
+///
+/// # Fields
+///
+/// - `restored` (`Vec<String>`) - Ids of pages set back to their content at the checkpoint.
+/// - `kept` (`Vec<String>`) - Ids of pages created after the checkpoint, left as they are.
+/// - `locked` (`Vec<String>`) - Ids of locked pages that differ from the checkpoint, left as they are.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, Deserialize)]
+pub struct RestoreReport {
+    pub restored: Vec<String>,
+    pub kept: Vec<String>,
+    #[serde(default)]
+    pub locked: Vec<String>,
+}
+
+impl Space {
+    /// Returns the checkpoints of the space, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// - `&[Checkpoint]` - The checkpoints.
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.metadata.checkpoints
+    }
+
+    /// Creates a checkpoint of the current version.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to create the checkpoint in.
+    /// - `name` (`&str`) - Name of the checkpoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Checkpoint>` - The created checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// A checkpoint with the name already exists, or IO errors when writing the metadata.
+    pub fn tag_version(&mut self, name: &str) -> Result<Checkpoint> {
+        let name = name.trim();
+        if name.is_empty() {
+            miette::bail!("Checkpoint names can't be empty");
+        }
+        if self
+            .checkpoints()
+            .iter()
+            .any(|checkpoint| checkpoint.name == name)
+        {
+            miette::bail!("Checkpoint '{}' already exists", name);
+        }
+
+        self.document.commit();
+        let checkpoint = Checkpoint {
+            name: name.to_string(),
+            created: Local::now().timestamp(),
+            version: self
+                .document
+                .oplog_vv()
+                .iter()
+                .map(|(peer, end)| (peer.to_string(), *end))
+                .collect(),
+        };
+        self.metadata.checkpoints.push(checkpoint.clone());
+        self.metadata.write(&*self.storage, &self.path)?;
+        Ok(checkpoint)
+    }
+
+    /// Sets all pages back to their content at a checkpoint.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to restore.
+    /// - `version` (`&str`) - Name of the checkpoint or id of a change.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<RestoreReport>` - Restored, kept and locked pages.
+    ///
+    /// # Errors
+    ///
+    /// The version is unknown or was compacted away, or IO errors when saving the space.
+    pub fn restore_version(&mut self, version: &str) -> Result<RestoreReport> {
+        let frontiers = self.version_frontiers(version)?;
+        if self.document.checkout(&frontiers).is_err() {
+            self.document.checkout_to_latest();
+            miette::bail!(
+                "Version '{}' is no longer available after compaction",
+                version
+            );
+        }
+        let old: BTreeMap<String, String> = container_names(&self.document)
+            .into_iter()
+            .filter(|id| id.ends_with(".md"))
+            .map(|id| {
+                let content = self.document.get_text(id.as_str()).to_string();
+                (id, content)
+            })
+            .collect();
+        self.document.checkout_to_latest();
+
+        let mut report = RestoreReport::default();
+        for id in container_names(&self.document) {
+            if !id.ends_with(".md") {
+                continue;
+            }
+            match old.get(&id) {
+                Some(content) => {
+                    let text = self.document.get_text(id.as_str());
+                    let current = text.to_string();
+                    if current != *content {
+                        if !self.allow_locked && lock::is_locked(&current) {
+                            report.locked.push(id);
+                            continue;
+                        }
+                        if let Some(parent) = self.path.join(&id).parent() {
+                            self.storage.create_dir_all(parent)?;
+                        }
+                        text.update(content, UpdateOptions::default())
+                            .into_diagnostic()?;
+                        self.dirty.insert(id.clone());
+                        report.restored.push(id);
+                    }
+                }
+                None => report.kept.push(id),
+            }
+        }
+
+        if !report.restored.is_empty() {
+            self.save()?;
+        }
+        Ok(report)
+    }
+
+    /// Resolves a checkpoint name or change id to the frontiers of that version.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to resolve the version in.
+    /// - `version` (`&str`) - Name of a checkpoint or id of a change, `counter@peer`.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Frontiers>` - Frontiers of the version.
+    ///
+    /// # Errors
+    ///
+    /// Neither a checkpoint nor a change with that name exists.
+    pub(crate) fn version_frontiers(&self, version: &str) -> Result<Frontiers> {
+        if let Some(checkpoint) = self
+            .checkpoints()
+            .iter()
+            .find(|checkpoint| checkpoint.name == version)
+        {
+            return Ok(self.document.vv_to_frontiers(&checkpoint.version_vector()));
+        }
+
+        match attribution::changes(&self.document)
+            .into_iter()
+            .find(|(_, _, change)| change.id == version)
+        {
+            Some((last, _, _)) => Ok(Frontiers::from(last)),
+            None => miette::bail!("Unknown version '{}'", version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_skips_locked_pages() {
+        let mut space = Space::in_memory().unwrap();
+        space.write_page("pages/A.md", "- One\n").unwrap();
+        space.tag_version("v1").unwrap();
+        space
+            .write_page("pages/A.md", "locked:: true\n- Two\n")
+            .unwrap();
+
+        let report = space.restore_version("v1").unwrap();
+        assert_eq!(report.locked, vec!["pages/A.md".to_string()]);
+        assert!(report.restored.is_empty());
+        assert_eq!(
+            space.read_page("pages/A.md").unwrap().as_deref(),
+            Some("locked:: true\n- Two\n")
+        );
+
+        space.set_allow_locked(true);
+        let report = space.restore_version("v1").unwrap();
+        assert_eq!(report.restored, vec!["pages/A.md".to_string()]);
+        assert_eq!(
+            space.read_page("pages/A.md").unwrap().as_deref(),
+            Some("- One\n")
+        );
+    }
+}
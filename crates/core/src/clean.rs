@@ -0,0 +1,313 @@
+//! Space Housekeeping
+//!
+//! Finds data a space no longer needs, for `flow clean --deep`:
+//!
+//! * Spaces registered more than once under different names
+//! * Stale temporary files in `.flow/`, left behind when Flow was killed mid-write
+//! * Backups exceeding the rotation settings (see [`backup`](crate::backup))
+//! * Assets below `assets/` no page links to
+//!
+//! Every [`Finding`] comes with the action fixing it, applied by [`fix`].
+
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::attach::ATTACHMENT_DIR;
+use crate::backup::{self, BackupSettings};
+use crate::config::Config;
+use crate::paths;
+use crate::space::{markdown_files, FLOW_DIR};
+use crate::storage::FsStorage;
+
+/// Extension of the temporary files written before being renamed into place.
+const TEMP_EXTENSION: &str = "tmp";
+
+/// Age after which a temporary file is stale, younger ones may still be written.
+const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Kinds of findings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingKind {
+    /// A space registered under another name as well.
+    DuplicateRegistration,
+    /// A file left behind by an interrupted write or a crashed process.
+    StaleFile,
+    /// Backups exceeding the rotation settings.
+    OversizedBackups,
+    /// An asset no page links to.
+    OrphanedAsset,
+}
+
+impl FindingKind {
+    /// Returns a short description of the kind.
+    ///
+    /// # Returns
+    ///
+    /// - `&'static str` - Description for tables and prompts.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::DuplicateRegistration => "duplicate registration",
+            Self::StaleFile => "stale file",
+            Self::OversizedBackups => "oversized backups",
+            Self::OrphanedAsset => "orphaned asset",
+        }
+    }
+
+    /// Returns the action fixing a finding of the kind.
+    ///
+    /// # Returns
+    ///
+    /// - `&'static str` - The action, as an imperative verb.
+    pub fn action(&self) -> &'static str {
+        match self {
+            Self::DuplicateRegistration => "unregister",
+            Self::StaleFile | Self::OrphanedAsset => "remove",
+            Self::OversizedBackups => "rotate",
+        }
+    }
+}
+
+/// Data a space no longer needs.
+///
+/// # Fields
+///
+/// - `kind` (`FindingKind`) - What was found.
+/// - `space` (`String`) - Name of the space, for duplicate registrations the name to unregister.
+/// - `path` (`PathBuf`) - The file, or the space for registrations and backups.
+/// - `detail` (`String`) - Human readable details.
+/// - `size` (`u64`) - Bytes freed by fixing the finding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub space: String,
+    pub path: PathBuf,
+    pub detail: String,
+    pub size: u64,
+}
+
+/// Finds spaces registered under more than one name.
+///
+/// The active name, or else the alphabetically first, is kept, every other
+/// name of the space is a finding.
+///
+/// # Arguments
+///
+/// - `config` (`&Config`) - Flow configuration.
+///
+/// # Returns
+///
+/// - `Vec<Finding>` - The names to unregister.
+pub fn duplicate_registrations(config: &Config) -> Vec<Finding> {
+    let mut names: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for (name, space) in config.all_spaces() {
+        names
+            .entry(paths::normalize(&space.path))
+            .or_default()
+            .push(name);
+    }
+
+    let active = config.get_active_space_name();
+    let mut findings = Vec::new();
+    for (path, mut names) in names {
+        if names.len() < 2 {
+            continue;
+        }
+        names.sort();
+        let kept = active
+            .filter(|active| names.iter().any(|name| name == active))
+            .map_or_else(|| names[0].clone(), str::to_string);
+        for name in names.iter().filter(|name| **name != kept) {
+            findings.push(Finding {
+                kind: FindingKind::DuplicateRegistration,
+                space: name.clone(),
+                path: path.clone(),
+                detail: format!("also registered as {}", kept),
+                size: 0,
+            });
+        }
+    }
+    findings
+}
+
+/// Inspects a space for stale files, oversized backups and orphaned assets.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name the space is registered under.
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<Vec<Finding>>` - The findings, in the order of their kinds.
+///
+/// # Errors
+///
+/// IO errors when reading directories or pages.
+pub fn inspect(name: &str, path: &Path) -> Result<Vec<Finding>> {
+    let finding = |kind, path: PathBuf, detail: String, size| Finding {
+        kind,
+        space: name.to_string(),
+        path,
+        detail,
+        size,
+    };
+    let mut findings = Vec::new();
+
+    let now = SystemTime::now();
+    for (file, size) in files(&path.join(FLOW_DIR))? {
+        if file.extension().is_none_or(|ext| ext != TEMP_EXTENSION) {
+            continue;
+        }
+        let modified = fs::metadata(&file)
+            .and_then(|metadata| metadata.modified())
+            .into_diagnostic()?;
+        if now.duration_since(modified).unwrap_or_default() >= STALE_AFTER {
+            let detail = "left behind by an interrupted write".to_string();
+            findings.push(finding(FindingKind::StaleFile, file, detail, size));
+        }
+    }
+
+    let backups = backup::list(path)?;
+    let excess = backup::excess(&backups, &BackupSettings::for_space(path));
+    if !excess.is_empty() {
+        let detail = format!(
+            "{} of {} backups exceed the rotation settings",
+            excess.len(),
+            backups.len()
+        );
+        let size = excess.iter().map(|backup| backup.size).sum();
+        findings.push(finding(
+            FindingKind::OversizedBackups,
+            path.to_path_buf(),
+            detail,
+            size,
+        ));
+    }
+
+    let mut pages = Vec::new();
+    for id in markdown_files(&FsStorage, path)? {
+        pages.push(fs::read_to_string(path.join(&id)).into_diagnostic()?);
+    }
+    let assets: Vec<(String, u64)> = files(&path.join(ATTACHMENT_DIR))?
+        .into_iter()
+        .filter_map(|(file, size)| Some((relative(path, &file)?, size)))
+        .collect();
+    for (asset, size) in unreferenced(assets, &pages) {
+        let detail = "not linked from any page".to_string();
+        findings.push(finding(
+            FindingKind::OrphanedAsset,
+            path.join(&asset),
+            detail,
+            size,
+        ));
+    }
+
+    Ok(findings)
+}
+
+/// Applies the action fixing a finding.
+///
+/// # Arguments
+///
+/// - `config` (`&mut Config`) - Flow configuration, for duplicate registrations.
+/// - `finding` (`&Finding`) - The finding.
+///
+/// # Errors
+///
+/// The configuration can't be saved, or IO errors when removing files.
+pub fn fix(config: &mut Config, finding: &Finding) -> Result<()> {
+    match finding.kind {
+        FindingKind::DuplicateRegistration => config.unregister_space(&finding.space),
+        FindingKind::StaleFile | FindingKind::OrphanedAsset => {
+            fs::remove_file(&finding.path).into_diagnostic()
+        }
+        FindingKind::OversizedBackups => {
+            backup::rotate(&finding.path, &BackupSettings::for_space(&finding.path))?;
+            Ok(())
+        }
+    }
+}
+
+/// Returns the assets no page links to.
+///
+/// Pages link assets relative to themselves (see [`attach::link`](crate::attach::link)),
+/// so an asset is linked if a page mentions its path relative to the space.
+///
+/// # Arguments
+///
+/// - `assets` (`Vec<(String, u64)>`) - Paths of the assets relative to the space, with their sizes.
+/// - `pages` (`&[String]`) - Markdown content of all pages.
+///
+/// # Returns
+///
+/// - `Vec<(String, u64)>` - The unlinked assets.
+fn unreferenced(assets: Vec<(String, u64)>, pages: &[String]) -> Vec<(String, u64)> {
+    assets
+        .into_iter()
+        .filter(|(asset, _)| !pages.iter().any(|page| page.contains(asset.as_str())))
+        .collect()
+}
+
+/// Returns a path relative to the space with `/` as separator.
+fn relative(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Returns all files below a directory with their sizes, sorted by path.
+fn files(dir: &Path) -> Result<Vec<(PathBuf, u64)>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).into_diagnostic()? {
+            let entry = entry.into_diagnostic()?;
+            let metadata = entry.metadata().into_diagnostic()?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                files.push((entry.path(), metadata.len()));
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unreferenced() {
+        let assets = vec![
+            ("assets/diagram.png".to_string(), 10),
+            ("assets/audio/standup.m4a".to_string(), 20),
+            ("assets/notes.pdf".to_string(), 30),
+        ];
+        let pages = vec![
+            "- ![diagram.png](../assets/diagram.png)\n".to_string(),
+            "- Standup [standup.m4a](../../assets/audio/standup.m4a)\n".to_string(),
+        ];
+        assert_eq!(
+            unreferenced(assets, &pages),
+            vec![("assets/notes.pdf".to_string(), 30)]
+        );
+    }
+}
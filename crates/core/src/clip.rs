@@ -0,0 +1,236 @@
+//! Web Clipping
+//!
+//! Saves web articles for reading later. The readable content of a page is
+//! extracted, stripped of navigation, ads and other clutter, converted to
+//! markdown and saved as a page below `clips/`, with its source as page
+//! properties:
+//!
+//! ```markdown
+//! source:: https://example.com/crdts
+//! clipped:: 2024-05-01
+//! type:: clip
+//!
+//! - # An introduction to CRDTs
+//! - Conflict-free replicated data types are ...
+//! ```
+//!
+//! Today's journal page links to the clip. Flow only goes online when asked
+//! to clip a URL, and fetching needs Flow built with the `clip` feature.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::page;
+use crate::space::Space;
+
+/// Namespace clipped pages are saved under.
+pub const CLIP_NAMESPACE: &str = "clips";
+
+/// Readable content of a web page.
+///
+/// # Fields
+///
+/// - `url` (`String`) - Address the page was fetched from.
+/// - `title` (`String`) - Title of the article, empty if unknown.
+/// - `markdown` (`String`) - The article as markdown.
+#[derive(Debug, Clone)]
+pub struct Clip {
+    pub url: String,
+    pub title: String,
+    pub markdown: String,
+}
+
+/// A clip saved to a space.
+///
+/// # Fields
+///
+/// - `url` (`String`) - Address the page was fetched from.
+/// - `title` (`String`) - Title of the clip.
+/// - `page` (`String`) - Id of the page the clip was saved to.
+/// - `journal` (`String`) - Id of the journal page linking to the clip.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SavedClip {
+    pub url: String,
+    pub title: String,
+    pub page: String,
+    pub journal: String,
+}
+
+impl Clip {
+    /// Fetches a web page and extracts its readable content.
+    ///
+    /// # Arguments
+    ///
+    /// - `url` (`&str`) - Address of the page, `http` or `https`.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The readable content of the page.
+    ///
+    /// # Errors
+    ///
+    /// The URL isn't a web address, the page can't be fetched, no readable
+    /// content is found, or Flow was built without the `clip` feature.
+    pub fn fetch(url: &str) -> Result<Self> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            miette::bail!("'{}' is not a web address", url);
+        }
+
+        #[cfg(feature = "clip")]
+        {
+            use miette::IntoDiagnostic;
+
+            let parsed = url::Url::parse(url).into_diagnostic()?;
+            let response = ureq::get(url).call().into_diagnostic()?;
+            let mut body = response.into_reader();
+            let article = readability::extractor::extract(&mut body, &parsed)
+                .map_err(|error| miette::miette!("No readable content in {}: {}", url, error))?;
+
+            Ok(Self {
+                url: url.to_string(),
+                title: article.title.trim().to_string(),
+                markdown: html2md::parse_html(&article.content),
+            })
+        }
+        #[cfg(not(feature = "clip"))]
+        {
+            miette::bail!("Clipping web pages requires Flow built with the 'clip' feature")
+        }
+    }
+
+    /// Splits the markdown into blocks.
+    ///
+    /// Paragraphs, headings and lists separated by blank lines become blocks;
+    /// code fences are kept together.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<String>` - Blocks, continuation lines indented below the first.
+    fn blocks(&self) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        let mut fenced = false;
+        for line in self.markdown.lines() {
+            if line.trim_start().starts_with("```") {
+                fenced = !fenced;
+            }
+            if line.trim().is_empty() && !fenced {
+                if !current.is_empty() {
+                    blocks.push(current.join("\n  "));
+                    current.clear();
+                }
+                continue;
+            }
+            current.push(line.trim_end());
+        }
+        if !current.is_empty() {
+            blocks.push(current.join("\n  "));
+        }
+        blocks
+    }
+}
+
+impl Space {
+    /// Saves a clip as a page and links it from today's journal.
+    ///
+    /// The page is named after the clip's title, or the URL if it has none,
+    /// and never overwrites an existing page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to save the clip to.
+    /// - `clip` (`&Clip`) - The clip.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<SavedClip>` - The saved clip.
+    ///
+    /// # Errors
+    ///
+    /// The journal page is locked, or IO errors when writing files.
+    pub fn save_clip(&mut self, clip: &Clip) -> Result<SavedClip> {
+        let title = if clip.title.is_empty() {
+            clip.url.clone()
+        } else {
+            clip.title.clone()
+        };
+        let name = match page::file_name(&title, ' ') {
+            name if name.is_empty() => "Clip".to_string(),
+            name => name,
+        };
+        let id = self.unused_page_id(&format!("{}/{}", CLIP_NAMESPACE, name))?;
+
+        let mut lines = vec![
+            format!("source:: {}", clip.url),
+            format!("clipped:: {}", self.today().format("%Y-%m-%d")),
+            "type:: clip".to_string(),
+            String::new(),
+        ];
+        lines.extend(clip.blocks().iter().map(|block| format!("- {}", block)));
+        let mut content = lines.join("\n");
+        content.push('\n');
+        self.write_page(&id, &content)?;
+
+        let journal = self.today_page()?;
+        self.append(
+            &journal,
+            &format!("- Clipped [[{}]]", page::name_from_id(&id)),
+        )?;
+
+        Ok(SavedClip {
+            url: clip.url.clone(),
+            title,
+            page: id,
+            journal,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip() -> Clip {
+        Clip {
+            url: "https://example.com/crdts".to_string(),
+            title: "CRDTs: an introduction".to_string(),
+            markdown: "# CRDTs\n\nConflict-free replicated\ndata types.\n\n```\nlet a = 1;\n\nlet b = 2;\n```\n".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_blocks_keep_code_fences_together() {
+        assert_eq!(
+            clip().blocks(),
+            vec![
+                "# CRDTs",
+                "Conflict-free replicated\n  data types.",
+                "```\n  let a = 1;\n  \n  let b = 2;\n  ```",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_clip_writes_page_and_links_journal() {
+        let mut space = Space::in_memory().unwrap();
+        let saved = space.save_clip(&clip()).unwrap();
+        assert_eq!(saved.page, "pages/clips/CRDTs- an introduction.md");
+
+        let content = space.read_page(&saved.page).unwrap().unwrap();
+        assert!(content.starts_with("source:: https://example.com/crdts\n"));
+        assert!(content
+            .contains("type:: clip\n\n- # CRDTs\n- Conflict-free replicated\n  data types.\n"));
+
+        let journal = space.read_page(&saved.journal).unwrap().unwrap();
+        assert!(journal.contains("- Clipped [[clips/CRDTs- an introduction]]"));
+
+        let again = space.save_clip(&clip()).unwrap();
+        assert_eq!(again.page, "pages/clips/CRDTs- an introduction (2).md");
+    }
+
+    #[test]
+    fn test_fetch_rejects_other_schemes() {
+        assert!(Clip::fetch("file:///etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Space Clock
+//!
+//! Which day "today" is for a space. By default it's the local date of the
+//! machine, but a space can pin a timezone so its journal doesn't depend on
+//! where the device happens to be, and move the day rollover past midnight so
+//! notes written at 1am still land on the previous day's journal page.
+//!
+//! Both are configured in the space metadata:
+//!
+//! ```toml
+//! [clock]
+//! timezone = "Europe/Berlin"
+//! rollover_hour = 3
+//! ```
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::space::{Metadata, Space};
+use crate::storage::FsStorage;
+
+/// Clock settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `timezone` (`Option<String>`) - IANA timezone name, the system timezone if unset or unknown.
+/// - `rollover_hour` (`u32`) - Hour the journal day starts at, 0 for midnight.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub rollover_hour: u32,
+}
+
+impl ClockSettings {
+    /// Reads the clock settings of the space at the given path.
+    ///
+    /// Cheaper than loading the space, for callers that only need the date.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `ClockSettings` - The settings, defaults if the metadata can't be read.
+    pub fn load(path: &Path) -> Self {
+        Metadata::read(&FsStorage, path)
+            .map(|metadata| metadata.clock)
+            .unwrap_or_default()
+    }
+
+    /// Returns the current time in the timezone of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `DateTime<FixedOffset>` - Current time.
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        match self
+            .timezone
+            .as_deref()
+            .and_then(|name| name.parse::<Tz>().ok())
+        {
+            Some(timezone) => Utc::now().with_timezone(&timezone).fixed_offset(),
+            None => Local::now().fixed_offset(),
+        }
+    }
+
+    /// Returns the journal day a point in time belongs to.
+    ///
+    /// # Arguments
+    ///
+    /// - `time` (`DateTime<FixedOffset>`) - Time in the timezone of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `NaiveDate` - The date, the previous one before the rollover hour.
+    pub fn day_of(&self, time: DateTime<FixedOffset>) -> NaiveDate {
+        (time - Duration::hours(self.rollover_hour.min(23) as i64)).date_naive()
+    }
+
+    /// Returns the current journal day.
+    ///
+    /// # Returns
+    ///
+    /// - `NaiveDate` - Today's date, honoring the timezone and rollover hour.
+    pub fn today(&self) -> NaiveDate {
+        self.day_of(self.now())
+    }
+
+    /// Returns the end of the current journal day.
+    ///
+    /// # Returns
+    ///
+    /// - `i64` - Unix timestamp of the last second before the next day starts.
+    pub fn end_of_today(&self) -> i64 {
+        let now = self.now();
+        let rollover =
+            NaiveTime::from_hms_opt(self.rollover_hour.min(23), 0, 0).unwrap_or_default();
+        let next = (self.day_of(now) + Duration::days(1)).and_time(rollover);
+        (next - now.naive_local()).num_seconds() + now.timestamp() - 1
+    }
+}
+
+impl Space {
+    /// Returns the clock settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&ClockSettings` - Reference to the space's clock settings.
+    pub fn clock_settings(&self) -> &ClockSettings {
+        &self.metadata.clock
+    }
+
+    /// Returns the current journal day of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `NaiveDate` - Today's date, honoring the timezone and rollover hour.
+    pub fn today(&self) -> NaiveDate {
+        self.metadata.clock.today()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_of() {
+        let settings = ClockSettings {
+            timezone: None,
+            rollover_hour: 3,
+        };
+        let time = |value| DateTime::parse_from_rfc3339(value).unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+
+        assert_eq!(settings.day_of(time("2024-05-15T02:59:00+02:00")), date(14));
+        assert_eq!(settings.day_of(time("2024-05-15T03:00:00+02:00")), date(15));
+        assert_eq!(settings.day_of(time("2024-05-15T23:59:00+02:00")), date(15));
+    }
+}
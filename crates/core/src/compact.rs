@@ -0,0 +1,327 @@
+//! Document History Compaction
+//!
+//! The Loro document keeps the full edit history of a space, which makes the
+//! snapshot grow forever. Compaction replaces the snapshot with a shallow
+//! snapshot that drops all history older than a configurable horizon while
+//! keeping the current state intact.
+//!
+//! Optionally the full-history snapshot is archived to `.flow/archive/` first.
+//!
+//! Pages deleted or renamed on disk leave their containers in the document.
+//! Garbage collection empties the containers without a markdown file and
+//! lists them in the trash, so they stop showing up as pages; the space they
+//! took is reclaimed once compaction trims history past the collection.
+
+use chrono::{Duration, Local};
+use loro::{ExportMode, Frontiers, LoroDoc, ID};
+use miette::{IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::backup;
+use crate::space::{
+    container_names, read_document, write_snapshot, Space, DOCUMENT_FILE, FLOW_DIR,
+    TRASH_CONTAINER, UPDATES_FILE,
+};
+use crate::timestamps::META_CONTAINER;
+
+const ARCHIVE_DIR: &str = "archive";
+const DEFAULT_HORIZON_DAYS: u32 = 90;
+
+/// Compaction settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `horizon_days` (`u32`) - History older than this many days is trimmed.
+/// - `archive` (`bool`) - Whether to keep a full-history copy when compacting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactSettings {
+    #[serde(default = "default_horizon_days")]
+    pub horizon_days: u32,
+    #[serde(default)]
+    pub archive: bool,
+}
+
+fn default_horizon_days() -> u32 {
+    DEFAULT_HORIZON_DAYS
+}
+
+impl Default for CompactSettings {
+    fn default() -> Self {
+        Self {
+            horizon_days: DEFAULT_HORIZON_DAYS,
+            archive: false,
+        }
+    }
+}
+
+/// Result of a compaction.
+///
+/// # Fields
+///
+/// - `compacted` (`bool`) - Whether any history was trimmed.
+/// - `size_before` (`u64`) - Snapshot size before compaction in bytes.
+/// - `size_after` (`u64`) - Snapshot size after compaction in bytes.
+/// - `archive` (`Option<PathBuf>`) - Path of the full-history archive, if one was kept.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactReport {
+    pub compacted: bool,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub archive: Option<PathBuf>,
+}
+
+/// Result of a garbage collection.
+///
+/// # Fields
+///
+/// - `pages` (`Vec<String>`) - Ids of the collected pages.
+/// - `bytes` (`u64`) - Content size of the collected pages in bytes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GarbageReport {
+    pub pages: Vec<String>,
+    pub bytes: u64,
+}
+
+impl CompactReport {
+    /// Returns the number of bytes saved by the compaction.
+    ///
+    /// # Returns
+    ///
+    /// - `u64` - Saved bytes (0 if the snapshot grew).
+    pub fn saved(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
+
+impl Space {
+    /// Returns the compaction settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&CompactSettings` - Reference to the space's compaction settings.
+    pub fn compact_settings(&self) -> &CompactSettings {
+        &self.metadata.compact
+    }
+
+    /// Empties the containers of pages whose markdown file no longer exists.
+    ///
+    /// Collected pages are listed in the trash container, which hides them
+    /// from the page list and keeps sync from writing them back to disk.
+    /// Writing a page with the same id later brings it back. Only spaces on
+    /// native storage are collected, elsewhere the document is all there is.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to collect.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<GarbageReport>` - The collected pages.
+    ///
+    /// # Errors
+    ///
+    /// Loro errors when updating containers or IO errors when saving.
+    pub fn collect_garbage(&mut self) -> Result<GarbageReport> {
+        let mut report = GarbageReport::default();
+        if !self.storage.is_native() {
+            return Ok(report);
+        }
+
+        let now = Local::now().timestamp();
+        let trash = self.document.get_map(TRASH_CONTAINER);
+        let meta = self.document.get_map(META_CONTAINER);
+        for id in container_names(&self.document) {
+            if !id.ends_with(".md") || self.storage.exists(&self.path.join(&id)) {
+                continue;
+            }
+            let text = self.document.get_text(id.as_str());
+            report.bytes += text.len_utf8() as u64;
+            text.delete(0, text.len_unicode()).into_diagnostic()?;
+            trash.insert(&id, now).into_diagnostic()?;
+            meta.delete(&id).into_diagnostic()?;
+            self.deleted.insert(id.clone());
+            report.pages.push(id);
+        }
+        if report.pages.is_empty() {
+            return Ok(report);
+        }
+
+        self.save()?;
+        Ok(report)
+    }
+
+    /// Trims document history older than the given horizon.
+    ///
+    /// Updates other processes saved since the space was loaded are imported
+    /// first, and the document is backed up (see [`backup`](crate::backup))
+    /// before the shallow snapshot replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to compact.
+    /// - `horizon_days` (`u32`) - History older than this many days is trimmed.
+    /// - `archive` (`bool`) - Whether to archive the full-history snapshot first.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<CompactReport>` - Report of the compaction.
+    ///
+    /// # Errors
+    ///
+    /// Import errors for corrupt data, export errors or IO errors when writing
+    /// the backup, snapshot or archive.
+    pub fn compact(&mut self, horizon_days: u32, archive: bool) -> Result<CompactReport> {
+        let flow_dir = self.path.join(FLOW_DIR);
+        let doc_path = flow_dir.join(DOCUMENT_FILE);
+        let size_before = fs::metadata(&doc_path).into_diagnostic()?.len()
+            + fs::metadata(flow_dir.join(UPDATES_FILE)).map_or(0, |metadata| metadata.len());
+        // The snapshot replaces the update log, which may hold other processes' saves
+        read_document(&*self.storage, &self.path, &self.document)?;
+
+        let cutoff = (Local::now() - Duration::days(horizon_days as i64)).timestamp();
+        let Some(frontiers) = frontiers_before(&self.document, cutoff) else {
+            return Ok(CompactReport {
+                compacted: false,
+                size_before,
+                size_after: size_before,
+                archive: None,
+            });
+        };
+
+        let archive = if archive {
+            let archive_dir = flow_dir.join(ARCHIVE_DIR);
+            fs::create_dir_all(&archive_dir).into_diagnostic()?;
+            let archive_path =
+                archive_dir.join(format!("{}.loro", Local::now().format("%Y%m%dT%H%M%S")));
+            // Export instead of copying, the snapshot may lag behind the update log
+            let full = self
+                .document
+                .export(ExportMode::Snapshot)
+                .into_diagnostic()?;
+            fs::write(&archive_path, full).into_diagnostic()?;
+            Some(archive_path)
+        } else {
+            None
+        };
+
+        let snapshot = self
+            .document
+            .export(ExportMode::shallow_snapshot(&frontiers))
+            .into_diagnostic()?;
+        backup::create(&self.path, &self.metadata.backups)?;
+        write_snapshot(&*self.storage, &self.path, &snapshot)?;
+
+        let document = LoroDoc::new();
+        document.set_record_timestamp(true);
+        document.import(&snapshot).into_diagnostic()?;
+        self.saved = document.oplog_vv();
+        self.pending = 0;
+        self.document = document;
+
+        Ok(CompactReport {
+            compacted: true,
+            size_before,
+            size_after: snapshot.len() as u64,
+            archive,
+        })
+    }
+}
+
+/// Finds the frontiers of the latest change committed before the cutoff.
+///
+/// Changes without a recorded timestamp are treated as older than any cutoff.
+///
+/// # Arguments
+///
+/// - `doc` (`&LoroDoc`) - Document to search.
+/// - `cutoff` (`i64`) - Unix timestamp in seconds.
+///
+/// # Returns
+///
+/// - `Option<Frontiers>` - Frontiers to compact to, if any history is older than the cutoff.
+fn frontiers_before(doc: &LoroDoc, cutoff: i64) -> Option<Frontiers> {
+    let mut latest: Option<(u32, ID)> = None;
+
+    for (&peer, &end) in doc.oplog_vv().iter() {
+        let mut counter = 0;
+        while counter < end {
+            let Some(change) = doc.get_change(ID::new(peer, counter)) else {
+                break;
+            };
+            counter = change.id.counter + change.len as i32;
+
+            if change.timestamp > cutoff {
+                continue;
+            }
+
+            let last_op = ID::new(peer, counter - 1);
+            if latest.is_none_or(|(lamport, _)| change.lamport > lamport) {
+                latest = Some((change.lamport, last_op));
+            }
+        }
+    }
+
+    latest.map(|(_, id)| Frontiers::from(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_space(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("flow-compact-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Space::init(&dir, None, true).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_compact_keeps_content_and_backs_up() {
+        let path = scratch_space("compact");
+        let mut space = Space::load(&path).unwrap();
+        space.write_page("notes.md", "- One\n").unwrap();
+        space.write_page("notes.md", "- Two\n").unwrap();
+
+        let report = space.compact(0, true).unwrap();
+        assert!(report.compacted);
+        assert!(report.archive.is_some_and(|archive| archive.exists()));
+        assert!(!backup::list(&path).unwrap().is_empty());
+        drop(space);
+
+        let mut space = Space::load(&path).unwrap();
+        assert_eq!(
+            space.read_page("notes.md").unwrap().as_deref(),
+            Some("- Two\n")
+        );
+        space.write_page("notes.md", "- Three\n").unwrap();
+        let space = Space::load(&path).unwrap();
+        assert_eq!(
+            space.read_page("notes.md").unwrap().as_deref(),
+            Some("- Three\n")
+        );
+    }
+
+    #[test]
+    fn test_collect_garbage_trashes_missing_pages() {
+        let path = scratch_space("garbage");
+        let mut space = Space::load(&path).unwrap();
+        space.write_page("kept.md", "- Kept\n").unwrap();
+        space.write_page("gone.md", "- Gone\n").unwrap();
+        fs::remove_file(path.join("gone.md")).unwrap();
+
+        let report = space.collect_garbage().unwrap();
+        assert_eq!(report.pages, vec!["gone.md"]);
+        assert!(space
+            .page_index()
+            .unwrap()
+            .pages()
+            .all(|entry| entry.id != "gone.md"));
+        assert_eq!(
+            space.read_page("kept.md").unwrap().as_deref(),
+            Some("- Kept\n")
+        );
+    }
+}
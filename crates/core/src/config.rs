@@ -7,17 +7,44 @@
 //! - `~/.config/flow/flow.toml`
 //!
 //! You can override the base directory with the `XDG_CONFIG_HOME` environment variable.
+//!
+//! The active space is global, set with `flow open`. A terminal session can
+//! work against another space by setting [`SESSION_ENV`] (`FLOW_GRAPH`) to a
+//! registered name or path, which takes precedence over `active_space`.
+//!
+//! Every change to the configuration first copies the previous version to
+//! `~/.config/flow/history/`, keeping the last few, so an accidental
+//! `flow clean` can be undone with `flow config undo`.
 
+use chrono::{Local, NaiveDate, Weekday};
 use miette::{Context, IntoDiagnostic, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
 
+use crate::attribution::Author;
+use crate::paths;
+use crate::reminders::NotificationSettings;
 use crate::space::Space;
 
 const APP_NAME: &str = "flow";
 const CONFIG_NAME: &str = "flow";
 
+/// Environment variable selecting the active space of a terminal session.
+pub const SESSION_ENV: &str = "FLOW_GRAPH";
+
+/// Directory next to `flow.toml` holding its previous versions.
+const HISTORY_DIR: &str = "history";
+const HISTORY_EXTENSION: &str = "toml";
+/// Number of previous versions kept.
+const HISTORY_COUNT: usize = 10;
+
+/// ISO 8601 date format, also the name of journal pages.
+const ISO_DATE: &str = "%Y-%m-%d";
+
 /// Main configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -25,12 +52,199 @@ pub struct Config {
     spaces: HashMap<String, SpaceConfig>,
     #[serde(default)]
     active_space: Option<String>,
+    #[serde(default)]
+    threads: usize,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    device: Option<String>,
+    #[serde(default)]
+    pdf_renderer: Option<String>,
+    #[serde(default)]
+    open_creates: bool,
+    #[serde(default)]
+    editor: Option<String>,
+    #[serde(default)]
+    theme: Theme,
+    #[serde(default)]
+    locale: Locale,
+}
+
+/// Prefixes of terminal output lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Icons {
+    /// Emoji, falling back to ASCII on terminals without emoji support.
+    #[default]
+    Emoji,
+    /// ASCII markers like `[+]` and `->`.
+    Ascii,
+    /// No prefixes at all.
+    Minimal,
+}
+
+impl std::str::FromStr for Icons {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "emoji" => Ok(Self::Emoji),
+            "ascii" => Ok(Self::Ascii),
+            "minimal" => Ok(Self::Minimal),
+            _ => Err(format!(
+                "unknown theme '{}', expected emoji, ascii or minimal",
+                value
+            )),
+        }
+    }
+}
+
+/// Output theme of the command line.
+///
+/// Colors are names (`red`, `green`, `yellow`, `blue`, `magenta`, `cyan`,
+/// `white`, `black`) or 256-color palette numbers; unset colors keep their
+/// defaults.
+///
+/// ```toml
+/// [theme]
+/// icons = "ascii"
+/// accent = "magenta"
+/// ```
+///
+/// # Fields
+///
+/// - `icons` (`Icons`) - Prefixes of output lines.
+/// - `success` (`Option<String>`) - Color of success messages.
+/// - `info` (`Option<String>`) - Color of info messages.
+/// - `warning` (`Option<String>`) - Color of warnings.
+/// - `error` (`Option<String>`) - Color of errors.
+/// - `accent` (`Option<String>`) - Color of keys in key-value output.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub icons: Icons,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+}
+
+/// Day weeks start on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    /// Monday, as in ISO 8601.
+    #[default]
+    Monday,
+    /// Sunday, as in the US calendar.
+    Sunday,
+    /// Saturday, as in much of the Middle East.
+    Saturday,
+}
+
+impl WeekStart {
+    /// Returns the weekday weeks start on.
+    ///
+    /// # Returns
+    ///
+    /// - `Weekday` - First day of the week.
+    pub fn weekday(self) -> Weekday {
+        match self {
+            Self::Monday => Weekday::Mon,
+            Self::Sunday => Weekday::Sun,
+            Self::Saturday => Weekday::Sat,
+        }
+    }
+}
+
+/// How dates are displayed.
+///
+/// Journal pages are always named `YYYY-MM-DD` so they sort and resolve the
+/// same everywhere; the date format only changes how days are shown.
+///
+/// ```toml
+/// [locale]
+/// first_day_of_week = "sunday"
+/// date_format = "%m/%d/%Y"
+/// ```
+///
+/// # Fields
+///
+/// - `first_day_of_week` (`WeekStart`) - Day weeks start on in weekly reviews and calendars.
+/// - `date_format` (`String`) - `strftime` format of displayed dates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Locale {
+    pub first_day_of_week: WeekStart,
+    pub date_format: String,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            first_day_of_week: WeekStart::default(),
+            date_format: ISO_DATE.to_string(),
+        }
+    }
+}
+
+impl Locale {
+    /// Returns the ISO 8601 locale: weeks start on Monday, dates are `YYYY-MM-DD`.
+    ///
+    /// # Returns
+    ///
+    /// - `Locale` - The ISO locale.
+    pub fn iso() -> Self {
+        Self::default()
+    }
+
+    /// Formats a date for display.
+    ///
+    /// # Arguments
+    ///
+    /// - `date` (`NaiveDate`) - Date to format.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - The date in the configured format, ISO if the format is invalid.
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        let mut formatted = String::new();
+        match write!(formatted, "{}", date.format(&self.date_format)) {
+            Ok(()) => formatted,
+            Err(_) => date.format(ISO_DATE).to_string(),
+        }
+    }
+}
+
+/// A previous version of the configuration.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Identifier of the version (the time it was replaced).
+/// - `path` (`PathBuf`) - Path of the file holding the version.
+/// - `size` (`u64`) - Size of the file in bytes.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfigVersion {
+    pub id: String,
+    pub path: PathBuf,
+    pub size: u64,
 }
 
 /// Space configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpaceConfig {
     pub path: PathBuf,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// Capabilities granted to the plugins of the space, by module name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub plugins: BTreeMap<String, Vec<String>>,
 }
 
 /// Default configuration.
@@ -39,6 +253,14 @@ impl Default for Config {
         Self {
             spaces: HashMap::new(),
             active_space: None,
+            threads: 0,
+            author: None,
+            device: None,
+            pdf_renderer: None,
+            open_creates: false,
+            editor: None,
+            theme: Theme::default(),
+            locale: Locale::default(),
         }
     }
 }
@@ -59,13 +281,270 @@ impl Config {
     }
 
     /// Saves the Flow configuration to disk
+    ///
+    /// The previous version is added to the history first, if it differs.
     pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Ok(previous) = fs::read_to_string(&path) {
+            let current = toml::to_string_pretty(self).into_diagnostic()?;
+            if previous.trim() != current.trim() {
+                Self::push_history(&path, &previous)?;
+            }
+        }
+
         confy::change_config_strategy(confy::ConfigStrategy::App);
         confy::store(APP_NAME, CONFIG_NAME, self)
             .into_diagnostic()
             .context("Failed to save Flow configuration")
     }
 
+    /// Returns the previous versions of the configuration, newest first.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<ConfigVersion>>` - The versions kept in the history.
+    ///
+    /// # Errors
+    ///
+    /// If the configuration directory can't be determined or read.
+    pub fn history() -> Result<Vec<ConfigVersion>> {
+        let dir = history_dir(&Self::path()?);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut versions = Vec::new();
+        for entry in fs::read_dir(&dir).into_diagnostic()? {
+            let entry = entry.into_diagnostic()?;
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != HISTORY_EXTENSION) {
+                continue;
+            }
+            let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let size = entry.metadata().into_diagnostic()?.len();
+            versions.push(ConfigVersion { id, path, size });
+        }
+
+        // Ids are timestamps, so sorting them sorts by time.
+        versions.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(versions)
+    }
+
+    /// Restores the previous version of the configuration.
+    ///
+    /// The version is taken out of the history, so undoing again goes
+    /// further back.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<ConfigVersion>` - The restored version.
+    ///
+    /// # Errors
+    ///
+    /// If there is no previous version, it can't be parsed, or IO errors when
+    /// writing the configuration.
+    pub fn undo() -> Result<ConfigVersion> {
+        let Some(version) = Self::history()?.into_iter().next() else {
+            miette::bail!("No previous configuration to restore");
+        };
+
+        let content = fs::read_to_string(&version.path).into_diagnostic()?;
+        toml::from_str::<Config>(&content)
+            .into_diagnostic()
+            .with_context(|| format!("Configuration version {} is invalid", version.id))?;
+        fs::write(Self::path()?, content).into_diagnostic()?;
+        fs::remove_file(&version.path).into_diagnostic()?;
+
+        Ok(version)
+    }
+
+    /// Adds a version of the configuration to the history, dropping the oldest.
+    fn push_history(path: &Path, content: &str) -> Result<()> {
+        let dir = history_dir(path);
+        fs::create_dir_all(&dir).into_diagnostic()?;
+
+        let id = Local::now().format("%Y%m%dT%H%M%S%3f").to_string();
+        fs::write(dir.join(format!("{}.{}", id, HISTORY_EXTENSION)), content).into_diagnostic()?;
+
+        for version in Self::history()?.iter().skip(HISTORY_COUNT) {
+            fs::remove_file(&version.path).into_diagnostic()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<PathBuf>` - Path of `flow.toml`, which may not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// If the configuration directory can't be determined.
+    pub fn path() -> Result<PathBuf> {
+        confy::change_config_strategy(confy::ConfigStrategy::App);
+        confy::get_configuration_file_path(APP_NAME, CONFIG_NAME)
+            .into_diagnostic()
+            .context("Failed to locate Flow configuration")
+    }
+
+    /// Returns the number of worker threads for parallel scanning.
+    ///
+    /// Configured via `threads` in the config file, `0` (the default) uses all
+    /// available cores.
+    ///
+    /// # Returns
+    ///
+    /// - `usize` - Number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Returns the identity changes made on this machine are attributed to.
+    ///
+    /// Configured via `author` and `device` in the config file, falling back to
+    /// the user and host names of the system.
+    ///
+    /// # Returns
+    ///
+    /// - `Author` - Author of local changes.
+    pub fn author(&self) -> Author {
+        let mut author = Author::default();
+        if let Some(name) = &self.author {
+            author.name = name.clone();
+        }
+        if let Some(device) = &self.device {
+            author.device = device.clone();
+        }
+        author
+    }
+
+    /// Returns the command used to render PDFs.
+    ///
+    /// Configured via `pdf_renderer` in the config file, a command with
+    /// `{input}` and `{output}` placeholders, e.g. `weasyprint {input} {output}`.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&str>` - Renderer command, `None` to detect an installed one.
+    pub fn pdf_renderer(&self) -> Option<&str> {
+        self.pdf_renderer.as_deref()
+    }
+
+    /// Returns whether opening a path without a space initializes one.
+    ///
+    /// Configured via `open_creates` in the config file, the default of
+    /// `flow open --create`.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if `flow open` creates missing spaces.
+    pub fn open_creates(&self) -> bool {
+        self.open_creates
+    }
+
+    /// Returns the editor pages are opened in.
+    ///
+    /// Configured via `editor` in the config file, a command that may carry
+    /// arguments, e.g. `code --wait`.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&str>` - Editor command, `None` to use `$VISUAL` or `$EDITOR`.
+    pub fn editor(&self) -> Option<&str> {
+        self.editor.as_deref()
+    }
+
+    /// Sets the editor pages are opened in and saves the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// - `editor` (`Option<String>`) - Editor command, `None` to use `$VISUAL` or `$EDITOR`.
+    ///
+    /// # Errors
+    ///
+    /// The configuration can't be saved.
+    pub fn set_editor(&mut self, editor: Option<String>) -> Result<()> {
+        self.editor = editor.filter(|editor| !editor.trim().is_empty());
+        self.save()
+    }
+
+    /// Returns the capabilities the user granted to a plugin of a space.
+    ///
+    /// Grants live in the user's configuration rather than in the space, so
+    /// a plugin shipped with a space can't grant itself anything.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    /// - `plugin` (`&str`) - Module name of the plugin (its file stem).
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<String>` - Granted capabilities, empty if the space isn't registered.
+    pub fn plugin_grants(&self, space_path: &Path, plugin: &str) -> Vec<String> {
+        self.find_by_path(space_path)
+            .and_then(|(_, config)| config.plugins.get(plugin).cloned())
+            .unwrap_or_default()
+    }
+
+    /// Grants capabilities to a plugin of a space, replacing earlier grants,
+    /// and saves the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    /// - `plugin` (`&str`) - Module name of the plugin (its file stem).
+    /// - `capabilities` (`Vec<String>`) - Capabilities to grant, empty revokes all.
+    ///
+    /// # Errors
+    ///
+    /// The space isn't registered or the configuration can't be saved.
+    pub fn grant_plugin(
+        &mut self,
+        space_path: &Path,
+        plugin: &str,
+        capabilities: Vec<String>,
+    ) -> Result<()> {
+        let name = self
+            .find_by_path(space_path)
+            .map(|(name, _)| name.clone())
+            .ok_or_else(|| {
+                miette::miette!("Space at '{}' is not registered", space_path.display())
+            })?;
+        let config = self.spaces.get_mut(&name).expect("space was just found");
+        if capabilities.is_empty() {
+            config.plugins.remove(plugin);
+        } else {
+            config.plugins.insert(plugin.to_string(), capabilities);
+        }
+        self.save()
+    }
+
+    /// Returns the output theme of the command line.
+    ///
+    /// Configured via the `[theme]` table in the config file.
+    ///
+    /// # Returns
+    ///
+    /// - `&Theme` - The configured theme.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Returns the date display settings.
+    ///
+    /// Configured via the `[locale]` table in the config file.
+    ///
+    /// # Returns
+    ///
+    /// - `&Locale` - The configured locale.
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
     /// Registers a space to the configuration
     ///
     /// # Arguments
@@ -78,23 +557,52 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// Returns an error if the configuration could not be saved to disk
+    /// Returns an error if another space is already registered under the same
+    /// name or the configuration could not be saved to disk
     pub fn register_space(&mut self, space: &Space) -> Result<()> {
-        // Canonicalize the path to ensure we always store absolute paths
-        let canonical_path = space
-            .path()
-            .canonicalize()
-            .into_diagnostic()
-            .context(format!(
-                "Failed to canonicalize path: {}",
-                space.path().display()
-            ))?;
+        self.register_space_as(space, space.name())
+    }
 
+    /// Registers a space to the configuration under the given name
+    ///
+    /// # Arguments
+    ///
+    /// - `space` (`&Space`) - The space to add to the configuration
+    /// - `name` (`&str`) - The name to register the space under
+    ///
+    /// # Returns
+    ///
+    /// - `Result<()>` - Ok if the space was added and saved successfully
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another space is already registered under the same
+    /// name or the configuration could not be saved to disk
+    pub fn register_space_as(&mut self, space: &Space, name: &str) -> Result<()> {
+        // Normalize the path to ensure we always store absolute paths
+        let canonical_path = paths::normalize(space.path());
+
+        if let Some(existing) = self.name_collision(name, &canonical_path) {
+            miette::bail!(
+                "A different space is already registered as '{}' ({})",
+                existing,
+                self.spaces[&existing].path.display()
+            );
+        }
+
+        // Re-registering a space keeps its settings
+        let (notifications, plugins) = self
+            .find_by_path(&canonical_path)
+            .map(|(_, config)| (config.notifications.clone(), config.plugins.clone()))
+            .unwrap_or_default();
         let entry = SpaceConfig {
             path: canonical_path,
+            notifications,
+            plugins,
         };
 
-        let space_name = space.name().to_owned();
+        // Keep the spelling of an existing registration of this space
+        let space_name = self.resolve_name(name).unwrap_or(name).to_owned();
         self.spaces.insert(space_name.clone(), entry);
 
         // Set as active if it's the first space
@@ -105,12 +613,44 @@ impl Config {
         self.save()
     }
 
+    /// Checks if registering a space under the given name would collide with another space.
+    ///
+    /// # Arguments
+    ///
+    /// - `name` (`&str`) - The name the space should be registered under
+    /// - `path` (`&std::path::Path`) - The path of the space
+    ///
+    /// # Returns
+    ///
+    /// - `Option<String>` - The registered name of the colliding space, if any
+    pub fn name_collision(&self, name: &str, path: &std::path::Path) -> Option<String> {
+        self.resolve_name(name)
+            .filter(|existing| !paths::same(&self.spaces[*existing].path, path))
+            .map(str::to_owned)
+    }
+
+    /// Returns the space selected for the current terminal session.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<String>` - Name or path of the space in [`SESSION_ENV`], if set.
+    pub fn session_space() -> Option<String> {
+        std::env::var(SESSION_ENV)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    }
+
     /// Gets the active space configuration
     ///
+    /// The space of the terminal session wins over the global active space.
+    ///
     /// # Returns
     ///
     /// - `Option<&SpaceConfig>` - The active space configuration if one is set and exists
     pub fn get_active_space(&self) -> Option<&SpaceConfig> {
+        if let Some(session) = Self::session_space() {
+            return self.get_space_config(&session);
+        }
         self.active_space
             .as_ref()
             .and_then(|name| self.spaces.get(name))
@@ -118,10 +658,18 @@ impl Config {
 
     /// Gets the name of the active space
     ///
+    /// The space of the terminal session wins over the global active space.
+    ///
     /// # Returns
     ///
     /// - `Option<&str>` - The name of the active space if one is set
     pub fn get_active_space_name(&self) -> Option<&str> {
+        if let Some(session) = Self::session_space() {
+            return self.resolve_name(&session).or_else(|| {
+                self.find_by_path(Path::new(&session))
+                    .map(|(name, _)| name.as_str())
+            });
+        }
         self.active_space.as_deref()
     }
 
@@ -135,14 +683,11 @@ impl Config {
     ///
     /// - `Option<&SpaceConfig>` - The space configuration if found by name or path
     pub fn get_space_config(&self, name_or_path: &str) -> Option<&SpaceConfig> {
-        if let Some(config) = self.spaces.get(name_or_path) {
-            return Some(config);
+        if let Some(name) = self.resolve_name(name_or_path) {
+            return self.spaces.get(name);
         }
 
-        let path = PathBuf::from(name_or_path);
-        self.spaces
-            .iter()
-            .find(|(_, config)| config.path == path)
+        self.find_by_path(Path::new(name_or_path))
             .map(|(_, config)| config)
     }
 
@@ -156,7 +701,7 @@ impl Config {
     ///
     /// - `bool` - `true` if a sapce with this path is registered, `false` otherwise
     pub fn is_space_registered(&self, path: &std::path::Path) -> bool {
-        self.spaces.values().any(|config| config.path == path)
+        self.find_by_path(path).is_some()
     }
 
     /// Sets the active space by name or path.
@@ -173,13 +718,12 @@ impl Config {
     ///
     /// Returns an error if no space with the given name or path exists or the configuration could not be saved
     pub fn set_active_space(&mut self, name_or_path: &str) -> Result<()> {
-        if self.spaces.contains_key(name_or_path) {
-            self.active_space = Some(name_or_path.to_string());
+        if let Some(name) = self.resolve_name(name_or_path) {
+            self.active_space = Some(name.to_string());
             return self.save();
         }
 
-        let path = PathBuf::from(name_or_path);
-        if let Some((space_name, _)) = self.spaces.iter().find(|(_, config)| config.path == path) {
+        if let Some((space_name, _)) = self.find_by_path(Path::new(name_or_path)) {
             self.active_space = Some(space_name.clone());
             return self.save();
         }
@@ -207,13 +751,10 @@ impl Config {
     ///
     /// Returns an error if no space with the given name or path exists or the configuration could not be saved
     pub fn unregister_space(&mut self, name_or_path: &str) -> Result<()> {
-        let space_name = if self.spaces.contains_key(name_or_path) {
-            name_or_path.to_string()
+        let space_name = if let Some(name) = self.resolve_name(name_or_path) {
+            name.to_string()
         } else {
-            let path = PathBuf::from(name_or_path);
-            self.spaces
-                .iter()
-                .find(|(_, config)| config.path == path)
+            self.find_by_path(Path::new(name_or_path))
                 .map(|(name, _)| name.clone())
                 .with_context(|| {
                     format!(
@@ -233,6 +774,55 @@ impl Config {
         self.save()
     }
 
+    /// Finds a registered space by path.
+    ///
+    /// Both the given and the registered paths are normalized before comparing,
+    /// so relative paths, trailing slashes and symlinks all match.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - The path to look up
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(&String, &SpaceConfig)>` - The name and configuration of the space if found
+    fn find_by_path(&self, path: &Path) -> Option<(&String, &SpaceConfig)> {
+        let normalized = paths::normalize(path);
+        self.spaces
+            .iter()
+            .find(|(_, config)| paths::normalize(&config.path) == normalized)
+    }
+
+    /// Resolves a space name to the name it is registered under.
+    ///
+    /// Exact matches win. On Windows and macOS, whose filesystems are usually
+    /// case-insensitive, names are additionally matched ignoring case while the
+    /// registered spelling is preserved.
+    ///
+    /// # Arguments
+    ///
+    /// - `name` (`&str`) - The name to resolve
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&str>` - The registered name if a matching space exists
+    fn resolve_name(&self, name: &str) -> Option<&str> {
+        if let Some((registered, _)) = self.spaces.get_key_value(name) {
+            return Some(registered.as_str());
+        }
+
+        if cfg!(any(windows, target_os = "macos")) {
+            let lowercase = name.to_lowercase();
+            return self
+                .spaces
+                .keys()
+                .find(|registered| registered.to_lowercase() == lowercase)
+                .map(String::as_str);
+        }
+
+        None
+    }
+
     /// Returns the number of registered spaces.
     ///
     /// # Returns
@@ -253,7 +843,193 @@ impl Config {
             .map(|(name, config)| (name.clone(), config.clone()))
             .collect()
     }
+
+    /// Exports the configuration for another machine.
+    ///
+    /// Space paths below the home directory start with `~`, the device name
+    /// is left out as it belongs to this machine.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - The configuration as TOML.
+    ///
+    /// # Errors
+    ///
+    /// If the configuration can't be serialized.
+    pub fn export(&self) -> Result<String> {
+        toml::to_string_pretty(&self.exported(paths::home().as_deref())).into_diagnostic()
+    }
+
+    /// Imports an exported configuration and saves it.
+    ///
+    /// Paths starting with `~` are expanded to this machine's home directory.
+    /// Without `merge` the imported configuration replaces this one, keeping
+    /// the device name. With `merge` imported spaces are added unless the name
+    /// is taken, and imported settings only fill in settings not made here.
+    ///
+    /// # Arguments
+    ///
+    /// - `content` (`&str`) - The exported configuration as TOML.
+    /// - `merge` (`bool`) - Whether to merge into this configuration.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<ConfigImport>` - The adopted and skipped spaces.
+    ///
+    /// # Errors
+    ///
+    /// If the content isn't a valid configuration or the configuration could
+    /// not be saved.
+    pub fn import(&mut self, content: &str, merge: bool) -> Result<ConfigImport> {
+        let imported: Config = toml::from_str(content)
+            .into_diagnostic()
+            .context("Failed to parse the imported configuration")?;
+        let report = self.adopt(imported, paths::home().as_deref(), merge);
+        self.save()?;
+        Ok(report)
+    }
+
+    /// Returns a copy of the configuration as exported.
+    fn exported(&self, home: Option<&Path>) -> Config {
+        let mut exported = self.clone();
+        exported.device = None;
+        if let Some(home) = home {
+            for space in exported.spaces.values_mut() {
+                space.path = paths::contract_home(&space.path, home);
+            }
+        }
+        exported
+    }
+
+    /// Adopts an imported configuration.
+    fn adopt(&mut self, mut imported: Config, home: Option<&Path>, merge: bool) -> ConfigImport {
+        if let Some(home) = home {
+            for space in imported.spaces.values_mut() {
+                space.path = paths::expand_home(&space.path, home);
+            }
+        }
+
+        let mut report = ConfigImport::default();
+        let mut names: Vec<&String> = imported.spaces.keys().collect();
+        names.sort();
+        for name in names {
+            let space = &imported.spaces[name];
+            if merge && self.resolve_name(name).is_some() {
+                report.skipped.push(name.clone());
+                continue;
+            }
+            if !space.path.exists() {
+                report.missing.push(name.clone());
+            }
+            report.added.push(name.clone());
+        }
+
+        if !merge {
+            let device = self.device.take();
+            *self = imported;
+            self.device = device;
+            return report;
+        }
+
+        for name in &report.added {
+            self.spaces
+                .insert(name.clone(), imported.spaces[name].clone());
+        }
+        if self.active_space.is_none() {
+            self.active_space = imported.active_space;
+        }
+        if self.threads == 0 {
+            self.threads = imported.threads;
+        }
+        if self.author.is_none() {
+            self.author = imported.author;
+        }
+        if self.pdf_renderer.is_none() {
+            self.pdf_renderer = imported.pdf_renderer;
+        }
+        self.open_creates |= imported.open_creates;
+        if self.editor.is_none() {
+            self.editor = imported.editor;
+        }
+        if self.theme == Theme::default() {
+            self.theme = imported.theme;
+        }
+        if self.locale == Locale::default() {
+            self.locale = imported.locale;
+        }
+        report
+    }
+}
+
+/// Spaces adopted by a configuration import.
+///
+/// # Fields
+///
+/// - `added` (`Vec<String>`) - Names of the imported spaces.
+/// - `skipped` (`Vec<String>`) - Names already registered here, kept as they are.
+/// - `missing` (`Vec<String>`) - Names of imported spaces whose directory doesn't exist here.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ConfigImport {
+    pub added: Vec<String>,
+    pub skipped: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+/// Returns the history directory next to the configuration file.
+fn history_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(HISTORY_DIR)
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    fn config(spaces: &[(&str, &str)]) -> Config {
+        let mut config = Config::default();
+        for (name, path) in spaces {
+            config.spaces.insert(
+                name.to_string(),
+                SpaceConfig {
+                    path: PathBuf::from(path),
+                    notifications: NotificationSettings::default(),
+                    plugins: BTreeMap::new(),
+                },
+            );
+        }
+        config
+    }
+
+    #[test]
+    fn test_export_templates_home_and_drops_device() {
+        let mut laptop = config(&[("notes", "/home/ada/notes"), ("shared", "/srv/shared")]);
+        laptop.device = Some("laptop".to_string());
+
+        let exported = laptop.exported(Some(Path::new("/home/ada")));
+        assert_eq!(exported.device, None);
+        assert_eq!(exported.spaces["notes"].path, Path::new("~/notes"));
+        assert_eq!(exported.spaces["shared"].path, Path::new("/srv/shared"));
+    }
+
+    #[test]
+    fn test_import_replaces_or_merges() {
+        let exported = config(&[("notes", "~/notes"), ("work", "~/work")]);
+
+        let mut desktop = config(&[("notes", "/data/notes"), ("old", "/data/old")]);
+        desktop.device = Some("desktop".to_string());
+        let report = desktop.adopt(exported.clone(), Some(Path::new("/Users/ada")), true);
+        assert_eq!(report.added, vec!["work"]);
+        assert_eq!(report.skipped, vec!["notes"]);
+        assert_eq!(desktop.spaces["notes"].path, Path::new("/data/notes"));
+        assert_eq!(desktop.spaces["work"].path, Path::new("/Users/ada/work"));
+        assert!(desktop.spaces.contains_key("old"));
+
+        let report = desktop.adopt(exported, Some(Path::new("/Users/ada")), false);
+        assert_eq!(report.added, vec!["notes", "work"]);
+        assert_eq!(desktop.spaces["notes"].path, Path::new("/Users/ada/notes"));
+        assert!(!desktop.spaces.contains_key("old"));
+        assert_eq!(desktop.device.as_deref(), Some("desktop"));
+    }
+}
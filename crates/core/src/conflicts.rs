@@ -0,0 +1,306 @@
+//! Sync Conflicts
+//!
+//! The document merges concurrent edits automatically, but two people
+//! rewriting the same block still produce a result neither of them wrote.
+//! After a sync import, blocks changed on both sides since their common
+//! version are detected with a three-way line diff and queued for review in
+//! `.flow/conflicts.json`. The queue is local to this machine and isn't synced.
+
+use chrono::Local;
+use loro::{LoroDoc, VersionVector};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::attribution::{self, Author};
+use crate::diff::changes;
+use crate::page;
+use crate::space::{container_names, write_atomic, Space, FLOW_DIR};
+
+const CONFLICTS_FILE: &str = "conflicts.json";
+
+/// A block changed concurrently on both sides of a sync.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `line` (`usize`) - 1-based line of the block in the merged page.
+/// - `base` (`String`) - The block as both sides last agreed on.
+/// - `local` (`String`) - The block as changed locally.
+/// - `remote` (`String`) - The block as changed by the peer.
+/// - `merged` (`String`) - The block after merging.
+/// - `local_author` (`Option<Author>`) - Author of the latest local change the peer missed.
+/// - `remote_author` (`Option<Author>`) - Author of the latest change received from the peer.
+/// - `detected` (`i64`) - Detection time as unix timestamp in seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+pub struct Conflict {
+    pub id: String,
+    pub page: String,
+    pub line: usize,
+    pub base: String,
+    pub local: String,
+    pub remote: String,
+    pub merged: String,
+    #[serde(default)]
+    pub local_author: Option<Author>,
+    #[serde(default)]
+    pub remote_author: Option<Author>,
+    pub detected: i64,
+}
+
+impl Space {
+    /// Returns the conflicts queued for review.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Conflict>>` - Queued conflicts, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Parse errors if the queue is corrupt.
+    pub fn conflicts(&self) -> Result<Vec<Conflict>> {
+        load(&self.path)
+    }
+
+    /// Empties the review queue.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<usize>` - Number of conflicts removed.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the queue.
+    pub fn clear_conflicts(&self) -> Result<usize> {
+        let count = load(&self.path)
+            .map(|conflicts| conflicts.len())
+            .unwrap_or(0);
+        save(&self.path, &[])?;
+        Ok(count)
+    }
+}
+
+/// Adds conflicts to the review queue of a space.
+///
+/// # Arguments
+///
+/// - `space_path` (`&Path`) - Path of the space.
+/// - `conflicts` (`&[Conflict]`) - Conflicts to add.
+///
+/// # Errors
+///
+/// IO errors when writing the queue.
+pub(crate) fn record(space_path: &Path, conflicts: &[Conflict]) -> Result<()> {
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    let mut queue = load(space_path).unwrap_or_default();
+    queue.extend_from_slice(conflicts);
+    save(space_path, &queue)
+}
+
+/// Finds blocks changed concurrently since two versions of a document diverged.
+///
+/// Checks out the local and remote versions and their common version to
+/// compare each page in all three states, then returns to the latest version.
+///
+/// # Arguments
+///
+/// - `doc` (`&LoroDoc`) - Document after importing the remote changes.
+/// - `local` (`&VersionVector`) - Version of the document before the import.
+/// - `remote` (`&VersionVector`) - Version the peer had when sending its changes.
+///
+/// # Returns
+///
+/// - `Result<Vec<Conflict>>` - Concurrently changed blocks.
+///
+/// # Errors
+///
+/// Loro errors when checking out a version.
+pub(crate) fn detect(
+    doc: &LoroDoc,
+    local: &VersionVector,
+    remote: &VersionVector,
+) -> Result<Vec<Conflict>> {
+    // Without local changes the peer missed, nothing happened concurrently
+    if remote.includes_vv(local) || local.includes_vv(remote) {
+        return Ok(Vec::new());
+    }
+
+    let merged = pages_at(doc, None)?;
+    let base = pages_at(doc, Some(&local.intersection(remote)))?;
+    let ours = pages_at(doc, Some(local))?;
+    let theirs = pages_at(doc, Some(remote))?;
+
+    // Attribute each side to its latest change the other side lacked
+    let history = attribution::changes(doc);
+    let latest = |side: &VersionVector, other: &VersionVector| {
+        history
+            .iter()
+            .rev()
+            .find(|(last, _, _)| side.includes_id(*last) && !other.includes_id(*last))
+            .and_then(|(_, _, change)| change.author.clone())
+    };
+    let local_author = latest(local, remote);
+    let remote_author = latest(remote, local);
+
+    let detected = Local::now().timestamp();
+    let mut conflicts = Vec::new();
+    for (id, merged) in &merged {
+        let (Some(local), Some(remote)) = (ours.get(id), theirs.get(id)) else {
+            continue;
+        };
+        let base = base.get(id).map(String::as_str).unwrap_or_default();
+        for mut conflict in three_way(base, local, remote, merged) {
+            conflict.id = id.clone();
+            conflict.page = page::name_from_id(id);
+            conflict.local_author = local_author.clone();
+            conflict.remote_author = remote_author.clone();
+            conflict.detected = detected;
+            conflicts.push(conflict);
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Reads the pages of a document at a version, the latest if `None`.
+fn pages_at(doc: &LoroDoc, version: Option<&VersionVector>) -> Result<BTreeMap<String, String>> {
+    if let Some(version) = version {
+        doc.checkout(&doc.vv_to_frontiers(version))
+            .into_diagnostic()?;
+    }
+    let pages = container_names(doc)
+        .into_iter()
+        .filter(|id| id.ends_with(".md"))
+        .map(|id| {
+            let content = doc.get_text(id.as_str()).to_string();
+            (id, content)
+        })
+        .collect();
+    doc.checkout_to_latest();
+    Ok(pages)
+}
+
+/// Finds the blocks of a page both sides changed.
+///
+/// Changed regions of both sides that overlap, or insert at the same line,
+/// are conflicts. Page fields of the returned conflicts are left empty.
+fn three_way(base: &str, local: &str, remote: &str, merged: &str) -> Vec<Conflict> {
+    let base: Vec<&str> = base.lines().collect();
+    let local: Vec<&str> = local.lines().collect();
+    let remote: Vec<&str> = remote.lines().collect();
+    let merged: Vec<&str> = merged.lines().collect();
+
+    let ours = changes(&base, &local);
+    let theirs = changes(&base, &remote);
+    let merges = changes(&local, &merged);
+
+    let mut conflicts = Vec::new();
+    for (our_base, our_lines) in &ours {
+        for (their_base, their_lines) in &theirs {
+            let overlaps = our_base.start == their_base.start
+                || (our_base.start < their_base.end && their_base.start < our_base.end);
+            // Both sides making the same change isn't a conflict
+            if !overlaps
+                || (our_base == their_base
+                    && local[our_lines.clone()] == remote[their_lines.clone()])
+            {
+                continue;
+            }
+
+            let block = our_base.start.min(their_base.start)..our_base.end.max(their_base.end);
+            let local_block = widen(&block, our_base, our_lines, local.len());
+            let remote_block = widen(&block, their_base, their_lines, remote.len());
+            let start = map_line(&merges, local_block.start, false).min(merged.len());
+            let end = map_line(&merges, local_block.end, true).clamp(start, merged.len());
+
+            conflicts.push(Conflict {
+                id: String::new(),
+                page: String::new(),
+                line: start + 1,
+                base: base[block].join("\n"),
+                local: local[local_block].join("\n"),
+                remote: remote[remote_block].join("\n"),
+                merged: merged[start..end].join("\n"),
+                local_author: None,
+                remote_author: None,
+                detected: 0,
+            });
+        }
+    }
+    conflicts
+}
+
+/// Widens a changed region of one side to cover a larger range of base lines.
+fn widen(
+    block: &Range<usize>,
+    changed_base: &Range<usize>,
+    changed: &Range<usize>,
+    len: usize,
+) -> Range<usize> {
+    let start = changed.start - (changed_base.start - block.start);
+    let end = (changed.end + (block.end - changed_base.end)).min(len);
+    start..end
+}
+
+/// Maps a line index through the regions one version changed in another.
+///
+/// Lines inside a changed region map to the start of its replacement, or to
+/// its end for exclusive `end` indices.
+fn map_line(regions: &[(Range<usize>, Range<usize>)], index: usize, end: bool) -> usize {
+    let mut mapped = index;
+    for (from, to) in regions {
+        if from.start < index && index < from.end {
+            return if end { to.end } else { to.start };
+        }
+        if from.end <= index {
+            mapped = mapped + to.len() - from.len();
+        }
+    }
+    mapped
+}
+
+/// Loads the review queue of a space, empty if missing.
+fn load(space_path: &Path) -> Result<Vec<Conflict>> {
+    match fs::read_to_string(space_path.join(FLOW_DIR).join(CONFLICTS_FILE)) {
+        Ok(json) => serde_json::from_str(&json).into_diagnostic(),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Persists the review queue of a space.
+fn save(space_path: &Path, conflicts: &[Conflict]) -> Result<()> {
+    let json = serde_json::to_string_pretty(conflicts).into_diagnostic()?;
+    write_atomic(
+        &space_path.join(FLOW_DIR).join(CONFLICTS_FILE),
+        json.as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_three_way() {
+        let base = "- one\n- two\n- three";
+        let local = "- one\n- two, rewritten\n- three";
+        let remote = "- one\n- two, reworded\n- three";
+        let merged = "- one\n- two, rewritten, reworded\n- three";
+
+        let conflicts = three_way(base, local, remote, merged);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].line, 2);
+        assert_eq!(conflicts[0].base, "- two");
+        assert_eq!(conflicts[0].local, "- two, rewritten");
+        assert_eq!(conflicts[0].remote, "- two, reworded");
+
+        let remote = "- one\n- two\n- three, reworded";
+        assert!(three_way(base, local, remote, merged).is_empty());
+    }
+}
@@ -0,0 +1,370 @@
+//! Capture Context
+//!
+//! Local state that changes where captured content goes, stored per space in
+//! `.flow/context.json`. It is deliberately kept out of the document: the
+//! context belongs to this machine and isn't synced.
+//!
+//! A sticky [`ContextTarget`] routes or tags everything [`Space::add`]
+//! captures: nodes are tagged, added to another page, or nested below a
+//! heading block of today's journal page.
+//!
+//! A running meeting takes precedence over page and heading targets. Starting
+//! a meeting adds a section to today's journal page, created from the meeting
+//! template (the page named `templates/meeting`, falling back to a single
+//! heading block). `{{title}}`, `{{date}}` and `{{time}}` in the template are
+//! replaced. Until the meeting ends, [`Space::add`] nests new nodes below that
+//! section.
+
+use chrono::{DateTime, Local};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::page;
+use crate::space::{write_atomic, Space, FLOW_DIR};
+
+const CONTEXT_FILE: &str = "context.json";
+
+/// Name of the page meeting sections are created from.
+const MEETING_TEMPLATE: &str = "templates/meeting";
+
+/// Section added when no meeting template exists.
+const DEFAULT_MEETING_TEMPLATE: &str = "- {{time}} {{title}} #meeting";
+
+/// A running meeting.
+///
+/// # Fields
+///
+/// - `title` (`String`) - Title of the meeting.
+/// - `page` (`String`) - Id of the journal page holding the meeting's section.
+/// - `started` (`i64`) - Start as unix timestamp in seconds.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct Meeting {
+    pub title: String,
+    pub page: String,
+    pub started: i64,
+}
+
+impl Meeting {
+    /// Returns when the meeting started.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<DateTime<Local>>` - Local start time, `None` if out of range.
+    pub fn started_at(&self) -> Option<DateTime<Local>> {
+        DateTime::from_timestamp(self.started, 0).map(|time| time.with_timezone(&Local))
+    }
+}
+
+/// Where captured nodes go, or how they are tagged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ContextTarget {
+    /// Tag every node, e.g. `#projectX`.
+    Tag(String),
+    /// Add nodes to the end of a page, e.g. `[[Project X]]`.
+    Page(String),
+    /// Nest nodes below a heading block of today's journal page, e.g. `## Standup`.
+    Heading(String),
+}
+
+impl ContextTarget {
+    /// Parses a target from its written form.
+    ///
+    /// `#tag` is a tag, `# Heading` (one or more `#` followed by a space) a
+    /// heading and `[[Page]]` or any other text a page.
+    ///
+    /// # Arguments
+    ///
+    /// - `value` (`&str`) - Written form of the target.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<Self>` - The target, `None` if the value is empty.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let hashes = value.len() - value.trim_start_matches('#').len();
+
+        if value.is_empty() || value.len() == hashes {
+            None
+        } else if hashes > 0 && value[hashes..].starts_with(' ') {
+            Some(Self::Heading(value.to_string()))
+        } else if hashes == 1 {
+            Some(Self::Tag(value[1..].trim_matches(['[', ']']).to_string()))
+        } else {
+            let page = value
+                .strip_prefix("[[")
+                .and_then(|page| page.strip_suffix("]]"))
+                .unwrap_or(value);
+            Some(Self::Page(page.trim().to_string()))
+        }
+    }
+}
+
+/// Capture context of a space.
+///
+/// # Fields
+///
+/// - `meeting` (`Option<Meeting>`) - The running meeting, if any.
+/// - `target` (`Option<ContextTarget>`) - The sticky target, if any.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, Deserialize)]
+pub struct Context {
+    #[serde(default)]
+    pub meeting: Option<Meeting>,
+    #[serde(default)]
+    pub target: Option<ContextTarget>,
+}
+
+impl Context {
+    /// Loads the context of a space, empty if missing or unreadable.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The capture context.
+    pub fn load(space_path: &Path) -> Self {
+        fs::read_to_string(space_path.join(FLOW_DIR).join(CONTEXT_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persists the context of a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Context`) - Context to persist.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the context file.
+    pub fn save(&self, space_path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).into_diagnostic()?;
+        write_atomic(
+            &space_path.join(FLOW_DIR).join(CONTEXT_FILE),
+            json.as_bytes(),
+        )
+    }
+}
+
+impl Space {
+    /// Starts a meeting, adding its section to today's journal page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to capture into.
+    /// - `title` (`&str`) - Title of the meeting.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Meeting>` - The started meeting.
+    ///
+    /// # Errors
+    ///
+    /// Another meeting is still running, or IO errors when writing files.
+    pub fn start_meeting(&mut self, title: &str) -> Result<Meeting> {
+        let mut context = Context::load(&self.path);
+        if let Some(running) = &context.meeting {
+            miette::bail!(
+                "Meeting '{}' is still running, end it before starting another one",
+                running.title
+            );
+        }
+
+        let now = self.clock_settings().now();
+        let day = self.clock_settings().day_of(now);
+        let date = day.format("%Y-%m-%d").to_string();
+        let id = self.journal_page(day);
+        let template = self
+            .read_page(&page::id_from_name(MEETING_TEMPLATE))?
+            .filter(|template| !template.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_MEETING_TEMPLATE.to_string());
+        let section = template
+            .trim_end()
+            .replace("{{title}}", title)
+            .replace("{{date}}", &date)
+            .replace("{{time}}", &now.format("%H:%M").to_string());
+        self.append(&id, &section)?;
+
+        let meeting = Meeting {
+            title: title.to_string(),
+            page: id,
+            started: now.timestamp(),
+        };
+        context.meeting = Some(meeting.clone());
+        context.save(&self.path)?;
+        Ok(meeting)
+    }
+
+    /// Ends the running meeting, so new nodes go to today's page again.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to capture into.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<Meeting>>` - The ended meeting, `None` if none was running.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the context file.
+    pub fn end_meeting(&self) -> Result<Option<Meeting>> {
+        let mut context = Context::load(&self.path);
+        let meeting = context.meeting.take();
+        if meeting.is_some() {
+            context.save(&self.path)?;
+        }
+        Ok(meeting)
+    }
+
+    /// Returns the capture context of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Context` - The current context.
+    pub fn context(&self) -> Context {
+        Context::load(&self.path)
+    }
+
+    /// Sets the sticky target of the capture context.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to capture into.
+    /// - `target` (`Option<ContextTarget>`) - New target, `None` to clear it.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Context>` - The updated context.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the context file.
+    pub fn set_context_target(&self, target: Option<ContextTarget>) -> Result<Context> {
+        let mut context = Context::load(&self.path);
+        context.target = target;
+        context.save(&self.path)?;
+        Ok(context)
+    }
+
+    /// Adds a node below a heading block, creating the heading if needed.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `heading` (`&str`) - Heading block to add below, e.g. `## Standup`.
+    /// - `content` (`&str`) - Content of the node.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading or writing the page.
+    pub(crate) fn add_below_heading(
+        &mut self,
+        id: &str,
+        heading: &str,
+        content: &str,
+    ) -> Result<()> {
+        // The file wins over the document, like for appends
+        let existing = match fs::read_to_string(self.path.join(id)) {
+            Ok(existing) if !self.dirty.contains(id) => existing,
+            _ => self.read_page(id)?.unwrap_or_default(),
+        };
+        self.write_page(id, &insert_below_heading(&existing, heading, content))
+    }
+
+    /// Returns the running meeting.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<Meeting>` - The running meeting, if any.
+    pub fn current_meeting(&self) -> Option<Meeting> {
+        Context::load(&self.path).meeting
+    }
+}
+
+/// Inserts a node as the last child of a heading block.
+///
+/// The heading block is appended to the page if it doesn't exist yet.
+fn insert_below_heading(content: &str, heading: &str, node: &str) -> String {
+    let mut lines: Vec<&str> = content.lines().collect();
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+
+    let found = lines.iter().position(|line| {
+        let block = line.trim_start();
+        block.strip_prefix("- ").unwrap_or(block).trim() == heading
+    });
+
+    let child;
+    match found {
+        Some(index) => {
+            let indent = indent_of(lines[index]);
+            let mut end = index + 1;
+            while end < lines.len()
+                && (lines[end].trim().is_empty() || indent_of(lines[end]) > indent)
+            {
+                end += 1;
+            }
+            // Keep trailing blank lines after the inserted node
+            while end > index + 1 && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+            // Further lines of the node are relative to the heading as well
+            let prefix = " ".repeat(indent);
+            child = format!(
+                "{}  - {}",
+                prefix,
+                node.replace('\n', &format!("\n{}", prefix))
+            );
+            lines.insert(end, &child);
+        }
+        None => {
+            child = format!("- {}\n  - {}", heading, node);
+            lines.push(&child);
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_context_target() {
+        assert_eq!(
+            ContextTarget::parse("#projectX"),
+            Some(ContextTarget::Tag("projectX".to_string()))
+        );
+        assert_eq!(
+            ContextTarget::parse("## Standup"),
+            Some(ContextTarget::Heading("## Standup".to_string()))
+        );
+        assert_eq!(
+            ContextTarget::parse("[[Project X]]"),
+            Some(ContextTarget::Page("Project X".to_string()))
+        );
+        assert_eq!(ContextTarget::parse("  "), None);
+    }
+
+    #[test]
+    fn test_insert_below_heading() {
+        let content = "- intro\n- ## Standup\n  - first\n- outro";
+        assert_eq!(
+            insert_below_heading(content, "## Standup", "second"),
+            "- intro\n- ## Standup\n  - first\n  - second\n- outro"
+        );
+        assert_eq!(
+            insert_below_heading("- intro", "## Standup", "first"),
+            "- intro\n- ## Standup\n  - first"
+        );
+    }
+}
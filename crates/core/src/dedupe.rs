@@ -0,0 +1,377 @@
+//! Duplicate Detection
+//!
+//! Finds pages and blocks with identical or nearly identical content, as
+//! quick capture tends to produce over time. Texts are split into word pairs
+//! whose MinHash signatures estimate how much two texts overlap; locality
+//! sensitive hashing over bands of the signatures keeps the comparisons to
+//! likely candidates, so large spaces don't compare every pair.
+//!
+//! Pages are compared with pages, journal pages excluded since they share
+//! their templates. Blocks are compared with blocks across all pages, except
+//! for blocks of pages already reported as duplicates.
+//!
+//! Duplicate pages are resolved by merging them into the page to keep, see
+//! [`merge`](crate::merge).
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::fulltext;
+use crate::page;
+use crate::space::Space;
+
+/// Similarity from which texts are duplicates, by default.
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// Number of hash functions of a signature.
+const PERMUTATIONS: usize = 64;
+
+/// Signature values per band, `PERMUTATIONS / ROWS` bands.
+const ROWS: usize = 4;
+
+/// Pages with fewer words are too short to compare.
+const MIN_PAGE_WORDS: usize = 8;
+
+/// Blocks with fewer words are too short to compare.
+const MIN_BLOCK_WORDS: usize = 4;
+
+/// Maximum number of characters shown of a duplicate.
+const PREVIEW_CHARS: usize = 80;
+
+/// What a duplicate is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKind {
+    Page,
+    Block,
+}
+
+/// A page or block with duplicates.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `line` (`Option<usize>`) - Line of the block, starting at 1, `None` for pages.
+/// - `text` (`String`) - Beginning of the text.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Duplicate {
+    pub id: String,
+    pub page: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub text: String,
+}
+
+/// Pages or blocks duplicating each other.
+///
+/// # Fields
+///
+/// - `kind` (`DuplicateKind`) - Whether the cluster holds pages or blocks.
+/// - `similarity` (`f64`) - Lowest estimated similarity linking the cluster, 1 for identical texts.
+/// - `items` (`Vec<Duplicate>`) - The duplicates, in order of their pages.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DuplicateCluster {
+    pub kind: DuplicateKind,
+    pub similarity: f64,
+    pub items: Vec<Duplicate>,
+}
+
+/// A text to compare.
+struct Candidate {
+    item: Duplicate,
+    signature: Vec<u64>,
+}
+
+impl Candidate {
+    /// Creates a candidate if the text is long enough to compare.
+    fn new(item: Duplicate, text: &str, min_words: usize) -> Option<Self> {
+        let words = fulltext::tokenize(text);
+        if words.len() < min_words {
+            return None;
+        }
+        Some(Self {
+            item,
+            signature: signature(&shingles(&words)),
+        })
+    }
+}
+
+impl Space {
+    /// Finds duplicate pages and blocks.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `threshold` (`f64`) - Similarity from which texts are duplicates, from 0 to 1.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<DuplicateCluster>>` - Page clusters first, most similar first.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages.
+    pub fn find_duplicates(&self, threshold: f64) -> Result<Vec<DuplicateCluster>> {
+        let mut pages = Vec::new();
+        let mut blocks = Vec::new();
+        for id in self.page_ids()? {
+            let Some(content) = self.read_page(&id)? else {
+                continue;
+            };
+            let name = page::name_from_id(&id);
+
+            if !page::is_journal(&id) {
+                let item = Duplicate {
+                    id: id.clone(),
+                    page: name.clone(),
+                    line: None,
+                    text: preview(
+                        body(&content)
+                            .lines()
+                            .map(block_text)
+                            .find(|t| !t.is_empty()),
+                    ),
+                };
+                pages.extend(Candidate::new(item, body(&content), MIN_PAGE_WORDS));
+            }
+
+            for (index, line) in content.lines().enumerate() {
+                let Some(text) = line.trim_start().strip_prefix("- ") else {
+                    continue;
+                };
+                let item = Duplicate {
+                    id: id.clone(),
+                    page: name.clone(),
+                    line: Some(index + 1),
+                    text: preview(Some(text)),
+                };
+                blocks.extend(Candidate::new(item, text, MIN_BLOCK_WORDS));
+            }
+        }
+
+        let mut found = clusters(DuplicateKind::Page, pages, threshold);
+        let duplicated: HashSet<String> = found
+            .iter()
+            .flat_map(|cluster| cluster.items.iter().map(|item| item.id.clone()))
+            .collect();
+        blocks.retain(|candidate| !duplicated.contains(&candidate.item.id));
+        found.extend(clusters(DuplicateKind::Block, blocks, threshold));
+        Ok(found)
+    }
+}
+
+/// Groups candidates whose estimated similarity reaches the threshold.
+fn clusters(
+    kind: DuplicateKind,
+    candidates: Vec<Candidate>,
+    threshold: f64,
+) -> Vec<DuplicateCluster> {
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        for (band, rows) in candidate.signature.chunks(ROWS).enumerate() {
+            let key = rows
+                .iter()
+                .fold(0xcbf29ce484222325, |hash, value| mix(hash ^ value));
+            buckets.entry((band, key)).or_default().push(index);
+        }
+    }
+
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    let mut lowest: HashMap<usize, f64> = HashMap::new();
+    let mut compared = HashSet::new();
+    for members in buckets.values().filter(|members| members.len() > 1) {
+        for (i, &a) in members.iter().enumerate() {
+            for &b in &members[i + 1..] {
+                if !compared.insert((a, b)) {
+                    continue;
+                }
+                let score = similarity(&candidates[a].signature, &candidates[b].signature);
+                if score < threshold {
+                    continue;
+                }
+                let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+                let low = [lowest.get(&root_a), lowest.get(&root_b)]
+                    .into_iter()
+                    .flatten()
+                    .fold(score, |low, s| low.min(*s));
+                parent[root_b] = root_a;
+                lowest.remove(&root_b);
+                lowest.insert(root_a, low);
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<Duplicate>> = HashMap::new();
+    for (index, candidate) in candidates.into_iter().enumerate() {
+        let root = find(&mut parent, index);
+        if lowest.contains_key(&root) {
+            groups.entry(root).or_default().push(candidate.item);
+        }
+    }
+
+    let mut found: Vec<DuplicateCluster> = groups
+        .into_iter()
+        .map(|(root, items)| DuplicateCluster {
+            kind,
+            similarity: lowest[&root],
+            items,
+        })
+        .collect();
+    for cluster in &mut found {
+        cluster
+            .items
+            .sort_by(|a, b| a.id.cmp(&b.id).then(a.line.cmp(&b.line)));
+    }
+    found.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| a.items[0].id.cmp(&b.items[0].id))
+            .then(a.items[0].line.cmp(&b.items[0].line))
+    });
+    found
+}
+
+/// Finds the representative of a candidate's cluster.
+fn find(parent: &mut [usize], mut index: usize) -> usize {
+    while parent[index] != index {
+        parent[index] = parent[parent[index]];
+        index = parent[index];
+    }
+    index
+}
+
+/// Hashes the word pairs of a text, or its single word.
+fn shingles(words: &[String]) -> HashSet<u64> {
+    if words.len() < 2 {
+        return words.iter().map(|word| word_hash(word)).collect();
+    }
+    words
+        .windows(2)
+        .map(|pair| word_hash(&format!("{} {}", pair[0], pair[1])))
+        .collect()
+}
+
+/// Computes the MinHash signature of a set of shingles.
+fn signature(shingles: &HashSet<u64>) -> Vec<u64> {
+    (0..PERMUTATIONS as u64)
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|shingle| mix(shingle ^ seed.wrapping_mul(0x9e3779b97f4a7c15)))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// Estimates the similarity of two texts from their signatures.
+fn similarity(a: &[u64], b: &[u64]) -> f64 {
+    let equal = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    equal as f64 / PERMUTATIONS as f64
+}
+
+/// Scrambles a value with the SplitMix64 finalizer.
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+/// Hashes a shingle with FNV-1a, which is stable across Rust versions.
+fn word_hash(word: &str) -> u64 {
+    word.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Returns a page without its page properties, which differ between copies.
+fn body(content: &str) -> &str {
+    let mut rest = content;
+    while let Some((line, next)) = rest.split_once('\n') {
+        if line.starts_with('-') || !line.contains("::") {
+            break;
+        }
+        rest = next;
+    }
+    rest
+}
+
+/// Returns the text of a line without its block marker.
+fn block_text(line: &str) -> &str {
+    let line = line.trim();
+    line.strip_prefix("- ").unwrap_or(line)
+}
+
+/// Shortens a text for display.
+fn preview(text: Option<&str>) -> String {
+    let text = text.unwrap_or_default();
+    match text.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn space() -> Space {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page(
+                "pages/Sync rewrite.md",
+                "- The sync rewrite moves conflict handling into a merge queue\n- Ship it after the beta\n",
+            )
+            .unwrap();
+        space
+            .write_page(
+                "pages/Sync plan.md",
+                "alias:: SP\n- The sync rewrite moves conflict handling into a merge queue\n- Ship it after the beta\n- Benchmark the queue first\n",
+            )
+            .unwrap();
+        space
+            .write_page(
+                "pages/Garden.md",
+                "- Planted tomatoes, basil and chili along the southern fence\n",
+            )
+            .unwrap();
+        space
+            .write_page(
+                "journal/2024-05-01.md",
+                "- Call the plumber about the kitchen sink\n",
+            )
+            .unwrap();
+        space
+            .write_page(
+                "journal/2024-05-02.md",
+                "- Call the plumber about the kitchen sink\n",
+            )
+            .unwrap();
+        space
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let found = space().find_duplicates(0.5).unwrap();
+        assert_eq!(found.len(), 2);
+
+        assert_eq!(found[0].kind, DuplicateKind::Page);
+        let pages: Vec<&str> = found[0].items.iter().map(|i| i.page.as_str()).collect();
+        assert_eq!(pages, vec!["Sync plan", "Sync rewrite"]);
+
+        assert_eq!(found[1].kind, DuplicateKind::Block);
+        assert_eq!(found[1].similarity, 1.0);
+        let lines: Vec<(&str, Option<usize>)> = found[1]
+            .items
+            .iter()
+            .map(|i| (i.page.as_str(), i.line))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![("2024-05-01", Some(1)), ("2024-05-02", Some(1))]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Page Diffs
+//!
+//! Line diffs of a page between its current state in the document and either
+//! a historical version or the markdown file on disk, in the structure of a
+//! unified diff. Versions are checkpoint names or the change ids listed by
+//! [`Space::log`].
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs;
+use std::ops::Range;
+
+use crate::page;
+use crate::space::{container_names, Space};
+
+/// Unchanged lines shown around each change.
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// Kind of a line in a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineKind {
+    /// Line present in both versions.
+    Context,
+    /// Line only present in the old version.
+    Removed,
+    /// Line only present in the new version.
+    Added,
+}
+
+/// A line in a diff.
+///
+/// # Fields
+///
+/// - `kind` (`LineKind`) - Whether the line was kept, removed or added.
+/// - `text` (`String`) - Content of the line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub text: String,
+}
+
+/// A group of nearby changes with their surrounding context.
+///
+/// # Fields
+///
+/// - `old_start` (`usize`) - 1-based first line in the old version, the line before if empty.
+/// - `old_lines` (`usize`) - Number of lines in the old version.
+/// - `new_start` (`usize`) - 1-based first line in the new version, the line before if empty.
+/// - `new_lines` (`usize`) - Number of lines in the new version.
+/// - `lines` (`Vec<DiffLine>`) - Lines of the hunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+impl Hunk {
+    /// Formats the hunk header, `@@ -1,3 +1,4 @@`.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - The header line.
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_lines, self.new_start, self.new_lines
+        )
+    }
+}
+
+/// Diff of a page between two versions.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `old` (`String`) - Label of the old version.
+/// - `new` (`String`) - Label of the new version.
+/// - `hunks` (`Vec<Hunk>`) - Changes between the versions, empty if they are equal.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct PageDiff {
+    pub id: String,
+    pub page: String,
+    pub old: String,
+    pub new: String,
+    pub hunks: Vec<Hunk>,
+}
+
+impl PageDiff {
+    /// Checks whether both versions are equal.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Formats the diff as unified diff.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - The unified diff, empty if both versions are equal.
+    pub fn to_unified(&self) -> String {
+        let mut out = String::new();
+        if self.is_empty() {
+            return out;
+        }
+
+        let _ = writeln!(out, "--- {}", self.old);
+        let _ = writeln!(out, "+++ {}", self.new);
+        for hunk in &self.hunks {
+            let _ = writeln!(out, "{}", hunk.header());
+            for line in &hunk.lines {
+                let prefix = match line.kind {
+                    LineKind::Context => ' ',
+                    LineKind::Removed => '-',
+                    LineKind::Added => '+',
+                };
+                let _ = writeln!(out, "{}{}", prefix, line.text);
+            }
+        }
+        out
+    }
+}
+
+impl Space {
+    /// Diffs a page in the document against its markdown file.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space containing the page.
+    /// - `name` (`&str`) - Name of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<PageDiff>>` - Changes from the document to the file, `None` if neither has the page.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the index.
+    pub fn diff_disk(&self, name: &str) -> Result<Option<PageDiff>> {
+        let id = self.page_index()?.resolve(name);
+        let disk = fs::read_to_string(self.path.join(&id)).ok();
+        if disk.is_none() && !container_names(&self.document).contains(&id) {
+            return Ok(None);
+        }
+
+        let current = self.document.get_text(id.as_str()).to_string();
+        Ok(Some(PageDiff {
+            hunks: diff(
+                &current,
+                disk.as_deref().unwrap_or_default(),
+                DEFAULT_CONTEXT,
+            ),
+            old: format!("document/{}", id),
+            new: format!("disk/{}", id),
+            page: page::name_from_id(&id),
+            id,
+        }))
+    }
+
+    /// Diffs a page at a historical version against its current state.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space containing the page.
+    /// - `name` (`&str`) - Name of the page.
+    /// - `version` (`&str`) - Name of a checkpoint or id of a change, `counter@peer`.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<PageDiff>>` - Changes since the version, `None` if the page doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// The version is unknown or was compacted away, or IO errors when loading the index.
+    pub fn diff_at(&self, name: &str, version: &str) -> Result<Option<PageDiff>> {
+        let id = self.page_index()?.resolve(name);
+        if !container_names(&self.document).contains(&id) {
+            return Ok(None);
+        }
+
+        let frontiers = self.version_frontiers(version)?;
+        let doc = &self.document;
+        if doc.checkout(&frontiers).is_err() {
+            doc.checkout_to_latest();
+            miette::bail!(
+                "Version '{}' is no longer available after compaction",
+                version
+            );
+        }
+        let old = doc.get_text(id.as_str()).to_string();
+        doc.checkout_to_latest();
+        let current = doc.get_text(id.as_str()).to_string();
+
+        Ok(Some(PageDiff {
+            hunks: diff(&old, &current, DEFAULT_CONTEXT),
+            old: format!("{}/{}", version, id),
+            new: format!("document/{}", id),
+            page: page::name_from_id(&id),
+            id,
+        }))
+    }
+}
+
+/// Diffs two texts line by line.
+///
+/// # Arguments
+///
+/// - `old` (`&str`) - Old version of the text.
+/// - `new` (`&str`) - New version of the text.
+/// - `context` (`usize`) - Unchanged lines to show around each change.
+///
+/// # Returns
+///
+/// - `Vec<Hunk>` - Changes between the texts, empty if they are equal.
+pub fn diff(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Changes closer than twice the context share a hunk
+    let mut groups: Vec<Vec<(Range<usize>, Range<usize>)>> = Vec::new();
+    for region in changes(&old, &new) {
+        match groups.last_mut() {
+            Some(group) if region.0.start - group[group.len() - 1].0.end <= 2 * context => {
+                group.push(region)
+            }
+            _ => groups.push(vec![region]),
+        }
+    }
+
+    let mut hunks = Vec::new();
+    for group in groups {
+        let (first, last) = (&group[0], &group[group.len() - 1]);
+        let old_start = first.0.start.saturating_sub(context);
+        let old_end = (last.0.end + context).min(old.len());
+        let new_start = first.1.start - (first.0.start - old_start);
+        let new_end = last.1.end + (old_end - last.0.end);
+
+        let mut lines = Vec::new();
+        let mut cursor = old_start;
+        let line = |kind, text: &str| DiffLine {
+            kind,
+            text: text.to_string(),
+        };
+        for (removed, added) in &group {
+            lines.extend(
+                old[cursor..removed.start]
+                    .iter()
+                    .map(|text| line(LineKind::Context, text)),
+            );
+            lines.extend(
+                old[removed.clone()]
+                    .iter()
+                    .map(|text| line(LineKind::Removed, text)),
+            );
+            lines.extend(
+                new[added.clone()]
+                    .iter()
+                    .map(|text| line(LineKind::Added, text)),
+            );
+            cursor = removed.end;
+        }
+        lines.extend(
+            old[cursor..old_end]
+                .iter()
+                .map(|text| line(LineKind::Context, text)),
+        );
+
+        let start = |start: usize, end: usize| if start == end { start } else { start + 1 };
+        hunks.push(Hunk {
+            old_start: start(old_start, old_end),
+            old_lines: old_end - old_start,
+            new_start: start(new_start, new_end),
+            new_lines: new_end - new_start,
+            lines,
+        });
+    }
+    hunks
+}
+
+/// Lists the regions of base lines another version changed.
+///
+/// # Arguments
+///
+/// - `base` (`&[&str]`) - Lines of the base version.
+/// - `other` (`&[&str]`) - Lines of the other version.
+///
+/// # Returns
+///
+/// - `Vec<(Range<usize>, Range<usize>)>` - Replaced base lines and the lines replacing them.
+pub(crate) fn changes(base: &[&str], other: &[&str]) -> Vec<(Range<usize>, Range<usize>)> {
+    // Longest common subsequence of lines, table of suffix lengths
+    let mut table = vec![vec![0usize; other.len() + 1]; base.len() + 1];
+    for i in (0..base.len()).rev() {
+        for j in (0..other.len()).rev() {
+            table[i][j] = if base[i] == other[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut regions = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let (mut base_start, mut other_start) = (0, 0);
+    while i < base.len() || j < other.len() {
+        if i < base.len() && j < other.len() && base[i] == other[j] {
+            if base_start < i || other_start < j {
+                regions.push((base_start..i, other_start..j));
+            }
+            i += 1;
+            j += 1;
+            base_start = i;
+            other_start = j;
+        } else if j < other.len() && (i == base.len() || table[i][j + 1] >= table[i + 1][j]) {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    if base_start < i || other_start < j {
+        regions.push((base_start..i, other_start..j));
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = "- a\n- b\n- c\n- d\n- e\n- f\n- g\n- h\n- i\n- j";
+        let new = "- a\n- B\n- c\n- d\n- e\n- f\n- g\n- h\n- i\n- j\n- k";
+
+        let hunks = diff(old, new, 2);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].header(), "@@ -1,4 +1,4 @@");
+        assert_eq!(hunks[1].header(), "@@ -9,2 +9,3 @@");
+        assert_eq!(hunks[1].lines[2].kind, LineKind::Added);
+        assert!(diff(old, old, 3).is_empty());
+    }
+}
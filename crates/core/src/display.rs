@@ -0,0 +1,513 @@
+//! Terminal Display
+//!
+//! `flow show` and the TUI render fenced blocks by their language instead of
+//! printing their source:
+//!
+//! * `csv` and `tsv` - An aligned table, the first row as header.
+//! * `mermaid` - The edges of a flowchart as arrows (`Build ──▶ Test`),
+//!   other diagrams as a notice followed by their source.
+//! * `tasks` - A task list of `- [ ]` and `- [x]` lines as checkboxes,
+//!   toggled with `x` in the TUI.
+//!
+//! Blocks of other languages are rendered by a plugin registering a renderer
+//! of the same name (see [`plugin`](crate::plugin), `plugins` feature),
+//! otherwise they are shown as they are. Fences may be indented or start a
+//! bullet (`- ```csv`), their lines keep the indentation.
+//!
+//! Every rendered line remembers the line of the markdown it came from, so
+//! the TUI's cursor can select a task or insert below a block while showing
+//! the rendered page.
+
+use std::collections::BTreeMap;
+
+use crate::space::Space;
+
+/// Opening and closing marker of a fenced block.
+const FENCE: &str = "```";
+
+/// Arrows between the nodes of a mermaid flowchart.
+const ARROWS: &[&str] = &["-.->", "==>", "-->", "---"];
+
+/// What a rendered line shows, for styling it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// Markdown outside of rendered blocks.
+    Text,
+    /// A line of a fenced block shown as it is.
+    Code,
+    /// A row of a table, `header` for the first.
+    Table { header: bool },
+    /// An edge of a diagram, or output of a plugin.
+    Diagram,
+    /// Explains why a block isn't rendered.
+    Notice,
+    /// A task of a task list.
+    Task { done: bool },
+}
+
+/// A line of rendered output.
+///
+/// # Fields
+///
+/// - `text` (`String`) - The rendered text, including its indentation.
+/// - `source` (`usize`) - Index of the markdown line it was rendered from.
+/// - `kind` (`LineKind`) - What the line shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayLine {
+    pub text: String,
+    pub source: usize,
+    pub kind: LineKind,
+}
+
+impl DisplayLine {
+    fn new(text: impl Into<String>, source: usize, kind: LineKind) -> Self {
+        Self {
+            text: text.into(),
+            source,
+            kind,
+        }
+    }
+}
+
+/// A fenced block found in markdown.
+struct Fence<'a> {
+    /// Indentation of the block, a bullet counts as two spaces
+    indent: String,
+    language: &'a str,
+    /// Index of the opening line
+    start: usize,
+    /// Lines between the markers, without the indentation
+    body: Vec<&'a str>,
+}
+
+impl Space {
+    /// Renders page content for the terminal, using the renderers of plugins.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space whose plugins render unknown languages.
+    /// - `content` (`&str`) - Markdown content of a page.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<DisplayLine>` - The rendered lines.
+    pub fn display(&self, content: &str) -> Vec<DisplayLine> {
+        // Loading the plugins is only worth it for blocks they could render
+        let mut renderers = None;
+        render(content, &mut |language, source| {
+            self.render_block(&mut renderers, language, source)
+        })
+    }
+
+    /// Renders a block with the plugin registering a renderer for its language.
+    #[cfg(feature = "plugins")]
+    fn render_block(
+        &self,
+        renderers: &mut Option<Vec<String>>,
+        language: &str,
+        source: &str,
+    ) -> Option<Result<String, String>> {
+        let renderers = renderers.get_or_insert_with(|| {
+            self.plugins()
+                .map(|plugins| {
+                    plugins
+                        .into_iter()
+                        .flat_map(|plugin| plugin.renderers)
+                        .collect()
+                })
+                .unwrap_or_default()
+        });
+        if !renderers.iter().any(|renderer| renderer == language) {
+            return None;
+        }
+        Some(
+            self.render_with_plugin(language, source)
+                .map_err(|error| error.to_string()),
+        )
+    }
+
+    /// Without plugins no block has a renderer.
+    #[cfg(not(feature = "plugins"))]
+    fn render_block(
+        &self,
+        _renderers: &mut Option<Vec<String>>,
+        _language: &str,
+        _source: &str,
+    ) -> Option<Result<String, String>> {
+        None
+    }
+}
+
+/// Renders markdown for the terminal.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content.
+/// - `external` (`&mut dyn FnMut(&str, &str) -> Option<Result<String, String>>`) - Renders
+///   blocks of other languages given the language and source, `None` if it can't.
+///
+/// # Returns
+///
+/// - `Vec<DisplayLine>` - The rendered lines.
+pub fn render(
+    content: &str,
+    external: &mut dyn FnMut(&str, &str) -> Option<Result<String, String>>,
+) -> Vec<DisplayLine> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut output = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let Some((fence, end)) = find_fence(&lines, index) else {
+            output.push(DisplayLine::new(lines[index], index, LineKind::Text));
+            index += 1;
+            continue;
+        };
+
+        let rendered = match fence.language {
+            "csv" => Some(table(&fence, ',')),
+            "tsv" => Some(table(&fence, '\t')),
+            "mermaid" => Some(mermaid(&fence)),
+            "tasks" => Some(tasks(&fence)),
+            "" => None,
+            language => external(language, &fence.body.join("\n")).map(|result| match result {
+                Ok(text) => text
+                    .lines()
+                    .map(|line| {
+                        DisplayLine::new(
+                            format!("{}{}", fence.indent, line),
+                            fence.start,
+                            LineKind::Diagram,
+                        )
+                    })
+                    .collect(),
+                Err(error) => {
+                    let mut notice = vec![DisplayLine::new(
+                        format!("{}[{} renderer failed: {}]", fence.indent, language, error),
+                        fence.start,
+                        LineKind::Notice,
+                    )];
+                    notice.extend(code(&fence));
+                    notice
+                }
+            }),
+        };
+        match rendered {
+            Some(rendered) => output.extend(rendered),
+            None => output.extend(
+                (index..=end).map(|line| DisplayLine::new(lines[line], line, LineKind::Code)),
+            ),
+        }
+        index = end + 1;
+    }
+    output
+}
+
+/// Finds the fenced block opening at a line.
+///
+/// # Returns
+///
+/// - `Option<(Fence, usize)>` - The block and the index of its closing line, `None` if no
+///   block opens here or it is never closed.
+fn find_fence<'a>(lines: &[&'a str], start: usize) -> Option<(Fence<'a>, usize)> {
+    let line = lines[start];
+    let trimmed = line.trim_start();
+    let (opening, bullet) = match trimmed.strip_prefix("- ") {
+        Some(rest) => (rest, "  "),
+        None => (trimmed, ""),
+    };
+    let language = opening.strip_prefix(FENCE)?.split_whitespace().next();
+    let indent = format!("{}{}", &line[..line.len() - trimmed.len()], bullet);
+
+    let end = (start + 1..lines.len()).find(|&index| lines[index].trim() == FENCE)?;
+    let body = lines[start + 1..end]
+        .iter()
+        .map(|line| {
+            let spaces = line.len() - line.trim_start().len();
+            &line[spaces.min(indent.len())..]
+        })
+        .collect();
+    Some((
+        Fence {
+            indent,
+            language: language.unwrap_or_default(),
+            start,
+            body,
+        },
+        end,
+    ))
+}
+
+/// Shows the source of a block without its markers.
+fn code(fence: &Fence) -> Vec<DisplayLine> {
+    fence
+        .body
+        .iter()
+        .enumerate()
+        .map(|(offset, line)| {
+            DisplayLine::new(
+                format!("{}{}", fence.indent, line),
+                fence.start + 1 + offset,
+                LineKind::Code,
+            )
+        })
+        .collect()
+}
+
+/// Renders a `csv` or `tsv` block as an aligned table.
+fn table(fence: &Fence, separator: char) -> Vec<DisplayLine> {
+    let rows: Vec<(usize, Vec<String>)> = fence
+        .body
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(offset, line)| (fence.start + 1 + offset, split_row(line, separator)))
+        .collect();
+    let columns = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for (_, cells) in &rows {
+        for (column, cell) in cells.iter().enumerate() {
+            widths[column] = widths[column].max(cell.chars().count());
+        }
+    }
+
+    let mut output = Vec::new();
+    for (row, (source, cells)) in rows.iter().enumerate() {
+        let text = (0..columns)
+            .map(|column| {
+                let cell = cells.get(column).map(String::as_str).unwrap_or_default();
+                format!("{:<width$}", cell, width = widths[column])
+            })
+            .collect::<Vec<_>>()
+            .join(" │ ");
+        output.push(DisplayLine::new(
+            format!("{}{}", fence.indent, text.trim_end()),
+            *source,
+            LineKind::Table { header: row == 0 },
+        ));
+        if row == 0 {
+            let rule = widths
+                .iter()
+                .map(|width| "─".repeat(*width))
+                .collect::<Vec<_>>()
+                .join("─┼─");
+            output.push(DisplayLine::new(
+                format!("{}{}", fence.indent, rule),
+                *source,
+                LineKind::Table { header: true },
+            ));
+        }
+    }
+    output
+}
+
+/// Splits a row of a table into cells, unquoting `"quoted, cells"`.
+fn split_row(line: &str, separator: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+        .into_iter()
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+/// Renders the edges of a mermaid flowchart, other diagrams as their source.
+fn mermaid(fence: &Fence) -> Vec<DisplayLine> {
+    let kind = fence
+        .body
+        .iter()
+        .find_map(|line| line.split_whitespace().next())
+        .unwrap_or("diagram");
+    let notice = |text: String| DisplayLine::new(text, fence.start, LineKind::Notice);
+
+    let mut edges = Vec::new();
+    if matches!(kind, "graph" | "flowchart") {
+        let mut labels = BTreeMap::new();
+        for (offset, line) in fence.body.iter().enumerate() {
+            let mut nodes = Vec::new();
+            let mut arrows = Vec::new();
+            let mut rest = line.trim().trim_end_matches(';');
+            while let Some((position, arrow)) = ARROWS
+                .iter()
+                .filter_map(|arrow| rest.find(arrow).map(|position| (position, *arrow)))
+                .min_by_key(|(position, arrow)| (*position, std::cmp::Reverse(arrow.len())))
+            {
+                nodes.push(node(&rest[..position], &mut labels));
+                rest = &rest[position + arrow.len()..];
+                // An edge label, `-->|yes|`
+                let label = match rest.strip_prefix('|').and_then(|r| r.split_once('|')) {
+                    Some((label, after)) => {
+                        rest = after;
+                        label.trim()
+                    }
+                    None => "",
+                };
+                arrows.push(label);
+            }
+            if arrows.is_empty() {
+                continue;
+            }
+            nodes.push(node(rest, &mut labels));
+            for (index, label) in arrows.iter().enumerate() {
+                let arrow = if label.is_empty() {
+                    "──▶".to_string()
+                } else {
+                    format!("──{}──▶", label)
+                };
+                edges.push(DisplayLine::new(
+                    format!(
+                        "{}{} {} {}",
+                        fence.indent,
+                        nodes[index],
+                        arrow,
+                        nodes[index + 1]
+                    ),
+                    fence.start + 1 + offset,
+                    LineKind::Diagram,
+                ));
+            }
+        }
+    }
+
+    if edges.is_empty() {
+        let mut lines = vec![notice(format!(
+            "{}[mermaid {} can't be drawn in the terminal, its source:]",
+            fence.indent, kind
+        ))];
+        lines.extend(code(fence));
+        return lines;
+    }
+    let mut lines = vec![notice(format!(
+        "{}[mermaid {}, shown as its edges]",
+        fence.indent, kind
+    ))];
+    lines.extend(edges);
+    lines
+}
+
+/// Returns the label of a flowchart node, remembering labels of `id[Label]` definitions.
+fn node(text: &str, labels: &mut BTreeMap<String, String>) -> String {
+    let text = text.trim();
+    let Some(open) = text.find(['[', '(', '{', '>']) else {
+        return labels
+            .get(text)
+            .cloned()
+            .unwrap_or_else(|| text.to_string());
+    };
+    let id = text[..open].trim().to_string();
+    let label = text[open..]
+        .trim_matches(|c| matches!(c, '[' | ']' | '(' | ')' | '{' | '}' | '>' | '"'))
+        .to_string();
+    labels.insert(id, label.clone());
+    label
+}
+
+/// Renders a task list as checkboxes.
+fn tasks(fence: &Fence) -> Vec<DisplayLine> {
+    fence
+        .body
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(offset, line)| {
+            let trimmed = line.trim_start();
+            let nested = &line[..line.len() - trimmed.len()];
+            let item = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+            let (kind, text) = if let Some(text) = item.strip_prefix("[ ]") {
+                (LineKind::Task { done: false }, format!("☐{}", text))
+            } else if let Some(text) = item
+                .strip_prefix("[x]")
+                .or_else(|| item.strip_prefix("[X]"))
+            {
+                (LineKind::Task { done: true }, format!("☑{}", text))
+            } else {
+                (LineKind::Text, item.to_string())
+            };
+            DisplayLine::new(
+                format!("{}{}{}", fence.indent, nested, text),
+                fence.start + 1 + offset,
+                kind,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(content: &str) -> Vec<String> {
+        render(content, &mut |_, _| None)
+            .into_iter()
+            .map(|line| line.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_render_csv_as_table() {
+        let content = "- Budget\n  ```csv\n  item,cost\n  \"Rent, May\",900\n  ```\n- After";
+        assert_eq!(
+            texts(content),
+            vec![
+                "- Budget",
+                "  item      │ cost",
+                "  ──────────┼─────",
+                "  Rent, May │ 900",
+                "- After",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_mermaid_flowchart_edges() {
+        let content = "```mermaid\ngraph LR\n  A[Build] -->|ok| B[Test] --> C\n  B --> A\n```";
+        assert_eq!(
+            texts(content),
+            vec![
+                "[mermaid graph, shown as its edges]",
+                "Build ──ok──▶ Test",
+                "Test ──▶ C",
+                "Test ──▶ Build",
+            ]
+        );
+
+        let sequence = texts("```mermaid\nsequenceDiagram\n  A->>B: Hi\n```");
+        assert_eq!(
+            sequence[0],
+            "[mermaid sequenceDiagram can't be drawn in the terminal, its source:]"
+        );
+        assert_eq!(sequence.len(), 3);
+    }
+
+    #[test]
+    fn test_render_tasks_keeps_source_lines() {
+        let lines = render("```tasks\n- [ ] Pack\n- [x] Book\n```", &mut |_, _| None);
+        assert_eq!(lines[0].text, "☐ Pack");
+        assert_eq!(lines[0].source, 1);
+        assert_eq!(lines[1].kind, LineKind::Task { done: true });
+        assert_eq!(lines[1].source, 2);
+    }
+
+    #[test]
+    fn test_render_other_languages() {
+        let content = "```rust\nfn main() {}\n```\n```shout\nhi\n```";
+        let lines = render(content, &mut |language, source| {
+            (language == "shout").then(|| Ok(source.to_uppercase()))
+        });
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, vec!["```rust", "fn main() {}", "```", "HI"]);
+    }
+}
@@ -0,0 +1,261 @@
+//! Embeds
+//!
+//! A block consisting of just `![[Page]]` or `{{embed [[Page]]}}` embeds
+//! another page; `![[Page#Section]]` embeds only the first block of the page
+//! starting with that text (a bullet with its children, or a heading with
+//! the content below it). Embedding happens when a page is shown or exported:
+//! the embed becomes a link to the embedded page, followed by its content
+//! nested below it. Embeds written inline in a longer block are left as they
+//! are.
+//!
+//! Embedded pages can embed further pages. An embed of a page that is
+//! already being expanded is left unexpanded, which breaks cycles, and
+//! nesting stops after [`MAX_EMBED_DEPTH`] levels.
+
+use miette::Result;
+
+use crate::index::PageIndex;
+use crate::page;
+use crate::space::Space;
+
+/// Maximum nesting of embeds within embeds.
+pub const MAX_EMBED_DEPTH: usize = 8;
+
+/// A page with its embeds expanded.
+///
+/// # Fields
+///
+/// - `content` (`String`) - Content with embedded pages inlined.
+/// - `embedded` (`Vec<String>`) - Ids of all pages embedded, directly or nested.
+#[derive(Debug, Clone, Default)]
+pub struct Expanded {
+    pub content: String,
+    pub embedded: Vec<String>,
+}
+
+/// State of an expansion, shared across nesting levels.
+struct Expansion<'a> {
+    space: &'a Space,
+    index: PageIndex,
+    allow: &'a dyn Fn(&str, &str) -> bool,
+    stack: Vec<String>,
+    embedded: Vec<String>,
+}
+
+impl Space {
+    /// Expands the embeds of a page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space containing the page.
+    /// - `id` (`&str`) - Id of the page, never embedded into itself.
+    /// - `content` (`&str`) - Markdown content of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Expanded>` - The expanded content and the embedded pages.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the index or reading embedded pages.
+    pub fn expand_embeds(&self, id: &str, content: &str) -> Result<Expanded> {
+        self.expand_embeds_with(id, content, &|_, _| true)
+    }
+
+    /// Expands the embeds of a page, embedding only pages a filter allows.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space containing the page.
+    /// - `id` (`&str`) - Id of the page, never embedded into itself.
+    /// - `content` (`&str`) - Markdown content of the page.
+    /// - `allow` (`&dyn Fn(&str, &str) -> bool`) - Called with the id and content of each embedded page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Expanded>` - The expanded content and the embedded pages.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the index or reading embedded pages.
+    pub fn expand_embeds_with(
+        &self,
+        id: &str,
+        content: &str,
+        allow: &dyn Fn(&str, &str) -> bool,
+    ) -> Result<Expanded> {
+        let mut expansion = Expansion {
+            space: self,
+            index: self.page_index()?,
+            allow,
+            stack: vec![id.to_string()],
+            embedded: Vec::new(),
+        };
+        let content = expansion.expand(content)?;
+        Ok(Expanded {
+            content,
+            embedded: expansion.embedded,
+        })
+    }
+}
+
+impl Expansion<'_> {
+    /// Expands the embeds of one level of content.
+    fn expand(&mut self, content: &str) -> Result<String> {
+        let mut lines = Vec::new();
+        for line in content.lines() {
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+            let (bullet, body) = match trimmed.strip_prefix("- ") {
+                Some(body) => (true, body),
+                None => (false, trimmed),
+            };
+
+            let Some(target) = embed_target(body) else {
+                lines.push(line.to_string());
+                continue;
+            };
+            let from = page::name_from_id(self.stack.last().map_or("", String::as_str));
+            let target = &page::absolute_name(&from, target);
+            let Some(embedded) = self.embed(target)? else {
+                lines.push(line.to_string());
+                continue;
+            };
+
+            let nested = if bullet {
+                lines.push(format!("{}- [[{}]]", indent, target));
+                format!("{}  ", indent)
+            } else {
+                indent.to_string()
+            };
+            for embedded_line in embedded.lines().filter(|l| !l.trim().is_empty()) {
+                lines.push(format!("{}{}", nested, embedded_line));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Reads and expands the content an embed refers to, `None` if it stays unexpanded.
+    fn embed(&mut self, target: &str) -> Result<Option<String>> {
+        let (name, section) = match target.split_once('#') {
+            Some((name, section)) => (name.trim(), Some(section.trim())),
+            None => (target, None),
+        };
+        let id = self.index.resolve(name);
+        if self.stack.contains(&id) || self.stack.len() > MAX_EMBED_DEPTH {
+            return Ok(None);
+        }
+        let Some(content) = self.space.read_page(&id)? else {
+            return Ok(None);
+        };
+        if !(self.allow)(&id, &content) {
+            return Ok(None);
+        }
+        let Some(content) = (match section {
+            Some(section) => find_section(&content, section),
+            None => Some(without_properties(&content)),
+        }) else {
+            return Ok(None);
+        };
+
+        if !self.embedded.contains(&id) {
+            self.embedded.push(id.clone());
+        }
+        self.stack.push(id);
+        let expanded = self.expand(&content);
+        self.stack.pop();
+        expanded.map(Some)
+    }
+}
+
+/// Returns the target of a block that consists of a single embed.
+fn embed_target(body: &str) -> Option<&str> {
+    let body = body.trim();
+    let link = match body.strip_prefix('!') {
+        Some(link) => link,
+        None => body.strip_prefix("{{embed")?.strip_suffix("}}")?.trim(),
+    };
+    let inner = link.strip_prefix("[[")?.strip_suffix("]]")?;
+    if inner.contains("]]") || inner.contains("[[") {
+        return None;
+    }
+    let target = inner.split('|').next().unwrap_or(inner).trim();
+    (!target.is_empty()).then_some(target)
+}
+
+/// Returns a page's content without its leading page properties.
+fn without_properties(content: &str) -> String {
+    let properties = page::properties(content).len();
+    content
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .skip(properties)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Finds the first block starting with a text, with the lines belonging to it.
+///
+/// A bullet owns the lines indented deeper than itself, a heading the lines
+/// up to the next heading of the same or a higher level. The block is
+/// returned dedented to its own indentation.
+fn find_section(content: &str, section: &str) -> Option<String> {
+    let section = section.to_lowercase();
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.iter().position(|line| {
+        let text = line.trim_start();
+        let text = text.strip_prefix("- ").unwrap_or(text);
+        text.trim_start_matches('#')
+            .trim()
+            .to_lowercase()
+            .starts_with(&section)
+    })?;
+
+    let first = lines[start];
+    let indent = first.len() - first.trim_start().len();
+    let heading = first.trim_start().len() - first.trim_start().trim_start_matches('#').len();
+
+    let mut block = vec![&first[indent..]];
+    for line in &lines[start + 1..] {
+        let trimmed = line.trim_start();
+        let line_indent = line.len() - trimmed.len();
+        let owned = if heading > 0 && indent == 0 {
+            let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+            !(line_indent == 0
+                && level > 0
+                && level <= heading
+                && trimmed[level..].starts_with(' '))
+        } else {
+            trimmed.is_empty() || line_indent > indent
+        };
+        if !owned {
+            break;
+        }
+        block.push(line.get(indent..).unwrap_or(trimmed));
+    }
+    Some(block.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_section() {
+        let content = "- Intro\n- Agenda\n  - budget\n  - hiring\n- Notes";
+        assert_eq!(
+            find_section(content, "agenda").as_deref(),
+            Some("- Agenda\n  - budget\n  - hiring")
+        );
+
+        let content = "# Plan\n- one\n## Details\n- two\n# Later";
+        assert_eq!(
+            find_section(content, "Plan").as_deref(),
+            Some("# Plan\n- one\n## Details\n- two")
+        );
+        assert_eq!(find_section(content, "missing"), None);
+        assert_eq!(embed_target("{{embed [[Plan|p]]}}"), Some("Plan"));
+        assert_eq!(embed_target("![[Plan#Details]]"), Some("Plan#Details"));
+        assert_eq!(embed_target("see ![[Plan]]"), None);
+    }
+}
@@ -0,0 +1,562 @@
+//! Semantic Search
+//!
+//! Finds pages by meaning rather than by exact words. Every page is turned
+//! into a vector by an [`Embedder`]; pages whose vectors point in similar
+//! directions are about similar things.
+//!
+//! Vectors are persisted in `.flow/index/embeddings.json` together with the
+//! content hash of each page, so only changed pages are embedded again. The
+//! default [`HashingEmbedder`] runs fully offline without a model. With the
+//! `semantic-http` feature, an [`HttpEmbedder`] can query any OpenAI-compatible
+//! embeddings endpoint instead, such as a local Ollama server. Other backends
+//! (e.g. in-process models) plug in by implementing [`Embedder`].
+
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::cancel::CancellationToken;
+use crate::fulltext;
+use crate::index::{PageIndex, INDEX_DIR};
+use crate::page;
+use crate::progress::Progress;
+use crate::semantic::{SemanticBackend, SemanticSettings};
+use crate::space::{Space, FLOW_DIR};
+
+const EMBEDDING_INDEX_FILE: &str = "embeddings.json";
+
+/// Number of pages embedded per backend call.
+const BATCH_SIZE: usize = 32;
+
+/// Computes embedding vectors for texts.
+pub trait Embedder: Send + Sync {
+    /// Identifies the backend and model, vectors of different ids are not comparable.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Id of the embedder.
+    fn id(&self) -> String;
+
+    /// Embeds a batch of texts.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Embedder`) - Embedder to use.
+    /// - `texts` (`&[&str]`) - Texts to embed.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Vec<f32>>>` - One vector per text, in the same order.
+    ///
+    /// # Errors
+    ///
+    /// Backend specific errors, e.g. when an endpoint is unreachable.
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Offline embedder hashing words and word pairs into a fixed-size vector.
+///
+/// # Fields
+///
+/// - `dimensions` (`usize`) - Size of the produced vectors.
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dimensions: usize,
+}
+
+impl HashingEmbedder {
+    /// Creates a hashing embedder.
+    ///
+    /// # Arguments
+    ///
+    /// - `dimensions` (`usize`) - Size of the produced vectors, at least 1.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The embedder.
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+
+    /// Adds a feature to a vector, signed by a second hash bit to reduce collisions.
+    fn add(&self, vector: &mut [f32], feature: &str, weight: f32) {
+        let hash = feature_hash(feature);
+        let slot = (hash % self.dimensions as u64) as usize;
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[slot] += sign * weight;
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn id(&self) -> String {
+        format!("hashing-{}", self.dimensions)
+    }
+
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts
+            .iter()
+            .map(|text| {
+                let tokens = fulltext::tokenize(text);
+                let mut vector = vec![0.0; self.dimensions];
+                for token in &tokens {
+                    self.add(&mut vector, token, 1.0);
+                }
+                for pair in tokens.windows(2) {
+                    self.add(&mut vector, &format!("{} {}", pair[0], pair[1]), 0.5);
+                }
+                // Dampen frequent words so long pages aren't dominated by them
+                for value in &mut vector {
+                    *value = value.signum() * value.abs().ln_1p();
+                }
+                normalize(vector)
+            })
+            .collect())
+    }
+}
+
+/// Embedder querying an OpenAI-compatible `/embeddings` endpoint.
+///
+/// # Fields
+///
+/// - `endpoint` (`String`) - URL of the endpoint.
+/// - `model` (`String`) - Model to request.
+#[cfg(feature = "semantic-http")]
+#[derive(Debug, Clone)]
+pub struct HttpEmbedder {
+    endpoint: String,
+    model: String,
+}
+
+#[cfg(feature = "semantic-http")]
+impl HttpEmbedder {
+    /// Creates an HTTP embedder.
+    ///
+    /// # Arguments
+    ///
+    /// - `endpoint` (`&str`) - URL of the endpoint, e.g. `http://localhost:11434/v1/embeddings`.
+    /// - `model` (`&str`) - Model to request.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The embedder.
+    pub fn new(endpoint: &str, model: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "semantic-http")]
+impl Embedder for HttpEmbedder {
+    fn id(&self) -> String {
+        format!("http-{}", self.model)
+    }
+
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        #[derive(Deserialize)]
+        struct Item {
+            embedding: Vec<f32>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            data: Vec<Item>,
+        }
+
+        let response: Response = ureq::post(&self.endpoint)
+            .send_json(serde_json::json!({ "model": self.model, "input": texts }))
+            .into_diagnostic()?
+            .into_json()
+            .into_diagnostic()?;
+        if response.data.len() != texts.len() {
+            miette::bail!(
+                "Embeddings endpoint returned {} vectors for {} texts",
+                response.data.len(),
+                texts.len()
+            );
+        }
+
+        Ok(response
+            .data
+            .into_iter()
+            .map(|item| normalize(item.embedding))
+            .collect())
+    }
+}
+
+/// Creates the embedder configured by the given settings.
+///
+/// # Arguments
+///
+/// - `settings` (`&SemanticSettings`) - Semantic search settings of a space.
+///
+/// # Returns
+///
+/// - `Result<Box<dyn Embedder>>` - The configured embedder.
+///
+/// # Errors
+///
+/// The HTTP backend is configured without an endpoint, or Flow was built
+/// without the `semantic-http` feature.
+pub fn embedder(settings: &SemanticSettings) -> Result<Box<dyn Embedder>> {
+    match settings.backend {
+        SemanticBackend::Hashing => Ok(Box::new(HashingEmbedder::new(settings.dimensions))),
+        #[cfg(feature = "semantic-http")]
+        SemanticBackend::Http => {
+            let Some(endpoint) = &settings.endpoint else {
+                miette::bail!("The HTTP embedding backend requires an endpoint in space.toml");
+            };
+            let model = settings.model.as_deref().unwrap_or("nomic-embed-text");
+            Ok(Box::new(HttpEmbedder::new(endpoint, model)))
+        }
+        #[cfg(not(feature = "semantic-http"))]
+        SemanticBackend::Http => {
+            miette::bail!(
+                "The HTTP embedding backend requires Flow built with the 'semantic-http' feature"
+            )
+        }
+    }
+}
+
+/// Stored vector of a page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PageVector {
+    hash: u64,
+    vector: Vec<f32>,
+}
+
+/// A page similar to a query or another page.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `score` (`f64`) - Cosine similarity, from -1 to 1, higher is more similar.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SimilarPage {
+    pub id: String,
+    pub page: String,
+    pub score: f64,
+}
+
+/// Embedding vectors of all pages in a space, keyed by page id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingIndex {
+    #[serde(default)]
+    embedder: String,
+    #[serde(default)]
+    pages: BTreeMap<String, PageVector>,
+}
+
+impl EmbeddingIndex {
+    /// Loads the embedding index of a space, empty if missing or unreadable.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The embedding index.
+    pub fn load(space_path: &Path) -> Self {
+        let index_path = space_path
+            .join(FLOW_DIR)
+            .join(INDEX_DIR)
+            .join(EMBEDDING_INDEX_FILE);
+        fs::read_to_string(index_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persists the index of a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`EmbeddingIndex`) - Index to persist.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the index file.
+    pub fn save(&self, space_path: &Path) -> Result<()> {
+        let index_dir = space_path.join(FLOW_DIR).join(INDEX_DIR);
+        fs::create_dir_all(&index_dir).into_diagnostic()?;
+        let json = serde_json::to_string(self).into_diagnostic()?;
+        fs::write(index_dir.join(EMBEDDING_INDEX_FILE), json).into_diagnostic()
+    }
+
+    /// Embeds new and changed pages and drops deleted ones.
+    ///
+    /// Changes are detected through the content hashes of the page index. All
+    /// vectors are recomputed when the embedder changed.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`EmbeddingIndex`) - Index to refresh.
+    /// - `space_path` (`&Path`) - Path of the space.
+    /// - `embedder` (`&dyn Embedder`) - Embedder computing the vectors.
+    /// - `progress` (`&dyn Progress`) - Receives one step per embedded page.
+    /// - `cancel` (`&CancellationToken`) - Checked before each batch.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<usize>` - Number of pages embedded.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages, embedder errors,
+    /// [`Cancelled`](crate::cancel::Cancelled) if the token was cancelled.
+    pub fn refresh(
+        &mut self,
+        space_path: &Path,
+        embedder: &dyn Embedder,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<usize> {
+        let embedder_id = embedder.id();
+        if self.embedder != embedder_id {
+            self.embedder = embedder_id;
+            self.pages.clear();
+        }
+
+        let index = PageIndex::load(space_path)?;
+        let ids: HashSet<&str> = index.pages().map(|entry| entry.id.as_str()).collect();
+        self.pages.retain(|id, _| ids.contains(id.as_str()));
+        let changed: Vec<(String, u64)> = index
+            .pages()
+            .filter(|entry| {
+                self.pages
+                    .get(&entry.id)
+                    .is_none_or(|stored| stored.hash != entry.hash)
+            })
+            .map(|entry| (entry.id.clone(), entry.hash))
+            .collect();
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        progress.start("Embedding pages", Some(changed.len() as u64));
+        for batch in changed.chunks(BATCH_SIZE) {
+            if let Err(cancelled) = cancel.check() {
+                progress.finish();
+                return Err(cancelled.into());
+            }
+            let contents = batch
+                .iter()
+                .map(|(id, _)| fs::read_to_string(space_path.join(id)).into_diagnostic())
+                .collect::<Result<Vec<_>>>()?;
+            let texts: Vec<&str> = contents.iter().map(String::as_str).collect();
+            let vectors = embedder.embed(&texts)?;
+            for ((id, hash), vector) in batch.iter().zip(vectors) {
+                self.pages.insert(
+                    id.clone(),
+                    PageVector {
+                        hash: *hash,
+                        vector,
+                    },
+                );
+            }
+            progress.advance(batch.len() as u64);
+        }
+        progress.finish();
+
+        Ok(changed.len())
+    }
+
+    /// Returns the pages closest to a vector.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`EmbeddingIndex`) - Index to search.
+    /// - `vector` (`&[f32]`) - Normalized query vector.
+    /// - `limit` (`usize`) - Maximum number of pages, `0` for all.
+    /// - `exclude` (`Option<&str>`) - Id of a page to leave out, e.g. the query page itself.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<SimilarPage>` - Pages ordered by similarity, most similar first.
+    pub fn nearest(&self, vector: &[f32], limit: usize, exclude: Option<&str>) -> Vec<SimilarPage> {
+        let mut similar: Vec<SimilarPage> = self
+            .pages
+            .iter()
+            .filter(|(id, _)| Some(id.as_str()) != exclude)
+            .map(|(id, stored)| SimilarPage {
+                id: id.clone(),
+                page: page::name_from_id(id),
+                score: dot(vector, &stored.vector),
+            })
+            .collect();
+
+        similar.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+        if limit > 0 {
+            similar.truncate(limit);
+        }
+        similar
+    }
+
+    /// Returns the number of embedded pages.
+    ///
+    /// # Returns
+    ///
+    /// - `usize` - Number of pages.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Checks whether the index contains no pages.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+impl Space {
+    /// Returns the semantic search settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&SemanticSettings` - Reference to the space's semantic search settings.
+    pub fn semantic_settings(&self) -> &SemanticSettings {
+        &self.metadata.semantic
+    }
+
+    /// Finds the pages most similar to a page.
+    ///
+    /// Refreshes the embedding index first.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `name` (`&str`) - Name or alias of the page.
+    /// - `limit` (`usize`) - Maximum number of pages, `0` for all.
+    /// - `progress` (`&dyn Progress`) - Receives embedding progress.
+    /// - `cancel` (`&CancellationToken`) - Cancels embedding.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<Vec<SimilarPage>>>` - Similar pages, `None` if the page doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors, embedder errors or [`Cancelled`](crate::cancel::Cancelled).
+    pub fn similar(
+        &self,
+        name: &str,
+        limit: usize,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Option<Vec<SimilarPage>>> {
+        let id = self.page_index()?.resolve(name);
+        let index = self.refreshed_embeddings(progress, cancel)?;
+
+        Ok(index
+            .pages
+            .get(&id)
+            .map(|stored| index.nearest(&stored.vector, limit, Some(&id))))
+    }
+
+    /// Finds the pages most similar to a free-text query.
+    ///
+    /// Refreshes the embedding index first.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `query` (`&str`) - Text describing what to look for.
+    /// - `limit` (`usize`) - Maximum number of pages, `0` for all.
+    /// - `progress` (`&dyn Progress`) - Receives embedding progress.
+    /// - `cancel` (`&CancellationToken`) - Cancels embedding.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<SimilarPage>>` - Pages ordered by similarity.
+    ///
+    /// # Errors
+    ///
+    /// IO errors, embedder errors or [`Cancelled`](crate::cancel::Cancelled).
+    pub fn search_semantic(
+        &self,
+        query: &str,
+        limit: usize,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<SimilarPage>> {
+        let embedder = embedder(&self.metadata.semantic)?;
+        let index = self.refreshed_embeddings(progress, cancel)?;
+        let vector = embedder.embed(&[query])?.pop().unwrap_or_default();
+
+        Ok(index.nearest(&vector, limit, None))
+    }
+
+    /// Loads the embedding index and brings it up to date with the pages.
+    fn refreshed_embeddings(
+        &self,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<EmbeddingIndex> {
+        let embedder = embedder(&self.metadata.semantic)?;
+        let mut index = EmbeddingIndex::load(&self.path);
+        // A read-only space keeps refreshed embeddings in memory only
+        if index.refresh(&self.path, embedder.as_ref(), progress, cancel)? > 0 && !self.read_only {
+            index.save(&self.path)?;
+        }
+        Ok(index)
+    }
+}
+
+/// Scales a vector to unit length, leaving zero vectors untouched.
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let length = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if length > 0.0 {
+        for value in &mut vector {
+            *value /= length;
+        }
+    }
+    vector
+}
+
+/// Dot product of two vectors, the cosine similarity for normalized vectors.
+fn dot(a: &[f32], b: &[f32]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (*x as f64) * (*y as f64))
+        .sum()
+}
+
+/// Hashes a feature with FNV-1a, which is stable across Rust versions.
+fn feature_hash(feature: &str) -> u64 {
+    feature.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashing_embedder_ranks_related_text_higher() {
+        let embedder = HashingEmbedder::new(256);
+        let vectors = embedder
+            .embed(&[
+                "rust borrow checker and lifetimes",
+                "lifetimes and the rust borrow checker explained",
+                "grocery list: apples, bread, milk",
+            ])
+            .unwrap();
+
+        let related = dot(&vectors[0], &vectors[1]);
+        let unrelated = dot(&vectors[0], &vectors[2]);
+        assert!(related > unrelated);
+        assert!((dot(&vectors[0], &vectors[0]) - 1.0).abs() < 1e-5);
+    }
+}
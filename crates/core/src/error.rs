@@ -0,0 +1,134 @@
+//! Errors
+//!
+//! Typed errors for the failures callers want to tell apart, e.g. a directory
+//! that isn't a space from a space whose document is corrupt. Core functions
+//! return [`miette::Result`], with these errors as the root of the report:
+//! frontends find them with [`Error::of`] (or `Report::downcast`) and fall
+//! back to the report's message for everything else.
+
+use miette::Diagnostic;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors of the core.
+#[derive(Debug, Error, Diagnostic)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    #[error("IO error at '{}'", path.display())]
+    #[diagnostic(code(flow_core::io))]
+    Io {
+        /// The file or directory.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A file of the space can't be parsed.
+    #[error("Can't parse '{}': {message}", path.display())]
+    #[diagnostic(code(flow_core::parse))]
+    Parse {
+        /// The file.
+        path: PathBuf,
+        /// What is wrong with it.
+        message: String,
+    },
+
+    /// The CRDT document of a space can't be imported.
+    #[error("Document of '{}' is corrupt: {message}", path.display())]
+    #[diagnostic(
+        code(flow_core::crdt),
+        help("Run `flow recover` to restore it from a backup or the markdown files")
+    )]
+    Crdt {
+        /// The space.
+        path: PathBuf,
+        /// The import error.
+        message: String,
+    },
+
+    /// The directory has no space metadata.
+    #[error("No space metadata found in '{}'", path.display())]
+    #[diagnostic(code(flow_core::not_found::space))]
+    MetadataMissing {
+        /// The directory.
+        path: PathBuf,
+    },
+
+    /// A page doesn't exist.
+    #[error("Page '{name}' not found")]
+    #[diagnostic(code(flow_core::not_found::page))]
+    PageNotFound {
+        /// Name of the page.
+        name: String,
+    },
+
+    /// A page name would place the page outside of the space.
+    #[error("'{name}' is not a valid page name")]
+    #[diagnostic(
+        code(flow_core::invalid_name),
+        help("Page names can't start with '/' or contain '.' or '..' segments")
+    )]
+    InvalidPageName {
+        /// Name of the page.
+        name: String,
+    },
+
+    /// A page is locked (see [`Space::set_locked`](crate::space::Space::set_locked)).
+    #[error("Page '{name}' is locked, unlock it or use --force to change it")]
+    #[diagnostic(code(flow_core::locked))]
+    PageLocked {
+        /// Name of the page.
+        name: String,
+    },
+
+    /// The space uses a newer format than this version of Flow supports.
+    #[error("Space format {found} is newer than the supported format {supported}")]
+    #[diagnostic(
+        code(flow_core::version_mismatch),
+        help("Upgrade Flow to modify this space")
+    )]
+    VersionMismatch {
+        /// Format of the space.
+        found: u32,
+        /// Newest supported format.
+        supported: u32,
+    },
+}
+
+impl Error {
+    /// Creates an IO error for a path.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - The file or directory.
+    ///
+    /// # Returns
+    ///
+    /// - `impl FnOnce(std::io::Error) -> miette::Report` - Converter for `map_err`.
+    pub(crate) fn io(path: &Path) -> impl FnOnce(std::io::Error) -> miette::Report + '_ {
+        move |source| {
+            Error::Io {
+                path: path.to_path_buf(),
+                source,
+            }
+            .into()
+        }
+    }
+
+    /// Finds the typed error of a report.
+    ///
+    /// # Arguments
+    ///
+    /// - `report` (`&miette::Report`) - Report returned by a core function.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&Error>` - The error, `None` if the report wasn't caused by one.
+    pub fn of(report: &miette::Report) -> Option<&Error> {
+        report.downcast_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {}
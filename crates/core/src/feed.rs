@@ -0,0 +1,210 @@
+//! Atom Feeds
+//!
+//! Generates an Atom feed of the most recently modified journal days and
+//! featured pages, so a published space doubles as a simple blog or
+//! changelog. Pages are featured with a `publish:: true` property (or
+//! `publish: true` in a YAML frontmatter block); pages opted out of
+//! publishing never appear, even journal days.
+//!
+//! Feeds are written by `flow publish` when enabled in the space metadata:
+//!
+//! ```toml
+//! [publish.feed]
+//! enabled = true
+//! url = "https://notes.example.com"
+//! entries = 20
+//! ```
+
+use chrono::{DateTime, Utc};
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::page;
+use crate::publish;
+use crate::render::{self, escape, RenderPage};
+use crate::space::Space;
+
+/// Name of the feed file in a published site.
+pub const FEED_FILE: &str = "feed.xml";
+
+/// Number of entries in a feed unless configured otherwise.
+pub const DEFAULT_FEED_ENTRIES: usize = 20;
+
+/// Feed settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `enabled` (`bool`) - Write a feed when publishing.
+/// - `url` (`Option<String>`) - Base URL of the published site, for absolute links.
+/// - `entries` (`usize`) - Maximum number of entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default = "default_entries")]
+    pub entries: usize,
+}
+
+impl Default for FeedSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: None,
+            entries: DEFAULT_FEED_ENTRIES,
+        }
+    }
+}
+
+fn default_entries() -> usize {
+    DEFAULT_FEED_ENTRIES
+}
+
+impl Space {
+    /// Generates an Atom feed of the space.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to generate the feed for.
+    /// - `url` (`Option<&str>`) - Base URL of the published site, links are relative if `None`.
+    /// - `limit` (`usize`) - Maximum number of entries.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - The Atom document.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages.
+    pub fn feed(&self, url: Option<&str>, limit: usize) -> Result<String> {
+        let published = self.published_pages()?;
+        Ok(self.feed_from(&published.pages, url, limit))
+    }
+
+    /// Generates an Atom feed from already published pages.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the pages belong to.
+    /// - `pages` (`&[RenderPage]`) - All published pages with embeds expanded, links to them resolve.
+    /// - `url` (`Option<&str>`) - Base URL of the published site.
+    /// - `limit` (`usize`) - Maximum number of entries.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - The Atom document.
+    pub(crate) fn feed_from(
+        &self,
+        pages: &[RenderPage],
+        url: Option<&str>,
+        limit: usize,
+    ) -> String {
+        let base = url.map(|url| url.trim_end_matches('/'));
+        let href = |name: &str| match base {
+            Some(base) => format!("{}/{}.html", base, render::slug(name)),
+            None => format!("{}.html", render::slug(name)),
+        };
+        let links = render::link_targets(pages, href);
+
+        let mut entries: Vec<(&RenderPage, i64, i64)> = pages
+            .iter()
+            .filter(|page| page::is_journal(&page.id) || is_featured(&page.content))
+            .map(|page| {
+                let times = self.page_times(&page.id);
+                (page, times.created, times.modified)
+            })
+            .collect();
+        entries.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| b.0.id.cmp(&a.0.id)));
+        entries.truncate(limit);
+
+        let feed_id = match base {
+            Some(base) => format!("{}/", base),
+            None => format!("urn:flow:{}", render::slug(self.name())),
+        };
+        let updated = entries
+            .iter()
+            .map(|(_, _, modified)| *modified)
+            .max()
+            .unwrap_or(0);
+
+        let mut atom = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        atom.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        let _ = writeln!(atom, "<title>{}</title>", escape(self.name()));
+        let _ = writeln!(atom, "<id>{}</id>", escape(&feed_id));
+        let _ = writeln!(atom, "<updated>{}</updated>", rfc3339(updated));
+        let _ = writeln!(
+            atom,
+            "<author><name>{}</name></author>",
+            escape(&self.author().name)
+        );
+        if let Some(base) = base {
+            let _ = writeln!(atom, "<link href=\"{}/\"/>", escape(base));
+            let _ = writeln!(
+                atom,
+                "<link rel=\"self\" href=\"{}/{}\"/>",
+                escape(base),
+                FEED_FILE
+            );
+        }
+
+        for (page, created, modified) in entries {
+            let name = page::name_from_id(&page.id);
+            let link = href(&name);
+            let id = match base {
+                Some(_) => link.clone(),
+                None => format!("{}:{}", feed_id, render::slug(&name)),
+            };
+            atom.push_str("<entry>\n");
+            let _ = writeln!(atom, "<title>{}</title>", escape(&name));
+            let _ = writeln!(atom, "<id>{}</id>", escape(&id));
+            let _ = writeln!(atom, "<link href=\"{}\"/>", escape(&link));
+            let _ = writeln!(atom, "<published>{}</published>", rfc3339(created));
+            let _ = writeln!(atom, "<updated>{}</updated>", rfc3339(modified));
+            let _ = writeln!(
+                atom,
+                "<content type=\"html\">{}</content>",
+                escape(&render::to_fragment(&page.content, &links))
+            );
+            atom.push_str("</entry>\n");
+        }
+        atom.push_str("</feed>\n");
+        atom
+    }
+}
+
+/// Checks whether a page is featured in feeds with `publish: true`.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `bool` - True if the page opted into publishing explicitly.
+pub fn is_featured(content: &str) -> bool {
+    publish::publish_flag(content)
+        .is_some_and(|flag| matches!(flag.as_str(), "true" | "yes" | "on"))
+}
+
+/// Formats a unix timestamp as RFC 3339 date-time in UTC.
+fn rfc3339(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_featured() {
+        assert!(is_featured("publish:: true\n\n- Release notes"));
+        assert!(is_featured("---\npublish: yes\n---\n- Release notes"));
+        assert!(!is_featured("- Just a page"));
+        assert!(!is_featured("publish:: false"));
+    }
+}
@@ -0,0 +1,244 @@
+//! Page Formatting
+//!
+//! Normalizes the markdown of pages, the way `flow fmt` applies it:
+//!
+//! - Bullets use `-` and are indented by two spaces per nesting level,
+//!   continuation lines by one level more than their bullet.
+//! - Headings have a single space after the `#`s and a blank line before
+//!   them, unless they are nested in a bullet.
+//! - Wikilinks have no padding around their target and label
+//!   (`[[ Page | label ]]` becomes `[[Page|label]]`).
+//! - Trailing whitespace, leading and trailing blank lines, and runs of blank
+//!   lines are removed.
+//!
+//! Fenced code blocks are left untouched. Formatting is idempotent, and
+//! changes are written through the document like any other edit.
+
+use loro::UpdateOptions;
+use miette::{IntoDiagnostic, Result};
+
+use crate::error::Error;
+use crate::lock;
+use crate::space::Space;
+
+/// Width of one level of indentation.
+const INDENT: &str = "  ";
+
+impl Space {
+    /// Formats a page, or all pages of the space.
+    ///
+    /// Locked pages are skipped when formatting all pages.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to format.
+    /// - `page` (`Option<&str>`) - Name or alias of the page to format, all pages if `None`.
+    /// - `check` (`bool`) - Only report the pages that are not formatted, without changing them.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<String>>` - Ids of the pages that were (or would be) changed.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading or saving pages, or if the page doesn't exist or is locked.
+    pub fn format_pages(&mut self, page: Option<&str>, check: bool) -> Result<Vec<String>> {
+        let ids = match page {
+            Some(name) => {
+                let id = self.page_index()?.resolve(name);
+                if self.read_page(&id)?.is_none() {
+                    return Err(Error::PageNotFound {
+                        name: name.to_string(),
+                    }
+                    .into());
+                }
+                if !check {
+                    self.ensure_writable(&id)?;
+                }
+                vec![id]
+            }
+            None => self.page_ids()?,
+        };
+
+        let mut changed = Vec::new();
+        for id in ids {
+            let Some(content) = self.read_page(&id)? else {
+                continue;
+            };
+            let formatted = format(&content);
+            if formatted == content || (!self.allow_locked && lock::is_locked(&content)) {
+                continue;
+            }
+            if !check {
+                let text = self.document.get_text(id.clone());
+                text.update(&formatted, UpdateOptions::default())
+                    .into_diagnostic()?;
+                self.dirty.insert(id.clone());
+            }
+            changed.push(id);
+        }
+
+        if !check && !changed.is_empty() {
+            self.save()?;
+        }
+        Ok(changed)
+    }
+}
+
+/// Formats the markdown content of a page.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `String` - The formatted content.
+pub fn format(content: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    // Original indentation of the open bullets, innermost last
+    let mut bullets: Vec<usize> = Vec::new();
+    let mut code = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if code || trimmed.starts_with("```") {
+            if trimmed.starts_with("```") {
+                code = !code;
+            }
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+            continue;
+        }
+
+        let indent = indent_width(line);
+        if let Some(item) = bullet(trimmed) {
+            while bullets.last().is_some_and(|open| *open > indent) {
+                bullets.pop();
+            }
+            if bullets.last() != Some(&indent) {
+                bullets.push(indent);
+            }
+            let item = inline(&heading(item).unwrap_or_else(|| item.to_string()));
+            let prefix = INDENT.repeat(bullets.len() - 1);
+            lines.push(format!("{}- {}", prefix, item).trim_end().to_string());
+            continue;
+        }
+
+        if indent > 0 && !bullets.is_empty() {
+            // Continuation of the innermost bullet
+            let prefix = INDENT.repeat(bullets.len());
+            lines.push(format!("{}{}", prefix, inline(trimmed)));
+            continue;
+        }
+        if indent > 0 {
+            // Indented text outside of a list keeps its meaning
+            lines.push(line.trim_end().to_string());
+            continue;
+        }
+        bullets.clear();
+
+        match heading(trimmed) {
+            Some(text) => {
+                if lines.last().is_some_and(|last| !last.is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(inline(&text));
+            }
+            None => lines.push(inline(trimmed)),
+        }
+    }
+
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Returns the width of a line's indentation, counting tabs as one level.
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { INDENT.len() } else { 1 })
+        .sum()
+}
+
+/// Returns the text of a bullet written with `-`, `*` or `+`.
+fn bullet(line: &str) -> Option<&str> {
+    if matches!(line, "-" | "*" | "+") {
+        return Some("");
+    }
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .map(str::trim_start)
+}
+
+/// Normalizes the spacing after the `#`s of a heading, `None` if the text is no heading.
+fn heading(text: &str) -> Option<String> {
+    let level = text.len() - text.trim_start_matches('#').len();
+    let rest = &text[level..];
+    // `#tag` has no space and is not a heading
+    ((1..=6).contains(&level) && rest.starts_with([' ', '\t']) && !rest.trim().is_empty())
+        .then(|| format!("{} {}", &text[..level], rest.trim()))
+}
+
+/// Removes the padding inside wikilinks, skipping inline code.
+fn inline(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['`', '[']) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with('`') {
+            let end = rest[1..].find('`').map_or(rest.len(), |end| end + 2);
+            result.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        let link = rest
+            .strip_prefix("[[")
+            .and_then(|inner| inner.find("]]").map(|end| &inner[..end]))
+            .filter(|inner| !inner.contains("[["));
+        match link {
+            Some(inner) => {
+                let parts: Vec<&str> = inner.split('|').map(str::trim).collect();
+                result.push_str("[[");
+                result.push_str(&parts.join("|"));
+                result.push_str("]]");
+                rest = &rest[inner.len() + 4..];
+            }
+            None => {
+                result.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let content = "\ntitle:: Plan   \n#  Goals\n* Ship [[ Flow | flow ]]\n\t+ docs\n        - nested\n      more\n\n\n##Not a heading\n```\n  *  raw\n```\n\n";
+        let formatted = format(content);
+        assert_eq!(
+            formatted,
+            "title:: Plan\n\n# Goals\n- Ship [[Flow|flow]]\n  - docs\n    - nested\n      more\n\n##Not a heading\n```\n  *  raw\n```"
+        );
+        assert_eq!(format(&formatted), formatted);
+        assert_eq!(inline("`[[ a ]]` and [[ b#H ]]"), "`[[ a ]]` and [[b#H]]");
+    }
+}
@@ -0,0 +1,351 @@
+//! Space Integrity Checking
+//!
+//! This module cross-checks the Loro document of a space against the markdown
+//! files on disk. Markdown files are the source of truth, the document is the
+//! persistence and sync layer; both are expected to hold the same content.
+//!
+//! The check works directly on the files in `.flow/` instead of going through
+//! [`Space::load`](crate::space::Space::load), so it can also inspect spaces
+//! whose snapshot no longer imports.
+
+use loro::{ExportMode, LoroDoc};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::backup::{self, Backup, BackupSettings};
+use crate::cancel::CancellationToken;
+use crate::progress::Progress;
+use crate::space::{
+    container_names, markdown_files, read_document, read_updates, write_atomic, write_snapshot,
+    DOCUMENT_FILE, FLOW_DIR, METADATA_FILE,
+};
+use crate::storage::FsStorage;
+use crate::timestamps::{self, PageTimes};
+
+/// A single integrity problem found in a space.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// The space metadata file is missing or unreadable.
+    MissingMetadata { message: String },
+    /// The document snapshot is missing.
+    MissingSnapshot,
+    /// The document snapshot could not be imported.
+    CorruptSnapshot { message: String },
+    /// The document and the markdown file hold different content.
+    Diverged { id: String },
+    /// The document tracks a page whose markdown file no longer exists.
+    MissingFile { id: String },
+    /// A markdown file exists on disk but is not tracked by the document.
+    UntrackedFile { id: String },
+    /// A container in the document that does not map to a markdown file.
+    OrphanedContainer { id: String },
+}
+
+impl Issue {
+    /// Returns a short human readable description of the issue.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Description of the issue.
+    pub fn describe(&self) -> String {
+        match self {
+            Issue::MissingMetadata { message } => format!("metadata unreadable: {}", message),
+            Issue::MissingSnapshot => "document snapshot missing".to_string(),
+            Issue::CorruptSnapshot { message } => format!("document snapshot corrupt: {}", message),
+            Issue::Diverged { id } => format!("{} differs from the document", id),
+            Issue::MissingFile { id } => format!("{} is tracked but missing on disk", id),
+            Issue::UntrackedFile { id } => format!("{} is not tracked by the document", id),
+            Issue::OrphanedContainer { id } => format!("{} is not a markdown page", id),
+        }
+    }
+}
+
+/// Result of an integrity check.
+///
+/// # Fields
+///
+/// - `checked` (`usize`) - Number of pages that were compared.
+/// - `issues` (`Vec<Issue>`) - All problems that were found.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    pub checked: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    /// Returns whether the space passed the check.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if no issues were found, false otherwise.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks the integrity of the space at the given path.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space to check.
+///
+/// # Returns
+///
+/// - `Result<Report>` - Report of all issues found.
+///
+/// # Errors
+///
+/// IO errors when the markdown files can not be read.
+pub fn check(path: &Path) -> Result<Report> {
+    let mut report = Report::default();
+    let flow_dir = path.join(FLOW_DIR);
+
+    if let Err(err) = fs::read_to_string(flow_dir.join(METADATA_FILE)) {
+        report.issues.push(Issue::MissingMetadata {
+            message: err.to_string(),
+        });
+    }
+
+    let doc = LoroDoc::new();
+    let doc_path = flow_dir.join(DOCUMENT_FILE);
+    match fs::read(&doc_path) {
+        Ok(bytes) => {
+            if let Err(err) = doc.import(&bytes) {
+                report.issues.push(Issue::CorruptSnapshot {
+                    message: err.to_string(),
+                });
+                return Ok(report);
+            }
+        }
+        Err(_) => {
+            report.issues.push(Issue::MissingSnapshot);
+            return Ok(report);
+        }
+    }
+
+    let updates = read_updates(&FsStorage, path)?;
+    for update in &updates {
+        if let Err(err) = doc.import(update) {
+            report.issues.push(Issue::CorruptSnapshot {
+                message: err.to_string(),
+            });
+            return Ok(report);
+        }
+    }
+
+    let files = markdown_files(&FsStorage, path)?;
+    let containers = container_names(&doc);
+
+    for id in &containers {
+        if !id.ends_with(".md") {
+            report
+                .issues
+                .push(Issue::OrphanedContainer { id: id.clone() });
+            continue;
+        }
+
+        let file_path = path.join(id);
+        if !file_path.exists() {
+            report.issues.push(Issue::MissingFile { id: id.clone() });
+            continue;
+        }
+
+        report.checked += 1;
+        let on_disk = fs::read_to_string(&file_path).into_diagnostic()?;
+        if on_disk != doc.get_text(id.as_str()).to_string() {
+            report.issues.push(Issue::Diverged { id: id.clone() });
+        }
+    }
+
+    for id in files {
+        if !containers.contains(&id) {
+            report.issues.push(Issue::UntrackedFile { id });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Result of rebuilding the document of a space.
+///
+/// # Fields
+///
+/// - `pages` (`usize`) - Number of pages written to the new document.
+/// - `backup` (`Option<Backup>`) - Backup of the replaced document, if one was written.
+#[derive(Debug, Clone, Serialize)]
+pub struct Rebuilt {
+    pub pages: usize,
+    pub backup: Option<Backup>,
+}
+
+/// Rebuilds the document of a space from its markdown files.
+///
+/// The existing snapshot is replaced by a fresh document containing one text
+/// container per markdown file. CRDT history is lost in the process, so the
+/// replaced document is backed up first (see [`backup`](crate::backup)). A
+/// document that no longer imports isn't backed up, recovery (see
+/// [`recovery`](crate::recovery)) preserves it instead.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space to rebuild.
+/// - `progress` (`&dyn Progress`) - Receives one step per imported file.
+/// - `cancel` (`&CancellationToken`) - Checked before each file, the snapshot
+///   is only replaced once all files were imported.
+///
+/// # Returns
+///
+/// - `Result<Rebuilt>` - Number of pages written and the backup of the replaced document.
+///
+/// # Errors
+///
+/// IO errors when reading markdown files, writing the backup or the snapshot,
+/// [`Cancelled`](crate::cancel::Cancelled) if the token was cancelled.
+pub fn rebuild_doc(
+    path: &Path,
+    progress: &dyn Progress,
+    cancel: &CancellationToken,
+) -> Result<Rebuilt> {
+    let files = markdown_files(&FsStorage, path)?;
+
+    let doc = LoroDoc::new();
+    progress.start("Importing pages", Some(files.len() as u64));
+    for id in &files {
+        if let Err(cancelled) = cancel.check() {
+            progress.finish();
+            return Err(cancelled.into());
+        }
+        let file_path = path.join(id);
+        let content = fs::read_to_string(&file_path).into_diagnostic()?;
+        doc.get_text(id.as_str())
+            .insert(0, &content)
+            .into_diagnostic()?;
+        // The previous times are lost with the document, the file times are the best guess
+        let times = PageTimes::from_file(&file_path);
+        timestamps::touch(&doc, id, times.created, times.modified)?;
+        progress.advance(1);
+    }
+    progress.finish();
+    doc.commit();
+
+    let backup = if read_document(&FsStorage, path, &LoroDoc::new()).is_ok() {
+        backup::create(path, &BackupSettings::for_space(path))?
+    } else {
+        None
+    };
+    let snapshot = doc.export(ExportMode::Snapshot).into_diagnostic()?;
+    write_snapshot(&FsStorage, path, &snapshot)?;
+
+    Ok(Rebuilt {
+        pages: files.len(),
+        backup,
+    })
+}
+
+/// Rewrites the markdown files of a space from its document.
+///
+/// Every markdown container in the document is written to its file, creating
+/// missing directories along the way. Files not tracked by the document are
+/// left untouched.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space to rebuild.
+/// - `progress` (`&dyn Progress`) - Receives one step per written file.
+/// - `cancel` (`&CancellationToken`) - Checked before each file, files are
+///   never left half-written.
+///
+/// # Returns
+///
+/// - `Result<usize>` - Number of files written.
+///
+/// # Errors
+///
+/// Fails if the snapshot can not be imported or files can not be written,
+/// [`Cancelled`](crate::cancel::Cancelled) if the token was cancelled.
+pub fn rebuild_files(
+    path: &Path,
+    progress: &dyn Progress,
+    cancel: &CancellationToken,
+) -> Result<usize> {
+    let doc = LoroDoc::new();
+    read_document(&FsStorage, path, &doc)?;
+
+    let ids: Vec<String> = container_names(&doc)
+        .into_iter()
+        .filter(|id| id.ends_with(".md"))
+        .collect();
+
+    let mut written = 0;
+    progress.start("Writing pages", Some(ids.len() as u64));
+    for id in ids {
+        if let Err(cancelled) = cancel.check() {
+            progress.finish();
+            return Err(cancelled.into());
+        }
+        let file_path = path.join(&id);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).into_diagnostic()?;
+        }
+        write_atomic(&file_path, doc.get_text(id.as_str()).to_string().as_bytes())?;
+        written += 1;
+        progress.advance(1);
+    }
+    progress.finish();
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::space::Space;
+    use std::path::PathBuf;
+
+    fn scratch_space(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("flow-fsck-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Space::init(&dir, None, true).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_rebuild_files_repairs_divergence() {
+        let path = scratch_space("files");
+        let mut space = Space::load(&path).unwrap();
+        space.write_page("notes.md", "- Original").unwrap();
+        assert!(check(&path).unwrap().is_clean());
+
+        fs::write(path.join("notes.md"), "- Edited").unwrap();
+        assert_eq!(
+            check(&path).unwrap().issues,
+            vec![Issue::Diverged {
+                id: "notes.md".to_string()
+            }]
+        );
+
+        let written = rebuild_files(&path, &NoProgress, &CancellationToken::new()).unwrap();
+        assert_eq!(written, 1);
+        assert!(check(&path).unwrap().is_clean());
+        assert_eq!(
+            fs::read_to_string(path.join("notes.md")).unwrap(),
+            "- Original"
+        );
+    }
+
+    #[test]
+    fn test_rebuild_doc_backs_up_document() {
+        let path = scratch_space("doc");
+        fs::write(path.join("notes.md"), "- Untracked").unwrap();
+
+        let rebuilt = rebuild_doc(&path, &NoProgress, &CancellationToken::new()).unwrap();
+        assert_eq!(rebuilt.pages, 1);
+        assert!(rebuilt.backup.is_some());
+        assert!(check(&path).unwrap().is_clean());
+    }
+}
@@ -0,0 +1,563 @@
+//! Ranked Full-Text Search
+//!
+//! An inverted index over all pages of a space, persisted as JSON in
+//! `.flow/index/text.json` and updated incrementally whenever pages are saved.
+//!
+//! Queries consist of whitespace separated clauses which must all match:
+//!
+//! - `word` matches pages containing the word.
+//! - `wor*` matches pages containing a word starting with `wor`.
+//! - `"two words"` matches pages containing the words in sequence.
+//!
+//! Matches are ranked by a BM25-style score, boosted by the field they occur
+//! in: page titles weigh more than headings, which weigh more than body text.
+
+use loro::LoroDoc;
+use miette::{IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::cancel::CancellationToken;
+use crate::index::INDEX_DIR;
+use crate::page;
+use crate::progress::{NoProgress, Progress};
+use crate::space::{container_names, markdown_files, FLOW_DIR};
+use crate::storage::{FsStorage, Storage};
+
+const TEXT_INDEX_FILE: &str = "text.json";
+
+/// Term frequency saturation of the BM25 score.
+const K1: f64 = 1.2;
+
+/// Field of a page a term occurs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Title,
+    Heading,
+    Body,
+}
+
+impl Field {
+    /// Weight of matches in this field.
+    fn boost(self) -> f64 {
+        match self {
+            Field::Title => 3.0,
+            Field::Heading => 2.0,
+            Field::Body => 1.0,
+        }
+    }
+}
+
+/// Occurrences of a term in one field of a page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posting {
+    doc: String,
+    field: Field,
+    positions: Vec<u32>,
+}
+
+/// Terms of an indexed page, used to remove it again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DocInfo {
+    terms: Vec<String>,
+}
+
+/// A page matching a query.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `score` (`f64`) - Relevance of the page, higher is better.
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedMatch {
+    pub id: String,
+    pub score: f64,
+}
+
+/// A single clause of a query.
+#[derive(Debug, Clone, PartialEq)]
+enum Clause {
+    Term(String),
+    Prefix(String),
+    Phrase(Vec<String>),
+}
+
+/// Persisted inverted index of a space.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TextIndex {
+    #[serde(default)]
+    terms: BTreeMap<String, Vec<Posting>>,
+    #[serde(default)]
+    docs: BTreeMap<String, DocInfo>,
+}
+
+impl TextIndex {
+    /// Loads the full-text index of a space.
+    ///
+    /// Rebuilds (and persists) the index if it is missing or unreadable.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The full-text index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index has to be rebuilt and files can't be read or written.
+    pub fn load(space_path: &Path) -> Result<Self> {
+        let index_path = space_path
+            .join(FLOW_DIR)
+            .join(INDEX_DIR)
+            .join(TEXT_INDEX_FILE);
+        if let Ok(json) = fs::read_to_string(&index_path) {
+            if let Ok(index) = serde_json::from_str(&json) {
+                return Ok(index);
+            }
+        }
+
+        let index = Self::rebuild(space_path, &NoProgress, &CancellationToken::new())?;
+        index.save(space_path)?;
+        Ok(index)
+    }
+
+    /// Reads the persisted full-text index of a space without ever rebuilding it.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<Self>` - The full-text index, `None` if it is missing or unreadable.
+    pub fn cached(space_path: &Path) -> Option<Self> {
+        let index_path = space_path
+            .join(FLOW_DIR)
+            .join(INDEX_DIR)
+            .join(TEXT_INDEX_FILE);
+        let json = fs::read_to_string(index_path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Loads the index of a space from a storage.
+    ///
+    /// Rebuilds (and persists) the index from the document if it is missing
+    /// or unreadable. Used for storage without markdown files on disk.
+    ///
+    /// # Arguments
+    ///
+    /// - `storage` (`&dyn Storage`) - Storage of the space.
+    /// - `space_path` (`&Path`) - Path of the space.
+    /// - `doc` (`&LoroDoc`) - Document of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The full-text index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index can't be read or written.
+    pub fn load_from(storage: &dyn Storage, space_path: &Path, doc: &LoroDoc) -> Result<Self> {
+        let index_path = space_path
+            .join(FLOW_DIR)
+            .join(INDEX_DIR)
+            .join(TEXT_INDEX_FILE);
+        if let Some(json) = storage.read_to_string(&index_path)? {
+            if let Ok(index) = serde_json::from_str(&json) {
+                return Ok(index);
+            }
+        }
+
+        let mut index = Self::default();
+        for id in container_names(doc)
+            .into_iter()
+            .filter(|id| id.ends_with(".md"))
+        {
+            index.update(&id, &doc.get_text(id.as_str()).to_string());
+        }
+        index.save_to(storage, space_path)?;
+        Ok(index)
+    }
+
+    /// Builds the index from the markdown files of a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    /// - `progress` (`&dyn Progress`) - Receives one step per indexed file.
+    /// - `cancel` (`&CancellationToken`) - Checked before each file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The rebuilt index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the markdown files,
+    /// [`Cancelled`](crate::cancel::Cancelled) if the token was cancelled.
+    pub fn rebuild(
+        space_path: &Path,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Self> {
+        let ids = markdown_files(&FsStorage, space_path)?;
+        let mut index = Self::default();
+
+        progress.start("Indexing text", Some(ids.len() as u64));
+        for id in &ids {
+            if let Err(cancelled) = cancel.check() {
+                progress.finish();
+                return Err(cancelled.into());
+            }
+            let content = fs::read_to_string(space_path.join(id)).into_diagnostic()?;
+            index.update(id, &content);
+            progress.advance(1);
+        }
+        progress.finish();
+
+        Ok(index)
+    }
+
+    /// Persists the index of a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`TextIndex`) - Index to persist.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the index file.
+    pub fn save(&self, space_path: &Path) -> Result<()> {
+        self.save_to(&FsStorage, space_path)
+    }
+
+    /// Persists the index of a space to a storage.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`TextIndex`) - Index to persist.
+    /// - `storage` (`&dyn Storage`) - Storage of the space.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the index file.
+    pub fn save_to(&self, storage: &dyn Storage, space_path: &Path) -> Result<()> {
+        let index_path = space_path
+            .join(FLOW_DIR)
+            .join(INDEX_DIR)
+            .join(TEXT_INDEX_FILE);
+        let json = serde_json::to_string(self).into_diagnostic()?;
+        storage.write(&index_path, json.as_bytes())
+    }
+
+    /// Indexes (or re-indexes) a single page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`TextIndex`) - Index to update.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `content` (`&str`) - Current markdown content of the page.
+    pub fn update(&mut self, id: &str, content: &str) {
+        self.remove(id);
+
+        let mut occurrences: HashMap<(String, Field), Vec<u32>> = HashMap::new();
+        let mut record = |field: Field, text: &str, offset: &mut u32| {
+            for token in tokenize(text) {
+                occurrences.entry((token, field)).or_default().push(*offset);
+                *offset += 1;
+            }
+            // Keep phrases from spanning lines
+            *offset += 1;
+        };
+
+        let mut title_offset = 0;
+        record(Field::Title, &page::name_from_id(id), &mut title_offset);
+
+        let mut heading_offset = 0;
+        let mut body_offset = 0;
+        for line in content.lines() {
+            let block = line.trim_start().trim_start_matches("- ");
+            if block.starts_with('#') && block.trim_start_matches('#').starts_with(' ') {
+                record(Field::Heading, block, &mut heading_offset);
+            } else {
+                record(Field::Body, line, &mut body_offset);
+            }
+        }
+
+        let mut doc_terms: Vec<String> = Vec::new();
+        for ((term, field), positions) in occurrences {
+            if !doc_terms.contains(&term) {
+                doc_terms.push(term.clone());
+            }
+            self.terms.entry(term).or_default().push(Posting {
+                doc: id.to_string(),
+                field,
+                positions,
+            });
+        }
+        self.docs
+            .insert(id.to_string(), DocInfo { terms: doc_terms });
+    }
+
+    /// Removes a page from the index.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`TextIndex`) - Index to update.
+    /// - `id` (`&str`) - Id of the page.
+    pub fn remove(&mut self, id: &str) {
+        let Some(info) = self.docs.remove(id) else {
+            return;
+        };
+        for term in info.terms {
+            if let Some(postings) = self.terms.get_mut(&term) {
+                postings.retain(|posting| posting.doc != id);
+                if postings.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Searches the index.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`TextIndex`) - Index to search.
+    /// - `query` (`&str`) - Query (see the module documentation for the syntax).
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<RankedMatch>` - Matching pages, best match first.
+    pub fn search(&self, query: &str) -> Vec<RankedMatch> {
+        let clauses = parse_query(query);
+        if clauses.is_empty() {
+            return Vec::new();
+        }
+
+        let mut total: Option<HashMap<&str, f64>> = None;
+        for clause in &clauses {
+            let scores = self.score_clause(clause);
+            total = Some(match total {
+                None => scores,
+                // All clauses must match
+                Some(total) => total
+                    .into_iter()
+                    .filter_map(|(doc, score)| scores.get(doc).map(|s| (doc, score + s)))
+                    .collect(),
+            });
+        }
+
+        let mut matches: Vec<RankedMatch> = total
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(doc, score)| RankedMatch {
+                id: doc.to_string(),
+                score,
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+        matches
+    }
+
+    /// Returns the number of indexed pages.
+    ///
+    /// # Returns
+    ///
+    /// - `usize` - Number of pages.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Checks whether the index contains no pages.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Scores all pages matching a single clause.
+    fn score_clause(&self, clause: &Clause) -> HashMap<&str, f64> {
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+
+        match clause {
+            Clause::Term(term) => self.add_term_scores(term, &mut scores),
+            Clause::Prefix(prefix) => {
+                for term in self
+                    .terms
+                    .range(prefix.clone()..)
+                    .map(|(term, _)| term)
+                    .take_while(|term| term.starts_with(prefix.as_str()))
+                {
+                    self.add_term_scores(term, &mut scores);
+                }
+            }
+            Clause::Phrase(words) => {
+                let Some(first) = words.first().and_then(|word| self.terms.get(word)) else {
+                    return scores;
+                };
+                let idf = words.iter().map(|word| self.idf(word)).sum::<f64>();
+
+                for posting in first {
+                    let matches = posting
+                        .positions
+                        .iter()
+                        .filter(|&&start| {
+                            words.iter().enumerate().skip(1).all(|(offset, word)| {
+                                self.positions(word, &posting.doc, posting.field)
+                                    .is_some_and(|positions| {
+                                        positions.contains(&(start + offset as u32))
+                                    })
+                            })
+                        })
+                        .count();
+                    if matches > 0 {
+                        *scores.entry(posting.doc.as_str()).or_default() +=
+                            saturate(matches) * idf * posting.field.boost();
+                    }
+                }
+            }
+        }
+
+        scores
+    }
+
+    /// Adds the scores of all pages containing a term.
+    fn add_term_scores<'a>(&'a self, term: &str, scores: &mut HashMap<&'a str, f64>) {
+        let Some(postings) = self.terms.get(term) else {
+            return;
+        };
+        let idf = self.idf(term);
+        for posting in postings {
+            *scores.entry(posting.doc.as_str()).or_default() +=
+                saturate(posting.positions.len()) * idf * posting.field.boost();
+        }
+    }
+
+    /// Positions of a term in one field of a page.
+    fn positions(&self, term: &str, doc: &str, field: Field) -> Option<&Vec<u32>> {
+        self.terms
+            .get(term)?
+            .iter()
+            .find(|posting| posting.doc == doc && posting.field == field)
+            .map(|posting| &posting.positions)
+    }
+
+    /// Inverse document frequency of a term.
+    fn idf(&self, term: &str) -> f64 {
+        let docs = self.docs.len() as f64;
+        let mut containing: Vec<&str> = self
+            .terms
+            .get(term)
+            .map(|postings| postings.iter().map(|p| p.doc.as_str()).collect())
+            .unwrap_or_default();
+        containing.dedup();
+        let df = containing.len() as f64;
+        (1.0 + (docs - df + 0.5) / (df + 0.5)).ln()
+    }
+}
+
+/// Saturated term frequency, so repeating a word doesn't dominate the score.
+fn saturate(frequency: usize) -> f64 {
+    let tf = frequency as f64;
+    tf * (K1 + 1.0) / (tf + K1)
+}
+
+/// Splits text into lowercase alphanumeric tokens.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Parses a query into clauses.
+fn parse_query(query: &str) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+
+    for (index, part) in query.split('"').enumerate() {
+        // Odd parts are inside quotes
+        if index % 2 == 1 {
+            let words = tokenize(part);
+            match words.len() {
+                0 => {}
+                1 => clauses.push(Clause::Term(words[0].clone())),
+                _ => clauses.push(Clause::Phrase(words)),
+            }
+            continue;
+        }
+
+        for word in part.split_whitespace() {
+            let prefix = word.ends_with('*');
+            let tokens = tokenize(word);
+            match tokens.as_slice() {
+                [] => {}
+                [token] if prefix => clauses.push(Clause::Prefix(token.clone())),
+                [token] => clauses.push(Clause::Term(token.clone())),
+                // Words like `flow-core` are matched as phrases
+                _ => clauses.push(Clause::Phrase(tokens)),
+            }
+        }
+    }
+
+    clauses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse_query(r#"flow "rich text" edit* flow-core"#),
+            vec![
+                Clause::Term("flow".to_string()),
+                Clause::Phrase(vec!["rich".to_string(), "text".to_string()]),
+                Clause::Prefix("edit".to_string()),
+                Clause::Phrase(vec!["flow".to_string(), "core".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_titles_rank_above_body_matches() {
+        let mut index = TextIndex::default();
+        index.update("pages/rust.md", "- a systems language\n");
+        index.update("pages/notes.md", "- learning rust today\n");
+        index.update("pages/other.md", "- nothing relevant\n");
+
+        let matches = index.search("rust");
+        let ids: Vec<&str> = matches.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["pages/rust.md", "pages/notes.md"]);
+    }
+
+    #[test]
+    fn test_phrase_and_prefix_queries() {
+        let mut index = TextIndex::default();
+        index.update("pages/a.md", "- rich text editing\n");
+        index.update("pages/b.md", "- text that is rich\n");
+
+        let phrase: Vec<String> = index
+            .search(r#""rich text""#)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(phrase, vec!["pages/a.md"]);
+
+        let prefix: Vec<String> = index.search("edi*").into_iter().map(|m| m.id).collect();
+        assert_eq!(prefix, vec!["pages/a.md"]);
+
+        index.remove("pages/a.md");
+        assert!(index.search("edi*").is_empty());
+    }
+}
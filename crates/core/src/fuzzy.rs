@@ -0,0 +1,155 @@
+//! Fuzzy Matching
+//!
+//! Scores how well a typed query matches a candidate such as a page name, for
+//! the CLI pickers and the TUI command palette. All characters of the query
+//! must appear in the candidate in order, ignoring case and whitespace in the
+//! query. Characters at the start of words and runs of consecutive characters
+//! score higher, gaps between matched characters lower.
+
+/// Score of every matched character.
+const MATCH: i64 = 16;
+
+/// Bonus of a character directly following the previous match.
+const CONSECUTIVE: i64 = 24;
+
+/// Bonus of a character starting a word.
+const WORD_START: i64 = 32;
+
+/// Penalty of every character skipped between two matches.
+const GAP: i64 = 2;
+
+/// A candidate matching a query.
+///
+/// # Fields
+///
+/// - `score` (`i64`) - How well the candidate matches, higher is better.
+/// - `positions` (`Vec<usize>`) - Character indices of the matched characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+/// Matches a query against a candidate.
+///
+/// Finds the best scoring way to match the query, not just the first one, so
+/// `gp` matches the word starts of `graph page`.
+///
+/// # Arguments
+///
+/// - `query` (`&str`) - Typed query, an empty query matches everything.
+/// - `candidate` (`&str`) - Text to match against.
+///
+/// # Returns
+///
+/// - `Option<Match>` - The best match, `None` if the candidate doesn't match.
+pub fn find(query: &str, candidate: &str) -> Option<Match> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(lowercase)
+        .collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    if query.is_empty() {
+        return Some(Match {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    if query.len() > chars.len() {
+        return None;
+    }
+
+    let bonus = |j: usize| {
+        let word_start = j == 0
+            || !chars[j - 1].is_alphanumeric()
+            || (chars[j - 1].is_lowercase() && chars[j].is_uppercase());
+        MATCH + if word_start { WORD_START } else { 0 }
+    };
+
+    // best[i][j]: score of matching query[..=i] with query[i] at chars[j]
+    let mut best = vec![vec![None; chars.len()]; query.len()];
+    let mut previous = vec![vec![0; chars.len()]; query.len()];
+    for (j, c) in chars.iter().enumerate() {
+        if lowercase(*c) == query[0] {
+            best[0][j] = Some(bonus(j));
+        }
+    }
+    for i in 1..query.len() {
+        let (done, rest) = best.split_at_mut(i);
+        let (matched, row) = (&done[i - 1], &mut rest[0]);
+        for (j, c) in chars.iter().enumerate().skip(i) {
+            if lowercase(*c) != query[i] {
+                continue;
+            }
+            for (k, score) in matched.iter().enumerate().take(j).skip(i - 1) {
+                let Some(score) = score else {
+                    continue;
+                };
+                let step = if k + 1 == j {
+                    CONSECUTIVE
+                } else {
+                    -GAP * (j - k - 1) as i64
+                };
+                let score = score + bonus(j) + step;
+                if row[j].is_none_or(|best| score > best) {
+                    row[j] = Some(score);
+                    previous[i][j] = k;
+                }
+            }
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut j, score) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by_key(|(j, score)| (*score, std::cmp::Reverse(*j)))?;
+    let mut positions = vec![j; query.len()];
+    for i in (0..last).rev() {
+        j = previous[i + 1][j];
+        positions[i] = j;
+    }
+
+    // Shorter candidates win ties, e.g. `flow` over `flow notes`
+    let unmatched = (chars.len() - query.len()) as i64;
+    Some(Match {
+        score: score - unmatched / 4,
+        positions,
+    })
+}
+
+/// Scores a candidate against a query.
+///
+/// # Arguments
+///
+/// - `query` (`&str`) - Typed query.
+/// - `candidate` (`&str`) - Text to match against.
+///
+/// # Returns
+///
+/// - `Option<i64>` - Score of the best match, `None` if the candidate doesn't match.
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    find(query, candidate).map(|found| found.score)
+}
+
+/// Returns the lowercase form of a character, ignoring multi-character expansions.
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("gp", "graph page").unwrap().positions, vec![0, 6]);
+        assert!(find("xyz", "graph page").is_none());
+        assert!(find("eg", "graph page").is_none());
+        assert!(score("flow", "flow").unwrap() > score("flow", "flow notes").unwrap());
+        assert!(score("note", "Notes").unwrap() > score("note", "a bnoxtxe").unwrap());
+        assert_eq!(find("", "anything").unwrap().score, 0);
+    }
+}
@@ -0,0 +1,198 @@
+//! Hooks
+//!
+//! Flow raises events at points of the space's life, and everything that
+//! reacts to them runs from here instead of from the features raising them.
+//! Today there is a single event, [`Event::DayStarted`], raised when today's
+//! journal page is first created, whether by `flow today --create` or by the
+//! first capture of the day. Starting a day
+//!
+//! 1. creates the page from the daily template (see [`journal`](crate::journal)),
+//! 2. carries open tasks over when enabled (see [`rollover`](crate::rollover)),
+//! 3. runs the user's hook scripts for the event.
+//!
+//! Hook scripts are executables in `.flow/hooks/` named after the event,
+//! with any extension (`.flow/hooks/day-started.sh`), run in the space
+//! directory in alphabetical order. They get the event in the environment:
+//!
+//! * `FLOW_EVENT` - Name of the event, e.g. `day-started`
+//! * `FLOW_SPACE` - Path of the space
+//! * `FLOW_DATE` - The day that started, as `YYYY-MM-DD`
+//! * `FLOW_PAGE` - Id of today's journal page, relative to the space
+//!
+//! Edits a script makes to today's page are taken over after it exits, a
+//! failing script fails the capture that started the day.
+
+use chrono::NaiveDate;
+use miette::{IntoDiagnostic, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use crate::space::{Space, FLOW_DIR};
+
+/// Directory below `.flow` holding the hook scripts.
+pub const HOOKS_DIR: &str = "hooks";
+
+/// Events hook scripts can react to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Today's journal page was created.
+    DayStarted,
+}
+
+impl Event {
+    /// Returns the name of the event, which hook scripts are named after.
+    ///
+    /// # Returns
+    ///
+    /// - `&'static str` - Kebab-case name of the event.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DayStarted => "day-started",
+        }
+    }
+}
+
+impl Space {
+    /// Returns the id of today's journal page, starting the day if the page
+    /// doesn't exist yet.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - Id of today's journal page.
+    ///
+    /// # Errors
+    ///
+    /// Starting the day fails, see [`Space::create_journal_page`].
+    pub fn today_page(&mut self) -> Result<String> {
+        let today = self.today();
+        self.create_journal_page(today)?;
+        Ok(self.journal_page(today))
+    }
+
+    /// Reacts to a day starting, once its journal page was created.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the day started in.
+    /// - `date` (`NaiveDate`) - The day that started.
+    ///
+    /// # Errors
+    ///
+    /// Carrying tasks over fails, a hook script can't be run or fails, or IO
+    /// errors when reading or writing pages.
+    pub(crate) fn start_day(&mut self, date: NaiveDate) -> Result<()> {
+        if self.metadata.journal.carry_over {
+            self.rollover()?;
+        }
+
+        // The scripts read the page from disk
+        self.flush()?;
+        let id = self.journal_page(date);
+        let date = date.format("%Y-%m-%d").to_string();
+        self.run_hooks(
+            Event::DayStarted,
+            &[("FLOW_DATE", &date), ("FLOW_PAGE", &id)],
+        )?;
+
+        // Take over what the scripts wrote to the page
+        let Some(content) = self.storage.read_to_string(&self.path.join(&id))? else {
+            return Ok(());
+        };
+        if self.read_page(&id)?.as_deref() != Some(content.as_str()) {
+            self.update_page(&id, &content)?;
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Runs the hook scripts of an event.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the event happened in.
+    /// - `event` (`Event`) - The event.
+    /// - `env` (`&[(&str, &str)]`) - Details of the event passed to the scripts.
+    ///
+    /// # Errors
+    ///
+    /// A script can't be run or exits unsuccessfully, or IO errors when
+    /// reading the hooks directory.
+    fn run_hooks(&self, event: Event, env: &[(&str, &str)]) -> Result<()> {
+        for script in self.hooks(event)? {
+            let output = process::Command::new(&script)
+                .envs(env.iter().copied())
+                .env("FLOW_EVENT", event.name())
+                .env("FLOW_SPACE", &self.path)
+                .current_dir(&self.path)
+                .output()
+                .map_err(|error| {
+                    miette::miette!("Failed to run hook '{}': {}", script.display(), error)
+                })?;
+            if !output.status.success() {
+                miette::bail!(
+                    "Hook '{}' failed with {}: {}",
+                    script.display(),
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the hook scripts of an event in the order they run.
+    fn hooks(&self, event: Event) -> Result<Vec<PathBuf>> {
+        let dir = self.path.join(FLOW_DIR).join(HOOKS_DIR);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut scripts = Vec::new();
+        for entry in fs::read_dir(&dir).into_diagnostic()? {
+            let path = entry.into_diagnostic()?.path();
+            let stem = path.file_stem().map(|stem| stem.to_string_lossy());
+            if path.is_file() && stem.as_deref() == Some(event.name()) {
+                scripts.push(path);
+            }
+        }
+        scripts.sort();
+        Ok(scripts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_today_page_starts_the_day_once() {
+        let mut space = Space::in_memory().unwrap();
+        space.metadata.journal.carry_over = true;
+        space
+            .write_page("journal/2000-01-01.md", "- TODO Water plants\n")
+            .unwrap();
+
+        let id = space.today_page().unwrap();
+        assert!(space
+            .read_page(&id)
+            .unwrap()
+            .unwrap()
+            .contains("- TODO Water plants"));
+
+        space.add("Second capture").unwrap();
+        assert_eq!(
+            space
+                .read_page(&id)
+                .unwrap()
+                .unwrap()
+                .matches("Water plants")
+                .count(),
+            1
+        );
+    }
+}
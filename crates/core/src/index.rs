@@ -0,0 +1,503 @@
+//! Page Index
+//!
+//! A small, persisted index of all pages in a space, stored as JSON in
+//! `.flow/index/pages.json`. It allows listing pages (e.g. for editor completion)
+//! without importing the Loro document or reading every markdown file.
+//!
+//! The index is updated by [`Space`](crate::space::Space) whenever pages are
+//! saved, and rebuilt from the markdown files if it is missing or unreadable.
+//! Rebuilding reads, hashes and parses files in parallel.
+
+use loro::LoroDoc;
+use miette::{IntoDiagnostic, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::cancel::CancellationToken;
+use crate::page;
+use crate::progress::{NoProgress, Progress};
+use crate::reminders;
+use crate::space::{container_names, markdown_files, FLOW_DIR};
+use crate::storage::{FsStorage, Storage};
+use crate::timestamps::{self, PageTimes};
+use crate::wordcount;
+
+pub(crate) const INDEX_DIR: &str = "index";
+pub(crate) const INDEX_FILE: &str = "pages.json";
+
+/// Index entry of a single page.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name of the page.
+/// - `id` (`String`) - Id of the page (relative markdown path).
+/// - `aliases` (`Vec<String>`) - Aliases declared by the page.
+/// - `created` (`i64`) - Creation as unix timestamp in seconds.
+/// - `modified` (`i64`) - Last modification as unix timestamp in seconds.
+/// - `links` (`Vec<String>`) - Targets of all wikilinks in the page.
+/// - `embeds` (`Vec<String>`) - Pages embedded into the page.
+/// - `tags` (`Vec<String>`) - Tags of the page.
+/// - `blocks` (`usize`) - Number of blocks in the page.
+/// - `due` (`Vec<i64>`) - When each open task of the page is scheduled or due, as unix timestamps.
+/// - `hash` (`u64`) - Hash of the page content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageEntry {
+    pub name: String,
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default)]
+    pub modified: i64,
+    #[serde(default)]
+    pub links: Vec<String>,
+    #[serde(default)]
+    pub embeds: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub blocks: usize,
+    #[serde(default)]
+    pub due: Vec<i64>,
+    #[serde(default)]
+    pub hash: u64,
+}
+
+impl PageEntry {
+    /// Builds the index entry of a page from its content.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` (`&str`) - Id of the page.
+    /// - `content` (`&str`) - Markdown content of the page.
+    /// - `times` (`PageTimes`) - Creation and modification time of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The index entry.
+    pub fn parse(id: &str, content: &str, times: PageTimes) -> Self {
+        let name = page::name_from_id(id);
+        // Relative links are stored by the name of the page they point to
+        let linked = page::absolute_links(content, &name);
+        Self {
+            name,
+            id: id.to_string(),
+            aliases: page::aliases(content),
+            created: times.created,
+            modified: times.modified,
+            links: page::links(&linked),
+            embeds: page::embeds(&linked),
+            tags: page::tags(content),
+            blocks: wordcount::count(content).blocks,
+            due: reminders::due(content),
+            hash: content_hash(content),
+        }
+    }
+}
+
+/// Index of all pages in a space, keyed by page id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageIndex {
+    #[serde(default)]
+    pages: BTreeMap<String, PageEntry>,
+}
+
+impl PageIndex {
+    /// Loads the index of the space at the given path.
+    ///
+    /// Rebuilds (and persists) the index if it is missing or unreadable.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The page index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index has to be rebuilt and files can't be read or written.
+    pub fn load(space_path: &Path) -> Result<Self> {
+        let index_path = space_path.join(FLOW_DIR).join(INDEX_DIR).join(INDEX_FILE);
+        if let Ok(json) = fs::read_to_string(&index_path) {
+            if let Ok(index) = serde_json::from_str(&json) {
+                return Ok(index);
+            }
+        }
+
+        let index = Self::rebuild(space_path)?;
+        index.save(space_path)?;
+        Ok(index)
+    }
+
+    /// Reads the persisted index of a space without ever rebuilding it.
+    ///
+    /// For callers that must stay fast, like shell prompts, and rather show
+    /// nothing than scan the space.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<Self>` - The page index, `None` if it is missing or unreadable.
+    pub fn cached(space_path: &Path) -> Option<Self> {
+        let index_path = space_path.join(FLOW_DIR).join(INDEX_DIR).join(INDEX_FILE);
+        let json = fs::read_to_string(index_path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Loads the index of a space from a storage.
+    ///
+    /// Rebuilds (and persists) the index from the document if it is missing
+    /// or unreadable. Used for storage without markdown files on disk.
+    ///
+    /// # Arguments
+    ///
+    /// - `storage` (`&dyn Storage`) - Storage of the space.
+    /// - `space_path` (`&Path`) - Path of the space.
+    /// - `doc` (`&LoroDoc`) - Document of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The page index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index can't be read or written.
+    pub fn load_from(storage: &dyn Storage, space_path: &Path, doc: &LoroDoc) -> Result<Self> {
+        let index_path = space_path.join(FLOW_DIR).join(INDEX_DIR).join(INDEX_FILE);
+        if let Some(json) = storage.read_to_string(&index_path)? {
+            if let Ok(index) = serde_json::from_str(&json) {
+                return Ok(index);
+            }
+        }
+
+        let times = timestamps::all(doc);
+        let mut index = Self::default();
+        for id in container_names(doc)
+            .into_iter()
+            .filter(|id| id.ends_with(".md"))
+        {
+            let content = doc.get_text(id.as_str()).to_string();
+            index.update(&id, &content, times.get(&id).copied().unwrap_or_default());
+        }
+        index.save_to(storage, space_path)?;
+        Ok(index)
+    }
+
+    /// Builds the index from the markdown files of a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The rebuilt page index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the markdown files.
+    pub fn rebuild(space_path: &Path) -> Result<Self> {
+        Self::rebuild_with(space_path, 0, &NoProgress, &CancellationToken::new())
+    }
+
+    /// Builds the index from the markdown files of a space in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    /// - `threads` (`usize`) - Number of worker threads, `0` uses all cores.
+    /// - `progress` (`&dyn Progress`) - Receives one step per scanned file.
+    /// - `cancel` (`&CancellationToken`) - Checked before each file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The rebuilt page index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the markdown files or starting the thread pool,
+    /// [`Cancelled`](crate::cancel::Cancelled) if the token was cancelled.
+    pub fn rebuild_with(
+        space_path: &Path,
+        threads: usize,
+        progress: &dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Self> {
+        let ids = markdown_files(&FsStorage, space_path)?;
+        // An unreadable document only costs the recorded times, files remain the source of truth
+        let times = timestamps::read(space_path).unwrap_or_default();
+        progress.start("Indexing pages", Some(ids.len() as u64));
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .into_diagnostic()?;
+
+        let entries = pool.install(|| {
+            ids.par_iter()
+                .map(|id| {
+                    cancel.check()?;
+                    let entry = scan(space_path, id, times.get(id).copied());
+                    progress.advance(1);
+                    entry
+                })
+                .collect::<Result<Vec<_>>>()
+        });
+        progress.finish();
+        let entries = entries?;
+
+        Ok(Self {
+            pages: entries
+                .into_iter()
+                .map(|entry| (entry.id.clone(), entry))
+                .collect(),
+        })
+    }
+
+    /// Persists the index of a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`PageIndex`) - Index to persist.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the index file.
+    pub fn save(&self, space_path: &Path) -> Result<()> {
+        self.save_to(&FsStorage, space_path)
+    }
+
+    /// Persists the index of a space to a storage.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`PageIndex`) - Index to persist.
+    /// - `storage` (`&dyn Storage`) - Storage of the space.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the index file.
+    pub fn save_to(&self, storage: &dyn Storage, space_path: &Path) -> Result<()> {
+        let index_path = space_path.join(FLOW_DIR).join(INDEX_DIR).join(INDEX_FILE);
+        let json = serde_json::to_string(self).into_diagnostic()?;
+        storage.write(&index_path, json.as_bytes())
+    }
+
+    /// Checks whether the index is out of date with the markdown files.
+    ///
+    /// The index is stale if pages were added or removed, or if any markdown
+    /// file was modified after the index was last written (e.g. by an external
+    /// editor).
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`PageIndex`) - Index to check.
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<bool>` - True if the index should be rebuilt.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading directories.
+    pub fn is_stale(&self, space_path: &Path) -> Result<bool> {
+        let ids = markdown_files(&FsStorage, space_path)?;
+        if ids.len() != self.pages.len() || ids.iter().any(|id| !self.pages.contains_key(id)) {
+            return Ok(true);
+        }
+
+        let Ok(written) = fs::metadata(space_path.join(FLOW_DIR).join(INDEX_DIR).join(INDEX_FILE))
+            .and_then(|metadata| metadata.modified())
+        else {
+            return Ok(true);
+        };
+
+        Ok(ids.iter().any(|id| {
+            fs::metadata(space_path.join(id))
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified > written)
+        }))
+    }
+
+    /// Updates the entry of a single page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`PageIndex`) - Index to update.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `content` (`&str`) - Current markdown content of the page.
+    /// - `times` (`PageTimes`) - Creation and modification time of the page.
+    pub fn update(&mut self, id: &str, content: &str, times: PageTimes) {
+        self.pages
+            .insert(id.to_string(), PageEntry::parse(id, content, times));
+    }
+
+    /// Removes the entry of a page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`PageIndex`) - Index to update.
+    /// - `id` (`&str`) - Id of the page.
+    pub fn remove(&mut self, id: &str) {
+        self.pages.remove(id);
+    }
+
+    /// Returns all pages of the index, ordered by id.
+    ///
+    /// # Returns
+    ///
+    /// - `impl Iterator<Item = &PageEntry>` - Iterator over all page entries.
+    pub fn pages(&self) -> impl Iterator<Item = &PageEntry> {
+        self.pages.values()
+    }
+
+    /// Finds a page by name or alias (case-insensitive).
+    ///
+    /// # Arguments
+    ///
+    /// - `name` (`&str`) - Name or alias of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&PageEntry>` - The page entry if found.
+    pub fn find(&self, name: &str) -> Option<&PageEntry> {
+        let lowercase = name.to_lowercase();
+        self.pages
+            .values()
+            .find(|entry| entry.name.to_lowercase() == lowercase)
+            .or_else(|| {
+                self.pages.values().find(|entry| {
+                    entry
+                        .aliases
+                        .iter()
+                        .any(|alias| alias.to_lowercase() == lowercase)
+                })
+            })
+    }
+
+    /// Resolves a page name or alias to a page id.
+    ///
+    /// Names not found in the index map to the id a page of that name would
+    /// have (see [`page::id_from_name`]).
+    ///
+    /// # Arguments
+    ///
+    /// - `name` (`&str`) - Name or alias of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Id of the page.
+    pub fn resolve(&self, name: &str) -> String {
+        self.find(name)
+            .map(|entry| entry.id.clone())
+            .unwrap_or_else(|| page::id_from_name(name))
+    }
+
+    /// Returns all pages linking to the page with the given name.
+    ///
+    /// Links to any alias of the page count as well.
+    ///
+    /// # Arguments
+    ///
+    /// - `name` (`&str`) - Name or alias of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<&PageEntry>` - Pages linking to the page.
+    pub fn backlinks(&self, name: &str) -> Vec<&PageEntry> {
+        let mut names = vec![name.to_lowercase()];
+        if let Some(entry) = self.find(name) {
+            names.push(entry.name.to_lowercase());
+            names.extend(entry.aliases.iter().map(|alias| alias.to_lowercase()));
+        }
+
+        self.pages
+            .values()
+            .filter(|entry| {
+                entry
+                    .links
+                    .iter()
+                    .any(|link| names.contains(&link.to_lowercase()))
+            })
+            .collect()
+    }
+
+    /// Returns all pages embedding the page with the given name.
+    ///
+    /// Embeds of any alias of the page count as well.
+    ///
+    /// # Arguments
+    ///
+    /// - `name` (`&str`) - Name or alias of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<&PageEntry>` - Pages embedding the page.
+    pub fn embedded_by(&self, name: &str) -> Vec<&PageEntry> {
+        let mut names = vec![name.to_lowercase()];
+        if let Some(entry) = self.find(name) {
+            names.push(entry.name.to_lowercase());
+            names.extend(entry.aliases.iter().map(|alias| alias.to_lowercase()));
+        }
+
+        self.pages
+            .values()
+            .filter(|entry| {
+                entry
+                    .embeds
+                    .iter()
+                    .any(|embed| names.contains(&embed.to_lowercase()))
+            })
+            .collect()
+    }
+
+    /// Returns the number of pages in the index.
+    ///
+    /// # Returns
+    ///
+    /// - `usize` - Number of pages.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Checks whether the index contains no pages.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+/// Reads and parses a single markdown file.
+///
+/// Pages without recorded times fall back to the file's modification time.
+fn scan(space_path: &Path, id: &str, times: Option<PageTimes>) -> Result<PageEntry> {
+    let file_path = space_path.join(id);
+    let content = fs::read_to_string(&file_path).into_diagnostic()?;
+    let times = times.unwrap_or_else(|| PageTimes::from_file(&file_path));
+    Ok(PageEntry::parse(id, content.as_str(), times))
+}
+
+/// Hashes page content with FNV-1a, which is stable across Rust versions.
+fn content_hash(content: &str) -> u64 {
+    content.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {}
@@ -0,0 +1,128 @@
+//! IMAP Mailboxes
+//!
+//! Reads mail from a folder on an IMAP server over TLS. Gmail exposes labels
+//! as folders, so a label works as well. Unseen messages are unprocessed;
+//! they're fetched without setting the `\Seen` flag, which is only set once a
+//! message was ingested.
+
+use miette::{IntoDiagnostic, Result};
+use native_tls::TlsStream;
+use std::net::TcpStream;
+
+use super::{Mailbox, RawMail};
+use crate::secrets::Secrets;
+
+/// Name of the secret holding the password if none is configured.
+const SECRET: &str = "mail";
+
+/// A folder on an IMAP server.
+///
+/// # Fields
+///
+/// - `session` (`imap::Session`) - Logged in session with the folder selected.
+pub struct Imap {
+    session: ::imap::Session<TlsStream<TcpStream>>,
+}
+
+impl Imap {
+    /// Logs in to a server and selects a folder.
+    ///
+    /// # Arguments
+    ///
+    /// - `host` (`&str`) - Host of the server.
+    /// - `port` (`u16`) - Port of the server.
+    /// - `user` (`&str`) - Account to log in with.
+    /// - `password` (`Option<&str>`) - Password or `secret:` reference, the `mail` secret if `None`.
+    /// - `mailbox` (`&str`) - Folder or label to read.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The logged in session.
+    ///
+    /// # Errors
+    ///
+    /// No password is stored, the server is unreachable, rejects the login, or
+    /// the folder doesn't exist.
+    pub fn connect(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: Option<&str>,
+        mailbox: &str,
+    ) -> Result<Self> {
+        let secrets = Secrets::open()?;
+        let password = match password {
+            Some(password) => secrets.resolve(password)?,
+            None => secrets.get(SECRET)?.ok_or_else(|| {
+                miette::miette!(
+                    "No password for {}, add it with 'flow auth set {}'",
+                    user,
+                    SECRET
+                )
+            })?,
+        };
+
+        let tls = native_tls::TlsConnector::new().into_diagnostic()?;
+        let client = ::imap::connect((host, port), host, &tls).into_diagnostic()?;
+        let mut session = client
+            .login(user, &password)
+            .map_err(|(error, _)| error)
+            .into_diagnostic()?;
+        session.select(mailbox).into_diagnostic()?;
+        Ok(Self { session })
+    }
+}
+
+impl Mailbox for Imap {
+    fn unprocessed(&mut self) -> Result<Vec<RawMail>> {
+        let mut uids: Vec<u32> = self
+            .session
+            .uid_search("UNSEEN")
+            .into_diagnostic()?
+            .into_iter()
+            .collect();
+        if uids.is_empty() {
+            return Ok(Vec::new());
+        }
+        uids.sort_unstable();
+
+        let set = uids
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let fetches = self
+            .session
+            .uid_fetch(&set, "BODY.PEEK[]")
+            .into_diagnostic()?;
+
+        let mut messages: Vec<(u32, RawMail)> = fetches
+            .iter()
+            .filter_map(|fetch| {
+                let uid = fetch.uid?;
+                Some((
+                    uid,
+                    RawMail {
+                        key: uid.to_string(),
+                        raw: fetch.body()?.to_vec(),
+                    },
+                ))
+            })
+            .collect();
+        messages.sort_by_key(|(uid, _)| *uid);
+        Ok(messages.into_iter().map(|(_, mail)| mail).collect())
+    }
+
+    fn mark_processed(&mut self, key: &str) -> Result<()> {
+        self.session
+            .uid_store(key, "+FLAGS (\\Seen)")
+            .into_diagnostic()?;
+        Ok(())
+    }
+}
+
+impl Drop for Imap {
+    fn drop(&mut self) {
+        let _ = self.session.logout();
+    }
+}
@@ -0,0 +1,336 @@
+//! Mail Ingestion
+//!
+//! Converts mail into notes, for the "send yourself a note" workflow. The
+//! subject becomes a block on today's journal page, or the name of a new
+//! page, with the sender and date as properties and the body's paragraphs as
+//! blocks below:
+//!
+//! ```markdown
+//! - Article on CRDTs
+//!   from:: Ada <ada@example.com>
+//!   date:: 2024-05-01 09:12
+//!   - Worth reading before the sync rewrite.
+//!   - [paper.pdf](../assets/mail/paper.pdf)
+//! ```
+//!
+//! Attachments are saved to `assets/mail/` and linked from the note. Signatures
+//! below a `-- ` line are dropped.
+
+use chrono::{DateTime, FixedOffset};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::{MailTarget, Mailbox};
+use crate::attach;
+use crate::page;
+use crate::space::Space;
+
+/// Directory attachments are saved to, relative to the space.
+pub const ATTACHMENT_DIR: &str = "assets/mail";
+
+/// A parsed message.
+///
+/// # Fields
+///
+/// - `subject` (`String`) - Subject, empty if missing.
+/// - `from` (`String`) - Sender, e.g. `Ada <ada@example.com>`.
+/// - `date` (`Option<DateTime<FixedOffset>>`) - When the message was sent.
+/// - `body` (`String`) - Plain text body, converted from HTML if needed.
+/// - `attachments` (`Vec<Attachment>`) - Attached files.
+#[derive(Debug, Clone, Default)]
+pub struct Mail {
+    pub subject: String,
+    pub from: String,
+    pub date: Option<DateTime<FixedOffset>>,
+    pub body: String,
+    pub attachments: Vec<Attachment>,
+}
+
+/// A file attached to a message.
+///
+/// # Fields
+///
+/// - `name` (`String`) - File name.
+/// - `content` (`Vec<u8>`) - Content of the file.
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub name: String,
+    pub content: Vec<u8>,
+}
+
+/// A message turned into a note.
+///
+/// # Fields
+///
+/// - `subject` (`String`) - Subject of the message.
+/// - `from` (`String`) - Sender of the message.
+/// - `page` (`String`) - Id of the page the note was written to.
+/// - `attachments` (`Vec<String>`) - Paths of the saved attachments, relative to the space.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IngestedMail {
+    pub subject: String,
+    pub from: String,
+    pub page: String,
+    pub attachments: Vec<String>,
+}
+
+impl Mail {
+    /// Parses a message in RFC 5322 format.
+    ///
+    /// # Arguments
+    ///
+    /// - `raw` (`&[u8]`) - The message.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The parsed message.
+    ///
+    /// # Errors
+    ///
+    /// The message is malformed, or Flow was built without the `mail` feature.
+    pub fn parse(raw: &[u8]) -> Result<Self> {
+        #[cfg(feature = "mail")]
+        {
+            use mail_parser::{MessageParser, MimeHeaders};
+
+            let Some(message) = MessageParser::default().parse(raw) else {
+                miette::bail!("Message is not valid mail");
+            };
+            let from = message
+                .from()
+                .and_then(|from| from.first())
+                .map(|addr| match (addr.name(), addr.address()) {
+                    (Some(name), Some(address)) => format!("{} <{}>", name, address),
+                    (name, address) => name.or(address).unwrap_or_default().to_string(),
+                })
+                .unwrap_or_default();
+
+            Ok(Self {
+                subject: message.subject().unwrap_or_default().trim().to_string(),
+                from,
+                date: message
+                    .date()
+                    .and_then(|date| DateTime::parse_from_rfc3339(&date.to_rfc3339()).ok()),
+                body: message
+                    .body_text(0)
+                    .map(|body| body.to_string())
+                    .unwrap_or_default(),
+                attachments: message
+                    .attachments()
+                    .filter_map(|part| {
+                        Some(Attachment {
+                            name: part.attachment_name()?.to_string(),
+                            content: part.contents().to_vec(),
+                        })
+                    })
+                    .collect(),
+            })
+        }
+        #[cfg(not(feature = "mail"))]
+        {
+            let _ = raw;
+            miette::bail!("Parsing mail requires Flow built with the 'mail' feature")
+        }
+    }
+
+    /// Splits the body into paragraphs.
+    ///
+    /// Hard wrapped lines are joined, the signature is dropped.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<String>` - Non-empty paragraphs.
+    fn paragraphs(&self) -> Vec<String> {
+        let mut paragraphs = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        for line in self.body.lines() {
+            if line == "-- " {
+                break;
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                if !current.is_empty() {
+                    paragraphs.push(current.join(" "));
+                    current.clear();
+                }
+            } else {
+                current.push(line);
+            }
+        }
+        if !current.is_empty() {
+            paragraphs.push(current.join(" "));
+        }
+        paragraphs
+    }
+}
+
+impl Space {
+    /// Ingests all unprocessed messages of a mailbox.
+    ///
+    /// Each message is marked processed right after its note was written, so
+    /// a failure halfway leaves the remaining messages for the next run.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to write the notes to.
+    /// - `mailbox` (`&mut dyn Mailbox`) - Mailbox to read.
+    /// - `target` (`MailTarget`) - What each message becomes.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<IngestedMail>>` - The ingested messages, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// The mailbox can't be read or updated, a message can't be parsed, the
+    /// journal page is locked, or IO errors when writing files.
+    pub fn ingest_mailbox(
+        &mut self,
+        mailbox: &mut dyn Mailbox,
+        target: MailTarget,
+    ) -> Result<Vec<IngestedMail>> {
+        let mut ingested = Vec::new();
+        for raw in mailbox.unprocessed()? {
+            let mail = Mail::parse(&raw.raw)?;
+            ingested.push(self.ingest_mail(&mail, target)?);
+            mailbox.mark_processed(&raw.key)?;
+        }
+        Ok(ingested)
+    }
+
+    /// Writes a message as a note.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to write the note to.
+    /// - `mail` (`&Mail`) - The message.
+    /// - `target` (`MailTarget`) - Whether the message becomes a journal block or a page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<IngestedMail>` - The written note.
+    ///
+    /// # Errors
+    ///
+    /// The journal page is locked, or IO errors when writing files.
+    pub fn ingest_mail(&mut self, mail: &Mail, target: MailTarget) -> Result<IngestedMail> {
+        let date = mail
+            .date
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string());
+        let id = match target {
+            MailTarget::Journal => self.today_page()?,
+            MailTarget::Page => self.mail_page_id(mail)?,
+        };
+
+        let mut attachments = Vec::with_capacity(mail.attachments.len());
+        let mut blocks = mail.paragraphs();
+        for attachment in &mail.attachments {
+            let path = self.save_asset(ATTACHMENT_DIR, &attachment.name, &attachment.content)?;
+            blocks.push(attach::link(&path, &id));
+            attachments.push(path);
+        }
+
+        let subject = if mail.subject.is_empty() {
+            "(no subject)"
+        } else {
+            &mail.subject
+        };
+        match target {
+            MailTarget::Journal => {
+                let mut lines = vec![format!("- {}", subject)];
+                lines.push(format!("  from:: {}", mail.from));
+                if let Some(date) = &date {
+                    lines.push(format!("  date:: {}", date));
+                }
+                lines.extend(blocks.iter().map(|block| format!("  - {}", block)));
+                self.append(&id, &lines.join("\n"))?;
+            }
+            MailTarget::Page => {
+                let mut lines = vec![format!("from:: {}", mail.from)];
+                if let Some(date) = &date {
+                    lines.push(format!("date:: {}", date));
+                }
+                lines.push("source:: mail".to_string());
+                lines.push(String::new());
+                lines.extend(blocks.iter().map(|block| format!("- {}", block)));
+                let mut content = lines.join("\n");
+                content.push('\n');
+                self.write_page(&id, &content)?;
+            }
+        }
+
+        Ok(IngestedMail {
+            subject: mail.subject.clone(),
+            from: mail.from.clone(),
+            page: id,
+            attachments,
+        })
+    }
+
+    /// Finds a free page id for a message, named after its subject.
+    fn mail_page_id(&self, mail: &Mail) -> Result<String> {
+        let base = match page::file_name(&mail.subject, ' ') {
+            name if name.is_empty() => match mail.date {
+                Some(date) => format!("Mail {}", date.format("%Y-%m-%d %H-%M")),
+                None => "Mail".to_string(),
+            },
+            name => name,
+        };
+
+        self.unused_page_id(&base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mail() -> Mail {
+        Mail {
+            subject: "Article on CRDTs".to_string(),
+            from: "Ada <ada@example.com>".to_string(),
+            date: DateTime::parse_from_rfc3339("2024-05-01T09:12:00+02:00").ok(),
+            body: "Worth reading\nbefore the sync rewrite.\n\nSee page 4.\n-- \nAda".to_string(),
+            attachments: vec![Attachment {
+                name: "paper.pdf".to_string(),
+                content: b"%PDF".to_vec(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_paragraphs_join_lines_and_drop_signature() {
+        assert_eq!(
+            mail().paragraphs(),
+            vec!["Worth reading before the sync rewrite.", "See page 4."]
+        );
+    }
+
+    #[test]
+    fn test_ingest_mail_into_journal() {
+        let mut space = Space::in_memory().unwrap();
+        let ingested = space.ingest_mail(&mail(), MailTarget::Journal).unwrap();
+        assert_eq!(ingested.attachments, vec!["assets/mail/paper.pdf"]);
+
+        let content = space.read_page(&ingested.page).unwrap().unwrap();
+        assert!(content.contains(
+            "- Article on CRDTs\n  from:: Ada <ada@example.com>\n  date:: 2024-05-01 09:12\n  - Worth reading before the sync rewrite.\n  - See page 4.\n  - [paper.pdf](../assets/mail/paper.pdf)"
+        ));
+    }
+
+    #[test]
+    fn test_ingest_mail_as_page() {
+        let mut space = Space::in_memory().unwrap();
+        let first = space.ingest_mail(&mail(), MailTarget::Page).unwrap();
+        let second = space.ingest_mail(&mail(), MailTarget::Page).unwrap();
+        assert_eq!(first.page, page::id_from_name("Article on CRDTs"));
+        assert_eq!(second.page, page::id_from_name("Article on CRDTs (2)"));
+        assert_eq!(second.attachments, vec!["assets/mail/paper-2.pdf"]);
+
+        let content = space.read_page(&first.page).unwrap().unwrap();
+        let properties = page::properties(&content);
+        assert_eq!(properties[0].1, "Ada <ada@example.com>");
+        assert!(content.contains("- [paper.pdf](../assets/mail/paper.pdf)"));
+    }
+}
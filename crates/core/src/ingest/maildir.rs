@@ -0,0 +1,149 @@
+//! Maildir Mailboxes
+//!
+//! Reads mail from a local maildir. Newly delivered messages wait in `new`,
+//! messages seen by a mail client in `cur` with their flags after `:2,` in the
+//! file name. Messages without the `S` (seen) flag are unprocessed; marking
+//! one processed moves it to `cur` and adds the flag, as mail clients do.
+
+use miette::{IntoDiagnostic, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{Mailbox, RawMail};
+
+/// Separator between the unique name of a message and its flags.
+const INFO_SEPARATOR: &str = ":2,";
+
+/// Flag of seen messages.
+const SEEN: char = 'S';
+
+/// A local maildir.
+///
+/// # Fields
+///
+/// - `path` (`PathBuf`) - Directory containing `new` and `cur`.
+pub struct Maildir {
+    path: PathBuf,
+}
+
+impl Maildir {
+    /// Opens a maildir.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - Directory containing `new` and `cur`.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The maildir.
+    ///
+    /// # Errors
+    ///
+    /// The directory isn't a maildir.
+    pub fn open(path: &Path) -> Result<Self> {
+        if !path.join("new").is_dir() || !path.join("cur").is_dir() {
+            miette::bail!("'{}' is not a maildir", path.display());
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Mailbox for Maildir {
+    fn unprocessed(&mut self) -> Result<Vec<RawMail>> {
+        let mut messages = Vec::new();
+        for dir in ["new", "cur"] {
+            for entry in fs::read_dir(self.path.join(dir)).into_diagnostic()? {
+                let entry = entry.into_diagnostic()?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with('.') || !entry.path().is_file() || seen(&name) {
+                    continue;
+                }
+                let modified = entry.metadata().and_then(|m| m.modified()).ok();
+                messages.push((modified, format!("{}/{}", dir, name), entry.path()));
+            }
+        }
+        messages.sort();
+
+        messages
+            .into_iter()
+            .map(|(_, key, path)| {
+                Ok(RawMail {
+                    key,
+                    raw: fs::read(path).into_diagnostic()?,
+                })
+            })
+            .collect()
+    }
+
+    fn mark_processed(&mut self, key: &str) -> Result<()> {
+        let name = key.rsplit('/').next().unwrap_or(key);
+        let (unique, flags) = name.split_once(INFO_SEPARATOR).unwrap_or((name, ""));
+        let mut flags: Vec<char> = flags.chars().chain([SEEN]).collect();
+        flags.sort_unstable();
+        flags.dedup();
+
+        let target = format!(
+            "{}{}{}",
+            unique,
+            INFO_SEPARATOR,
+            flags.into_iter().collect::<String>()
+        );
+        fs::rename(self.path.join(key), self.path.join("cur").join(target)).into_diagnostic()
+    }
+}
+
+/// Checks whether the flags in a message's file name include `S`.
+fn seen(name: &str) -> bool {
+    name.split_once(INFO_SEPARATOR)
+        .is_some_and(|(_, flags)| flags.contains(SEEN))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maildir(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("flow-maildir-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        for dir in ["new", "cur", "tmp"] {
+            fs::create_dir_all(path.join(dir)).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_unprocessed_and_mark_processed() {
+        let path = maildir("mark");
+        fs::write(path.join("new/1.host"), "Subject: new\n\nHello").unwrap();
+        fs::write(path.join("cur/2.host:2,F"), "Subject: flagged\n\nHello").unwrap();
+        fs::write(path.join("cur/3.host:2,S"), "Subject: seen\n\nHello").unwrap();
+
+        let mut mailbox = Maildir::open(&path).unwrap();
+        let mut keys: Vec<String> = mailbox
+            .unprocessed()
+            .unwrap()
+            .into_iter()
+            .map(|mail| mail.key)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, vec!["cur/2.host:2,F", "new/1.host"]);
+
+        for key in &keys {
+            mailbox.mark_processed(key).unwrap();
+        }
+        assert!(mailbox.unprocessed().unwrap().is_empty());
+        assert!(path.join("cur/1.host:2,S").is_file());
+        assert!(path.join("cur/2.host:2,FS").is_file());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_open_rejects_other_directories() {
+        let path = std::env::temp_dir();
+        assert!(Maildir::open(&path.join("flow-no-maildir")).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Ingestion
+//!
+//! Turns items arriving outside of Flow into notes. Mail sent to a dedicated
+//! mailbox, IMAP folder or Gmail label becomes a journal block or a page of
+//! its own (see [`mail`]), and is marked processed afterwards so it's only
+//! ingested once.
+//!
+//! Mailboxes are configured per space in the space metadata. Passwords are
+//! usually `secret:` references and default to the secret named `mail`:
+//!
+//! ```toml
+//! [ingest.mail]
+//! kind = "imap"
+//! host = "imap.fastmail.com"
+//! user = "me@example.com"
+//! mailbox = "Flow"
+//! target = "page"
+//! ```
+//!
+//! ```toml
+//! [ingest.mail]
+//! kind = "maildir"
+//! path = "/home/me/Mail/flow"
+//! ```
+//!
+//! Parsing mail and reading IMAP folders needs Flow built with the `mail`
+//! feature.
+
+#[cfg(feature = "mail")]
+pub mod imap;
+pub mod mail;
+pub mod maildir;
+
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::space::Space;
+
+/// Ingestion settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `mail` (`Option<MailSettings>`) - Mailbox to ingest, `None` if not configured.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IngestSettings {
+    #[serde(default)]
+    pub mail: Option<MailSettings>,
+}
+
+/// Settings of the ingested mailbox.
+///
+/// # Fields
+///
+/// - `source` (`MailSource`) - Where the mail is read from.
+/// - `target` (`MailTarget`) - What each message becomes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailSettings {
+    #[serde(flatten)]
+    pub source: MailSource,
+    #[serde(default)]
+    pub target: MailTarget,
+}
+
+/// A mailbox mail is read from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum MailSource {
+    /// A folder or Gmail label on an IMAP server, read over TLS.
+    Imap {
+        /// Host of the server.
+        host: String,
+        /// Port of the server.
+        #[serde(default = "default_port")]
+        port: u16,
+        /// Account to log in with.
+        user: String,
+        /// Password, usually a `secret:` reference.
+        #[serde(default)]
+        password: Option<String>,
+        /// Folder or label to read.
+        #[serde(default = "default_mailbox")]
+        mailbox: String,
+    },
+    /// A local maildir, e.g. kept in sync by `mbsync` or `offlineimap`.
+    Maildir {
+        /// Directory containing `new` and `cur`.
+        path: PathBuf,
+    },
+}
+
+fn default_port() -> u16 {
+    993
+}
+
+fn default_mailbox() -> String {
+    "INBOX".to_string()
+}
+
+impl MailSource {
+    /// Returns a short description of where mail is read from.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Description, e.g. `me@example.com@imap.fastmail.com/Flow`.
+    pub fn describe(&self) -> String {
+        match self {
+            MailSource::Imap {
+                host,
+                user,
+                mailbox,
+                ..
+            } => format!("{}@{}/{}", user, host, mailbox),
+            MailSource::Maildir { path } => path.display().to_string(),
+        }
+    }
+}
+
+/// What an ingested message becomes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailTarget {
+    /// A block on today's journal page.
+    #[default]
+    Journal,
+    /// A page named after the subject.
+    Page,
+}
+
+/// A message waiting in a mailbox.
+///
+/// # Fields
+///
+/// - `key` (`String`) - Key of the message within its mailbox.
+/// - `raw` (`Vec<u8>`) - The message in RFC 5322 format.
+#[derive(Debug, Clone)]
+pub struct RawMail {
+    pub key: String,
+    pub raw: Vec<u8>,
+}
+
+/// A mailbox mail is ingested from.
+pub trait Mailbox {
+    /// Lists the messages not processed yet.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Mailbox`) - Mailbox to read.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<RawMail>>` - Unprocessed messages, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// The mailbox is unreachable or can't be read.
+    fn unprocessed(&mut self) -> Result<Vec<RawMail>>;
+
+    /// Marks a message as processed, so it isn't ingested again.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Mailbox`) - Mailbox of the message.
+    /// - `key` (`&str`) - Key of the message.
+    ///
+    /// # Errors
+    ///
+    /// The mailbox is unreachable or the message can't be updated.
+    fn mark_processed(&mut self, key: &str) -> Result<()>;
+}
+
+/// Opens the mailbox configured by the given settings.
+///
+/// # Arguments
+///
+/// - `settings` (`&MailSettings`) - Mail settings of a space.
+///
+/// # Returns
+///
+/// - `Result<Box<dyn Mailbox>>` - The opened mailbox.
+///
+/// # Errors
+///
+/// The password can't be resolved, the server is unreachable or rejects the
+/// login, or Flow was built without the `mail` feature for IMAP mailboxes.
+pub fn mailbox(settings: &MailSettings) -> Result<Box<dyn Mailbox>> {
+    match &settings.source {
+        #[cfg(feature = "mail")]
+        MailSource::Imap {
+            host,
+            port,
+            user,
+            password,
+            mailbox,
+        } => Ok(Box::new(imap::Imap::connect(
+            host,
+            *port,
+            user,
+            password.as_deref(),
+            mailbox,
+        )?)),
+        #[cfg(not(feature = "mail"))]
+        MailSource::Imap { .. } => {
+            miette::bail!("Reading IMAP mailboxes requires Flow built with the 'mail' feature")
+        }
+        MailSource::Maildir { path } => Ok(Box::new(maildir::Maildir::open(path)?)),
+    }
+}
+
+impl Space {
+    /// Returns the ingestion settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&IngestSettings` - Reference to the space's ingestion settings.
+    pub fn ingest_settings(&self) -> &IngestSettings {
+        &self.metadata.ingest
+    }
+}
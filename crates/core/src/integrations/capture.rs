@@ -0,0 +1,237 @@
+//! Issue Capture
+//!
+//! Captures issues into today's journal page as a block linking back to the
+//! issue, tagged `#issue` and with the issue's labels:
+//!
+//! ```markdown
+//! - **open** [mrbandler/flow#12](https://github.com/mrbandler/flow/issues/12) Sync over LAN #issue #enhancement
+//! ```
+//!
+//! The leading state marker (`open`, `closed` or `merged`) is refreshed for
+//! all captured issues by [`Space::sync_issues`].
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::{Issue, IssueState};
+use crate::page;
+use crate::space::Space;
+
+/// Tag marking captured issues.
+pub const ISSUE_TAG: &str = "issue";
+
+/// Change of a captured issue's state.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page holding the issue.
+/// - `url` (`String`) - URL of the issue.
+/// - `from` (`IssueState`) - Previous state marker.
+/// - `to` (`IssueState`) - Current state of the issue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct IssueUpdate {
+    pub id: String,
+    pub url: String,
+    pub from: IssueState,
+    pub to: IssueState,
+}
+
+/// Formats an issue as a block.
+///
+/// # Arguments
+///
+/// - `issue` (`&Issue`) - The issue.
+///
+/// # Returns
+///
+/// - `String` - The block, tagged `#issue` and with the issue's labels.
+pub fn block(issue: &Issue) -> String {
+    let mut block = format!(
+        "- **{}** [{}]({}) {} #{}",
+        issue.state.as_str(),
+        issue.reference,
+        issue.url,
+        issue.title,
+        ISSUE_TAG
+    );
+    for label in &issue.labels {
+        if label.contains(char::is_whitespace) {
+            block.push_str(&format!(" #[[{}]]", label));
+        } else {
+            block.push_str(&format!(" #{}", label));
+        }
+    }
+    block
+}
+
+/// Parses a captured issue block.
+///
+/// # Arguments
+///
+/// - `line` (`&str`) - Line of a page.
+///
+/// # Returns
+///
+/// - `Option<(IssueState, &str)>` - State marker and URL of the issue.
+fn captured(line: &str) -> Option<(IssueState, &str)> {
+    let text = line.trim_start().strip_prefix("- **")?;
+    if !page::tags(text).iter().any(|tag| tag == ISSUE_TAG) {
+        return None;
+    }
+    let (state, rest) = text.split_once("** [")?;
+    let (_, rest) = rest.split_once("](")?;
+    let (url, _) = rest.split_once(')')?;
+    Some((IssueState::parse(state)?, url))
+}
+
+impl Space {
+    /// Captures an issue into today's journal page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to capture into.
+    /// - `issue` (`&Issue`) - The fetched issue.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - Id of the journal page.
+    ///
+    /// # Errors
+    ///
+    /// The journal page is locked, or IO errors when writing files.
+    pub fn capture_issue(&mut self, issue: &Issue) -> Result<String> {
+        let id = self.today_page()?;
+        self.append(&id, &block(issue))?;
+        Ok(id)
+    }
+
+    /// Refreshes the state markers of all captured issues.
+    ///
+    /// Each issue is fetched once, even if it was captured several times.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space holding the captured issues.
+    /// - `fetch` (`impl FnMut(&str) -> Result<Issue>`) - Fetches an issue by its URL.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<IssueUpdate>>` - Issues whose state changed.
+    ///
+    /// # Errors
+    ///
+    /// Fetching an issue fails, a page holding a changed issue is locked, or
+    /// IO errors when writing files.
+    pub fn sync_issues(
+        &mut self,
+        mut fetch: impl FnMut(&str) -> Result<Issue>,
+    ) -> Result<Vec<IssueUpdate>> {
+        let mut states: Vec<(String, IssueState)> = Vec::new();
+        let mut updates = Vec::new();
+
+        // Every changed page is saved at once
+        self.transaction(|space| {
+            for id in space.page_ids()? {
+                let Some(content) = space.read_page(&id)? else {
+                    continue;
+                };
+
+                let mut changed = false;
+                let mut lines = Vec::new();
+                for line in content.lines() {
+                    let Some((marker, url)) = captured(line) else {
+                        lines.push(line.to_string());
+                        continue;
+                    };
+
+                    let state = match states.iter().find(|(known, _)| known == url) {
+                        Some((_, state)) => *state,
+                        None => {
+                            let state = fetch(url)?.state;
+                            states.push((url.to_string(), state));
+                            state
+                        }
+                    };
+                    if state == marker {
+                        lines.push(line.to_string());
+                        continue;
+                    }
+
+                    let prefix = format!("**{}**", marker.as_str());
+                    lines.push(line.replacen(&prefix, &format!("**{}**", state.as_str()), 1));
+                    updates.push(IssueUpdate {
+                        id: id.clone(),
+                        url: url.to_string(),
+                        from: marker,
+                        to: state,
+                    });
+                    changed = true;
+                }
+
+                if changed {
+                    let mut updated = lines.join("\n");
+                    if content.ends_with('\n') {
+                        updated.push('\n');
+                    }
+                    space.write_page(&id, &updated)?;
+                }
+            }
+
+            Ok(updates)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://github.com/mrbandler/flow/pull/12";
+
+    fn issue(state: IssueState) -> Issue {
+        Issue {
+            url: URL.to_string(),
+            reference: "mrbandler/flow#12".to_string(),
+            title: "Sync over LAN".to_string(),
+            state,
+            labels: vec!["enhancement".to_string(), "good first issue".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_block() {
+        let block = block(&issue(IssueState::Open));
+        assert_eq!(
+            block,
+            "- **open** [mrbandler/flow#12](https://github.com/mrbandler/flow/pull/12) Sync over LAN #issue #enhancement #[[good first issue]]"
+        );
+        assert_eq!(captured(&block), Some((IssueState::Open, URL)));
+        assert_eq!(
+            captured("- **open** [link](https://example.com) no tag"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sync_issues_updates_markers() {
+        let mut space = Space::in_memory().unwrap();
+        let id = space.capture_issue(&issue(IssueState::Open)).unwrap();
+
+        let updates = space
+            .sync_issues(|_| Ok(issue(IssueState::Merged)))
+            .unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].to, IssueState::Merged);
+        assert!(space
+            .read_page(&id)
+            .unwrap()
+            .unwrap()
+            .contains("- **merged** ["));
+
+        let updates = space
+            .sync_issues(|_| Ok(issue(IssueState::Merged)))
+            .unwrap();
+        assert!(updates.is_empty());
+    }
+}
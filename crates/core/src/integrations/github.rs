@@ -0,0 +1,154 @@
+//! GitHub issues and pull requests.
+
+use miette::{IntoDiagnostic, Result};
+use serde::Deserialize;
+
+use super::{split_url, Issue, IssueProvider, IssueState, ProviderSettings};
+
+/// Host of github.com, used unless a GitHub Enterprise host is configured.
+const DEFAULT_HOST: &str = "github.com";
+
+/// GitHub provider.
+///
+/// # Fields
+///
+/// - `settings` (`ProviderSettings`) - Host and token.
+pub struct GitHub {
+    settings: ProviderSettings,
+}
+
+impl GitHub {
+    /// Creates the provider.
+    ///
+    /// # Arguments
+    ///
+    /// - `settings` (`ProviderSettings`) - Host and token.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The provider.
+    pub fn new(settings: ProviderSettings) -> Self {
+        Self { settings }
+    }
+
+    fn host(&self) -> &str {
+        self.settings.host.as_deref().unwrap_or(DEFAULT_HOST)
+    }
+
+    /// Parses `https://<host>/<owner>/<repo>/issues/<n>` (or `/pull/<n>`).
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(&str, bool, u64)>` - Repository, whether it is a pull request, and number.
+    fn parse<'a>(&self, url: &'a str) -> Option<(&'a str, bool, u64)> {
+        let (host, path) = split_url(url)?;
+        if host != self.host() {
+            return None;
+        }
+        let (path, number) = path.rsplit_once('/')?;
+        let (repo, kind) = path.rsplit_once('/')?;
+        let pull = match kind {
+            "issues" => false,
+            "pull" => true,
+            _ => return None,
+        };
+        if repo.split('/').count() != 2 {
+            return None;
+        }
+        Some((repo, pull, number.parse().ok()?))
+    }
+}
+
+impl IssueProvider for GitHub {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        self.parse(url).is_some()
+    }
+
+    fn fetch(&self, url: &str) -> Result<Issue> {
+        #[derive(Deserialize)]
+        struct Label {
+            name: String,
+        }
+        #[derive(Deserialize)]
+        struct PullRequest {
+            merged_at: Option<String>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            title: String,
+            state: String,
+            labels: Vec<Label>,
+            pull_request: Option<PullRequest>,
+        }
+
+        let Some((repo, pull, number)) = self.parse(url) else {
+            miette::bail!("'{}' is not the URL of a GitHub issue", url);
+        };
+        let api = if self.host() == DEFAULT_HOST {
+            "https://api.github.com".to_string()
+        } else {
+            format!("https://{}/api/v3", self.host())
+        };
+
+        // Pull requests are issues as well, the issues endpoint serves both
+        let mut request = ureq::get(&format!("{}/repos/{}/issues/{}", api, repo, number))
+            .set("Accept", "application/vnd.github+json");
+        if let Some(token) = self.settings.token(self.name())? {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        let response: Response = request
+            .call()
+            .into_diagnostic()?
+            .into_json()
+            .into_diagnostic()?;
+
+        let state = if response
+            .pull_request
+            .is_some_and(|pull| pull.merged_at.is_some())
+        {
+            IssueState::Merged
+        } else if response.state == "open" {
+            IssueState::Open
+        } else {
+            IssueState::Closed
+        };
+        Ok(Issue {
+            url: format!(
+                "https://{}/{}/{}/{}",
+                self.host(),
+                repo,
+                if pull { "pull" } else { "issues" },
+                number
+            ),
+            reference: format!("{}#{}", repo, number),
+            title: response.title,
+            state,
+            labels: response
+                .labels
+                .into_iter()
+                .map(|label| label.name)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let github = GitHub::new(ProviderSettings::default());
+        assert_eq!(
+            github.parse("https://github.com/mrbandler/flow/pull/12#issuecomment-1"),
+            Some(("mrbandler/flow", true, 12))
+        );
+        assert!(github.matches("https://github.com/mrbandler/flow/issues/3/"));
+        assert!(!github.matches("https://github.com/mrbandler/flow/tree/12"));
+        assert!(!github.matches("https://example.com/mrbandler/flow/issues/12"));
+    }
+}
@@ -0,0 +1,130 @@
+//! GitLab issues and merge requests.
+
+use miette::{IntoDiagnostic, Result};
+use serde::Deserialize;
+
+use super::{split_url, Issue, IssueProvider, IssueState, ProviderSettings};
+
+/// Host of gitlab.com, used unless a self-hosted instance is configured.
+const DEFAULT_HOST: &str = "gitlab.com";
+
+/// GitLab provider.
+///
+/// # Fields
+///
+/// - `settings` (`ProviderSettings`) - Host and token.
+pub struct GitLab {
+    settings: ProviderSettings,
+}
+
+impl GitLab {
+    /// Creates the provider.
+    ///
+    /// # Arguments
+    ///
+    /// - `settings` (`ProviderSettings`) - Host and token.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The provider.
+    pub fn new(settings: ProviderSettings) -> Self {
+        Self { settings }
+    }
+
+    fn host(&self) -> &str {
+        self.settings.host.as_deref().unwrap_or(DEFAULT_HOST)
+    }
+
+    /// Parses `https://<host>/<group>/<project>/-/issues/<n>` (or `/-/merge_requests/<n>`).
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(&str, bool, u64)>` - Project path, whether it is a merge request, and number.
+    fn parse<'a>(&self, url: &'a str) -> Option<(&'a str, bool, u64)> {
+        let (host, path) = split_url(url)?;
+        if host != self.host() {
+            return None;
+        }
+        let (path, number) = path.rsplit_once('/')?;
+        let (project, kind) = path.split_once("/-/")?;
+        let merge = match kind {
+            "issues" => false,
+            "merge_requests" => true,
+            _ => return None,
+        };
+        Some((project, merge, number.parse().ok()?))
+    }
+}
+
+impl IssueProvider for GitLab {
+    fn name(&self) -> &'static str {
+        "gitlab"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        self.parse(url).is_some()
+    }
+
+    fn fetch(&self, url: &str) -> Result<Issue> {
+        #[derive(Deserialize)]
+        struct Response {
+            title: String,
+            state: String,
+            labels: Vec<String>,
+        }
+
+        let Some((project, merge, number)) = self.parse(url) else {
+            miette::bail!("'{}' is not the URL of a GitLab issue", url);
+        };
+        let kind = if merge { "merge_requests" } else { "issues" };
+
+        let mut request = ureq::get(&format!(
+            "https://{}/api/v4/projects/{}/{}/{}",
+            self.host(),
+            project.replace('/', "%2F"),
+            kind,
+            number
+        ));
+        if let Some(token) = self.settings.token(self.name())? {
+            request = request.set("PRIVATE-TOKEN", &token);
+        }
+        let response: Response = request
+            .call()
+            .into_diagnostic()?
+            .into_json()
+            .into_diagnostic()?;
+
+        // Besides `opened`, `closed` and `merged` there's `locked`, a closed discussion
+        let state = match response.state.as_str() {
+            "opened" => IssueState::Open,
+            "merged" => IssueState::Merged,
+            _ => IssueState::Closed,
+        };
+        Ok(Issue {
+            url: format!("https://{}/{}/-/{}/{}", self.host(), project, kind, number),
+            reference: format!("{}{}{}", project, if merge { '!' } else { '#' }, number),
+            title: response.title,
+            state,
+            labels: response.labels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let gitlab = GitLab::new(ProviderSettings {
+            host: Some("gitlab.example.com".to_string()),
+            ..ProviderSettings::default()
+        });
+        assert_eq!(
+            gitlab.parse("https://gitlab.example.com/group/sub/app/-/merge_requests/3"),
+            Some(("group/sub/app", true, 3))
+        );
+        assert!(!gitlab.matches("https://gitlab.com/group/app/-/issues/3"));
+        assert!(!gitlab.matches("https://gitlab.example.com/group/app/-/tree/3"));
+    }
+}
@@ -0,0 +1,152 @@
+//! Jira issues.
+
+use base64::Engine;
+use miette::{IntoDiagnostic, Result};
+use serde::Deserialize;
+
+use super::{split_url, Issue, IssueProvider, IssueState, ProviderSettings};
+
+/// Host suffix of Atlassian Cloud sites, matched without configuration.
+const CLOUD_SUFFIX: &str = ".atlassian.net";
+
+/// Jira provider.
+///
+/// Jira Cloud authenticates with the account email and an API token, Jira
+/// Data Center with a personal access token alone.
+///
+/// # Fields
+///
+/// - `settings` (`ProviderSettings`) - Host, email and token.
+pub struct Jira {
+    settings: ProviderSettings,
+}
+
+impl Jira {
+    /// Creates the provider.
+    ///
+    /// # Arguments
+    ///
+    /// - `settings` (`ProviderSettings`) - Host, email and token.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The provider.
+    pub fn new(settings: ProviderSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Parses `https://<host>/browse/<KEY-n>`.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(&str, &str)>` - Host and issue key.
+    fn parse<'a>(&self, url: &'a str) -> Option<(&'a str, &'a str)> {
+        let (host, path) = split_url(url)?;
+        let known = match &self.settings.host {
+            Some(configured) => host == configured,
+            None => host.ends_with(CLOUD_SUFFIX),
+        };
+        if !known {
+            return None;
+        }
+        let key = path.strip_prefix("browse/")?;
+        let (project, number) = key.rsplit_once('-')?;
+        if project.is_empty() || key.contains('/') || number.parse::<u64>().is_err() {
+            return None;
+        }
+        Some((host, key))
+    }
+}
+
+impl IssueProvider for Jira {
+    fn name(&self) -> &'static str {
+        "jira"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        self.parse(url).is_some()
+    }
+
+    fn fetch(&self, url: &str) -> Result<Issue> {
+        #[derive(Deserialize)]
+        struct Category {
+            key: String,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Status {
+            status_category: Category,
+        }
+        #[derive(Deserialize)]
+        struct Fields {
+            summary: String,
+            status: Status,
+            #[serde(default)]
+            labels: Vec<String>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            fields: Fields,
+        }
+
+        let Some((host, key)) = self.parse(url) else {
+            miette::bail!("'{}' is not the URL of a Jira issue", url);
+        };
+
+        let mut request = ureq::get(&format!(
+            "https://{}/rest/api/2/issue/{}?fields=summary,status,labels",
+            host, key
+        ));
+        if let Some(token) = self.settings.token(self.name())? {
+            let authorization = match &self.settings.email {
+                Some(email) => format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD
+                        .encode(format!("{}:{}", email, token))
+                ),
+                None => format!("Bearer {}", token),
+            };
+            request = request.set("Authorization", &authorization);
+        }
+        let response: Response = request
+            .call()
+            .into_diagnostic()?
+            .into_json()
+            .into_diagnostic()?;
+
+        let fields = response.fields;
+        Ok(Issue {
+            url: format!("https://{}/browse/{}", host, key),
+            reference: key.to_string(),
+            title: fields.summary,
+            state: if fields.status.status_category.key == "done" {
+                IssueState::Closed
+            } else {
+                IssueState::Open
+            },
+            labels: fields.labels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let jira = Jira::new(ProviderSettings::default());
+        assert_eq!(
+            jira.parse("https://example.atlassian.net/browse/ENG-42?focusedCommentId=1"),
+            Some(("example.atlassian.net", "ENG-42"))
+        );
+        assert!(!jira.matches("https://example.atlassian.net/browse/ENG"));
+        assert!(!jira.matches("https://jira.example.com/browse/ENG-42"));
+
+        let server = Jira::new(ProviderSettings {
+            host: Some("jira.example.com".to_string()),
+            ..ProviderSettings::default()
+        });
+        assert!(server.matches("https://jira.example.com/browse/ENG-42"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Linear issues.
+
+use miette::{IntoDiagnostic, Result};
+use serde::Deserialize;
+
+use super::{split_url, Issue, IssueProvider, IssueState, ProviderSettings};
+
+/// Host of Linear's web app.
+const HOST: &str = "linear.app";
+
+/// Endpoint of Linear's GraphQL API.
+const API: &str = "https://api.linear.app/graphql";
+
+/// Query fetching an issue by its identifier.
+const QUERY: &str =
+    "query($id: String!) { issue(id: $id) { title state { type } labels { nodes { name } } } }";
+
+/// Linear provider.
+///
+/// # Fields
+///
+/// - `settings` (`ProviderSettings`) - API key.
+pub struct Linear {
+    settings: ProviderSettings,
+}
+
+impl Linear {
+    /// Creates the provider.
+    ///
+    /// # Arguments
+    ///
+    /// - `settings` (`ProviderSettings`) - API key.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The provider.
+    pub fn new(settings: ProviderSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Parses `https://linear.app/<workspace>/issue/<ID-n>/<slug>`.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(&str, &str)>` - Workspace and issue identifier.
+    fn parse<'a>(&self, url: &'a str) -> Option<(&'a str, &'a str)> {
+        let (host, path) = split_url(url)?;
+        if host != HOST {
+            return None;
+        }
+        let mut segments = path.split('/');
+        let workspace = segments.next()?;
+        if segments.next()? != "issue" {
+            return None;
+        }
+        let id = segments.next()?;
+        let (_, number) = id.rsplit_once('-')?;
+        number.parse::<u64>().ok()?;
+        Some((workspace, id))
+    }
+}
+
+impl IssueProvider for Linear {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        self.parse(url).is_some()
+    }
+
+    fn fetch(&self, url: &str) -> Result<Issue> {
+        #[derive(Deserialize)]
+        struct Label {
+            name: String,
+        }
+        #[derive(Deserialize)]
+        struct Labels {
+            nodes: Vec<Label>,
+        }
+        #[derive(Deserialize)]
+        struct State {
+            #[serde(rename = "type")]
+            kind: String,
+        }
+        #[derive(Deserialize)]
+        struct Node {
+            title: String,
+            state: State,
+            labels: Labels,
+        }
+        #[derive(Deserialize)]
+        struct Data {
+            issue: Option<Node>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            data: Option<Data>,
+        }
+
+        let Some((workspace, id)) = self.parse(url) else {
+            miette::bail!("'{}' is not the URL of a Linear issue", url);
+        };
+        let Some(token) = self.settings.token(self.name())? else {
+            miette::bail!("Linear needs an API key, add it with 'flow auth set linear'");
+        };
+
+        let response: Response = ureq::post(API)
+            .set("Authorization", &token)
+            .send_json(serde_json::json!({ "query": QUERY, "variables": { "id": id } }))
+            .into_diagnostic()?
+            .into_json()
+            .into_diagnostic()?;
+        let Some(issue) = response.data.and_then(|data| data.issue) else {
+            miette::bail!("Linear issue {} not found", id);
+        };
+
+        Ok(Issue {
+            url: format!("https://{}/{}/issue/{}", HOST, workspace, id),
+            reference: id.to_string(),
+            title: issue.title,
+            state: match issue.state.kind.as_str() {
+                "completed" | "canceled" => IssueState::Closed,
+                _ => IssueState::Open,
+            },
+            labels: issue
+                .labels
+                .nodes
+                .into_iter()
+                .map(|label| label.name)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let linear = Linear::new(ProviderSettings::default());
+        assert_eq!(
+            linear.parse("https://linear.app/acme/issue/ENG-42/fix-the-sync"),
+            Some(("acme", "ENG-42"))
+        );
+        assert!(!linear.matches("https://linear.app/acme/project/sync-42"));
+        assert!(!linear.matches("https://example.com/acme/issue/ENG-42"));
+    }
+}
@@ -0,0 +1,282 @@
+//! Integrations
+//!
+//! Brings context from work trackers into daily notes. Issues of GitHub,
+//! GitLab, Jira and Linear are fetched by an [`IssueProvider`] each and
+//! captured as blocks linking back to them (see [`capture`]).
+//!
+//! Providers are configured per space in the space metadata. Tokens are
+//! usually `secret:` references and default to the secret named after the
+//! provider, e.g. `flow auth set linear`:
+//!
+//! ```toml
+//! [integrations.gitlab]
+//! host = "gitlab.example.com"
+//!
+//! [integrations.jira]
+//! host = "example.atlassian.net"
+//! email = "me@example.com"
+//! token = "secret:jira-work"
+//! ```
+//!
+//! Fetching issues needs Flow built with the `integrations` feature.
+
+pub mod capture;
+#[cfg(feature = "integrations")]
+pub mod github;
+#[cfg(feature = "integrations")]
+pub mod gitlab;
+#[cfg(feature = "integrations")]
+pub mod jira;
+#[cfg(feature = "integrations")]
+pub mod linear;
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::secrets::Secrets;
+use crate::space::Space;
+
+/// Fetches issues from a work tracker.
+pub trait IssueProvider: Send + Sync {
+    /// Name of the provider, as used in the space metadata.
+    ///
+    /// # Returns
+    ///
+    /// - `&str` - E.g. `github`.
+    fn name(&self) -> &'static str;
+
+    /// Checks whether a URL points to an issue of this provider.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`IssueProvider`) - Provider to check.
+    /// - `url` (`&str`) - URL to check.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the provider can fetch the issue.
+    fn matches(&self, url: &str) -> bool;
+
+    /// Fetches an issue.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`IssueProvider`) - Provider to fetch from.
+    /// - `url` (`&str`) - URL of the issue.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Issue>` - The issue.
+    ///
+    /// # Errors
+    ///
+    /// The tracker is unreachable, the issue doesn't exist or isn't accessible.
+    fn fetch(&self, url: &str) -> Result<Issue>;
+}
+
+/// State of an issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+    Open,
+    Closed,
+    Merged,
+}
+
+impl IssueState {
+    /// Parses a state as written as marker.
+    ///
+    /// # Arguments
+    ///
+    /// - `state` (`&str`) - `open`, `closed` or `merged`.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<Self>` - The state, `None` for unknown markers.
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "open" => Some(IssueState::Open),
+            "closed" => Some(IssueState::Closed),
+            "merged" => Some(IssueState::Merged),
+            _ => None,
+        }
+    }
+
+    /// Returns the marker of the state.
+    ///
+    /// # Returns
+    ///
+    /// - `&str` - `open`, `closed` or `merged`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+            IssueState::Merged => "merged",
+        }
+    }
+}
+
+/// An issue fetched from its tracker.
+///
+/// # Fields
+///
+/// - `url` (`String`) - Canonical URL of the issue.
+/// - `reference` (`String`) - Short reference, e.g. `mrbandler/flow#12` or `ENG-42`.
+/// - `title` (`String`) - Title of the issue.
+/// - `state` (`IssueState`) - Whether the issue is open, closed or merged.
+/// - `labels` (`Vec<String>`) - Labels of the issue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Issue {
+    pub url: String,
+    pub reference: String,
+    pub title: String,
+    pub state: IssueState,
+    pub labels: Vec<String>,
+}
+
+/// Integration settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `github` (`ProviderSettings`) - GitHub, or GitHub Enterprise with a `host`.
+/// - `gitlab` (`ProviderSettings`) - GitLab, `gitlab.com` unless a `host` is set.
+/// - `jira` (`ProviderSettings`) - Jira, needs a `host` outside of Atlassian Cloud.
+/// - `linear` (`ProviderSettings`) - Linear.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrationSettings {
+    #[serde(default)]
+    pub github: ProviderSettings,
+    #[serde(default)]
+    pub gitlab: ProviderSettings,
+    #[serde(default)]
+    pub jira: ProviderSettings,
+    #[serde(default)]
+    pub linear: ProviderSettings,
+}
+
+/// Settings of a single provider.
+///
+/// # Fields
+///
+/// - `host` (`Option<String>`) - Host of a self-hosted instance.
+/// - `email` (`Option<String>`) - Account email, used by Jira Cloud together with the token.
+/// - `token` (`Option<String>`) - Access token, usually a `secret:` reference.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderSettings {
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl ProviderSettings {
+    /// Resolves the access token of a provider.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`ProviderSettings`) - Settings of the provider.
+    /// - `provider` (`&str`) - Name of the provider, the secret used if no token is configured.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<String>>` - The token, `None` if none is configured or stored.
+    ///
+    /// # Errors
+    ///
+    /// The secrets can't be read, or a referenced secret doesn't exist.
+    pub fn token(&self, provider: &str) -> Result<Option<String>> {
+        let secrets = Secrets::open()?;
+        match &self.token {
+            Some(token) => secrets.resolve(token).map(Some),
+            None => secrets.get(provider),
+        }
+    }
+}
+
+/// Creates the providers configured by the given settings.
+///
+/// # Arguments
+///
+/// - `settings` (`&IntegrationSettings`) - Integration settings of a space.
+///
+/// # Returns
+///
+/// - `Result<Vec<Box<dyn IssueProvider>>>` - All providers.
+///
+/// # Errors
+///
+/// Flow was built without the `integrations` feature.
+pub fn providers(settings: &IntegrationSettings) -> Result<Vec<Box<dyn IssueProvider>>> {
+    #[cfg(feature = "integrations")]
+    {
+        Ok(vec![
+            Box::new(github::GitHub::new(settings.github.clone())),
+            Box::new(gitlab::GitLab::new(settings.gitlab.clone())),
+            Box::new(jira::Jira::new(settings.jira.clone())),
+            Box::new(linear::Linear::new(settings.linear.clone())),
+        ])
+    }
+    #[cfg(not(feature = "integrations"))]
+    {
+        let _ = settings;
+        miette::bail!("Fetching issues requires Flow built with the 'integrations' feature")
+    }
+}
+
+/// Fetches an issue with the first provider matching its URL.
+///
+/// # Arguments
+///
+/// - `providers` (`&[Box<dyn IssueProvider>]`) - Providers to choose from.
+/// - `url` (`&str`) - URL of the issue.
+///
+/// # Returns
+///
+/// - `Result<Issue>` - The issue.
+///
+/// # Errors
+///
+/// No provider matches the URL, or fetching fails.
+pub fn fetch(providers: &[Box<dyn IssueProvider>], url: &str) -> Result<Issue> {
+    let Some(provider) = providers.iter().find(|provider| provider.matches(url)) else {
+        miette::bail!(
+            "'{}' is not the URL of a GitHub, GitLab, Jira or Linear issue",
+            url
+        );
+    };
+    provider.fetch(url)
+}
+
+/// Splits an issue URL into host and path.
+///
+/// The scheme, query, fragment and trailing slashes are dropped.
+///
+/// # Arguments
+///
+/// - `url` (`&str`) - URL to split.
+///
+/// # Returns
+///
+/// - `Option<(&str, &str)>` - Host and path, `None` if the URL isn't HTTP(S).
+#[cfg(feature = "integrations")]
+fn split_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let rest = rest.split(['?', '#']).next()?.trim_end_matches('/');
+    rest.split_once('/')
+}
+
+impl Space {
+    /// Returns the integration settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&IntegrationSettings` - Reference to the space's integration settings.
+    pub fn integration_settings(&self) -> &IntegrationSettings {
+        &self.metadata.integrations
+    }
+}
@@ -0,0 +1,397 @@
+//! Journal Ranges
+//!
+//! Reading consecutive journal pages, e.g. to review a week at once, and
+//! creating journal pages for a given day. The journal activity, how much was
+//! written on each day, comes from the page index and reads no page content.
+//!
+//! New journal pages start from the daily template, the page named
+//! `templates/daily`, if it exists. `{{date}}` in the template is replaced
+//! with the name of the new page.
+//!
+//! A space keeps one journal page per day by default. It can switch to one
+//! page per week (named `YYYY-Www` after the ISO week) or per month (named
+//! `YYYY-MM`) in the space metadata; pages written before a switch keep
+//! their names and stay part of the journal:
+//!
+//! ```toml
+//! [journal]
+//! granularity = "weekly"
+//! ```
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use miette::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::page;
+use crate::space::Space;
+
+/// Name of the page new journal pages are created from.
+const DAILY_TEMPLATE: &str = "templates/daily";
+
+/// How much time a journal page covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    /// One page per day, named `YYYY-MM-DD`.
+    #[default]
+    Daily,
+    /// One page per ISO week, named `YYYY-Www`.
+    Weekly,
+    /// One page per month, named `YYYY-MM`.
+    Monthly,
+}
+
+impl Granularity {
+    /// Returns the name of the journal page covering a day.
+    ///
+    /// # Arguments
+    ///
+    /// - `date` (`NaiveDate`) - Day to find the page of.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Name of the journal page.
+    pub fn page_name(self, date: NaiveDate) -> String {
+        match self {
+            Self::Daily => date.format("%Y-%m-%d").to_string(),
+            Self::Weekly => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Self::Monthly => date.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Journal settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `granularity` (`Granularity`) - How much time a new journal page covers.
+/// - `carry_over` (`bool`) - Whether the first capture of a day carries open tasks over (see [`rollover`](crate::rollover)).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalSettings {
+    #[serde(default)]
+    pub granularity: Granularity,
+    #[serde(default)]
+    pub carry_over: bool,
+}
+
+/// Returns the days covered by a journal page.
+///
+/// Understands the names of all granularities, so a journal mixing daily,
+/// weekly and monthly pages reads as one.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name of the page.
+///
+/// # Returns
+///
+/// - `Option<(NaiveDate, NaiveDate)>` - First and last day, `None` if the name is no journal date.
+pub fn period(name: &str) -> Option<(NaiveDate, NaiveDate)> {
+    if let Ok(date) = NaiveDate::parse_from_str(name, "%Y-%m-%d") {
+        return Some((date, date));
+    }
+    if let Some((year, week)) = name.split_once("-W") {
+        if year.len() != 4 || week.len() != 2 {
+            return None;
+        }
+        let first =
+            NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)?;
+        return Some((first, first + Days::new(6)));
+    }
+
+    let (year, month) = name.split_once('-')?;
+    if year.len() != 4 || month.len() != 2 {
+        return None;
+    }
+    let first = NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)?;
+    let last = first.checked_add_months(Months::new(1))? - Days::new(1);
+    Some((first, last))
+}
+
+/// A single journal page.
+///
+/// # Fields
+///
+/// - `date` (`NaiveDate`) - First day covered by the journal page.
+/// - `id` (`String`) - Id of the page.
+/// - `content` (`String`) - Markdown content of the page.
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalDay {
+    pub date: NaiveDate,
+    pub id: String,
+    pub content: String,
+}
+
+/// Returns the week a day falls into.
+///
+/// # Arguments
+///
+/// - `date` (`NaiveDate`) - Any day of the week.
+/// - `start` (`Weekday`) - Day weeks start on.
+///
+/// # Returns
+///
+/// - `(NaiveDate, NaiveDate)` - First and last day of the week.
+pub fn week(date: NaiveDate, start: Weekday) -> (NaiveDate, NaiveDate) {
+    let first = date.week(start).first_day();
+    (first, first + Days::new(6))
+}
+
+impl Space {
+    /// Returns the journal settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&JournalSettings` - Reference to the space's journal settings.
+    pub fn journal_settings(&self) -> &JournalSettings {
+        &self.metadata.journal
+    }
+
+    /// Replaces the journal settings and writes the space metadata.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to configure.
+    /// - `settings` (`JournalSettings`) - The new settings.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the metadata.
+    pub fn set_journal_settings(&mut self, settings: JournalSettings) -> Result<()> {
+        self.metadata.journal = settings;
+        self.metadata.write(&*self.storage, &self.path)
+    }
+
+    /// Returns the daily template new journal pages start from.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<String>>` - Markdown content of the template, `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the template.
+    pub fn daily_template(&self) -> Result<Option<String>> {
+        self.read_page(&page::id_from_name(DAILY_TEMPLATE))
+    }
+
+    /// Replaces the daily template new journal pages start from.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to configure.
+    /// - `content` (`&str`) - Markdown content of the template, `{{date}}` is replaced with the page name.
+    ///
+    /// # Errors
+    ///
+    /// The template page is locked, or IO errors when writing it.
+    pub fn set_daily_template(&mut self, content: &str) -> Result<()> {
+        self.write_page(&page::id_from_name(DAILY_TEMPLATE), content)
+    }
+
+    /// Returns the id of the journal page covering a day.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the page belongs to.
+    /// - `date` (`NaiveDate`) - Day to find the page of.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Id of the page, which may not exist yet.
+    pub fn journal_page(&self, date: NaiveDate) -> String {
+        page::journal_id(&self.metadata.journal.granularity.page_name(date))
+    }
+
+    /// Returns the id of a page with the given name.
+    ///
+    /// Like [`page::id_from_name`], but week and month names also map to
+    /// journal pages if the journal uses them or such a journal page exists.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the page belongs to.
+    /// - `name` (`&str`) - Name of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Id of the page, which may not exist yet.
+    pub fn page_id(&self, name: &str) -> String {
+        let id = page::journal_id(name);
+        match period(name) {
+            Some((first, _))
+                if self.metadata.journal.granularity.page_name(first) == name
+                    || self.storage.exists(&self.path.join(&id)) =>
+            {
+                id
+            }
+            _ => page::id_from_name(name),
+        }
+    }
+
+    /// Returns the journal pages within a date range, oldest first.
+    ///
+    /// Pages covering any day of the range are included, days without a
+    /// journal page are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to read from.
+    /// - `since` (`Option<NaiveDate>`) - First day to include, unbounded if `None`.
+    /// - `until` (`Option<NaiveDate>`) - Last day to include, unbounded if `None`.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<JournalDay>>` - Journal pages in the range.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages from disk.
+    pub fn journal(
+        &self,
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> Result<Vec<JournalDay>> {
+        let mut days = Vec::new();
+
+        for id in self.page_ids()? {
+            if !page::is_journal(&id) {
+                continue;
+            }
+            let Some((date, last)) = period(&page::name_from_id(&id)) else {
+                continue;
+            };
+            if since.is_some_and(|since| last < since) || until.is_some_and(|until| date > until) {
+                continue;
+            }
+
+            if let Some(content) = self.read_page(&id)? {
+                days.push(JournalDay { date, id, content });
+            }
+        }
+
+        days.sort_by_key(|day| day.date);
+        Ok(days)
+    }
+
+    /// Returns the number of blocks written on each day with a journal page.
+    ///
+    /// Pages covering more than a day count for their first day.
+    ///
+    /// Backed by the page index, so even long journals are read without
+    /// touching their pages.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to read from.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<BTreeMap<NaiveDate, usize>>` - Blocks per day, empty pages count as one.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index has to be rebuilt.
+    pub fn journal_activity(&self) -> Result<BTreeMap<NaiveDate, usize>> {
+        Ok(self
+            .page_index()?
+            .pages()
+            .filter(|entry| page::is_journal(&entry.id))
+            .filter_map(|entry| {
+                let (date, _) = period(&entry.name)?;
+                Some((date, entry.blocks.max(1)))
+            })
+            .collect())
+    }
+
+    /// Creates the journal page covering a day from the daily template.
+    ///
+    /// Does nothing if the page already exists. Creating today's page starts
+    /// the day (see [`Space::today_page`]).
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to create the page in.
+    /// - `date` (`NaiveDate`) - Day covered by the journal page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<bool>` - True if the page was created.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the template or writing the page, or starting
+    /// the day fails.
+    pub fn create_journal_page(&mut self, date: NaiveDate) -> Result<bool> {
+        let name = self.metadata.journal.granularity.page_name(date);
+        let id = page::journal_id(&name);
+        if self.read_page(&id)?.is_some() {
+            return Ok(false);
+        }
+
+        let template = self.daily_template()?.unwrap_or_default();
+        // The page and what starting the day carries over are saved at once
+        self.transaction(|space| {
+            space.write_page(&id, &template.replace("{{date}}", &name))?;
+            if id == space.journal_page(space.today()) {
+                space.start_day(space.today())?;
+            }
+            Ok(true)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_week() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let day = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+
+        assert_eq!(week(date, Weekday::Mon), (day(13), day(19)));
+        assert_eq!(week(date, Weekday::Sun), (day(12), day(18)));
+        assert_eq!(week(day(12), Weekday::Sun), (day(12), day(18)));
+        assert_eq!(week(date, Weekday::Sat), (day(11), day(17)));
+    }
+
+    #[test]
+    fn test_period() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let day = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+
+        for granularity in [
+            Granularity::Daily,
+            Granularity::Weekly,
+            Granularity::Monthly,
+        ] {
+            let (first, last) = period(&granularity.page_name(date)).unwrap();
+            assert!(first <= date && date <= last);
+        }
+        assert_eq!(Granularity::Weekly.page_name(date), "2024-W20");
+        assert_eq!(period("2024-W20"), Some((day(13), day(19))));
+        assert_eq!(period("2024-05"), Some((day(1), day(31))));
+        assert_eq!(period("2024-W5"), None);
+        assert_eq!(period("notes"), None);
+    }
+
+    #[test]
+    fn test_page_id_follows_granularity() {
+        let mut space = Space::in_memory().unwrap();
+        assert_eq!(space.page_id("2023-10"), "pages/2023-10.md");
+        assert_eq!(space.page_id("2023-10-02"), "journal/2023-10-02.md");
+
+        space.metadata.journal.granularity = Granularity::Monthly;
+        assert_eq!(space.page_id("2023-10"), "journal/2023-10.md");
+        assert_eq!(space.page_id("2023-W41"), "pages/2023-W41.md");
+
+        space.write_page("journal/2023-W41.md", "- Week").unwrap();
+        assert_eq!(space.page_id("2023-W41"), "journal/2023-W41.md");
+    }
+}
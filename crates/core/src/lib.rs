@@ -1,2 +1,87 @@
+//! Flow Core
+//!
+//! The graph engine behind Flow: spaces of markdown pages kept in a CRDT
+//! document, with indexes, search, sync and the features built on top.
+//!
+//! Tools depending on this crate should use the [`api`] module, the stable
+//! surface covered by semantic versioning. The other modules are the building
+//! blocks of Flow's own frontends and may change in any release; modules only
+//! the terminal frontends need are hidden from the documentation.
+
+pub mod ai;
+pub mod api;
+pub mod archive;
+pub mod attach;
+pub mod attribution;
+pub mod audio;
+pub mod backup;
+pub mod cancel;
+pub mod cards;
+pub mod checkpoints;
+pub mod clean;
+pub mod clip;
+pub mod clock;
+pub(crate) mod compact;
 pub mod config;
+pub mod conflicts;
+pub mod context;
+pub mod dedupe;
+pub mod diff;
+#[doc(hidden)]
+pub mod display;
+pub(crate) mod embed;
+#[cfg(feature = "semantic")]
+pub mod embedding;
+pub mod error;
+pub mod feed;
+pub(crate) mod format;
+pub mod fsck;
+pub mod fulltext;
+#[doc(hidden)]
+pub mod fuzzy;
+pub(crate) mod hooks;
+pub mod index;
+pub mod ingest;
+pub mod integrations;
+pub mod journal;
+pub mod links;
+pub(crate) mod lock;
+pub mod mentions;
+pub mod merge;
+#[doc(hidden)]
+pub mod migration;
+pub mod namespace;
+pub mod ocr;
+pub mod org;
+pub mod outline;
+pub mod page;
+pub mod paths;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod progress;
+pub mod project;
+pub mod publish;
+pub mod recent;
+pub mod recovery;
+pub mod reminders;
+pub mod render;
+pub mod repo;
+pub mod resurface;
+pub mod rollover;
+pub mod search;
+pub mod secrets;
+pub(crate) mod semantic;
 pub mod space;
+pub mod split;
+pub mod stats;
+pub mod status;
+pub mod storage;
+pub mod sync;
+pub mod tagging;
+pub mod template;
+#[doc(hidden)]
+pub mod timestamps;
+pub(crate) mod transaction;
+pub mod wordcount;
+
+pub use error::Error;
@@ -0,0 +1,342 @@
+//! Link Checking
+//!
+//! Reports wikilinks and embeds pointing to pages that don't exist and pages
+//! that are cut off from the rest of the space. The report is computed from the page
+//! index alone, so checking even large spaces reads no page content.
+//!
+//! The same index yields the [`LinkGraph`] of a space, which `flow export dot`
+//! writes as Graphviz and the desktop app draws as its graph view.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::index::PageIndex;
+use crate::page;
+use crate::space::Space;
+
+/// A wikilink or embed whose target doesn't exist.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page containing the link.
+/// - `page` (`String`) - Name of the page containing the link.
+/// - `target` (`String`) - Target of the link as written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct DeadLink {
+    pub id: String,
+    pub page: String,
+    pub target: String,
+}
+
+/// A page without inbound or outbound links.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct OrphanPage {
+    pub id: String,
+    pub page: String,
+}
+
+/// Result of a link check.
+///
+/// # Fields
+///
+/// - `dead` (`Vec<DeadLink>`) - Links to missing pages, ordered by page.
+/// - `orphans` (`Vec<OrphanPage>`) - Unlinked pages, ordered by id. Journal
+///   pages are never reported, they are reachable by date.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LinkReport {
+    pub dead: Vec<DeadLink>,
+    pub orphans: Vec<OrphanPage>,
+}
+
+impl LinkReport {
+    /// Checks whether the report contains no problems.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if there are neither dead links nor orphans.
+    pub fn is_clean(&self) -> bool {
+        self.dead.is_empty() && self.orphans.is_empty()
+    }
+
+    /// Returns the distinct targets of all dead links.
+    ///
+    /// Targets differing only in case are reported once, in their first spelling.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<String>` - Missing page names.
+    pub fn missing(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.dead
+            .iter()
+            .filter(|dead| seen.insert(dead.target.to_lowercase()))
+            .map(|dead| dead.target.clone())
+            .collect()
+    }
+}
+
+/// Checks the links of all pages in an index.
+///
+/// # Arguments
+///
+/// - `index` (`&PageIndex`) - Index of the space to check.
+///
+/// # Returns
+///
+/// - `LinkReport` - Dead links and orphan pages.
+pub fn check(index: &PageIndex) -> LinkReport {
+    let mut report = LinkReport::default();
+    let mut targets = HashSet::new();
+
+    for entry in index.pages() {
+        for link in entry.links.iter().chain(&entry.embeds) {
+            targets.insert(link.to_lowercase());
+            if index.find(link).is_none() {
+                report.dead.push(DeadLink {
+                    id: entry.id.clone(),
+                    page: entry.name.clone(),
+                    target: link.clone(),
+                });
+            }
+        }
+    }
+
+    for entry in index.pages() {
+        if page::is_journal(&entry.id) || !entry.links.is_empty() || !entry.embeds.is_empty() {
+            continue;
+        }
+        let linked = targets.contains(&entry.name.to_lowercase())
+            || entry
+                .aliases
+                .iter()
+                .any(|alias| targets.contains(&alias.to_lowercase()));
+        if !linked {
+            report.orphans.push(OrphanPage {
+                id: entry.id.clone(),
+                page: entry.name.clone(),
+            });
+        }
+    }
+
+    report
+}
+
+/// A page in the link graph.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `name` (`String`) - Name of the page.
+/// - `tags` (`Vec<String>`) - Tags of the page.
+/// - `journal` (`bool`) - Whether the page is a journal page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub name: String,
+    pub tags: Vec<String>,
+    pub journal: bool,
+}
+
+/// A link between two pages in the link graph.
+///
+/// # Fields
+///
+/// - `from` (`usize`) - Index of the linking node.
+/// - `to` (`usize`) - Index of the linked node.
+/// - `embed` (`bool`) - Whether the page is embedded rather than linked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub embed: bool,
+}
+
+/// Pages of a space and the links between them.
+///
+/// Dead links are left out, so every edge connects two existing pages.
+///
+/// # Fields
+///
+/// - `nodes` (`Vec<GraphNode>`) - All pages, ordered by id.
+/// - `edges` (`Vec<GraphEdge>`) - Links between pages, without duplicates.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LinkGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl LinkGraph {
+    /// Renders the graph in the Graphviz DOT language.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`LinkGraph`) - Graph to render.
+    /// - `name` (`&str`) - Name of the graph, usually the space name.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - The DOT source, embeds drawn dashed.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph {} {{\n", dot_string(name));
+        for node in &self.nodes {
+            let shape = if node.journal { "box" } else { "ellipse" };
+            dot.push_str(&format!(
+                "    {} [label={}, shape={}];\n",
+                dot_string(&node.id),
+                dot_string(&node.name),
+                shape
+            ));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "    {} -> {}{};\n",
+                dot_string(&self.nodes[edge.from].id),
+                dot_string(&self.nodes[edge.to].id),
+                if edge.embed { " [style=dashed]" } else { "" }
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Builds the link graph of all pages in an index.
+///
+/// # Arguments
+///
+/// - `index` (`&PageIndex`) - Index of the space.
+///
+/// # Returns
+///
+/// - `LinkGraph` - Pages and the links between them.
+pub fn graph(index: &PageIndex) -> LinkGraph {
+    let nodes: Vec<GraphNode> = index
+        .pages()
+        .map(|entry| GraphNode {
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            tags: entry.tags.clone(),
+            journal: page::is_journal(&entry.id),
+        })
+        .collect();
+    let positions: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(position, node)| (node.id.as_str(), position))
+        .collect();
+
+    let mut edges = Vec::new();
+    let mut seen = HashSet::new();
+    for (from, entry) in index.pages().enumerate() {
+        let targets = entry
+            .links
+            .iter()
+            .map(|link| (link, false))
+            .chain(entry.embeds.iter().map(|embed| (embed, true)));
+        for (target, embed) in targets {
+            let Some(to) = index
+                .find(target)
+                .and_then(|target| positions.get(target.id.as_str()).copied())
+            else {
+                continue;
+            };
+            if from != to && seen.insert((from, to, embed)) {
+                edges.push(GraphEdge { from, to, embed });
+            }
+        }
+    }
+
+    LinkGraph { nodes, edges }
+}
+
+/// Quotes a string as DOT identifier.
+fn dot_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Space {
+    /// Creates empty pages for the given names.
+    ///
+    /// Names of pages that already exist are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to create the pages in.
+    /// - `names` (`&[String]`) - Names of the pages to create.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<String>>` - Ids of the created pages.
+    ///
+    /// # Errors
+    ///
+    /// A name leaves the space, or IO errors when creating directories or
+    /// saving the space.
+    pub fn create_pages(&mut self, names: &[String]) -> Result<Vec<String>> {
+        let mut created = Vec::new();
+
+        for name in names {
+            let id = self.page_id(name);
+            page::check_id(&id)?;
+            let file_path = self.path.join(&id);
+            if self.storage.exists(&file_path) || created.contains(&id) {
+                continue;
+            }
+            if let Some(parent) = file_path.parent() {
+                self.storage.create_dir_all(parent)?;
+            }
+
+            // Registers the page, its (empty) content is written on save
+            self.document.get_text(id.clone());
+            self.dirty.insert(id.clone());
+            created.push(id);
+        }
+
+        if !created.is_empty() {
+            self.save()?;
+        }
+        Ok(created)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestamps::PageTimes;
+
+    #[test]
+    fn test_graph() {
+        let mut index = PageIndex::default();
+        for (id, content) in [
+            ("pages/a.md", "- [[B]] and [[b]] ![[C]] [[Missing]]"),
+            ("pages/b.md", "alias:: Bee\n- [[A]]"),
+            ("pages/c.md", "- [[Bee]]"),
+        ] {
+            index.update(id, content, PageTimes::default());
+        }
+
+        let graph = graph(&index);
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(graph.edges.len(), 4);
+        assert!(graph.edges.contains(&GraphEdge {
+            from: 0,
+            to: 2,
+            embed: true
+        }));
+        assert!(graph.edges.contains(&GraphEdge {
+            from: 2,
+            to: 1,
+            embed: false
+        }));
+        assert!(graph
+            .to_dot("notes")
+            .contains("\"pages/a.md\" -> \"pages/c.md\" [style=dashed];"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Locked Pages
+//!
+//! A page with a `locked:: true` property is read-only: adding to it, writing
+//! or formatting it fails, and changes a sync peer made to it are reverted,
+//! until the space is told to allow writing locked pages (the `--force` of
+//! the command line). Useful for finalized meeting minutes and published
+//! posts.
+//!
+//! Locking and unlocking a page sets or removes the property, so the lock
+//! syncs along with the page.
+
+use miette::Result;
+
+use crate::error::Error;
+use crate::page;
+use crate::space::Space;
+
+/// Property marking a page as locked.
+pub const LOCKED_PROPERTY: &str = "locked";
+
+/// Checks whether a page is locked.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `bool` - True if the page has a truthy `locked::` property.
+pub fn is_locked(content: &str) -> bool {
+    page::properties(content).iter().any(|(key, value)| {
+        key == LOCKED_PROPERTY && matches!(value.to_lowercase().as_str(), "true" | "yes")
+    })
+}
+
+/// Sets or removes the `locked::` property of a page.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+/// - `locked` (`bool`) - Whether the page should be locked.
+///
+/// # Returns
+///
+/// - `String` - The content with the property added or removed.
+fn set_locked(content: &str, locked: bool) -> String {
+    let properties = page::properties(content).len();
+    let mut lines = Vec::new();
+    if locked {
+        lines.push(format!("{}:: true", LOCKED_PROPERTY));
+    }
+    for (index, line) in content
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .enumerate()
+    {
+        let lock = index < properties
+            && line
+                .split_once("::")
+                .is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case(LOCKED_PROPERTY));
+        if !lock {
+            lines.push(line.to_string());
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+impl Space {
+    /// Allows or forbids writing locked pages.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to write to.
+    /// - `allow` (`bool`) - Whether locked pages may be written.
+    pub fn set_allow_locked(&mut self, allow: bool) {
+        self.allow_locked = allow;
+    }
+
+    /// Checks whether a page of the space is locked.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<bool>` - True if the page exists and is locked.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the page.
+    pub fn is_locked(&self, id: &str) -> Result<bool> {
+        Ok(self
+            .read_page(id)?
+            .is_some_and(|content| is_locked(&content)))
+    }
+
+    /// Locks or unlocks a page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `locked` (`bool`) - Whether the page should be locked.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<bool>` - True if the lock changed, false if it already was as requested.
+    ///
+    /// # Errors
+    ///
+    /// The page doesn't exist, or IO errors when writing it.
+    pub fn set_locked(&mut self, id: &str, locked: bool) -> Result<bool> {
+        let content = self.read_page(id)?.ok_or_else(|| Error::PageNotFound {
+            name: page::name_from_id(id),
+        })?;
+        if is_locked(&content) == locked {
+            return Ok(false);
+        }
+
+        let allow = self.allow_locked;
+        self.allow_locked = true;
+        let written = self.write_page(id, &set_locked(&content, locked));
+        self.allow_locked = allow;
+        written.map(|_| true)
+    }
+
+    /// Fails if a page is locked and locked pages may not be written, or its
+    /// id leaves the space (see [`page::check_id`]).
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    ///
+    /// # Errors
+    ///
+    /// The id is invalid, the page is locked, or IO errors when reading it.
+    pub(crate) fn ensure_writable(&self, id: &str) -> Result<()> {
+        page::check_id(id)?;
+        if !self.allow_locked && self.is_locked(id)? {
+            return Err(Error::PageLocked {
+                name: page::name_from_id(id),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_locked() {
+        let content = "title:: Minutes\n- decided things\n";
+
+        let locked = set_locked(content, true);
+        assert!(is_locked(&locked));
+        assert_eq!(locked, "locked:: true\ntitle:: Minutes\n- decided things\n");
+        assert_eq!(set_locked(&locked, false), content);
+    }
+}
@@ -0,0 +1,308 @@
+//! Unlinked Mentions
+//!
+//! Finds plain-text occurrences of a page's name or aliases that aren't
+//! wrapped in a `[[wikilink]]`, and optionally links them.
+//!
+//! Candidate pages come from the full-text index, so only pages containing
+//! the name as a phrase are scanned. Occurrences inside wikilinks, inline
+//! code, fenced code blocks, tags and property lines are ignored, as are
+//! partial words.
+
+use loro::UpdateOptions;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::lock;
+use crate::page;
+use crate::space::Space;
+
+/// A plain-text occurrence of a page name.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page containing the mention.
+/// - `page` (`String`) - Name of the page containing the mention.
+/// - `line` (`usize`) - Line of the mention, starting at 1.
+/// - `column` (`usize`) - Character column of the mention, starting at 1.
+/// - `matched` (`String`) - Mentioned text as written.
+/// - `text` (`String`) - Content of the line.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct Mention {
+    pub id: String,
+    pub page: String,
+    pub line: usize,
+    pub column: usize,
+    pub matched: String,
+    pub text: String,
+}
+
+impl Space {
+    /// Finds unlinked mentions of a page in all other pages.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `name` (`&str`) - Name or alias of the mentioned page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Mention>>` - Mentions ordered by page and position.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the indexes or reading pages.
+    pub fn mentions(&self, name: &str) -> Result<Vec<Mention>> {
+        let names = self.mention_names(name)?;
+        let mut mentions = Vec::new();
+        for (id, content) in self.mentioning_pages(name)? {
+            mentions.extend(find_mentions(&id, &content, &names));
+        }
+        Ok(mentions)
+    }
+
+    /// Turns all unlinked mentions of a page into wikilinks.
+    ///
+    /// Mentions keep their original spelling, e.g. `flow` becomes `[[flow]]`,
+    /// which resolves case-insensitively to the page. Locked pages are left
+    /// as they are.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to modify.
+    /// - `name` (`&str`) - Name or alias of the mentioned page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Mention>>` - The mentions that were linked.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading or saving pages.
+    pub fn link_mentions(&mut self, name: &str) -> Result<Vec<Mention>> {
+        let names = self.mention_names(name)?;
+        let mut linked = Vec::new();
+
+        for (id, content) in self.mentioning_pages(name)? {
+            let mentions = find_mentions(&id, &content, &names);
+            if mentions.is_empty() || (!self.allow_locked && lock::is_locked(&content)) {
+                continue;
+            }
+
+            let mut lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+            // Replace back to front so earlier columns stay valid
+            for mention in mentions.iter().rev() {
+                let line = &mut lines[mention.line - 1];
+                let start = char_to_byte(line, mention.column - 1);
+                let end = start + mention.matched.len();
+                line.replace_range(start..end, &format!("[[{}]]", mention.matched));
+            }
+
+            let text = self.document.get_text(id.clone());
+            text.update(&lines.join("\n"), UpdateOptions::default())
+                .into_diagnostic()?;
+            self.dirty.insert(id);
+            linked.extend(mentions);
+        }
+
+        if !linked.is_empty() {
+            self.save()?;
+        }
+        Ok(linked)
+    }
+
+    /// Returns the name and aliases of a page, longest first.
+    fn mention_names(&self, name: &str) -> Result<Vec<String>> {
+        let index = self.page_index()?;
+        let mut names = match index.find(name) {
+            Some(entry) => {
+                let mut names = vec![entry.name.clone()];
+                names.extend(entry.aliases.iter().cloned());
+                names
+            }
+            None => vec![name.to_string()],
+        };
+        names.retain(|name| !name.trim().is_empty());
+        names.sort_by_key(|name| std::cmp::Reverse(name.chars().count()));
+        Ok(names)
+    }
+
+    /// Returns the pages which may mention a page, excluding the page itself.
+    fn mentioning_pages(&self, name: &str) -> Result<Vec<(String, String)>> {
+        let own_id = self.page_index()?.resolve(name);
+        let text_index = self.text_index()?;
+
+        let mut ids = BTreeSet::new();
+        for name in self.mention_names(name)? {
+            let query = format!("\"{}\"", name.replace('"', " "));
+            ids.extend(text_index.search(&query).into_iter().map(|hit| hit.id));
+        }
+        ids.remove(&own_id);
+
+        let mut pages = Vec::new();
+        for id in ids {
+            if let Some(content) = self.read_page(&id)? {
+                pages.push((id, content));
+            }
+        }
+        Ok(pages)
+    }
+}
+
+/// Finds unlinked mentions of any of the given names in a page.
+///
+/// Where names overlap, the earliest and then longest match wins.
+fn find_mentions(id: &str, content: &str, names: &[String]) -> Vec<Mention> {
+    let mut mentions = Vec::new();
+    let mut fenced = false;
+
+    for (index, line) in content.split('\n').enumerate() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced || is_property(line) {
+            continue;
+        }
+
+        let ignored = ignored_ranges(line);
+        let mut found: Vec<Range<usize>> = names
+            .iter()
+            .flat_map(|name| occurrences(line, name))
+            .filter(|range| {
+                !ignored
+                    .iter()
+                    .any(|ignored| range.start < ignored.end && ignored.start < range.end)
+            })
+            .collect();
+        found.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+
+        let mut last_end = 0;
+        for range in found {
+            if range.start < last_end {
+                continue;
+            }
+            last_end = range.end;
+            mentions.push(Mention {
+                id: id.to_string(),
+                page: page::name_from_id(id),
+                line: index + 1,
+                column: line[..range.start].chars().count() + 1,
+                matched: line[range.clone()].to_string(),
+                text: line.to_string(),
+            });
+        }
+    }
+
+    mentions
+}
+
+/// Returns the byte ranges of case-insensitive, whole-word occurrences of a name.
+fn occurrences(line: &str, name: &str) -> Vec<Range<usize>> {
+    let needle: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut ranges = Vec::new();
+
+    for start in 0..chars.len() {
+        let mut matched = 0;
+        let mut end = start;
+        while matched < needle.len() && end < chars.len() {
+            let lower: Vec<char> = chars[end].1.to_lowercase().collect();
+            if needle.get(matched..matched + lower.len()) != Some(lower.as_slice()) {
+                break;
+            }
+            matched += lower.len();
+            end += 1;
+        }
+        if matched < needle.len() {
+            continue;
+        }
+
+        let before = start.checked_sub(1).map(|i| chars[i].1);
+        let after = chars.get(end).map(|(_, c)| *c);
+        let boundary_before = before.is_none_or(|c| !c.is_alphanumeric() && c != '#');
+        let boundary_after = after.is_none_or(|c| !c.is_alphanumeric());
+        if boundary_before && boundary_after {
+            let end_byte = chars.get(end).map_or(line.len(), |(byte, _)| *byte);
+            ranges.push(chars[start].0..end_byte);
+        }
+    }
+
+    ranges
+}
+
+/// Returns the byte ranges of wikilinks and inline code in a line.
+fn ignored_ranges(line: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+
+    let mut offset = 0;
+    while let Some(start) = line[offset..].find("[[") {
+        let start = offset + start;
+        let Some(end) = line[start..].find("]]") else {
+            break;
+        };
+        let end = start + end + 2;
+        ranges.push(start..end);
+        offset = end;
+    }
+
+    let mut offset = 0;
+    while let Some(start) = line[offset..].find('`') {
+        let start = offset + start;
+        let Some(end) = line[start + 1..].find('`') else {
+            break;
+        };
+        let end = start + 1 + end + 1;
+        ranges.push(start..end);
+        offset = end;
+    }
+
+    ranges
+}
+
+/// Checks whether a line is a `key:: value` property.
+fn is_property(line: &str) -> bool {
+    line.split_once("::").is_some_and(|(key, _)| {
+        let key = key.trim().trim_start_matches("- ");
+        !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    })
+}
+
+/// Converts a character offset into a byte offset.
+fn char_to_byte(line: &str, chars: usize) -> usize {
+    line.char_indices()
+        .nth(chars)
+        .map_or(line.len(), |(byte, _)| byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_mentions_skips_links_code_and_partial_words() {
+        let content = "Working on Flow today\n\
+                       Already linked: [[Flow]]\n\
+                       `flow` in code and a #flow tag\n\
+                       Workflow and flowchart\n\
+                       alias:: flow\n\
+                       see flow, then Flow CLI";
+        let names = vec!["Flow CLI".to_string(), "Flow".to_string()];
+
+        let mentions = find_mentions("pages/notes.md", content, &names);
+        let found: Vec<(usize, usize, &str)> = mentions
+            .iter()
+            .map(|m| (m.line, m.column, m.matched.as_str()))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![(1, 12, "Flow"), (6, 5, "flow"), (6, 16, "Flow CLI")]
+        );
+    }
+}
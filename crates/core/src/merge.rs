@@ -0,0 +1,227 @@
+//! Page Merging
+//!
+//! Consolidates one page into another. The content of the source page is
+//! appended to the target page under a heading naming the source:
+//!
+//! ```markdown
+//! - ## Sync plan
+//!   - The sync rewrite moves conflict handling into a merge queue
+//!   - Benchmark the queue first
+//! ```
+//!
+//! Links and embeds pointing to the source page or one of its aliases are
+//! rewritten to point to the target, keeping labels and heading suffixes
+//! (`[[Sync plan#Risks|risks]]` becomes `[[Sync rewrite#Risks|risks]]`).
+//! Finally the source page is moved to the trash. Locked pages linking to the
+//! source are left untouched and reported.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::lock;
+use crate::page;
+use crate::space::Space;
+
+/// A page merged into another.
+///
+/// # Fields
+///
+/// - `source` (`String`) - Id of the merged and trashed page.
+/// - `target` (`String`) - Id of the page it was merged into.
+/// - `relinked` (`Vec<String>`) - Ids of the pages whose links were rewritten.
+/// - `links` (`usize`) - Number of rewritten links.
+/// - `locked` (`Vec<String>`) - Ids of locked pages still linking to the source.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MergedPage {
+    pub source: String,
+    pub target: String,
+    pub relinked: Vec<String>,
+    pub links: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locked: Vec<String>,
+}
+
+impl Space {
+    /// Merges a page into another.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the pages belong to.
+    /// - `source` (`&str`) - Id of the page to merge.
+    /// - `target` (`&str`) - Id of the page to merge it into.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<MergedPage>` - The merged page and the rewritten links.
+    ///
+    /// # Errors
+    ///
+    /// A page doesn't exist, both are the same, the source or target is
+    /// locked, or IO errors when reading or writing pages.
+    pub fn merge_page(&mut self, source: &str, target: &str) -> Result<MergedPage> {
+        if source == target {
+            miette::bail!("Can't merge a page into itself");
+        }
+        let Some(source_content) = self.read_page(source)? else {
+            miette::bail!("Page '{}' doesn't exist", page::name_from_id(source));
+        };
+        let Some(target_content) = self.read_page(target)? else {
+            miette::bail!("Page '{}' doesn't exist", page::name_from_id(target));
+        };
+        self.ensure_writable(source)?;
+        self.ensure_writable(target)?;
+
+        let source_name = page::name_from_id(source);
+        let target_name = page::name_from_id(target);
+        let mut names = vec![source_name.to_lowercase()];
+        names.extend(
+            page::aliases(&source_content)
+                .iter()
+                .map(|a| a.to_lowercase()),
+        );
+
+        let mut merged = MergedPage {
+            source: source.to_string(),
+            target: target.to_string(),
+            relinked: Vec::new(),
+            links: 0,
+            locked: Vec::new(),
+        };
+
+        let index = self.page_index()?;
+        let mut linking: Vec<String> = index
+            .backlinks(&source_name)
+            .into_iter()
+            .chain(index.embedded_by(&source_name))
+            .map(|entry| entry.id.clone())
+            .filter(|id| id != source && id != target)
+            .collect();
+        linking.sort();
+        linking.dedup();
+
+        for id in linking {
+            let Some(content) = self.read_page(&id)? else {
+                continue;
+            };
+            let from = page::name_from_id(&id);
+            let (updated, count) = relink(&content, &from, &names, &target_name);
+            if count == 0 {
+                continue;
+            }
+            if !self.allow_locked && lock::is_locked(&content) {
+                merged.locked.push(id);
+                continue;
+            }
+            self.update_page(&id, &updated)?;
+            merged.relinked.push(id);
+            merged.links += count;
+        }
+
+        let mut content = target_content;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&section(&source_name, &source_content));
+        let (content, count) = relink(&content, &target_name, &names, &target_name);
+        merged.links += count;
+        self.update_page(target, &content)?;
+
+        self.save()?;
+        self.delete_page(source)?;
+        Ok(merged)
+    }
+}
+
+/// Nests the blocks of a page under a heading block named after it.
+///
+/// Page properties are dropped, they belong to the merged page.
+fn section(name: &str, content: &str) -> String {
+    let mut lines = vec![format!("- ## {}", name)];
+    let mut properties = true;
+    for line in content.lines() {
+        properties &= !line.starts_with('-') && line.contains("::");
+        if properties || line.trim().is_empty() {
+            continue;
+        }
+        lines.push(format!("  {}", line));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Points links and embeds to any of the given names to the target.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of a page.
+/// - `from` (`&str`) - Name of the page, relative links are resolved against it.
+/// - `names` (`&[String]`) - Lowercase names and aliases of the source page.
+/// - `target` (`&str`) - Name of the target page.
+///
+/// # Returns
+///
+/// - `(String, usize)` - The updated content and the number of rewritten links.
+fn relink(content: &str, from: &str, names: &[String], target: &str) -> (String, usize) {
+    page::rewrite_links(content, |link| {
+        names
+            .contains(&page::absolute_name(from, link).to_lowercase())
+            .then(|| target.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relink() {
+        let names = vec!["sync plan".to_string(), "sp".to_string()];
+        let (content, count) = relink(
+            "- See [[Sync plan]], [[sp#Risks|risks]] and ![[Sync Plan]]\n- [[Other]]\n",
+            "Roadmap",
+            &names,
+            "Sync rewrite",
+        );
+        assert_eq!(count, 3);
+        assert_eq!(
+            content,
+            "- See [[Sync rewrite]], [[Sync rewrite#Risks|risks]] and ![[Sync rewrite]]\n- [[Other]]\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_page() {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page("pages/Sync rewrite.md", "- Merge queue first\n")
+            .unwrap();
+        space
+            .write_page(
+                "pages/Sync plan.md",
+                "alias:: SP\n- Benchmark the queue\n  - Before the beta\n",
+            )
+            .unwrap();
+        space
+            .write_page(
+                "journal/2024-05-01.md",
+                "- Discussed [[SP]] with the team\n",
+            )
+            .unwrap();
+
+        let merged = space
+            .merge_page("pages/Sync plan.md", "pages/Sync rewrite.md")
+            .unwrap();
+        assert_eq!(merged.relinked, vec!["journal/2024-05-01.md"]);
+        assert_eq!(merged.links, 1);
+
+        assert_eq!(
+            space.read_page("pages/Sync rewrite.md").unwrap().unwrap(),
+            "- Merge queue first\n- ## Sync plan\n  - Benchmark the queue\n    - Before the beta\n"
+        );
+        assert_eq!(
+            space.read_page("journal/2024-05-01.md").unwrap().unwrap(),
+            "- Discussed [[Sync rewrite]] with the team\n"
+        );
+        assert!(space.read_page("pages/Sync plan.md").unwrap().is_none());
+    }
+}
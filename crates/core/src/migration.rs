@@ -0,0 +1,239 @@
+//! Space Format Migrations
+//!
+//! Every space records the on-disk format it was written with in its metadata.
+//! When a space with an older format is loaded, all migrations between its
+//! format and [`CURRENT_FORMAT`] are run in order. Spaces with a newer format
+//! can still be read, but are never written to.
+//!
+//! Adding a migration means bumping [`CURRENT_FORMAT`] and appending an entry
+//! to [`MIGRATIONS`] that upgrades from the previous format.
+
+use miette::{IntoDiagnostic, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::backup::{self, BackupSettings};
+use crate::error::Error;
+use crate::space::{Metadata, DOCUMENT_FILE, FLOW_DIR, METADATA_FILE};
+use crate::storage::FsStorage;
+
+/// On-disk format written by this version of Flow.
+pub const CURRENT_FORMAT: u32 = 3;
+
+const LEGACY_METADATA_FILE: &str = "graph.toml";
+const LEGACY_DOCUMENT_FILE: &str = "graph.loro";
+const LEGACY_INDEX_FILE: &str = "index.json";
+
+/// A single migration step.
+///
+/// # Fields
+///
+/// - `from` (`u32`) - Format the migration upgrades from (to `from + 1`).
+/// - `description` (`&'static str`) - Human readable description of the step.
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    apply: fn(&Path) -> Result<()>,
+}
+
+/// All migrations, ordered by the format they upgrade from.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "Rename legacy graph.toml/graph.loro to space.toml/space.loro",
+        apply: rename_legacy_files,
+    },
+    Migration {
+        from: 1,
+        description: "Introduce the incremental update log (space.updates)",
+        apply: introduce_update_log,
+    },
+    Migration {
+        from: 2,
+        description: "Move the page index from index.json into the index/ directory",
+        apply: remove_legacy_index,
+    },
+];
+
+/// Reads the on-disk format of the space at the given path.
+///
+/// Spaces written before formats were recorded report format 0.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<u32>` - Format of the space.
+///
+/// # Errors
+///
+/// Returns an error if the path contains no space metadata or it can not be parsed.
+pub fn format_of(path: &Path) -> Result<u32> {
+    let flow_dir = path.join(FLOW_DIR);
+    let metadata_path = flow_dir.join(METADATA_FILE);
+
+    if !metadata_path.exists() {
+        if flow_dir.join(LEGACY_METADATA_FILE).exists() {
+            return Ok(0);
+        }
+        return Err(Error::MetadataMissing {
+            path: path.to_path_buf(),
+        }
+        .into());
+    }
+
+    let metadata_toml = fs::read_to_string(&metadata_path).map_err(Error::io(&metadata_path))?;
+    let table: toml::Table = toml::from_str(&metadata_toml).map_err(|err| Error::Parse {
+        path: metadata_path,
+        message: err.message().to_string(),
+    })?;
+    let format = table
+        .get("format")
+        .and_then(|value| value.as_integer())
+        .unwrap_or(0);
+
+    Ok(format as u32)
+}
+
+/// Returns the migrations that still need to run for the space at the given path.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<Vec<&'static Migration>>` - Pending migrations in execution order.
+///
+/// # Errors
+///
+/// Returns an error if the format of the space can not be read.
+pub fn pending(path: &Path) -> Result<Vec<&'static Migration>> {
+    let format = format_of(path)?;
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|migration| migration.from >= format)
+        .collect())
+}
+
+/// Runs all pending migrations for the space at the given path.
+///
+/// The document snapshot is backed up before the first migration that changes
+/// it runs. Legacy files are renamed first, so format 0 spaces are backed up
+/// under the current file names.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<Vec<&'static Migration>>` - The migrations that were applied.
+///
+/// # Errors
+///
+/// Returns an error if a migration fails. Migrations applied before the failing
+/// one are kept, the format is only bumped once all of them succeeded.
+pub fn migrate(path: &Path) -> Result<Vec<&'static Migration>> {
+    let pending = pending(path)?;
+    if pending.is_empty() {
+        return Ok(pending);
+    }
+
+    let mut backed_up = false;
+    for migration in &pending {
+        // Renaming is lossless, the backup needs the current file names
+        if !backed_up && migration.from > 0 {
+            backup::create(path, &BackupSettings::for_space(path))?;
+            backed_up = true;
+        }
+        (migration.apply)(path)?;
+    }
+
+    let mut metadata = Metadata::read(&FsStorage, path)?;
+    metadata.format = CURRENT_FORMAT;
+    metadata.write(&FsStorage, path)?;
+
+    Ok(pending)
+}
+
+/// Format 0 → 1: spaces used to be called graphs on disk.
+fn rename_legacy_files(path: &Path) -> Result<()> {
+    let flow_dir = path.join(FLOW_DIR);
+
+    for (legacy, current) in [
+        (LEGACY_METADATA_FILE, METADATA_FILE),
+        (LEGACY_DOCUMENT_FILE, DOCUMENT_FILE),
+    ] {
+        let legacy_path = flow_dir.join(legacy);
+        let current_path = flow_dir.join(current);
+        if legacy_path.exists() && !current_path.exists() {
+            fs::rename(legacy_path, current_path).into_diagnostic()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Format 1 → 2: saves append to `space.updates` between snapshots.
+///
+/// Nothing to convert, the bump only keeps older versions (which would ignore
+/// the update log) from writing to the space.
+fn introduce_update_log(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Format 2 → 3: indexes live in `.flow/index/`.
+///
+/// The legacy page index is removed, it is rebuilt on first use.
+fn remove_legacy_index(path: &Path) -> Result<()> {
+    let legacy_path = path.join(FLOW_DIR).join(LEGACY_INDEX_FILE);
+    if legacy_path.exists() {
+        fs::remove_file(legacy_path).into_diagnostic()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use loro::{ExportMode, LoroDoc};
+
+    #[test]
+    fn test_migrate_backs_up_format_0_space() {
+        let path =
+            std::env::temp_dir().join(format!("flow-migration-legacy-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        let flow_dir = path.join(FLOW_DIR);
+        fs::create_dir_all(&flow_dir).unwrap();
+        fs::write(
+            flow_dir.join(LEGACY_METADATA_FILE),
+            "name = \"legacy\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let doc = LoroDoc::new();
+        doc.get_text("notes.md").insert(0, "- Legacy").unwrap();
+        doc.commit();
+        fs::write(
+            flow_dir.join(LEGACY_DOCUMENT_FILE),
+            doc.export(ExportMode::Snapshot).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(format_of(&path).unwrap(), 0);
+        assert_eq!(migrate(&path).unwrap().len(), MIGRATIONS.len());
+        assert_eq!(format_of(&path).unwrap(), CURRENT_FORMAT);
+        assert!(flow_dir.join(DOCUMENT_FILE).exists());
+        assert!(!flow_dir.join(LEGACY_DOCUMENT_FILE).exists());
+
+        let backups = backup::list(&path).unwrap();
+        assert_eq!(backups.len(), 1);
+        let restored = LoroDoc::new();
+        restored
+            .import(&fs::read(&backups[0].path).unwrap())
+            .unwrap();
+        assert_eq!(restored.get_text("notes.md").to_string(), "- Legacy");
+    }
+}
@@ -0,0 +1,277 @@
+//! Namespaces
+//!
+//! Page names separated by `/` form a hierarchy of namespaces, e.g.
+//! `projects/flow/sync-design` lives in `projects/flow`, which lives in
+//! `projects`. Pages of a namespace are stored in the matching directory
+//! below `pages/`, and links between them can be relative to the linking
+//! page (see [`page::absolute_name`]):
+//!
+//! ```markdown
+//! - Design notes in [[./sync-design]], scheduling in [[../roadmap]]
+//! ```
+//!
+//! Renaming a page renames its whole namespace: every page below it moves
+//! along, and links to any of the moved pages are rewritten. Relative links
+//! that still point to the same page after the move are left as they are.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::lock;
+use crate::page::{self, NAMESPACE_SEPARATOR};
+use crate::space::Space;
+
+/// A page moved to a new name.
+///
+/// # Fields
+///
+/// - `from` (`String`) - Previous id of the page.
+/// - `to` (`String`) - New id of the page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Renamed {
+    pub from: String,
+    pub to: String,
+}
+
+/// A page or namespace renamed with its children.
+///
+/// # Fields
+///
+/// - `renamed` (`Vec<Renamed>`) - The moved pages.
+/// - `relinked` (`Vec<String>`) - Ids of other pages whose links were rewritten.
+/// - `links` (`usize`) - Number of rewritten links.
+/// - `locked` (`Vec<String>`) - Ids of locked pages still linking to old names.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RenamedPages {
+    pub renamed: Vec<Renamed>,
+    pub relinked: Vec<String>,
+    pub links: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locked: Vec<String>,
+}
+
+impl Space {
+    /// Renames a page or namespace with all pages below it.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the pages belong to.
+    /// - `from` (`&str`) - Name of the page or namespace.
+    /// - `to` (`&str`) - New name.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<RenamedPages>` - The moved pages and the rewritten links.
+    ///
+    /// # Errors
+    ///
+    /// No page has the name or lives below it, the new name is taken or below
+    /// the old one, a moved page is locked, or IO errors when reading or
+    /// writing pages.
+    pub fn rename_namespace(&mut self, from: &str, to: &str) -> Result<RenamedPages> {
+        let from = from.trim_matches(NAMESPACE_SEPARATOR);
+        let to = to.trim_matches(NAMESPACE_SEPARATOR);
+        if to.is_empty() || page::is_journal(&page::id_from_name(to)) {
+            miette::bail!("'{}' is not a valid page name", to);
+        }
+        page::check_id(&page::id_from_name(to))?;
+        if from.to_lowercase() == to.to_lowercase() {
+            miette::bail!("'{}' already has that name", from);
+        }
+        if to
+            .to_lowercase()
+            .starts_with(&format!("{}{}", from.to_lowercase(), NAMESPACE_SEPARATOR))
+        {
+            miette::bail!("Can't move '{}' below itself", from);
+        }
+
+        // Old id to new name of every page moving along
+        let index = self.page_index()?;
+        let mut moves: BTreeMap<String, String> = BTreeMap::new();
+        for entry in index.pages().filter(|entry| !page::is_journal(&entry.id)) {
+            if let Some(rest) = below(&entry.name, from) {
+                moves.insert(entry.id.clone(), format!("{}{}", to, rest));
+            }
+        }
+        if moves.is_empty() {
+            miette::bail!("No page or namespace named '{}'", from);
+        }
+        for (id, name) in &moves {
+            self.ensure_writable(id)?;
+            let new = page::id_from_name(name);
+            if index.pages().any(|entry| entry.id == new) && !moves.contains_key(&new) {
+                miette::bail!("Page '{}' already exists", name);
+            }
+        }
+        let names: HashMap<String, String> = moves
+            .iter()
+            .map(|(id, name)| (page::name_from_id(id).to_lowercase(), name.clone()))
+            .collect();
+
+        let mut renamed = RenamedPages {
+            renamed: Vec::new(),
+            relinked: Vec::new(),
+            links: 0,
+            locked: Vec::new(),
+        };
+        let mut moved: BTreeMap<String, (String, String)> = BTreeMap::new();
+        for id in self.page_ids()? {
+            let Some(content) = self.read_page(&id)? else {
+                continue;
+            };
+            let old_name = page::name_from_id(&id);
+            let new_name = moves.get(&id).cloned().unwrap_or_else(|| old_name.clone());
+            let (updated, count) = page::rewrite_links(&content, |target| {
+                let absolute = page::absolute_name(&old_name, target);
+                let moved_to = names.get(&absolute.to_lowercase()).cloned();
+                if !page::is_relative(target) {
+                    return moved_to;
+                }
+                // Relative links stay if they still point to the same page
+                let now = moved_to.unwrap_or(absolute);
+                let kept = page::absolute_name(&new_name, target);
+                (kept.to_lowercase() != now.to_lowercase()).then_some(now)
+            });
+            renamed.links += count;
+
+            if moves.contains_key(&id) {
+                moved.insert(id, (page::id_from_name(&new_name), updated));
+            } else if count > 0 {
+                if !self.allow_locked && lock::is_locked(&content) {
+                    renamed.links -= count;
+                    renamed.locked.push(id);
+                    continue;
+                }
+                self.update_page(&id, &updated)?;
+                renamed.relinked.push(id);
+            }
+        }
+
+        for (to, content) in moved.values() {
+            self.update_page(to, content)?;
+        }
+        self.save()?;
+        for (from, (to, _)) in moved {
+            self.delete_page(&from)?;
+            renamed.renamed.push(Renamed { from, to });
+        }
+        Ok(renamed)
+    }
+}
+
+/// Returns the part of a name below a namespace, empty for the namespace itself.
+fn below<'a>(name: &'a str, namespace: &str) -> Option<&'a str> {
+    let length = namespace.chars().count();
+    let split = name
+        .char_indices()
+        .nth(length)
+        .map_or(name.len(), |(index, _)| index);
+    let (head, rest) = name.split_at(split);
+    (head.to_lowercase() == namespace.to_lowercase()
+        && (rest.is_empty() || rest.starts_with(NAMESPACE_SEPARATOR)))
+    .then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn space() -> Space {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page(
+                "pages/projects/flow.md",
+                "- Overview, see [[./flow/sync]]\n",
+            )
+            .unwrap();
+        space
+            .write_page(
+                "pages/projects/flow/sync.md",
+                "- Part of [[projects/flow]], next to [[./roadmap]] and [[../garden]]\n",
+            )
+            .unwrap();
+        space
+            .write_page("pages/projects/flow/roadmap.md", "- Ship sync\n")
+            .unwrap();
+        space
+            .write_page("pages/projects/garden.md", "- Tomatoes\n")
+            .unwrap();
+        space
+            .write_page(
+                "journal/2024-05-01.md",
+                "- Worked on [[projects/flow/sync|sync]]\n",
+            )
+            .unwrap();
+        space
+    }
+
+    #[test]
+    fn test_below() {
+        assert_eq!(below("projects/flow/sync", "Projects/Flow"), Some("/sync"));
+        assert_eq!(below("projects/flow", "projects/flow"), Some(""));
+        assert_eq!(below("projects/flowchart", "projects/flow"), None);
+    }
+
+    #[test]
+    fn test_rename_namespace_moves_children_and_rewrites_links() {
+        let mut space = space();
+        let renamed = space
+            .rename_namespace("projects/flow", "archive/flow")
+            .unwrap();
+        assert_eq!(
+            renamed.renamed,
+            vec![
+                Renamed {
+                    from: "pages/projects/flow.md".to_string(),
+                    to: "pages/archive/flow.md".to_string(),
+                },
+                Renamed {
+                    from: "pages/projects/flow/roadmap.md".to_string(),
+                    to: "pages/archive/flow/roadmap.md".to_string(),
+                },
+                Renamed {
+                    from: "pages/projects/flow/sync.md".to_string(),
+                    to: "pages/archive/flow/sync.md".to_string(),
+                },
+            ]
+        );
+        assert_eq!(renamed.relinked, vec!["journal/2024-05-01.md"]);
+
+        assert!(space.read_page("pages/projects/flow.md").unwrap().is_none());
+        assert_eq!(
+            space.read_page("pages/archive/flow.md").unwrap().unwrap(),
+            "- Overview, see [[./flow/sync]]\n"
+        );
+        assert_eq!(
+            space
+                .read_page("pages/archive/flow/sync.md")
+                .unwrap()
+                .unwrap(),
+            "- Part of [[archive/flow]], next to [[./roadmap]] and [[projects/garden]]\n"
+        );
+        assert_eq!(
+            space.read_page("journal/2024-05-01.md").unwrap().unwrap(),
+            "- Worked on [[archive/flow/sync|sync]]\n"
+        );
+        assert_eq!(
+            space
+                .page_index()
+                .unwrap()
+                .backlinks("archive/flow/roadmap")[0]
+                .id,
+            "pages/archive/flow/sync.md"
+        );
+    }
+
+    #[test]
+    fn test_rename_namespace_refuses_taken_names() {
+        let mut space = space();
+        assert!(space
+            .rename_namespace("projects/garden", "projects/flow")
+            .is_err());
+        assert!(space.rename_namespace("projects", "projects/old").is_err());
+        assert!(space.rename_namespace("nothing", "something").is_err());
+    }
+}
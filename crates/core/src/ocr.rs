@@ -0,0 +1,121 @@
+//! Text Recognition
+//!
+//! Reads the text of attached images (see [`attach`](crate::attach)). The
+//! recognizer is configured per space in the space metadata; the
+//! [Tesseract](https://github.com/tesseract-ocr/tesseract) binary runs
+//! locally, other engines plug in by implementing [`Recognizer`]:
+//!
+//! ```toml
+//! [ocr]
+//! kind = "tesseract"
+//! languages = "eng+deu"
+//! ```
+
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::space::Space;
+
+/// An engine recognizing text in images.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum OcrBackend {
+    /// A local Tesseract binary.
+    Tesseract {
+        /// Path or name of the binary.
+        #[serde(default = "default_binary")]
+        binary: PathBuf,
+        /// Languages to recognize, e.g. `eng+deu`, Tesseract's default if not set.
+        #[serde(default)]
+        languages: Option<String>,
+    },
+}
+
+fn default_binary() -> PathBuf {
+    PathBuf::from("tesseract")
+}
+
+/// Recognizes text in images.
+pub trait Recognizer {
+    /// Recognizes the text of an image.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Recognizer`) - Recognizer to use.
+    /// - `path` (`&Path`) - Path of the image.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - The recognized text, empty if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Engine specific errors, e.g. when a binary is missing.
+    fn recognize(&self, path: &Path) -> Result<String>;
+}
+
+/// Recognizer running a local Tesseract binary.
+///
+/// # Fields
+///
+/// - `binary` (`PathBuf`) - Path or name of the binary.
+/// - `languages` (`Option<String>`) - Languages to recognize.
+#[derive(Debug, Clone)]
+pub struct Tesseract {
+    binary: PathBuf,
+    languages: Option<String>,
+}
+
+impl Recognizer for Tesseract {
+    fn recognize(&self, path: &Path) -> Result<String> {
+        // `stdout` as output base prints the text instead of writing a file
+        let mut command = process::Command::new(&self.binary);
+        command.arg(path).arg("stdout");
+        if let Some(languages) = &self.languages {
+            command.args(["-l", languages]);
+        }
+
+        let output = command.output().map_err(|error| {
+            miette::miette!("Failed to run '{}': {}", self.binary.display(), error)
+        })?;
+        if !output.status.success() {
+            miette::bail!(
+                "'{}' failed: {}",
+                self.binary.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// Creates the recognizer configured by the given backend.
+///
+/// # Arguments
+///
+/// - `backend` (`&OcrBackend`) - OCR backend of a space.
+///
+/// # Returns
+///
+/// - `Box<dyn Recognizer>` - The configured recognizer.
+pub fn recognizer(backend: &OcrBackend) -> Box<dyn Recognizer> {
+    match backend {
+        OcrBackend::Tesseract { binary, languages } => Box::new(Tesseract {
+            binary: binary.clone(),
+            languages: languages.clone(),
+        }),
+    }
+}
+
+impl Space {
+    /// Returns the OCR backend of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&OcrBackend>` - The configured backend, `None` if not configured.
+    pub fn ocr_backend(&self) -> Option<&OcrBackend> {
+        self.metadata.ocr.as_ref()
+    }
+}
@@ -0,0 +1,670 @@
+//! Org-mode Conversion
+//!
+//! Converts pages between Flow's markdown outlines and org-mode files. Bullets
+//! become headlines (one star per nesting level), so task keywords like
+//! `TODO` and `DONE` at the start of a bullet map to org task states as they
+//! are. The block properties `scheduled::`, `deadline::` and `closed::` map
+//! to the org planning line, other block properties to a `:PROPERTIES:`
+//! drawer, and page properties to `#+key:` keywords.
+//!
+//! Markdown headings outside of bullets become headlines as well, with the
+//! bullets below them nested one level deeper; importing the file again turns
+//! them into bullets.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use loro::UpdateOptions;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::page;
+use crate::progress::Progress;
+use crate::space::Space;
+
+/// File extension of org files.
+pub const ORG_EXTENSION: &str = "org";
+
+/// Block properties mapped to the org planning line, in org order.
+const PLANNING: &[(&str, &str)] = &[
+    ("scheduled", "SCHEDULED"),
+    ("deadline", "DEADLINE"),
+    ("closed", "CLOSED"),
+];
+
+/// Outcome of an org import.
+///
+/// # Fields
+///
+/// - `imported` (`Vec<String>`) - Ids of the pages written.
+/// - `skipped` (`Vec<String>`) - Ids of existing pages left untouched.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, Deserialize)]
+pub struct OrgImport {
+    pub imported: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+impl Space {
+    /// Writes pages as org files into a directory.
+    ///
+    /// Each page is written to `<name>.org`, names with slashes create
+    /// subdirectories.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to export from.
+    /// - `target` (`&Path`) - Directory to write the org files to.
+    /// - `page` (`Option<&str>`) - Name of a single page to export, all pages if `None`.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<PathBuf>>` - Paths of the written files.
+    ///
+    /// # Errors
+    ///
+    /// The page doesn't exist, or IO errors when writing the files.
+    pub fn export_org(&self, target: &Path, page: Option<&str>) -> Result<Vec<PathBuf>> {
+        let ids = match page {
+            Some(name) => vec![self.page_index()?.resolve(name)],
+            None => self.page_ids()?,
+        };
+
+        let mut written = Vec::with_capacity(ids.len());
+        for id in ids {
+            let name = page::name_from_id(&id);
+            let Some(content) = self.read_page(&id)? else {
+                return Err(Error::PageNotFound { name }.into());
+            };
+
+            let path = target.join(format!("{}.{}", name, ORG_EXTENSION));
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).into_diagnostic()?;
+            }
+            fs::write(&path, to_org(&name, &content)).into_diagnostic()?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// Imports org files as pages.
+    ///
+    /// Pages are named after the `#+title:` of a file, or its path relative
+    /// to the imported directory without the extension.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to import into.
+    /// - `source` (`&Path`) - Org file or directory of org files.
+    /// - `overwrite` (`bool`) - Replace existing pages instead of skipping them, locked pages are always skipped.
+    /// - `progress` (`&dyn Progress`) - Receives one step per converted file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<OrgImport>` - Imported and skipped pages.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the org files or saving the space.
+    pub fn import_org(
+        &mut self,
+        source: &Path,
+        overwrite: bool,
+        progress: &dyn Progress,
+    ) -> Result<OrgImport> {
+        let files = if source.is_dir() {
+            org_files(source)?
+        } else {
+            vec![source.to_path_buf()]
+        };
+        let root = if source.is_dir() {
+            source
+        } else {
+            source.parent().unwrap_or(source)
+        };
+
+        let existing = self.page_ids()?;
+        let mut report = OrgImport::default();
+        progress.start("Converting org files", Some(files.len() as u64));
+        for file in files {
+            progress.advance(1);
+            let org = fs::read_to_string(&file).into_diagnostic()?;
+            let (title, content) = from_org(&org);
+            let name = title.unwrap_or_else(|| {
+                let relative = file.strip_prefix(root).unwrap_or(&file).with_extension("");
+                relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            });
+
+            let id = page::id_from_name(&name);
+            if existing.contains(&id) && (!overwrite || self.ensure_writable(&id).is_err()) {
+                report.skipped.push(id);
+                continue;
+            }
+            if let Some(parent) = self.path.join(&id).parent() {
+                fs::create_dir_all(parent).into_diagnostic()?;
+            }
+            self.document
+                .get_text(id.as_str())
+                .update(&content, UpdateOptions::default())
+                .into_diagnostic()?;
+            self.dirty.insert(id.clone());
+            report.imported.push(id);
+        }
+        progress.finish();
+
+        if !report.imported.is_empty() {
+            self.save()?;
+        }
+        Ok(report)
+    }
+}
+
+/// Converts a markdown page to org.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name of the page, written as `#+title:`.
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `String` - The org document.
+pub fn to_org(name: &str, content: &str) -> String {
+    let mut org = format!("#+title: {}\n", name);
+    let mut lines = content.lines().peekable();
+
+    // Page properties become keywords
+    while let Some(line) = lines.peek() {
+        if line.trim().is_empty() {
+            lines.next();
+            continue;
+        }
+        match property(line) {
+            Some((key, value)) if !line.starts_with(char::is_whitespace) => {
+                org.push_str(&format!("#+{}: {}\n", key, value));
+                lines.next();
+            }
+            _ => break,
+        }
+    }
+
+    // Level of the last heading, and indentation of the open bullets
+    let mut heading = 0;
+    let mut bullets: Vec<usize> = Vec::new();
+    let mut headline = Headline::default();
+    let mut code = false;
+
+    for line in lines {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if code {
+            if trimmed.starts_with("```") {
+                org.push_str("#+end_src\n");
+                code = false;
+            } else {
+                org.push_str(line.get(headline.body_indent.min(indent)..).unwrap_or(line));
+                org.push('\n');
+            }
+            continue;
+        }
+
+        if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or((trimmed == "-").then_some(""))
+        {
+            headline.flush(&mut org);
+            while bullets.last().is_some_and(|open| *open >= indent) {
+                bullets.pop();
+            }
+            bullets.push(indent);
+            let level = heading + bullets.len();
+            org.push_str(&format!("{} {}\n", "*".repeat(level), inline_to_org(item)));
+            headline = Headline {
+                open: true,
+                body_indent: indent + 2,
+                ..Headline::default()
+            };
+            continue;
+        }
+
+        if headline.open && indent > 0 {
+            if let Some((key, value)) = property(trimmed) {
+                if !headline.flushed {
+                    headline.properties.push((key, value));
+                    continue;
+                }
+            }
+            headline.flush(&mut org);
+            if let Some(language) = trimmed.strip_prefix("```") {
+                org.push_str(&format!("#+begin_src {}\n", language.trim()).replace(" \n", "\n"));
+                code = true;
+            } else {
+                org.push_str(&inline_to_org(trimmed));
+                org.push('\n');
+            }
+            continue;
+        }
+
+        headline.flush(&mut org);
+        headline.open = false;
+        bullets.clear();
+
+        let hashes = trimmed.len() - trimmed.trim_start_matches('#').len();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            heading = hashes;
+            org.push_str(&format!(
+                "{} {}\n",
+                "*".repeat(hashes),
+                inline_to_org(trimmed[hashes..].trim())
+            ));
+        } else if let Some(language) = trimmed.strip_prefix("```") {
+            org.push_str(&format!("#+begin_src {}\n", language.trim()).replace(" \n", "\n"));
+            headline.body_indent = 0;
+            code = true;
+        } else if trimmed.is_empty() {
+            org.push('\n');
+        } else {
+            org.push_str(&inline_to_org(trimmed));
+            org.push('\n');
+        }
+    }
+    headline.flush(&mut org);
+    if code {
+        org.push_str("#+end_src\n");
+    }
+    org
+}
+
+/// Planning and properties of the current headline, written before its body.
+#[derive(Default)]
+struct Headline {
+    open: bool,
+    flushed: bool,
+    body_indent: usize,
+    properties: Vec<(String, String)>,
+}
+
+impl Headline {
+    /// Writes the planning line and property drawer once.
+    fn flush(&mut self, org: &mut String) {
+        if self.flushed {
+            return;
+        }
+        self.flushed = true;
+
+        let planning: Vec<String> = PLANNING
+            .iter()
+            .filter_map(|(key, keyword)| {
+                let (_, value) = self.properties.iter().find(|(k, _)| k == key)?;
+                let active = *key != "closed";
+                Some(format!("{}: {}", keyword, timestamp_to_org(value, active)))
+            })
+            .collect();
+        if !planning.is_empty() {
+            org.push_str(&planning.join(" "));
+            org.push('\n');
+        }
+
+        let drawer: Vec<&(String, String)> = self
+            .properties
+            .iter()
+            .filter(|(key, _)| !PLANNING.iter().any(|(planning, _)| planning == key))
+            .collect();
+        if !drawer.is_empty() {
+            org.push_str(":PROPERTIES:\n");
+            for (key, value) in drawer {
+                org.push_str(&format!(":{}: {}\n", key, value));
+            }
+            org.push_str(":END:\n");
+        }
+    }
+}
+
+/// Converts an org document to markdown.
+///
+/// # Arguments
+///
+/// - `org` (`&str`) - The org document.
+///
+/// # Returns
+///
+/// - `(Option<String>, String)` - The `#+title:` if any, and the markdown content.
+pub fn from_org(org: &str) -> (Option<String>, String) {
+    let mut title = None;
+    let mut markdown = String::new();
+    // Indentation of continuation lines of the current headline, if any
+    let mut body: Option<String> = None;
+    let mut drawer: Option<bool> = None;
+    let mut code = false;
+
+    for line in org.lines() {
+        let trimmed = line.trim();
+        let indent = body.clone().unwrap_or_default();
+
+        if code {
+            if trimmed.eq_ignore_ascii_case("#+end_src") {
+                markdown.push_str(&format!("{}```\n", indent));
+                code = false;
+            } else {
+                markdown.push_str(&format!("{}{}\n", indent, line));
+            }
+            continue;
+        }
+
+        // Drawers, `Some(true)` for properties, `Some(false)` for others like logbooks
+        if let Some(properties) = drawer {
+            if trimmed.eq_ignore_ascii_case(":END:") {
+                drawer = None;
+            } else if properties {
+                if let Some((key, value)) = drawer_property(trimmed) {
+                    markdown.push_str(&format!("{}{}:: {}\n", indent, key, value));
+                }
+            }
+            continue;
+        }
+
+        let stars = line.len() - line.trim_start_matches('*').len();
+        if stars > 0 && line[stars..].starts_with(' ') {
+            let (text, tags) = split_tags(line[stars..].trim());
+            let bullet = "  ".repeat(stars - 1);
+            markdown.push_str(&format!("{}- {}\n", bullet, inline_to_markdown(text)));
+            let indent = format!("{}  ", bullet);
+            if !tags.is_empty() {
+                markdown.push_str(&format!("{}tags:: {}\n", indent, tags.join(", ")));
+            }
+            body = Some(indent);
+            continue;
+        }
+
+        if let (true, Some(properties)) = (body.is_some(), planning(trimmed)) {
+            for (key, value) in properties {
+                markdown.push_str(&format!("{}{}:: {}\n", indent, key, value));
+            }
+            continue;
+        }
+        if trimmed.starts_with(':') && trimmed.ends_with(':') && trimmed.len() > 2 {
+            drawer = Some(trimmed.eq_ignore_ascii_case(":PROPERTIES:"));
+            continue;
+        }
+
+        if let Some(keyword) = trimmed.strip_prefix("#+") {
+            let (key, value) = keyword.split_once(':').unwrap_or((keyword, ""));
+            let key = key.trim().to_lowercase();
+            if let Some(language) = key.strip_prefix("begin_src") {
+                let language = format!("{} {}", language, value).trim().to_string();
+                markdown.push_str(&format!("{}```{}\n", indent, language));
+                code = true;
+            } else if key == "title" {
+                title = Some(value.trim().to_string());
+            } else if body.is_none() && !key.starts_with("begin") && !key.starts_with("end") {
+                markdown.push_str(&format!("{}:: {}\n", key, value.trim()));
+            }
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            // Blank lines would end the block of a bullet
+            if body.is_none() {
+                markdown.push('\n');
+            }
+            continue;
+        }
+        markdown.push_str(&format!("{}{}\n", indent, inline_to_markdown(trimmed)));
+    }
+
+    (title, markdown.trim_matches('\n').to_string())
+}
+
+/// Parses a `key:: value` property line.
+fn property(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.trim().split_once("::")?;
+    let key = key.trim();
+    if key.is_empty() || key.contains(char::is_whitespace) || key.starts_with('-') {
+        return None;
+    }
+    Some((key.to_lowercase(), value.trim().to_string()))
+}
+
+/// Parses a `:KEY: value` drawer line.
+fn drawer_property(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.strip_prefix(':')?.split_once(':')?;
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((key.to_lowercase(), value.trim().to_string()))
+}
+
+/// Parses an org planning line into block properties.
+fn planning(line: &str) -> Option<Vec<(String, String)>> {
+    let mut properties = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        let (key, keyword) = PLANNING
+            .iter()
+            .find(|(_, keyword)| rest.starts_with(&format!("{}:", keyword)))?;
+        rest = rest[keyword.len() + 1..].trim_start();
+        let close = match rest.chars().next()? {
+            '<' => '>',
+            '[' => ']',
+            _ => return None,
+        };
+        let end = rest.find(close)?;
+        properties.push((key.to_string(), timestamp_from_org(&rest[1..end])));
+        rest = rest[end + 1..].trim_start();
+    }
+    (!properties.is_empty()).then_some(properties)
+}
+
+/// Converts a property value like `2024-05-01 10:00 +1w` to an org timestamp.
+///
+/// Values that don't start with a date are kept as they are.
+fn timestamp_to_org(value: &str, active: bool) -> String {
+    let mut parts = value.split_whitespace();
+    let Some(date) = parts
+        .next()
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    else {
+        return value.to_string();
+    };
+
+    let mut stamp = date.format("%Y-%m-%d %a").to_string();
+    for part in parts {
+        stamp.push(' ');
+        stamp.push_str(part);
+    }
+    if active {
+        format!("<{}>", stamp)
+    } else {
+        format!("[{}]", stamp)
+    }
+}
+
+/// Converts the inside of an org timestamp to a property value, dropping the weekday.
+fn timestamp_from_org(stamp: &str) -> String {
+    let mut parts: Vec<&str> = stamp.split_whitespace().collect();
+    if parts.len() > 1
+        && NaiveDate::parse_from_str(parts[0], "%Y-%m-%d").is_ok()
+        && parts[1].chars().all(char::is_alphabetic)
+    {
+        parts.remove(1);
+    }
+    let value = parts.join(" ");
+    // Normalize `HH:MM` times that org writes without a leading zero
+    match NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M") {
+        Ok(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+        Err(_) => value,
+    }
+}
+
+/// Splits trailing org tags (`:work:urgent:`) off a headline.
+fn split_tags(text: &str) -> (&str, Vec<&str>) {
+    if let Some((head, last)) = text.rsplit_once(char::is_whitespace) {
+        if last.len() > 2 && last.starts_with(':') && last.ends_with(':') {
+            let tags = last[1..last.len() - 1].split(':').collect::<Vec<_>>();
+            if tags.iter().all(|tag| {
+                !tag.is_empty()
+                    && tag
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || "_@#%".contains(c))
+            }) {
+                return (head.trim_end(), tags);
+            }
+        }
+    }
+    (text, Vec::new())
+}
+
+/// Converts inline markdown to org: links, wikilinks, emphasis and code.
+fn inline_to_org(text: &str) -> String {
+    let mut org = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                org.push_str(&format!("~{}~", &rest[1..1 + end]));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if rest.starts_with("[[") {
+            if let Some(end) = rest.find("]]") {
+                match rest[2..end].split_once('|') {
+                    Some((target, label)) => org.push_str(&format!("[[{}][{}]]", target, label)),
+                    None => org.push_str(&rest[..end + 2]),
+                }
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if c == '[' {
+            let link = rest
+                .find("](")
+                .and_then(|middle| rest[middle..].find(')').map(|end| (middle, middle + end)));
+            if let Some((middle, end)) = link {
+                org.push_str(&format!(
+                    "[[{}][{}]]",
+                    &rest[middle + 2..end],
+                    &rest[1..middle]
+                ));
+                rest = &rest[end + 1..];
+                continue;
+            }
+        } else if rest.starts_with("**") {
+            org.push('*');
+            rest = &rest[2..];
+            continue;
+        } else if c == '*' {
+            org.push('/');
+            rest = &rest[1..];
+            continue;
+        }
+        org.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    org
+}
+
+/// Converts inline org to markdown: links, emphasis, code and verbatim.
+fn inline_to_markdown(text: &str) -> String {
+    let mut markdown = String::new();
+    let mut rest = text;
+    let mut previous: Option<char> = None;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("[[") {
+            if let Some(end) = rest.find("]]") {
+                let inner = &rest[2..end];
+                let (target, label) = match inner.split_once("][") {
+                    Some((target, label)) => (target, Some(label)),
+                    None => (inner, None),
+                };
+                markdown.push_str(&match (target.contains("://"), label) {
+                    (true, label) => format!("[{}]({})", label.unwrap_or(target), target),
+                    (false, Some(label)) => format!("[[{}|{}]]", target, label),
+                    (false, None) => format!("[[{}]]", target),
+                });
+                rest = &rest[end + 2..];
+                previous = Some(']');
+                continue;
+            }
+        } else if matches!(c, '*' | '/' | '~' | '=')
+            && previous.is_none_or(|p| p.is_whitespace() || "([{\"'".contains(p))
+        {
+            if let Some(end) = emphasis_end(&rest[1..], c) {
+                let inner = &rest[1..1 + end];
+                markdown.push_str(&match c {
+                    '*' => format!("**{}**", inner),
+                    '/' => format!("*{}*", inner),
+                    _ => format!("`{}`", inner),
+                });
+                rest = &rest[end + 2..];
+                previous = Some(c);
+                continue;
+            }
+        }
+        markdown.push(c);
+        previous = Some(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    markdown
+}
+
+/// Finds the closing marker of an org emphasis, relative to the text after the opening one.
+fn emphasis_end(text: &str, marker: char) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let end = text.find(marker)?;
+    let inner = &text[..end];
+    let after = text[end + 1..].chars().next();
+    (!inner.is_empty()
+        && !inner.ends_with(char::is_whitespace)
+        && after.is_none_or(|c| c.is_whitespace() || ".,;:!?)]}\"'".contains(c)))
+    .then_some(end)
+}
+
+/// Lists the org files below a directory, sorted.
+fn org_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).into_diagnostic()? {
+            let path = entry.into_diagnostic()?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == ORG_EXTENSION) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_org_roundtrip() {
+        let markdown = "alias:: Plans\n\n- TODO Ship **flow** *now* with [[Ada|her]]\n  scheduled:: 2024-05-01 10:00\n  deadline:: 2024-05-03\n  effort:: 2h\n  - DONE Write `docs`\n    closed:: 2024-04-30\n- See [site](https://example.com)";
+
+        let org = to_org("Roadmap", markdown);
+        assert_eq!(
+            org,
+            "#+title: Roadmap\n#+alias: Plans\n* TODO Ship *flow* /now/ with [[Ada][her]]\nSCHEDULED: <2024-05-01 Wed 10:00> DEADLINE: <2024-05-03 Fri>\n:PROPERTIES:\n:effort: 2h\n:END:\n** DONE Write ~docs~\nCLOSED: [2024-04-30 Tue]\n* See [[https://example.com][site]]\n"
+        );
+
+        let (title, back) = from_org(&org);
+        assert_eq!(title.as_deref(), Some("Roadmap"));
+        assert_eq!(
+            back,
+            "alias:: Plans\n- TODO Ship **flow** *now* with [[Ada|her]]\n  scheduled:: 2024-05-01 10:00\n  deadline:: 2024-05-03\n  effort:: 2h\n  - DONE Write `docs`\n    closed:: 2024-04-30\n- See [site](https://example.com)"
+        );
+    }
+}
@@ -0,0 +1,467 @@
+//! Outline Operations
+//!
+//! Indents, outdents and moves blocks together with their children, for
+//! scripts and editor plugins that manipulate the outline. Pages are stored
+//! as text, so blocks are addressed by their page and the line they start at
+//! (see [`Block`](crate::api::Block)) and every operation is a text edit of
+//! the affected pages.
+//!
+//! - Indenting makes a block the last child of its previous sibling.
+//! - Outdenting makes a block the next sibling of its parent, after the
+//!   parent's children.
+//! - Moving places a block after another block, at its depth, or at the end
+//!   of a page, across pages if needed.
+//!
+//! Plain lines, like the file of `flow add --file`, are turned into nested
+//! blocks by [`nest`].
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::page;
+use crate::space::Space;
+
+/// Indentation of one nesting level.
+const INDENT: &str = "  ";
+
+/// Where a block is moved to.
+#[derive(Debug, Clone)]
+pub enum Destination {
+    /// After a block, as its next sibling.
+    After { id: String, line: usize },
+    /// To the end of a page, as a top-level block.
+    Page(String),
+}
+
+/// Location of a block after an outline operation.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `line` (`usize`) - Line the block starts at, starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct BlockLocation {
+    pub id: String,
+    pub page: String,
+    pub line: usize,
+}
+
+impl BlockLocation {
+    fn new(id: &str, index: usize) -> Self {
+        Self {
+            id: id.to_string(),
+            page: page::name_from_id(id),
+            line: index + 1,
+        }
+    }
+}
+
+impl Space {
+    /// Makes a block the last child of its previous sibling.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `line` (`usize`) - Line the block starts at, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<BlockLocation>` - The new location of the block.
+    ///
+    /// # Errors
+    ///
+    /// The line doesn't start a block, the block has no previous sibling, the
+    /// page is locked, or IO errors when reading or writing the page.
+    pub fn indent_block(&mut self, id: &str, line: usize) -> Result<BlockLocation> {
+        let mut lines = self.outline(id)?;
+        let block = span(&lines, id, line)?;
+        let sibling = (0..block.start)
+            .rev()
+            .find_map(|index| depth(&lines[index]).filter(|depth| *depth <= block.depth));
+        if sibling != Some(block.depth) {
+            miette::bail!(
+                "Block at line {} of '{}' has no previous sibling to indent under",
+                line,
+                page::name_from_id(id)
+            );
+        }
+
+        for text in lines[block.start..block.end].iter_mut() {
+            if !text.trim().is_empty() {
+                text.insert_str(0, INDENT);
+            }
+        }
+        self.write_outline(id, &lines)?;
+        Ok(BlockLocation::new(id, block.start))
+    }
+
+    /// Makes a block the next sibling of its parent.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `line` (`usize`) - Line the block starts at, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<BlockLocation>` - The new location of the block.
+    ///
+    /// # Errors
+    ///
+    /// The line doesn't start a block, the block is a top-level block, the
+    /// page is locked, or IO errors when reading or writing the page.
+    pub fn outdent_block(&mut self, id: &str, line: usize) -> Result<BlockLocation> {
+        let mut lines = self.outline(id)?;
+        let block = span(&lines, id, line)?;
+        let Some(parent) = (0..block.start)
+            .rev()
+            .find(|index| depth(&lines[*index]).is_some_and(|depth| depth < block.depth))
+        else {
+            miette::bail!(
+                "Block at line {} of '{}' is a top-level block",
+                line,
+                page::name_from_id(id)
+            );
+        };
+
+        let taken = take(&mut lines, &block);
+        let index = place(&mut lines, taken, Some(parent));
+        self.write_outline(id, &lines)?;
+        Ok(BlockLocation::new(id, index))
+    }
+
+    /// Moves a block with its children.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the pages belong to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `line` (`usize`) - Line the block starts at, starting at 1.
+    /// - `destination` (`&Destination`) - Where to move the block.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<BlockLocation>` - The new location of the block.
+    ///
+    /// # Errors
+    ///
+    /// A line doesn't start a block, the block would be moved into itself, a
+    /// page is locked, or IO errors when reading or writing pages.
+    pub fn move_block(
+        &mut self,
+        id: &str,
+        line: usize,
+        destination: &Destination,
+    ) -> Result<BlockLocation> {
+        let mut lines = self.outline(id)?;
+        let block = span(&lines, id, line)?;
+        let (target, after) = match destination {
+            Destination::After { id, line } => (id.as_str(), Some(*line)),
+            Destination::Page(id) => (id.as_str(), None),
+        };
+
+        if target == id {
+            let after = match after {
+                Some(after) => {
+                    let index = span(&lines, id, after)?.start;
+                    if (block.start..block.end).contains(&index) {
+                        miette::bail!("Can't move a block into itself");
+                    }
+                    // Lines after the block move up once it is taken out
+                    Some(if index > block.start {
+                        index - (block.end - block.start)
+                    } else {
+                        index
+                    })
+                }
+                None => None,
+            };
+            let taken = take(&mut lines, &block);
+            let index = place(&mut lines, taken, after);
+            self.write_outline(id, &lines)?;
+            return Ok(BlockLocation::new(id, index));
+        }
+
+        let mut target_lines = self.outline(target)?;
+        let after = match after {
+            Some(after) => Some(span(&target_lines, target, after)?.start),
+            None => None,
+        };
+        self.ensure_writable(id)?;
+        self.ensure_writable(target)?;
+        let taken = take(&mut lines, &block);
+        let index = place(&mut target_lines, taken, after);
+        self.write_outline(target, &target_lines)?;
+        self.write_outline(id, &lines)?;
+        Ok(BlockLocation::new(target, index))
+    }
+
+    /// Reads the lines of a page.
+    fn outline(&self, id: &str) -> Result<Vec<String>> {
+        let Some(content) = self.read_page(id)? else {
+            miette::bail!("Page '{}' doesn't exist", page::name_from_id(id));
+        };
+        Ok(content.lines().map(str::to_string).collect())
+    }
+
+    /// Writes the lines of a page.
+    fn write_outline(&mut self, id: &str, lines: &[String]) -> Result<()> {
+        let mut content = lines.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        self.write_page(id, &content)
+    }
+}
+
+/// Lines of a block and its children.
+struct Span {
+    start: usize,
+    end: usize,
+    depth: usize,
+}
+
+/// Returns the nesting depth of a line starting a block.
+fn depth(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    if !trimmed.starts_with("- ") && trimmed != "-" {
+        return None;
+    }
+    let indent = &line[..line.len() - trimmed.len()];
+    Some(
+        indent
+            .chars()
+            .map(|c| if c == '\t' { 2 } else { 1 })
+            .sum::<usize>()
+            / 2,
+    )
+}
+
+/// Finds the lines of the block starting at a line.
+fn span(lines: &[String], id: &str, line: usize) -> Result<Span> {
+    let start = line.wrapping_sub(1);
+    let Some(level) = lines.get(start).and_then(|text| depth(text)) else {
+        miette::bail!(
+            "Line {} of '{}' doesn't start a block",
+            line,
+            page::name_from_id(id)
+        );
+    };
+    let mut end = (start + 1..lines.len())
+        .find(|index| depth(&lines[*index]).is_some_and(|d| d <= level))
+        .unwrap_or(lines.len());
+    // Blank lines between blocks stay where they are
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    Ok(Span {
+        start,
+        end,
+        depth: level,
+    })
+}
+
+/// Removes a block from its page, with indentation relative to the block.
+fn take(lines: &mut Vec<String>, block: &Span) -> Vec<String> {
+    let taken: Vec<String> = lines.drain(block.start..block.end).collect();
+    let indent = taken[0].len() - taken[0].trim_start().len();
+    taken
+        .into_iter()
+        .map(|line| {
+            let whitespace = line.len() - line.trim_start().len();
+            line[whitespace.min(indent)..].to_string()
+        })
+        .collect()
+}
+
+/// Inserts a block after another block and its children, at its depth, or
+/// at the end of the page as a top-level block.
+///
+/// # Returns
+///
+/// - `usize` - Index of the first line of the inserted block.
+fn place(lines: &mut Vec<String>, block: Vec<String>, after: Option<usize>) -> usize {
+    let (index, indent) = match after {
+        Some(after) => {
+            let level = depth(&lines[after]).unwrap_or_default();
+            let mut end = (after + 1..lines.len())
+                .find(|index| depth(&lines[*index]).is_some_and(|d| d <= level))
+                .unwrap_or(lines.len());
+            while end > after + 1 && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+            (end, INDENT.repeat(level))
+        }
+        None => {
+            while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
+            (lines.len(), String::new())
+        }
+    };
+
+    let block = block.into_iter().map(|line| {
+        if line.is_empty() {
+            line
+        } else {
+            format!("{}{}", indent, line)
+        }
+    });
+    lines.splice(index..index, block);
+    index
+}
+
+/// Turns plain lines into blocks, nested by their indentation.
+///
+/// Empty lines are skipped and a leading `- ` is dropped. A line indented
+/// deeper than the one above becomes its child, however deep; tabs and
+/// spaces both count as indentation.
+///
+/// # Arguments
+///
+/// - `text` (`&str`) - Lines to turn into blocks.
+///
+/// # Returns
+///
+/// - `Vec<(usize, String)>` - Nesting depth and content of every block.
+pub(crate) fn nest(text: &str) -> Vec<(usize, String)> {
+    let mut indents: Vec<usize> = Vec::new();
+    let mut blocks = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        while indents.last().is_some_and(|last| *last >= indent) {
+            indents.pop();
+        }
+        let content = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+        blocks.push((indents.len(), content.to_string()));
+        indents.push(indent);
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "title:: Plan\n- One\n  - One a\n- Two\n  continued\n  - Two a\n- Three\n";
+
+    fn space() -> Space {
+        let mut space = Space::in_memory().unwrap();
+        space.write_page("pages/Plan.md", PAGE).unwrap();
+        space
+    }
+
+    fn read(space: &Space, id: &str) -> String {
+        space.read_page(id).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_indent_block() {
+        let mut space = space();
+        let moved = space.indent_block("pages/Plan.md", 4).unwrap();
+        assert_eq!(moved.line, 4);
+        assert_eq!(
+            read(&space, "pages/Plan.md"),
+            "title:: Plan\n- One\n  - One a\n  - Two\n    continued\n    - Two a\n- Three\n"
+        );
+
+        assert!(space.indent_block("pages/Plan.md", 2).is_err());
+        assert!(space.indent_block("pages/Plan.md", 1).is_err());
+    }
+
+    #[test]
+    fn test_outdent_block() {
+        let mut space = space();
+        space
+            .write_page("pages/Plan.md", "- One\n  - One a\n  - One b\n- Two\n")
+            .unwrap();
+        let moved = space.outdent_block("pages/Plan.md", 2).unwrap();
+        assert_eq!(moved.line, 3);
+        assert_eq!(
+            read(&space, "pages/Plan.md"),
+            "- One\n  - One b\n- One a\n- Two\n"
+        );
+        assert!(space.outdent_block("pages/Plan.md", 1).is_err());
+    }
+
+    #[test]
+    fn test_move_block_within_page() {
+        let mut space = space();
+        let destination = Destination::After {
+            id: "pages/Plan.md".to_string(),
+            line: 7,
+        };
+        let moved = space.move_block("pages/Plan.md", 2, &destination).unwrap();
+        assert_eq!(moved.line, 6);
+        assert_eq!(
+            read(&space, "pages/Plan.md"),
+            "title:: Plan\n- Two\n  continued\n  - Two a\n- Three\n- One\n  - One a\n"
+        );
+
+        let into_itself = Destination::After {
+            id: "pages/Plan.md".to_string(),
+            line: 4,
+        };
+        assert!(space.move_block("pages/Plan.md", 2, &into_itself).is_err());
+    }
+
+    #[test]
+    fn test_move_block_to_other_page() {
+        let mut space = space();
+        space
+            .write_page("pages/Later.md", "- Someday\n  - Maybe\n")
+            .unwrap();
+
+        let after = Destination::After {
+            id: "pages/Later.md".to_string(),
+            line: 2,
+        };
+        let moved = space.move_block("pages/Plan.md", 4, &after).unwrap();
+        assert_eq!(moved.line, 3);
+        assert_eq!(
+            read(&space, "pages/Later.md"),
+            "- Someday\n  - Maybe\n  - Two\n    continued\n    - Two a\n"
+        );
+        assert_eq!(
+            read(&space, "pages/Plan.md"),
+            "title:: Plan\n- One\n  - One a\n- Three\n"
+        );
+
+        let moved = space
+            .move_block(
+                "pages/Plan.md",
+                3,
+                &Destination::Page("pages/Later.md".to_string()),
+            )
+            .unwrap();
+        assert_eq!(moved.line, 6);
+        assert_eq!(
+            read(&space, "pages/Later.md"),
+            "- Someday\n  - Maybe\n  - Two\n    continued\n    - Two a\n- One a\n"
+        );
+    }
+
+    #[test]
+    fn test_nest() {
+        let text = "Groceries\n  - Milk\n\tOat\n\n- Call mom\n    deep\n  shallow\n";
+        assert_eq!(
+            nest(text),
+            vec![
+                (0, "Groceries".to_string()),
+                (1, "Milk".to_string()),
+                (1, "Oat".to_string()),
+                (0, "Call mom".to_string()),
+                (1, "deep".to_string()),
+                (1, "shallow".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,645 @@
+//! Page Naming and Properties
+//!
+//! Pages are the markdown files of a space. They are identified internally by
+//! their path relative to the space root (e.g. `journal/2024-05-01.md`), which
+//! is also the name of their text container in the document. Users refer to
+//! pages by name: journal pages by their date, other pages by their path below
+//! `pages/` without the extension (e.g. `projects/flow`).
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::{Component, Path};
+
+use crate::error::Error;
+use crate::render;
+use crate::space::JOURNAL_DIR;
+
+pub(crate) const PAGES_DIR: &str = "pages";
+const EXTENSION: &str = ".md";
+
+/// Separator of namespaces in page names, e.g. `projects/flow/sync-design`.
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// Keywords of open tasks.
+pub const OPEN_TASK_KEYWORDS: &[&str] = &["TODO", "LATER", "NOW", "DOING", "WAITING"];
+
+/// Keyword of done tasks.
+pub const DONE_KEYWORD: &str = "DONE";
+
+/// Returns the user-facing name of the page with the given id.
+///
+/// # Arguments
+///
+/// - `id` (`&str`) - Id of the page (relative markdown path).
+///
+/// # Returns
+///
+/// - `String` - Name of the page.
+pub fn name_from_id(id: &str) -> String {
+    let without_ext = id.strip_suffix(EXTENSION).unwrap_or(id);
+    without_ext
+        .strip_prefix(&format!("{}/", JOURNAL_DIR))
+        .or_else(|| without_ext.strip_prefix(&format!("{}/", PAGES_DIR)))
+        .unwrap_or(without_ext)
+        .to_string()
+}
+
+/// Returns the id of a page with the given name.
+///
+/// Names that are dates (`YYYY-MM-DD`) map to journal pages, all other names
+/// map to pages below `pages/`. Week and month names only map to journal
+/// pages within a space that uses them (see [`Space::page_id`](crate::space::Space::page_id)).
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name of the page.
+///
+/// # Returns
+///
+/// - `String` - Id of the page (relative markdown path).
+pub fn id_from_name(name: &str) -> String {
+    if chrono::NaiveDate::parse_from_str(name, "%Y-%m-%d").is_ok() {
+        journal_id(name)
+    } else {
+        format!("{}/{}{}", PAGES_DIR, name, EXTENSION)
+    }
+}
+
+/// Checks that a page id stays within the space.
+///
+/// Ids are built from user-supplied names (see [`id_from_name`]), so a name
+/// like `../../notes` would otherwise write outside of the space.
+///
+/// # Arguments
+///
+/// - `id` (`&str`) - Id of the page.
+///
+/// # Errors
+///
+/// [`Error::InvalidPageName`] if the id is absolute or has `.`, `..` or
+/// empty segments.
+pub fn check_id(id: &str) -> Result<()> {
+    let relative = Path::new(id)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if !relative
+        || id
+            .split(['/', '\\'])
+            .any(|segment| matches!(segment, "" | "." | ".."))
+    {
+        return Err(Error::InvalidPageName {
+            name: name_from_id(id),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns the id of the journal page with the given name.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name of the journal page, e.g. `2024-W20`.
+///
+/// # Returns
+///
+/// - `String` - Id of the page (relative markdown path).
+pub fn journal_id(name: &str) -> String {
+    format!("{}/{}{}", JOURNAL_DIR, name, EXTENSION)
+}
+
+/// Checks whether the page with the given id is a journal page.
+///
+/// # Arguments
+///
+/// - `id` (`&str`) - Id of the page.
+///
+/// # Returns
+///
+/// - `bool` - True if the page lives in the journal.
+pub fn is_journal(id: &str) -> bool {
+    id.starts_with(&format!("{}/", JOURNAL_DIR))
+}
+
+/// Parses the page-level properties of a page.
+///
+/// Page properties are `key:: value` lines at the very top of the page,
+/// before the first block.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<(String, String)>` - Property keys and values in order of appearance.
+pub fn properties(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .map_while(|line| {
+            let (key, value) = line.split_once("::")?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) || key.starts_with('-') {
+                return None;
+            }
+            Some((key.to_lowercase(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Adds values to a comma separated page property.
+///
+/// The first leading property with one of the given keys is extended; if
+/// there is none, a property with the first key is inserted at the top.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+/// - `keys` (`&[&str]`) - Keys of the property, e.g. `["tags", "tag"]`.
+/// - `values` (`&[String]`) - Values to add.
+///
+/// # Returns
+///
+/// - `String` - The updated content.
+pub fn extend_property(content: &str, keys: &[&str], values: &[String]) -> String {
+    let count = properties(content).len();
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let offset = lines.iter().take_while(|l| l.trim().is_empty()).count();
+    let property = lines
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(count)
+        .find(|(_, line)| {
+            line.split_once("::")
+                .is_some_and(|(key, _)| keys.contains(&key.trim().to_lowercase().as_str()))
+        })
+        .map(|(index, _)| index);
+    match property {
+        Some(index) => {
+            let line = lines[index].trim_end();
+            let separator = if line.ends_with("::") { " " } else { ", " };
+            lines[index] = format!("{}{}{}", line, separator, values.join(", "));
+        }
+        None => lines.insert(offset, format!("{}:: {}", keys[0], values.join(", "))),
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Returns the aliases declared by a page via an `alias::` property.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<String>` - Aliases of the page.
+pub fn aliases(content: &str) -> Vec<String> {
+    properties(content)
+        .into_iter()
+        .filter(|(key, _)| key == "alias" || key == "aliases")
+        .flat_map(|(_, value)| {
+            value
+                .split(',')
+                .map(|alias| alias.trim().trim_start_matches("[[").trim_end_matches("]]"))
+                .filter(|alias| !alias.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Returns the targets of all wikilinks (`[[Target]]`) in a page.
+///
+/// Link labels (`[[Target|label]]`) and heading suffixes (`[[Target#Heading]]`)
+/// are stripped, so targets are page names. Targets are returned in order of
+/// appearance, without duplicates. Embeds are not links, see [`embeds`].
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<String>` - Link targets.
+pub fn links(content: &str) -> Vec<String> {
+    wikilinks(content, false)
+}
+
+/// Returns the pages embedded into a page with `![[Page]]` or `{{embed [[Page]]}}`.
+///
+/// Section suffixes (`![[Page#Section]]`) are stripped, so targets are page
+/// names. Targets are returned in order of appearance, without duplicates.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<String>` - Embedded page names.
+pub fn embeds(content: &str) -> Vec<String> {
+    wikilinks(content, true)
+}
+
+/// Collects the targets of either plain wikilinks or embeds.
+fn wikilinks(content: &str, embeds: bool) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("[[") {
+        let before = &rest[..start];
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else {
+            break;
+        };
+
+        let embed = before.ends_with('!') || before.trim_end().ends_with("{{embed");
+        let inner = &after[..end];
+        let target = inner.split('|').next().unwrap_or(inner);
+        let target = target.split('#').next().unwrap_or(target).trim();
+        if embed == embeds && !target.is_empty() && !targets.iter().any(|t| t == target) {
+            targets.push(target.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+
+    targets
+}
+
+/// Checks whether a link target is relative to the namespace of the linking page.
+///
+/// # Arguments
+///
+/// - `target` (`&str`) - Link target, e.g. `./sync-design` or `../roadmap`.
+///
+/// # Returns
+///
+/// - `bool` - True for targets starting with `./` or `../`.
+pub fn is_relative(target: &str) -> bool {
+    target.starts_with("./") || target.starts_with("../")
+}
+
+/// Resolves a link target relative to the page containing the link.
+///
+/// `./` refers to the namespace of the linking page and `../` to its parent,
+/// so `[[./sync-design]]` on `projects/flow/roadmap` links to
+/// `projects/flow/sync-design`. Other targets are returned unchanged.
+///
+/// # Arguments
+///
+/// - `from` (`&str`) - Name of the linking page.
+/// - `target` (`&str`) - Link target.
+///
+/// # Returns
+///
+/// - `String` - The absolute page name.
+pub fn absolute_name(from: &str, target: &str) -> String {
+    if !is_relative(target) {
+        return target.to_string();
+    }
+
+    let mut parts: Vec<&str> = from.split(NAMESPACE_SEPARATOR).collect();
+    parts.pop();
+    for segment in target.split(NAMESPACE_SEPARATOR) {
+        match segment {
+            "." | "" => {}
+            ".." => {
+                parts.pop();
+            }
+            segment => parts.push(segment),
+        }
+    }
+    parts.join(&NAMESPACE_SEPARATOR.to_string())
+}
+
+/// Rewrites the targets of the wikilinks and embeds of a page.
+///
+/// Labels (`[[Target|label]]`) and heading suffixes (`[[Target#Heading]]`)
+/// are kept, only the page name is replaced.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+/// - `rewrite` (`impl FnMut(&str) -> Option<String>`) - New target of a link target as written, `None` to keep it.
+///
+/// # Returns
+///
+/// - `(String, usize)` - The updated content and the number of rewritten links.
+pub fn rewrite_links(
+    content: &str,
+    mut rewrite: impl FnMut(&str) -> Option<String>,
+) -> (String, usize) {
+    let mut result = String::with_capacity(content.len());
+    let mut count = 0;
+    let mut rest = content;
+
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else {
+            break;
+        };
+        let inner = &after[..end];
+        let split = inner.find(['|', '#']).unwrap_or(inner.len());
+        result.push_str(&rest[..start + 2]);
+        match rewrite(inner[..split].trim()) {
+            Some(target) => {
+                result.push_str(&target);
+                result.push_str(&inner[split..]);
+                count += 1;
+            }
+            None => result.push_str(inner),
+        }
+        result.push_str("]]");
+        rest = &after[end + 2..];
+    }
+    result.push_str(rest);
+
+    (result, count)
+}
+
+/// Replaces relative link targets of a page with absolute page names.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+/// - `name` (`&str`) - Name of the page.
+///
+/// # Returns
+///
+/// - `String` - Content with absolute links only.
+pub fn absolute_links(content: &str, name: &str) -> String {
+    rewrite_links(content, |target| {
+        is_relative(target).then(|| absolute_name(name, target))
+    })
+    .0
+}
+
+/// A heading of a page.
+///
+/// # Fields
+///
+/// - `level` (`usize`) - Heading level, 1 for `#`.
+/// - `text` (`String`) - Text of the heading.
+/// - `anchor` (`String`) - Anchor of the heading, the target of `[[Page#Heading]]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    pub anchor: String,
+}
+
+/// Returns the outline of a page.
+///
+/// Headings are markdown headings at the start of a line or of a bullet
+/// (`- ## Heading`). Headings inside fenced code are skipped.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<Heading>` - Headings in order of appearance.
+pub fn headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut code = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            code = !code;
+            continue;
+        }
+        if code {
+            continue;
+        }
+        let text = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+        let level = text.len() - text.trim_start_matches('#').len();
+        let Some(text) = text[level..].strip_prefix(' ').map(str::trim) else {
+            continue;
+        };
+        if (1..=6).contains(&level) && !text.is_empty() {
+            headings.push(Heading {
+                level,
+                text: text.to_string(),
+                anchor: render::slug(text),
+            });
+        }
+    }
+
+    headings
+}
+
+/// Returns the tags of a page.
+///
+/// Tags come from a `tags::` page property and from inline `#tag` tokens.
+/// Tags are returned without the leading `#`, in order of appearance and
+/// without duplicates.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<String>` - Tags of the page.
+pub fn tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut push = |tag: &str| {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    };
+
+    for (_, value) in properties(content)
+        .into_iter()
+        .filter(|(key, _)| key == "tags" || key == "tag")
+    {
+        for tag in value.split(',') {
+            push(
+                tag.trim()
+                    .trim_start_matches('#')
+                    .trim_start_matches("[[")
+                    .trim_end_matches("]]"),
+            );
+        }
+    }
+
+    for word in content.split_whitespace() {
+        // `#` followed by whitespace is a heading and never reaches here
+        let Some(tag) = word.strip_prefix('#') else {
+            continue;
+        };
+        let end = tag
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '/')))
+            .unwrap_or(tag.len());
+        push(&tag[..end]);
+    }
+
+    tags
+}
+
+/// Toggles the task of a block line between open and done.
+///
+/// Open tasks (see [`OPEN_TASK_KEYWORDS`]) become `DONE`, done tasks become
+/// `TODO`. Task list items (`- [ ]`, `- [x]`) are checked and unchecked.
+/// Indentation and the rest of the line are kept.
+///
+/// # Arguments
+///
+/// - `line` (`&str`) - Line of a block, e.g. `  - TODO Ship it`.
+///
+/// # Returns
+///
+/// - `Option<String>` - The toggled line, `None` if the line isn't a task.
+pub fn toggle_task(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let text = trimmed.strip_prefix("- ")?;
+    if let Some(rest) = text.strip_prefix("[ ]") {
+        return Some(format!("{}- [x]{}", indent, rest));
+    }
+    if let Some(rest) = text
+        .strip_prefix("[x]")
+        .or_else(|| text.strip_prefix("[X]"))
+    {
+        return Some(format!("{}- [ ]{}", indent, rest));
+    }
+    let keyword = text.split_whitespace().next()?;
+    let rest = text.strip_prefix(keyword)?;
+    let toggled = if keyword == DONE_KEYWORD {
+        "TODO"
+    } else if OPEN_TASK_KEYWORDS.contains(&keyword) {
+        DONE_KEYWORD
+    } else {
+        return None;
+    };
+    Some(format!("{}- {}{}", indent, toggled, rest))
+}
+
+/// Makes a string safe to use as a file name.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name to clean, e.g. a mail subject or attachment name.
+/// - `space` (`char`) - Replacement for whitespace.
+///
+/// # Returns
+///
+/// - `String` - The name without path separators, reserved characters and
+///   surrounding dots or whitespace.
+pub fn file_name(name: &str, space: char) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_whitespace() => space,
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_matches(|c: char| c == '.' || c == '-' || c.is_whitespace())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip_through_ids() {
+        assert_eq!(name_from_id("journal/2024-05-01.md"), "2024-05-01");
+        assert_eq!(name_from_id("pages/projects/flow.md"), "projects/flow");
+        assert_eq!(id_from_name("2024-05-01"), "journal/2024-05-01.md");
+        assert_eq!(id_from_name("projects/flow"), "pages/projects/flow.md");
+        assert_eq!(id_from_name("2023-10"), "pages/2023-10.md");
+        assert_eq!(journal_id("2024-W20"), "journal/2024-W20.md");
+    }
+
+    #[test]
+    fn test_check_id_rejects_escaping_names() {
+        assert!(check_id(&id_from_name("projects/flow")).is_ok());
+        assert!(check_id(&id_from_name("../../notes")).is_err());
+        assert!(check_id(&id_from_name("projects/../../notes")).is_err());
+        assert!(check_id(&id_from_name("projects//flow")).is_err());
+        assert!(check_id("/etc/passwd.md").is_err());
+    }
+
+    #[test]
+    fn test_links_strip_labels_and_duplicates() {
+        let content = "- See [[Flow]] and [[Rust|the language]]\n- Again [[Flow]] [[ ]]";
+        assert_eq!(links(content), vec!["Flow", "Rust"]);
+    }
+
+    #[test]
+    fn test_embeds_are_not_links() {
+        let content = "- ![[Meeting#Agenda]]\n- {{embed [[Roadmap]]}} and [[Flow]]";
+        assert_eq!(embeds(content), vec!["Meeting", "Roadmap"]);
+        assert_eq!(links(content), vec!["Flow"]);
+    }
+
+    #[test]
+    fn test_relative_links_resolve_within_namespace() {
+        let from = "projects/flow/roadmap";
+        assert_eq!(absolute_name(from, "./sync"), "projects/flow/sync");
+        assert_eq!(absolute_name(from, "../garden"), "projects/garden");
+        assert_eq!(absolute_name(from, "Rust"), "Rust");
+        assert_eq!(
+            absolute_links("- [[./sync#Risks|risks]] and ![[../garden]]", from),
+            "- [[projects/flow/sync#Risks|risks]] and ![[projects/garden]]"
+        );
+    }
+
+    #[test]
+    fn test_headings_outline() {
+        let content = "# Plan\n- ## Next Steps\n```\n# not a heading\n```\n#tag\n";
+        let outline: Vec<_> = headings(content)
+            .into_iter()
+            .map(|h| (h.level, h.text, h.anchor))
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                (1, "Plan".to_string(), "plan".to_string()),
+                (2, "Next Steps".to_string(), "next-steps".to_string()),
+            ]
+        );
+        assert_eq!(links("- [[Plan#Next Steps|next]]"), vec!["Plan"]);
+    }
+
+    #[test]
+    fn test_tags_from_property_and_inline() {
+        let content = "tags:: rust, #cli\n# Heading\n- Working on #flow/core, also #rust.\n";
+        assert_eq!(tags(content), vec!["rust", "cli", "flow/core"]);
+    }
+
+    #[test]
+    fn test_aliases_are_read_from_leading_properties() {
+        let content = "alias:: Flow, [[flow-app]]\ntype:: project\n- alias:: not a property\n";
+        assert_eq!(aliases(content), vec!["Flow", "flow-app"]);
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("Re: a/b?", ' '), "Re- a-b");
+        assert_eq!(file_name("my scan.png", '-'), "my-scan.png");
+        assert_eq!(file_name("../..", '-'), "");
+    }
+
+    #[test]
+    fn test_toggle_task() {
+        assert_eq!(
+            toggle_task("  - TODO Ship").as_deref(),
+            Some("  - DONE Ship")
+        );
+        assert_eq!(toggle_task("- [ ] Pack").as_deref(), Some("- [x] Pack"));
+        assert_eq!(toggle_task("- [x] Pack").as_deref(), Some("- [ ] Pack"));
+        assert_eq!(toggle_task("- Notes"), None);
+    }
+}
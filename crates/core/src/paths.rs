@@ -0,0 +1,213 @@
+//! Path Normalization
+//!
+//! Spaces are looked up by path in many places (`--graph ./notes`, `open notes/`,
+//! registered absolute paths). This module provides the single normalization
+//! used for all of these comparisons, so that relative, trailing-slash,
+//! symlinked and canonical spellings of the same directory compare equal.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Normalizes a path for storage and comparison.
+///
+/// Existing paths are canonicalized (resolving symlinks). Paths that don't
+/// exist (yet) are made absolute and normalized lexically. On Windows the
+/// extended-length prefix added by canonicalization (`\\?\`, `\\?\UNC\`) is
+/// removed again, so stored paths match what users type.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path to normalize.
+///
+/// # Returns
+///
+/// - `PathBuf` - Normalized absolute path.
+pub fn normalize(path: &Path) -> PathBuf {
+    let normalized = path
+        .canonicalize()
+        .or_else(|_| std::path::absolute(path).map(|absolute| lexical(&absolute)))
+        .unwrap_or_else(|_| lexical(path));
+
+    let as_str = normalized.to_string_lossy();
+    match strip_verbatim(&as_str) {
+        Some(stripped) => PathBuf::from(stripped),
+        None => normalized,
+    }
+}
+
+/// Checks whether two paths point to the same location after normalization.
+///
+/// # Arguments
+///
+/// - `a` (`&Path`) - First path.
+/// - `b` (`&Path`) - Second path.
+///
+/// # Returns
+///
+/// - `bool` - True if both paths normalize to the same path.
+pub fn same(a: &Path, b: &Path) -> bool {
+    normalize(a) == normalize(b)
+}
+
+/// Returns the home directory of the current user.
+///
+/// # Returns
+///
+/// - `Option<PathBuf>` - `$HOME`, or `%USERPROFILE%` on Windows, if set.
+pub fn home() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Replaces a leading home directory with `~`, for paths shared between machines.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path to contract.
+/// - `home` (`&Path`) - Home directory.
+///
+/// # Returns
+///
+/// - `PathBuf` - The path starting with `~`, or unchanged if it's outside of `home`.
+pub fn contract_home(path: &Path, home: &Path) -> PathBuf {
+    match path.strip_prefix(home) {
+        Ok(rest) => Path::new("~").join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Replaces a leading `~` with the home directory.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path to expand.
+/// - `home` (`&Path`) - Home directory.
+///
+/// # Returns
+///
+/// - `PathBuf` - The path below `home`, or unchanged if it doesn't start with `~`.
+pub fn expand_home(path: &Path, home: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Removes the Windows extended-length prefix from a path string.
+///
+/// `\\?\C:\notes` becomes `C:\notes` and `\\?\UNC\server\share` becomes
+/// `\\server\share`.
+///
+/// # Arguments
+///
+/// - `path` (`&str`) - Path to strip.
+///
+/// # Returns
+///
+/// - `Option<String>` - The stripped path, or `None` if it had no prefix.
+pub fn strip_verbatim(path: &str) -> Option<String> {
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        return Some(format!(r"\\{}", unc));
+    }
+    path.strip_prefix(r"\\?\").map(str::to_string)
+}
+
+/// Normalizes `.` and `..` components without touching the filesystem.
+fn lexical(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("flow-paths-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn test_relative_and_trailing_slash_match_absolute() {
+        let cwd = std::env::current_dir().unwrap();
+        let absolute = normalize(&cwd);
+
+        assert_eq!(normalize(Path::new(".")), absolute);
+        assert_eq!(normalize(Path::new("./")), absolute);
+        assert!(same(Path::new("."), &cwd));
+    }
+
+    #[test]
+    fn test_missing_paths_are_normalized_lexically() {
+        let dir = scratch_dir("missing");
+
+        let spelled = dir.join("a").join(".").join("b").join("..").join("notes");
+        assert_eq!(normalize(&spelled), dir.join("a").join("notes"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_resolve_to_target() {
+        let dir = scratch_dir("symlink");
+        let target = dir.join("notes");
+        let link = dir.join("link");
+        fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(same(&link, &target));
+    }
+
+    #[test]
+    fn test_home_round_trip() {
+        let home = Path::new("/home/ada");
+        let notes = Path::new("/home/ada/notes/work");
+
+        let contracted = contract_home(notes, home);
+        assert_eq!(contracted, Path::new("~/notes/work"));
+        assert_eq!(
+            expand_home(&contracted, Path::new("/Users/ada")),
+            Path::new("/Users/ada/notes/work")
+        );
+        assert_eq!(
+            contract_home(Path::new("/srv/notes"), home),
+            Path::new("/srv/notes")
+        );
+        assert_eq!(
+            expand_home(Path::new("/srv/notes"), home),
+            Path::new("/srv/notes")
+        );
+        assert_eq!(
+            expand_home(Path::new("~ada/notes"), home),
+            Path::new("~ada/notes")
+        );
+    }
+
+    #[test]
+    fn test_strip_verbatim_prefixes() {
+        assert_eq!(
+            strip_verbatim(r"\\?\C:\notes"),
+            Some(r"C:\notes".to_string())
+        );
+        assert_eq!(
+            strip_verbatim(r"\\?\UNC\server\share\notes"),
+            Some(r"\\server\share\notes".to_string())
+        );
+        assert_eq!(strip_verbatim(r"C:\notes"), None);
+        assert_eq!(strip_verbatim("/home/notes"), None);
+    }
+}
@@ -0,0 +1,586 @@
+//! WASM Plugins
+//!
+//! Plugins are WebAssembly modules in `.flow/plugins/`, run in process but
+//! sandboxed by [wasmtime](https://wasmtime.dev): a plugin can only reach
+//! the space through the host functions below, and only those its manifest
+//! requests and the user granted. The manifest is a TOML file next to the
+//! module with the same stem (`.flow/plugins/todo.wasm` and
+//! `.flow/plugins/todo.toml`):
+//!
+//! ```toml
+//! name = "todo"
+//! description = "Collects open tasks"
+//! capabilities = ["read", "write"]
+//! ```
+//!
+//! A module without manifest is named after its file and has no
+//! capabilities. Plugins arrive with the space, e.g. through sync or an
+//! archive, so requesting a capability isn't enough: the user grants it in
+//! their own configuration (see [`Config::grant_plugin`]), by module stem:
+//!
+//! ```toml
+//! [spaces.notes.plugins]
+//! todo = ["read", "write"]
+//! ```
+//!
+//! Every call gets a fixed amount of fuel and memory is capped, so runaway
+//! plugins are stopped.
+//!
+//! # ABI
+//!
+//! Strings cross the boundary as UTF-8 in the plugin's exported `memory`.
+//! Strings passed to the host are `(ptr, len)` pairs, strings returned by
+//! the host are written into memory allocated with the plugin's exported
+//! `flow_alloc(len) -> ptr` and returned as `i64` with the pointer in the
+//! upper and the length in the lower 32 bits (`0` for none).
+//!
+//! The plugin exports:
+//!
+//! * `flow_init()` - Optional, called once after loading to register commands and renderers
+//! * `flow_command(ptr, len) -> i64` - Runs a command, given `{ "command", "args" }` as JSON
+//! * `flow_render(ptr, len) -> i64` - Runs a renderer, given `{ "renderer", "content" }` as JSON
+//!
+//! The host provides, in the `flow` module:
+//!
+//! * `log(ptr, len)` - Adds a line to the output log
+//! * `register_command(ptr, len)` and `register_renderer(ptr, len)` - Register by name
+//! * `list_pages() -> i64` - JSON array of page names (`read`)
+//! * `read_page(ptr, len) -> i64` - Content of a page by name or alias (`read`)
+//! * `search(ptr, len) -> i64` - JSON array of names of pages containing the text (`read`)
+//! * `append_block(page_ptr, page_len, ptr, len) -> i32` - Appends a block to a page (`write`)
+//!
+//! Appended blocks are applied to the space after the call returns, a
+//! failing call changes nothing.
+//!
+//! A renderer also renders the fenced blocks whose language is its name
+//! when pages are shown in the terminal (see [`display`](crate::display)).
+
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use wasmtime::{
+    Caller, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+use crate::config::Config;
+use crate::index::PageIndex;
+use crate::page;
+use crate::space::{Space, FLOW_DIR};
+
+/// Directory below `.flow` holding the plugins.
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// Fuel of a single call into a plugin, roughly the number of instructions.
+const FUEL: u64 = 1_000_000_000;
+
+/// Largest linear memory a plugin may grow to, in bytes.
+const MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+/// What a plugin may do with the space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Capability {
+    /// List, read and search pages.
+    Read,
+    /// Append blocks to pages.
+    Write,
+}
+
+impl Capability {
+    /// Returns the name of the capability, as written in manifests and grants.
+    ///
+    /// # Returns
+    ///
+    /// - `&'static str` - The name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+}
+
+impl std::str::FromStr for Capability {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "read" => Ok(Self::Read),
+            "write" => Ok(Self::Write),
+            _ => Err(format!(
+                "unknown capability '{}', expected read or write",
+                value
+            )),
+        }
+    }
+}
+
+/// Manifest of a plugin.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name of the plugin.
+/// - `description` (`String`) - What the plugin does.
+/// - `capabilities` (`Vec<Capability>`) - Capabilities granted to the plugin.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+}
+
+/// An installed plugin.
+///
+/// # Fields
+///
+/// - `manifest` (`PluginManifest`) - Manifest of the plugin.
+/// - `path` (`PathBuf`) - Path of the module.
+/// - `granted` (`Vec<Capability>`) - Requested capabilities the user granted.
+/// - `commands` (`Vec<String>`) - Commands the plugin registered.
+/// - `renderers` (`Vec<String>`) - Renderers the plugin registered.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PluginInfo {
+    #[serde(flatten)]
+    pub manifest: PluginManifest,
+    pub path: PathBuf,
+    pub granted: Vec<Capability>,
+    pub commands: Vec<String>,
+    pub renderers: Vec<String>,
+}
+
+/// Result of running a plugin command.
+///
+/// # Fields
+///
+/// - `output` (`String`) - Output returned by the plugin.
+/// - `log` (`Vec<String>`) - Lines the plugin logged.
+/// - `appended` (`Vec<String>`) - Ids of the pages the plugin appended to.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct PluginOutput {
+    pub output: String,
+    pub log: Vec<String>,
+    pub appended: Vec<String>,
+}
+
+/// State a plugin instance can reach through the host functions.
+struct HostState {
+    /// Module name, which grants are keyed by
+    plugin: String,
+    /// Capabilities both requested and granted
+    capabilities: Vec<Capability>,
+    limits: StoreLimits,
+    /// Content of all pages by name, only filled with the `read` capability
+    pages: BTreeMap<String, String>,
+    index: PageIndex,
+    appends: Vec<(String, String)>,
+    commands: Vec<String>,
+    renderers: Vec<String>,
+    log: Vec<String>,
+}
+
+/// A loaded plugin instance.
+struct Loaded {
+    manifest: PluginManifest,
+    path: PathBuf,
+    store: Store<HostState>,
+    instance: Instance,
+}
+
+impl Space {
+    /// Lists the installed plugins with the commands and renderers they register.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<PluginInfo>>` - Installed plugins, ordered by file name.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the plugins directory, or a plugin failing to load.
+    pub fn plugins(&self) -> Result<Vec<PluginInfo>> {
+        let mut plugins = Vec::new();
+        for path in self.plugin_paths()? {
+            let loaded = self.load_plugin(&path)?;
+            let state = loaded.store.data();
+            plugins.push(PluginInfo {
+                granted: state.capabilities.clone(),
+                commands: state.commands.clone(),
+                renderers: state.renderers.clone(),
+                manifest: loaded.manifest,
+                path: loaded.path,
+            });
+        }
+        Ok(plugins)
+    }
+
+    /// Runs a command registered by a plugin.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the plugin runs against.
+    /// - `command` (`&str`) - Name of the command.
+    /// - `args` (`&[String]`) - Arguments passed to the command.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<PluginOutput>` - The output and effects of the command.
+    ///
+    /// # Errors
+    ///
+    /// No plugin registers the command, the plugin traps or exceeds its fuel,
+    /// or IO errors when saving appended blocks.
+    pub fn run_plugin_command(&mut self, command: &str, args: &[String]) -> Result<PluginOutput> {
+        let mut loaded = self
+            .find_plugin(|state| state.commands.iter().any(|c| c == command))?
+            .ok_or_else(|| miette::miette!("No plugin provides the command '{}'", command))?;
+
+        let input = json!({ "command": command, "args": args }).to_string();
+        let output = loaded.call("flow_command", &input)?;
+        let state = loaded.store.into_data();
+
+        let mut appended = Vec::new();
+        for (name, block) in state.appends {
+            let id = state.index.resolve(&name);
+            self.append(&id, &format!("- {}", block))?;
+            if !appended.contains(&id) {
+                appended.push(id);
+            }
+        }
+        Ok(PluginOutput {
+            output,
+            log: state.log,
+            appended,
+        })
+    }
+
+    /// Renders content with a renderer registered by a plugin.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the plugin runs against.
+    /// - `renderer` (`&str`) - Name of the renderer.
+    /// - `content` (`&str`) - Markdown content to render.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - The rendered content.
+    ///
+    /// # Errors
+    ///
+    /// No plugin registers the renderer, or the plugin traps or exceeds its fuel.
+    pub fn render_with_plugin(&self, renderer: &str, content: &str) -> Result<String> {
+        let mut loaded = self
+            .find_plugin(|state| state.renderers.iter().any(|r| r == renderer))?
+            .ok_or_else(|| miette::miette!("No plugin provides the renderer '{}'", renderer))?;
+        let input = json!({ "renderer": renderer, "content": content }).to_string();
+        loaded.call("flow_render", &input)
+    }
+
+    /// Returns the paths of all plugin modules.
+    fn plugin_paths(&self) -> Result<Vec<PathBuf>> {
+        let dir = self.path.join(FLOW_DIR).join(PLUGINS_DIR);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+            .into_diagnostic()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Loads the first plugin whose registrations match.
+    fn find_plugin(&self, matches: impl Fn(&HostState) -> bool) -> Result<Option<Loaded>> {
+        for path in self.plugin_paths()? {
+            let loaded = self.load_plugin(&path)?;
+            if matches(loaded.store.data()) {
+                return Ok(Some(loaded));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Instantiates a plugin and runs its `flow_init`.
+    fn load_plugin(&self, path: &Path) -> Result<Loaded> {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let manifest = match fs::read_to_string(path.with_extension("toml")) {
+            Ok(manifest) => toml::from_str(&manifest).into_diagnostic()?,
+            Err(_) => PluginManifest {
+                name: stem.clone(),
+                ..PluginManifest::default()
+            },
+        };
+
+        // Without a configuration nothing is granted
+        let granted = Config::load()
+            .map(|config| config.plugin_grants(&self.path, &stem))
+            .unwrap_or_default();
+        let capabilities: Vec<Capability> = manifest
+            .capabilities
+            .iter()
+            .copied()
+            .filter(|capability| granted.iter().any(|name| name == capability.name()))
+            .collect();
+
+        let mut pages = BTreeMap::new();
+        if capabilities.contains(&Capability::Read) {
+            for id in self.page_ids()? {
+                if let Some(content) = self.read_page(&id)? {
+                    pages.insert(page::name_from_id(&id), content);
+                }
+            }
+        }
+        let state = HostState {
+            plugin: stem,
+            capabilities,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY)
+                .instances(1)
+                .build(),
+            pages,
+            index: self.page_index()?,
+            appends: Vec::new(),
+            commands: Vec::new(),
+            renderers: Vec::new(),
+            log: Vec::new(),
+        };
+
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(wasm_error)?;
+        let module = Module::from_file(&engine, path).map_err(wasm_error)?;
+        let mut linker = Linker::new(&engine);
+        link_host(&mut linker).map_err(wasm_error)?;
+
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL).map_err(wasm_error)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(wasm_error)?;
+        if let Ok(init) = instance.get_typed_func::<(), ()>(&mut store, "flow_init") {
+            init.call(&mut store, ()).map_err(wasm_error)?;
+        }
+
+        Ok(Loaded {
+            manifest,
+            path: path.to_path_buf(),
+            store,
+            instance,
+        })
+    }
+}
+
+impl Loaded {
+    /// Calls an export taking and returning a string.
+    fn call(&mut self, export: &str, input: &str) -> Result<String> {
+        self.store.set_fuel(FUEL).map_err(wasm_error)?;
+        let func = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&mut self.store, export)
+            .map_err(|_| {
+                miette::miette!("Plugin '{}' doesn't export {}", self.manifest.name, export)
+            })?;
+
+        let memory = self
+            .instance
+            .get_memory(&mut self.store, "memory")
+            .ok_or_else(|| miette::miette!("Plugin '{}' exports no memory", self.manifest.name))?;
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "flow_alloc")
+            .map_err(wasm_error)?;
+        let ptr = alloc
+            .call(&mut self.store, input.len() as i32)
+            .map_err(wasm_error)?;
+        memory
+            .write(&mut self.store, ptr as usize, input.as_bytes())
+            .map_err(wasm_error)?;
+
+        let packed = func
+            .call(&mut self.store, (ptr, input.len() as i32))
+            .map_err(wasm_error)?;
+        read_packed(&memory, &self.store, packed).map_err(wasm_error)
+    }
+}
+
+/// Defines the host functions a plugin can import.
+fn link_host(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        "flow",
+        "log",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            let line = read_string(&mut caller, ptr, len)?;
+            caller.data_mut().log.push(line);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "flow",
+        "register_command",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            let name = read_string(&mut caller, ptr, len)?;
+            caller.data_mut().commands.push(name);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "flow",
+        "register_renderer",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            let name = read_string(&mut caller, ptr, len)?;
+            caller.data_mut().renderers.push(name);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap("flow", "list_pages", |mut caller: Caller<'_, HostState>| {
+        require(&caller, Capability::Read)?;
+        let names: Vec<&String> = caller.data().pages.keys().collect();
+        let json = serde_json::to_string(&names)?;
+        write_string(&mut caller, &json)
+    })?;
+    linker.func_wrap(
+        "flow",
+        "read_page",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            require(&caller, Capability::Read)?;
+            let name = read_string(&mut caller, ptr, len)?;
+            let state = caller.data();
+            let name = page::name_from_id(&state.index.resolve(&name));
+            match state.pages.get(&name).cloned() {
+                Some(content) => write_string(&mut caller, &content),
+                None => Ok(0),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        "flow",
+        "search",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            require(&caller, Capability::Read)?;
+            let query = read_string(&mut caller, ptr, len)?.to_lowercase();
+            let names: Vec<&String> = caller
+                .data()
+                .pages
+                .iter()
+                .filter(|(_, content)| content.to_lowercase().contains(&query))
+                .map(|(name, _)| name)
+                .collect();
+            let json = serde_json::to_string(&names)?;
+            write_string(&mut caller, &json)
+        },
+    )?;
+    linker.func_wrap(
+        "flow",
+        "append_block",
+        |mut caller: Caller<'_, HostState>, page_ptr: i32, page_len: i32, ptr: i32, len: i32| {
+            require(&caller, Capability::Write)?;
+            let name = read_string(&mut caller, page_ptr, page_len)?;
+            let block = read_string(&mut caller, ptr, len)?;
+            caller.data_mut().appends.push((name, block));
+            Ok(0i32)
+        },
+    )?;
+    Ok(())
+}
+
+/// Fails the call unless the plugin was granted a capability.
+fn require(caller: &Caller<'_, HostState>, capability: Capability) -> wasmtime::Result<()> {
+    let state = caller.data();
+    if state.capabilities.contains(&capability) {
+        Ok(())
+    } else {
+        Err(wasmtime::Error::msg(format!(
+            "plugin lacks the {} capability, grant it with `flow plugin grant {} {}` if it is requested in its manifest",
+            capability.name(),
+            state.plugin,
+            capability.name()
+        )))
+    }
+}
+
+/// Returns the exported memory of the calling plugin.
+fn memory(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("plugin exports no memory"))
+}
+
+/// Reads a string passed by the plugin.
+fn read_string(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> wasmtime::Result<String> {
+    let memory = memory(caller)?;
+    let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+    check_bounds(memory.data_size(&*caller), ptr, len)?;
+    let mut bytes = vec![0; len];
+    memory.read(&*caller, ptr, &mut bytes)?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Fails unless a string lies within the plugin's memory.
+///
+/// Checked before allocating, the length comes from the plugin.
+fn check_bounds(size: usize, ptr: usize, len: usize) -> wasmtime::Result<()> {
+    if ptr.checked_add(len).is_none_or(|end| end > size) {
+        return Err(wasmtime::Error::msg(
+            "plugin passed a string outside its memory",
+        ));
+    }
+    Ok(())
+}
+
+/// Writes a string into memory allocated by the plugin, returning it packed.
+fn write_string(caller: &mut Caller<'_, HostState>, value: &str) -> wasmtime::Result<i64> {
+    let memory = memory(caller)?;
+    let alloc = caller
+        .get_export("flow_alloc")
+        .and_then(|export| export.into_func())
+        .ok_or_else(|| wasmtime::Error::msg("plugin exports no flow_alloc"))?
+        .typed::<i32, i32>(&*caller)?;
+    let ptr = alloc.call(&mut *caller, value.len() as i32)?;
+    memory.write(&mut *caller, ptr as usize, value.as_bytes())?;
+    Ok((i64::from(ptr) << 32) | value.len() as i64)
+}
+
+/// Reads a string returned packed by the plugin.
+fn read_packed(memory: &Memory, store: &Store<HostState>, packed: i64) -> wasmtime::Result<String> {
+    if packed == 0 {
+        return Ok(String::new());
+    }
+    let (ptr, len) = (
+        (packed >> 32) as u32 as usize,
+        (packed & 0xffff_ffff) as usize,
+    );
+    check_bounds(memory.data_size(store), ptr, len)?;
+    let mut bytes = vec![0; len];
+    memory.read(store, ptr, &mut bytes)?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Converts a wasmtime error into a diagnostic.
+fn wasm_error(error: wasmtime::Error) -> miette::Report {
+    miette::miette!("Plugin failed: {:#}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_bounds() {
+        assert!(check_bounds(64, 0, 64).is_ok());
+        assert!(check_bounds(64, 60, 4).is_ok());
+        assert!(check_bounds(64, 60, 5).is_err());
+        assert!(check_bounds(64, usize::MAX, 1).is_err());
+    }
+}
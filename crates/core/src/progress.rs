@@ -0,0 +1,48 @@
+//! Progress Reporting
+//!
+//! Long-running operations report their progress through the [`Progress`]
+//! trait, so frontends can render it however they like (progress bars in the
+//! CLI, status lines in the TUI) or not at all. [`NoProgress`] discards all
+//! reports.
+//!
+//! Sync sessions have no end to measure against, they report what they send
+//! and receive through [`SyncEvent`](crate::sync::SyncEvent) instead.
+
+/// Receiver of progress reports from long-running operations.
+///
+/// Implementations must be `Sync`, as operations may report from several
+/// worker threads at once.
+pub trait Progress: Sync {
+    /// Starts a new phase of the operation.
+    ///
+    /// # Arguments
+    ///
+    /// - `message` (`&str`) - Description of the phase.
+    /// - `total` (`Option<u64>`) - Number of steps, `None` if unknown.
+    fn start(&self, message: &str, total: Option<u64>);
+
+    /// Advances the current phase.
+    ///
+    /// # Arguments
+    ///
+    /// - `steps` (`u64`) - Number of completed steps since the last report.
+    fn advance(&self, steps: u64);
+
+    /// Finishes the current phase.
+    fn finish(&self);
+}
+
+/// Progress receiver discarding all reports.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _message: &str, _total: Option<u64>) {}
+
+    fn advance(&self, _steps: u64) {}
+
+    fn finish(&self) {}
+}
+
+#[cfg(test)]
+mod tests {}
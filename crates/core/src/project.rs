@@ -0,0 +1,99 @@
+//! Project Scratchpads
+//!
+//! Notes captured with `flow project note` go to a page named after the git
+//! repository of the working directory, turning a space into a per-repository
+//! engineering log without any configuration. Each note ends with a link to
+//! the journal page of the day it was taken on, so it also shows up there as
+//! a backlink.
+
+use miette::Result;
+use std::path::Path;
+
+use crate::page;
+use crate::repo;
+use crate::space::Space;
+
+/// Detects the project a path belongs to.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path inside the project, usually the working directory.
+///
+/// # Returns
+///
+/// - `Option<String>` - Name of the enclosing git repository, `None` outside of one.
+pub fn detect(path: &Path) -> Option<String> {
+    repo::root(path)?
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// Returns the open tasks of a project page.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<String>` - Text of each open task, including its keyword.
+pub fn open_tasks(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("- "))
+        .filter(|text| {
+            let keyword = text.split_whitespace().next().unwrap_or_default();
+            page::OPEN_TASK_KEYWORDS.contains(&keyword)
+        })
+        .map(|text| text.trim().to_string())
+        .collect()
+}
+
+impl Space {
+    /// Adds a note to the page of a project, creating the page if needed.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to capture into.
+    /// - `project` (`&str`) - Name of the project page.
+    /// - `note` (`&str`) - Content of the note.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - Id of the project page.
+    ///
+    /// # Errors
+    ///
+    /// The project page is locked, or IO errors when writing files.
+    pub fn add_project_note(&mut self, project: &str, note: &str) -> Result<String> {
+        let id = self.page_index()?.resolve(project);
+        let day = page::name_from_id(&self.journal_page(self.today()));
+        self.append(&id, &format!("- {} [[{}]]", note, day))?;
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_tasks() {
+        let content = "- [[2024-05-01]] TODO not a task\n- TODO ship it\n  - DOING review\n- DONE merged\n- LATER docs";
+        assert_eq!(
+            open_tasks(content),
+            ["TODO ship it", "DOING review", "LATER docs"]
+        );
+    }
+
+    #[test]
+    fn test_add_project_note() {
+        let mut space = Space::in_memory().unwrap();
+        let id = space.add_project_note("flow", "TODO ship it").unwrap();
+
+        assert_eq!(id, page::id_from_name("flow"));
+        let content = space.read_page(&id).unwrap().unwrap();
+        assert!(content.contains("\n- TODO ship it [["));
+        assert_eq!(open_tasks(&content).len(), 1);
+    }
+}
@@ -0,0 +1,580 @@
+//! Publishing
+//!
+//! Publishes the pages of a space as a static HTML site to the targets
+//! configured in the space metadata:
+//!
+//! ```toml
+//! [publish.targets.site]
+//! kind = "dir"
+//! path = "/srv/www/notes"
+//!
+//! [publish.targets.pages]
+//! kind = "github-pages"
+//! remote = "origin"
+//! ```
+//!
+//! The site is built into `.flow/publish/<target>/` first. Pages are only
+//! re-rendered when they, or pages embedded into them, were modified since
+//! the last build, unless the set of published pages changed, which changes
+//! the links of every page. Pages with a `publish:: false` property (or
+//! `publish: false` in a YAML frontmatter block) are excluded, and are not
+//! embedded into published pages either. With `[publish.feed]` enabled, an
+//! Atom feed of recent journal days and featured pages is written next to
+//! them (see [`feed`](crate::feed)).
+//!
+//! Deploying to a directory copies the changed files. The other targets hand
+//! the built site to their command line tools: `git` for GitHub Pages,
+//! `netlify` for Netlify and `aws` for S3, which upload incrementally on
+//! their own.
+
+use chrono::Local;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::feed::{FeedSettings, FEED_FILE};
+use crate::page;
+use crate::progress::Progress;
+use crate::render::{self, RenderPage};
+use crate::secrets::Secrets;
+use crate::space::{write_atomic, Space, FLOW_DIR};
+
+const PUBLISH_DIR: &str = "publish";
+const INDEX_PAGE: &str = "index.html";
+
+/// Publish settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `targets` (`BTreeMap<String, Target>`) - Configured targets by name.
+/// - `feed` (`FeedSettings`) - Atom feed published with the site.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishSettings {
+    #[serde(default)]
+    pub targets: BTreeMap<String, Target>,
+    #[serde(default)]
+    pub feed: FeedSettings,
+}
+
+/// A place a site is published to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Target {
+    /// A local directory, e.g. served by a web server.
+    Dir {
+        /// Directory to copy the site to.
+        path: PathBuf,
+    },
+    /// A branch pushed to a git remote, served by GitHub Pages.
+    GithubPages {
+        /// Remote name of the repository containing the space, or a repository URL.
+        #[serde(default = "default_remote")]
+        remote: String,
+        /// Branch to push the site to.
+        #[serde(default = "default_branch")]
+        branch: String,
+    },
+    /// A Netlify site, deployed with the `netlify` CLI.
+    Netlify {
+        /// Id of the Netlify site.
+        site: String,
+        /// Access token, usually a `secret:` reference.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// An S3 bucket, synced with the `aws` CLI.
+    S3 {
+        /// Name of the bucket.
+        bucket: String,
+        /// Key prefix to publish under.
+        #[serde(default)]
+        prefix: Option<String>,
+        /// Region of the bucket, the AWS default if `None`.
+        #[serde(default)]
+        region: Option<String>,
+    },
+}
+
+fn default_remote() -> String {
+    "origin".to_string()
+}
+
+fn default_branch() -> String {
+    "gh-pages".to_string()
+}
+
+impl Target {
+    /// Returns a short description of where the target publishes to.
+    ///
+    /// # Returns
+    ///
+    /// - `String` - Description, e.g. `s3://bucket/prefix`.
+    pub fn describe(&self) -> String {
+        match self {
+            Target::Dir { path } => path.display().to_string(),
+            Target::GithubPages { remote, branch } => format!("{} ({})", remote, branch),
+            Target::Netlify { site, .. } => format!("netlify site {}", site),
+            Target::S3 { bucket, prefix, .. } => match prefix {
+                Some(prefix) => format!("s3://{}/{}", bucket, prefix.trim_matches('/')),
+                None => format!("s3://{}", bucket),
+            },
+        }
+    }
+}
+
+/// Outcome of publishing to a target.
+///
+/// # Fields
+///
+/// - `target` (`String`) - Name of the target.
+/// - `site` (`PathBuf`) - Directory the site was built in.
+/// - `published` (`Vec<String>`) - Ids of all published pages.
+/// - `rendered` (`Vec<String>`) - Ids of the pages rendered in this build.
+/// - `excluded` (`Vec<String>`) - Ids of pages excluded with `publish: false`.
+/// - `removed` (`Vec<String>`) - Files removed since the last build.
+/// - `feed` (`bool`) - Whether the Atom feed was written.
+/// - `deployed` (`bool`) - Whether the site was deployed, false if nothing changed.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, Deserialize)]
+pub struct PublishReport {
+    pub target: String,
+    pub site: PathBuf,
+    pub published: Vec<String>,
+    pub rendered: Vec<String>,
+    pub excluded: Vec<String>,
+    pub removed: Vec<String>,
+    pub feed: bool,
+    pub deployed: bool,
+}
+
+/// State of the last build of a target.
+///
+/// # Fields
+///
+/// - `pages` (`BTreeMap<String, i64>`) - Modification time of each rendered page by id.
+/// - `links` (`BTreeMap<String, String>`) - Link targets the pages were rendered with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BuildState {
+    #[serde(default)]
+    pages: BTreeMap<String, i64>,
+    #[serde(default)]
+    links: BTreeMap<String, String>,
+}
+
+/// Pages of a space split by whether they are published.
+///
+/// # Fields
+///
+/// - `pages` (`Vec<RenderPage>`) - Published pages with their embeds expanded.
+/// - `excluded` (`Vec<String>`) - Ids of pages opted out of publishing.
+/// - `modified` (`BTreeMap<String, i64>`) - Latest modification of each published page or a page it embeds.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Published {
+    pub(crate) pages: Vec<RenderPage>,
+    pub(crate) excluded: Vec<String>,
+    pub(crate) modified: BTreeMap<String, i64>,
+}
+
+impl Space {
+    /// Returns the publish settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&PublishSettings` - Reference to the publish settings.
+    pub fn publish_settings(&self) -> &PublishSettings {
+        &self.metadata.publish
+    }
+
+    /// Builds the site of a target and deploys it.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to publish.
+    /// - `target` (`&str`) - Name of the target.
+    /// - `full` (`bool`) - Re-render every page, ignoring the last build.
+    /// - `progress` (`&dyn Progress`) - Receives one step per published page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<PublishReport>` - Built pages and whether they were deployed.
+    ///
+    /// # Errors
+    ///
+    /// The target isn't configured, its deploy tool failed, or IO errors when
+    /// building the site.
+    pub fn publish(
+        &self,
+        target: &str,
+        full: bool,
+        progress: &dyn Progress,
+    ) -> Result<PublishReport> {
+        let Some(settings) = self.publish_settings().targets.get(target) else {
+            miette::bail!(
+                "Publish target '{}' is not configured in space.toml",
+                target
+            );
+        };
+
+        let state_path = self
+            .path
+            .join(FLOW_DIR)
+            .join(PUBLISH_DIR)
+            .join(format!("{}.json", target));
+        let previous: BuildState = match fs::read_to_string(&state_path) {
+            Ok(json) if !full => serde_json::from_str(&json).unwrap_or_default(),
+            _ => BuildState::default(),
+        };
+
+        let (mut report, state) = self.build_site(target, &previous, progress)?;
+        if report.rendered.is_empty() && report.removed.is_empty() {
+            return Ok(report);
+        }
+        deploy(settings, &report.site, &self.path, &report.removed)?;
+        report.deployed = true;
+
+        // Only a deployed build counts, a failed deploy is retried next time
+        let json = serde_json::to_string_pretty(&state).into_diagnostic()?;
+        write_atomic(&state_path, json.as_bytes())?;
+        Ok(report)
+    }
+
+    /// Renders the published pages of a target into its build directory.
+    fn build_site(
+        &self,
+        target: &str,
+        previous: &BuildState,
+        progress: &dyn Progress,
+    ) -> Result<(PublishReport, BuildState)> {
+        let site = self.path.join(FLOW_DIR).join(PUBLISH_DIR).join(target);
+        let empty = BuildState::default();
+        // A deleted build directory is built from scratch
+        let previous = if site.join(INDEX_PAGE).exists() {
+            previous
+        } else {
+            &empty
+        };
+
+        let Published {
+            pages,
+            excluded,
+            modified,
+        } = self.published_pages()?;
+        let mut report = PublishReport {
+            target: target.to_string(),
+            site: site.clone(),
+            excluded,
+            ..PublishReport::default()
+        };
+
+        let file = |name: &str| format!("{}.html", render::slug(name));
+        let links = render::link_targets(&pages, file);
+        let state = BuildState {
+            pages: modified,
+            links: links.clone().into_iter().collect(),
+        };
+        // Changed links affect every page, so everything is rendered again
+        let relink = state.links != previous.links;
+
+        fs::create_dir_all(&site).into_diagnostic()?;
+        progress.start("Rendering pages", Some(pages.len() as u64));
+        for page in &pages {
+            progress.advance(1);
+            if !relink && previous.pages.get(&page.id) == state.pages.get(&page.id) {
+                continue;
+            }
+            let html = render::to_site_page(page, &links, INDEX_PAGE);
+            write_atomic(
+                &site.join(file(&page::name_from_id(&page.id))),
+                html.as_bytes(),
+            )?;
+            report.rendered.push(page.id.clone());
+        }
+        progress.finish();
+
+        // Pages no longer published, or left over from an interrupted build
+        let mut expected: Vec<String> = pages
+            .iter()
+            .map(|page| file(&page::name_from_id(&page.id)))
+            .collect();
+        expected.push(INDEX_PAGE.to_string());
+        for entry in fs::read_dir(&site).into_diagnostic()? {
+            let name = entry
+                .into_diagnostic()?
+                .file_name()
+                .to_string_lossy()
+                .to_string();
+            if name.ends_with(".html") && !expected.contains(&name) {
+                fs::remove_file(site.join(&name)).into_diagnostic()?;
+                report.removed.push(name);
+            }
+        }
+
+        if !report.rendered.is_empty() || !report.removed.is_empty() {
+            write_atomic(
+                &site.join(INDEX_PAGE),
+                site_index(self.name(), &pages).as_bytes(),
+            )?;
+            let feed = &self.metadata.publish.feed;
+            if feed.enabled {
+                let atom = self.feed_from(&pages, feed.url.as_deref(), feed.entries);
+                write_atomic(&site.join(FEED_FILE), atom.as_bytes())?;
+                report.feed = true;
+            }
+        }
+
+        report.published = pages.into_iter().map(|page| page.id).collect();
+        Ok((report, state))
+    }
+
+    /// Reads all pages, split into published and excluded ones.
+    ///
+    /// Embeds of published pages are expanded, embeds of excluded pages are
+    /// left as they are.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Published>` - Published pages with their embeds expanded and ids of excluded pages.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages.
+    pub(crate) fn published_pages(&self) -> Result<Published> {
+        let mut published = Published::default();
+        for id in self.page_ids()? {
+            let Some(content) = self.read_page(&id)? else {
+                continue;
+            };
+            if !is_published(&content) {
+                published.excluded.push(id);
+                continue;
+            }
+
+            let expanded =
+                self.expand_embeds_with(&id, &content, &|_, content| is_published(content))?;
+            // Changes to embedded pages show up in the embedding page
+            let modified = expanded
+                .embedded
+                .iter()
+                .chain(std::iter::once(&id))
+                .map(|id| self.page_times(id).modified)
+                .max()
+                .unwrap_or(0);
+            published.modified.insert(id.clone(), modified);
+            published.pages.push(RenderPage {
+                id,
+                content: expanded.content,
+            });
+        }
+        Ok(published)
+    }
+}
+
+/// Checks whether a page may be published.
+///
+/// Pages are published unless a `publish::` page property or a `publish:`
+/// key in a YAML frontmatter block is `false`.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `bool` - False if the page opted out of publishing.
+pub fn is_published(content: &str) -> bool {
+    !matches!(
+        publish_flag(content).as_deref(),
+        Some("false" | "no" | "off")
+    )
+}
+
+/// Reads the lowercase `publish` flag of a page, from its properties or frontmatter.
+pub(crate) fn publish_flag(content: &str) -> Option<String> {
+    page::properties(content)
+        .into_iter()
+        .find(|(key, _)| key == "publish")
+        .map(|(_, value)| value)
+        .or_else(|| frontmatter(content, "publish"))
+        .map(|value| value.to_lowercase())
+}
+
+/// Reads a key from a YAML frontmatter block at the top of a page.
+fn frontmatter(content: &str, key: &str) -> Option<String> {
+    let mut lines = content.trim_start().lines();
+    if lines.next()?.trim() != "---" {
+        return None;
+    }
+    lines
+        .take_while(|line| line.trim() != "---")
+        .filter_map(|line| line.split_once(':'))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, value)| value.trim().trim_matches(['"', '\'']).to_string())
+}
+
+/// Renders the index of a site: journal days newest first, then other pages by name.
+fn site_index(title: &str, pages: &[RenderPage]) -> String {
+    let (mut journal, mut other): (Vec<_>, Vec<_>) =
+        pages.iter().partition(|page| page::is_journal(&page.id));
+    journal.sort_by(|a, b| b.id.cmp(&a.id));
+    other.sort_by_key(|page| page::name_from_id(&page.id).to_lowercase());
+
+    let entries: Vec<(String, String)> = other
+        .into_iter()
+        .chain(journal)
+        .map(|page| {
+            let name = page::name_from_id(&page.id);
+            let href = format!("{}.html", render::slug(&name));
+            (name, href)
+        })
+        .collect();
+    render::to_site_index(title, &entries)
+}
+
+/// Deploys a built site to a target.
+fn deploy(target: &Target, site: &Path, space_path: &Path, removed: &[String]) -> Result<()> {
+    match target {
+        Target::Dir { path } => copy_changed(site, &space_path.join(path), removed),
+        Target::GithubPages { remote, branch } => {
+            // Remote names refer to the repository the space lives in
+            let url = if remote.contains(':') || remote.contains('/') {
+                remote.clone()
+            } else {
+                let output = process::Command::new("git")
+                    .args(["remote", "get-url", remote])
+                    .current_dir(space_path)
+                    .output()
+                    .into_diagnostic()?;
+                if !output.status.success() {
+                    miette::bail!("Git remote '{}' not found", remote);
+                }
+                String::from_utf8_lossy(&output.stdout).trim().to_string()
+            };
+
+            let message = format!("Publish {}", Local::now().format("%Y-%m-%d %H:%M"));
+            if !site.join(".git").exists() {
+                run(site, "git", &["init", "--quiet"], &[])?;
+            }
+            run(
+                site,
+                "git",
+                &["checkout", "--quiet", "-B", branch.as_str()],
+                &[],
+            )?;
+            run(site, "git", &["add", "--all"], &[])?;
+            // Nothing to commit is fine, the push still brings the remote up to date
+            let _ = process::Command::new("git")
+                .args(["commit", "--quiet", "-m", &message])
+                .current_dir(site)
+                .status();
+            run(
+                site,
+                "git",
+                &["push", "--quiet", "--force", url.as_str(), branch.as_str()],
+                &[],
+            )
+        }
+        Target::Netlify { site: id, token } => {
+            let site_dir = site.to_string_lossy();
+            let mut env = Vec::new();
+            if let Some(token) = token {
+                env.push(("NETLIFY_AUTH_TOKEN", Secrets::open()?.resolve(token)?));
+            }
+            run(
+                site,
+                "netlify",
+                &[
+                    "deploy",
+                    "--prod",
+                    "--dir",
+                    site_dir.as_ref(),
+                    "--site",
+                    id.as_str(),
+                ],
+                &env,
+            )
+        }
+        Target::S3 {
+            bucket,
+            prefix,
+            region,
+        } => {
+            let destination = match prefix {
+                Some(prefix) => format!("s3://{}/{}", bucket, prefix.trim_matches('/')),
+                None => format!("s3://{}", bucket),
+            };
+            let site_dir = site.to_string_lossy();
+            let mut args = vec![
+                "s3",
+                "sync",
+                site_dir.as_ref(),
+                destination.as_str(),
+                "--delete",
+                "--exclude",
+                ".git/*",
+            ];
+            if let Some(region) = region {
+                args.extend(["--region", region.as_str()]);
+            }
+            run(site, "aws", &args, &[])
+        }
+    }
+}
+
+/// Runs a deploy tool, failing if it can't be started or exits unsuccessfully.
+fn run(dir: &Path, program: &str, args: &[&str], env: &[(&str, String)]) -> Result<()> {
+    let status = process::Command::new(program)
+        .args(args)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .current_dir(dir)
+        .stdout(process::Stdio::null())
+        .status()
+        .map_err(|error| miette::miette!("Failed to run '{}': {}", program, error))?;
+    if !status.success() {
+        miette::bail!(
+            "'{} {}' failed with {}",
+            program,
+            args.first().unwrap_or(&""),
+            status
+        );
+    }
+    Ok(())
+}
+
+/// Copies the files of a site to a directory, skipping unchanged ones, and
+/// removes the files the site no longer has.
+fn copy_changed(site: &Path, target: &Path, removed: &[String]) -> Result<()> {
+    fs::create_dir_all(target).into_diagnostic()?;
+    for entry in fs::read_dir(site).into_diagnostic()? {
+        let source = entry.into_diagnostic()?.path();
+        let Some(name) = source.file_name().filter(|_| source.is_file()) else {
+            continue;
+        };
+        let content = fs::read(&source).into_diagnostic()?;
+        let destination = target.join(name);
+        if fs::read(&destination).ok().as_deref() != Some(content.as_slice()) {
+            fs::write(destination, content).into_diagnostic()?;
+        }
+    }
+    for name in removed {
+        let _ = fs::remove_file(target.join(name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_published() {
+        assert!(is_published("- a public note"));
+        assert!(!is_published("publish:: false\n\n- private"));
+        assert!(!is_published(
+            "---\ntitle: Notes\npublish: \"false\"\n---\n- private"
+        ));
+        assert!(is_published("publish:: true\n- public"));
+    }
+}
@@ -0,0 +1,78 @@
+//! Recent Pages
+//!
+//! Lists the most recently modified pages, based on the times recorded in the
+//! document (see [`timestamps`](crate::timestamps)), to resume where work
+//! left off.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::space::Space;
+
+/// A recently modified page.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `modified` (`i64`) - Last modification as unix timestamp in seconds.
+/// - `preview` (`String`) - Last non-empty line of the page, usually the latest block.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RecentPage {
+    pub id: String,
+    pub page: String,
+    pub modified: i64,
+    pub preview: String,
+}
+
+impl Space {
+    /// Returns the most recently modified pages, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to read from.
+    /// - `limit` (`usize`) - Maximum number of pages, `0` for all.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<RecentPage>>` - Recently modified pages.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the index or reading pages.
+    pub fn recent(&self, limit: usize) -> Result<Vec<RecentPage>> {
+        let index = self.page_index()?;
+        let mut entries: Vec<_> = index.pages().collect();
+        entries.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| a.id.cmp(&b.id)));
+        if limit > 0 {
+            entries.truncate(limit);
+        }
+
+        let mut recent = Vec::new();
+        for entry in entries {
+            let content = self.read_page(&entry.id)?.unwrap_or_default();
+            recent.push(RecentPage {
+                id: entry.id.clone(),
+                page: entry.name.clone(),
+                modified: entry.modified,
+                preview: preview(&content),
+            });
+        }
+        Ok(recent)
+    }
+}
+
+/// Returns the last non-empty line of a page, without its list marker.
+fn preview(content: &str) -> String {
+    content
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| line.trim_start_matches("- ").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {}
@@ -0,0 +1,350 @@
+//! Space Recovery
+//!
+//! Recovers a space whose document no longer loads, e.g. after a crash
+//! truncated the snapshot. A recovery either restores a backup (see
+//! [`backup`](crate::backup)) and takes over the content of markdown files
+//! that changed since, or rebuilds the document from the markdown files,
+//! keeping all content but losing the history.
+//!
+//! Like [`fsck`](crate::fsck) this works on the files in `.flow/` directly.
+//! The broken document is copied to `.flow/corrupt/` before anything is
+//! replaced, so a recovery never destroys data.
+
+use chrono::Local;
+use loro::{ExportMode, LoroDoc, UpdateOptions};
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup::{self, Backup};
+use crate::cancel::CancellationToken;
+use crate::fsck::{self, Issue};
+use crate::progress::Progress;
+use crate::space::{
+    container_names, markdown_files, write_snapshot, DOCUMENT_FILE, FLOW_DIR, UPDATES_FILE,
+};
+use crate::storage::FsStorage;
+use crate::timestamps::{self, PageTimes};
+
+const CORRUPT_DIR: &str = "corrupt";
+
+/// A backup a space can be recovered from.
+///
+/// # Fields
+///
+/// - `backup` (`Backup`) - The backup.
+/// - `pages` (`usize`) - Number of pages in the backup.
+/// - `changed` (`usize`) - Markdown files that differ from the backup and would be taken over.
+/// - `error` (`Option<String>`) - Why the backup can't be imported, `None` if it can.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Candidate {
+    pub backup: Backup,
+    pub pages: usize,
+    pub changed: usize,
+    pub error: Option<String>,
+}
+
+/// What a recovery of a space would start from.
+///
+/// # Fields
+///
+/// - `problem` (`Option<String>`) - Why the document doesn't load, `None` if it does.
+/// - `backups` (`Vec<Candidate>`) - Backups of the space, newest first.
+/// - `files` (`usize`) - Number of markdown files a rebuild would import.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Plan {
+    pub problem: Option<String>,
+    pub backups: Vec<Candidate>,
+    pub files: usize,
+}
+
+impl Plan {
+    /// Returns the newest backup that can be imported.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&Candidate>` - The backup, `None` if no backup is usable.
+    pub fn best(&self) -> Option<&Candidate> {
+        self.backups
+            .iter()
+            .find(|candidate| candidate.error.is_none())
+    }
+}
+
+/// How to recover a space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Method {
+    /// Restore the backup with the given id, then take over changed files.
+    Backup(String),
+    /// Rebuild the document from the markdown files.
+    Rebuild,
+}
+
+/// Result of a recovery.
+///
+/// # Fields
+///
+/// - `preserved` (`Vec<PathBuf>`) - Copies of the replaced document files.
+/// - `backup` (`Option<String>`) - Id of the restored backup, `None` for a rebuild.
+/// - `pages` (`usize`) - Number of pages in the recovered document.
+/// - `updated` (`Vec<String>`) - Pages taken over from their markdown files.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RecoveryReport {
+    pub preserved: Vec<PathBuf>,
+    pub backup: Option<String>,
+    pub pages: usize,
+    pub updated: Vec<String>,
+}
+
+/// Inspects a space and the backups it could be recovered from.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<Plan>` - The problem of the document and the recovery options.
+///
+/// # Errors
+///
+/// IO errors when reading the markdown files or backups.
+pub fn plan(path: &Path) -> Result<Plan> {
+    let problem = fsck::check(path)?
+        .issues
+        .into_iter()
+        .find_map(|issue| match issue {
+            Issue::MissingSnapshot => Some(issue.describe()),
+            Issue::CorruptSnapshot { .. } => Some(issue.describe()),
+            _ => None,
+        });
+
+    let files = markdown_files(&FsStorage, path)?;
+    let mut backups = Vec::new();
+    for backup in backup::list(path)? {
+        let doc = LoroDoc::new();
+        let imported = fs::read(&backup.path)
+            .into_diagnostic()
+            .and_then(|bytes| doc.import(&bytes).into_diagnostic());
+        let candidate = match imported {
+            Ok(_) => Candidate {
+                pages: container_names(&doc)
+                    .iter()
+                    .filter(|id| id.ends_with(".md"))
+                    .count(),
+                changed: changed_files(path, &doc, &files)?.len(),
+                backup,
+                error: None,
+            },
+            Err(err) => Candidate {
+                backup,
+                pages: 0,
+                changed: 0,
+                error: Some(err.to_string()),
+            },
+        };
+        backups.push(candidate);
+    }
+
+    Ok(Plan {
+        problem,
+        backups,
+        files: files.len(),
+    })
+}
+
+/// Recovers the document of a space.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space.
+/// - `method` (`&Method`) - Whether to restore a backup or rebuild.
+/// - `progress` (`&dyn Progress`) - Receives one step per imported file.
+/// - `cancel` (`&CancellationToken`) - Checked before each file, the document
+///   is only replaced once the recovery is complete.
+///
+/// # Returns
+///
+/// - `Result<RecoveryReport>` - What was recovered.
+///
+/// # Errors
+///
+/// The backup doesn't exist or can't be imported, IO errors when reading or
+/// writing files, [`Cancelled`](crate::cancel::Cancelled) if the token was
+/// cancelled.
+pub fn recover(
+    path: &Path,
+    method: &Method,
+    progress: &dyn Progress,
+    cancel: &CancellationToken,
+) -> Result<RecoveryReport> {
+    let id = match method {
+        Method::Backup(id) => id,
+        Method::Rebuild => {
+            let preserved = preserve(path)?;
+            let pages = fsck::rebuild_doc(path, progress, cancel)?.pages;
+            return Ok(RecoveryReport {
+                preserved,
+                backup: None,
+                pages,
+                updated: Vec::new(),
+            });
+        }
+    };
+
+    let backup = backup::list(path)?
+        .into_iter()
+        .find(|backup| backup.id == *id)
+        .ok_or_else(|| miette::miette!("Backup '{}' not found", id))?;
+    let doc = LoroDoc::new();
+    doc.set_record_timestamp(true);
+    doc.import(&fs::read(&backup.path).into_diagnostic()?)
+        .into_diagnostic()?;
+
+    let files = markdown_files(&FsStorage, path)?;
+    let changed = changed_files(path, &doc, &files)?;
+    progress.start("Taking over changed pages", Some(changed.len() as u64));
+    for (id, content) in &changed {
+        if let Err(cancelled) = cancel.check() {
+            progress.finish();
+            return Err(cancelled.into());
+        }
+        doc.get_text(id.as_str())
+            .update(content, UpdateOptions::default())
+            .into_diagnostic()?;
+        let times = PageTimes::from_file(&path.join(id));
+        timestamps::touch(&doc, id, times.created, times.modified)?;
+        progress.advance(1);
+    }
+    progress.finish();
+    doc.commit();
+
+    let preserved = preserve(path)?;
+    let snapshot = doc.export(ExportMode::Snapshot).into_diagnostic()?;
+    write_snapshot(&FsStorage, path, &snapshot)?;
+
+    Ok(RecoveryReport {
+        preserved,
+        backup: Some(backup.id),
+        pages: container_names(&doc)
+            .iter()
+            .filter(|id| id.ends_with(".md"))
+            .count(),
+        updated: changed.into_iter().map(|(id, _)| id).collect(),
+    })
+}
+
+/// Returns the markdown files whose content differs from a document.
+fn changed_files(path: &Path, doc: &LoroDoc, files: &[String]) -> Result<Vec<(String, String)>> {
+    let mut changed = Vec::new();
+    for id in files {
+        let content = fs::read_to_string(path.join(id)).into_diagnostic()?;
+        if doc.get_text(id.as_str()).to_string() != content {
+            changed.push((id.clone(), content));
+        }
+    }
+    Ok(changed)
+}
+
+/// Copies the document files of a space to `.flow/corrupt/`.
+fn preserve(path: &Path) -> Result<Vec<PathBuf>> {
+    let flow_dir = path.join(FLOW_DIR);
+    let corrupt_dir = flow_dir.join(CORRUPT_DIR);
+    let stamp = Local::now().format("%Y%m%dT%H%M%S").to_string();
+
+    let mut preserved = Vec::new();
+    for file in [DOCUMENT_FILE, UPDATES_FILE] {
+        let source = flow_dir.join(file);
+        if !source.exists() {
+            continue;
+        }
+        fs::create_dir_all(&corrupt_dir).into_diagnostic()?;
+        let target = corrupt_dir.join(format!("{}-{}", stamp, file));
+        fs::copy(&source, &target).into_diagnostic()?;
+        preserved.push(target);
+    }
+    Ok(preserved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupSettings;
+    use crate::progress::NoProgress;
+    use crate::space::Space;
+
+    fn scratch_space(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("flow-recovery-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Space::init(&dir, None, true).unwrap();
+        dir
+    }
+
+    /// Cuts the snapshot of a space in half, as a crash mid-write would.
+    fn truncate_snapshot(path: &Path) {
+        let doc_path = path.join(FLOW_DIR).join(DOCUMENT_FILE);
+        let bytes = fs::read(&doc_path).unwrap();
+        fs::write(&doc_path, &bytes[..bytes.len() / 2]).unwrap();
+    }
+
+    #[test]
+    fn test_recover_from_backup_takes_over_changed_files() {
+        let path = scratch_space("backup");
+        let mut space = Space::load(&path).unwrap();
+        space.write_page("notes.md", "- Old\n").unwrap();
+        backup::create(&path, &BackupSettings::default()).unwrap();
+        space.write_page("notes.md", "- New\n").unwrap();
+        drop(space);
+        truncate_snapshot(&path);
+
+        let plan = plan(&path).unwrap();
+        assert!(plan.problem.is_some());
+        let best = plan.best().unwrap();
+        assert_eq!(best.changed, 1);
+
+        let method = Method::Backup(best.backup.id.clone());
+        let report = recover(&path, &method, &NoProgress, &CancellationToken::new()).unwrap();
+        assert_eq!(report.updated, vec!["notes.md"]);
+        assert!(!report.preserved.is_empty());
+
+        let space = Space::load(&path).unwrap();
+        assert_eq!(
+            space.read_page("notes.md").unwrap().as_deref(),
+            Some("- New\n")
+        );
+    }
+
+    #[test]
+    fn test_recover_rebuilds_from_markdown() {
+        let path = scratch_space("rebuild");
+        let mut space = Space::load(&path).unwrap();
+        space.write_page("notes.md", "- Kept\n").unwrap();
+        space.write_page("pages/other.md", "- Also kept\n").unwrap();
+        drop(space);
+        truncate_snapshot(&path);
+
+        let plan = plan(&path).unwrap();
+        assert!(plan.problem.is_some());
+        assert_eq!(plan.files, 2);
+
+        let report = recover(
+            &path,
+            &Method::Rebuild,
+            &NoProgress,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(report.pages, 2);
+        assert!(report.backup.is_none());
+        assert!(report.preserved.iter().all(|copy| copy.exists()));
+
+        let space = Space::load(&path).unwrap();
+        assert_eq!(
+            space.read_page("pages/other.md").unwrap().as_deref(),
+            Some("- Also kept\n")
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Reminders
+//!
+//! Finds open tasks that are scheduled or due, for `flow remind` and the
+//! notifications fired by the daemon. A task is a block starting with an open
+//! task keyword (`TODO`, `LATER`, `NOW`, `DOING`, `WAITING`); its
+//! `scheduled::` and `deadline::` block properties, written as `2024-05-01`
+//! or `2024-05-01 10:00`, are when it reminds. Dates without a time remind
+//! at [`DEFAULT_TIME`].
+//!
+//! Whether reminders notify is configured per space in the Flow
+//! configuration, including quiet hours during which nothing is shown.
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::page;
+use crate::space::Space;
+
+/// Time of day reminders for dates without a time are due.
+pub const DEFAULT_TIME: (u32, u32) = (9, 0);
+
+/// Why a task reminds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderKind {
+    Scheduled,
+    Deadline,
+}
+
+/// A reminder of an open task.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page holding the task.
+/// - `page` (`String`) - Name of that page.
+/// - `task` (`String`) - Text of the task, including its keyword.
+/// - `kind` (`ReminderKind`) - Whether the task is scheduled or due.
+/// - `at` (`i64`) - When the task reminds as unix timestamp in seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Reminder {
+    pub id: String,
+    pub page: String,
+    pub task: String,
+    pub kind: ReminderKind,
+    pub at: i64,
+}
+
+/// Notification settings of a space.
+///
+/// # Fields
+///
+/// - `enabled` (`bool`) - Whether reminders of the space fire notifications.
+/// - `quiet_hours` (`Option<QuietHours>`) - Time of day without notifications.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quiet_hours: None,
+        }
+    }
+}
+
+impl NotificationSettings {
+    /// Checks whether notifications may be shown at a time of day.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`NotificationSettings`) - Settings to check.
+    /// - `time` (`NaiveTime`) - Local time of day.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if notifications are enabled and it isn't quiet.
+    pub fn allows(&self, time: NaiveTime) -> bool {
+        self.enabled
+            && !self
+                .quiet_hours
+                .as_ref()
+                .is_some_and(|quiet| quiet.contains(time))
+    }
+}
+
+/// A daily time range, which may wrap around midnight (e.g. 22:00 to 07:00).
+///
+/// # Fields
+///
+/// - `start` (`NaiveTime`) - First quiet time of day.
+/// - `end` (`NaiveTime`) - Time of day notifications resume.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Checks whether a time of day lies in the range.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`QuietHours`) - Range to check.
+    /// - `time` (`NaiveTime`) - Time of day.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the time is quiet.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl Space {
+    /// Returns the reminders of open tasks in a time range.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `from` (`i64`) - Start of the range as unix timestamp, exclusive.
+    /// - `until` (`i64`) - End of the range as unix timestamp, inclusive.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Reminder>>` - Reminders in the range, earliest first.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages.
+    pub fn reminders(&self, from: i64, until: i64) -> Result<Vec<Reminder>> {
+        let mut reminders = Vec::new();
+        for id in self.page_ids()? {
+            let content = self.read_page(&id)?.unwrap_or_default();
+            reminders.extend(
+                tasks(&id, &content)
+                    .into_iter()
+                    .filter(|reminder| from < reminder.at && reminder.at <= until),
+            );
+        }
+        reminders.sort_by(|a, b| a.at.cmp(&b.at).then_with(|| a.id.cmp(&b.id)));
+        Ok(reminders)
+    }
+}
+
+/// Parses the reminders of all open tasks of a page.
+///
+/// # Arguments
+///
+/// - `id` (`&str`) - Id of the page.
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<Reminder>` - Reminders in order of appearance.
+pub fn tasks(id: &str, content: &str) -> Vec<Reminder> {
+    let name = page::name_from_id(id);
+    let mut reminders = Vec::new();
+    // Indentation and text of the open task whose properties are being read
+    let mut task: Option<(usize, String)> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if let Some((task_indent, text)) = &task {
+            let property = (indent > *task_indent && !trimmed.starts_with("- "))
+                .then(|| trimmed.split_once("::"))
+                .flatten();
+            match property {
+                Some((key, value)) => {
+                    let kind = match key.trim().to_lowercase().as_str() {
+                        "scheduled" => Some(ReminderKind::Scheduled),
+                        "deadline" => Some(ReminderKind::Deadline),
+                        _ => None,
+                    };
+                    if let Some((kind, at)) = kind.zip(parse_time(value)) {
+                        reminders.push(Reminder {
+                            id: id.to_string(),
+                            page: name.clone(),
+                            task: text.clone(),
+                            kind,
+                            at,
+                        });
+                    }
+                    continue;
+                }
+                None => task = None,
+            }
+        }
+
+        if let Some(text) = trimmed.strip_prefix("- ") {
+            let keyword = text.split_whitespace().next().unwrap_or_default();
+            if page::OPEN_TASK_KEYWORDS.contains(&keyword) {
+                task = Some((indent, text.trim().to_string()));
+            }
+        }
+    }
+
+    reminders
+}
+
+/// Returns when each open task of a page first reminds.
+///
+/// Used by the page index, so due tasks can be counted without reading pages.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `Vec<i64>` - Earliest scheduled or deadline time of each open task, as unix timestamps.
+pub fn due(content: &str) -> Vec<i64> {
+    let mut due: Vec<(String, i64)> = Vec::new();
+    for reminder in tasks("", content) {
+        match due.last_mut() {
+            // Properties of the same task follow each other
+            Some((task, at)) if *task == reminder.task => *at = (*at).min(reminder.at),
+            _ => due.push((reminder.task, reminder.at)),
+        }
+    }
+    due.into_iter().map(|(_, at)| at).collect()
+}
+
+/// Parses a `2024-05-01` or `2024-05-01 10:00` property value into a local timestamp.
+fn parse_time(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let time = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            let (hour, minute) = DEFAULT_TIME;
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(hour, minute, 0)
+        })?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tasks() {
+        let content = "- TODO Ship [[flow]]\n  scheduled:: 2024-05-01 10:00\n  deadline:: 2024-05-03\n  - TODO Child\n- DONE Old\n  deadline:: 2024-04-01\n- LATER Read\n  deadline:: someday";
+        let reminders = tasks("pages/roadmap.md", content);
+        assert_eq!(reminders.len(), 2);
+        assert_eq!(reminders[0].task, "TODO Ship [[flow]]");
+        assert_eq!(reminders[0].kind, ReminderKind::Scheduled);
+        assert_eq!(reminders[1].kind, ReminderKind::Deadline);
+        assert!(reminders[0].at < reminders[1].at);
+
+        let quiet = QuietHours {
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+        };
+        assert!(quiet.contains(NaiveTime::from_hms_opt(23, 30, 0).unwrap()));
+        assert!(quiet.contains(NaiveTime::from_hms_opt(6, 59, 0).unwrap()));
+        assert!(!quiet.contains(NaiveTime::from_hms_opt(12, 0, 0).unwrap()));
+    }
+}
@@ -0,0 +1,557 @@
+//! HTML Rendering
+//!
+//! Renders pages to a standalone HTML document, the input for exports like
+//! PDF. Only the markdown Flow pages are written in is supported: headings,
+//! nested bullets, paragraphs, fenced code, emphasis, inline code, links and
+//! wikilinks. Wikilinks to pages that are part of the output link to them,
+//! as anchors in a single document or as files of a static site; links to
+//! other pages are rendered as plain text. Headings get anchors from their
+//! slug, which `[[Page#Heading]]` links point to.
+//!
+//! PDFs are produced from the HTML by an external renderer, a command with
+//! `{input}` and `{output}` placeholders. Without a configured renderer the
+//! common ones are tried in order.
+
+use miette::{IntoDiagnostic, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process;
+
+use crate::page;
+
+/// Renderers tried when none is configured, in order.
+pub const PDF_RENDERERS: &[&str] = &[
+    "weasyprint {input} {output}",
+    "wkhtmltopdf --quiet {input} {output}",
+    "pandoc {input} --output {output}",
+];
+
+/// Stylesheet embedded in rendered documents.
+const STYLE: &str =
+    "body{font-family:sans-serif;line-height:1.5;max-width:46em;margin:2em auto;color:#222}\
+section{page-break-after:always}\
+code,pre{font-family:monospace;background:#f4f4f4}\
+pre{padding:.5em;white-space:pre-wrap}\
+a{color:#2a5db0;text-decoration:none}\
+.wikilink{color:#2a5db0}\
+.property{color:#666;margin:0}";
+
+/// A page to render.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `content` (`String`) - Markdown content of the page.
+#[derive(Debug, Clone)]
+pub struct RenderPage {
+    pub id: String,
+    pub content: String,
+}
+
+/// Returns the URL-safe slug of a page name.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name of the page.
+///
+/// # Returns
+///
+/// - `String` - Lowercase slug, e.g. `projects-flow`.
+pub fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Returns the HTML anchor of a page.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name of the page.
+///
+/// # Returns
+///
+/// - `String` - Anchor id, e.g. `page-projects-flow`.
+pub fn anchor(name: &str) -> String {
+    format!("page-{}", slug(name))
+}
+
+/// Maps the names and aliases of pages to link targets.
+///
+/// # Arguments
+///
+/// - `pages` (`&[RenderPage]`) - Pages that can be linked to.
+/// - `href` (`impl Fn(&str) -> String`) - Link target of a page name.
+///
+/// # Returns
+///
+/// - `HashMap<String, String>` - Link targets by lowercase name or alias.
+pub fn link_targets(
+    pages: &[RenderPage],
+    href: impl Fn(&str) -> String,
+) -> HashMap<String, String> {
+    let mut targets = HashMap::new();
+    for page in pages {
+        let name = page::name_from_id(&page.id);
+        let target = href(&name);
+        for alias in page::aliases(&page.content) {
+            targets.insert(alias.to_lowercase(), target.clone());
+        }
+        targets.insert(name.to_lowercase(), target);
+    }
+    targets
+}
+
+/// Renders pages to a standalone HTML document.
+///
+/// # Arguments
+///
+/// - `title` (`&str`) - Title of the document.
+/// - `pages` (`&[RenderPage]`) - Pages to render, in order.
+///
+/// # Returns
+///
+/// - `String` - The HTML document.
+pub fn to_html(title: &str, pages: &[RenderPage]) -> String {
+    // Wikilinks resolve to pages in the document
+    let links = link_targets(pages, |name| format!("#{}", anchor(name)));
+
+    let mut body = String::new();
+    for page in pages {
+        let name = page::name_from_id(&page.id);
+        let _ = writeln!(
+            body,
+            "<section id=\"{}\">\n<h1>{}</h1>",
+            anchor(&name),
+            escape(&name)
+        );
+        let content = page::absolute_links(&page.content, &name);
+        render_blocks(&mut body, &content, &links, Some(&anchor(&name)));
+        body.push_str("</section>\n");
+    }
+    document(title, &body)
+}
+
+/// Renders a single page of a static site.
+///
+/// # Arguments
+///
+/// - `page` (`&RenderPage`) - Page to render.
+/// - `links` (`&HashMap<String, String>`) - Link targets of other pages, see [`link_targets`].
+/// - `index` (`&str`) - Link target of the site index.
+///
+/// # Returns
+///
+/// - `String` - The HTML document.
+pub fn to_site_page(page: &RenderPage, links: &HashMap<String, String>, index: &str) -> String {
+    let name = page::name_from_id(&page.id);
+    let mut body = format!(
+        "<nav><a href=\"{}\">Index</a></nav>\n<article>\n<h1>{}</h1>\n",
+        escape(index),
+        escape(&name)
+    );
+    let content = page::absolute_links(&page.content, &name);
+    render_blocks(&mut body, &content, links, None);
+    body.push_str("</article>\n");
+    document(&name, &body)
+}
+
+/// Renders the index of a static site.
+///
+/// # Arguments
+///
+/// - `title` (`&str`) - Title of the site.
+/// - `entries` (`&[(String, String)]`) - Page names and their link targets, in order.
+///
+/// # Returns
+///
+/// - `String` - The HTML document.
+pub fn to_site_index(title: &str, entries: &[(String, String)]) -> String {
+    let mut body = format!("<h1>{}</h1>\n<ul>\n", escape(title));
+    for (name, href) in entries {
+        let _ = writeln!(
+            body,
+            "<li><a href=\"{}\">{}</a></li>",
+            escape(href),
+            escape(name)
+        );
+    }
+    body.push_str("</ul>\n");
+    document(title, &body)
+}
+
+/// Renders the content of a page as an HTML fragment, without a document around it.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+/// - `links` (`&HashMap<String, String>`) - Link targets of other pages, see [`link_targets`].
+///
+/// # Returns
+///
+/// - `String` - The HTML fragment.
+pub fn to_fragment(content: &str, links: &HashMap<String, String>) -> String {
+    let mut html = String::new();
+    render_blocks(&mut html, content, links, None);
+    html
+}
+
+/// Wraps a rendered body into a standalone HTML document.
+fn document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+/// Renders HTML to a PDF file with an external renderer.
+///
+/// # Arguments
+///
+/// - `html` (`&str`) - HTML document to render.
+/// - `output` (`&Path`) - PDF file to write.
+/// - `renderer` (`Option<&str>`) - Renderer command, with `{input}` and `{output}` placeholders. Tries [`PDF_RENDERERS`] if `None`.
+///
+/// # Returns
+///
+/// - `Result<String>` - The renderer command that produced the PDF.
+///
+/// # Errors
+///
+/// No renderer is installed, the renderer failed, or IO errors when writing
+/// the intermediate HTML file.
+pub fn to_pdf(html: &str, output: &Path, renderer: Option<&str>) -> Result<String> {
+    let input = output.with_extension("html.partial");
+    fs::write(&input, html).into_diagnostic()?;
+
+    let candidates = match renderer {
+        Some(renderer) => vec![renderer],
+        None => PDF_RENDERERS.to_vec(),
+    };
+    let mut result = None;
+    for candidate in candidates {
+        match run_renderer(candidate, &input, output) {
+            Err(error) if renderer.is_none() && error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => {
+                result = Some(Err(miette::miette!(
+                    "Failed to run PDF renderer '{}': {}",
+                    candidate,
+                    error
+                )));
+                break;
+            }
+            Ok(status) if status.success() => {
+                result = Some(Ok(candidate.to_string()));
+                break;
+            }
+            Ok(status) => {
+                result = Some(Err(miette::miette!(
+                    "PDF renderer '{}' failed with {}",
+                    candidate,
+                    status
+                )));
+                break;
+            }
+        }
+    }
+    let _ = fs::remove_file(&input);
+
+    result.unwrap_or_else(|| {
+        Err(miette::miette!(
+            "No PDF renderer found, install weasyprint, wkhtmltopdf or pandoc, or set `pdf_renderer` in the Flow configuration"
+        ))
+    })
+}
+
+/// Runs a renderer command, substituting the input and output paths.
+fn run_renderer(
+    renderer: &str,
+    input: &Path,
+    output: &Path,
+) -> std::io::Result<process::ExitStatus> {
+    let mut parts = renderer.split_whitespace().map(|part| {
+        part.replace("{input}", &input.to_string_lossy())
+            .replace("{output}", &output.to_string_lossy())
+    });
+    let program = parts
+        .next()
+        .ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "empty command"))?;
+    process::Command::new(program)
+        .args(parts)
+        .stdout(process::Stdio::null())
+        .status()
+}
+
+/// Renders the blocks of a page.
+///
+/// Headings get ids from their slug, prefixed with `prefix` when several
+/// pages share a document.
+fn render_blocks(
+    html: &mut String,
+    content: &str,
+    links: &HashMap<String, String>,
+    prefix: Option<&str>,
+) {
+    // Indentation of the open lists, innermost last
+    let mut lists: Vec<usize> = Vec::new();
+    let mut paragraph = false;
+    let mut code = false;
+
+    let close_paragraph = |html: &mut String, paragraph: &mut bool| {
+        if *paragraph {
+            html.push_str("</p>\n");
+            *paragraph = false;
+        }
+    };
+    let close_lists = |html: &mut String, lists: &mut Vec<usize>, indent: Option<usize>| {
+        while lists
+            .last()
+            .is_some_and(|open| indent.is_none_or(|indent| *open > indent))
+        {
+            html.push_str("</li>\n</ul>\n");
+            lists.pop();
+        }
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.starts_with("```") {
+            if code {
+                html.push_str("</code></pre>\n");
+            } else {
+                close_paragraph(html, &mut paragraph);
+                html.push_str("<pre><code>");
+            }
+            code = !code;
+            continue;
+        }
+        if code {
+            let _ = writeln!(html, "{}", escape(line));
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            close_paragraph(html, &mut paragraph);
+            close_lists(html, &mut lists, None);
+            continue;
+        }
+
+        if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or((trimmed == "-").then_some(""))
+        {
+            close_paragraph(html, &mut paragraph);
+            close_lists(html, &mut lists, Some(indent));
+            if lists.last() == Some(&indent) {
+                html.push_str("</li>\n<li>");
+            } else {
+                html.push_str("<ul>\n<li>");
+                lists.push(indent);
+            }
+            match heading(item) {
+                Some((level, text)) => {
+                    let _ = write!(
+                        html,
+                        "<h{0} id=\"{1}\">{2}</h{0}>",
+                        level,
+                        escape(&heading_id(prefix, text)),
+                        inline(text, links)
+                    );
+                }
+                None => html.push_str(&inline(item, links)),
+            }
+            continue;
+        }
+
+        if !lists.is_empty() && indent > 0 {
+            // Continuation of the current bullet
+            let _ = write!(html, "<br>{}", inline(trimmed, links));
+            continue;
+        }
+        close_lists(html, &mut lists, None);
+
+        if let Some((level, text)) = heading(trimmed) {
+            close_paragraph(html, &mut paragraph);
+            let _ = writeln!(
+                html,
+                "<h{0} id=\"{1}\">{2}</h{0}>",
+                level,
+                escape(&heading_id(prefix, text)),
+                inline(text, links)
+            );
+        } else if trimmed.contains(":: ") && !paragraph {
+            let _ = writeln!(html, "<p class=\"property\">{}</p>", inline(trimmed, links));
+        } else if paragraph {
+            let _ = write!(html, "<br>\n{}", inline(trimmed, links));
+        } else {
+            let _ = write!(html, "<p>{}", inline(trimmed, links));
+            paragraph = true;
+        }
+    }
+
+    if code {
+        html.push_str("</code></pre>\n");
+    }
+    close_paragraph(html, &mut paragraph);
+    close_lists(html, &mut lists, None);
+}
+
+/// Splits a markdown heading into its level and text.
+///
+/// Levels are shifted by one, `h1` is reserved for page titles.
+fn heading(text: &str) -> Option<(usize, &str)> {
+    let hashes = text.len() - text.trim_start_matches('#').len();
+    let rest = &text[hashes..];
+    (1..=6)
+        .contains(&hashes)
+        .then_some(())
+        .and_then(|_| rest.strip_prefix(' '))
+        .map(|rest| ((hashes + 1).min(6), rest.trim()))
+}
+
+/// Returns the id of a heading, see [`page::headings`] for its anchor.
+fn heading_id(prefix: Option<&str>, text: &str) -> String {
+    match prefix {
+        Some(prefix) => format!("{}-{}", prefix, slug(text)),
+        None => slug(text),
+    }
+}
+
+/// Returns the link target of a heading on a page with the given link target.
+///
+/// Pages linked by fragment share a document with prefixed heading ids,
+/// other pages are separate documents.
+fn section_href(href: &str, section: &str) -> String {
+    if href.starts_with('#') {
+        format!("{}-{}", href, slug(section))
+    } else {
+        format!("{}#{}", href, slug(section))
+    }
+}
+
+/// Renders inline markdown: code, wikilinks, links, bold and italics.
+fn inline(text: &str, links: &HashMap<String, String>) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    let (mut bold, mut italic) = (false, false);
+
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                let _ = write!(html, "<code>{}</code>", escape(&rest[1..1 + end]));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if rest.starts_with("[[") {
+            if let Some(end) = rest.find("]]") {
+                let inner = &rest[2..end];
+                let (target, label) = inner.split_once('|').unwrap_or((inner, inner));
+                let (target, label) = (target.trim(), escape(label.trim()));
+                let (target, section) = match target.split_once('#') {
+                    Some((target, section)) => (target.trim(), Some(section.trim())),
+                    None => (target, None),
+                };
+                match links.get(&target.to_lowercase()) {
+                    Some(href) => {
+                        let href = match section {
+                            Some(section) => section_href(href, section),
+                            None => href.clone(),
+                        };
+                        let _ = write!(html, "<a href=\"{}\">{}</a>", escape(&href), label);
+                    }
+                    None => {
+                        let _ = write!(html, "<span class=\"wikilink\">{}</span>", label);
+                    }
+                }
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if c == '[' {
+            let link = rest
+                .find("](")
+                .and_then(|middle| rest[middle..].find(')').map(|end| (middle, middle + end)));
+            if let Some((middle, end)) = link {
+                let _ = write!(
+                    html,
+                    "<a href=\"{}\">{}</a>",
+                    escape(&rest[middle + 2..end]),
+                    escape(&rest[1..middle])
+                );
+                rest = &rest[end + 1..];
+                continue;
+            }
+        } else if rest.starts_with("**") {
+            html.push_str(if bold { "</strong>" } else { "<strong>" });
+            bold = !bold;
+            rest = &rest[2..];
+            continue;
+        } else if c == '*' || (c == '_' && !html.ends_with(|c: char| c.is_alphanumeric())) {
+            html.push_str(if italic { "</em>" } else { "<em>" });
+            italic = !italic;
+            rest = &rest[1..];
+            continue;
+        }
+
+        html.push_str(&escape(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    if italic {
+        html.push_str("</em>");
+    }
+    if bold {
+        html.push_str("</strong>");
+    }
+    html
+}
+
+/// Escapes text for HTML and XML.
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html() {
+        let pages = vec![
+            RenderPage {
+                id: "journal/2024-05-01.md".to_string(),
+                content: "- Met with [[Ada]] about **flow**\n  - next: `ship`\n- [[Elsewhere]], [[Ada#About|bio]]"
+                    .to_string(),
+            },
+            RenderPage {
+                id: "pages/Ada.md".to_string(),
+                content: "# About\nWorks on <engines>".to_string(),
+            },
+        ];
+
+        let html = to_html("Notes", &pages);
+        assert!(html.contains("<section id=\"page-2024-05-01\">"));
+        assert!(html.contains("<a href=\"#page-ada\">Ada</a>"));
+        assert!(html.contains("<strong>flow</strong>"));
+        assert!(html.contains("<ul>\n<li>next: <code>ship</code></li>\n</ul>"));
+        assert!(html.contains("<span class=\"wikilink\">Elsewhere</span>"));
+        assert!(html.contains("<h2 id=\"page-ada-about\">About</h2>"));
+        assert!(html.contains("<a href=\"#page-ada-about\">bio</a>"));
+        assert!(html.contains("Works on &lt;engines&gt;"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Docs Spaces
+//!
+//! A docs space lives in a subdirectory of a code repository (e.g.
+//! `docs/notes`), holding per-project developer notes next to the code. Its
+//! markdown pages are committed with the code, while the internals in `.flow/`
+//! are kept out of git via the repository's `.gitignore`. Optionally the
+//! snapshot and metadata stay tracked, so a fresh clone is a complete space.
+//!
+//! Docs spaces are registered under a name namespaced by the repository,
+//! `<repo>/<space>`, so the notes of different projects don't collide.
+//!
+//! A space of its own can be tracked with git as well (see [`track`]), the
+//! whole `.flow/` directory is ignored then.
+
+use miette::{IntoDiagnostic, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::paths;
+use crate::space::{write_atomic, DOCUMENT_FILE, FLOW_DIR, METADATA_FILE};
+
+/// Directory marking the root of a git repository.
+const GIT_DIR: &str = ".git";
+
+/// Ignore file at the root of a git repository.
+const GITIGNORE_FILE: &str = ".gitignore";
+
+/// Comment heading the ignore rules of a docs space.
+const GITIGNORE_HEADER: &str = "# Flow space internals";
+
+/// Finds the git repository a path lies in.
+///
+/// The path doesn't need to exist yet. `.git` may be a directory or, in
+/// worktrees and submodules, a file.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path inside the repository.
+///
+/// # Returns
+///
+/// - `Option<PathBuf>` - Root directory of the repository, `None` outside of one.
+pub fn root(path: &Path) -> Option<PathBuf> {
+    paths::normalize(path)
+        .ancestors()
+        .find(|dir| dir.join(GIT_DIR).exists())
+        .map(Path::to_path_buf)
+}
+
+/// Tracks a space with git, creating a repository unless it already lies in one.
+///
+/// The internals in `.flow/` are ignored, the markdown pages are the part
+/// worth versioning.
+///
+/// # Arguments
+///
+/// - `space` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<PathBuf>` - Root directory of the repository.
+///
+/// # Errors
+///
+/// `git` can't be run or fails, or IO errors when writing the `.gitignore`.
+pub fn track(space: &Path) -> Result<PathBuf> {
+    let root = match root(space) {
+        Some(root) => root,
+        None => {
+            let status = process::Command::new("git")
+                .arg("init")
+                .current_dir(space)
+                .stdout(process::Stdio::null())
+                .status()
+                .map_err(|error| miette::miette!("Failed to run 'git': {}", error))?;
+            if !status.success() {
+                miette::bail!("'git init' failed with {}", status);
+            }
+            paths::normalize(space)
+        }
+    };
+    ignore_internals(&root, space, false)?;
+    Ok(root)
+}
+
+/// Namespaces the name of a docs space by its repository.
+///
+/// # Arguments
+///
+/// - `root` (`&Path`) - Root directory of the repository.
+/// - `name` (`&str`) - Name of the space.
+///
+/// # Returns
+///
+/// - `String` - `<repo>/<name>`, or `name` if the root has no directory name.
+pub fn namespaced(root: &Path, name: &str) -> String {
+    match root.file_name() {
+        Some(repo) => format!("{}/{}", repo.to_string_lossy(), name),
+        None => name.to_string(),
+    }
+}
+
+/// Adds the ignore rules of a docs space to the repository's `.gitignore`.
+///
+/// Rules already present are left alone, so initializing the same space again
+/// doesn't duplicate them.
+///
+/// # Arguments
+///
+/// - `root` (`&Path`) - Root directory of the repository.
+/// - `space` (`&Path`) - Path of the space inside the repository.
+/// - `track_snapshot` (`bool`) - Keep the snapshot and metadata tracked.
+///
+/// # Returns
+///
+/// - `Result<bool>` - True if the `.gitignore` was changed.
+///
+/// # Errors
+///
+/// IO errors when reading or writing the `.gitignore`, or if `space` isn't
+/// inside `root`.
+pub fn ignore_internals(root: &Path, space: &Path, track_snapshot: bool) -> Result<bool> {
+    let relative = paths::normalize(space)
+        .strip_prefix(paths::normalize(root))
+        .into_diagnostic()?
+        .to_path_buf();
+    let rules = rules(&relative, track_snapshot);
+
+    let path = root.join(GITIGNORE_FILE);
+    let existing = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).into_diagnostic(),
+    };
+    let missing: Vec<&String> = rules
+        .iter()
+        .filter(|rule| !existing.lines().any(|line| line.trim() == rule.as_str()))
+        .collect();
+    if missing.is_empty() {
+        return Ok(false);
+    }
+
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str(GITIGNORE_HEADER);
+    content.push('\n');
+    for rule in missing {
+        content.push_str(rule);
+        content.push('\n');
+    }
+    write_atomic(&path, content.as_bytes())?;
+    Ok(true)
+}
+
+/// Builds the ignore rules of a docs space.
+///
+/// # Arguments
+///
+/// - `relative` (`&Path`) - Path of the space relative to the repository root.
+/// - `track_snapshot` (`bool`) - Keep the snapshot and metadata tracked.
+///
+/// # Returns
+///
+/// - `Vec<String>` - Rules anchored at the repository root.
+fn rules(relative: &Path, track_snapshot: bool) -> Vec<String> {
+    let mut dir = String::from("/");
+    for component in relative.components() {
+        dir.push_str(&component.as_os_str().to_string_lossy());
+        dir.push('/');
+    }
+    dir.push_str(FLOW_DIR);
+
+    if !track_snapshot {
+        return vec![format!("{}/", dir)];
+    }
+    vec![
+        format!("{}/*", dir),
+        format!("!{}/{}", dir, METADATA_FILE),
+        format!("!{}/{}", dir, DOCUMENT_FILE),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_repo(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("flow-repo-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(GIT_DIR)).unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn test_rules() {
+        let relative = Path::new("docs").join("notes");
+        assert_eq!(rules(&relative, false), ["/docs/notes/.flow/"]);
+        assert_eq!(
+            rules(&relative, true),
+            [
+                "/docs/notes/.flow/*",
+                "!/docs/notes/.flow/space.toml",
+                "!/docs/notes/.flow/space.loro",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_root_and_namespaced() {
+        let repo = scratch_repo("root");
+        let space = repo.join("docs").join("notes");
+        fs::create_dir_all(&space).unwrap();
+
+        assert_eq!(root(&space), Some(repo.clone()));
+        let name = namespaced(&repo, "notes");
+        assert!(name.starts_with("flow-repo-root-"));
+        assert!(name.ends_with("/notes"));
+    }
+
+    #[test]
+    fn test_ignore_internals_is_idempotent() {
+        let repo = scratch_repo("ignore");
+        let space = repo.join("docs").join("notes");
+        fs::create_dir_all(&space).unwrap();
+        fs::write(repo.join(GITIGNORE_FILE), "/target").unwrap();
+
+        assert!(ignore_internals(&repo, &space, false).unwrap());
+        assert!(!ignore_internals(&repo, &space, false).unwrap());
+        assert_eq!(
+            fs::read_to_string(repo.join(GITIGNORE_FILE)).unwrap(),
+            "/target\n\n# Flow space internals\n/docs/notes/.flow/\n"
+        );
+    }
+}
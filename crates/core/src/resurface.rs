@@ -0,0 +1,206 @@
+//! Resurfacing
+//!
+//! Picks old pages to revisit, at random or the ones least recently seen.
+//! A page counts as seen when it was modified or last resurfaced; resurfaced
+//! pages are recorded per space in `.flow/seen.json`, so repeated runs move
+//! on to other pages. Like the capture context, this is local state and
+//! isn't synced.
+
+use chrono::Utc;
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::page;
+use crate::space::{write_atomic, Space, FLOW_DIR};
+
+const SEEN_FILE: &str = "seen.json";
+
+/// Seconds in a day.
+const DAY: i64 = 24 * 60 * 60;
+
+/// Which pages to resurface.
+///
+/// # Fields
+///
+/// - `tag` (`Option<String>`) - Only pages with this tag.
+/// - `older_than` (`Option<i64>`) - Only pages not modified for this many days.
+/// - `count` (`usize`) - Number of pages to pick.
+/// - `least_seen` (`bool`) - Pick the least recently seen pages instead of random ones.
+#[derive(Debug, Clone, Default)]
+pub struct ResurfaceOptions {
+    pub tag: Option<String>,
+    pub older_than: Option<i64>,
+    pub count: usize,
+    pub least_seen: bool,
+}
+
+/// A resurfaced page.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `modified` (`i64`) - Last modification as unix timestamp in seconds.
+/// - `seen` (`Option<i64>`) - When the page was last resurfaced, `None` if never.
+/// - `preview` (`String`) - First block of the page.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Resurfaced {
+    pub id: String,
+    pub page: String,
+    pub modified: i64,
+    pub seen: Option<i64>,
+    pub preview: String,
+}
+
+/// When pages were last resurfaced.
+///
+/// # Fields
+///
+/// - `pages` (`BTreeMap<String, i64>`) - Unix timestamp by page id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Seen {
+    #[serde(default)]
+    pages: BTreeMap<String, i64>,
+}
+
+impl Seen {
+    /// Loads the seen pages of a space, empty if missing or unreadable.
+    fn load(space_path: &Path) -> Self {
+        fs::read_to_string(space_path.join(FLOW_DIR).join(SEEN_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persists the seen pages of a space.
+    fn save(&self, space_path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).into_diagnostic()?;
+        write_atomic(&space_path.join(FLOW_DIR).join(SEEN_FILE), json.as_bytes())
+    }
+}
+
+impl Space {
+    /// Picks pages to revisit and records them as seen.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to pick from.
+    /// - `options` (`&ResurfaceOptions`) - Filters and number of pages.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Resurfaced>>` - Picked pages, empty if no page matches.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the index, reading pages or writing the seen file.
+    pub fn resurface(&self, options: &ResurfaceOptions) -> Result<Vec<Resurfaced>> {
+        let now = Utc::now().timestamp();
+        let index = self.page_index()?;
+        let mut seen = Seen::load(&self.path);
+        let tag = options
+            .tag
+            .as_deref()
+            .map(|tag| tag.trim_start_matches('#'));
+
+        let mut candidates: Vec<_> = index
+            .pages()
+            .filter(|entry| {
+                options
+                    .older_than
+                    .is_none_or(|days| entry.modified <= now - days * DAY)
+            })
+            .filter(|entry| {
+                tag.is_none_or(|tag| entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            })
+            .collect();
+
+        if options.least_seen {
+            let last_seen = |id: &str, modified: i64| {
+                seen.pages
+                    .get(id)
+                    .map_or(modified, |at| (*at).max(modified))
+            };
+            candidates
+                .sort_by_key(|entry| (last_seen(&entry.id, entry.modified), entry.id.clone()));
+        } else {
+            candidates.sort_by_cached_key(|_| Uuid::new_v4().as_u128());
+        }
+        candidates.truncate(options.count.max(1));
+
+        let mut picked = Vec::new();
+        for entry in candidates {
+            let content = self.read_page(&entry.id)?.unwrap_or_default();
+            picked.push(Resurfaced {
+                id: entry.id.clone(),
+                page: entry.name.clone(),
+                modified: entry.modified,
+                seen: seen.pages.insert(entry.id.clone(), now),
+                preview: preview(&content),
+            });
+        }
+        if !picked.is_empty() {
+            seen.save(&self.path)?;
+        }
+        Ok(picked)
+    }
+}
+
+/// Parses an age like `90d`, `2w`, `6m` or `1y` into days.
+///
+/// Months count as 30 days, years as 365. A plain number is a number of days.
+///
+/// # Arguments
+///
+/// - `value` (`&str`) - Age as written.
+///
+/// # Returns
+///
+/// - `Option<i64>` - Number of days, `None` if the age is malformed.
+pub fn parse_age(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => value.split_at(split),
+        None => (value, "d"),
+    };
+    let days = match unit.trim() {
+        "d" => 1,
+        "w" => 7,
+        "m" => 30,
+        "y" => 365,
+        _ => return None,
+    };
+    number.parse::<i64>().ok().map(|number| number * days)
+}
+
+/// Returns the first block of a page, skipping its properties.
+fn preview(content: &str) -> String {
+    let properties = page::properties(content).len();
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .nth(properties)
+        .map(|line| line.trim_start_matches("- ").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("90d"), Some(90));
+        assert_eq!(parse_age("2w"), Some(14));
+        assert_eq!(parse_age("1y"), Some(365));
+        assert_eq!(parse_age("12"), Some(12));
+        assert_eq!(parse_age("3 months"), None);
+        assert_eq!(parse_age("d"), None);
+    }
+}
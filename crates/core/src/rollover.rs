@@ -0,0 +1,219 @@
+//! Task Rollover
+//!
+//! Carries unfinished tasks over to the current journal page, as in a bullet
+//! journal: every open task (see [`page::OPEN_TASK_KEYWORDS`]) of the latest
+//! earlier journal page is copied to today's page with its children, and the
+//! original is marked as migrated so it isn't carried over again:
+//!
+//! ```markdown
+//! - MIGRATED Call the plumber
+//!   - About the kitchen sink
+//! ```
+//!
+//! Rollover runs with `flow rollover`, or when the day starts (see
+//! [`Space::today_page`]) if enabled in the space metadata:
+//!
+//! ```toml
+//! [journal]
+//! carry_over = true
+//! ```
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::journal;
+use crate::page;
+use crate::space::Space;
+
+/// Keyword replacing the keyword of tasks carried over to a later page.
+pub const MIGRATED_KEYWORD: &str = "MIGRATED";
+
+/// Tasks carried over to the current journal page.
+///
+/// # Fields
+///
+/// - `from` (`Option<String>`) - Id of the journal page the tasks came from, `None` if there is none.
+/// - `to` (`String`) - Id of the current journal page.
+/// - `tasks` (`Vec<String>`) - Text of the carried over tasks, including their keyword.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Rollover {
+    pub from: Option<String>,
+    pub to: String,
+    pub tasks: Vec<String>,
+}
+
+impl Space {
+    /// Carries the open tasks of the previous journal page over to today's page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to roll over.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Rollover>` - The carried over tasks, none if everything is done.
+    ///
+    /// # Errors
+    ///
+    /// A journal page is locked, or IO errors when reading or writing pages.
+    pub fn rollover(&mut self) -> Result<Rollover> {
+        let today = self.today();
+        let to = self.journal_page(today);
+        let start = journal::period(&page::name_from_id(&to)).map_or(today, |(first, _)| first);
+
+        let previous = self
+            .page_ids()?
+            .into_iter()
+            .filter(|id| page::is_journal(id))
+            .filter_map(|id| {
+                let (first, _) = journal::period(&page::name_from_id(&id))?;
+                (first < start).then_some((first, id))
+            })
+            .max();
+        let mut rollover = Rollover {
+            from: previous.as_ref().map(|(_, id)| id.clone()),
+            to: to.clone(),
+            tasks: Vec::new(),
+        };
+        let Some((_, from)) = previous else {
+            return Ok(rollover);
+        };
+        let content = self.read_page(&from)?.unwrap_or_default();
+        let (marked, carried) = carry_over(&content);
+        if carried.is_empty() {
+            return Ok(rollover);
+        }
+
+        // Marked first, so starting the day doesn't carry the tasks over again
+        self.ensure_writable(&to)?;
+        self.transaction(|space| {
+            space.write_page(&from, &marked)?;
+            space.create_journal_page(today)?;
+            space.append(&to, &carried.join("\n"))
+        })?;
+
+        rollover.tasks = carried
+            .iter()
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .filter_map(|line| line.strip_prefix("- "))
+            .map(str::to_string)
+            .collect();
+        Ok(rollover)
+    }
+}
+
+/// Returns the keyword of a block line if it is an open task.
+fn open_task(line: &str) -> Option<&str> {
+    let keyword = line
+        .trim_start()
+        .strip_prefix("- ")?
+        .split_whitespace()
+        .next()?;
+    page::OPEN_TASK_KEYWORDS
+        .contains(&keyword)
+        .then_some(keyword)
+}
+
+/// Returns the indentation width of a line.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Splits the open tasks off a page.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `(String, Vec<String>)` - The page with its open tasks marked as
+///   migrated, and the lines of the open tasks with their children as
+///   top-level blocks.
+fn carry_over(content: &str) -> (String, Vec<String>) {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut carried = Vec::new();
+
+    let mut index = 0;
+    while index < lines.len() {
+        let Some(keyword) = open_task(&lines[index]) else {
+            index += 1;
+            continue;
+        };
+        let indent = indentation(&lines[index]);
+        let end = (index + 1..lines.len())
+            .find(|i| {
+                let line = &lines[*i];
+                !line.trim().is_empty() && indentation(line) <= indent
+            })
+            .unwrap_or(lines.len());
+
+        // Children of a task move along with it
+        for line in &lines[index..end] {
+            if !line.trim().is_empty() {
+                carried.push(line[indent.min(indentation(line))..].to_string());
+            }
+        }
+        lines[index] = lines[index].replacen(keyword, MIGRATED_KEYWORD, 1);
+        index = end;
+    }
+
+    let mut marked = lines.join("\n");
+    if content.ends_with('\n') {
+        marked.push('\n');
+    }
+    (marked, carried)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_carry_over() {
+        let content = "- Standup\n  - TODO Review PR\n    - Left comments\n- DONE Ship it\n- LATER Call the plumber\n";
+        let (marked, carried) = carry_over(content);
+        assert_eq!(
+            marked,
+            "- Standup\n  - MIGRATED Review PR\n    - Left comments\n- DONE Ship it\n- MIGRATED Call the plumber\n"
+        );
+        assert_eq!(
+            carried,
+            vec![
+                "- TODO Review PR",
+                "  - Left comments",
+                "- LATER Call the plumber"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rollover_from_latest_journal_page() {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page("journal/2000-01-01.md", "- TODO Ancient\n")
+            .unwrap();
+        space
+            .write_page(
+                "journal/2000-01-03.md",
+                "- TODO Water plants\n- DONE Ship it\n",
+            )
+            .unwrap();
+
+        let rollover = space.rollover().unwrap();
+        assert_eq!(rollover.from.as_deref(), Some("journal/2000-01-03.md"));
+        assert_eq!(rollover.tasks, vec!["TODO Water plants"]);
+        assert!(space
+            .read_page(&rollover.to)
+            .unwrap()
+            .unwrap()
+            .contains("- TODO Water plants"));
+        assert_eq!(
+            space.read_page("journal/2000-01-03.md").unwrap().unwrap(),
+            "- MIGRATED Water plants\n- DONE Ship it\n"
+        );
+
+        assert!(space.rollover().unwrap().tasks.is_empty());
+    }
+}
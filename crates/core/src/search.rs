@@ -0,0 +1,333 @@
+//! Full-Text Search
+//!
+//! Case-insensitive substring search over all pages of a space. Each matching
+//! line is reported as a separate hit, so callers can jump straight to it.
+//!
+//! [`Space::search_ranked`] uses the full-text index (see
+//! [`fulltext`](crate::fulltext)) instead, returning whole pages ordered by
+//! relevance.
+//!
+//! Searches can be narrowed with a [`SearchScope`]. Scopes are evaluated
+//! against the page index before any page content is read, so scoped searches
+//! only pay for the pages in scope.
+
+use chrono::{Local, NaiveDate, TimeZone};
+use miette::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+
+use crate::fulltext;
+use crate::index::PageEntry;
+use crate::journal;
+use crate::page;
+use crate::space::{container_names, Space};
+
+/// A page matching a ranked search.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `score` (`f64`) - Relevance of the page, higher is better.
+/// - `line` (`Option<usize>`) - First line containing a query term, starting at 1.
+/// - `text` (`Option<String>`) - Content of that line.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RankedHit {
+    pub id: String,
+    pub page: String,
+    pub score: f64,
+    pub line: Option<usize>,
+    pub text: Option<String>,
+}
+
+/// Restricts which pages a search looks at.
+///
+/// # Fields
+///
+/// - `journal_only` (`bool`) - Only search journal pages.
+/// - `pages_only` (`bool`) - Only search non-journal pages.
+/// - `tags` (`Vec<String>`) - Only search pages with all of these tags.
+/// - `path` (`Option<String>`) - Only search pages whose id matches this glob
+///   (`*` within a path segment, `**` across segments, `?` a single character).
+/// - `since` (`Option<NaiveDate>`) - Only search journal pages from this date
+///   on and other pages modified since this date.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchScope {
+    pub journal_only: bool,
+    pub pages_only: bool,
+    pub tags: Vec<String>,
+    pub path: Option<String>,
+    pub since: Option<NaiveDate>,
+}
+
+impl SearchScope {
+    /// Checks whether the scope restricts anything.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if every page is in scope.
+    pub fn is_unscoped(&self) -> bool {
+        !self.journal_only
+            && !self.pages_only
+            && self.tags.is_empty()
+            && self.path.is_none()
+            && self.since.is_none()
+    }
+
+    /// Checks whether an indexed page is in scope.
+    ///
+    /// # Arguments
+    ///
+    /// - `entry` (`&PageEntry`) - Index entry of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the page should be searched.
+    pub fn matches(&self, entry: &PageEntry) -> bool {
+        let journal = page::is_journal(&entry.id);
+        if (self.journal_only && !journal) || (self.pages_only && journal) {
+            return false;
+        }
+
+        if !self.tags.iter().all(|tag| {
+            entry
+                .tags
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(tag.trim_start_matches('#')))
+        }) {
+            return false;
+        }
+
+        if let Some(pattern) = &self.path {
+            if !glob_match(pattern, &entry.id) {
+                return false;
+            }
+        }
+
+        if let Some(since) = self.since {
+            let in_range = match journal::period(&entry.name) {
+                Some((_, last)) if journal => last >= since,
+                _ => since
+                    .and_hms_opt(0, 0, 0)
+                    .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+                    .is_none_or(|midnight| entry.modified >= midnight.timestamp()),
+            };
+            if !in_range {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// A single line matching a search query.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page containing the match.
+/// - `page` (`String`) - Name of the page containing the match.
+/// - `line` (`usize`) - Line number of the match, starting at 1.
+/// - `text` (`String`) - Content of the matching line.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub page: String,
+    pub line: usize,
+    pub text: String,
+}
+
+impl Space {
+    /// Searches all pages for lines containing the query (case-insensitive).
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `query` (`&str`) - Text to search for.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<SearchHit>>` - Matching lines, ordered by page id and line.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when listing pages.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>> {
+        self.search_scoped(query, &SearchScope::default())
+    }
+
+    /// Searches the pages in scope for lines containing the query (case-insensitive).
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `query` (`&str`) - Text to search for.
+    /// - `scope` (`&SearchScope`) - Pages to search.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<SearchHit>>` - Matching lines, ordered by page id and line.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when listing pages or loading the index.
+    pub fn search_scoped(&self, query: &str, scope: &SearchScope) -> Result<Vec<SearchHit>> {
+        let query = query.to_lowercase();
+        let mut hits = Vec::new();
+        if query.is_empty() {
+            return Ok(hits);
+        }
+
+        let ids = if scope.is_unscoped() {
+            self.page_ids()?
+        } else {
+            self.page_index()?
+                .pages()
+                .filter(|entry| scope.matches(entry))
+                .map(|entry| entry.id.clone())
+                .collect()
+        };
+
+        // Resolve tracked pages once instead of per page in `read_page`
+        let tracked: HashSet<String> = container_names(&self.document).into_iter().collect();
+        for id in ids {
+            let content = if tracked.contains(&id) {
+                self.document.get_text(id.as_str()).to_string()
+            } else {
+                match fs::read_to_string(self.path.join(&id)) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                }
+            };
+
+            for (index, line) in content.lines().enumerate() {
+                if line.to_lowercase().contains(&query) {
+                    hits.push(SearchHit {
+                        id: id.clone(),
+                        page: page::name_from_id(&id),
+                        line: index + 1,
+                        text: line.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(hits)
+    }
+}
+
+impl Space {
+    /// Searches the full-text index, returning pages ordered by relevance.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to search.
+    /// - `query` (`&str`) - Query (see [`fulltext`](crate::fulltext) for the syntax).
+    /// - `scope` (`&SearchScope`) - Pages to search.
+    /// - `limit` (`usize`) - Maximum number of hits, `0` for all.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<RankedHit>>` - Matching pages, best match first.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when loading the indexes or reading pages.
+    pub fn search_ranked(
+        &self,
+        query: &str,
+        scope: &SearchScope,
+        limit: usize,
+    ) -> Result<Vec<RankedHit>> {
+        let matches = self.text_index()?.search(query);
+
+        let in_scope: Option<HashSet<String>> = if scope.is_unscoped() {
+            None
+        } else {
+            Some(
+                self.page_index()?
+                    .pages()
+                    .filter(|entry| scope.matches(entry))
+                    .map(|entry| entry.id.clone())
+                    .collect(),
+            )
+        };
+
+        let terms = fulltext::tokenize(&query.replace('*', ""));
+        let mut hits = Vec::new();
+        for ranked in matches {
+            if in_scope
+                .as_ref()
+                .is_some_and(|ids| !ids.contains(&ranked.id))
+            {
+                continue;
+            }
+            if limit > 0 && hits.len() == limit {
+                break;
+            }
+
+            let snippet = self.read_page(&ranked.id)?.and_then(|content| {
+                content.lines().enumerate().find_map(|(index, line)| {
+                    let lower = line.to_lowercase();
+                    terms
+                        .iter()
+                        .any(|term| lower.contains(term.as_str()))
+                        .then(|| (index + 1, line.to_string()))
+                })
+            });
+
+            hits.push(RankedHit {
+                page: page::name_from_id(&ranked.id),
+                id: ranked.id,
+                score: ranked.score,
+                line: snippet.as_ref().map(|(line, _)| *line),
+                text: snippet.map(|(_, text)| text),
+            });
+        }
+
+        Ok(hits)
+    }
+}
+
+/// Matches a page id against a glob pattern.
+///
+/// `*` matches any characters except `/`, `**` matches any characters and `?`
+/// matches a single character other than `/`.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_at(&pattern, &text)
+}
+
+fn glob_match_at(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            let rest = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=text.len()).any(|skip| glob_match_at(rest, &text[skip..]))
+        }
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|&skip| skip == 0 || text[skip - 1] != '/')
+            .any(|skip| glob_match_at(rest, &text[skip..])),
+        ['?', rest @ ..] => matches!(text, [c, ..] if *c != '/') && glob_match_at(rest, &text[1..]),
+        [p, rest @ ..] => matches!(text, [c, ..] if c == p) && glob_match_at(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("pages/*.md", "pages/flow.md"));
+        assert!(!glob_match("pages/*.md", "pages/projects/flow.md"));
+        assert!(glob_match("pages/**/*.md", "pages/projects/flow.md"));
+        assert!(glob_match("pages/**/*.md", "pages/flow.md"));
+        assert!(glob_match("journal/2024-05-0?.md", "journal/2024-05-01.md"));
+        assert!(!glob_match("journal/*", "pages/flow.md"));
+    }
+}
@@ -0,0 +1,360 @@
+//! Secrets
+//!
+//! Credentials such as sync passwords and API tokens are kept out of
+//! `flow.toml`. Config values reference them by name instead, e.g.
+//! `token = "secret:github"`, and are resolved with [`Secrets::resolve`].
+//!
+//! Secrets are stored in the OS keychain (macOS Keychain, Windows Credential
+//! Manager, Secret Service on Linux) when Flow is built with the `keychain`
+//! feature and a keychain is available. Otherwise they fall back to
+//! `secrets.toml` next to the config file, readable only by the current user.
+//! The fallback file also records which secrets live in the keychain, since
+//! keychains can't be listed.
+
+use miette::{Context, IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Service name secrets are stored under in the keychain.
+#[cfg(feature = "keychain")]
+const SERVICE: &str = "flow";
+const SECRETS_FILE: &str = "secrets.toml";
+
+/// Prefix of config values referencing a secret.
+pub const SECRET_PREFIX: &str = "secret:";
+
+/// Where a secret is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    /// The OS keychain.
+    Keychain,
+    /// The fallback secrets file.
+    File,
+}
+
+/// Contents of the secrets file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SecretsFile {
+    #[serde(default)]
+    keychain: BTreeSet<String>,
+    #[serde(default)]
+    file: BTreeMap<String, String>,
+}
+
+/// Access to the stored secrets.
+///
+/// # Fields
+///
+/// - `path` (`PathBuf`) - Path of the fallback secrets file.
+#[derive(Debug, Clone)]
+pub struct Secrets {
+    path: PathBuf,
+}
+
+impl Secrets {
+    /// Opens the secrets stored next to the Flow configuration.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - The secrets.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration directory can't be determined.
+    pub fn open() -> Result<Self> {
+        confy::change_config_strategy(confy::ConfigStrategy::App);
+        let config_path = confy::get_configuration_file_path("flow", "flow")
+            .into_diagnostic()
+            .context("Failed to locate the Flow configuration")?;
+        let dir = config_path.parent().unwrap_or(Path::new("."));
+        Ok(Self::at(&dir.join(SECRETS_FILE)))
+    }
+
+    /// Opens secrets using the given fallback file.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - Path of the fallback secrets file.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The secrets.
+    pub fn at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Stores a secret, replacing any previous value.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Secrets`) - Secrets to store into.
+    /// - `name` (`&str`) - Name of the secret.
+    /// - `value` (`&str`) - Value of the secret.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<SecretBackend>` - Where the secret was stored.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the secrets file.
+    pub fn set(&self, name: &str, value: &str) -> Result<SecretBackend> {
+        let mut file = self.read()?;
+        file.file.remove(name);
+        file.keychain.remove(name);
+
+        let backend = if keychain::set(name, value) {
+            file.keychain.insert(name.to_string());
+            SecretBackend::Keychain
+        } else {
+            file.file.insert(name.to_string(), value.to_string());
+            SecretBackend::File
+        };
+
+        self.write(&file)?;
+        Ok(backend)
+    }
+
+    /// Reads a secret.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Secrets`) - Secrets to read from.
+    /// - `name` (`&str`) - Name of the secret.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<String>>` - Value of the secret, `None` if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the secrets file.
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        let file = self.read()?;
+        if file.keychain.contains(name) {
+            return Ok(keychain::get(name));
+        }
+        Ok(file.file.get(name).cloned())
+    }
+
+    /// Removes a secret.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Secrets`) - Secrets to remove from.
+    /// - `name` (`&str`) - Name of the secret.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<bool>` - True if the secret existed.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the secrets file.
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let mut file = self.read()?;
+        let in_keychain = file.keychain.remove(name);
+        if in_keychain {
+            keychain::remove(name);
+        }
+        let in_file = file.file.remove(name).is_some();
+
+        if in_keychain || in_file {
+            self.write(&file)?;
+        }
+        Ok(in_keychain || in_file)
+    }
+
+    /// Lists the names of all secrets.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<(String, SecretBackend)>>` - Names and backends, ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the secrets file.
+    pub fn list(&self) -> Result<Vec<(String, SecretBackend)>> {
+        let file = self.read()?;
+        let mut names: Vec<(String, SecretBackend)> = file
+            .keychain
+            .into_iter()
+            .map(|name| (name, SecretBackend::Keychain))
+            .chain(
+                file.file
+                    .into_keys()
+                    .map(|name| (name, SecretBackend::File)),
+            )
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Resolves a config value that may reference a secret.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Secrets`) - Secrets to resolve from.
+    /// - `value` (`&str`) - Config value, `secret:<name>` references a secret.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - The secret's value, or the value itself if it is no reference.
+    ///
+    /// # Errors
+    ///
+    /// The referenced secret doesn't exist.
+    pub fn resolve(&self, value: &str) -> Result<String> {
+        let Some(name) = value.strip_prefix(SECRET_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        self.get(name)?.ok_or_else(|| {
+            miette::miette!(
+                "Secret '{}' is not set, add it with 'flow auth set {}'",
+                name,
+                name
+            )
+        })
+    }
+
+    /// Reads the secrets file, empty if it doesn't exist.
+    ///
+    /// Any other error fails, writing would replace an unreadable file.
+    fn read(&self) -> Result<SecretsFile> {
+        match fs::read_to_string(&self.path) {
+            Ok(toml) => toml::from_str(&toml)
+                .into_diagnostic()
+                .with_context(|| format!("Failed to parse '{}'", self.path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(SecretsFile::default()),
+            Err(err) => Err(err)
+                .into_diagnostic()
+                .with_context(|| format!("Failed to read '{}'", self.path.display())),
+        }
+    }
+
+    /// Writes the secrets file, readable only by the current user.
+    fn write(&self, file: &SecretsFile) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).into_diagnostic()?;
+        }
+        let toml = toml::to_string(file).into_diagnostic()?;
+
+        let tmp_path = self.path.with_extension("toml.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut tmp = options.open(&tmp_path).into_diagnostic()?;
+        tmp.write_all(toml.as_bytes()).into_diagnostic()?;
+        tmp.sync_all().into_diagnostic()?;
+        fs::rename(&tmp_path, &self.path).into_diagnostic()
+    }
+}
+
+/// OS keychain access, reporting unavailability instead of failing.
+#[cfg(feature = "keychain")]
+mod keychain {
+    use super::SERVICE;
+
+    /// Stores a secret, returns false if the keychain is unavailable.
+    pub(super) fn set(name: &str, value: &str) -> bool {
+        keyring::Entry::new(SERVICE, name)
+            .and_then(|entry| entry.set_password(value))
+            .is_ok()
+    }
+
+    /// Reads a secret.
+    pub(super) fn get(name: &str) -> Option<String> {
+        keyring::Entry::new(SERVICE, name)
+            .and_then(|entry| entry.get_password())
+            .ok()
+    }
+
+    /// Removes a secret.
+    pub(super) fn remove(name: &str) {
+        let _ = keyring::Entry::new(SERVICE, name).and_then(|entry| entry.delete_credential());
+    }
+}
+
+/// Stand-in without keychain support, all secrets go to the file.
+#[cfg(not(feature = "keychain"))]
+mod keychain {
+    pub(super) fn set(_name: &str, _value: &str) -> bool {
+        false
+    }
+
+    pub(super) fn get(_name: &str) -> Option<String> {
+        None
+    }
+
+    pub(super) fn remove(_name: &str) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_secrets(name: &str) -> Secrets {
+        let path =
+            std::env::temp_dir().join(format!("flow-secrets-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        let _ = fs::remove_file(&path);
+        Secrets::at(&path)
+    }
+
+    #[test]
+    fn test_set_get_remove() {
+        let secrets = scratch_secrets("roundtrip");
+        let name = format!("test-{}", std::process::id());
+        assert_eq!(secrets.get(&name).unwrap(), None);
+
+        let backend = secrets.set(&name, "hunter2").unwrap();
+        assert_eq!(secrets.get(&name).unwrap().as_deref(), Some("hunter2"));
+        assert_eq!(secrets.list().unwrap(), vec![(name.clone(), backend)]);
+
+        secrets.set(&name, "hunter3").unwrap();
+        assert_eq!(secrets.get(&name).unwrap().as_deref(), Some("hunter3"));
+
+        assert!(secrets.remove(&name).unwrap());
+        assert!(!secrets.remove(&name).unwrap());
+        assert_eq!(secrets.get(&name).unwrap(), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let secrets = scratch_secrets("resolve");
+        let name = format!("test-resolve-{}", std::process::id());
+        assert_eq!(secrets.resolve("plain").unwrap(), "plain");
+        assert!(secrets.resolve(&format!("secret:{}", name)).is_err());
+
+        secrets.set(&name, "hunter2").unwrap();
+        assert_eq!(
+            secrets.resolve(&format!("secret:{}", name)).unwrap(),
+            "hunter2"
+        );
+        secrets.remove(&name).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_file_is_an_error() {
+        let secrets = scratch_secrets("unreadable");
+        // A directory can't be read as file, which isn't the same as no secrets
+        fs::create_dir_all(&secrets.path).unwrap();
+        assert!(secrets.get("any").is_err());
+        assert!(secrets.set("any", "value").is_err());
+
+        fs::remove_dir_all(&secrets.path).unwrap();
+        fs::write(&secrets.path, "file = [").unwrap();
+        assert!(secrets.list().is_err());
+    }
+}
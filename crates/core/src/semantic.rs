@@ -0,0 +1,59 @@
+//! Semantic Search Settings
+//!
+//! Per-space configuration of the embedding backend used by semantic search.
+//! The settings are always part of the space metadata, so builds without the
+//! `semantic` feature keep them intact; the search itself lives in
+//! [`embedding`](crate::embedding) and is only compiled with the feature.
+
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_DIMENSIONS: usize = 256;
+
+/// Backend computing the embeddings of a space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SemanticBackend {
+    /// Local feature hashing of words and word pairs, needs no model.
+    #[default]
+    Hashing,
+    /// OpenAI-compatible `/embeddings` endpoint, e.g. a local Ollama server.
+    Http,
+}
+
+/// Semantic search settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `backend` (`SemanticBackend`) - Backend computing the embeddings.
+/// - `endpoint` (`Option<String>`) - URL of the embeddings endpoint, for the HTTP backend.
+/// - `model` (`Option<String>`) - Model requested from the endpoint, for the HTTP backend.
+/// - `dimensions` (`usize`) - Vector size of the hashing backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticSettings {
+    #[serde(default)]
+    pub backend: SemanticBackend,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default = "default_dimensions")]
+    pub dimensions: usize,
+}
+
+fn default_dimensions() -> usize {
+    DEFAULT_DIMENSIONS
+}
+
+impl Default for SemanticSettings {
+    fn default() -> Self {
+        Self {
+            backend: SemanticBackend::default(),
+            endpoint: None,
+            model: None,
+            dimensions: DEFAULT_DIMENSIONS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {}
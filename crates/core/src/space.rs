@@ -1,14 +1,49 @@
 use chrono::Local;
-use loro::{ExportMode, LoroDoc, UpdateOptions};
-use miette::{IntoDiagnostic, Result};
+use loro::{ExportMode, LoroDoc, LoroValue, UpdateOptions, VersionVector};
+use miette::{Context as _, IntoDiagnostic, Result};
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 
-const FLOW_DIR: &str = ".flow";
-const METADATA_FILE: &str = "space.toml";
-const DOCUMENT_FILE: &str = "space.loro";
-const JOURNAL_DIR: &str = "journal";
+use crate::ai::AiSettings;
+use crate::attribution::Author;
+use crate::audio::AudioSettings;
+use crate::backup::{self, BackupSettings};
+use crate::cancel::CancellationToken;
+use crate::checkpoints::Checkpoint;
+use crate::clock::ClockSettings;
+use crate::compact::CompactSettings;
+use crate::context::{Context, ContextTarget};
+use crate::error::Error;
+use crate::fulltext::TextIndex;
+use crate::index::PageIndex;
+use crate::ingest::IngestSettings;
+use crate::integrations::IntegrationSettings;
+use crate::journal::JournalSettings;
+use crate::migration::{self, CURRENT_FORMAT};
+use crate::ocr::OcrBackend;
+use crate::outline;
+use crate::page;
+use crate::progress::NoProgress;
+use crate::publish::PublishSettings;
+use crate::semantic::SemanticSettings;
+use crate::storage::{FsStorage, MemoryStorage, Storage};
+use crate::sync::SyncSettings;
+use crate::timestamps::{self, META_CONTAINER};
+
+pub(crate) const FLOW_DIR: &str = ".flow";
+pub(crate) const METADATA_FILE: &str = "space.toml";
+pub(crate) const DOCUMENT_FILE: &str = "space.loro";
+pub(crate) const UPDATES_FILE: &str = "space.updates";
+/// Path of spaces created with [`Space::in_memory`].
+pub const MEMORY_PATH: &str = "/memory";
+/// Number of incremental updates appended before a full snapshot is written.
+const CHECKPOINT_INTERVAL: usize = 64;
+pub(crate) const JOURNAL_DIR: &str = "journal";
+/// Name of the map container listing garbage collected pages.
+pub(crate) const TRASH_CONTAINER: &str = "trash";
 
 /// Space metadata.
 ///
@@ -16,10 +51,102 @@ const JOURNAL_DIR: &str = "journal";
 ///
 /// - `name` (`String`) - Name of the space.
 /// - `version` (`String`) - Version the space was created with.
+/// - `format` (`u32`) - On-disk format of the space (see [`migration`](crate::migration)).
+/// - `backups` (`BackupSettings`) - Backup rotation settings.
+/// - `compact` (`CompactSettings`) - History compaction settings.
+/// - `semantic` (`SemanticSettings`) - Semantic search settings.
+/// - `sync` (`SyncSettings`) - Sync remotes.
+/// - `checkpoints` (`Vec<Checkpoint>`) - Named versions of the space.
+/// - `publish` (`PublishSettings`) - Publish targets.
+/// - `clock` (`ClockSettings`) - Timezone and day rollover of the journal.
+/// - `journal` (`JournalSettings`) - Granularity of journal pages.
+/// - `integrations` (`IntegrationSettings`) - Work trackers issues are captured from.
+/// - `ingest` (`IngestSettings`) - Mailbox notes are ingested from.
+/// - `audio` (`AudioSettings`) - Transcription of audio memos.
+/// - `ocr` (`Option<OcrBackend>`) - Text recognition of attached images.
+/// - `ai` (`AiSettings`) - Language model for summaries and questions.
 #[derive(serde::Serialize, serde::Deserialize)]
-struct Metadata {
-    name: String,
-    version: String,
+pub(crate) struct Metadata {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    #[serde(default)]
+    pub(crate) format: u32,
+    #[serde(default)]
+    pub(crate) backups: BackupSettings,
+    #[serde(default)]
+    pub(crate) compact: CompactSettings,
+    #[serde(default)]
+    pub(crate) semantic: SemanticSettings,
+    #[serde(default)]
+    pub(crate) sync: SyncSettings,
+    #[serde(default)]
+    pub(crate) checkpoints: Vec<Checkpoint>,
+    #[serde(default)]
+    pub(crate) publish: PublishSettings,
+    #[serde(default)]
+    pub(crate) clock: ClockSettings,
+    #[serde(default)]
+    pub(crate) journal: JournalSettings,
+    #[serde(default)]
+    pub(crate) integrations: IntegrationSettings,
+    #[serde(default)]
+    pub(crate) ingest: IngestSettings,
+    #[serde(default)]
+    pub(crate) audio: AudioSettings,
+    #[serde(default)]
+    pub(crate) ocr: Option<OcrBackend>,
+    #[serde(default)]
+    pub(crate) ai: AiSettings,
+}
+
+impl Metadata {
+    /// Reads the metadata of the space at the given path.
+    ///
+    /// # Arguments
+    ///
+    /// - `storage` (`&dyn Storage`) - Storage of the space.
+    /// - `path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - Metadata of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the file or TOML errors when parsing it.
+    pub(crate) fn read(storage: &dyn Storage, path: &Path) -> Result<Self> {
+        let metadata_path = path.join(FLOW_DIR).join(METADATA_FILE);
+        let metadata_toml =
+            storage
+                .read_to_string(&metadata_path)?
+                .ok_or_else(|| Error::MetadataMissing {
+                    path: path.to_path_buf(),
+                })?;
+        toml::from_str(&metadata_toml).map_err(|err| {
+            Error::Parse {
+                path: metadata_path,
+                message: err.message().to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Writes the metadata of the space at the given path.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Metadata`) - Metadata to write.
+    /// - `storage` (`&dyn Storage`) - Storage of the space.
+    /// - `path` (`&Path`) - Path of the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the file or TOML errors when serializing it.
+    pub(crate) fn write(&self, storage: &dyn Storage, path: &Path) -> Result<()> {
+        let metadata_path = path.join(FLOW_DIR).join(METADATA_FILE);
+        let metadata_toml = toml::to_string_pretty(self).into_diagnostic()?;
+        storage.write(&metadata_path, metadata_toml.as_bytes())
+    }
 }
 
 /// Space.
@@ -28,20 +155,61 @@ struct Metadata {
 ///
 /// - `path` (`PathBuf`) - Path of the space.
 /// - `metadata` (`Metadata`) - Metadata of the space.
+/// - `document` (`LoroDoc`) - CRDT document holding all pages.
+/// - `dirty` (`HashSet<String>`) - Pages modified since the last save.
+/// - `deleted` (`HashSet<String>`) - Pages deleted since the last save, their files are removed when saving.
+/// - `saved` (`VersionVector`) - Document version at the last save.
+/// - `pending` (`usize`) - Updates appended to the update log since the last snapshot.
+/// - `author` (`Author`) - Identity changes are attributed to.
+/// - `storage` (`Arc<dyn Storage>`) - Where the files of the space are kept.
+/// - `allow_locked` (`bool`) - Whether locked pages may be written (see [`Space::set_locked`]).
+/// - `read_only` (`bool`) - Whether the space was opened with [`Space::load_readonly`].
+/// - `transaction` (`usize`) - Depth of the running transactions, saves are deferred above zero (see [`Space::transaction`]).
 pub struct Space {
-    path: PathBuf,
-    metadata: Metadata,
-    document: LoroDoc,
-    dirty: HashSet<String>,
+    pub(crate) path: PathBuf,
+    pub(crate) metadata: Metadata,
+    pub(crate) document: LoroDoc,
+    pub(crate) dirty: HashSet<String>,
+    pub(crate) deleted: HashSet<String>,
+    pub(crate) saved: VersionVector,
+    pub(crate) pending: usize,
+    pub(crate) author: Author,
+    pub(crate) storage: Arc<dyn Storage>,
+    pub(crate) allow_locked: bool,
+    pub(crate) read_only: bool,
+    pub(crate) transaction: usize,
 }
 
 impl Space {
     /// Initializes a new space given a path and a optional name.
     ///
+    /// Missing parent directories of `path` are created.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - Path to create the space in.
+    /// - `name` (`Option<&String>`) - Optional name of the space (if none is provided it will fallback to the path's basename).
+    /// - `bare` (`bool`) - Skip scaffolding the journal directory.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - Initialized space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when creating directories or writing files.
+    pub fn init(path: &Path, name: Option<&String>, bare: bool) -> Result<Self> {
+        Self::init_in(Arc::new(FsStorage), path, name, bare)
+    }
+
+    /// Initializes a new space in the given storage.
+    ///
     /// # Arguments
     ///
+    /// - `storage` (`Arc<dyn Storage>`) - Storage to keep the files of the space in.
     /// - `path` (`&Path`) - Path to create the space in.
     /// - `name` (`Option<&String>`) - Optional name of the space (if none is provided it will fallback to the path's basename).
+    /// - `bare` (`bool`) - Skip scaffolding the journal directory.
     ///
     /// # Returns
     ///
@@ -50,16 +218,31 @@ impl Space {
     /// # Errors
     ///
     /// IO errors when creating directories or writing files.
-    pub fn init(path: &Path, name: Option<&String>) -> Result<Self> {
+    pub fn init_in(
+        storage: Arc<dyn Storage>,
+        path: &Path,
+        name: Option<&String>,
+        bare: bool,
+    ) -> Result<Self> {
         let flow_dir = path.join(FLOW_DIR);
-        fs::create_dir_all(&flow_dir).into_diagnostic()?;
+        storage
+            .create_dir_all(&flow_dir)
+            .with_context(|| format!("Failed to create space directory '{}'", path.display()))?;
 
-        // Create journal directory
-        let journal_dir = path.join(JOURNAL_DIR);
-        fs::create_dir_all(&journal_dir).into_diagnostic()?;
+        if !bare {
+            let journal_dir = path.join(JOURNAL_DIR);
+            storage.create_dir_all(&journal_dir)?;
+        }
 
+        // Resolve `.` and friends so the default name is the directory's real name
+        let canonical = if storage.is_native() {
+            path.canonicalize().into_diagnostic()?
+        } else {
+            path.to_path_buf()
+        };
         let space_name = name.map(|s| s.to_string()).unwrap_or_else(|| {
-            path.file_name()
+            canonical
+                .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("flow-space") // TODO: Find a better default name or generate one
                 .to_string()
@@ -67,27 +250,72 @@ impl Space {
         let metadata = Metadata {
             name: space_name,
             version: env!("CARGO_PKG_VERSION").to_string(),
+            format: CURRENT_FORMAT,
+            backups: BackupSettings::default(),
+            compact: CompactSettings::default(),
+            semantic: SemanticSettings::default(),
+            sync: SyncSettings::default(),
+            checkpoints: Vec::new(),
+            publish: PublishSettings::default(),
+            clock: ClockSettings::default(),
+            journal: JournalSettings::default(),
+            integrations: IntegrationSettings::default(),
+            ingest: IngestSettings::default(),
+            audio: AudioSettings::default(),
+            ocr: None,
+            ai: AiSettings::default(),
         };
 
-        let metadata_path = flow_dir.join(METADATA_FILE);
-        let metadata_json = toml::to_string_pretty(&metadata).into_diagnostic()?;
-        fs::write(metadata_path, metadata_json).into_diagnostic()?;
+        metadata.write(&*storage, path)?;
 
         let doc = LoroDoc::new();
-        let doc_path = flow_dir.join(DOCUMENT_FILE);
+        doc.set_record_timestamp(true);
         let snapshot = doc.export(ExportMode::Snapshot).into_diagnostic()?;
-        fs::write(doc_path, snapshot).into_diagnostic()?;
+        write_snapshot(&*storage, path, &snapshot)?;
 
         Ok(Space {
             path: path.to_path_buf(),
             metadata: metadata,
+            saved: doc.oplog_vv(),
             document: doc,
             dirty: HashSet::new(),
+            deleted: HashSet::new(),
+            pending: 0,
+            author: Author::default(),
+            storage,
+            allow_locked: false,
+            read_only: false,
+            transaction: 0,
         })
     }
 
+    /// Creates a space that only lives in memory.
+    ///
+    /// The space keeps its files in a [`MemoryStorage`] and never touches the
+    /// filesystem, e.g. for tests or throwaway scratch captures. Its files can
+    /// be persisted by initializing it with [`Space::init_in`] on a storage
+    /// the caller keeps instead.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - Empty space named after [`MEMORY_PATH`].
+    ///
+    /// # Errors
+    ///
+    /// Only fails if the document can't be exported.
+    pub fn in_memory() -> Result<Self> {
+        Self::init_in(
+            Arc::new(MemoryStorage::new()),
+            Path::new(MEMORY_PATH),
+            None,
+            false,
+        )
+    }
+
     /// Loads a space given a path.
     ///
+    /// Spaces in an older on-disk format are migrated before loading.
+    ///
     /// # Arguments
     ///
     /// - `path` (`&Path`) - Path of the space to load.
@@ -100,26 +328,99 @@ impl Space {
     ///
     /// IO errors when creating directories or writing files.
     pub fn load(path: &Path) -> Result<Self> {
-        let flow_dir = path.join(FLOW_DIR);
-        let metadata_path = flow_dir.join(METADATA_FILE);
+        Self::load_from(Arc::new(FsStorage), path)
+    }
 
-        let metadata_json = std::fs::read_to_string(metadata_path).into_diagnostic()?;
-        let metadata: Metadata = toml::from_str(&metadata_json).into_diagnostic()?;
+    /// Loads a space from the given storage.
+    ///
+    /// Spaces in native storage in an older on-disk format are migrated before loading.
+    ///
+    /// # Arguments
+    ///
+    /// - `storage` (`Arc<dyn Storage>`) - Storage the files of the space are kept in.
+    /// - `path` (`&Path`) - Path of the space to load.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - Loaded space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading files or import errors for corrupt data.
+    pub fn load_from(storage: Arc<dyn Storage>, path: &Path) -> Result<Self> {
+        if storage.is_native() {
+            migration::migrate(path)?;
+        }
+        let metadata = Metadata::read(&*storage, path)?;
 
         let doc = LoroDoc::new();
-        let doc_path = flow_dir.join(DOCUMENT_FILE);
-        if doc_path.exists() {
-            let doc_content = std::fs::read(doc_path).into_diagnostic()?;
-            doc.import(&doc_content).into_diagnostic()?;
-        }
+        doc.set_record_timestamp(true);
+        let pending = read_document(&*storage, path, &doc)?;
 
         // TODO: Load and index all markdown files in the space directory.
 
         Ok(Space {
             path: path.to_path_buf(),
             metadata: metadata,
+            saved: doc.oplog_vv(),
             document: doc,
             dirty: HashSet::new(),
+            deleted: HashSet::new(),
+            pending,
+            author: Author::default(),
+            storage,
+            allow_locked: false,
+            read_only: false,
+            transaction: 0,
+        })
+    }
+
+    /// Loads a space for reading only.
+    ///
+    /// Nothing is written to the space: older formats aren't migrated, saving
+    /// fails and missing indexes are rebuilt in memory without persisting
+    /// them. Only the statistics cache is written, if possible (see
+    /// [`Space::stats`]). Safe on read-only filesystems and network mounts, and alongside
+    /// other processes writing the space.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - Path of the space to load.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Self>` - Loaded space.
+    ///
+    /// # Errors
+    ///
+    /// The space needs a migration, IO errors when reading files or import
+    /// errors for corrupt data.
+    pub fn load_readonly(path: &Path) -> Result<Self> {
+        if !migration::pending(path)?.is_empty() {
+            miette::bail!(
+                "Space at '{}' uses an older format, run `flow migrate` before opening it read-only",
+                path.display()
+            );
+        }
+        let metadata = Metadata::read(&FsStorage, path)?;
+
+        let doc = LoroDoc::new();
+        doc.set_record_timestamp(true);
+        let pending = read_document(&FsStorage, path, &doc)?;
+
+        Ok(Space {
+            path: path.to_path_buf(),
+            metadata,
+            saved: doc.oplog_vv(),
+            document: doc,
+            dirty: HashSet::new(),
+            deleted: HashSet::new(),
+            pending,
+            author: Author::default(),
+            storage: Arc::new(FsStorage),
+            allow_locked: false,
+            read_only: true,
+            transaction: 0,
         })
     }
 
@@ -136,8 +437,34 @@ impl Space {
         path.join(".flow").exists()
     }
 
+    /// Finds a space enclosing the given path.
+    ///
+    /// Walks up the ancestors of `path` (excluding `path` itself) looking for a
+    /// `.flow` directory. Used to prevent initializing nested spaces.
+    ///
+    /// # Arguments
+    ///
+    /// - `path` (`&Path`) - Path to start searching from (does not need to exist).
+    ///
+    /// # Returns
+    ///
+    /// - `Option<PathBuf>` - Root path of the enclosing space, if any.
+    pub fn enclosing(path: &Path) -> Option<PathBuf> {
+        let absolute = std::path::absolute(path).ok()?;
+        absolute
+            .ancestors()
+            .skip(1)
+            .find(|ancestor| Space::exists(ancestor))
+            .map(Path::to_path_buf)
+    }
+
     /// Adds a node to the todays page.
     ///
+    /// The capture context (see [`context`](crate::context)) can tag the node
+    /// or route it below a running meeting, to another page or below a heading.
+    /// Which page is today's follows the space clock (see [`clock`](crate::clock)).
+    /// The first capture of a day starts it (see [`Space::today_page`]).
+    ///
     /// # Arguments
     ///
     /// - `&mut self` (`Space`) - Space to add the node to todays page to.
@@ -145,33 +472,305 @@ impl Space {
     ///
     /// # Errors
     ///
-    /// IO errors when creating directories or writing files.
+    /// The target page is locked, or IO errors when creating directories or writing files.
     pub fn add(&mut self, content: &str) -> Result<()> {
-        let journal_path = self.path.join(JOURNAL_DIR);
-        fs::create_dir_all(&journal_path).into_diagnostic()?;
+        self.add_blocks(vec![(0, content.to_string())])
+    }
 
-        let today = Local::now().format("%Y-%m-%d").to_string();
-        let daily_path = journal_path.join(format!("{}.md", today));
-        let id = format!("{}/{}.md", JOURNAL_DIR, today);
-        let text = self.document.get_text(id.clone());
+    /// Adds every non-empty line of a text as its own node, like [`Space::add`].
+    ///
+    /// Indented lines become children of the line above, a leading `- ` is
+    /// dropped. All nodes are added in a single change and saved once.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to add the nodes to.
+    /// - `text` (`&str`) - Lines to add, e.g. the content of a file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<usize>` - Number of added nodes.
+    ///
+    /// # Errors
+    ///
+    /// The target page is locked, or IO errors when creating directories or writing files.
+    pub fn add_all(&mut self, text: &str) -> Result<usize> {
+        let blocks = outline::nest(text);
+        let count = blocks.len();
+        if count > 0 {
+            self.add_blocks(blocks)?;
+        }
+        Ok(count)
+    }
 
-        if daily_path.exists() {
-            let existing = fs::read_to_string(&daily_path).into_diagnostic()?;
+    /// Adds nodes with their nesting depth where the capture context routes them.
+    fn add_blocks(&mut self, blocks: Vec<(usize, String)>) -> Result<()> {
+        let context = Context::load(&self.path);
+        let tag = match &context.target {
+            Some(ContextTarget::Tag(tag)) if tag.contains(char::is_whitespace) => {
+                Some(format!("#[[{}]]", tag))
+            }
+            Some(ContextTarget::Tag(tag)) => Some(format!("#{}", tag)),
+            _ => None,
+        };
+        // Children are tagged along with their top-level node
+        let blocks: Vec<(usize, String)> = blocks
+            .into_iter()
+            .map(|(depth, content)| match &tag {
+                Some(tag) if depth == 0 => (depth, format!("{} {}", content, tag)),
+                _ => (depth, content),
+            })
+            .collect();
+        let lines = |base: usize| -> Vec<String> {
+            blocks
+                .iter()
+                .map(|(depth, content)| format!("{}- {}", "  ".repeat(base + depth), content))
+                .collect()
+        };
+
+        let today_id = self.today_page()?;
+        match (context.meeting, context.target) {
+            (Some(meeting), _) => self.append(&meeting.page, &lines(1).join("\n")),
+            (None, Some(ContextTarget::Page(name))) => {
+                let id = self.page_index()?.resolve(&name);
+                self.append(&id, &lines(0).join("\n"))
+            }
+            (None, Some(ContextTarget::Heading(heading))) => {
+                // Below the heading, relative to the first node
+                let node = lines(1).join("\n");
+                let node = node.strip_prefix("  - ").unwrap_or(&node);
+                self.add_below_heading(&today_id, &heading, node)
+            }
+            _ => self.append(&today_id, &lines(0).join("\n")),
+        }
+    }
+
+    /// Appends lines to the end of a page and saves the space.
+    ///
+    /// Changes made to the markdown file outside of Flow are taken over first.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `lines` (`&str`) - Lines to append, without a leading line break.
+    ///
+    /// # Errors
+    ///
+    /// The page is locked, or IO errors when creating directories or writing files.
+    pub(crate) fn append(&mut self, id: &str, lines: &str) -> Result<()> {
+        self.ensure_writable(id)?;
+        let text = self.document.get_text(id);
+        // Unsaved changes of a transaction are newer than the file
+        let unsaved = self.dirty.contains(id);
+        if let Some(existing) = self
+            .storage
+            .read_to_string(&self.path.join(id))?
+            .filter(|_| !unsaved)
+        {
             text.update(&existing, UpdateOptions::default())
                 .into_diagnostic()?;
         }
 
-        // TODO: Check content to add for multi lines. Currently we assume that it's a single line.
-        text.push_str(&format!("\n- {}", content))
-            .into_diagnostic()?;
+        text.push_str(&format!("\n{}", lines)).into_diagnostic()?;
+
+        self.dirty.insert(id.to_string());
+        self.save()
+    }
+
+    /// Returns the ids of all pages in the space.
+    ///
+    /// Includes markdown files on disk as well as pages only known to the
+    /// document.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<String>>` - Sorted page ids.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading directories.
+    pub fn page_ids(&self) -> Result<Vec<String>> {
+        let mut ids = markdown_files(&*self.storage, &self.path)?;
+        for id in container_names(&self.document) {
+            if id.ends_with(".md") && !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Returns the id of a page with the given name that doesn't exist yet.
+    ///
+    /// Taken names get a counter appended, e.g. `Name (2)`.
+    ///
+    /// # Arguments
+    ///
+    /// - `name` (`&str`) - Preferred name of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - Id of the unused page.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading directories.
+    pub(crate) fn unused_page_id(&self, name: &str) -> Result<String> {
+        let existing = self.page_ids()?;
+        let mut id = page::id_from_name(name);
+        let mut counter = 1;
+        while existing.contains(&id) {
+            counter += 1;
+            id = page::id_from_name(&format!("{} ({})", name, counter));
+        }
+        Ok(id)
+    }
+
+    /// Saves a file below a directory of the space without overwriting others.
+    ///
+    /// Taken names get a counter appended, e.g. `scan-2.png`.
+    ///
+    /// # Arguments
+    ///
+    /// - `dir` (`&str`) - Directory relative to the space, e.g. `assets/mail`.
+    /// - `name` (`&str`) - Preferred file name, cleaned with [`page::file_name`].
+    /// - `content` (`&[u8]`) - Content of the file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<String>` - Path of the saved file, relative to the space.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when creating directories or writing files.
+    pub(crate) fn save_asset(&self, dir: &str, name: &str, content: &[u8]) -> Result<String> {
+        let name = match page::file_name(name, '-') {
+            name if name.is_empty() => "attachment".to_string(),
+            name => name,
+        };
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (name.as_str(), String::new()),
+        };
+
+        let mut relative = format!("{}/{}", dir, name);
+        let mut counter = 1;
+        while self.storage.exists(&self.path.join(&relative)) {
+            counter += 1;
+            relative = format!("{}/{}-{}{}", dir, stem, counter, extension);
+        }
+        self.storage.write(&self.path.join(&relative), content)?;
+        Ok(relative)
+    }
+
+    /// Reads the content of a page.
+    ///
+    /// Pages tracked by the document are read from it, other pages from disk.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` (`&str`) - Id of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<String>>` - Content of the page, `None` if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the markdown file.
+    pub fn read_page(&self, id: &str) -> Result<Option<String>> {
+        if container_names(&self.document)
+            .iter()
+            .any(|name| name == id)
+        {
+            return Ok(Some(self.document.get_text(id).to_string()));
+        }
 
-        self.dirty.insert(id);
-        self.save()?;
+        self.storage.read_to_string(&self.path.join(id))
+    }
+
+    /// Replaces the content of a page and saves the space.
+    ///
+    /// Creates the page if it doesn't exist yet.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `content` (`&str`) - New markdown content of the page.
+    ///
+    /// # Errors
+    ///
+    /// The page is locked, or IO errors when creating directories or writing files.
+    pub fn write_page(&mut self, id: &str, content: &str) -> Result<()> {
+        self.ensure_writable(id)?;
+        self.document
+            .get_text(id)
+            .update(content, UpdateOptions::default())
+            .into_diagnostic()?;
+        self.dirty.insert(id.to_string());
+        self.save()
+    }
 
+    /// Replaces the content of a page without saving the space.
+    ///
+    /// For changes spanning several pages, which are saved at once.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `content` (`&str`) - New markdown content of the page.
+    ///
+    /// # Errors
+    ///
+    /// The id leaves the space, or Loro errors when updating the page's container.
+    pub(crate) fn update_page(&mut self, id: &str, content: &str) -> Result<()> {
+        page::check_id(id)?;
+        self.document
+            .get_text(id)
+            .update(content, UpdateOptions::default())
+            .into_diagnostic()?;
+        self.dirty.insert(id.to_string());
         Ok(())
     }
 
-    /// Saves the space to disk.
+    /// Deletes a page and saves the space.
+    ///
+    /// The page's container is emptied and listed in the trash container, as
+    /// garbage collection does for pages deleted outside of Flow. Its file is
+    /// removed by the save, so within a transaction only once it ends.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    ///
+    /// # Errors
+    ///
+    /// The page is locked, Loro errors when updating containers, or IO errors
+    /// when removing or writing files.
+    pub(crate) fn delete_page(&mut self, id: &str) -> Result<()> {
+        self.ensure_writable(id)?;
+        let text = self.document.get_text(id);
+        text.delete(0, text.len_unicode()).into_diagnostic()?;
+        self.document
+            .get_map(TRASH_CONTAINER)
+            .insert(id, Local::now().timestamp())
+            .into_diagnostic()?;
+        self.document
+            .get_map(META_CONTAINER)
+            .delete(id)
+            .into_diagnostic()?;
+        self.dirty.remove(id);
+        self.deleted.insert(id.to_string());
+        self.save()
+    }
+
+    /// Saves the space to disk, recording the modification of all dirty pages.
+    ///
+    /// Within a transaction the save is deferred until it ends.
     ///
     /// # Arguments
     ///
@@ -179,31 +778,163 @@ impl Space {
     ///
     /// # Errors
     ///
-    /// IO errors when writing files.
-    fn save(&mut self) -> Result<()> {
-        let flow_dir = self.path.join(FLOW_DIR);
+    /// IO errors when writing files, or the space uses a newer format than supported.
+    pub(crate) fn save(&mut self) -> Result<()> {
+        if self.transaction > 0 {
+            return Ok(());
+        }
+        let now = Local::now().timestamp();
+        for id in &self.dirty {
+            timestamps::touch(&self.document, id, now, now)?;
+        }
+        self.document
+            .set_next_commit_message(&self.author.to_message());
+        self.persist()
+    }
 
-        let metadata_path = flow_dir.join(METADATA_FILE);
-        let metadata_json = toml::to_string_pretty(&self.metadata).into_diagnostic()?;
-        fs::write(metadata_path, metadata_json).into_diagnostic()?;
+    /// Writes the document, the dirty pages and the indexes to disk, and
+    /// removes the files of deleted pages.
+    ///
+    /// Unlike [`Space::save`], page times are left as they are, e.g. for
+    /// changes merged from another device which carry their own times.
+    ///
+    /// Changes since the last save are appended to the update log. Every
+    /// [`CHECKPOINT_INTERVAL`] saves a full snapshot is written instead (after
+    /// backing up the previous one) and the log is cleared, so a single save
+    /// never pays for exporting the whole document. Updates appended by other
+    /// processes are imported first, so the snapshot never drops them.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to save.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing files, or the space uses a newer format than supported.
+    pub(crate) fn persist(&mut self) -> Result<()> {
+        if self.read_only {
+            miette::bail!("Space '{}' was opened read-only", self.metadata.name);
+        }
+        if self.metadata.format > CURRENT_FORMAT {
+            return Err(Error::VersionMismatch {
+                found: self.metadata.format,
+                supported: CURRENT_FORMAT,
+            }
+            .into());
+        }
 
-        let doc_path = flow_dir.join(DOCUMENT_FILE);
-        let snapshot = self
-            .document
-            .export(ExportMode::Snapshot)
-            .into_diagnostic()?;
-        fs::write(doc_path, snapshot).into_diagnostic()?;
+        let storage = &*self.storage;
+        let doc_path = self.path.join(FLOW_DIR).join(DOCUMENT_FILE);
+        if self.pending < CHECKPOINT_INTERVAL && storage.exists(&doc_path) {
+            let update = self
+                .document
+                .export(ExportMode::updates(&self.saved))
+                .into_diagnostic()?;
+            append_update(storage, &self.path, &update)?;
+            self.pending += 1;
+        } else {
+            // Other processes may have appended to the log since this space
+            // was loaded, which the snapshot replaces
+            read_document(storage, &self.path, &self.document)?;
+            self.metadata.write(storage, &self.path)?;
+            if storage.is_native() {
+                backup::create(&self.path, &self.metadata.backups)?;
+            }
+
+            let snapshot = self
+                .document
+                .export(ExportMode::Snapshot)
+                .into_diagnostic()?;
+            write_snapshot(storage, &self.path, &snapshot)?;
+            self.pending = 0;
+        }
+        self.saved = self.document.oplog_vv();
 
+        let mut index = self.page_index()?;
+        let mut text_index = self.text_index()?;
+        // A page deleted and written again in one transaction keeps its file
+        for id in self.deleted.difference(&self.dirty) {
+            storage.remove(&self.path.join(id))?;
+            index.remove(id);
+            text_index.remove(id);
+        }
         for id in &self.dirty {
             let file_path = self.path.join(id);
-            let text = self.document.get_text(id.to_string());
-            fs::write(&file_path, text.to_string()).into_diagnostic()?;
+            let content = self.document.get_text(id.to_string()).to_string();
+            storage.write(&file_path, content.as_bytes())?;
+            index.update(
+                id,
+                &content,
+                timestamps::get(&self.document, id).unwrap_or_default(),
+            );
+            text_index.update(id, &content);
         }
+        index.save_to(storage, &self.path)?;
+        text_index.save_to(storage, &self.path)?;
         self.dirty.clear();
+        self.deleted.clear();
 
         Ok(())
     }
 
+    /// Loads the page index of the space from its storage.
+    ///
+    /// Outside of native storage a missing index is rebuilt from the
+    /// document, which holds every page written through Flow. A read-only
+    /// space rebuilds a missing index without persisting it.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<PageIndex>` - The page index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index has to be rebuilt and files can't be read or written.
+    pub fn page_index(&self) -> Result<PageIndex> {
+        if self.read_only {
+            return PageIndex::cached(&self.path)
+                .map(Ok)
+                .unwrap_or_else(|| PageIndex::rebuild(&self.path));
+        }
+        if self.storage.is_native() {
+            return PageIndex::load(&self.path);
+        }
+        PageIndex::load_from(&*self.storage, &self.path, &self.document)
+    }
+
+    /// Loads the full-text index of the space from its storage.
+    ///
+    /// Outside of native storage a missing index is rebuilt from the document.
+    /// A read-only space rebuilds a missing index without persisting it.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<TextIndex>` - The full-text index.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index has to be rebuilt and files can't be read or written.
+    pub fn text_index(&self) -> Result<TextIndex> {
+        if self.read_only {
+            return TextIndex::cached(&self.path).map(Ok).unwrap_or_else(|| {
+                TextIndex::rebuild(&self.path, &NoProgress, &CancellationToken::new())
+            });
+        }
+        if self.storage.is_native() {
+            return TextIndex::load(&self.path);
+        }
+        TextIndex::load_from(&*self.storage, &self.path, &self.document)
+    }
+
+    /// Checks whether the space was opened read-only.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the space was loaded with [`Space::load_readonly`].
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Returns the path of the space.
     ///
     /// # Returns
@@ -221,7 +952,291 @@ impl Space {
     pub fn name(&self) -> &str {
         &self.metadata.name
     }
+
+    /// Returns the backup settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&BackupSettings` - Reference to the space's backup settings.
+    pub fn backup_settings(&self) -> &BackupSettings {
+        &self.metadata.backups
+    }
+}
+
+/// Imports the snapshot and the update log of a space into a document.
+///
+/// # Arguments
+///
+/// - `storage` (`&dyn Storage`) - Storage of the space.
+/// - `path` (`&Path`) - Path of the space.
+/// - `doc` (`&LoroDoc`) - Document to import into.
+///
+/// # Returns
+///
+/// - `Result<usize>` - Number of updates imported from the update log.
+///
+/// # Errors
+///
+/// IO errors when reading the files or import errors for corrupt data.
+pub(crate) fn read_document(storage: &dyn Storage, path: &Path, doc: &LoroDoc) -> Result<usize> {
+    let doc_path = path.join(FLOW_DIR).join(DOCUMENT_FILE);
+    let corrupt = |err: loro::LoroError| -> miette::Report {
+        Error::Crdt {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        }
+        .into()
+    };
+    if let Some(snapshot) = storage.read(&doc_path)? {
+        doc.import(&snapshot).map_err(corrupt)?;
+    }
+
+    let updates = read_updates(storage, path)?;
+    for update in &updates {
+        doc.import(update).map_err(corrupt)?;
+    }
+    Ok(updates.len())
+}
+
+/// Writes the snapshot of a space and clears its update log.
+///
+/// # Arguments
+///
+/// - `storage` (`&dyn Storage`) - Storage of the space.
+/// - `path` (`&Path`) - Path of the space.
+/// - `snapshot` (`&[u8]`) - Exported snapshot.
+///
+/// # Errors
+///
+/// IO errors when writing the snapshot or removing the update log.
+pub(crate) fn write_snapshot(storage: &dyn Storage, path: &Path, snapshot: &[u8]) -> Result<()> {
+    let flow_dir = path.join(FLOW_DIR);
+    storage.write(&flow_dir.join(DOCUMENT_FILE), snapshot)?;
+    storage.remove(&flow_dir.join(UPDATES_FILE))
+}
+
+/// Returns the sizes and modification times of the snapshot and update log
+/// of a space, to notice saves by other processes.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Vec<Option<(u64, SystemTime)>>` - State of each file, `None` if it doesn't exist.
+pub fn disk_state(path: &Path) -> Vec<Option<(u64, SystemTime)>> {
+    [DOCUMENT_FILE, UPDATES_FILE]
+        .iter()
+        .map(|file| {
+            fs::metadata(path.join(FLOW_DIR).join(file))
+                .and_then(|metadata| Ok((metadata.len(), metadata.modified()?)))
+                .ok()
+        })
+        .collect()
+}
+
+/// Writes a file atomically.
+///
+/// The content is written to a temporary file next to the target, which is
+/// then renamed over it. Readers (and interrupted writers) never observe a
+/// partially written file.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the file to write.
+/// - `content` (`&[u8]`) - Content to write.
+///
+/// # Errors
+///
+/// IO errors when writing or renaming the file.
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    fs::write(&temp_path, content).into_diagnostic()?;
+    fs::rename(&temp_path, path).into_diagnostic()
+}
+
+/// Reads all updates from the update log of a space.
+///
+/// Each update is stored with a little-endian `u32` length prefix. A
+/// truncated trailing update (e.g. from a crash mid-write) is ignored.
+///
+/// # Arguments
+///
+/// - `storage` (`&dyn Storage`) - Storage of the space.
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<Vec<Vec<u8>>>` - Updates in the order they were appended.
+///
+/// # Errors
+///
+/// IO errors when reading the update log.
+pub(crate) fn read_updates(storage: &dyn Storage, path: &Path) -> Result<Vec<Vec<u8>>> {
+    let updates_path = path.join(FLOW_DIR).join(UPDATES_FILE);
+    let Some(bytes) = storage.read(&updates_path)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut updates = Vec::new();
+    let mut rest = bytes.as_slice();
+    while let Some((length, tail)) = rest.split_first_chunk::<4>() {
+        let length = u32::from_le_bytes(*length) as usize;
+        if tail.len() < length {
+            break;
+        }
+        updates.push(tail[..length].to_vec());
+        rest = &tail[length..];
+    }
+    Ok(updates)
+}
+
+/// Appends an update to the update log of a space.
+fn append_update(storage: &dyn Storage, path: &Path, update: &[u8]) -> Result<()> {
+    let updates_path = path.join(FLOW_DIR).join(UPDATES_FILE);
+    let mut record = Vec::with_capacity(update.len() + 4);
+    record.extend_from_slice(&(update.len() as u32).to_le_bytes());
+    record.extend_from_slice(update);
+    storage.append(&updates_path, &record)
+}
+
+/// Lists the names of all root text containers in a document.
+///
+/// Containers emptied by garbage collection (see [`compact`](crate::compact))
+/// are left out until a page with their name is written again.
+///
+/// # Arguments
+///
+/// - `doc` (`&LoroDoc`) - Document to list the containers of.
+///
+/// # Returns
+///
+/// - `Vec<String>` - Sorted container names (relative markdown paths), without
+///   the timestamp and trash containers.
+pub(crate) fn container_names(doc: &LoroDoc) -> Vec<String> {
+    let LoroValue::Map(map) = doc.get_value() else {
+        return Vec::new();
+    };
+    let trashed = |name: &str| match map.get(TRASH_CONTAINER) {
+        Some(LoroValue::Map(trash)) => trash.contains_key(name),
+        _ => false,
+    };
+
+    let mut names: Vec<String> = map
+        .iter()
+        .filter(|(name, _)| name.as_str() != META_CONTAINER && name.as_str() != TRASH_CONTAINER)
+        .filter(|(name, value)| {
+            !(trashed(name) && matches!(value, LoroValue::String(text) if text.is_empty()))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Recursively collects all markdown files of a space.
+///
+/// Hidden directories (including `.flow`) are skipped. Paths are returned
+/// relative to the space root using `/` as separator, which matches the
+/// container naming in the document.
+///
+/// # Arguments
+///
+/// - `storage` (`&dyn Storage`) - Storage of the space.
+/// - `root` (`&Path`) - Root path of the space.
+///
+/// # Returns
+///
+/// - `Result<Vec<String>>` - Sorted relative paths of all markdown files.
+///
+/// # Errors
+///
+/// IO errors when reading directories.
+pub(crate) fn markdown_files(storage: &dyn Storage, root: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in storage.list(&dir)? {
+            let path = entry.path;
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+
+            if entry.dir {
+                if !hidden {
+                    pending.push(path);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "md") {
+                let relative = path.strip_prefix(root).into_diagnostic()?;
+                let id = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push(id);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory() {
+        let mut space = Space::in_memory().unwrap();
+        space.add("Scratch note").unwrap();
+
+        let id = space.journal_page(space.today());
+        assert_eq!(space.name(), "memory");
+        assert!(space
+            .read_page(&id)
+            .unwrap()
+            .is_some_and(|content| content.contains("- Scratch note")));
+        assert_eq!(space.page_ids().unwrap(), vec![id]);
+    }
+
+    #[test]
+    fn test_checkpoint_keeps_concurrent_updates() {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let path = Path::new("/space");
+        let mut first = Space::init_in(storage.clone(), path, None, true).unwrap();
+        let mut second = Space::load_from(storage.clone(), path).unwrap();
+
+        for i in 0..CHECKPOINT_INTERVAL + 2 {
+            first
+                .write_page(&format!("first-{i}.md"), "- First")
+                .unwrap();
+            second
+                .write_page(&format!("second-{i}.md"), "- Second")
+                .unwrap();
+        }
+
+        let space = Space::load_from(storage, path).unwrap();
+        for i in 0..CHECKPOINT_INTERVAL + 2 {
+            assert_eq!(
+                space
+                    .read_page(&format!("first-{i}.md"))
+                    .unwrap()
+                    .as_deref(),
+                Some("- First")
+            );
+            assert_eq!(
+                space
+                    .read_page(&format!("second-{i}.md"))
+                    .unwrap()
+                    .as_deref(),
+                Some("- Second")
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Page Splitting
+//!
+//! Breaks up an overgrown page along its top-level headings. Every section
+//! starting at a heading of the highest level found at the top level of the
+//! page, either a markdown heading (`## Reading`) or a heading block
+//! (`- ## Reading`), becomes a page named after the heading. Children of a
+//! heading block become the top-level blocks of the new page.
+//!
+//! The sections are replaced with links to the new pages, or embeds to keep
+//! the original page readable as before:
+//!
+//! ```markdown
+//! - Notes collected while planning the garden
+//! - ![[Seeds]]
+//! - ![[Watering]]
+//! ```
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::page;
+use crate::space::Space;
+
+/// A page split into pages by its headings.
+///
+/// # Fields
+///
+/// - `page` (`String`) - Id of the split page.
+/// - `pages` (`Vec<String>`) - Ids of the pages created from its sections.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SplitPage {
+    pub page: String,
+    pub pages: Vec<String>,
+}
+
+/// A section of a page starting at a top-level heading.
+struct Section {
+    title: String,
+    start: usize,
+    end: usize,
+    block: bool,
+}
+
+impl Space {
+    /// Splits a page into one page per top-level heading.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `embed` (`bool`) - Whether sections are replaced with embeds instead of links.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<SplitPage>` - The split page and the created pages.
+    ///
+    /// # Errors
+    ///
+    /// The page doesn't exist, has no headings or is locked, or IO errors
+    /// when writing pages.
+    pub fn split_page(&mut self, id: &str, embed: bool) -> Result<SplitPage> {
+        let Some(content) = self.read_page(id)? else {
+            miette::bail!("Page '{}' doesn't exist", page::name_from_id(id));
+        };
+        self.ensure_writable(id)?;
+
+        let lines: Vec<&str> = content.lines().collect();
+        let sections = sections(&lines);
+        if sections.is_empty() {
+            miette::bail!(
+                "Page '{}' has no headings to split by",
+                page::name_from_id(id)
+            );
+        }
+
+        let mut kept: Vec<String> = lines[..sections[0].start]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        let mut pages = Vec::new();
+        for section in &sections {
+            let new = self.unused_page_id(&section.title)?;
+            let name = page::name_from_id(&new);
+            self.write_page(
+                &new,
+                &body(&lines[section.start + 1..section.end], section.block),
+            )?;
+            kept.push(format!("- {}[[{}]]", if embed { "!" } else { "" }, name));
+            pages.push(new);
+        }
+
+        self.write_page(id, &(kept.join("\n") + "\n"))?;
+        Ok(SplitPage {
+            page: id.to_string(),
+            pages,
+        })
+    }
+}
+
+/// Finds the sections of a page, in order.
+fn sections(lines: &[&str]) -> Vec<Section> {
+    let mut headings: Vec<(usize, usize, String, bool)> = Vec::new();
+    let mut code = false;
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            code = !code;
+            continue;
+        }
+        if code || line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let block = line.starts_with("- ");
+        if let Some(heading) = page::headings(line).into_iter().next() {
+            headings.push((index, heading.level, heading.text, block));
+        }
+    }
+
+    let Some(top) = headings.iter().map(|(_, level, _, _)| *level).min() else {
+        return Vec::new();
+    };
+    let starts: Vec<(usize, String, bool)> = headings
+        .into_iter()
+        .filter(|(_, level, _, _)| *level == top)
+        .map(|(index, _, text, block)| (index, text, block))
+        .collect();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, (start, text, block))| Section {
+            title: title(text),
+            start: *start,
+            end: starts.get(i + 1).map_or(lines.len(), |next| next.0),
+            block: *block,
+        })
+        .collect()
+}
+
+/// Turns the text of a heading into a page name.
+fn title(text: &str) -> String {
+    text.replace("[[", "").replace("]]", "").trim().to_string()
+}
+
+/// Builds the content of a new page from the lines of a section.
+///
+/// Children of a heading block lose one level of indentation.
+fn body(lines: &[&str], block: bool) -> String {
+    let mut content: Vec<&str> = lines
+        .iter()
+        .map(|line| {
+            if block {
+                line.strip_prefix("  ").unwrap_or(line)
+            } else {
+                line
+            }
+        })
+        .collect();
+    while content.first().is_some_and(|line| line.trim().is_empty()) {
+        content.remove(0);
+    }
+    while content.last().is_some_and(|line| line.trim().is_empty()) {
+        content.pop();
+    }
+    content.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_heading_blocks() {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page(
+                "pages/Garden.md",
+                "tags:: home\n- Notes on the garden\n- # Seeds\n  - Tomatoes\n    - San Marzano\n  - ## Ordering\n- # Watering\n  - Every morning\n",
+            )
+            .unwrap();
+
+        let split = space.split_page("pages/Garden.md", true).unwrap();
+        assert_eq!(split.pages, vec!["pages/Seeds.md", "pages/Watering.md"]);
+        assert_eq!(
+            space.read_page("pages/Garden.md").unwrap().unwrap(),
+            "tags:: home\n- Notes on the garden\n- ![[Seeds]]\n- ![[Watering]]\n"
+        );
+        assert_eq!(
+            space.read_page("pages/Seeds.md").unwrap().unwrap(),
+            "- Tomatoes\n  - San Marzano\n- ## Ordering\n"
+        );
+        assert_eq!(
+            space.read_page("pages/Watering.md").unwrap().unwrap(),
+            "- Every morning\n"
+        );
+        assert_eq!(
+            space.page_index().unwrap().embedded_by("Seeds")[0].id,
+            "pages/Garden.md"
+        );
+    }
+
+    #[test]
+    fn test_split_markdown_headings_into_unused_pages() {
+        let mut space = Space::in_memory().unwrap();
+        space.write_page("pages/Reading.md", "- Taken\n").unwrap();
+        space
+            .write_page(
+                "pages/Log.md",
+                "## [[Reading]]\n- Dune\n\n## Music\n- Jazz\n",
+            )
+            .unwrap();
+
+        let split = space.split_page("pages/Log.md", false).unwrap();
+        assert_eq!(split.pages, vec!["pages/Reading (2).md", "pages/Music.md"]);
+        assert_eq!(
+            space.read_page("pages/Log.md").unwrap().unwrap(),
+            "- [[Reading (2)]]\n- [[Music]]\n"
+        );
+        assert_eq!(
+            space.read_page("pages/Reading (2).md").unwrap().unwrap(),
+            "- Dune\n"
+        );
+    }
+}
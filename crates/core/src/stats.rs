@@ -0,0 +1,212 @@
+//! Space Statistics
+//!
+//! Counting the words and tasks of a space reads every page, so the results
+//! are cached in `.flow/index/stats.json`, keyed by the version vector of the
+//! document. Any change to the document, local or synced, changes the
+//! version, so [`Space::stats`] recomputes lazily on the next call and is
+//! otherwise a single small read. Callers that must not load the document,
+//! like shell prompts, read the statistics with [`SpaceStats::cached`] as long
+//! as the page index didn't change since.
+
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::{INDEX_DIR, INDEX_FILE};
+use crate::page;
+use crate::space::{Space, FLOW_DIR};
+use crate::wordcount::{self, WordCount};
+
+/// File the statistics are cached in, below `.flow/index/`.
+const STATS_FILE: &str = "stats.json";
+
+/// Statistics of a space.
+///
+/// # Fields
+///
+/// - `version` (`String`) - Hex encoded version vector of the document the statistics were computed at.
+/// - `pages` (`usize`) - Number of pages, including journal pages.
+/// - `journal_pages` (`usize`) - Number of journal pages.
+/// - `counts` (`WordCount`) - Words, blocks and characters of all pages.
+/// - `open_tasks` (`usize`) - Number of open tasks (see [`page::OPEN_TASK_KEYWORDS`]).
+/// - `done_tasks` (`usize`) - Number of done tasks.
+/// - `links` (`usize`) - Number of links between pages.
+/// - `tags` (`usize`) - Number of distinct tags.
+/// - `due` (`Vec<i64>`) - Unix timestamps of all due dates, sorted.
+/// - `modified` (`i64`) - Unix timestamp of the latest page modification.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SpaceStats {
+    pub version: String,
+    pub pages: usize,
+    pub journal_pages: usize,
+    pub counts: WordCount,
+    pub open_tasks: usize,
+    pub done_tasks: usize,
+    pub links: usize,
+    pub tags: usize,
+    pub due: Vec<i64>,
+    pub modified: i64,
+}
+
+impl SpaceStats {
+    /// Reads the last computed statistics of a space without loading the document.
+    ///
+    /// For callers that must stay fast, like shell prompts. The page index is
+    /// written on every change, so statistics older than it are outdated.
+    ///
+    /// # Arguments
+    ///
+    /// - `space_path` (`&Path`) - Path of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<Self>` - The statistics, `None` if never computed, unreadable or outdated.
+    pub fn cached(space_path: &Path) -> Option<Self> {
+        let path = stats_path(space_path);
+        let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+        let index = space_path.join(FLOW_DIR).join(INDEX_DIR).join(INDEX_FILE);
+        if let (Ok(stats), Ok(index)) = (modified(&path), modified(&index)) {
+            if stats < index {
+                return None;
+            }
+        }
+
+        let json = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+}
+
+impl Space {
+    /// Returns the statistics of the space, recomputing them if the document
+    /// changed since they were cached.
+    ///
+    /// Caching is best-effort, also for read-only spaces: the cache only holds
+    /// derived numbers, and failing to write it (e.g. on a read-only mount)
+    /// just means recomputing next time.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space to compute the statistics of.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<SpaceStats>` - Statistics at the current version of the document.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages or the index.
+    pub fn stats(&self) -> Result<SpaceStats> {
+        let version = hex(&self.document.oplog_vv().encode());
+        let path = stats_path(&self.path);
+        if let Some(json) = self.storage.read_to_string(&path)? {
+            if let Ok(stats) = serde_json::from_str::<SpaceStats>(&json) {
+                if stats.version == version {
+                    return Ok(stats);
+                }
+            }
+        }
+
+        let mut stats = SpaceStats {
+            version,
+            ..SpaceStats::default()
+        };
+        for id in self.page_ids()? {
+            let Some(content) = self.read_page(&id)? else {
+                continue;
+            };
+            stats.pages += 1;
+            if page::is_journal(&id) {
+                stats.journal_pages += 1;
+            }
+            stats.counts.add(&wordcount::count(&content));
+            let (open, done) = tasks(&content);
+            stats.open_tasks += open;
+            stats.done_tasks += done;
+        }
+
+        let index = self.page_index()?;
+        let mut tags = BTreeSet::new();
+        for entry in index.pages() {
+            stats.links += entry.links.len();
+            stats.due.extend(&entry.due);
+            stats.modified = stats.modified.max(entry.modified);
+            tags.extend(entry.tags.iter().map(|tag| tag.to_lowercase()));
+        }
+        stats.tags = tags.len();
+        stats.due.sort_unstable();
+
+        let json = serde_json::to_string(&stats).into_diagnostic()?;
+        let _ = self.storage.write(&path, json.as_bytes());
+        Ok(stats)
+    }
+}
+
+/// Counts the open and done tasks of a page.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `(usize, usize)` - Number of open and of done tasks.
+fn tasks(content: &str) -> (usize, usize) {
+    let mut open = 0;
+    let mut done = 0;
+    for line in content.lines() {
+        let Some(text) = line.trim_start().strip_prefix("- ") else {
+            continue;
+        };
+        match text.split_whitespace().next() {
+            Some(keyword) if page::OPEN_TASK_KEYWORDS.contains(&keyword) => open += 1,
+            Some(page::DONE_KEYWORD) => done += 1,
+            _ => {}
+        }
+    }
+    (open, done)
+}
+
+/// Returns the path of the statistics cache of a space.
+fn stats_path(space_path: &Path) -> PathBuf {
+    space_path.join(FLOW_DIR).join(INDEX_DIR).join(STATS_FILE)
+}
+
+/// Encodes bytes as lowercase hex.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tasks() {
+        let content = "- TODO Write\n  - DONE Outline\n- LATER Edit\n- Todo list\n- DONE\n";
+        assert_eq!(tasks(content), (2, 2));
+    }
+
+    #[test]
+    fn test_stats_follow_document_version() {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page("pages/Plan.md", "- TODO Write the plan #writing\n")
+            .unwrap();
+
+        let stats = space.stats().unwrap();
+        assert_eq!(stats.pages, 1);
+        assert_eq!(stats.open_tasks, 1);
+        assert_eq!(stats.tags, 1);
+        assert_eq!(space.stats().unwrap(), stats);
+
+        space
+            .write_page("pages/Plan.md", "- DONE Write the plan #writing\n")
+            .unwrap();
+        let updated = space.stats().unwrap();
+        assert_ne!(updated.version, stats.version);
+        assert_eq!((updated.open_tasks, updated.done_tasks), (0, 1));
+    }
+}
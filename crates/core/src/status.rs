@@ -0,0 +1,157 @@
+//! Space Status
+//!
+//! Everything `flow status` reports about a space, the equivalent of
+//! `git status`: whether the markdown files and the document diverged, when
+//! the space was last saved, the changes each sync remote hasn't received
+//! yet, whether the page index is fresh and which pages are locked.
+//!
+//! Like [`fsck`](crate::fsck) the status is read from the files in `.flow/`
+//! without loading the space, so it can be taken while another process
+//! writes it. Reading every page makes it too slow for shell prompts, which
+//! use the cached numbers of `flow status --porcelain` instead.
+
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::fsck::{self, Issue};
+use crate::index::PageIndex;
+use crate::lock;
+use crate::space::{markdown_files, Metadata, DOCUMENT_FILE, FLOW_DIR, UPDATES_FILE};
+use crate::storage::FsStorage;
+use crate::sync;
+
+/// Freshness of the page index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum IndexState {
+    /// The index matches the markdown files.
+    Fresh,
+    /// Pages were added, removed or edited outside of Flow since it was written.
+    Stale,
+    /// The index was never written.
+    Missing,
+}
+
+/// Changes a sync remote hasn't received yet.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name of the remote.
+/// - `address` (`Option<String>`) - Address the remote is reached at.
+/// - `pending` (`Vec<String>`) - Ids of the pages the remote syncs that changed since the last sync.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RemoteStatus {
+    pub name: String,
+    pub address: Option<String>,
+    pub pending: Vec<String>,
+}
+
+/// Status of a space.
+///
+/// # Fields
+///
+/// - `changes` (`Vec<Issue>`) - Differences between the document and the markdown files, none if in sync.
+/// - `saved` (`Option<i64>`) - Unix timestamp of the last save, `None` if never saved.
+/// - `synced` (`Option<i64>`) - Unix timestamp of the last sync, `None` if never synced.
+/// - `remotes` (`Vec<RemoteStatus>`) - Pending changes per configured remote.
+/// - `index` (`IndexState`) - Freshness of the page index.
+/// - `locked` (`Vec<String>`) - Ids of the locked pages (see [`Space::set_locked`](crate::space::Space::set_locked)).
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SpaceStatus {
+    pub changes: Vec<Issue>,
+    pub saved: Option<i64>,
+    pub synced: Option<i64>,
+    pub remotes: Vec<RemoteStatus>,
+    pub index: IndexState,
+    pub locked: Vec<String>,
+}
+
+impl SpaceStatus {
+    /// Checks whether the document and the markdown files hold the same content.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if there are no changes.
+    pub fn is_clean(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Takes the status of the space at the given path.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<SpaceStatus>` - Status of the space.
+///
+/// # Errors
+///
+/// The metadata can't be read, or IO errors when reading pages or directories.
+pub fn inspect(path: &Path) -> Result<SpaceStatus> {
+    let metadata = Metadata::read(&FsStorage, path)?;
+    let changes = fsck::check(path)?.issues;
+
+    // The update log is written on most saves, the snapshot on checkpoints
+    let flow_dir = path.join(FLOW_DIR);
+    let saved = [DOCUMENT_FILE, UPDATES_FILE]
+        .iter()
+        .filter_map(|file| modified(&flow_dir.join(file)))
+        .max();
+
+    let index = PageIndex::cached(path);
+    let index_state = match &index {
+        None => IndexState::Missing,
+        Some(index) if index.is_stale(path)? => IndexState::Stale,
+        Some(_) => IndexState::Fresh,
+    };
+
+    // Without a sync every page is pending
+    let synced = sync::last_synced(path);
+    let index = index.unwrap_or_default();
+    let remotes = metadata
+        .sync
+        .remotes
+        .iter()
+        .map(|(name, remote)| RemoteStatus {
+            name: name.clone(),
+            address: remote.address.clone(),
+            pending: index
+                .pages()
+                .filter(|entry| synced.is_none_or(|synced| entry.modified > synced))
+                .filter(|entry| remote.allows(&entry.id))
+                .map(|entry| entry.id.clone())
+                .collect(),
+        })
+        .collect();
+
+    let mut locked = Vec::new();
+    for id in markdown_files(&FsStorage, path)? {
+        let content = fs::read_to_string(path.join(&id)).into_diagnostic()?;
+        if lock::is_locked(&content) {
+            locked.push(id);
+        }
+    }
+
+    Ok(SpaceStatus {
+        changes,
+        saved,
+        synced,
+        remotes,
+        index: index_state,
+        locked,
+    })
+}
+
+/// Returns the modification time of a file as unix timestamp, `None` if it doesn't exist.
+fn modified(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).and_then(|metadata| metadata.modified());
+    let since = modified.ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(since.as_secs() as i64)
+}
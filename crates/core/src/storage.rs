@@ -0,0 +1,418 @@
+//! Storage Backends
+//!
+//! A [`Space`](crate::space::Space) reads and writes its metadata, document,
+//! update log, pages and indexes through a [`Storage`], so the same graph
+//! logic runs on top of the filesystem or without one. [`FsStorage`] is the
+//! default; [`MemoryStorage`] keeps every file in memory, which is what the
+//! `wasm32-unknown-unknown` build uses in the browser. The web frontend
+//! persists it by exporting its files (e.g. into IndexedDB) and hands them
+//! back with [`MemoryStorage::from_files`] on the next start.
+//!
+//! Paths are the same in every backend: the space path joined with the
+//! relative path of a file. Features that work with files outside of the
+//! space itself (backups, migrations, sync, publishing) are only available
+//! for native storage.
+
+use miette::{IntoDiagnostic, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::Error;
+use crate::space::write_atomic;
+
+/// An entry of a directory listing.
+///
+/// # Fields
+///
+/// - `path` (`PathBuf`) - Path of the entry.
+/// - `dir` (`bool`) - True if the entry is a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageEntry {
+    pub path: PathBuf,
+    pub dir: bool,
+}
+
+/// Where the files of a space are kept.
+pub trait Storage: Send + Sync {
+    /// Reads a file.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to read from.
+    /// - `path` (`&Path`) - Path of the file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<Vec<u8>>>` - Content of the file, `None` if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors other than the file not existing.
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>>;
+
+    /// Replaces the content of a file atomically, creating missing parent directories.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to write to.
+    /// - `path` (`&Path`) - Path of the file.
+    /// - `content` (`&[u8]`) - Content to write.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the file.
+    fn write(&self, path: &Path, content: &[u8]) -> Result<()>;
+
+    /// Appends to a file, creating it if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to write to.
+    /// - `path` (`&Path`) - Path of the file.
+    /// - `content` (`&[u8]`) - Content to append.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the file.
+    fn append(&self, path: &Path, content: &[u8]) -> Result<()>;
+
+    /// Removes a file, doing nothing if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to remove from.
+    /// - `path` (`&Path`) - Path of the file.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when removing the file.
+    fn remove(&self, path: &Path) -> Result<()>;
+
+    /// Checks whether a file or directory exists.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to check.
+    /// - `path` (`&Path`) - Path to check.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the path exists.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Creates a directory and its parents.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to create the directory in.
+    /// - `path` (`&Path`) - Path of the directory.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when creating the directories.
+    fn create_dir_all(&self, path: &Path) -> Result<()>;
+
+    /// Lists the entries of a directory.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to list.
+    /// - `dir` (`&Path`) - Path of the directory.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<StorageEntry>>` - Entries of the directory, empty if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading the directory.
+    fn list(&self, dir: &Path) -> Result<Vec<StorageEntry>>;
+
+    /// Whether paths are real files on disk.
+    ///
+    /// Only native storage supports backups, migrations and pages edited
+    /// outside of Flow.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True for the filesystem.
+    fn is_native(&self) -> bool {
+        false
+    }
+
+    /// Reads a UTF-8 file.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Storage`) - Storage to read from.
+    /// - `path` (`&Path`) - Path of the file.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Option<String>>` - Content of the file, `None` if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// IO errors or content that isn't valid UTF-8.
+    fn read_to_string(&self, path: &Path) -> Result<Option<String>> {
+        self.read(path)?
+            .map(|bytes| String::from_utf8(bytes).into_diagnostic())
+            .transpose()
+    }
+}
+
+/// Storage on the local filesystem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsStorage;
+
+impl Storage for FsStorage {
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(Error::io(path)(error)),
+        }
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(Error::io(parent))?;
+        }
+        write_atomic(path, content)
+    }
+
+    fn append(&self, path: &Path, content: &[u8]) -> Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(Error::io(path))?;
+        file.write_all(content).map_err(Error::io(path))
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(Error::io(path)(error)),
+            _ => Ok(()),
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        fs::create_dir_all(path).map_err(Error::io(path))
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<StorageEntry>> {
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+            let path = entry.map_err(Error::io(dir))?.path();
+            entries.push(StorageEntry {
+                dir: path.is_dir(),
+                path,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+}
+
+/// Storage keeping all files in memory.
+///
+/// Directories exist implicitly as long as they contain a file, or once
+/// created explicitly.
+///
+/// # Fields
+///
+/// - `files` (`Mutex<BTreeMap<PathBuf, Vec<u8>>>`) - Content by file path.
+/// - `dirs` (`Mutex<BTreeSet<PathBuf>>`) - Explicitly created directories.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    dirs: Mutex<BTreeSet<PathBuf>>,
+}
+
+impl MemoryStorage {
+    /// Creates an empty storage.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - Storage without files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a storage holding previously exported files.
+    ///
+    /// # Arguments
+    ///
+    /// - `files` (`BTreeMap<PathBuf, Vec<u8>>`) - Content by file path, as returned by [`MemoryStorage::files`].
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - Storage with the files.
+    pub fn from_files(files: BTreeMap<PathBuf, Vec<u8>>) -> Self {
+        Self {
+            files: Mutex::new(files),
+            dirs: Mutex::default(),
+        }
+    }
+
+    /// Exports all files, e.g. to persist them.
+    ///
+    /// # Returns
+    ///
+    /// - `BTreeMap<PathBuf, Vec<u8>>` - Content by file path.
+    pub fn files(&self) -> BTreeMap<PathBuf, Vec<u8>> {
+        self.files.lock().expect("storage lock poisoned").clone()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .files
+            .lock()
+            .expect("storage lock poisoned")
+            .get(path)
+            .cloned())
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> Result<()> {
+        self.files
+            .lock()
+            .expect("storage lock poisoned")
+            .insert(path.to_path_buf(), content.to_vec());
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, content: &[u8]) -> Result<()> {
+        self.files
+            .lock()
+            .expect("storage lock poisoned")
+            .entry(path.to_path_buf())
+            .or_default()
+            .extend_from_slice(content);
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        self.files
+            .lock()
+            .expect("storage lock poisoned")
+            .remove(path);
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let files = self.files.lock().expect("storage lock poisoned");
+        let dirs = self.dirs.lock().expect("storage lock poisoned");
+        files
+            .keys()
+            .chain(dirs.iter())
+            .any(|key| key.starts_with(path))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        self.dirs
+            .lock()
+            .expect("storage lock poisoned")
+            .insert(path.to_path_buf());
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<StorageEntry>> {
+        let files = self.files.lock().expect("storage lock poisoned");
+        let dirs = self.dirs.lock().expect("storage lock poisoned");
+
+        let mut entries = BTreeMap::new();
+        for (key, is_file) in files
+            .keys()
+            .map(|key| (key, true))
+            .chain(dirs.iter().map(|key| (key, false)))
+        {
+            let Ok(relative) = key.strip_prefix(dir) else {
+                continue;
+            };
+            let mut components = relative.components();
+            let Some(first) = components.next() else {
+                continue;
+            };
+            let is_dir = !is_file || components.next().is_some();
+            *entries.entry(dir.join(first)).or_insert(is_dir) |= is_dir;
+        }
+
+        Ok(entries
+            .into_iter()
+            .map(|(path, dir)| StorageEntry { path, dir })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::space::Space;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        let root = Path::new("/space");
+        storage.write(&root.join("journal/a.md"), b"- a").unwrap();
+        storage.append(&root.join("b.md"), b"- b").unwrap();
+        storage.append(&root.join("b.md"), b"\n- c").unwrap();
+
+        assert!(storage.exists(&root.join("journal")));
+        assert_eq!(
+            storage.read_to_string(&root.join("b.md")).unwrap(),
+            Some("- b\n- c".to_string())
+        );
+        assert_eq!(
+            storage.list(root).unwrap(),
+            vec![
+                StorageEntry {
+                    path: root.join("b.md"),
+                    dir: false
+                },
+                StorageEntry {
+                    path: root.join("journal"),
+                    dir: true
+                },
+            ]
+        );
+
+        storage.remove(&root.join("b.md")).unwrap();
+        assert_eq!(storage.read(&root.join("b.md")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_memory_space() {
+        let storage = Arc::new(MemoryStorage::new());
+        let root = Path::new("/space");
+        let mut space = Space::init_in(storage.clone(), root, None, false).unwrap();
+        space.write_page("notes.md", "- Hello [[flow]]").unwrap();
+
+        let space = Space::load_from(storage, root).unwrap();
+        assert_eq!(space.name(), "space");
+        assert_eq!(space.page_ids().unwrap(), vec!["notes.md".to_string()]);
+        assert_eq!(
+            space
+                .search_ranked("hello", &Default::default(), 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
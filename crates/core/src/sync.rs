@@ -0,0 +1,829 @@
+//! Live Sync
+//!
+//! Streams document updates between two devices over TCP while both are
+//! connected. The CRDT guarantees that both sides converge no matter in which
+//! order updates arrive, so the protocol only has to make sure every update
+//! eventually reaches the other side:
+//!
+//! 1. Each side sends its document version.
+//! 2. Each side answers with all updates the other side is missing.
+//! 3. From then on, local changes (made by this process or by other Flow
+//!    processes writing to the same space) are sent as they happen, and
+//!    received updates are merged and written to the markdown files.
+//!
+//! Updates are always preceded by the sender's version, which tells the
+//! receiver what the sender had seen when it made its changes. Blocks both
+//! sides changed since then are reported as [`Conflict`]s and queued for review.
+//!
+//! Whenever a peer is known to have all local changes, the time is recorded
+//! in `.flow/synced`, so prompts can tell pages changed since (see
+//! [`last_synced`]) without loading the document.
+//!
+//! Frames are a one byte kind, a little-endian `u32` length and the payload.
+//!
+//! Before anything else, both sides prove they know the shared secret set in
+//! the space metadata. Each sends a random challenge and answers the peer's
+//! with a hash of the secret and both challenges, so the secret itself never
+//! crosses the network:
+//!
+//! ```toml
+//! [sync]
+//! secret = "secret:sync"
+//! ```
+//!
+//! Without a secret, listening is only allowed on loopback addresses.
+//! Connections aren't encrypted, so sync is meant for trusted local networks.
+//!
+//! # Selective sync
+//!
+//! Remotes configured in the space metadata can restrict which pages they
+//! receive with include and exclude globs, matched against page ids and names:
+//!
+//! ```toml
+//! [sync.remotes.team]
+//! address = "192.168.1.20"
+//! exclude = ["private/**"]
+//! ```
+//!
+//! Loro updates can't be split by container, so filtered remotes don't see the
+//! space's document at all. They sync a separate projection document, kept in
+//! `.flow/remotes/<name>.loro`, which only ever contains the pages the remote
+//! may see. Local changes are copied into the projection before sending and
+//! changes from the remote are copied back, so neither updates nor snapshots
+//! sent to the remote contain excluded pages. Pages excluded after they were
+//! already shared stay in the projection with their old content, but their
+//! changes are no longer sent.
+
+use loro::{ExportMode, LoroDoc, UpdateOptions, VersionVector};
+use miette::{miette, Context, IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::cancel::CancellationToken;
+use crate::conflicts::{self, Conflict};
+use crate::lock;
+use crate::page;
+use crate::search::glob_match;
+use crate::secrets::Secrets;
+use crate::space::{container_names, disk_state, read_document, write_atomic, Space, FLOW_DIR};
+
+/// Port used when no port is given.
+pub const DEFAULT_PORT: u16 = 7420;
+
+/// How often local changes and cancellation are checked.
+const TICK: Duration = Duration::from_millis(250);
+
+/// Largest accepted frame, guards against garbage on the socket.
+const MAX_FRAME: usize = 256 * 1024 * 1024;
+
+/// How long a peer may take to answer the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Directory of the projection documents of filtered remotes.
+const REMOTES_DIR: &str = "remotes";
+
+/// File holding the time a peer last had all local changes.
+const SYNCED_FILE: &str = "synced";
+
+const FRAME_VERSION: u8 = 0;
+const FRAME_UPDATES: u8 = 1;
+const FRAME_CHALLENGE: u8 = 2;
+const FRAME_PROOF: u8 = 3;
+
+/// Something that happened during a sync session.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SyncEvent {
+    /// Listening for peers.
+    Listening { addr: String },
+    /// A peer connected.
+    Connected { peer: String },
+    /// Updates were sent to the peer.
+    Sent { bytes: usize },
+    /// Updates were received and merged.
+    Received {
+        bytes: usize,
+        pages: Vec<String>,
+        conflicts: Vec<Conflict>,
+    },
+    /// The peer disconnected.
+    Disconnected { peer: String },
+    /// A peer failed the handshake and was turned away.
+    Rejected { peer: String, reason: String },
+}
+
+/// Totals of a sync session.
+///
+/// # Fields
+///
+/// - `peers` (`usize`) - Number of peers synced with.
+/// - `sent` (`usize`) - Bytes of updates sent.
+/// - `received` (`usize`) - Bytes of updates received.
+/// - `pages` (`usize`) - Number of page changes merged from peers.
+/// - `conflicts` (`usize`) - Number of concurrently changed blocks.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct SyncStats {
+    pub peers: usize,
+    pub sent: usize,
+    pub received: usize,
+    pub pages: usize,
+    pub conflicts: usize,
+}
+
+/// Sync settings of a space, stored in the space metadata.
+///
+/// # Fields
+///
+/// - `secret` (`Option<String>`) - Secret shared by all devices syncing the space, usually a `secret:` reference.
+/// - `remotes` (`BTreeMap<String, Remote>`) - Configured remotes by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub remotes: BTreeMap<String, Remote>,
+}
+
+/// A configured sync remote.
+///
+/// # Fields
+///
+/// - `address` (`Option<String>`) - Address to connect to, host or host:port.
+/// - `include` (`Vec<String>`) - Only pages matching one of these globs are synced, all if empty.
+/// - `exclude` (`Vec<String>`) - Pages matching one of these globs are never synced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Remote {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl Remote {
+    /// Checks whether the remote syncs every page.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if no patterns restrict the remote.
+    pub fn is_unfiltered(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Checks whether a page may be synced with the remote.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Remote`) - Remote to check.
+    /// - `id` (`&str`) - Id of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - True if the page is included and not excluded.
+    pub fn allows(&self, id: &str) -> bool {
+        let name = page::name_from_id(id);
+        let matches = |pattern: &String| glob_match(pattern, id) || glob_match(pattern, &name);
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+/// Projection document synced with a filtered remote.
+struct Projection {
+    remote: Remote,
+    doc: LoroDoc,
+    path: PathBuf,
+}
+
+impl Projection {
+    /// Loads the projection of a remote, empty on first sync.
+    fn load(space_path: &Path, name: &str, remote: &Remote) -> Result<Self> {
+        let path = space_path
+            .join(FLOW_DIR)
+            .join(REMOTES_DIR)
+            .join(format!("{}.loro", name));
+        let doc = LoroDoc::new();
+        if let Ok(snapshot) = fs::read(&path) {
+            doc.import(&snapshot).into_diagnostic()?;
+        }
+        Ok(Self {
+            remote: remote.clone(),
+            doc,
+            path,
+        })
+    }
+
+    /// Persists the projection.
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).into_diagnostic()?;
+        }
+        let snapshot = self.doc.export(ExportMode::Snapshot).into_diagnostic()?;
+        write_atomic(&self.path, &snapshot)
+    }
+}
+
+/// Resolves an address, adding the [`DEFAULT_PORT`] if none is given.
+///
+/// # Arguments
+///
+/// - `addr` (`&str`) - IP address or host name, optionally with a port.
+///
+/// # Returns
+///
+/// - `Result<SocketAddr>` - The first address the host resolves to.
+///
+/// # Errors
+///
+/// Returns an error if the host can't be resolved.
+pub fn resolve(addr: &str) -> Result<SocketAddr> {
+    if let Ok(ip) = addr.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, DEFAULT_PORT));
+    }
+    let has_port = addr
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    let with_port = if has_port {
+        addr.to_string()
+    } else {
+        format!("{}:{}", addr, DEFAULT_PORT)
+    };
+
+    with_port
+        .to_socket_addrs()
+        .into_diagnostic()?
+        .next()
+        .ok_or_else(|| miette!("Could not resolve '{}'", addr))
+}
+
+/// Returns when a peer last had all changes of a space.
+///
+/// # Arguments
+///
+/// - `space_path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Option<i64>` - Unix timestamp in seconds, `None` if the space never synced.
+pub fn last_synced(space_path: &Path) -> Option<i64> {
+    fs::read_to_string(space_path.join(FLOW_DIR).join(SYNCED_FILE))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Records that a peer has all changes of a space as of now.
+fn mark_synced(space_path: &Path) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    write_atomic(
+        &space_path.join(FLOW_DIR).join(SYNCED_FILE),
+        now.to_string().as_bytes(),
+    )
+}
+
+impl Space {
+    /// Returns the sync settings of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `&SyncSettings` - Reference to the space's sync settings.
+    pub fn sync_settings(&self) -> &SyncSettings {
+        &self.metadata.sync
+    }
+
+    /// Adds a sync remote, replacing a remote of the same name, and writes
+    /// the space metadata.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to configure.
+    /// - `name` (`&str`) - Name of the remote.
+    /// - `remote` (`Remote`) - The remote.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing the metadata.
+    pub fn add_remote(&mut self, name: &str, remote: Remote) -> Result<()> {
+        self.metadata.sync.remotes.insert(name.to_string(), remote);
+        self.metadata.write(&*self.storage, &self.path)
+    }
+
+    /// Accepts peers one after another and syncs with each until cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to sync.
+    /// - `addr` (`SocketAddr`) - Address to listen on.
+    /// - `remote` (`Option<&str>`) - Configured remote whose filters apply to all peers.
+    /// - `cancel` (`&CancellationToken`) - Stops listening and ends the current session.
+    /// - `on_event` (`&mut dyn FnMut(SyncEvent)`) - Receives session events.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<SyncStats>` - Totals over all sessions.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when binding the address or saving the space, the remote
+    /// isn't configured, or no secret is set and the address isn't loopback.
+    pub fn sync_listen(
+        &mut self,
+        addr: SocketAddr,
+        remote: Option<&str>,
+        cancel: &CancellationToken,
+        on_event: &mut dyn FnMut(SyncEvent),
+    ) -> Result<SyncStats> {
+        let remote = self.remote(remote)?;
+        let secret = self.sync_secret()?;
+        if secret.is_empty() && !addr.ip().is_loopback() {
+            miette::bail!(
+                "Listening on {} lets anyone on the network sync, set `secret = \"secret:sync\"` under [sync] in space.toml and store it with `flow auth set sync` first",
+                addr
+            );
+        }
+        let listener = TcpListener::bind(addr).into_diagnostic()?;
+        listener.set_nonblocking(true).into_diagnostic()?;
+        on_event(SyncEvent::Listening {
+            addr: listener.local_addr().into_diagnostic()?.to_string(),
+        });
+
+        let mut stats = SyncStats::default();
+        while !cancel.is_cancelled() {
+            match listener.accept() {
+                Ok((mut stream, peer)) => {
+                    stream.set_nonblocking(false).into_diagnostic()?;
+                    // A peer failing the handshake must not stop listening
+                    if let Err(err) = handshake(&mut stream, &secret) {
+                        on_event(SyncEvent::Rejected {
+                            peer: peer.to_string(),
+                            reason: err.to_string(),
+                        });
+                        continue;
+                    }
+                    let session = self.sync_stream(stream, remote.as_ref(), cancel, on_event)?;
+                    stats.peers += session.peers;
+                    stats.sent += session.sent;
+                    stats.received += session.received;
+                    stats.pages += session.pages;
+                    stats.conflicts += session.conflicts;
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => thread::sleep(TICK),
+                Err(err) => return Err(err).into_diagnostic(),
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Connects to a listening peer and syncs until either side disconnects.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to sync.
+    /// - `addr` (`SocketAddr`) - Address of the peer.
+    /// - `remote` (`Option<&str>`) - Configured remote whose filters apply to the peer.
+    /// - `cancel` (`&CancellationToken`) - Ends the session.
+    /// - `on_event` (`&mut dyn FnMut(SyncEvent)`) - Receives session events.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<SyncStats>` - Totals of the session.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when connecting or saving the space, the remote isn't
+    /// configured, or the peer doesn't know the sync secret.
+    pub fn sync_connect(
+        &mut self,
+        addr: SocketAddr,
+        remote: Option<&str>,
+        cancel: &CancellationToken,
+        on_event: &mut dyn FnMut(SyncEvent),
+    ) -> Result<SyncStats> {
+        let remote = self.remote(remote)?;
+        let secret = self.sync_secret()?;
+        let mut stream = TcpStream::connect(addr).into_diagnostic()?;
+        handshake(&mut stream, &secret)?;
+        self.sync_stream(stream, remote.as_ref(), cancel, on_event)
+    }
+
+    /// Resolves the sync secret of the space, empty if none is set.
+    fn sync_secret(&self) -> Result<String> {
+        match &self.metadata.sync.secret {
+            Some(secret) => Secrets::open()?.resolve(secret),
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Looks up a configured remote by name.
+    fn remote(&self, name: Option<&str>) -> Result<Option<(String, Remote)>> {
+        let Some(name) = name else {
+            return Ok(None);
+        };
+        match self.metadata.sync.remotes.get(name) {
+            Some(remote) => Ok(Some((name.to_string(), remote.clone()))),
+            None => miette::bail!("Remote '{}' is not configured in space.toml", name),
+        }
+    }
+
+    /// Runs a sync session over an established connection.
+    fn sync_stream(
+        &mut self,
+        mut stream: TcpStream,
+        remote: Option<&(String, Remote)>,
+        cancel: &CancellationToken,
+        on_event: &mut dyn FnMut(SyncEvent),
+    ) -> Result<SyncStats> {
+        let peer = stream
+            .peer_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default();
+        on_event(SyncEvent::Connected { peer: peer.clone() });
+        let mut stats = SyncStats {
+            peers: 1,
+            ..SyncStats::default()
+        };
+
+        let (frames, incoming) = mpsc::channel();
+        let mut reader = stream.try_clone().into_diagnostic()?;
+        thread::spawn(move || {
+            while let Ok(frame) = read_frame(&mut reader) {
+                if frames.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Filtered remotes sync the projection, everyone else the document itself
+        let projection = match remote {
+            Some((name, remote)) if !remote.is_unfiltered() => {
+                let projection = Projection::load(&self.path, name, remote)?;
+                self.project(&projection)?;
+                Some(projection)
+            }
+            _ => None,
+        };
+        let doc = match &projection {
+            Some(projection) => projection.doc.clone(),
+            None => self.document.clone(),
+        };
+
+        write_frame(&mut stream, FRAME_VERSION, &doc.oplog_vv().encode())?;
+
+        // Version the peer is known to have, unknown until it sent its version
+        let mut peer_version: Option<VersionVector> = None;
+        // Version the peer last announced, the base of the updates that follow
+        let mut announced: Option<VersionVector> = None;
+        let mut on_disk = disk_state(&self.path);
+
+        while !cancel.is_cancelled() {
+            match incoming.recv_timeout(TICK) {
+                Ok((FRAME_VERSION, payload)) => {
+                    let version = VersionVector::decode(&payload).into_diagnostic()?;
+                    match &mut peer_version {
+                        Some(known) => known.merge(&version),
+                        None => peer_version = Some(version.clone()),
+                    }
+                    announced = Some(version);
+                }
+                Ok((FRAME_UPDATES, payload)) => {
+                    let local = doc.oplog_vv();
+                    let pages = match &projection {
+                        Some(projection) => {
+                            projection.doc.import(&payload).into_diagnostic()?;
+                            projection.save()?;
+                            self.absorb(projection)?
+                        }
+                        None => self.merge(&payload)?,
+                    };
+                    on_disk = disk_state(&self.path);
+
+                    // History compacted away can't be compared, skip detection then
+                    let conflicts = match &announced {
+                        Some(remote) => conflicts::detect(&doc, &local, remote).unwrap_or_default(),
+                        None => Vec::new(),
+                    };
+                    conflicts::record(&self.path, &conflicts)?;
+
+                    stats.received += payload.len();
+                    stats.pages += pages.len();
+                    stats.conflicts += conflicts.len();
+                    on_event(SyncEvent::Received {
+                        bytes: payload.len(),
+                        pages,
+                        conflicts,
+                    });
+                    if peer_version.as_ref() == Some(&doc.oplog_vv()) {
+                        mark_synced(&self.path)?;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    // Pick up changes other Flow processes saved meanwhile
+                    let current = disk_state(&self.path);
+                    if current != on_disk {
+                        self.pending = read_document(&*self.storage, &self.path, &self.document)?;
+                        self.saved = self.document.oplog_vv();
+                        on_disk = current;
+                        if let Some(projection) = &projection {
+                            self.project(projection)?;
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            if let Some(version) = &peer_version {
+                if *version != doc.oplog_vv() {
+                    let updates = doc.export(ExportMode::updates(version)).into_diagnostic()?;
+                    let sent = write_frame(&mut stream, FRAME_VERSION, &doc.oplog_vv().encode())
+                        .and_then(|_| write_frame(&mut stream, FRAME_UPDATES, &updates));
+                    if sent.is_err() {
+                        break;
+                    }
+                    peer_version = Some(doc.oplog_vv());
+                    mark_synced(&self.path)?;
+                    stats.sent += updates.len();
+                    on_event(SyncEvent::Sent {
+                        bytes: updates.len(),
+                    });
+                }
+            }
+        }
+
+        if peer_version.is_some_and(|version| version == doc.oplog_vv()) {
+            mark_synced(&self.path)?;
+        }
+        let _ = stream.shutdown(Shutdown::Both);
+        on_event(SyncEvent::Disconnected { peer });
+        Ok(stats)
+    }
+
+    /// Imports updates from a peer and writes the pages they changed.
+    ///
+    /// Changes to locked pages are reverted, so the peer receives the locked
+    /// content back with the next update.
+    fn merge(&mut self, updates: &[u8]) -> Result<Vec<String>> {
+        let locked: Vec<(String, String)> = if self.allow_locked {
+            Vec::new()
+        } else {
+            container_names(&self.document)
+                .into_iter()
+                .filter(|id| id.ends_with(".md"))
+                .map(|id| {
+                    let content = self.document.get_text(id.as_str()).to_string();
+                    (id, content)
+                })
+                .filter(|(_, content)| lock::is_locked(content))
+                .collect()
+        };
+        self.document.import(updates).into_diagnostic()?;
+        for (id, content) in &locked {
+            let text = self.document.get_text(id.as_str());
+            if text.to_string() != *content {
+                text.update(content, UpdateOptions::default())
+                    .into_diagnostic()?;
+            }
+        }
+
+        let mut pages = Vec::new();
+        for id in container_names(&self.document) {
+            if !id.ends_with(".md") {
+                continue;
+            }
+            let content = self.document.get_text(id.as_str()).to_string();
+            if fs::read_to_string(self.path.join(&id)).ok().as_deref() != Some(content.as_str()) {
+                if let Some(parent) = self.path.join(&id).parent() {
+                    fs::create_dir_all(parent).into_diagnostic()?;
+                }
+                self.dirty.insert(id.clone());
+                pages.push(id);
+            }
+        }
+
+        // The updates carry their own page times
+        self.persist()?;
+        Ok(pages)
+    }
+
+    /// Copies the pages a remote may see into its projection.
+    fn project(&self, projection: &Projection) -> Result<()> {
+        let mut changed = false;
+        for id in container_names(&self.document) {
+            if !id.ends_with(".md") || !projection.remote.allows(&id) {
+                continue;
+            }
+            let content = self.document.get_text(id.as_str()).to_string();
+            let projected = projection.doc.get_text(id.as_str());
+            if projected.to_string() != content {
+                projected
+                    .update(&content, UpdateOptions::default())
+                    .into_diagnostic()?;
+                changed = true;
+            }
+        }
+
+        if changed {
+            projection.doc.commit();
+            projection.save()?;
+        }
+        Ok(())
+    }
+
+    /// Copies pages changed by a remote from its projection into the space.
+    ///
+    /// Changes to locked pages are reverted in the projection instead.
+    fn absorb(&mut self, projection: &Projection) -> Result<Vec<String>> {
+        let mut pages = Vec::new();
+        let mut reverted = false;
+        for id in container_names(&projection.doc) {
+            // Never let a remote write pages it isn't allowed to see
+            if !id.ends_with(".md") || !projection.remote.allows(&id) {
+                continue;
+            }
+            let content = projection.doc.get_text(id.as_str()).to_string();
+            let text = self.document.get_text(id.as_str());
+            if text.to_string() != content {
+                if !self.allow_locked && lock::is_locked(&text.to_string()) {
+                    reverted = true;
+                    continue;
+                }
+                if let Some(parent) = self.path.join(&id).parent() {
+                    fs::create_dir_all(parent).into_diagnostic()?;
+                }
+                text.update(&content, UpdateOptions::default())
+                    .into_diagnostic()?;
+                self.dirty.insert(id.clone());
+                pages.push(id);
+            }
+        }
+
+        if !pages.is_empty() {
+            self.save()?;
+        }
+        if reverted {
+            self.project(projection)?;
+        }
+        Ok(pages)
+    }
+}
+
+/// Proves to the peer that this side knows the secret and checks that it does too.
+///
+/// # Arguments
+///
+/// - `stream` (`&mut TcpStream`) - Connection to the peer.
+/// - `secret` (`&str`) - The shared sync secret.
+///
+/// # Errors
+///
+/// The peer doesn't answer in time, doesn't speak the protocol or its proof
+/// doesn't match the secret.
+fn handshake(stream: &mut TcpStream, secret: &str) -> Result<()> {
+    stream
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .into_diagnostic()?;
+    let challenge = Uuid::new_v4();
+    write_frame(stream, FRAME_CHALLENGE, challenge.as_bytes())?;
+    let peer_challenge = expect_frame(stream, FRAME_CHALLENGE)?;
+    // A reflected challenge would let the peer replay this side's proof
+    if peer_challenge == challenge.as_bytes() {
+        miette::bail!("Sync peer sent back our own challenge");
+    }
+
+    write_frame(
+        stream,
+        FRAME_PROOF,
+        &proof(secret, &peer_challenge, challenge.as_bytes()),
+    )?;
+    let peer_proof = expect_frame(stream, FRAME_PROOF)?;
+    let expected = proof(secret, challenge.as_bytes(), &peer_challenge);
+    // Compare in constant time, the proof shouldn't leak through timing
+    let same = peer_proof.len() == expected.len()
+        && peer_proof
+            .iter()
+            .zip(&expected)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0;
+    if !same {
+        miette::bail!("Sync peer doesn't know the sync secret");
+    }
+
+    stream.set_read_timeout(None).into_diagnostic()
+}
+
+/// Reads a frame of the given kind.
+fn expect_frame(stream: &mut TcpStream, kind: u8) -> Result<Vec<u8>> {
+    let (received, payload) = read_frame(stream)
+        .into_diagnostic()
+        .context("Sync peer didn't complete the handshake")?;
+    if received != kind {
+        miette::bail!("Sync peer doesn't speak this version of the sync protocol");
+    }
+    Ok(payload)
+}
+
+/// Hashes the secret with the challenge being answered and the answering side's own.
+fn proof(secret: &str, challenge: &[u8], own: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(secret.as_bytes());
+    hasher.update(challenge);
+    hasher.update(own);
+    hasher.finalize().to_vec()
+}
+
+/// Writes a single frame.
+fn write_frame(stream: &mut impl Write, kind: u8, payload: &[u8]) -> Result<()> {
+    stream.write_all(&[kind]).into_diagnostic()?;
+    stream
+        .write_all(&(payload.len() as u32).to_le_bytes())
+        .into_diagnostic()?;
+    stream.write_all(payload).into_diagnostic()?;
+    stream.flush().into_diagnostic()
+}
+
+/// Reads a single frame.
+fn read_frame(stream: &mut impl Read) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 5];
+    stream.read_exact(&mut header)?;
+    let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if len > MAX_FRAME {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+
+    let mut payload = vec![0; len];
+    stream.read_exact(&mut payload)?;
+    Ok((header[0], payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_allows() {
+        let remote = Remote {
+            address: None,
+            include: Vec::new(),
+            exclude: vec!["private/**".to_string()],
+        };
+        assert!(remote.allows("pages/projects/flow.md"));
+        assert!(!remote.allows("pages/private/diary.md"));
+        assert!(remote.allows("journal/2024-05-01.md"));
+    }
+
+    #[test]
+    fn test_frames_roundtrip() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, FRAME_UPDATES, b"ops").unwrap();
+        write_frame(&mut buffer, FRAME_VERSION, b"").unwrap();
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            (FRAME_UPDATES, b"ops".to_vec())
+        );
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            (FRAME_VERSION, Vec::new())
+        );
+        assert!(read_frame(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_handshake_checks_secret() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let peer = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let first = handshake(&mut stream, "shared").is_ok();
+            let (mut stream, _) = listener.accept().unwrap();
+            let second = handshake(&mut stream, "shared").is_ok();
+            (first, second)
+        });
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        assert!(handshake(&mut stream, "shared").is_ok());
+        let mut stream = TcpStream::connect(addr).unwrap();
+        assert!(handshake(&mut stream, "guessed").is_err());
+        assert_eq!(peer.join().unwrap(), (true, false));
+    }
+}
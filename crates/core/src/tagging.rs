@@ -0,0 +1,364 @@
+//! Tag Suggestions
+//!
+//! Proposes tags for pages from the tags already used in a space. By default
+//! suggestions are heuristic: every tag gets a TF-IDF profile of the words on
+//! the pages carrying it, and a page is offered the tags whose profiles are
+//! closest to its own words. Tags named on the page get a boost. Nothing
+//! leaves the machine.
+//!
+//! With a [`LanguageModel`] configured (see [`ai`](crate::ai)), the model is
+//! asked instead, given the page and the tag vocabulary of the space.
+//!
+//! Accepted tags are added to the page's `tags::` property, which is created
+//! if the page has none yet.
+
+use miette::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::ai::LanguageModel;
+use crate::fulltext;
+use crate::page;
+use crate::space::Space;
+
+/// Minimum similarity of a heuristic suggestion.
+const MIN_SCORE: f64 = 0.2;
+
+/// Boost of tags whose words appear on the page.
+const NAME_BOOST: f64 = 0.2;
+
+const SUGGEST_PROMPT: &str = "You tag personal notes. Reply with nothing but a comma \
+separated list of tags for the note, most fitting first. Prefer the existing tags listed \
+below and only make up a new tag if none fits.";
+
+/// Tags proposed for a page.
+///
+/// # Fields
+///
+/// - `id` (`String`) - Id of the page.
+/// - `page` (`String`) - Name of the page.
+/// - `tags` (`Vec<String>`) - Proposed tags, best first.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TagSuggestion {
+    pub id: String,
+    pub page: String,
+    pub tags: Vec<String>,
+}
+
+/// The tags of a space with the TF-IDF profiles of their pages.
+///
+/// # Fields
+///
+/// - `idf` (`HashMap<String, f64>`) - Inverse document frequency of each word.
+/// - `profiles` (`BTreeMap<String, HashMap<String, f64>>`) - Normalized word weights of each tag.
+#[derive(Debug, Clone, Default)]
+pub struct TagVocabulary {
+    idf: HashMap<String, f64>,
+    profiles: BTreeMap<String, HashMap<String, f64>>,
+}
+
+impl TagVocabulary {
+    /// Builds the vocabulary from the contents of pages.
+    ///
+    /// # Arguments
+    ///
+    /// - `pages` (`&[String]`) - Markdown content of every page of a space.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The vocabulary of all tags used on the pages.
+    pub fn build(pages: &[String]) -> Self {
+        let tokens: Vec<Vec<String>> = pages
+            .iter()
+            .map(|content| fulltext::tokenize(content))
+            .collect();
+
+        let mut frequency: HashMap<&str, usize> = HashMap::new();
+        for words in &tokens {
+            for word in words.iter().collect::<HashSet<_>>() {
+                *frequency.entry(word).or_default() += 1;
+            }
+        }
+        let docs = pages.len() as f64;
+        let idf: HashMap<String, f64> = frequency
+            .into_iter()
+            .map(|(word, df)| {
+                (
+                    word.to_string(),
+                    ((docs + 1.0) / (df as f64 + 1.0)).ln() + 1.0,
+                )
+            })
+            .collect();
+
+        let mut vocabulary = Self {
+            idf,
+            profiles: BTreeMap::new(),
+        };
+        for (content, words) in pages.iter().zip(&tokens) {
+            let vector = vocabulary.vector(words);
+            for tag in page::tags(content) {
+                let profile = vocabulary.profiles.entry(tag).or_default();
+                for (word, weight) in &vector {
+                    *profile.entry(word.clone()).or_default() += weight;
+                }
+            }
+        }
+        for profile in vocabulary.profiles.values_mut() {
+            normalize(profile);
+        }
+        vocabulary
+    }
+
+    /// Returns the tags of the vocabulary.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<&str>` - Tags in alphabetical order.
+    pub fn tags(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    /// Proposes tags for a page from its words.
+    ///
+    /// # Arguments
+    ///
+    /// - `content` (`&str`) - Markdown content of the page.
+    /// - `limit` (`usize`) - Maximum number of tags.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<String>` - Tags the page doesn't carry yet, best first.
+    pub fn suggest(&self, content: &str, limit: usize) -> Vec<String> {
+        let words = fulltext::tokenize(content);
+        let vector = self.vector(&words);
+        let present: HashSet<&str> = words.iter().map(String::as_str).collect();
+        let existing = page::tags(content);
+
+        let mut scored: Vec<(&str, f64)> = self
+            .profiles
+            .iter()
+            .filter(|(tag, _)| !existing.contains(tag))
+            .map(|(tag, profile)| {
+                let similarity: f64 = vector
+                    .iter()
+                    .filter_map(|(word, weight)| profile.get(word).map(|w| w * weight))
+                    .sum();
+                let named = fulltext::tokenize(tag)
+                    .iter()
+                    .all(|word| present.contains(word.as_str()));
+                (
+                    tag.as_str(),
+                    similarity + if named { NAME_BOOST } else { 0.0 },
+                )
+            })
+            .filter(|(_, score)| *score >= MIN_SCORE)
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(tag, _)| tag.to_string())
+            .collect()
+    }
+
+    /// Weighs words by their frequency and rarity, normalized to unit length.
+    fn vector(&self, words: &[String]) -> HashMap<String, f64> {
+        let mut vector: HashMap<String, f64> = HashMap::new();
+        for word in words {
+            *vector.entry(word.clone()).or_default() += 1.0;
+        }
+        for (word, weight) in vector.iter_mut() {
+            *weight = weight.ln_1p() * self.idf.get(word).copied().unwrap_or(1.0);
+        }
+        normalize(&mut vector);
+        vector
+    }
+}
+
+/// Asks a language model to propose tags for a page.
+///
+/// # Arguments
+///
+/// - `model` (`&dyn LanguageModel`) - Model proposing the tags.
+/// - `vocabulary` (`&TagVocabulary`) - Tags already used in the space.
+/// - `content` (`&str`) - Markdown content of the page.
+/// - `limit` (`usize`) - Maximum number of tags.
+///
+/// # Returns
+///
+/// - `Result<Vec<String>>` - Tags the page doesn't carry yet, best first.
+///
+/// # Errors
+///
+/// The model fails.
+pub fn suggest_with_model(
+    model: &dyn LanguageModel,
+    vocabulary: &TagVocabulary,
+    content: &str,
+    limit: usize,
+) -> Result<Vec<String>> {
+    let prompt = format!(
+        "Existing tags: {}\n\nNote:\n{}",
+        vocabulary.tags().join(", "),
+        content
+    );
+    let existing = page::tags(content);
+    let mut tags: Vec<String> = Vec::new();
+    for tag in model.complete(SUGGEST_PROMPT, &prompt)?.split([',', '\n']) {
+        let tag = tag
+            .trim()
+            .trim_start_matches(['-', '*'])
+            .trim()
+            .trim_start_matches('#')
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-");
+        if !tag.is_empty() && !existing.contains(&tag) && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.truncate(limit);
+    Ok(tags)
+}
+
+/// Scales a vector to unit length.
+fn normalize(vector: &mut HashMap<String, f64>) {
+    let length = vector.values().map(|w| w * w).sum::<f64>().sqrt();
+    if length > 0.0 {
+        for weight in vector.values_mut() {
+            *weight /= length;
+        }
+    }
+}
+
+impl Space {
+    /// Builds the tag vocabulary of the space.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<TagVocabulary>` - All tags with the profiles of their pages.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when reading pages.
+    pub fn tag_vocabulary(&self) -> Result<TagVocabulary> {
+        let mut pages = Vec::new();
+        for id in self.page_ids()? {
+            if let Some(content) = self.read_page(&id)? {
+                pages.push(content);
+            }
+        }
+        Ok(TagVocabulary::build(&pages))
+    }
+
+    /// Returns the pages without any tag, journal pages excluded.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<String>>` - Ids of the untagged pages.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when the index has to be rebuilt.
+    pub fn untagged_pages(&self) -> Result<Vec<String>> {
+        Ok(self
+            .page_index()?
+            .pages()
+            .filter(|entry| entry.tags.is_empty() && !page::is_journal(&entry.id))
+            .map(|entry| entry.id.clone())
+            .collect())
+    }
+
+    /// Adds tags to the `tags::` property of a page.
+    ///
+    /// Tags the page already carries are skipped; the property is created at
+    /// the top of the page if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    /// - `tags` (`&[String]`) - Tags to add.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<String>>` - The tags that were added.
+    ///
+    /// # Errors
+    ///
+    /// The page is locked, or IO errors when reading or writing the page.
+    pub fn add_tags(&mut self, id: &str, tags: &[String]) -> Result<Vec<String>> {
+        let content = self.read_page(id)?.unwrap_or_default();
+        let existing = page::tags(&content);
+        let mut added: Vec<String> = Vec::new();
+        for tag in tags {
+            if !existing.contains(tag) && !added.contains(tag) {
+                added.push(tag.clone());
+            }
+        }
+        if added.is_empty() {
+            return Ok(added);
+        }
+
+        let updated = page::extend_property(&content, &["tags", "tag"], &added);
+        self.write_page(id, &updated)?;
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages() -> Vec<String> {
+        vec![
+            "tags:: rust\n- Borrow checker and lifetimes in rust\n".to_string(),
+            "- Ownership, lifetimes and the borrow checker #rust\n".to_string(),
+            "tags:: garden\n- Planted tomatoes and basil\n".to_string(),
+            "- Watering tomatoes #garden\n".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_suggest_closest_tags() {
+        let vocabulary = TagVocabulary::build(&pages());
+        assert_eq!(vocabulary.tags(), vec!["garden", "rust"]);
+        assert_eq!(
+            vocabulary.suggest("- Fighting the borrow checker again\n", 3),
+            vec!["rust"]
+        );
+        assert_eq!(
+            vocabulary.suggest("- Harvested tomatoes\n", 3),
+            vec!["garden"]
+        );
+        assert!(vocabulary
+            .suggest("tags:: rust\n- borrow checker\n", 3)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_add_tags_extends_or_creates_property() {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .write_page("pages/Note.md", "alias:: N\n- Borrow checker\n")
+            .unwrap();
+        let added = space
+            .add_tags("pages/Note.md", &["rust".to_string(), "rust".to_string()])
+            .unwrap();
+        assert_eq!(added, vec!["rust"]);
+        assert_eq!(
+            space.read_page("pages/Note.md").unwrap().unwrap(),
+            "tags:: rust\nalias:: N\n- Borrow checker\n"
+        );
+
+        space
+            .add_tags("pages/Note.md", &["rust".to_string(), "lang".to_string()])
+            .unwrap();
+        assert_eq!(
+            space.read_page("pages/Note.md").unwrap().unwrap(),
+            "tags:: rust, lang\nalias:: N\n- Borrow checker\n"
+        );
+    }
+}
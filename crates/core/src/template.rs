@@ -0,0 +1,515 @@
+//! Space Templates
+//!
+//! `flow init --template <name>` scaffolds a new space from a template of the
+//! local registry, `~/.config/flow/templates/` next to `flow.toml`. A
+//! template is a directory holding the files to copy into the space and a
+//! `template.toml` manifest describing it:
+//!
+//! ```toml
+//! name = "meeting-notes"
+//! description = "Meetings with agenda, minutes and the people attending"
+//! directories = ["meetings", "people"]
+//!
+//! [[variables]]
+//! name = "team"
+//! description = "Name of the team"
+//! default = "Platform"
+//! ```
+//!
+//! `{{team}}` in file names and markdown files is replaced with the value of
+//! the variable, as are the built-in `{{graph}}` (name of the space) and
+//! `{{date}}` (today). A variable without default must be given. Markdown
+//! files become pages of the space, other files are copied as they are.
+//! Hidden files and the manifest itself are skipped. A directory without
+//! manifest is a template named after the directory, without variables.
+//!
+//! Templates are added to the registry from a local directory or a git URL,
+//! which is cloned with `git` and stored without its history. `--template`
+//! takes a directory or git URL as well, git templates used that way are
+//! fetched again into `.cache/` of the registry each time.
+
+use miette::{IntoDiagnostic, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::config::Config;
+use crate::space::Space;
+
+/// Directory next to `flow.toml` holding the registry.
+const TEMPLATES_DIR: &str = "templates";
+
+/// Manifest file of a template.
+pub const MANIFEST_FILE: &str = "template.toml";
+
+/// Directory of the registry git templates are fetched into when used directly.
+const CACHE_DIR: &str = ".cache";
+
+/// A variable filled in when scaffolding.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name of the variable, used as `{{name}}`.
+/// - `description` (`String`) - What the variable is for, shown when asked for it.
+/// - `default` (`Option<String>`) - Value if none is given, the variable is required without.
+#[derive(Debug, Clone, Serialize, JsonSchema, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// Manifest of a template.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name of the template.
+/// - `description` (`String`) - What the template sets up.
+/// - `directories` (`Vec<String>`) - Directories created in the space, even if the template has no files in them.
+/// - `variables` (`Vec<TemplateVariable>`) - Variables filled in when scaffolding.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, Deserialize)]
+pub struct TemplateManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub directories: Vec<String>,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+}
+
+/// A template on disk.
+///
+/// # Fields
+///
+/// - `manifest` (`TemplateManifest`) - Manifest of the template.
+/// - `path` (`PathBuf`) - Directory of the template.
+/// - `files` (`Vec<String>`) - Relative paths of the files scaffolded, before variables are replaced.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SpaceTemplate {
+    #[serde(flatten)]
+    pub manifest: TemplateManifest,
+    pub path: PathBuf,
+    pub files: Vec<String>,
+}
+
+impl SpaceTemplate {
+    /// Reads the template in a directory.
+    ///
+    /// # Arguments
+    ///
+    /// - `dir` (`&Path`) - Directory of the template.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<SpaceTemplate>` - The template.
+    ///
+    /// # Errors
+    ///
+    /// The directory doesn't exist, the manifest is invalid, or IO errors when
+    /// reading the directory.
+    pub fn read(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            miette::bail!("Template directory '{}' not found", dir.display());
+        }
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let mut manifest = if manifest_path.exists() {
+            let content = fs::read_to_string(&manifest_path).into_diagnostic()?;
+            toml::from_str::<TemplateManifest>(&content).map_err(|error| {
+                miette::miette!("Invalid manifest '{}': {}", manifest_path.display(), error)
+            })?
+        } else {
+            TemplateManifest::default()
+        };
+        if manifest.name.is_empty() {
+            manifest.name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+        }
+
+        let mut files = template_files(dir)?;
+        files.retain(|file| file != MANIFEST_FILE);
+        Ok(Self {
+            manifest,
+            path: dir.to_path_buf(),
+            files,
+        })
+    }
+
+    /// Fills in the variables of the template.
+    ///
+    /// # Arguments
+    ///
+    /// - `given` (`&BTreeMap<String, String>`) - Values given for the variables.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<BTreeMap<String, String>>` - Value of every variable, given or default.
+    ///
+    /// # Errors
+    ///
+    /// A given variable isn't declared by the template, or a required one is missing.
+    pub fn values(&self, given: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>> {
+        let variables = &self.manifest.variables;
+        if let Some(unknown) = given
+            .keys()
+            .find(|name| !variables.iter().any(|variable| &variable.name == *name))
+        {
+            miette::bail!(
+                "Template '{}' has no variable '{}'",
+                self.manifest.name,
+                unknown
+            );
+        }
+
+        let mut values = BTreeMap::new();
+        for variable in variables {
+            let value = given
+                .get(&variable.name)
+                .or(variable.default.as_ref())
+                .ok_or_else(|| {
+                    miette::miette!(
+                        "Template '{}' needs a value for '{}'",
+                        self.manifest.name,
+                        variable.name
+                    )
+                })?;
+            values.insert(variable.name.clone(), value.clone());
+        }
+        Ok(values)
+    }
+}
+
+/// Returns the directory of the template registry.
+///
+/// # Returns
+///
+/// - `Result<PathBuf>` - The registry, which may not exist yet.
+///
+/// # Errors
+///
+/// If the configuration directory can't be determined.
+pub fn registry_dir() -> Result<PathBuf> {
+    Ok(Config::path()?
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(TEMPLATES_DIR))
+}
+
+/// Lists the templates of the registry.
+///
+/// # Returns
+///
+/// - `Result<Vec<SpaceTemplate>>` - Templates ordered by directory name.
+///
+/// # Errors
+///
+/// A manifest is invalid, or IO errors when reading the registry.
+pub fn list() -> Result<Vec<SpaceTemplate>> {
+    let dir = registry_dir()?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs: Vec<PathBuf> = fs::read_dir(&dir)
+        .into_diagnostic()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir() && !is_hidden(path))
+        .collect();
+    dirs.sort();
+    dirs.iter().map(|dir| SpaceTemplate::read(dir)).collect()
+}
+
+/// Resolves the template to scaffold a space from.
+///
+/// # Arguments
+///
+/// - `source` (`&str`) - Name of a template in the registry, a directory or a git URL.
+///
+/// # Returns
+///
+/// - `Result<SpaceTemplate>` - The template.
+///
+/// # Errors
+///
+/// No template is found, cloning fails, or the manifest is invalid.
+pub fn resolve(source: &str) -> Result<SpaceTemplate> {
+    let registry = registry_dir()?;
+    if is_git_url(source) {
+        let cache = registry.join(CACHE_DIR).join(cache_name(source));
+        fetch(source, &cache)?;
+        return SpaceTemplate::read(&cache);
+    }
+    if valid_name(source) && registry.join(source).is_dir() {
+        return SpaceTemplate::read(&registry.join(source));
+    }
+    if Path::new(source).is_dir() {
+        return SpaceTemplate::read(Path::new(source));
+    }
+    miette::bail!(
+        "No template '{}', see the registry with 'flow template list'",
+        source
+    )
+}
+
+/// Adds a template to the registry.
+///
+/// # Arguments
+///
+/// - `source` (`&str`) - Directory or git URL of the template.
+/// - `name` (`Option<&str>`) - Name to add the template as, defaults to the name in its manifest.
+///
+/// # Returns
+///
+/// - `Result<SpaceTemplate>` - The added template.
+///
+/// # Errors
+///
+/// The name is invalid or taken, cloning fails, the manifest is invalid, or
+/// IO errors when copying files.
+pub fn add(source: &str, name: Option<&str>) -> Result<SpaceTemplate> {
+    let registry = registry_dir()?;
+    let incoming = registry.join(CACHE_DIR).join("incoming");
+    fetch(source, &incoming)?;
+
+    let template = SpaceTemplate::read(&incoming)?;
+    let name = name.unwrap_or(&template.manifest.name).to_string();
+    let target = registry.join(&name);
+    let added = if !valid_name(&name) {
+        Err(miette::miette!("Invalid template name '{}'", name))
+    } else if target.exists() {
+        Err(miette::miette!(
+            "Template '{}' already exists, remove it first",
+            name
+        ))
+    } else {
+        fs::rename(&incoming, &target).into_diagnostic()
+    };
+    if let Err(error) = added {
+        let _ = fs::remove_dir_all(&incoming);
+        return Err(error);
+    }
+
+    let mut template = SpaceTemplate::read(&target)?;
+    template.manifest.name = name;
+    Ok(template)
+}
+
+/// Removes a template from the registry.
+///
+/// # Arguments
+///
+/// - `name` (`&str`) - Name of the template.
+///
+/// # Returns
+///
+/// - `Result<SpaceTemplate>` - The removed template.
+///
+/// # Errors
+///
+/// The registry has no such template, or IO errors when removing it.
+pub fn remove(name: &str) -> Result<SpaceTemplate> {
+    let dir = registry_dir()?.join(name);
+    if !valid_name(name) || !dir.is_dir() {
+        miette::bail!("No template '{}' in the registry", name);
+    }
+    let template = SpaceTemplate::read(&dir)?;
+    fs::remove_dir_all(&dir).into_diagnostic()?;
+    Ok(template)
+}
+
+impl Space {
+    /// Scaffolds the files of a template into the space.
+    ///
+    /// All pages are written in a single transaction.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to scaffold into.
+    /// - `template` (`&SpaceTemplate`) - The template.
+    /// - `given` (`&BTreeMap<String, String>`) - Values given for the variables of the template.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<String>>` - Relative paths of the scaffolded files.
+    ///
+    /// # Errors
+    ///
+    /// A variable is unknown or missing (see [`SpaceTemplate::values`]), or IO
+    /// errors when reading the template or writing the space.
+    pub fn apply_template(
+        &mut self,
+        template: &SpaceTemplate,
+        given: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>> {
+        let mut values = template.values(given)?;
+        values.insert("graph".to_string(), self.name().to_string());
+        values.insert(
+            "date".to_string(),
+            self.today().format("%Y-%m-%d").to_string(),
+        );
+
+        self.transaction(|space| {
+            for dir in &template.manifest.directories {
+                let dir = substitute(dir, &values);
+                space.storage.create_dir_all(&space.path.join(dir))?;
+            }
+
+            let mut scaffolded = Vec::new();
+            for file in &template.files {
+                let target = substitute(file, &values);
+                let source = template.path.join(file);
+                if target.ends_with(".md") {
+                    let content = fs::read_to_string(&source).into_diagnostic()?;
+                    space.write_page(&target, &substitute(&content, &values))?;
+                } else {
+                    let content = fs::read(&source).into_diagnostic()?;
+                    space.storage.write(&space.path.join(&target), &content)?;
+                }
+                scaffolded.push(target);
+            }
+            Ok(scaffolded)
+        })
+    }
+}
+
+/// Checks whether a template source is a git URL rather than a name or directory.
+fn is_git_url(source: &str) -> bool {
+    ["https://", "http://", "ssh://", "git://", "git@"]
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+        || source.ends_with(".git")
+}
+
+/// Checks whether a name can be a directory of the registry.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+/// Returns the directory name a git URL is cached under.
+fn cache_name(url: &str) -> String {
+    url.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Copies a template from a directory or clones it from a git URL, replacing `target`.
+fn fetch(source: &str, target: &Path) -> Result<()> {
+    if target.exists() {
+        fs::remove_dir_all(target).into_diagnostic()?;
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).into_diagnostic()?;
+    }
+
+    if !is_git_url(source) {
+        let dir = Path::new(source);
+        if !dir.is_dir() {
+            miette::bail!("Template directory '{}' not found", dir.display());
+        }
+        for file in template_files(dir)? {
+            let destination = target.join(&file);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).into_diagnostic()?;
+            }
+            fs::copy(dir.join(&file), destination).into_diagnostic()?;
+        }
+        return Ok(());
+    }
+
+    // `--` keeps a source starting with `-` from being read as an option
+    let status = process::Command::new("git")
+        .args(["clone", "--depth", "1", "--quiet", "--", source])
+        .arg(target)
+        .stdout(process::Stdio::null())
+        .status()
+        .map_err(|error| miette::miette!("Failed to run 'git': {}", error))?;
+    if !status.success() {
+        miette::bail!("'git clone {}' failed with {}", source, status);
+    }
+    fs::remove_dir_all(target.join(".git")).into_diagnostic()
+}
+
+/// Returns the relative paths of the files of a template, skipping hidden ones.
+fn template_files(root: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).into_diagnostic()? {
+            let path = entry.into_diagnostic()?.path();
+            if is_hidden(&path) {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(root).into_diagnostic()?;
+            files.push(
+                relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            );
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Checks whether a file or directory is hidden.
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Replaces `{{name}}` with the value of every variable.
+fn substitute(text: &str, values: &BTreeMap<String, String>) -> String {
+    values.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{{{}}}}}", name), value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_template_fills_in_variables() {
+        let dir = std::env::temp_dir().join(format!("flow-template-{}", std::process::id()));
+        fs::create_dir_all(dir.join("pages")).unwrap();
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            "name = \"team\"\ndirectories = [\"people\"]\n\n[[variables]]\nname = \"team\"\n\n[[variables]]\nname = \"lead\"\ndefault = \"Ada\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("pages/{{team}}.md"),
+            "- Team {{team}} led by {{lead}} in {{graph}}\n",
+        )
+        .unwrap();
+        fs::write(dir.join(".hidden.md"), "- Skipped\n").unwrap();
+
+        let template = SpaceTemplate::read(&dir).unwrap();
+        assert_eq!(template.files, vec!["pages/{{team}}.md"]);
+
+        let mut space = Space::in_memory().unwrap();
+        assert!(space.apply_template(&template, &BTreeMap::new()).is_err());
+
+        let given = BTreeMap::from([("team".to_string(), "Sync".to_string())]);
+        let scaffolded = space.apply_template(&template, &given).unwrap();
+        assert_eq!(scaffolded, vec!["pages/Sync.md"]);
+        assert_eq!(
+            space.read_page("pages/Sync.md").unwrap().as_deref(),
+            Some("- Team Sync led by Ada in memory\n")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Page Timestamps
+//!
+//! Creation and modification times of pages, recorded in the document itself
+//! rather than taken from the file system. File times are reset by copies,
+//! checkouts and sync tools; times stored in the document travel with it and
+//! merge like any other change.
+//!
+//! Times live in a `meta` map container of the document, holding one map with
+//! `created` and `modified` unix timestamps per page id. Pages without
+//! recorded times (e.g. created by an external editor) fall back to the
+//! modification time of their file.
+
+use loro::{LoroDoc, LoroMap, LoroValue};
+use miette::{IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::space::{read_document, Space};
+use crate::storage::FsStorage;
+
+/// Name of the map container holding page timestamps.
+pub(crate) const META_CONTAINER: &str = "meta";
+
+/// Creation and modification time of a page.
+///
+/// # Fields
+///
+/// - `created` (`i64`) - Creation as unix timestamp in seconds.
+/// - `modified` (`i64`) - Last modification as unix timestamp in seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageTimes {
+    pub created: i64,
+    pub modified: i64,
+}
+
+impl PageTimes {
+    /// Returns the times of a page from its file's modification time.
+    ///
+    /// # Arguments
+    ///
+    /// - `file_path` (`&Path`) - Path of the markdown file.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - Times with both fields set to the file's modification time.
+    pub fn from_file(file_path: &Path) -> Self {
+        let modified = fs::metadata(file_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        Self {
+            created: modified,
+            modified,
+        }
+    }
+}
+
+impl Space {
+    /// Returns the recorded times of a page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&self` (`Space`) - Space the page belongs to.
+    /// - `id` (`&str`) - Id of the page.
+    ///
+    /// # Returns
+    ///
+    /// - `PageTimes` - Recorded times, or the file's modification time if none were recorded.
+    pub fn page_times(&self, id: &str) -> PageTimes {
+        get(&self.document, id).unwrap_or_else(|| PageTimes::from_file(&self.path.join(id)))
+    }
+}
+
+/// Records a modification of a page, setting its creation time on first use.
+///
+/// # Arguments
+///
+/// - `doc` (`&LoroDoc`) - Document of the space.
+/// - `id` (`&str`) - Id of the page.
+/// - `created` (`i64`) - Creation time to record if the page has none yet.
+/// - `modified` (`i64`) - Modification time to record.
+///
+/// # Errors
+///
+/// Loro errors when updating the map.
+pub(crate) fn touch(doc: &LoroDoc, id: &str, created: i64, modified: i64) -> Result<()> {
+    let times = doc
+        .get_map(META_CONTAINER)
+        .get_or_create_container(id, LoroMap::new())
+        .into_diagnostic()?;
+    if times.get("created").is_none() {
+        times.insert("created", created).into_diagnostic()?;
+    }
+    times.insert("modified", modified).into_diagnostic()
+}
+
+/// Returns the recorded times of a page.
+///
+/// # Arguments
+///
+/// - `doc` (`&LoroDoc`) - Document of the space.
+/// - `id` (`&str`) - Id of the page.
+///
+/// # Returns
+///
+/// - `Option<PageTimes>` - Recorded times, `None` if none were recorded.
+pub(crate) fn get(doc: &LoroDoc, id: &str) -> Option<PageTimes> {
+    doc.get_map(META_CONTAINER)
+        .get(id)
+        .and_then(|times| from_value(&times.get_deep_value()))
+}
+
+/// Returns the recorded times of all pages in a document.
+///
+/// # Arguments
+///
+/// - `doc` (`&LoroDoc`) - Document of the space.
+///
+/// # Returns
+///
+/// - `HashMap<String, PageTimes>` - Times keyed by page id.
+pub(crate) fn all(doc: &LoroDoc) -> HashMap<String, PageTimes> {
+    let LoroValue::Map(pages) = doc.get_map(META_CONTAINER).get_deep_value() else {
+        return HashMap::new();
+    };
+
+    pages
+        .iter()
+        .filter_map(|(id, value)| Some((id.clone(), from_value(value)?)))
+        .collect()
+}
+
+/// Reads the recorded times of all pages of a space without loading it.
+///
+/// # Arguments
+///
+/// - `space_path` (`&Path`) - Path of the space.
+///
+/// # Returns
+///
+/// - `Result<HashMap<String, PageTimes>>` - Times keyed by page id.
+///
+/// # Errors
+///
+/// Fails if the document can not be read.
+pub(crate) fn read(space_path: &Path) -> Result<HashMap<String, PageTimes>> {
+    let doc = LoroDoc::new();
+    read_document(&FsStorage, space_path, &doc)?;
+    Ok(all(&doc))
+}
+
+/// Parses the times map of a single page.
+fn from_value(value: &LoroValue) -> Option<PageTimes> {
+    let LoroValue::Map(times) = value else {
+        return None;
+    };
+    let field = |name: &str| match times.get(name) {
+        Some(LoroValue::I64(time)) => Some(*time),
+        _ => None,
+    };
+
+    let modified = field("modified")?;
+    Some(PageTimes {
+        created: field("created").unwrap_or(modified),
+        modified,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touch_keeps_creation_time() {
+        let doc = LoroDoc::new();
+        touch(&doc, "pages/a.md", 100, 100).unwrap();
+        touch(&doc, "pages/a.md", 200, 200).unwrap();
+
+        assert_eq!(
+            get(&doc, "pages/a.md"),
+            Some(PageTimes {
+                created: 100,
+                modified: 200
+            })
+        );
+    }
+}
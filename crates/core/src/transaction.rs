@@ -0,0 +1,186 @@
+//! Transactions
+//!
+//! Every change through [`Space`] saves the space on its own: a commit, an
+//! export of the document and a write of the changed files and the indexes.
+//! Operations made of many changes, like rollover, starting a day from its
+//! template or updating captured issues, run them in a transaction instead:
+//!
+//! ```ignore
+//! space.transaction(|space| {
+//!     space.write_page("pages/A.md", "- One")?;
+//!     space.write_page("pages/B.md", "- Two")
+//! })?;
+//! ```
+//!
+//! Saves are deferred while the closure runs and the space is saved once
+//! after it returned, all changes in a single commit. If the closure fails,
+//! the document is reloaded from the last save and the changes since are
+//! dropped. A transaction started within another joins the outer one.
+//!
+//! Deleted pages are part of the transaction too, their files are only
+//! removed by the final save. Changes before hook scripts run are written
+//! right away (see [`Space::flush`]), since the scripts read the files. This
+//! is a save as well, so they survive a failing transaction.
+
+use loro::LoroDoc;
+use miette::Result;
+
+use crate::space::{read_document, Space};
+
+impl Space {
+    /// Runs changes as a single transaction, saving the space once.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`Space`) - Space to change.
+    /// - `changes` (`impl FnOnce(&mut Space) -> Result<T>`) - The changes.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<T>` - What the changes returned.
+    ///
+    /// # Errors
+    ///
+    /// The changes fail, in which case the changes not saved yet are dropped
+    /// (changes flushed before hooks ran stay saved), or IO errors when saving
+    /// or reloading the space.
+    pub fn transaction<T>(&mut self, changes: impl FnOnce(&mut Space) -> Result<T>) -> Result<T> {
+        // Earlier unsaved changes must survive a rollback
+        if self.transaction == 0 && !self.dirty.is_empty() {
+            self.save()?;
+        }
+
+        self.transaction += 1;
+        let result = changes(self);
+        self.transaction -= 1;
+        if self.transaction > 0 {
+            return result;
+        }
+
+        match result {
+            Ok(value) => {
+                if !self.dirty.is_empty() || self.document.oplog_vv() != self.saved {
+                    self.save()?;
+                }
+                Ok(value)
+            }
+            Err(err) => {
+                self.rollback()?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Saves the changes of the running transaction right away.
+    ///
+    /// For external processes reading the files in the middle of a
+    /// transaction, like hook scripts. Outside of a transaction nothing is
+    /// left to save.
+    ///
+    /// # Errors
+    ///
+    /// IO errors when writing files.
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        if self.transaction == 0 || (self.dirty.is_empty() && self.deleted.is_empty()) {
+            return Ok(());
+        }
+        let depth = std::mem::take(&mut self.transaction);
+        let saved = self.save();
+        self.transaction = depth;
+        saved
+    }
+
+    /// Drops the unsaved changes by reloading the document from the last save.
+    fn rollback(&mut self) -> Result<()> {
+        let doc = LoroDoc::new();
+        doc.set_record_timestamp(true);
+        self.pending = read_document(&*self.storage, &self.path, &doc)?;
+        self.saved = doc.oplog_vv();
+        self.document = doc;
+        self.dirty.clear();
+        self.deleted.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_saves_once_or_not_at_all() {
+        let mut space = Space::in_memory().unwrap();
+        space
+            .transaction(|space| {
+                space.write_page("pages/A.md", "- One\n")?;
+                assert_eq!(
+                    space
+                        .storage
+                        .read_to_string(&space.path.join("pages/A.md"))?,
+                    None
+                );
+                space.write_page("pages/B.md", "- Two\n")
+            })
+            .unwrap();
+        assert_eq!(
+            space
+                .storage
+                .read_to_string(&space.path.join("pages/A.md"))
+                .unwrap()
+                .as_deref(),
+            Some("- One\n")
+        );
+
+        let failed = space.transaction(|space| {
+            space.write_page("pages/A.md", "- Changed\n")?;
+            miette::bail!("Changed my mind")
+        });
+        assert!(failed.is_err());
+        assert_eq!(
+            space.read_page("pages/A.md").unwrap().as_deref(),
+            Some("- One\n")
+        );
+    }
+
+    #[test]
+    fn test_flushed_changes_survive_rollback() {
+        let mut space = Space::in_memory().unwrap();
+        let failed = space.transaction(|space| {
+            space.write_page("pages/A.md", "- Flushed\n")?;
+            space.flush()?;
+            space.write_page("pages/B.md", "- Dropped\n")?;
+            miette::bail!("Hook failed")
+        });
+        assert!(failed.is_err());
+        assert_eq!(
+            space.read_page("pages/A.md").unwrap().as_deref(),
+            Some("- Flushed\n")
+        );
+        assert_eq!(space.read_page("pages/B.md").unwrap(), None);
+    }
+
+    #[test]
+    fn test_deleted_page_survives_rollback() {
+        let mut space = Space::in_memory().unwrap();
+        space.write_page("pages/A.md", "- Kept\n").unwrap();
+
+        let failed = space.transaction(|space| {
+            space.delete_page("pages/A.md")?;
+            assert!(space.storage.exists(&space.path.join("pages/A.md")));
+            miette::bail!("Changed my mind")
+        });
+        assert!(failed.is_err());
+        assert_eq!(
+            space
+                .storage
+                .read_to_string(&space.path.join("pages/A.md"))
+                .unwrap()
+                .as_deref(),
+            Some("- Kept\n")
+        );
+        assert_eq!(
+            space.read_page("pages/A.md").unwrap().as_deref(),
+            Some("- Kept\n")
+        );
+    }
+}
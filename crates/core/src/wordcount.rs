@@ -0,0 +1,129 @@
+//! Word Counts
+//!
+//! Counts the words, blocks and characters of pages and estimates their
+//! reading time, for `flow wc` and writing-habit tracking. Page properties
+//! and list markers are not counted; a fenced code block counts as one
+//! block.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::page;
+
+/// Reading speed the reading time is estimated with, in words per minute.
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// Counts of a page or a set of pages.
+///
+/// # Fields
+///
+/// - `words` (`usize`) - Number of words.
+/// - `blocks` (`usize`) - Number of blocks: bullets, headings, paragraphs and code blocks.
+/// - `characters` (`usize`) - Number of characters, without indentation, list markers and line breaks.
+/// - `reading_minutes` (`usize`) - Estimated reading time, rounded up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct WordCount {
+    pub words: usize,
+    pub blocks: usize,
+    pub characters: usize,
+    pub reading_minutes: usize,
+}
+
+impl WordCount {
+    /// Adds the counts of another page.
+    ///
+    /// # Arguments
+    ///
+    /// - `&mut self` (`WordCount`) - Counts to add to.
+    /// - `other` (`&WordCount`) - Counts to add.
+    pub fn add(&mut self, other: &WordCount) {
+        self.words += other.words;
+        self.blocks += other.blocks;
+        self.characters += other.characters;
+        self.reading_minutes = reading_minutes(self.words);
+    }
+}
+
+/// Counts the words, blocks and characters of a page.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Markdown content of the page.
+///
+/// # Returns
+///
+/// - `WordCount` - Counts of the page.
+pub fn count(content: &str) -> WordCount {
+    let mut counts = WordCount::default();
+    let properties = page::properties(content).len();
+    let mut code = false;
+    let mut paragraph = false;
+
+    let lines = content
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .skip(properties);
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            if !code {
+                counts.blocks += 1;
+            }
+            code = !code;
+            paragraph = false;
+            continue;
+        }
+        if trimmed.is_empty() && !code {
+            paragraph = false;
+            continue;
+        }
+
+        let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+        let text = if code {
+            trimmed
+        } else if let Some(text) = trimmed
+            .strip_prefix("- ")
+            .or((trimmed == "-").then_some(""))
+        {
+            counts.blocks += 1;
+            paragraph = false;
+            text
+        } else if level > 0 && trimmed[level..].starts_with(' ') {
+            counts.blocks += 1;
+            paragraph = false;
+            trimmed[level..].trim_start()
+        } else {
+            // Indented lines continue a bullet, other lines start or continue a paragraph
+            if !paragraph && line.len() == line.trim_start().len() {
+                counts.blocks += 1;
+                paragraph = true;
+            }
+            trimmed
+        };
+        counts.words += text.split_whitespace().count();
+        counts.characters += text.chars().count();
+    }
+
+    counts.reading_minutes = reading_minutes(counts.words);
+    counts
+}
+
+/// Estimates the reading time of a number of words in minutes, rounded up.
+fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count() {
+        let content = "type:: note\n# Notes\n- Wrote [[flow]] docs\n  - more text\n  continued here\n\nA paragraph\nstill it\n```\nlet x = 1;\n```";
+        let counts = count(content);
+        assert_eq!(counts.blocks, 5);
+        assert_eq!(counts.words, 16);
+        assert_eq!(counts.reading_minutes, 1);
+        assert_eq!(count("").reading_minutes, 0);
+    }
+}
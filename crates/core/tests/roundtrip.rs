@@ -0,0 +1,93 @@
+//! Round-trip Properties
+//!
+//! Random edit sequences applied through [`Space::write_page`] must leave the
+//! CRDT document, the markdown files and a freshly loaded space agreeing on
+//! every page's content.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use flow_core::space::Space;
+use flow_core::storage::{MemoryStorage, Storage};
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+const ROOT: &str = "/space";
+const PAGES: [&str; 3] = [
+    "pages/notes.md",
+    "pages/projects/flow.md",
+    "journal/2024-05-01.md",
+];
+
+/// An edit of a page's content.
+#[derive(Debug, Clone)]
+enum Edit {
+    Insert { at: Index, text: String },
+    Delete { at: Index, len: usize },
+    Replace { text: String },
+}
+
+impl Edit {
+    /// Applies the edit to the content of a page.
+    fn apply(&self, content: &str) -> String {
+        let mut chars: Vec<char> = content.chars().collect();
+        match self {
+            Edit::Insert { at, text } => {
+                let at = at.index(chars.len() + 1);
+                chars.splice(at..at, text.chars());
+            }
+            Edit::Delete { at, len } => {
+                let at = at.index(chars.len() + 1);
+                let end = (at + len).min(chars.len());
+                chars.drain(at..end);
+            }
+            Edit::Replace { text } => return text.clone(),
+        }
+        chars.into_iter().collect()
+    }
+}
+
+/// Markdown-ish text: bullets, indentation, links, tags and non-ASCII.
+fn text() -> impl Strategy<Value = String> {
+    "(- |  |\n|\\[\\[|\\]\\]|#tag|::|[a-zA-Z0-9 ]|ä|→|😀){0,24}"
+}
+
+fn edit() -> impl Strategy<Value = Edit> {
+    prop_oneof![
+        4 => (any::<Index>(), text()).prop_map(|(at, text)| Edit::Insert { at, text }),
+        2 => (any::<Index>(), 0..16usize).prop_map(|(at, len)| Edit::Delete { at, len }),
+        1 => text().prop_map(|text| Edit::Replace { text }),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_edits_round_trip(edits in prop::collection::vec((0..PAGES.len(), edit()), 1..24)) {
+        let storage = Arc::new(MemoryStorage::new());
+        let root = Path::new(ROOT);
+        let mut space = Space::init_in(storage.clone(), root, None, false).unwrap();
+
+        let mut expected: BTreeMap<String, String> = BTreeMap::new();
+        for (page, edit) in &edits {
+            let id = PAGES[*page].to_string();
+            let content = edit.apply(expected.get(&id).map(String::as_str).unwrap_or_default());
+            space.write_page(&id, &content).unwrap();
+            prop_assert_eq!(space.read_page(&id).unwrap(), Some(content.clone()));
+            expected.insert(id, content);
+        }
+
+        let reloaded = Space::load_from(storage.clone(), root).unwrap();
+        prop_assert_eq!(
+            reloaded.page_ids().unwrap(),
+            expected.keys().cloned().collect::<Vec<_>>()
+        );
+        for (id, content) in &expected {
+            let file = storage.read_to_string(&root.join(id)).unwrap();
+            prop_assert_eq!(file.as_ref(), Some(content));
+            prop_assert_eq!(reloaded.read_page(id).unwrap().as_ref(), Some(content));
+        }
+    }
+}
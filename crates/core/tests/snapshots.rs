@@ -0,0 +1,130 @@
+//! Exporter Snapshots
+//!
+//! Pins the output of the exporters, review changes with `cargo insta review`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use flow_core::attribution::Author;
+use flow_core::index::PageIndex;
+use flow_core::links;
+use flow_core::org::{from_org, to_org};
+use flow_core::render::{self, RenderPage};
+use flow_core::space::Space;
+use flow_core::storage::MemoryStorage;
+use flow_core::timestamps::PageTimes;
+
+const MARKDOWN: &str = "alias:: Plans\n\n- TODO Ship **flow** *now* with [[Ada|her]]\n  scheduled:: 2024-05-01 10:00\n  deadline:: 2024-05-03\n  effort:: 2h\n  - DONE Write `docs`\n    closed:: 2024-04-30\n- See [site](https://example.com)";
+
+const PAGE: &str = "alias:: Plans\n\n# Goals\n- Ship **flow** with [[Ada]]\n  due:: 2024-05-03\n  - Read [[Ada#Notes|her notes]] and *reviews*\n- See [site](https://example.com) & [[Elsewhere]]\n\nPlain text\ncontinued\n\n```\nlet fast = a < b;\n```";
+
+/// The roadmap page and the page it links to.
+fn render_pages() -> Vec<RenderPage> {
+    vec![
+        RenderPage {
+            id: "pages/Roadmap.md".to_string(),
+            content: PAGE.to_string(),
+        },
+        RenderPage {
+            id: "pages/Ada.md".to_string(),
+            content: "- ## Notes\n  - Works on <engines>".to_string(),
+        },
+    ]
+}
+
+/// Replaces the times of a feed, pages are stamped when they are written.
+fn mask_times(atom: &str) -> String {
+    atom.lines()
+        .map(|line| {
+            if line.starts_with("<updated>") {
+                "<updated>[time]</updated>"
+            } else if line.starts_with("<published>") {
+                "<published>[time]</published>"
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_org_export() {
+    insta::assert_snapshot!(to_org("Roadmap", MARKDOWN));
+}
+
+#[test]
+fn test_org_import() {
+    let (title, markdown) = from_org(&to_org("Roadmap", MARKDOWN));
+    assert_eq!(title.as_deref(), Some("Roadmap"));
+    insta::assert_snapshot!(markdown);
+}
+
+#[test]
+fn test_publish_site_page() {
+    let pages = render_pages();
+    let links = render::link_targets(&pages, |name| format!("{}.html", render::slug(name)));
+    let html = render::to_site_page(&pages[0], &links, "index.html");
+    insta::assert_snapshot!(html);
+}
+
+#[test]
+fn test_pdf_input() {
+    let html = render::to_html("Roadmap", &render_pages());
+    insta::assert_snapshot!(html);
+}
+
+#[test]
+fn test_feed() {
+    let mut space = Space::init_in(
+        Arc::new(MemoryStorage::new()),
+        Path::new("/blog"),
+        Some(&"Blog".to_string()),
+        true,
+    )
+    .unwrap();
+    space.set_author(Author {
+        name: "Ada".to_string(),
+        device: "laptop".to_string(),
+    });
+    space
+        .write_page(
+            "journal/2024-05-01.md",
+            "- Shipped [[Roadmap]] to **everyone**",
+        )
+        .unwrap();
+    space
+        .write_page("pages/Roadmap.md", "- Plans for [[2024-05-01]]")
+        .unwrap();
+    space
+        .write_page("pages/Draft.md", "publish:: false\n- Secret")
+        .unwrap();
+    // Written last, so it is the newest entry even within the same second
+    space
+        .write_page(
+            "pages/Changelog.md",
+            "publish:: true\n- Added feeds, see [[Draft]]",
+        )
+        .unwrap();
+
+    let atom = mask_times(&space.feed(Some("https://notes.example.com"), 20).unwrap());
+    insta::assert_snapshot!(atom);
+}
+
+#[test]
+fn test_export_dot() {
+    let mut index = PageIndex::default();
+    for (id, content) in [
+        ("journal/2024-05-01.md", "- Met [[Ada]] about [[Plans]]"),
+        ("pages/Ada.md", "- ![[Roadmap]]"),
+        (
+            "pages/Roadmap.md",
+            "alias:: Plans\n- Ask [[Ada]], see [[Missing]]",
+        ),
+    ] {
+        index.update(id, content, PageTimes::default());
+    }
+
+    let dot = links::graph(&index).to_dot("notes");
+    insta::assert_snapshot!(dot);
+}
@@ -0,0 +1,101 @@
+//! Desktop application.
+//!
+//! Runs the graph window of the active space (see [`graph`](crate::graph))
+//! next to the quick capture (see [`capture`](crate::capture)). Closing the
+//! graph window keeps the quick capture running in the background.
+
+use iced::window;
+use iced::{Element, Size, Subscription, Task};
+use miette::{IntoDiagnostic, Result};
+
+use crate::capture::{self, QuickCapture};
+use crate::graph::{self, GraphView};
+
+/// Initial size of the graph window.
+const WINDOW_SIZE: Size = Size::new(1200.0, 800.0);
+
+/// Messages of the desktop application.
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// A message of the graph view.
+    Graph(graph::Message),
+    /// A message of the quick capture.
+    Capture(capture::Message),
+}
+
+/// State of the desktop application.
+///
+/// # Fields
+///
+/// - `main` (`window::Id`) - The graph window.
+/// - `graph` (`Result<GraphView, String>`) - The graph view, or why the space couldn't be loaded.
+/// - `capture` (`QuickCapture`) - The quick capture.
+struct Desktop {
+    main: window::Id,
+    graph: std::result::Result<GraphView, String>,
+    capture: QuickCapture,
+}
+
+/// Runs the desktop application until the process is stopped.
+///
+/// # Errors
+///
+/// Returns an error if the hotkey can't be registered or the window system
+/// fails.
+pub fn run() -> Result<()> {
+    let capture = QuickCapture::new()?;
+
+    iced::daemon(Desktop::title, Desktop::update, Desktop::view)
+        .subscription(Desktop::subscription)
+        .run_with(move || {
+            let (main, open) = window::open(window::Settings {
+                size: WINDOW_SIZE,
+                ..window::Settings::default()
+            });
+            let state = Desktop {
+                main,
+                graph: GraphView::load().map_err(|error| error.to_string()),
+                capture,
+            };
+            (state, open.discard())
+        })
+        .into_diagnostic()
+}
+
+impl Desktop {
+    fn title(&self, window: window::Id) -> String {
+        match &self.graph {
+            Ok(graph) if window == self.main => graph.title(),
+            _ if window == self.main => "Flow".to_string(),
+            _ => self.capture.title(),
+        }
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Graph(message) => match &mut self.graph {
+                Ok(graph) => graph.update(message).map(Message::Graph),
+                Err(_) => Task::none(),
+            },
+            Message::Capture(message) => self.capture.update(message).map(Message::Capture),
+        }
+    }
+
+    fn view(&self, window: window::Id) -> Element<'_, Message> {
+        if Some(window) == self.capture.window() {
+            return self.capture.view().map(Message::Capture);
+        }
+        match &self.graph {
+            Ok(graph) => graph.view().map(Message::Graph),
+            Err(error) => iced::widget::text(error).into(),
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let capture = self.capture.subscription().map(Message::Capture);
+        match &self.graph {
+            Ok(graph) => Subscription::batch([capture, graph.subscription().map(Message::Graph)]),
+            Err(_) => capture,
+        }
+    }
+}
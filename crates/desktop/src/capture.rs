@@ -0,0 +1,206 @@
+//! Quick capture.
+//!
+//! A global hotkey (Ctrl+Shift+Space, Cmd+Shift+Space on macOS) opens a small
+//! always-on-top window with a single text field. Enter adds the text as a
+//! node to today's journal page of the active space, exactly like
+//! `flow add`; Escape or an empty submit closes the window. The hotkey works
+//! regardless of which application is focused.
+
+use flow_core::config::Config;
+use flow_core::space::Space;
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::keyboard::{self, key, Key};
+use iced::widget::{column, text, text_input};
+use iced::window::{self, Level, Position};
+use iced::{Element, Size, Subscription, Task};
+use miette::{miette, IntoDiagnostic, Result};
+
+/// Size of the capture window.
+const WINDOW_SIZE: Size = Size::new(560.0, 64.0);
+
+/// Messages of the quick capture window.
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// The global hotkey was pressed.
+    HotKey,
+    /// The text of the field changed.
+    Input(String),
+    /// Enter was pressed in the field.
+    Submit,
+    /// Escape was pressed.
+    Cancel,
+    /// A window was closed.
+    Closed(window::Id),
+}
+
+/// State of the quick capture.
+///
+/// # Fields
+///
+/// - `_hotkeys` (`GlobalHotKeyManager`) - Keeps the hotkey registered while running.
+/// - `window` (`Option<window::Id>`) - The open capture window, if any.
+/// - `input` (`String`) - Text typed so far.
+/// - `error` (`Option<String>`) - Why the last capture failed.
+pub struct QuickCapture {
+    _hotkeys: GlobalHotKeyManager,
+    window: Option<window::Id>,
+    input: String,
+    error: Option<String>,
+}
+
+/// Adds a node to today's journal page of the active space.
+///
+/// # Arguments
+///
+/// - `content` (`&str`) - Content of the node.
+///
+/// # Errors
+///
+/// Returns an error if no space is active or it can't be loaded or saved.
+pub fn capture(content: &str) -> Result<()> {
+    let config = Config::load()?;
+    let active = config
+        .get_active_space()
+        .ok_or_else(|| miette!("No active space, run `flow init` first"))?;
+    let mut space = Space::load(&active.path)?;
+    space.set_author(config.author());
+    space.add(content)
+}
+
+impl QuickCapture {
+    /// Registers the quick capture hotkey.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hotkey can't be registered, e.g. because another
+    /// application already uses it.
+    pub fn new() -> Result<Self> {
+        let hotkeys = GlobalHotKeyManager::new().into_diagnostic()?;
+        hotkeys.register(hotkey()).into_diagnostic()?;
+        Ok(Self {
+            _hotkeys: hotkeys,
+            window: None,
+            input: String::new(),
+            error: None,
+        })
+    }
+
+    /// Returns the open capture window, if any.
+    pub fn window(&self) -> Option<window::Id> {
+        self.window
+    }
+
+    pub fn title(&self) -> String {
+        "Flow Quick Capture".to_string()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::HotKey => match self.window {
+                Some(id) => window::gain_focus(id),
+                None => {
+                    let (id, open) = window::open(window::Settings {
+                        size: WINDOW_SIZE,
+                        position: Position::Centered,
+                        resizable: false,
+                        decorations: false,
+                        level: Level::AlwaysOnTop,
+                        ..window::Settings::default()
+                    });
+                    self.window = Some(id);
+                    open.discard().chain(text_input::focus(input_id()))
+                }
+            },
+            Message::Input(input) => {
+                self.input = input;
+                self.error = None;
+                Task::none()
+            }
+            Message::Submit => {
+                let content = self.input.trim().to_string();
+                if !content.is_empty() {
+                    if let Err(error) = capture(&content) {
+                        self.error = Some(error.to_string());
+                        return Task::none();
+                    }
+                }
+                self.close()
+            }
+            Message::Cancel => self.close(),
+            Message::Closed(id) => {
+                if self.window == Some(id) {
+                    self.window = None;
+                    self.input.clear();
+                    self.error = None;
+                }
+                Task::none()
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let input = text_input("Add to today's journal…", &self.input)
+            .id(input_id())
+            .on_input(Message::Input)
+            .on_submit(Message::Submit)
+            .padding(12)
+            .size(18);
+
+        match &self.error {
+            Some(error) => column![input, text(error).size(12)].padding(4).into(),
+            None => column![input].padding(4).into(),
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::batch([
+            Subscription::run(hotkey_events),
+            window::close_events().map(Message::Closed),
+            keyboard::on_key_press(|key, _| {
+                matches!(key, Key::Named(key::Named::Escape)).then_some(Message::Cancel)
+            }),
+        ])
+    }
+
+    /// Closes the capture window, if open.
+    fn close(&mut self) -> Task<Message> {
+        match self.window {
+            Some(id) => window::close(id),
+            None => Task::none(),
+        }
+    }
+}
+
+/// Returns the quick capture hotkey of the platform.
+fn hotkey() -> HotKey {
+    let modifier = if cfg!(target_os = "macos") {
+        Modifiers::SUPER
+    } else {
+        Modifiers::CONTROL
+    };
+    HotKey::new(Some(modifier | Modifiers::SHIFT), Code::Space)
+}
+
+/// Returns the id of the capture text field.
+fn input_id() -> text_input::Id {
+    text_input::Id::new("quick-capture")
+}
+
+/// Streams presses of the global hotkey.
+fn hotkey_events() -> impl iced::futures::Stream<Item = Message> {
+    iced::stream::channel(16, |mut output| async move {
+        let (sender, mut presses) = mpsc::unbounded();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state == HotKeyState::Pressed {
+                let _ = sender.unbounded_send(());
+            }
+        }));
+
+        while presses.next().await.is_some() {
+            let _ = output.send(Message::HotKey).await;
+        }
+    })
+}
@@ -0,0 +1,452 @@
+//! Graph view.
+//!
+//! Draws the pages of the active space as a force-directed graph with an edge
+//! for every wikilink and embed, taken from the link index (the same
+//! [`links::graph`] `flow export dot` writes). Typing in the search field
+//! highlights matching pages and Enter opens the first match. Hovering a page
+//! previews its first lines, clicking it opens the page next to the graph.
+
+use std::time::Duration;
+
+use flow_core::config::Config;
+use flow_core::links::{self, LinkGraph};
+use flow_core::space::Space;
+use iced::mouse;
+use iced::widget::canvas::{self, event, Cache, Canvas, Geometry, Path, Stroke, Text};
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{
+    Color, Element, Length, Point, Rectangle, Renderer, Size, Subscription, Task, Theme, Vector,
+};
+use miette::{miette, Result};
+
+/// Radius of a page on screen, in pixels.
+const NODE_RADIUS: f32 = 5.0;
+
+/// Distance linked pages settle at, in layout units.
+const IDEAL_DISTANCE: f32 = 60.0;
+
+/// Factor the layout temperature cools by on each step.
+const COOLING: f32 = 0.97;
+
+/// Temperature below which the layout counts as settled.
+const MIN_TEMPERATURE: f32 = 0.5;
+
+/// Interval of layout steps while the layout settles.
+const TICK: Duration = Duration::from_millis(16);
+
+/// Graphs with up to this many pages label every page.
+const LABEL_LIMIT: usize = 60;
+
+/// Number of lines shown when previewing a hovered page.
+const PREVIEW_LINES: usize = 12;
+
+/// Messages of the graph view.
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// The search text changed.
+    Search(String),
+    /// Enter was pressed in the search field.
+    OpenFirst,
+    /// The layout should take a step.
+    Tick,
+    /// The cursor moved onto a page or off all pages.
+    Hover(Option<usize>),
+    /// A page was clicked.
+    Open(usize),
+    /// The open page was closed.
+    Close,
+}
+
+/// Force-directed layout of a graph (Fruchterman-Reingold).
+///
+/// # Fields
+///
+/// - `positions` (`Vec<Vector>`) - Position of each node, in layout units.
+/// - `temperature` (`f32`) - Maximum distance a node may move per step.
+struct Layout {
+    positions: Vec<Vector>,
+    temperature: f32,
+}
+
+impl Layout {
+    /// Spreads the nodes on a golden-angle spiral, so no two start at the same spot.
+    fn new(count: usize) -> Self {
+        let positions = (0..count)
+            .map(|i| {
+                let radius = IDEAL_DISTANCE * (i as f32).sqrt();
+                let angle = i as f32 * 2.399_963;
+                Vector::new(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+        Self {
+            positions,
+            temperature: IDEAL_DISTANCE,
+        }
+    }
+
+    /// Checks whether the nodes stopped moving noticeably.
+    fn is_settled(&self) -> bool {
+        self.temperature < MIN_TEMPERATURE
+    }
+
+    /// Moves every node one step along the forces acting on it.
+    ///
+    /// All nodes repel each other, linked nodes attract each other and a weak
+    /// gravity keeps unlinked pages from drifting off.
+    fn step(&mut self, graph: &LinkGraph) {
+        let count = self.positions.len();
+        let mut forces = vec![Vector::new(0.0, 0.0); count];
+
+        for a in 0..count {
+            for b in a + 1..count {
+                let delta = self.positions[a] - self.positions[b];
+                let distance = length(delta).max(0.01);
+                let force = delta * (IDEAL_DISTANCE * IDEAL_DISTANCE / (distance * distance));
+                forces[a] = forces[a] + force;
+                forces[b] = forces[b] - force;
+            }
+        }
+        for edge in &graph.edges {
+            let delta = self.positions[edge.from] - self.positions[edge.to];
+            let force = delta * (length(delta) / IDEAL_DISTANCE);
+            forces[edge.from] = forces[edge.from] - force;
+            forces[edge.to] = forces[edge.to] + force;
+        }
+
+        for (position, force) in self.positions.iter_mut().zip(forces) {
+            let force = force - *position * 0.05;
+            let magnitude = length(force);
+            if magnitude > 0.0 {
+                *position = *position + force * (magnitude.min(self.temperature) / magnitude);
+            }
+        }
+        self.temperature *= COOLING;
+    }
+}
+
+/// Maps layout positions onto the canvas, fitting the whole graph.
+///
+/// # Fields
+///
+/// - `center` (`Vector`) - Layout position drawn at the middle of the canvas.
+/// - `middle` (`Point`) - Middle of the canvas.
+/// - `scale` (`f32`) - Pixels per layout unit.
+struct Projection {
+    center: Vector,
+    middle: Point,
+    scale: f32,
+}
+
+impl Projection {
+    /// Returns the canvas point of a layout position.
+    fn apply(&self, position: Vector) -> Point {
+        self.middle + (position - self.center) * self.scale
+    }
+}
+
+/// State of the graph view.
+///
+/// # Fields
+///
+/// - `space` (`Space`) - The active space.
+/// - `graph` (`LinkGraph`) - Pages and links of the space.
+/// - `layout` (`Layout`) - Positions of the pages.
+/// - `cache` (`Cache`) - Drawn graph, cleared whenever it changes.
+/// - `search` (`String`) - Text of the search field.
+/// - `hovered` (`Option<usize>`) - Page under the cursor.
+/// - `preview` (`Option<String>`) - First lines of the hovered page.
+/// - `opened` (`Option<(usize, String)>`) - Open page and its content.
+pub struct GraphView {
+    space: Space,
+    graph: LinkGraph,
+    layout: Layout,
+    cache: Cache,
+    search: String,
+    hovered: Option<usize>,
+    preview: Option<String>,
+    opened: Option<(usize, String)>,
+}
+
+impl GraphView {
+    /// Loads the graph of the active space.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no space is active or it or its index can't be loaded.
+    pub fn load() -> Result<Self> {
+        let config = Config::load()?;
+        let active = config
+            .get_active_space()
+            .ok_or_else(|| miette!("No active space, run `flow init` first"))?;
+        let space = Space::load(&active.path)?;
+        let graph = links::graph(&space.page_index()?);
+
+        Ok(Self {
+            layout: Layout::new(graph.nodes.len()),
+            space,
+            graph,
+            cache: Cache::new(),
+            search: String::new(),
+            hovered: None,
+            preview: None,
+            opened: None,
+        })
+    }
+
+    /// Returns the title of the graph window.
+    pub fn title(&self) -> String {
+        format!("Flow - {}", self.space.name())
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Search(search) => self.search = search,
+            Message::OpenFirst => {
+                if let Some(node) = (0..self.graph.nodes.len()).find(|&node| self.matches(node)) {
+                    self.open(node);
+                }
+            }
+            Message::Tick => self.layout.step(&self.graph),
+            Message::Hover(node) => {
+                self.hovered = node;
+                self.preview = node.map(|node| {
+                    self.read(node)
+                        .lines()
+                        .take(PREVIEW_LINES)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                });
+            }
+            Message::Open(node) => self.open(node),
+            Message::Close => self.opened = None,
+        }
+        self.cache.clear();
+        Task::none()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let search = text_input("Search pages…", &self.search)
+            .on_input(Message::Search)
+            .on_submit(Message::OpenFirst)
+            .padding(8);
+        let graph = Canvas::new(self).width(Length::Fill).height(Length::Fill);
+
+        let panel: Element<'_, Message> = match (&self.opened, self.hovered) {
+            (Some((node, content)), _) => column![
+                row![
+                    text(&self.graph.nodes[*node].name)
+                        .size(20)
+                        .width(Length::Fill),
+                    button("Close").on_press(Message::Close),
+                ],
+                scrollable(text(content)).height(Length::Fill),
+            ]
+            .spacing(8)
+            .into(),
+            (None, Some(node)) => column![
+                text(&self.graph.nodes[node].name).size(20),
+                text(self.preview.as_deref().unwrap_or_default()).size(13),
+            ]
+            .spacing(8)
+            .into(),
+            (None, None) => text(format!(
+                "{} pages, {} links",
+                self.graph.nodes.len(),
+                self.graph.edges.len()
+            ))
+            .into(),
+        };
+
+        column![
+            search,
+            row![
+                graph,
+                container(panel)
+                    .width(Length::Fixed(320.0))
+                    .height(Length::Fill)
+                    .padding(12),
+            ],
+        ]
+        .spacing(4)
+        .padding(4)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.layout.is_settled() {
+            Subscription::none()
+        } else {
+            iced::time::every(TICK).map(|_| Message::Tick)
+        }
+    }
+
+    /// Opens a page next to the graph.
+    fn open(&mut self, node: usize) {
+        self.opened = Some((node, self.read(node)));
+    }
+
+    /// Reads the content of a page, or why it can't be read.
+    fn read(&self, node: usize) -> String {
+        match self.space.read_page(&self.graph.nodes[node].id) {
+            Ok(content) => content.unwrap_or_default(),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    /// Checks whether a page matches the search, by name or tag.
+    fn matches(&self, node: usize) -> bool {
+        let search = self.search.trim().to_lowercase();
+        let node = &self.graph.nodes[node];
+        search.is_empty()
+            || node.name.to_lowercase().contains(&search)
+            || node
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(search.trim_start_matches('#')))
+    }
+
+    /// Returns the projection fitting the graph onto a canvas of the given size.
+    fn projection(&self, size: Size) -> Projection {
+        let (mut min, mut max) = (
+            Vector::new(f32::MAX, f32::MAX),
+            Vector::new(f32::MIN, f32::MIN),
+        );
+        for position in &self.layout.positions {
+            min = Vector::new(min.x.min(position.x), min.y.min(position.y));
+            max = Vector::new(max.x.max(position.x), max.y.max(position.y));
+        }
+        if self.layout.positions.is_empty() {
+            (min, max) = (Vector::new(0.0, 0.0), Vector::new(0.0, 0.0));
+        }
+
+        let padding = 4.0 * NODE_RADIUS + 40.0;
+        let scale = ((size.width - 2.0 * padding) / (max.x - min.x).max(1.0))
+            .min((size.height - 2.0 * padding) / (max.y - min.y).max(1.0))
+            .clamp(0.05, 2.0);
+        Projection {
+            center: (min + max) * 0.5,
+            middle: Point::new(size.width / 2.0, size.height / 2.0),
+            scale,
+        }
+    }
+
+    /// Returns the page drawn at a canvas point, if any.
+    fn node_at(&self, size: Size, point: Point) -> Option<usize> {
+        let projection = self.projection(size);
+        self.layout
+            .positions
+            .iter()
+            .map(|position| projection.apply(*position).distance(point))
+            .enumerate()
+            .filter(|(_, distance)| *distance <= NODE_RADIUS + 4.0)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(node, _)| node)
+    }
+}
+
+impl canvas::Program<Message> for GraphView {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let node = cursor
+            .position_in(bounds)
+            .and_then(|point| self.node_at(bounds.size(), point));
+
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => match node {
+                Some(node) => (event::Status::Captured, Some(Message::Open(node))),
+                None => (event::Status::Ignored, None),
+            },
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. } | mouse::Event::CursorLeft)
+                if node != self.hovered =>
+            {
+                (event::Status::Captured, Some(Message::Hover(node)))
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let geometry = self.cache.draw(renderer, bounds.size(), |frame| {
+            let palette = theme.palette();
+            let faded = Color {
+                a: 0.2,
+                ..palette.text
+            };
+            let projection = self.projection(frame.size());
+            let selected = self.hovered.or(self.opened.as_ref().map(|(node, _)| *node));
+
+            for edge in &self.graph.edges {
+                let touches = selected.is_some_and(|node| edge.from == node || edge.to == node);
+                let color = if touches { palette.primary } else { faded };
+                frame.stroke(
+                    &Path::line(
+                        projection.apply(self.layout.positions[edge.from]),
+                        projection.apply(self.layout.positions[edge.to]),
+                    ),
+                    Stroke::default().with_width(1.0).with_color(color),
+                );
+            }
+
+            let searching = !self.search.trim().is_empty();
+            for (node, page) in self.graph.nodes.iter().enumerate() {
+                let center = projection.apply(self.layout.positions[node]);
+                let matches = self.matches(node);
+                let color = if selected == Some(node) {
+                    palette.primary
+                } else if !matches {
+                    faded
+                } else if page.journal {
+                    palette.success
+                } else {
+                    palette.text
+                };
+                frame.fill(&Path::circle(center, NODE_RADIUS), color);
+
+                let labelled = selected == Some(node)
+                    || (matches && (searching || self.graph.nodes.len() <= LABEL_LIMIT));
+                if labelled {
+                    frame.fill_text(Text {
+                        content: page.name.clone(),
+                        position: center + Vector::new(NODE_RADIUS + 3.0, -6.0),
+                        color,
+                        size: 12.0.into(),
+                        ..Text::default()
+                    });
+                }
+            }
+        });
+        vec![geometry]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if self.hovered.is_some() && cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// Returns the length of a vector.
+fn length(vector: Vector) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
+}
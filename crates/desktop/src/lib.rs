@@ -1,3 +1,20 @@
+//! Flow desktop application.
+//!
+//! `flow desktop` opens the link graph of the active space (see [`graph`])
+//! and registers the global quick-capture hotkey (see [`capture`]), so
+//! thoughts can be captured into today's journal page while another
+//! application is focused.
+
+pub mod app;
+pub mod capture;
+pub mod graph;
+
+/// Runs the desktop application.
+///
+/// # Errors
+///
+/// Returns an error if the hotkey can't be registered or the window system
+/// fails.
 pub fn run() -> miette::Result<()> {
-    Ok(())
+    app::run()
 }
@@ -0,0 +1,20 @@
+//! Regenerates `include/flow.h` from the `extern "C"` functions.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("set by cargo"));
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml is valid");
+    // A failing generation keeps the committed header, the library still builds
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(crate_dir.join("include").join("flow.h"));
+        }
+        Err(error) => println!("cargo:warning=Failed to generate flow.h: {}", error),
+    }
+}
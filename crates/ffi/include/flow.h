@@ -0,0 +1,97 @@
+#ifndef FLOW_H
+#define FLOW_H
+
+/* Generated by cbindgen from crates/ffi, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Opaque handle of an opened space.
+ */
+typedef struct FlowSpace FlowSpace;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Opens an existing space.
+ *
+ * `path` must be a valid NUL-terminated string. Returns `NULL` on failure.
+ */
+FlowSpace *flow_space_open(const char *path);
+
+/**
+ * Opens the active space of the Flow configuration.
+ *
+ * Returns `NULL` on failure or if no space is active.
+ */
+FlowSpace *flow_space_open_active(void);
+
+/**
+ * Initializes a new space and opens it.
+ *
+ * `name` may be `NULL` to derive it from the path. Returns `NULL` on failure.
+ */
+FlowSpace *flow_space_init(const char *path, const char *name);
+
+/**
+ * Releases a space handle, `NULL` is ignored.
+ */
+void flow_space_free(FlowSpace *space);
+
+/**
+ * Adds content to today's journal page.
+ *
+ * Returns `0` on success, `-1` on failure.
+ */
+int32_t flow_add(FlowSpace *space, const char *content);
+
+/**
+ * Searches the pages of a space, best match first.
+ *
+ * Returns a JSON array of ranked hits, `NULL` on failure.
+ */
+char *flow_search(FlowSpace *space, const char *query, uintptr_t limit);
+
+/**
+ * Lists the pages of a space.
+ *
+ * Returns a JSON array of pages, `NULL` on failure.
+ */
+char *flow_list_pages(FlowSpace *space);
+
+/**
+ * Reads a page by name or alias.
+ *
+ * Returns a JSON object with `name`, `id` and `content`, `NULL` on failure
+ * or if the page doesn't exist.
+ */
+char *flow_read_page(FlowSpace *space, const char *name);
+
+/**
+ * Releases a string returned by this library, `NULL` is ignored.
+ */
+void flow_string_free(char *string);
+
+/**
+ * Returns the message of the last failure on the calling thread.
+ *
+ * `NULL` if nothing failed yet. Valid until the next call on this thread;
+ * don't free it.
+ */
+const char *flow_last_error(void);
+
+/**
+ * Returns the static version string of the library; don't free it.
+ */
+const char *flow_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FLOW_H */
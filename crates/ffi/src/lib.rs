@@ -0,0 +1,367 @@
+//! Flow C ABI
+//!
+//! A stable `extern "C"` interface to `flow-core`, so editor plugins (a VS
+//! Code native module, Neovim through LuaJIT FFI) and other languages can
+//! embed the core instead of spawning `flow` processes. The header
+//! `include/flow.h` is generated from this file by cbindgen.
+//!
+//! Conventions:
+//!
+//! * Spaces are opaque `FlowSpace` handles, released with `flow_space_free`.
+//! * Strings passed in are NUL-terminated UTF-8 and borrowed for the call.
+//! * Structured results are returned as NUL-terminated JSON strings owned by
+//!   the caller and released with `flow_string_free`.
+//! * Failures return `NULL` or `-1`; `flow_last_error` describes the last
+//!   failure of the calling thread.
+//! * Panics never cross the boundary, they are reported as failures.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+
+use flow_core::config::Config;
+use flow_core::index::PageIndex;
+use flow_core::search::SearchScope;
+use flow_core::space::Space;
+use miette::{miette, IntoDiagnostic, Result};
+use serde::Serialize;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Opaque handle of an opened space.
+pub struct FlowSpace {
+    space: Space,
+}
+
+/// A page as listed by `flow_list_pages`.
+///
+/// # Fields
+///
+/// - `name` (`&str`) - Name of the page.
+/// - `id` (`&str`) - Id of the page.
+/// - `aliases` (`&[String]`) - Aliases of the page.
+/// - `tags` (`&[String]`) - Tags of the page.
+/// - `created` (`i64`) - Creation as unix timestamp in seconds.
+/// - `modified` (`i64`) - Last modification as unix timestamp in seconds.
+#[derive(Serialize)]
+struct ListedPage<'a> {
+    name: &'a str,
+    id: &'a str,
+    aliases: &'a [String],
+    tags: &'a [String],
+    created: i64,
+    modified: i64,
+}
+
+/// A page as returned by `flow_read_page`.
+///
+/// # Fields
+///
+/// - `name` (`String`) - Name the page was requested by.
+/// - `id` (`String`) - Id of the page.
+/// - `content` (`String`) - Markdown content of the page.
+#[derive(Serialize)]
+struct ReadPage {
+    name: String,
+    id: String,
+    content: String,
+}
+
+/// Opens an existing space.
+///
+/// # Arguments
+///
+/// - `path` (`*const c_char`) - Path of the space.
+///
+/// # Returns
+///
+/// - `*mut FlowSpace` - Handle of the space, `NULL` on failure.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn flow_space_open(path: *const c_char) -> *mut FlowSpace {
+    guard(ptr::null_mut(), || {
+        let path = PathBuf::from(str_arg(path, "path")?);
+        open(Space::load(&path)?)
+    })
+}
+
+/// Opens the active space of the Flow configuration.
+///
+/// # Returns
+///
+/// - `*mut FlowSpace` - Handle of the space, `NULL` on failure or if no space is active.
+#[no_mangle]
+pub extern "C" fn flow_space_open_active() -> *mut FlowSpace {
+    guard(ptr::null_mut(), || {
+        let config = Config::load()?;
+        let space = config
+            .get_active_space()
+            .ok_or_else(|| miette!("No active space"))?;
+        open(Space::load(&space.path)?)
+    })
+}
+
+/// Initializes a new space and opens it.
+///
+/// # Arguments
+///
+/// - `path` (`*const c_char`) - Path of the new space.
+/// - `name` (`*const c_char`) - Name of the space, `NULL` to derive it from the path.
+///
+/// # Returns
+///
+/// - `*mut FlowSpace` - Handle of the space, `NULL` on failure.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string, `name` one or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn flow_space_init(
+    path: *const c_char,
+    name: *const c_char,
+) -> *mut FlowSpace {
+    guard(ptr::null_mut(), || {
+        let path = PathBuf::from(str_arg(path, "path")?);
+        let name = if name.is_null() {
+            None
+        } else {
+            Some(str_arg(name, "name")?.to_string())
+        };
+        open(Space::init(&path, name.as_ref(), false)?)
+    })
+}
+
+/// Releases a space handle.
+///
+/// # Arguments
+///
+/// - `space` (`*mut FlowSpace`) - Handle to release, `NULL` is ignored.
+///
+/// # Safety
+///
+/// `space` must come from one of the `flow_space_*` functions and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn flow_space_free(space: *mut FlowSpace) {
+    if !space.is_null() {
+        drop(Box::from_raw(space));
+    }
+}
+
+/// Adds content to today's journal page.
+///
+/// # Arguments
+///
+/// - `space` (`*mut FlowSpace`) - Space to add to.
+/// - `content` (`*const c_char`) - Markdown content to add.
+///
+/// # Returns
+///
+/// - `i32` - `0` on success, `-1` on failure.
+///
+/// # Safety
+///
+/// `space` must be a live handle and `content` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn flow_add(space: *mut FlowSpace, content: *const c_char) -> i32 {
+    guard(-1, || {
+        let space = space_arg(space)?;
+        space.space.add(str_arg(content, "content")?)?;
+        Ok(0)
+    })
+}
+
+/// Searches the pages of a space, best match first.
+///
+/// # Arguments
+///
+/// - `space` (`*mut FlowSpace`) - Space to search.
+/// - `query` (`*const c_char`) - Search query.
+/// - `limit` (`usize`) - Maximum number of hits.
+///
+/// # Returns
+///
+/// - `*mut c_char` - JSON array of ranked hits, `NULL` on failure.
+///
+/// # Safety
+///
+/// `space` must be a live handle and `query` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn flow_search(
+    space: *mut FlowSpace,
+    query: *const c_char,
+    limit: usize,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let space = space_arg(space)?;
+        let hits =
+            space
+                .space
+                .search_ranked(str_arg(query, "query")?, &SearchScope::default(), limit)?;
+        json(&hits)
+    })
+}
+
+/// Lists the pages of a space.
+///
+/// # Arguments
+///
+/// - `space` (`*mut FlowSpace`) - Space to list.
+///
+/// # Returns
+///
+/// - `*mut c_char` - JSON array of pages, `NULL` on failure.
+///
+/// # Safety
+///
+/// `space` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn flow_list_pages(space: *mut FlowSpace) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let space = space_arg(space)?;
+        let index = PageIndex::load(space.space.path())?;
+        let pages: Vec<ListedPage> = index
+            .pages()
+            .map(|entry| ListedPage {
+                name: &entry.name,
+                id: &entry.id,
+                aliases: &entry.aliases,
+                tags: &entry.tags,
+                created: entry.created,
+                modified: entry.modified,
+            })
+            .collect();
+        json(&pages)
+    })
+}
+
+/// Reads a page by name or alias.
+///
+/// # Arguments
+///
+/// - `space` (`*mut FlowSpace`) - Space to read from.
+/// - `name` (`*const c_char`) - Name or alias of the page.
+///
+/// # Returns
+///
+/// - `*mut c_char` - JSON object with `name`, `id` and `content`, `NULL` on failure or if the page doesn't exist.
+///
+/// # Safety
+///
+/// `space` must be a live handle and `name` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn flow_read_page(space: *mut FlowSpace, name: *const c_char) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let space = space_arg(space)?;
+        let name = str_arg(name, "name")?;
+        let id = PageIndex::load(space.space.path())?.resolve(name);
+        let content = space
+            .space
+            .read_page(&id)?
+            .ok_or_else(|| miette!("Page '{}' not found", name))?;
+        json(&ReadPage {
+            name: name.to_string(),
+            id,
+            content,
+        })
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Arguments
+///
+/// - `string` (`*mut c_char`) - String to release, `NULL` is ignored.
+///
+/// # Safety
+///
+/// `string` must come from this library and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn flow_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Returns the message of the last failure on the calling thread.
+///
+/// # Returns
+///
+/// - `*const c_char` - Error message, `NULL` if nothing failed yet. Valid until the next call on this thread; don't free it.
+#[no_mangle]
+pub extern "C" fn flow_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Returns the version of the library.
+///
+/// # Returns
+///
+/// - `*const c_char` - Static version string; don't free it.
+#[no_mangle]
+pub extern "C" fn flow_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Runs a call, turning errors and panics into the failure value and recording the error.
+fn guard<T>(failure: T, call: impl FnOnce() -> Result<T>) -> T {
+    let message = match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(Ok(value)) => return value,
+        // The message and its causes, without the terminal report formatting
+        Ok(Err(error)) => error
+            .chain()
+            .map(|cause| cause.to_string())
+            .collect::<Vec<_>>()
+            .join(": "),
+        Err(panic) => panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Flow panicked".to_string()),
+    };
+    set_last_error(&message);
+    failure
+}
+
+/// Records the last error of the calling thread.
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+/// Boxes a space into a handle.
+fn open(space: Space) -> Result<*mut FlowSpace> {
+    Ok(Box::into_raw(Box::new(FlowSpace { space })))
+}
+
+/// Borrows the space behind a handle.
+unsafe fn space_arg<'a>(space: *mut FlowSpace) -> Result<&'a mut FlowSpace> {
+    space
+        .as_mut()
+        .ok_or_else(|| miette!("Argument 'space' is null"))
+}
+
+/// Borrows a string argument.
+unsafe fn str_arg<'a>(string: *const c_char, name: &str) -> Result<&'a str> {
+    if string.is_null() {
+        miette::bail!("Argument '{}' is null", name);
+    }
+    CStr::from_ptr(string).to_str().into_diagnostic()
+}
+
+/// Serializes a result into a caller-owned JSON string.
+fn json<T: Serialize + ?Sized>(value: &T) -> Result<*mut c_char> {
+    let json = serde_json::to_string(value).into_diagnostic()?;
+    Ok(CString::new(json).into_diagnostic()?.into_raw())
+}
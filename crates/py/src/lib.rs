@@ -0,0 +1,234 @@
+//! Flow Python Bindings
+//!
+//! Exposes spaces of `flow-core` to Python, so notes can be analyzed in
+//! notebooks and scripts without parsing markdown:
+//!
+//! ```python
+//! import flow
+//!
+//! space = flow.Space.active()
+//! for page in space.pages(tag="project"):
+//!     print(page["name"], len(space.backlinks(page["name"])))
+//! ```
+//!
+//! Pages, search hits and journal days are returned as plain dictionaries
+//! and lists with the same fields as the CLI's `--json` output. Failures
+//! raise `flow.FlowError`.
+
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use flow_core::config::Config;
+use flow_core::index::PageIndex;
+use flow_core::search::SearchScope;
+use flow_core::space::Space as CoreSpace;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+use serde_json::Value;
+
+create_exception!(flow, FlowError, PyException, "Raised when Flow fails.");
+
+/// A Flow space.
+///
+/// # Fields
+///
+/// - `space` (`CoreSpace`) - The opened space.
+#[pyclass(name = "Space", module = "flow", unsendable)]
+struct Space {
+    space: CoreSpace,
+}
+
+#[pymethods]
+impl Space {
+    /// Opens the space at a path.
+    #[staticmethod]
+    fn open(path: PathBuf) -> PyResult<Self> {
+        let space = CoreSpace::load(&path).map_err(error)?;
+        Ok(Self { space })
+    }
+
+    /// Opens the active space of the Flow configuration.
+    #[staticmethod]
+    fn active() -> PyResult<Self> {
+        let config = Config::load().map_err(error)?;
+        let path = config
+            .get_active_space()
+            .map(|space| space.path.clone())
+            .ok_or_else(|| FlowError::new_err("No active space"))?;
+        Self::open(path)
+    }
+
+    /// Initializes a new space at a path and opens it.
+    #[staticmethod]
+    #[pyo3(signature = (path, name = None, bare = false))]
+    fn init(path: PathBuf, name: Option<String>, bare: bool) -> PyResult<Self> {
+        let space = CoreSpace::init(&path, name.as_ref(), bare).map_err(error)?;
+        Ok(Self { space })
+    }
+
+    /// Name of the space.
+    #[getter]
+    fn name(&self) -> String {
+        self.space.name().to_string()
+    }
+
+    /// Path of the space.
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.space.path().to_path_buf()
+    }
+
+    /// Lists the pages of the space, optionally only those with a tag.
+    #[pyo3(signature = (tag = None))]
+    fn pages(&self, py: Python<'_>, tag: Option<&str>) -> PyResult<PyObject> {
+        let index = self.index()?;
+        let tag = tag.map(|tag| tag.trim_start_matches('#'));
+        let pages: Vec<_> = index
+            .pages()
+            .filter(|entry| {
+                tag.is_none_or(|tag| entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            })
+            .collect();
+        to_py(py, &pages)
+    }
+
+    /// Returns the index entry of a page by name or alias, `None` if it doesn't exist.
+    fn page(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        to_py(py, &self.index()?.find(name))
+    }
+
+    /// Reads the markdown content of a page by name or alias, `None` if it doesn't exist.
+    fn read(&self, name: &str) -> PyResult<Option<String>> {
+        let id = self.index()?.resolve(name);
+        self.space.read_page(&id).map_err(error)
+    }
+
+    /// Replaces the content of a page by name or alias, creating it if needed.
+    fn write(&mut self, name: &str, content: &str) -> PyResult<()> {
+        let id = self.index()?.resolve(name);
+        self.space.write_page(&id, content).map_err(error)
+    }
+
+    /// Adds a node to today's journal page, honoring the capture context.
+    fn add(&mut self, content: &str) -> PyResult<()> {
+        self.space.add(content).map_err(error)
+    }
+
+    /// Searches the space, best match first.
+    #[pyo3(signature = (
+        query,
+        limit = 20,
+        tags = None,
+        journal_only = false,
+        pages_only = false,
+        path = None,
+        since = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        limit: usize,
+        tags: Option<Vec<String>>,
+        journal_only: bool,
+        pages_only: bool,
+        path: Option<String>,
+        since: Option<NaiveDate>,
+    ) -> PyResult<PyObject> {
+        let scope = SearchScope {
+            journal_only,
+            pages_only,
+            tags: tags.unwrap_or_default(),
+            path,
+            since,
+        };
+        let hits = self
+            .space
+            .search_ranked(query, &scope, limit)
+            .map_err(error)?;
+        to_py(py, &hits)
+    }
+
+    /// Lists the pages linking to a page by name or alias.
+    fn backlinks(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        to_py(py, &self.index()?.backlinks(name))
+    }
+
+    /// Lists the journal pages between two dates, both inclusive and optional.
+    #[pyo3(signature = (since = None, until = None))]
+    fn journal(
+        &self,
+        py: Python<'_>,
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> PyResult<PyObject> {
+        to_py(py, &self.space.journal(since, until).map_err(error)?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Space(name={:?}, path={:?})",
+            self.space.name(),
+            self.space.path()
+        )
+    }
+}
+
+impl Space {
+    /// Loads the page index of the space.
+    fn index(&self) -> PyResult<PageIndex> {
+        PageIndex::load(self.space.path()).map_err(error)
+    }
+}
+
+/// Converts a Flow error into a Python exception.
+fn error(report: miette::Report) -> PyErr {
+    FlowError::new_err(report.to_string())
+}
+
+/// Converts a serializable value into Python dictionaries, lists and scalars.
+fn to_py<T: Serialize + ?Sized>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value).map_err(|e| FlowError::new_err(e.to_string()))?;
+    json_to_py(py, &value)
+}
+
+/// Converts a JSON value into the matching Python object.
+fn json_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(value) => value.into_py(py),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(value), _) => value.into_py(py),
+            (None, Some(value)) => value.into_py(py),
+            _ => number.as_f64().unwrap_or_default().into_py(py),
+        },
+        Value::String(value) => value.into_py(py),
+        Value::Array(items) => {
+            let list = PyList::empty_bound(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_py(py)
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in fields {
+                dict.set_item(key, json_to_py(py, value)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+/// The `flow` Python module.
+#[pymodule]
+fn flow(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("FlowError", m.py().get_type_bound::<FlowError>())?;
+    m.add_class::<Space>()?;
+    Ok(())
+}
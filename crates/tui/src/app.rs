@@ -0,0 +1,648 @@
+//! Journal browser.
+//!
+//! The sidebar shows the [`Calendar`] heatmap of the active space's journal
+//! and a key reference, the main pane the selected day's journal page or any
+//! page opened from the [`Palette`]. Tab moves the focus between calendar and
+//! page; on the page a cursor selects the line tasks are toggled and templates
+//! inserted at. Pages are shown rendered (see [`flow_core::display`]): tables,
+//! diagrams and task lists of fenced blocks, which the cursor steps through
+//! line by line while edits apply to the markdown they came from.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Days, Months, NaiveDate};
+use flow_core::cancel::CancellationToken;
+use flow_core::config::{Config, Locale};
+use flow_core::display::{DisplayLine, LineKind};
+use flow_core::journal;
+use flow_core::page;
+use flow_core::space::Space;
+use flow_core::sync::{self, SyncEvent};
+use miette::{miette, IntoDiagnostic, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Margin};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::calendar::{self, Calendar};
+use crate::palette::{Action, Entry, Palette};
+
+/// How long to wait for a key before checking on a running sync.
+const POLL: Duration = Duration::from_millis(250);
+
+/// Prefix of the names of template pages.
+const TEMPLATES: &str = "templates/";
+
+/// Keys listed below the calendar.
+const HELP: &[(&str, &str)] = &[
+    ("←→ h l", "day"),
+    ("↑↓ k j", "week"),
+    ("[ ]", "month"),
+    ("{ }", "year"),
+    ("n p", "next/prev entry"),
+    ("t", "today"),
+    ("tab", "focus page"),
+    ("x", "toggle task"),
+    ("^p", "palette"),
+    ("q", "quit"),
+];
+
+/// Pane receiving navigation keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Calendar,
+    Page,
+}
+
+/// A sync running in the background.
+///
+/// # Fields
+///
+/// - `remote` (`String`) - Name of the remote synced with.
+/// - `cancel` (`CancellationToken`) - Stops the sync.
+/// - `events` (`Receiver<Result<SyncEvent, String>>`) - Events of the sync, an error ends it.
+struct RunningSync {
+    remote: String,
+    cancel: CancellationToken,
+    events: Receiver<std::result::Result<SyncEvent, String>>,
+}
+
+/// State of the journal browser.
+///
+/// # Fields
+///
+/// - `space` (`Space`) - The active space.
+/// - `locale` (`Locale`) - First day of the week and date format.
+/// - `activity` (`BTreeMap<NaiveDate, usize>`) - Blocks written per journal day.
+/// - `today` (`NaiveDate`) - Current day.
+/// - `selected` (`NaiveDate`) - Day selected in the calendar.
+/// - `page` (`String`) - Id of the shown page.
+/// - `content` (`Option<String>`) - Content of the shown page, if it exists.
+/// - `display` (`Vec<DisplayLine>`) - The shown page rendered for the terminal.
+/// - `focus` (`Focus`) - Pane receiving navigation keys.
+/// - `cursor` (`usize`) - Line of the cursor in the rendered page.
+/// - `palette` (`Option<Palette>`) - The open command palette.
+/// - `sync` (`Option<RunningSync>`) - The running sync.
+/// - `status` (`Option<String>`) - Message shown below the page.
+pub struct App {
+    space: Space,
+    locale: Locale,
+    activity: BTreeMap<NaiveDate, usize>,
+    today: NaiveDate,
+    selected: NaiveDate,
+    page: String,
+    content: Option<String>,
+    display: Vec<DisplayLine>,
+    focus: Focus,
+    cursor: usize,
+    palette: Option<Palette>,
+    sync: Option<RunningSync>,
+    status: Option<String>,
+}
+
+impl App {
+    /// Opens the journal of the active space at today's page.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no space is active or it or its index can't be loaded.
+    pub fn load() -> Result<Self> {
+        let config = Config::load()?;
+        let active = config
+            .get_active_space()
+            .ok_or_else(|| miette!("No active space, run `flow init` first"))?;
+        let mut space = Space::load(&active.path)?;
+        space.set_author(config.author());
+        let today = space.today();
+
+        let mut app = Self {
+            locale: config.locale().clone(),
+            activity: space.journal_activity()?,
+            space,
+            today,
+            selected: today,
+            page: String::new(),
+            content: None,
+            display: Vec::new(),
+            focus: Focus::Calendar,
+            cursor: 0,
+            palette: None,
+            sync: None,
+            status: None,
+        };
+        app.select(today)?;
+        Ok(app)
+    }
+
+    /// Runs the browser until it is quit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the terminal fails or a page can't be read.
+    pub fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let result = self.event_loop(terminal);
+        if let Some(sync) = &self.sync {
+            sync.cancel.cancel();
+        }
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame)).into_diagnostic()?;
+
+            self.poll_sync()?;
+            if !event::poll(POLL).into_diagnostic()? {
+                continue;
+            }
+            let Event::Key(key) = event::read().into_diagnostic()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.palette.is_some() {
+                self.palette_key(key)?;
+                continue;
+            }
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('p') {
+                self.palette = Some(Palette::new(self.entries()?));
+                continue;
+            }
+
+            self.status = None;
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab => {
+                    self.focus = match self.focus {
+                        Focus::Calendar => Focus::Page,
+                        Focus::Page => Focus::Calendar,
+                    }
+                }
+                KeyCode::Char('x') if self.focus == Focus::Page => {
+                    self.run_action(Action::ToggleTask)?
+                }
+                _ if self.focus == Focus::Page => self.page_key(key),
+                _ => self.calendar_key(key)?,
+            }
+        }
+    }
+
+    /// Handles a navigation key while the calendar has focus.
+    fn calendar_key(&mut self, key: KeyEvent) -> Result<()> {
+        let selected = self.selected;
+        let target = match key.code {
+            KeyCode::Left | KeyCode::Char('h') => selected.checked_sub_days(Days::new(1)),
+            KeyCode::Right | KeyCode::Char('l') => selected.checked_add_days(Days::new(1)),
+            KeyCode::Up | KeyCode::Char('k') => selected.checked_sub_days(Days::new(7)),
+            KeyCode::Down | KeyCode::Char('j') => selected.checked_add_days(Days::new(7)),
+            KeyCode::Char('[') | KeyCode::PageUp => selected.checked_sub_months(Months::new(1)),
+            KeyCode::Char(']') | KeyCode::PageDown => selected.checked_add_months(Months::new(1)),
+            KeyCode::Char('{') => selected.checked_sub_months(Months::new(12)),
+            KeyCode::Char('}') => selected.checked_add_months(Months::new(12)),
+            KeyCode::Char('n') => selected
+                .succ_opt()
+                .and_then(|next| self.activity.range(next..).next())
+                .map(|(date, _)| *date),
+            KeyCode::Char('p') => self
+                .activity
+                .range(..selected)
+                .next_back()
+                .map(|(date, _)| *date),
+            KeyCode::Char('t') | KeyCode::Home => Some(self.today),
+            _ => None,
+        };
+        match target {
+            Some(date) => self.select(date),
+            None => Ok(()),
+        }
+    }
+
+    /// Handles a navigation key while the page has focus.
+    fn page_key(&mut self, key: KeyEvent) {
+        let lines = self.display.len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if self.cursor + 1 < lines => self.cursor += 1,
+            KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => self.cursor = lines.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    /// Handles a key while the palette is open.
+    fn palette_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(palette) = &mut self.palette else {
+            return Ok(());
+        };
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Enter => {
+                let action = palette.action();
+                self.palette = None;
+                if let Some(action) = action {
+                    self.status = None;
+                    self.run_action(action)?;
+                }
+            }
+            KeyCode::Up => palette.up(),
+            KeyCode::Down => palette.down(),
+            KeyCode::Char('p') if control => palette.up(),
+            KeyCode::Char('n') if control => palette.down(),
+            KeyCode::Backspace => palette.pop(),
+            KeyCode::Char(c) if !control => palette.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Lists the commands, templates and pages offered by the palette.
+    fn entries(&self) -> Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        if self.content.is_some() {
+            entries.push(Entry::new("Toggle task", "command", Action::ToggleTask));
+        }
+        match &self.sync {
+            Some(sync) => entries.push(Entry::new(
+                format!("Stop sync with {}", sync.remote),
+                "command",
+                Action::StopSync,
+            )),
+            None => entries.extend(
+                self.space
+                    .sync_settings()
+                    .remotes
+                    .iter()
+                    .filter(|(_, remote)| remote.address.is_some())
+                    .map(|(name, _)| {
+                        Entry::new(
+                            format!("Sync with {}", name),
+                            "command",
+                            Action::Sync(name.clone()),
+                        )
+                    }),
+            ),
+        }
+
+        let index = self.space.page_index()?;
+        for entry in index.pages() {
+            if let Some(template) = entry.name.strip_prefix(TEMPLATES) {
+                entries.push(Entry::new(
+                    format!("Insert {}", template),
+                    "template",
+                    Action::InsertTemplate(entry.id.clone()),
+                ));
+            }
+        }
+        // Newest journal days first, they are opened most often
+        let mut pages: Vec<_> = index.pages().collect();
+        pages.sort_by_key(|entry| std::cmp::Reverse(entry.modified));
+        entries.extend(
+            pages
+                .into_iter()
+                .map(|entry| Entry::new(&entry.name, "page", Action::Open(entry.id.clone()))),
+        );
+        Ok(entries)
+    }
+
+    /// Runs the action of a palette entry or key.
+    fn run_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Open(id) => {
+                self.open(&id)?;
+                self.focus = Focus::Page;
+            }
+            Action::NewPage(name) => {
+                self.space.create_pages(std::slice::from_ref(&name))?;
+                let id = self.space.page_id(&name);
+                self.open(&id)?;
+                self.focus = Focus::Page;
+                self.status = Some(format!("Created {}", name));
+            }
+            Action::ToggleTask => {
+                let Some(content) = &self.content else {
+                    return Ok(());
+                };
+                let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+                let source = self.display.get(self.cursor).map(|line| line.source);
+                let Some((source, toggled)) = source.and_then(|source| {
+                    let toggled = page::toggle_task(lines.get(source)?)?;
+                    Some((source, toggled))
+                }) else {
+                    self.status = Some("No task on this line".to_string());
+                    return Ok(());
+                };
+                lines[source] = toggled;
+                self.write(lines.join("\n"))?;
+            }
+            Action::InsertTemplate(id) => {
+                let template = self.space.read_page(&id)?.unwrap_or_default();
+                let date = if page::is_journal(&self.page) {
+                    self.selected
+                } else {
+                    self.today
+                };
+                let template = template
+                    .replace("{{date}}", &date.format("%Y-%m-%d").to_string())
+                    .replace("{{title}}", &page::name_from_id(&self.page));
+
+                let mut lines: Vec<&str> = self
+                    .content
+                    .as_deref()
+                    .unwrap_or_default()
+                    .lines()
+                    .collect();
+                let at = self.insertion_line().min(lines.len());
+                lines.splice(at..at, template.lines());
+                let content = lines.join("\n");
+                self.write(content)?;
+                self.status = Some(format!("Inserted {}", page::name_from_id(&id)));
+            }
+            Action::Sync(remote) => self.start_sync(remote)?,
+            Action::StopSync => {
+                if let Some(sync) = self.sync.take() {
+                    sync.cancel.cancel();
+                    self.status = Some(format!("Stopped sync with {}", sync.remote));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts syncing with a configured remote in the background.
+    ///
+    /// The sync works on its own copy of the space; pages it receives are
+    /// written to disk and picked up by reloading the space.
+    fn start_sync(&mut self, remote: String) -> Result<()> {
+        let address = self
+            .space
+            .sync_settings()
+            .remotes
+            .get(&remote)
+            .and_then(|remote| remote.address.clone())
+            .ok_or_else(|| miette!("Remote '{}' has no address", remote))?;
+        let addr = sync::resolve(&address)?;
+        let path: PathBuf = self.space.path().to_path_buf();
+        let cancel = CancellationToken::new();
+        let (sender, events) = mpsc::channel();
+
+        let token = cancel.clone();
+        let name = remote.clone();
+        thread::spawn(move || {
+            let result = Space::load(&path).and_then(|mut space| {
+                space.sync_connect(addr, Some(&name), &token, &mut |event| {
+                    let _ = sender.send(Ok(event));
+                })
+            });
+            if let Err(error) = result {
+                let _ = sender.send(Err(error.to_string()));
+            }
+        });
+
+        self.status = Some(format!("Connecting to {}", remote));
+        self.sync = Some(RunningSync {
+            remote,
+            cancel,
+            events,
+        });
+        Ok(())
+    }
+
+    /// Shows the events of a running sync and reloads pages it received.
+    fn poll_sync(&mut self) -> Result<()> {
+        let Some(sync) = &self.sync else {
+            return Ok(());
+        };
+        let mut received = false;
+        let mut ended = false;
+        loop {
+            match sync.events.try_recv() {
+                Ok(Ok(SyncEvent::Connected { peer })) => {
+                    self.status = Some(format!("Syncing with {} ({})", sync.remote, peer));
+                }
+                Ok(Ok(SyncEvent::Received {
+                    pages, conflicts, ..
+                })) => {
+                    received = true;
+                    self.status = Some(match conflicts.len() {
+                        0 => format!("Received {} pages from {}", pages.len(), sync.remote),
+                        count => format!(
+                            "{} conflicts with {}, see `flow sync --show-conflicts`",
+                            count, sync.remote
+                        ),
+                    });
+                }
+                Ok(Ok(SyncEvent::Disconnected { peer })) => {
+                    self.status = Some(format!("{} disconnected", peer));
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(error)) => {
+                    self.status = Some(format!("Sync failed: {}", error));
+                    ended = true;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    ended = true;
+                    break;
+                }
+            }
+        }
+
+        if ended {
+            self.sync = None;
+        }
+        if received {
+            let author = Config::load()?.author();
+            self.space = Space::load(self.space.path())?;
+            self.space.set_author(author);
+            self.activity = self.space.journal_activity()?;
+            let page = self.page.clone();
+            self.show(&page)?;
+        }
+        Ok(())
+    }
+
+    /// Selects a day and shows its journal page.
+    fn select(&mut self, date: NaiveDate) -> Result<()> {
+        self.selected = date;
+        let id = self.space.journal_page(date);
+        self.open(&id)
+    }
+
+    /// Shows a page with the cursor on its first line, selecting its day if it is a journal page.
+    fn open(&mut self, id: &str) -> Result<()> {
+        if page::is_journal(id) {
+            if let Some((first, last)) = journal::period(&page::name_from_id(id)) {
+                // Keep the selected day if the page covers it, e.g. on weekly pages
+                if self.selected < first || self.selected > last {
+                    self.selected = first;
+                }
+            }
+        }
+        self.cursor = 0;
+        self.show(id)
+    }
+
+    /// Reads and shows a page, keeping the cursor within it.
+    fn show(&mut self, id: &str) -> Result<()> {
+        self.content = self.space.read_page(id)?;
+        self.page = id.to_string();
+        self.render();
+        Ok(())
+    }
+
+    /// Renders the shown page, keeping the cursor within it.
+    fn render(&mut self) {
+        self.display = match &self.content {
+            Some(content) => self.space.display(content),
+            None => Vec::new(),
+        };
+        self.cursor = self.cursor.min(self.display.len().saturating_sub(1));
+    }
+
+    /// Returns the markdown line to insert below the cursor at.
+    ///
+    /// Inside a fenced block that is the line after the block, so the
+    /// inserted lines don't end up in its table or diagram.
+    fn insertion_line(&self) -> usize {
+        let Some(line) = self.display.get(self.cursor) else {
+            return 0;
+        };
+        if line.kind == LineKind::Text {
+            return line.source + 1;
+        }
+        self.display[self.cursor..]
+            .iter()
+            .find(|line| line.kind == LineKind::Text)
+            .map_or(usize::MAX, |line| line.source)
+    }
+
+    /// Replaces the content of the shown page, unless it is locked.
+    fn write(&mut self, content: String) -> Result<()> {
+        if self.space.is_locked(&self.page)? {
+            self.status = Some("Page is locked".to_string());
+            return Ok(());
+        }
+        self.space.write_page(&self.page, &content)?;
+        self.content = Some(content);
+        self.render();
+        if page::is_journal(&self.page) {
+            self.activity = self.space.journal_activity()?;
+        }
+        Ok(())
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [sidebar, main] =
+            Layout::horizontal([Constraint::Length(calendar::WIDTH + 4), Constraint::Min(0)])
+                .areas(frame.area());
+
+        let focused = |focus| {
+            if self.focus == focus {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            }
+        };
+
+        let block = Block::bordered()
+            .title(format!(" {} ", self.space.name()))
+            .border_style(focused(Focus::Calendar));
+        let inner = block.inner(sidebar).inner(Margin::new(1, 0));
+        frame.render_widget(block, sidebar);
+
+        let [calendar_area, help_area] =
+            Layout::vertical([Constraint::Length(calendar::HEIGHT + 1), Constraint::Min(0)])
+                .areas(inner);
+        frame.render_widget(
+            Calendar {
+                activity: &self.activity,
+                selected: self.selected,
+                today: self.today,
+                first_day: self.locale.first_day_of_week.weekday(),
+            },
+            calendar_area,
+        );
+        let help: Vec<Line> = HELP
+            .iter()
+            .map(|(keys, action)| {
+                Line::styled(
+                    format!("{:<7} {}", keys, action),
+                    Style::default().fg(Color::DarkGray),
+                )
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(help), help_area);
+
+        let name = page::name_from_id(&self.page);
+        let daily = journal::period(&name).is_some_and(|(first, last)| first == last);
+        let title = if page::is_journal(&self.page) && daily {
+            format!(
+                " {}, {} ",
+                self.selected.format("%A"),
+                self.locale.format_date(self.selected)
+            )
+        } else {
+            format!(" {} ", name)
+        };
+        let mut block = Block::bordered()
+            .title(title)
+            .border_style(focused(Focus::Page));
+        if let Some(status) = &self.status {
+            block = block.title_bottom(format!(" {} ", status));
+        }
+        let content = match &self.content {
+            Some(_) => {
+                let lines: Vec<Line> = self
+                    .display
+                    .iter()
+                    .enumerate()
+                    .map(|(index, line)| {
+                        let mut style = line_style(line.kind);
+                        if self.focus == Focus::Page && index == self.cursor {
+                            style = style.add_modifier(Modifier::REVERSED);
+                        }
+                        Line::styled(line.text.as_str(), style)
+                    })
+                    .collect();
+                // Keep the cursor line in view
+                let height = main.height.saturating_sub(2) as usize;
+                let scroll = (self.cursor + 1).saturating_sub(height) as u16;
+                Paragraph::new(lines).scroll((scroll, 0))
+            }
+            None => Paragraph::new("No page here yet, Ctrl-P to create or open one")
+                .style(Style::default().fg(Color::DarkGray)),
+        };
+        frame.render_widget(content.block(block).wrap(Wrap { trim: false }), main);
+
+        if let Some(palette) = &self.palette {
+            palette.render(frame, frame.area());
+        }
+    }
+}
+
+/// Returns the style of a rendered line.
+fn line_style(kind: LineKind) -> Style {
+    match kind {
+        LineKind::Text => Style::default(),
+        LineKind::Code => Style::default().fg(Color::Gray),
+        LineKind::Table { header: true } => Style::default().add_modifier(Modifier::BOLD),
+        LineKind::Table { header: false } => Style::default(),
+        LineKind::Diagram => Style::default().fg(Color::Cyan),
+        LineKind::Notice => Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC),
+        LineKind::Task { done: false } => Style::default().fg(Color::Yellow),
+        LineKind::Task { done: true } => Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::CROSSED_OUT),
+    }
+}
@@ -0,0 +1,132 @@
+//! Calendar heatmap.
+//!
+//! A month calendar whose days are shaded by how much was written in their
+//! journal page, like a contribution graph. Days without a journal page stay
+//! blank, today is underlined and the selected day is highlighted. Weeks
+//! start on the configured first day of the week.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::Widget;
+
+/// Width of the calendar: seven days of two digits and a space.
+pub const WIDTH: u16 = 7 * 3;
+
+/// Height of the calendar: title, weekdays and up to six weeks.
+pub const HEIGHT: u16 = 8;
+
+/// Background colors of the heatmap levels, from least to most written.
+const LEVELS: [Color; 4] = [
+    Color::Rgb(14, 68, 41),
+    Color::Rgb(0, 109, 50),
+    Color::Rgb(38, 166, 65),
+    Color::Rgb(57, 211, 83),
+];
+
+/// Month calendar shaded by journal activity.
+///
+/// # Fields
+///
+/// - `activity` (`&BTreeMap<NaiveDate, usize>`) - Blocks written per day.
+/// - `selected` (`NaiveDate`) - Highlighted day, its month is shown.
+/// - `today` (`NaiveDate`) - Underlined day.
+/// - `first_day` (`Weekday`) - Day shown in the first column.
+pub struct Calendar<'a> {
+    pub activity: &'a BTreeMap<NaiveDate, usize>,
+    pub selected: NaiveDate,
+    pub today: NaiveDate,
+    pub first_day: Weekday,
+}
+
+impl Calendar<'_> {
+    /// Returns the heatmap level of a day, `None` if it has no journal page.
+    ///
+    /// Levels are relative to the most written day of the shown month, so
+    /// quiet months still show their busier days.
+    fn level(&self, date: NaiveDate) -> Option<usize> {
+        let blocks = *self.activity.get(&date)?;
+        let max = self
+            .activity
+            .range(first_of_month(date)..last_of_month(date).succ_opt()?)
+            .map(|(_, blocks)| *blocks)
+            .max()
+            .unwrap_or(blocks)
+            .max(1);
+        Some(((blocks * LEVELS.len()).div_ceil(max)).clamp(1, LEVELS.len()) - 1)
+    }
+}
+
+impl Widget for Calendar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < WIDTH || area.height < 3 {
+            return;
+        }
+
+        let title = self.selected.format("%B %Y").to_string();
+        let x = area.x + (WIDTH.saturating_sub(title.len() as u16)) / 2;
+        buf.set_string(
+            x,
+            area.y,
+            &title,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        let weekdays: Vec<String> =
+            std::iter::successors(Some(self.first_day), |day| Some(day.succ()))
+                .take(7)
+                .map(|day| day.to_string()[..2].to_string())
+                .collect();
+        buf.set_string(
+            area.x,
+            area.y + 1,
+            weekdays.join(" "),
+            Style::default().fg(Color::DarkGray),
+        );
+
+        let first = first_of_month(self.selected);
+        let offset = (first.weekday().num_days_from_monday() + 7
+            - self.first_day.num_days_from_monday())
+            % 7;
+        let offset = offset as u16;
+        for (day, date) in first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+            .enumerate()
+        {
+            let cell = day as u16 + offset;
+            let (column, row) = (cell % 7, cell / 7);
+            let y = area.y + 2 + row;
+            if y >= area.bottom() {
+                break;
+            }
+
+            let mut style = match self.level(date) {
+                Some(level) => Style::default().fg(Color::White).bg(LEVELS[level]),
+                None => Style::default(),
+            };
+            if date == self.today {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            if date == self.selected {
+                style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            buf.set_string(area.x + column * 3, y, format!("{:>2}", date.day()), style);
+        }
+    }
+}
+
+/// Returns the first day of the month of a date.
+pub fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// Returns the last day of the month of a date.
+pub fn last_of_month(date: NaiveDate) -> NaiveDate {
+    first_of_month(date)
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.checked_sub_days(Days::new(1)))
+        .unwrap_or(date)
+}
@@ -1,3 +1,23 @@
+//! Flow terminal user interface.
+//!
+//! `flow tui` browses the journal of the active space: a calendar heatmap in
+//! the sidebar (see [`calendar`]) shows which days have entries and how much
+//! was written, and the keyboard jumps to any day's page (see [`app`]). The
+//! Ctrl-P [`palette`] opens pages and runs commands.
+
+pub mod app;
+pub mod calendar;
+pub mod palette;
+
+/// Runs the terminal user interface.
+///
+/// # Errors
+///
+/// Returns an error if the active space can't be loaded or the terminal fails.
 pub fn run() -> miette::Result<()> {
-    Ok(())
+    let app = app::App::load()?;
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
 }
@@ -0,0 +1,212 @@
+//! Command palette.
+//!
+//! Ctrl-P opens a palette over the journal browser listing the pages of the
+//! space, commands and templates. Typing filters and ranks them with the
+//! same [`fuzzy`] matching as the CLI pickers; if no page has the typed name,
+//! the palette offers to create it.
+
+use flow_core::fuzzy;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListState, Paragraph};
+use ratatui::Frame;
+
+/// What a palette entry does when chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Opens the page with the given id.
+    Open(String),
+    /// Creates a page with the given name and opens it.
+    NewPage(String),
+    /// Toggles the task on the cursor line of the open page.
+    ToggleTask,
+    /// Starts syncing with the configured remote of the given name.
+    Sync(String),
+    /// Stops the running sync.
+    StopSync,
+    /// Inserts the template page with the given id below the cursor line.
+    InsertTemplate(String),
+}
+
+/// An entry of the palette.
+///
+/// # Fields
+///
+/// - `label` (`String`) - Text the query is matched against.
+/// - `kind` (`&'static str`) - Kind shown next to the label, e.g. `page`.
+/// - `action` (`Action`) - What the entry does.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub label: String,
+    pub kind: &'static str,
+    pub action: Action,
+}
+
+impl Entry {
+    /// Creates an entry.
+    pub fn new(label: impl Into<String>, kind: &'static str, action: Action) -> Self {
+        Self {
+            label: label.into(),
+            kind,
+            action,
+        }
+    }
+}
+
+/// State of the open palette.
+///
+/// # Fields
+///
+/// - `query` (`String`) - Typed query.
+/// - `entries` (`Vec<Entry>`) - All entries, in the order shown for an empty query.
+/// - `results` (`Vec<(Entry, Vec<usize>)>`) - Matching entries, best first, with matched characters.
+/// - `selected` (`usize`) - Index of the highlighted result.
+pub struct Palette {
+    query: String,
+    entries: Vec<Entry>,
+    results: Vec<(Entry, Vec<usize>)>,
+    selected: usize,
+}
+
+impl Palette {
+    /// Opens a palette over the given entries.
+    pub fn new(entries: Vec<Entry>) -> Self {
+        let mut palette = Self {
+            query: String::new(),
+            entries,
+            results: Vec::new(),
+            selected: 0,
+        };
+        palette.filter();
+        palette
+    }
+
+    /// Appends a character to the query.
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.filter();
+    }
+
+    /// Removes the last character of the query.
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.filter();
+    }
+
+    /// Highlights the previous result.
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Highlights the next result.
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Returns the action of the highlighted result.
+    pub fn action(&self) -> Option<Action> {
+        self.results
+            .get(self.selected)
+            .map(|(entry, _)| entry.action.clone())
+    }
+
+    /// Ranks the entries by how well they match the query.
+    fn filter(&mut self) {
+        let mut results: Vec<(i64, usize, &Entry, Vec<usize>)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(order, entry)| {
+                let found = fuzzy::find(&self.query, &entry.label)?;
+                Some((found.score, order, entry, found.positions))
+            })
+            .collect();
+        results.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.results = results
+            .into_iter()
+            .map(|(_, _, entry, positions)| (entry.clone(), positions))
+            .collect();
+
+        let name = self.query.trim();
+        let exists = self.entries.iter().any(|entry| {
+            matches!(entry.action, Action::Open(_)) && entry.label.eq_ignore_ascii_case(name)
+        });
+        if !name.is_empty() && !exists {
+            self.results.push((
+                Entry::new(
+                    format!("New page \"{}\"", name),
+                    "command",
+                    Action::NewPage(name.to_string()),
+                ),
+                Vec::new(),
+            ));
+        }
+        self.selected = 0;
+    }
+
+    /// Draws the palette centered over an area.
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::bordered().title(" Go to page, command or template ");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [input, list] =
+            Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(inner);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("> ", Style::default().fg(Color::DarkGray)),
+                Span::raw(&self.query),
+                Span::styled("█", Style::default().fg(Color::DarkGray)),
+            ])),
+            input,
+        );
+
+        let width = list.width as usize;
+        let items: Vec<Line> = self
+            .results
+            .iter()
+            .map(|(entry, positions)| {
+                let mut spans: Vec<Span> = entry
+                    .label
+                    .chars()
+                    .enumerate()
+                    .map(|(index, c)| {
+                        let style = if positions.contains(&index) {
+                            Style::default()
+                                .fg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default()
+                        };
+                        Span::styled(c.to_string(), style)
+                    })
+                    .collect();
+                let used = entry.label.chars().count() + entry.kind.len();
+                spans.push(Span::raw(" ".repeat(width.saturating_sub(used).max(1))));
+                spans.push(Span::styled(
+                    entry.kind,
+                    Style::default().fg(Color::DarkGray),
+                ));
+                Line::from(spans)
+            })
+            .collect();
+
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut state,
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use console::set_colors_enabled;
 use miette::{IntoDiagnostic, Result};
 
@@ -43,7 +43,9 @@ fn main() -> Result<()> {
 }
 
 fn run() -> Result<()> {
-    let flow = Flow::parse();
+    // Examples are added to the long help of every command that has some
+    let matches = flow_cli::examples::with_examples(Flow::command()).get_matches();
+    let flow = Flow::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
 
     match flow.command {
         #[cfg(feature = "tui")]
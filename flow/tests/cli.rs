@@ -0,0 +1,229 @@
+//! End-to-end CLI Tests
+//!
+//! Runs the `flow` binary against graphs in temporary directories, asserting
+//! exit codes, human output and the shape of `--json` output. Every test gets
+//! its own home, configuration and runtime directory, so it neither sees nor
+//! touches the user's graphs or a running daemon.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// An isolated environment to run `flow` in.
+struct Env {
+    dir: TempDir,
+}
+
+impl Env {
+    fn new() -> Self {
+        Self {
+            dir: TempDir::new().unwrap(),
+        }
+    }
+
+    /// Path of the graph created by [`Env::init`].
+    fn graph(&self) -> PathBuf {
+        self.dir.path().join("notes")
+    }
+
+    /// A `flow` command isolated to this environment.
+    fn flow(&self) -> Command {
+        let home = self.dir.path().join("home");
+        let mut command = Command::cargo_bin("flow").unwrap();
+        command
+            .current_dir(self.dir.path())
+            .env("HOME", &home)
+            .env("XDG_CONFIG_HOME", home.join(".config"))
+            .env("XDG_RUNTIME_DIR", &home)
+            .env("APPDATA", &home)
+            .env_remove("VISUAL")
+            .env_remove("EDITOR");
+        command
+    }
+
+    /// Runs `flow` with `--json` and parses its output.
+    fn json(&self, args: &[&str]) -> Value {
+        let output = self
+            .flow()
+            .args(args)
+            .arg("--json")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    /// Initializes the graph `notes`.
+    fn init(&self) -> Value {
+        self.json(&["init", self.graph().to_str().unwrap()])
+    }
+}
+
+/// Sorted keys of a JSON object.
+fn keys(value: &Value) -> Vec<&str> {
+    let mut keys: Vec<&str> = value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn test_journal_flow_json() {
+    let env = Env::new();
+
+    let init = env.init();
+    assert_eq!(keys(&init), ["name", "path"]);
+    assert_eq!(init["name"], "notes");
+
+    let add = env.json(&["add", "Write integration tests", "--graph", "notes"]);
+    assert_eq!(keys(&add), ["content", "context", "message"]);
+    assert_eq!(add["content"], "Write integration tests");
+
+    let show = env.json(&["show", "--graph", "notes"]);
+    let page = &show["pages"][0];
+    assert_eq!(keys(page), ["content", "created", "id", "modified", "name"]);
+    assert!(page["content"]
+        .as_str()
+        .unwrap()
+        .contains("- Write integration tests"));
+
+    let search = env.json(&["search", "integration", "--graph", "notes"]);
+    assert_eq!(keys(&search), ["hits", "query"]);
+    assert_eq!(search["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(search["hits"][0]["page"], page["name"]);
+
+    std::fs::remove_dir_all(env.graph()).unwrap();
+    let clean = env.json(&["clean"]);
+    assert_eq!(keys(&clean), ["checked", "dry_run", "kept", "removed"]);
+    assert_eq!(clean["removed"][0]["name"], "notes");
+    assert_eq!(clean["removed"][0]["reason"], "directory not found");
+}
+
+#[test]
+fn test_journal_flow_human() {
+    let env = Env::new();
+
+    env.flow()
+        .args(["init", env.graph().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Graph initialized successfully"));
+    env.flow()
+        .args(["add", "Write integration tests"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added to today's journal"));
+    env.flow()
+        .arg("show")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- Write integration tests"));
+    env.flow()
+        .args(["search", "integration"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 match"));
+}
+
+#[test]
+fn test_errors_exit_nonzero() {
+    let env = Env::new();
+
+    env.flow()
+        .args(["show", "--json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No active graph"));
+
+    env.init();
+    env.flow()
+        .args(["show", "missing", "--graph", "notes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Page 'missing' not found"));
+    env.flow()
+        .args(["init", env.graph().to_str().unwrap(), "--json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Graph already exists"));
+}
+
+#[test]
+fn test_schema_matches_json_output() {
+    let env = Env::new();
+    env.init();
+
+    let add = env.json(&["add", "Check the schema", "--graph", "notes"]);
+    let schema = env.json(&["schema", "add"]);
+    assert_eq!(schema["command"], "add");
+    let properties = &schema["schemas"]["add"]["properties"];
+    assert_eq!(keys(properties), keys(&add));
+
+    let all = env.json(&["schema"]);
+    assert!(all["command"].is_null());
+    assert!(keys(&all["schemas"]).contains(&"today"));
+
+    env.flow()
+        .args(["schema", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No output schema for command 'missing'",
+        ));
+}
+
+#[test]
+fn test_init_docs_graph_in_repository() {
+    let env = Env::new();
+    let repository = env.dir.path().join("project");
+    std::fs::create_dir_all(repository.join(".git")).unwrap();
+    let notes = repository.join("docs").join("notes");
+
+    let init = env.json(&["init", notes.to_str().unwrap(), "--docs"]);
+    assert_eq!(keys(&init), ["name", "path", "repository"]);
+    assert_eq!(init["name"], "project/notes");
+    let gitignore = std::fs::read_to_string(repository.join(".gitignore")).unwrap();
+    assert!(gitignore.contains("/docs/notes/.flow/\n"));
+
+    env.flow()
+        .args(["init", env.graph().to_str().unwrap(), "--docs", "--json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not inside a git repository"));
+}
+
+#[test]
+fn test_project_notes_and_tasks() {
+    let env = Env::new();
+    env.init();
+
+    env.flow()
+        .args(["project", "note", "Outside of a repository", "--json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not inside a git repository"));
+
+    std::fs::create_dir_all(env.dir.path().join(".git")).unwrap();
+    let project = env.dir.path().file_name().unwrap().to_str().unwrap();
+    let note = env.json(&["project", "note", "TODO ship the release"]);
+    assert_eq!(note["project"], project);
+
+    let tasks = env.json(&["project", "tasks"]);
+    assert_eq!(
+        tasks["tasks"][0].as_str().unwrap().split(" [[").next(),
+        Some("TODO ship the release")
+    );
+    env.flow()
+        .args(["project", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- TODO ship the release"));
+}